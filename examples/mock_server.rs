@@ -0,0 +1,31 @@
+//! Run the mock Realtime server on a fixed port for clients in other processes.
+//!
+//! ```sh
+//! cargo run --features mock --example mock_server -- --port 8765
+//! ```
+//!
+//! Then connect with `RealtimeBuilder::base_url("ws://127.0.0.1:8765/v1/realtime")`
+//! and any API key. Saying "weather" triggers a `get_weather` tool call.
+
+use oai_rt_rs::mock::MockServer;
+use serde_json::json;
+
+const DEFAULT_PORT: u16 = 8765;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> oai_rt_rs::Result<()> {
+    let port = std::env::args()
+        .skip_while(|arg| arg != "--port")
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+
+    let server = MockServer::builder()
+        .tool_call("weather", "get_weather", json!({ "city": "Paris" }))
+        .bind(("127.0.0.1", port))
+        .await?;
+    println!("Mock Realtime server listening on {}", server.url());
+
+    std::future::pending::<()>().await;
+    Ok(())
+}
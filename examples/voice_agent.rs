@@ -0,0 +1,68 @@
+//! A voice agent with one tool, run against the API or the in-crate mock server.
+//!
+//! ```sh
+//! cargo run --features mock --example voice_agent -- --mock
+//! OPENAI_API_KEY=sk-... cargo run --features mock --example voice_agent
+//! ```
+//!
+//! Turns are sent as text so the example runs without a microphone; replies are
+//! printed as transcripts along with how much audio arrived.
+
+use oai_rt_rs::mock::MockServer;
+use oai_rt_rs::sdk::{Realtime, VoiceEvent};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, JsonSchema)]
+struct WeatherArgs {
+    city: String,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> oai_rt_rs::Result<()> {
+    let mock = if std::env::args().any(|arg| arg == "--mock") {
+        let server = MockServer::builder()
+            .tool_call("weather", "get_weather", json!({ "city": "Paris" }))
+            .start()
+            .await?;
+        Some(server)
+    } else {
+        None
+    };
+
+    let builder = Realtime::builder()
+        .voice_session()
+        .instructions("You are a concise assistant. Use get_weather for weather questions.")
+        .tool("get_weather", |args: WeatherArgs| async move {
+            Ok(json!({ "city": args.city, "forecast": "sunny", "celsius": 21 }))
+        });
+    let builder = match &mock {
+        Some(server) => builder.api_key("mock").base_url(server.url()),
+        None => builder.api_key(std::env::var("OPENAI_API_KEY").unwrap_or_default()),
+    };
+    let mut session = builder.connect_ws().await?;
+
+    for prompt in ["Hello!", "What's the weather in Paris?"] {
+        println!("> {prompt}");
+        session.say(prompt).await?;
+        session.respond().await?;
+
+        // A tool call ends one response and the SDK starts another for the answer,
+        // so wait for a response that carried a transcript.
+        let mut audio_bytes = 0;
+        let mut answered = false;
+        while let Some(event) = session.next_voice_event().await? {
+            match event {
+                VoiceEvent::AudioDelta { pcm, .. } => audio_bytes += pcm.len(),
+                VoiceEvent::TranscriptDone { transcript, .. } => {
+                    println!("< {transcript} ({} ms of audio)", audio_bytes / 48);
+                    answered = true;
+                }
+                VoiceEvent::ResponseDone { .. } if answered => break,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
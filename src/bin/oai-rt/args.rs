@@ -0,0 +1,128 @@
+//! Command-line arguments.
+
+use crate::print::Filter;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: oai-rt [OPTIONS]             connect and send events interactively
+       oai-rt print FILE [OPTIONS]  print a recorded session
+
+Options:
+  --model MODEL     model to connect to
+  --url URL         Realtime WebSocket URL, for compatible servers
+  --mock            connect to the in-crate mock server instead of the API; not
+                    with --url or --model
+  --record FILE     write every event sent and received to FILE as JSONL
+  --replay FILE     send the client events recorded in FILE, then continue
+  --filter LIST     show only event types starting with one of the comma-separated
+                    prefixes; a prefix starting with ! hides matching types
+  --no-color        print without ANSI colors (also when NO_COLOR is set)
+  -h, --help        show this help
+
+Connecting to the API needs OPENAI_API_KEY. Type :help at the prompt for commands.";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ConnectArgs {
+    pub model: Option<String>,
+    pub url: Option<String>,
+    pub mock: bool,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Connect(ConnectArgs),
+    Print(PathBuf),
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub filter: Filter,
+    pub color: bool,
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = args.into_iter().peekable();
+    let print = if args.next_if(|arg| arg == "print").is_some() {
+        let path = args.next().ok_or("print needs a recording to read")?;
+        Some(PathBuf::from(path))
+    } else {
+        None
+    };
+    let mut connect = ConnectArgs::default();
+    let mut filter = Filter::default();
+    let mut color = std::env::var_os("NO_COLOR").is_none();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--model" => connect.model = Some(value()?),
+            "--url" => connect.url = Some(value()?),
+            "--mock" => connect.mock = true,
+            "--record" => connect.record = Some(value()?.into()),
+            "--replay" => connect.replay = Some(value()?.into()),
+            "--filter" => filter = Filter::parse([value()?.as_str()]),
+            "--no-color" => color = false,
+            "-h" | "--help" => {
+                return Ok(Args {
+                    command: Command::Help,
+                    filter,
+                    color,
+                });
+            }
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+    if connect.mock && (connect.url.is_some() || connect.model.is_some()) {
+        return Err("--mock cannot be combined with --url or --model".to_string());
+    }
+    let command = match print {
+        Some(_) if connect != ConnectArgs::default() => {
+            return Err("print only takes --filter and --no-color".to_string());
+        }
+        Some(path) => Command::Print(path),
+        None => Command::Connect(connect),
+    };
+    Ok(Args {
+        command,
+        filter,
+        color,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Args, String> {
+        parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parses_connect_and_print_commands() {
+        let args =
+            parse_args(&["--mock", "--record", "out.jsonl", "--filter", "response."]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Connect(ConnectArgs {
+                mock: true,
+                record: Some("out.jsonl".into()),
+                ..ConnectArgs::default()
+            })
+        );
+        assert_eq!(args.filter, Filter::parse(["response."]));
+
+        let args = parse_args(&["print", "out.jsonl", "--no-color"]).unwrap();
+        assert_eq!(args.command, Command::Print("out.jsonl".into()));
+        assert!(!args.color);
+
+        assert!(parse_args(&["print"]).is_err());
+        assert!(parse_args(&["print", "out.jsonl", "--mock"]).is_err());
+        assert!(parse_args(&["--model"]).is_err());
+        assert!(parse_args(&["--mock", "--url", "ws://localhost:1"]).is_err());
+        assert!(parse_args(&["--mock", "--model", "gpt-realtime"]).is_err());
+        assert!(parse_args(&["--verbose"]).is_err());
+    }
+}
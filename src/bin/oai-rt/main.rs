@@ -0,0 +1,179 @@
+//! `oai-rt`: an interactive Realtime client for debugging the protocol.
+//!
+//! ```sh
+//! cargo run --features cli --bin oai-rt -- --mock
+//! OPENAI_API_KEY=sk-... cargo run --features cli --bin oai-rt -- --record session.jsonl
+//! cargo run --features cli --bin oai-rt -- print session.jsonl --filter response.
+//! ```
+//!
+//! Client events are typed as JSON or edited from templates; server events are
+//! printed as they arrive.
+
+#![allow(clippy::multiple_crate_versions)]
+
+mod args;
+mod print;
+mod record;
+mod repl;
+mod templates;
+
+use args::{Command, ConnectArgs};
+use futures::StreamExt;
+use oai_rt_rs::mock::MockServer;
+use oai_rt_rs::{RealtimeClient, ServerEvent};
+use print::{Direction, Printer};
+use record::Recorder;
+use repl::{Input, Output};
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::ExitCode;
+use tokio::sync::mpsc;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}\n\n{}", args::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let printer = Printer {
+        color: args.color && std::io::stdout().is_terminal(),
+        filter: args.filter,
+    };
+    let result = match args.command {
+        Command::Help => {
+            println!("{}", args::USAGE);
+            Ok(())
+        }
+        Command::Print(path) => print_recording(&path, &printer),
+        Command::Connect(connect) => run(connect, printer).await,
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("oai-rt: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_recording(path: &Path, printer: &Printer) -> CliResult<()> {
+    for record in record::read(path)? {
+        if let Some(text) = printer.render(record.direction, &record.event) {
+            println!("{text}");
+        }
+    }
+    Ok(())
+}
+
+async fn run(args: ConnectArgs, mut printer: Printer) -> CliResult<()> {
+    let mock = if args.mock {
+        Some(MockServer::start().await?)
+    } else {
+        None
+    };
+    let (url, api_key) = match &mock {
+        Some(server) => (server.url(), "mock".to_string()),
+        None => (
+            args.url
+                .unwrap_or_else(|| oai_rt_rs::transport::ws::WS_BASE_URL.to_string()),
+            std::env::var("OPENAI_API_KEY").map_err(|_| "OPENAI_API_KEY is not set")?,
+        ),
+    };
+    let replay = args.replay.as_deref().map(record::read).transpose()?;
+    let mut recorder = args.record.as_deref().map(Recorder::create).transpose()?;
+
+    let client = RealtimeClient::connect_to(&url, &api_key, args.model.as_deref(), None).await?;
+    let (mut sender, receiver) = client.split();
+    // Raw frames, so event types and fields the crate does not model are shown and
+    // recorded as the server sent them.
+    let mut frames = receiver.try_into_raw_stream();
+
+    let (input_tx, mut input_rx) = mpsc::unbounded_channel();
+    let (mut out, editor) = repl::spawn(input_tx.clone())?;
+    out.print(printer.note(&format!("connected to {url}; :help lists commands")));
+    if let Some(recorded) = replay {
+        let (events, skipped) = record::client_events(&recorded);
+        if skipped > 0 {
+            out.print(printer.note(&format!(
+                "skipping {skipped} recorded events that cannot be sent again"
+            )));
+        }
+        tokio::spawn(async move {
+            for (gap, event) in events {
+                tokio::time::sleep(gap).await;
+                if input_tx.send(Input::Send(event)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    loop {
+        tokio::select! {
+            frame = frames.next() => {
+                let text = match frame {
+                    Some(Ok(text)) => text,
+                    Some(Err(err)) => {
+                        out.print(printer.note(&format!("connection failed: {err}")));
+                        break;
+                    }
+                    None => {
+                        out.print(printer.note("the server closed the connection"));
+                        break;
+                    }
+                };
+                let event = match serde_json::from_str::<serde_json::Value>(&text) {
+                    Ok(event) => event,
+                    Err(err) => {
+                        out.print(printer.note(&format!("server sent invalid JSON ({err}): {text}")));
+                        continue;
+                    }
+                };
+                show(&mut out, &printer, recorder.as_mut(), Direction::Server, &event)?;
+                if let Err(err) = serde_json::from_value::<ServerEvent>(event) {
+                    out.print(printer.note(&format!("this crate cannot parse that event: {err}")));
+                }
+            }
+            input = input_rx.recv() => match input {
+                Some(Input::Send(event)) => {
+                    let value = serde_json::to_value(&event)?;
+                    match sender.send(event).await {
+                        Ok(()) => show(&mut out, &printer, recorder.as_mut(), Direction::Client, &value)?,
+                        Err(err) => out.print(printer.note(&format!("not sent: {err}"))),
+                    }
+                }
+                Some(Input::Filter(filter)) => printer.filter = filter,
+                Some(Input::Quit) | None => break,
+            },
+        }
+    }
+    // The editor restores the terminal when its current prompt ends.
+    drop(input_rx);
+    if !editor.is_finished() {
+        out.print(printer.note("press Enter to exit"));
+    }
+    let _ = tokio::task::spawn_blocking(move || editor.join()).await;
+    Ok(())
+}
+
+fn show(
+    out: &mut Output,
+    printer: &Printer,
+    recorder: Option<&mut Recorder>,
+    direction: Direction,
+    event: &serde_json::Value,
+) -> CliResult<()> {
+    if let Some(recorder) = recorder {
+        recorder.write(direction, event)?;
+    }
+    if let Some(text) = printer.render(direction, event) {
+        out.print(text);
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Rendering events for the terminal.
+
+use serde_json::Value;
+
+/// Base64 payloads longer than this are shown as their length.
+const MAX_PAYLOAD_CHARS: usize = 48;
+
+const RESET: &str = "\x1b[0m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Client,
+    Server,
+}
+
+impl Direction {
+    /// Name used for the `direction` field of recordings, as in `EventLog` files.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Client => "client",
+            Self::Server => "server",
+        }
+    }
+
+    pub fn parse(direction: &str) -> Option<Self> {
+        match direction {
+            "client" => Some(Self::Client),
+            "server" => Some(Self::Server),
+            _ => None,
+        }
+    }
+}
+
+/// Which event types are shown. Patterns are type prefixes; a pattern starting with
+/// `!` hides the types it matches. With no plain patterns, every type not hidden is
+/// shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn parse<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut filter = Self::default();
+        for pattern in patterns.into_iter().flat_map(|p| p.split(',')) {
+            let pattern = pattern.trim();
+            if let Some(hidden) = pattern.strip_prefix('!') {
+                filter.exclude.push(hidden.to_string());
+            } else if !pattern.is_empty() {
+                filter.include.push(pattern.to_string());
+            }
+        }
+        filter
+    }
+
+    pub fn allows(&self, event_type: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|p| event_type.starts_with(p.as_str()));
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|p| event_type.starts_with(p.as_str()))
+    }
+}
+
+pub struct Printer {
+    pub color: bool,
+    pub filter: Filter,
+}
+
+impl Printer {
+    /// A header line with the direction and type, then the event as indented JSON.
+    /// `None` if the filter hides the event.
+    pub fn render(&self, direction: Direction, event: &Value) -> Option<String> {
+        let event_type = event.get("type").and_then(Value::as_str).unwrap_or("?");
+        if !self.filter.allows(event_type) {
+            return None;
+        }
+        let mut event = event.clone();
+        shorten_payloads(&mut event);
+        let body = serde_json::to_string_pretty(&event).unwrap_or_default();
+        let (arrow, color) = match direction {
+            Direction::Client => ("→", BLUE),
+            Direction::Server => ("←", server_color(event_type)),
+        };
+        Some(if self.color {
+            format!("{color}{arrow} {event_type}{RESET}\n{DIM}{body}{RESET}")
+        } else {
+            format!("{arrow} {event_type}\n{body}")
+        })
+    }
+
+    /// A line about the tool itself rather than the protocol.
+    pub fn note(&self, message: &str) -> String {
+        if self.color {
+            format!("{YELLOW}* {message}{RESET}")
+        } else {
+            format!("* {message}")
+        }
+    }
+}
+
+fn server_color(event_type: &str) -> &'static str {
+    if event_type == "error" || event_type.ends_with(".failed") {
+        BOLD_RED
+    } else if event_type.starts_with("session.") {
+        CYAN
+    } else if event_type.starts_with("response.") {
+        GREEN
+    } else {
+        YELLOW
+    }
+}
+
+/// Replace long audio payloads with their length so they do not flood the terminal.
+fn shorten_payloads(event: &mut Value) {
+    let audio_delta =
+        event.get("type").and_then(Value::as_str) == Some("response.output_audio.delta");
+    let Some(fields) = event.as_object_mut() else {
+        return;
+    };
+    for (key, value) in fields.iter_mut() {
+        let payload = key == "audio" || (audio_delta && key == "delta");
+        let long = value.as_str().map_or(0, str::len);
+        if payload && long > MAX_PAYLOAD_CHARS {
+            *value = Value::String(format!("<{long} base64 chars>"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn filters_by_prefix_and_hides_negated_prefixes() {
+        let filter = Filter::parse(["response.,error", "!response.output_audio.delta"]);
+        assert!(filter.allows("response.done"));
+        assert!(filter.allows("error"));
+        assert!(!filter.allows("response.output_audio.delta"));
+        assert!(!filter.allows("session.created"));
+        assert!(Filter::default().allows("session.created"));
+    }
+
+    #[test]
+    fn render_keeps_nulls_and_shortens_audio_payloads() {
+        let printer = Printer {
+            color: false,
+            filter: Filter::default(),
+        };
+        let event = json!({
+            "type": "input_audio_buffer.append",
+            "event_id": null,
+            "audio": "A".repeat(100),
+        });
+        let text = printer.render(Direction::Client, &event).unwrap();
+        assert!(text.starts_with("→ input_audio_buffer.append\n"));
+        assert!(text.contains("<100 base64 chars>"));
+        assert!(text.contains(r#""event_id": null"#));
+
+        let hidden = Printer {
+            color: false,
+            filter: Filter::parse(["session."]),
+        };
+        assert!(hidden.render(Direction::Client, &event).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+//! Recording sessions as JSONL and reading them back.
+//!
+//! Lines are `{"ts_ms", "direction", "event"}`, the format `EventLog` writes, so
+//! recordings from either can be printed or replayed.
+
+use crate::print::Direction;
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use oai_rt_rs::ClientEvent;
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Longest pause kept between replayed events; longer ones are shortened to this.
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(5);
+
+/// Writes every event sent and received, unredacted.
+pub struct Recorder(BufWriter<File>);
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self(BufWriter::new(File::create(path)?)))
+    }
+
+    pub fn write(&mut self, direction: Direction, event: &Value) -> io::Result<()> {
+        let ts_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let line = json!({ "ts_ms": ts_ms, "direction": direction.as_str(), "event": event });
+        writeln!(self.0, "{line}")?;
+        // Flushed per line so a crash or Ctrl-C keeps everything up to it.
+        self.0.flush()
+    }
+}
+
+pub struct Recorded {
+    pub ts_ms: u64,
+    pub direction: Direction,
+    pub event: Value,
+}
+
+pub fn read(path: &Path) -> io::Result<Vec<Recorded>> {
+    let mut recorded = Vec::new();
+    for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |what: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {what}", path.display(), number + 1),
+            )
+        };
+        let mut record: Value =
+            serde_json::from_str(&line).map_err(|err| invalid(&err.to_string()))?;
+        let direction = record
+            .get("direction")
+            .and_then(Value::as_str)
+            .and_then(Direction::parse)
+            .ok_or_else(|| invalid("missing or unknown direction"))?;
+        recorded.push(Recorded {
+            ts_ms: record.get("ts_ms").and_then(Value::as_u64).unwrap_or(0),
+            direction,
+            event: record
+                .get_mut("event")
+                .map(Value::take)
+                .ok_or_else(|| invalid("missing event"))?,
+        });
+    }
+    Ok(recorded)
+}
+
+/// The client events of a recording, each with the pause before it, and how many
+/// were skipped. Appends whose audio was redacted, as `EventLog` does by default,
+/// cannot be sent again and are skipped.
+pub fn client_events(recorded: &[Recorded]) -> (Vec<(Duration, ClientEvent)>, usize) {
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    let mut last_ts = None;
+    for record in recorded {
+        if record.direction != Direction::Client {
+            continue;
+        }
+        let event = serde_json::from_value::<ClientEvent>(record.event.clone())
+            .ok()
+            .filter(|event| !is_redacted_append(event));
+        let Some(event) = event else {
+            skipped += 1;
+            continue;
+        };
+        let gap = last_ts.map_or(Duration::ZERO, |last| {
+            Duration::from_millis(record.ts_ms.saturating_sub(last)).min(MAX_REPLAY_GAP)
+        });
+        last_ts = Some(record.ts_ms);
+        events.push((gap, event));
+    }
+    (events, skipped)
+}
+
+fn is_redacted_append(event: &ClientEvent) -> bool {
+    match event {
+        ClientEvent::InputAudioBufferAppend { audio, .. } => {
+            general_purpose::STANDARD.decode(audio).is_err()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ts_ms: u64, direction: Direction, event: Value) -> Recorded {
+        Recorded {
+            ts_ms,
+            direction,
+            event,
+        }
+    }
+
+    #[test]
+    fn replays_client_events_with_capped_gaps_and_skips_redacted_audio() {
+        let recorded = [
+            record(1_000, Direction::Client, json!({"type": "response.create"})),
+            record(
+                1_200,
+                Direction::Server,
+                json!({"type": "response.created"}),
+            ),
+            record(
+                1_500,
+                Direction::Client,
+                json!({"type": "input_audio_buffer.append", "audio": "[redacted 4 bytes]"}),
+            ),
+            record(
+                60_000,
+                Direction::Client,
+                json!({"type": "response.cancel"}),
+            ),
+        ];
+        let (events, skipped) = client_events(&recorded);
+        assert_eq!(skipped, 1);
+        let [(first, create), (gap, cancel)] = events.as_slice() else {
+            panic!("unexpected events: {}", events.len());
+        };
+        assert_eq!(*first, Duration::ZERO);
+        assert_eq!(create.event_type(), "response.create");
+        assert_eq!(*gap, MAX_REPLAY_GAP);
+        assert_eq!(cancel.event_type(), "response.cancel");
+    }
+}
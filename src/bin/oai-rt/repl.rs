@@ -0,0 +1,209 @@
+//! The line editor. It runs on its own thread so waiting for input never holds up
+//! the connection.
+
+use crate::print::Filter;
+use crate::templates::{self, TEMPLATES};
+use oai_rt_rs::ClientEvent;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, ExternalPrinter, Helper};
+use std::thread::JoinHandle;
+use tokio::sync::mpsc;
+
+const PROMPT: &str = "oai-rt> ";
+const EDIT_PROMPT: &str = "  edit> ";
+const COMMANDS: &[&str] = &[":filter", ":help", ":quit"];
+
+pub const HELP: &str = "\
+Type a client event type (Tab completes it) to edit its template, then Enter to send.
+A line starting with `{` is sent as a client event.
+
+:filter [PREFIX|!PREFIX ...]  show only event types starting with PREFIX, hiding
+                              those starting with !PREFIX; no argument shows all
+:help                         show this help
+:quit                         disconnect (or Ctrl-D)";
+
+/// What the editor thread asks of the connection.
+pub enum Input {
+    Send(ClientEvent),
+    Filter(Filter),
+    Quit,
+}
+
+/// What a line typed at the prompt asks for.
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Send(ClientEvent),
+    /// Open a template for editing.
+    Edit(&'static str),
+    Filter(Filter),
+    Help,
+    Quit,
+    Invalid(String),
+    Empty,
+}
+
+pub fn parse_line(line: &str) -> Line {
+    let line = line.trim();
+    if line.is_empty() {
+        return Line::Empty;
+    }
+    if line.starts_with('{') {
+        return match serde_json::from_str(line) {
+            Ok(event) => Line::Send(event),
+            Err(err) => Line::Invalid(format!("not a client event: {err}")),
+        };
+    }
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    match command {
+        ":filter" => Line::Filter(Filter::parse(rest.split_whitespace())),
+        ":help" => Line::Help,
+        ":quit" | ":q" => Line::Quit,
+        _ => templates::template(line).map_or_else(
+            || Line::Invalid(format!("unknown event type or command `{line}`; see :help")),
+            Line::Edit,
+        ),
+    }
+}
+
+/// Where text for the terminal goes while the editor may be showing a prompt.
+pub enum Output {
+    /// Printed above the prompt, which is redrawn after it.
+    Editor(Box<dyn ExternalPrinter + Send>),
+    Stdout,
+}
+
+impl Output {
+    pub fn print(&mut self, text: String) {
+        match self {
+            Self::Editor(printer) => {
+                if let Err(err) = printer.print(text) {
+                    eprintln!("failed to print: {err}");
+                }
+            }
+            Self::Stdout => println!("{text}"),
+        }
+    }
+}
+
+/// Start the editor thread, which sends what the user asks for to `tx` until they
+/// quit or `tx` is closed.
+pub fn spawn(tx: mpsc::UnboundedSender<Input>) -> rustyline::Result<(Output, JoinHandle<()>)> {
+    let mut editor: Editor<EventHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(EventHelper));
+    let output = editor
+        .create_external_printer()
+        .map_or(Output::Stdout, |printer| Output::Editor(Box::new(printer)));
+    let thread = std::thread::Builder::new()
+        .name("oai-rt-editor".to_string())
+        .spawn(move || run(&mut editor, &tx))?;
+    Ok((output, thread))
+}
+
+fn run(editor: &mut Editor<EventHelper, DefaultHistory>, tx: &mpsc::UnboundedSender<Input>) {
+    while !tx.is_closed() {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            // Ctrl-C discards the line being typed.
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("failed to read input: {err}");
+                break;
+            }
+        };
+        let _ = editor.add_history_entry(line.trim());
+        let mut parsed = parse_line(&line);
+        if let Line::Edit(template) = parsed {
+            parsed = editor
+                .readline_with_initial(EDIT_PROMPT, (template, ""))
+                .map_or(Line::Empty, |edited| parse_line(&edited));
+        }
+        let input = match parsed {
+            Line::Send(event) => Input::Send(event),
+            Line::Filter(filter) => Input::Filter(filter),
+            Line::Quit => break,
+            Line::Help => {
+                println!("{HELP}");
+                continue;
+            }
+            Line::Invalid(message) => {
+                eprintln!("{message}");
+                continue;
+            }
+            Line::Edit(_) | Line::Empty => continue,
+        };
+        if tx.send(input).is_err() {
+            break;
+        }
+    }
+    let _ = tx.send(Input::Quit);
+}
+
+/// Tab completion of client event types and commands.
+struct EventHelper;
+
+impl Completer for EventHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        // Only the first word names an event type or command.
+        if start > 0 {
+            return Ok((start, Vec::new()));
+        }
+        let word = &line[..pos];
+        let candidates = TEMPLATES
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(COMMANDS.iter().copied())
+            .filter(|name| name.starts_with(word))
+            .map(String::from)
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for EventHelper {
+    type Hint = String;
+}
+
+impl Highlighter for EventHelper {}
+
+impl Validator for EventHelper {}
+
+impl Helper for EventHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_events_templates_or_commands() {
+        assert!(matches!(
+            parse_line(r#" {"type":"response.cancel"} "#),
+            Line::Send(ClientEvent::ResponseCancel { .. })
+        ));
+        assert_eq!(
+            parse_line("response.create"),
+            Line::Edit(templates::template("response.create").unwrap())
+        );
+        assert_eq!(
+            parse_line(":filter response. !response.output_audio.delta"),
+            Line::Filter(Filter::parse(["response.", "!response.output_audio.delta"]))
+        );
+        assert_eq!(parse_line(":q"), Line::Quit);
+        assert_eq!(parse_line("   "), Line::Empty);
+        assert!(matches!(parse_line("{\"type\":1}"), Line::Invalid(_)));
+        assert!(matches!(parse_line("response.done"), Line::Invalid(_)));
+    }
+}
@@ -0,0 +1,73 @@
+//! Starting points for each client event, opened for editing by typing its type.
+
+/// One template per client event type, in the order the API reference lists them.
+pub const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "session.update",
+        r#"{"type":"session.update","session":{"type":"realtime","instructions":"You are a helpful assistant."}}"#,
+    ),
+    (
+        "input_audio_buffer.append",
+        r#"{"type":"input_audio_buffer.append","audio":""}"#,
+    ),
+    (
+        "input_audio_buffer.commit",
+        r#"{"type":"input_audio_buffer.commit"}"#,
+    ),
+    (
+        "input_audio_buffer.clear",
+        r#"{"type":"input_audio_buffer.clear"}"#,
+    ),
+    (
+        "conversation.item.create",
+        r#"{"type":"conversation.item.create","item":{"type":"message","role":"user","content":[{"type":"input_text","text":"Hello!"}]}}"#,
+    ),
+    (
+        "conversation.item.retrieve",
+        r#"{"type":"conversation.item.retrieve","item_id":""}"#,
+    ),
+    (
+        "conversation.item.truncate",
+        r#"{"type":"conversation.item.truncate","item_id":"","content_index":0,"audio_end_ms":0}"#,
+    ),
+    (
+        "conversation.item.delete",
+        r#"{"type":"conversation.item.delete","item_id":""}"#,
+    ),
+    (
+        "response.create",
+        r#"{"type":"response.create","response":{"output_modalities":["text"]}}"#,
+    ),
+    ("response.cancel", r#"{"type":"response.cancel"}"#),
+    (
+        "output_audio_buffer.clear",
+        r#"{"type":"output_audio_buffer.clear"}"#,
+    ),
+];
+
+pub fn template(event_type: &str) -> Option<&'static str> {
+    TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == event_type)
+        .map(|(_, template)| *template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oai_rt_rs::ClientEvent;
+
+    #[test]
+    fn every_template_is_the_client_event_it_names() {
+        for (name, template) in TEMPLATES {
+            let event: ClientEvent = serde_json::from_str(template)
+                .unwrap_or_else(|err| panic!("{name} template does not parse: {err}"));
+            assert_eq!(event.event_type(), *name);
+        }
+        assert_eq!(
+            template("response.cancel"),
+            Some(r#"{"type":"response.cancel"}"#)
+        );
+        assert_eq!(template("response.done"), None);
+    }
+}
@@ -2,7 +2,7 @@ use crate::transport::ws::WsStream;
 use futures::stream::ReuniteError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Message};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -26,10 +26,20 @@ pub struct ServerError {
     pub event_id: Option<String>,
 }
 
+/// SDK error type.
+///
+/// Large causes are boxed so `Result<T>` stays small; wrapped causes are reachable
+/// through `std::error::Error::source`.
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("WebSocket error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(#[source] Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Failed to connect: {0}")]
+    Connect(#[source] Box<ConnectError>),
+
+    #[error("Timed out after {0:?} connecting to the server")]
+    ConnectTimeout(std::time::Duration),
 
     #[error("HTTP protocol error: {0}")]
     Http(#[from] reqwest::Error),
@@ -47,13 +57,26 @@ pub enum Error {
     Io(#[from] std::io::Error),
 
     #[error("OpenAI API error: {0:?}")]
-    Api(ServerError),
+    Api(Box<ServerError>),
 
     #[error("The connection was closed unexpectedly")]
     ConnectionClosed,
 
+    #[error("Timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    /// The operation's `CancellationToken` was cancelled before it finished.
+    #[error("The operation was cancelled")]
+    Cancelled,
+
+    #[error("Blocked by guardrail: {0}")]
+    GuardrailBlocked(String),
+
+    #[error("Response ended with status {0:?}")]
+    ResponseIncomplete(crate::protocol::models::ResponseStatus),
+
     #[error("Failed to reunite split client: {0}")]
-    Reunite(#[from] ReuniteError<WsStream, Message>),
+    Reunite(#[source] Box<ReuniteError<WsStream, Message>>),
 
     #[error("MIME type error: {0}")]
     Mime(String),
@@ -61,8 +84,215 @@ pub enum Error {
     #[error("Invalid client event: {0}")]
     InvalidClientEvent(String),
 
+    #[error("Invalid SDP: {0}")]
+    InvalidSdp(String),
+
+    #[error("Session is read-only: cannot send {0}")]
+    ReadOnly(&'static str),
+
+    /// An outgoing event serialized to more than the configured maximum size.
+    #[error("{event_type} event is {bytes} bytes, over the maximum outbound event size")]
+    EventTooLarge {
+        event_type: &'static str,
+        bytes: usize,
+    },
+
+    /// Outbound sends are paused and the queue of held events is full; see
+    /// `SessionHandle::pause_outbound`.
+    #[error("Outbound sends are paused and all {0} queued slots are taken")]
+    OutboundQueueFull(usize),
+
+    #[error("Session closed: the {0} channel overflowed")]
+    ChannelOverflow(&'static str),
+
+    #[error("Invalid audio from server: {0}")]
+    InvalidAudio(String),
+
     #[error("Not implemented: {0}")]
     NotImplemented(&'static str),
+
+    /// Another writer holds the input audio lease; see `AudioInputLease`.
+    #[error("Input audio is leased to another writer")]
+    AudioInputLeased,
+
+    /// The server closed the connection because its credentials expired or were
+    /// rejected mid-session.
+    #[error("Server closed the connection: authentication expired ({0})")]
+    AuthExpired(String),
+
+    /// The server closed a connection that had been idle too long.
+    #[error("Server closed the idle connection ({0})")]
+    IdleTimeout(String),
+
+    /// The server closed the connection with a code that has no variant of its own.
+    #[error("Server closed the connection with code {code}: {reason}")]
+    ServerClosed { code: u16, reason: String },
+}
+
+impl Error {
+    /// The connection failed, closed, or timed out.
+    #[must_use]
+    pub const fn is_connection(&self) -> bool {
+        matches!(
+            self,
+            Self::WebSocket(_)
+                | Self::Connect(_)
+                | Self::ConnectTimeout(_)
+                | Self::Http(_)
+                | Self::Io(_)
+                | Self::ConnectionClosed
+                | Self::ChannelOverflow(_)
+                | Self::Timeout(_)
+                | Self::Reunite(_)
+                | Self::AuthExpired(_)
+                | Self::IdleTimeout(_)
+                | Self::ServerClosed { .. }
+        )
+    }
+
+    /// The server reported an error or sent something that could not be decoded.
+    #[must_use]
+    pub const fn is_protocol(&self) -> bool {
+        matches!(
+            self,
+            Self::Serialization(_)
+                | Self::Api(_)
+                | Self::ResponseIncomplete(_)
+                | Self::InvalidAudio(_)
+        )
+    }
+
+    /// The input was rejected client-side before anything was sent.
+    #[must_use]
+    pub const fn is_validation(&self) -> bool {
+        matches!(
+            self,
+            Self::Url(_)
+                | Self::Header(_)
+                | Self::Mime(_)
+                | Self::InvalidClientEvent(_)
+                | Self::InvalidSdp(_)
+                | Self::GuardrailBlocked(_)
+                | Self::ReadOnly(_)
+                | Self::EventTooLarge { .. }
+                | Self::AudioInputLeased
+                | Self::OutboundQueueFull(_)
+        )
+    }
+}
+
+/// The close frame the server ended a WebSocket connection with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerClose {
+    pub code: u16,
+    pub reason: String,
+}
+
+impl ServerClose {
+    /// Normal closure.
+    pub const NORMAL: u16 = 1000;
+    /// Sent in place of a code when the close frame had none.
+    pub const NO_STATUS: u16 = 1005;
+    /// The endpoint received a message that violates its policy.
+    pub const POLICY_VIOLATION: u16 = 1008;
+    /// IANA-registered `Unauthorized`.
+    pub const UNAUTHORIZED: u16 = 3000;
+    /// IANA-registered `Forbidden`.
+    pub const FORBIDDEN: u16 = 3003;
+    /// IANA-registered `Timeout`.
+    pub const TIMEOUT: u16 = 3008;
+
+    pub(crate) fn from_frame(frame: Option<&CloseFrame>) -> Self {
+        frame.map_or(
+            Self {
+                code: Self::NO_STATUS,
+                reason: String::new(),
+            },
+            |frame| Self {
+                code: frame.code.into(),
+                reason: frame.reason.to_string(),
+            },
+        )
+    }
+
+    /// The typed error this close stands for, or `None` for a normal closure.
+    ///
+    /// Expired credentials (`3000`, `3003`, or `1008` with a reason about auth) map to
+    /// `Error::AuthExpired`, idle closes (`3008`) to `Error::IdleTimeout`, and any other
+    /// code but `1000` to `Error::ServerClosed`.
+    #[must_use]
+    pub fn error(&self) -> Option<Error> {
+        let reason = self.reason.to_ascii_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| reason.contains(word));
+        let auth = matches!(self.code, Self::UNAUTHORIZED | Self::FORBIDDEN)
+            || (self.code == Self::POLICY_VIOLATION && mentions(&["auth", "token", "expired"]));
+        if auth {
+            Some(Error::AuthExpired(self.reason.clone()))
+        } else if self.code == Self::TIMEOUT {
+            Some(Error::IdleTimeout(self.reason.clone()))
+        } else if self.code == Self::NORMAL {
+            None
+        } else {
+            Some(Error::ServerClosed {
+                code: self.code,
+                reason: self.reason.clone(),
+            })
+        }
+    }
+}
+
+/// The step of opening a WebSocket connection that failed.
+#[derive(Error, Debug)]
+pub enum ConnectError {
+    #[error("DNS lookup of {host} failed: {source}")]
+    Dns {
+        host: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("TCP connection to {host} failed: {source}")]
+    Tcp {
+        host: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("TLS handshake with {host} failed: {source}")]
+    Tls {
+        host: String,
+        #[source]
+        source: tokio_tungstenite::tungstenite::Error,
+    },
+
+    /// The server answered the upgrade request with a non-101 status, e.g. 401 for a
+    /// bad API key.
+    #[error("WebSocket upgrade rejected with HTTP {status}")]
+    Upgrade { status: u16, body: Option<String> },
+}
+
+impl From<ConnectError> for Error {
+    fn from(err: ConnectError) -> Self {
+        Self::Connect(Box::new(err))
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(err))
+    }
+}
+
+impl From<ReuniteError<WsStream, Message>> for Error {
+    fn from(err: ReuniteError<WsStream, Message>) -> Self {
+        Self::Reunite(Box::new(err))
+    }
+}
+
+impl From<ServerError> for Error {
+    fn from(err: ServerError) -> Self {
+        Self::Api(Box::new(err))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,114 @@
+//! Forward a session's events to browsers over Server-Sent Events or a WebSocket.
+//!
+//! Each event is sent as its [`SdkEvent`] JSON: the SSE event name (or the `type`
+//! field of a WebSocket message) is the variant, e.g. `transcript_delta`.
+//!
+//! Neither bridge reads ahead of the client. A slow browser leaves events queued in
+//! the session, where the builder's `overflow_policy` decides what happens once the
+//! queue is full.
+
+use crate::sdk::{SdkEvent, Session};
+use axum::extract::ws::{Message, WebSocket};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::Stream;
+use std::convert::Infallible;
+
+/// Default filter: assistant text and transcripts, user transcriptions and errors.
+#[must_use]
+pub const fn is_text_event(event: &SdkEvent) -> bool {
+    matches!(
+        event,
+        SdkEvent::TextDelta { .. }
+            | SdkEvent::TextDone { .. }
+            | SdkEvent::TranscriptDelta { .. }
+            | SdkEvent::TranscriptDone { .. }
+            | SdkEvent::InputTranscriptionDelta { .. }
+            | SdkEvent::InputTranscriptionCompleted { .. }
+            | SdkEvent::Error { .. }
+    )
+}
+
+/// Stream the session's text and transcript events as an SSE response.
+///
+/// The stream ends when the session closes; dropping it (the client disconnecting)
+/// closes the session.
+pub fn sse(session: Session) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    sse_with(session, is_text_event)
+}
+
+/// Like [`sse`], forwarding the events `filter` accepts.
+pub fn sse_with(
+    session: Session,
+    filter: fn(&SdkEvent) -> bool,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(Some(session), move |session| async move {
+        let mut session = session?;
+        match next_json(&mut session, filter).await {
+            Ok(Some((name, json))) => {
+                Some((Ok(Event::default().event(name).data(json)), Some(session)))
+            }
+            Ok(None) => None,
+            // Report the failure, then end the stream.
+            Err(message) => Some((
+                Ok(Event::default().event("session_error").data(message)),
+                None,
+            )),
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Forward the session's text and transcript events to `socket` as JSON text messages.
+///
+/// Returns when either side closes. Incoming messages other than close are ignored.
+pub async fn websocket(socket: WebSocket, session: Session) {
+    websocket_with(socket, session, is_text_event).await;
+}
+
+/// Like [`websocket`], forwarding the events `filter` accepts.
+pub async fn websocket_with(
+    mut socket: WebSocket,
+    mut session: Session,
+    filter: fn(&SdkEvent) -> bool,
+) {
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            next = next_json(&mut session, filter) => {
+                let message = match next {
+                    Ok(Some((_, json))) => Message::Text(json.into()),
+                    Ok(None) => Message::Close(None),
+                    Err(message) => {
+                        tracing::warn!(%message, "session failed; closing websocket bridge");
+                        Message::Close(None)
+                    }
+                };
+                let closing = matches!(message, Message::Close(_));
+                if socket.send(message).await.is_err() || closing {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// The next accepted event as its `type` tag and full JSON.
+async fn next_json(
+    session: &mut Session,
+    filter: fn(&SdkEvent) -> bool,
+) -> Result<Option<(String, String)>, String> {
+    loop {
+        let Some(event) = session.next_event().await.map_err(|err| err.to_string())? else {
+            return Ok(None);
+        };
+        if !filter(&event) {
+            continue;
+        }
+        let json = serde_json::to_value(&event).map_err(|err| err.to_string())?;
+        let name = json["type"].as_str().unwrap_or_default().to_string();
+        return Ok(Some((name, json.to_string())));
+    }
+}
@@ -0,0 +1,4 @@
+//! Optional glue for web frameworks, each behind a feature of the same name.
+
+#[cfg(feature = "axum")]
+pub mod axum;
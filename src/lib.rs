@@ -3,39 +3,94 @@
 #![allow(clippy::multiple_crate_versions)]
 
 pub mod error;
+pub mod integrations;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod outbound;
+pub mod prelude;
 pub mod protocol;
 pub mod sdk;
+mod trace;
 pub mod transport;
 
-pub use error::{Error, Result};
-pub use protocol::client_events::ClientEvent;
-pub use protocol::models::{
-    ApprovalFilter, ApprovalMode, AudioConfig, AudioFormat, CachedTokenDetails, ContentPart,
-    ConversationMode, Eagerness, Infinite, InputAudioConfig, InputAudioTranscription, InputItem,
-    InputTokenDetails, Item, ItemStatus, MaxTokens, McpError, McpToolConfig, McpToolInfo, Modality,
-    NoiseReduction, NoiseReductionType, OutputAudioConfig, OutputModalities, OutputTokenDetails,
-    PromptRef, RequireApproval, Response, ResponseConfig, ResponseStatus, RetentionRatioTruncation,
-    Role, Session, SessionConfig, SessionKind, SessionUpdate, SessionUpdateConfig, Temperature,
-    TokenLimits, Tool, ToolChoice, ToolChoiceMode, Tracing, TracingAuto, TracingConfig, Truncation,
-    TruncationStrategy, TruncationType, Usage, Voice,
+pub use error::{ConnectError, Error, Result, ServerClose};
+pub use outbound::{EventSizeStats, LargestEvent, OutboundStats, OutboundTotals};
+pub use protocol::{ClientEvent, ServerEvent};
+pub use trace::TraceConfig;
+
+/// Declares deprecated root aliases for structs that moved to a namespace.
+macro_rules! moved {
+    ($module:ident, $note:literal: $($name:ident $(<$lt:lifetime>)?),* $(,)?) => {
+        $(
+            #[doc(hidden)]
+            #[deprecated(since = "0.3.0", note = $note)]
+            pub type $name $(<$lt>)? = $module::$name $(<$lt>)?;
+        )*
+    };
+}
+
+// The root used to re-export every model and SDK type. These aliases keep old paths
+// compiling, with a warning pointing at `oai_rt_rs::sdk`, `oai_rt_rs::protocol::models`
+// or `oai_rt_rs::prelude`.
+moved! {
+    models, "use `oai_rt_rs::protocol::models`":
+    ApprovalFilter, AudioConfig, CachedTokenDetails, InputAudioConfig, InputAudioTranscription,
+    InputTokenDetails, McpToolConfig, McpToolInfo, NoiseReduction, OutputAudioConfig,
+    OutputTokenDetails, Response, ResponseConfig, RetentionRatioTruncation, Session,
+    SessionConfig, SessionUpdate, SessionUpdateConfig, Temperature, TokenLimits, TraceInfo,
+    TracingConfig, Usage,
+}
+moved! {
+    sdk, "use `oai_rt_rs::sdk`":
+    Agent, AnsweringMachineDetection, AudioChunk, AudioIn<'a>, AudioInputLease, AudioProgress,
+    BargeInPolicy, CallReport, CancellationToken, ChannelCapacities, ConversationAnalytics,
+    ConversationFork, ConversationGap, DroppedMessages, EventLog, EventStream<'a>,
+    ExpiryPolicy, F32Conversion, Guardrails, HandoffOptions, IdleTimeoutPolicy,
+    InstructionGuard, OrderingViolation, RealtimeBuilder, ResponseBuilder, ResponseRoute,
+    ResponseTextReader, ScheduledResponse, SentEvent, SessionHandle, SpeakableChunk,
+    SpeakableChunking, ToolCall, ToolCallStream, ToolContext, ToolFailure, ToolOutputOptions,
+    ToolRegistry, ToolResult, TranscriptChunk, TranscriptionFailure, VoiceEventStream<'a>,
+    VoiceSessionBuilder,
+}
+#[cfg(feature = "amd")]
+moved! { sdk, "use `oai_rt_rs::sdk`": HeuristicAmd }
+#[cfg(feature = "moderation")]
+moved! { sdk, "use `oai_rt_rs::sdk`": OpenAiModeration }
+#[doc(hidden)]
+#[deprecated(since = "0.3.0", note = "use `oai_rt_rs::sdk::Session`")]
+pub type RealtimeSession = sdk::Session;
+#[doc(hidden)]
+#[deprecated(since = "0.3.0", note = "use `oai_rt_rs::sdk`")]
+pub type ToolFuture<T> = sdk::ToolFuture<T>;
+// An alias does not carry enum variants into `use` paths (`use oai_rt_rs::Role::User`)
+// or a unit struct's value, and traits and functions cannot be deprecated through a
+// re-export, so these stay plain re-exports, hidden and deprecated in the docs only,
+// until the aliases above are removed.
+#[doc(hidden)]
+pub use models::{
+    ApprovalMode, AudioFormat, ContentPart, ConversationMode, Eagerness, Infinite, InputItem, Item,
+    ItemStatus, MaxTokens, McpError, Modality, NoiseReductionType, OutputModalities, PromptRef,
+    RequireApproval, ResponseStatus, Role, SessionKind, Tool, ToolChoice, ToolChoiceMode, Tracing,
+    TracingAuto, Truncation, TruncationStrategy, TruncationType, UsageKind, Voice,
 };
-pub use protocol::server_events::ServerEvent;
+#[doc(hidden)]
 pub use sdk::{
-    AudioChunk, AudioIn, EventStream, Realtime, RealtimeBuilder, ResponseBuilder, SdkEvent,
-    Session as RealtimeSession, SessionHandle, ToolCall, ToolFuture, ToolRegistry, ToolResult,
-    ToolSpec, TranscriptChunk, VoiceEvent, VoiceEventStream, VoiceSessionBuilder,
+    AmdInput, AmdVerdict, AnsweringMachineDetector, AudioFailurePolicy, CallLifecycle,
+    ClassifyInput, Clipping, CloseReason, Guardrail, GuardrailStage, GuardrailVerdict,
+    HandoffContext, LocalAudioPosition, OrderingRule, OverflowPolicy, Realtime, RotationPolicy,
+    SdkEvent, SessionRole, SessionState, ToolDispatcher, ToolErrorPolicy, ToolSpec,
+    TranscriptionFailurePolicy, VoiceEvent, export_chrome_trace, handoff,
 };
 
 use crate::protocol::models;
 use futures::stream::BoxStream;
 use futures::{SinkExt, StreamExt};
+use outbound::Outbound;
 use serde_json::from_str;
 use tokio_tungstenite::tungstenite::protocol::Message;
 use transport::ws::WsStream;
 
-const TRACE_LOG_MAX_BYTES: usize = 1024;
 const MAX_INPUT_AUDIO_CHUNK_BYTES: usize = 15 * 1024 * 1024;
-const TRACE_TRUNCATE_SUFFIX: &str = "... (truncated)";
 
 /// The main client for interacting with the `OpenAI` Realtime API.
 ///
@@ -44,6 +99,9 @@ const TRACE_TRUNCATE_SUFFIX: &str = "... (truncated)";
 #[must_use]
 pub struct RealtimeClient {
     stream: WsStream,
+    trace: TraceConfig,
+    outbound: Outbound,
+    close: Option<ServerClose>,
 }
 
 impl RealtimeClient {
@@ -57,20 +115,93 @@ impl RealtimeClient {
         call_id: Option<&str>,
     ) -> Result<Self> {
         let stream = transport::ws::connect(api_key, model, call_id).await?;
-        Ok(Self { stream })
+        Ok(Self {
+            stream,
+            trace: TraceConfig::default(),
+            outbound: Outbound::default(),
+            close: None,
+        })
+    }
+
+    /// Connect to a Realtime-compatible endpoint other than the `OpenAI` API.
+    ///
+    /// # Errors
+    /// Returns an error if the connection fails or if the URL is invalid.
+    pub async fn connect_to(
+        base_url: &str,
+        api_key: &str,
+        model: Option<&str>,
+        call_id: Option<&str>,
+    ) -> Result<Self> {
+        let stream = transport::ws::connect_to(base_url, api_key, model, call_id).await?;
+        Ok(Self {
+            stream,
+            trace: TraceConfig::default(),
+            outbound: Outbound::default(),
+            close: None,
+        })
+    }
+
+    /// Like [`Self::connect_to`], giving up after `timeout`. Dropping the future
+    /// cancels the attempt.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectTimeout` if `timeout` passes first, or `Error::Connect`
+    /// if resolving, connecting, TLS or the WebSocket upgrade fails.
+    pub async fn connect_with_timeout(
+        base_url: &str,
+        api_key: &str,
+        model: Option<&str>,
+        call_id: Option<&str>,
+        timeout: std::time::Duration,
+    ) -> Result<Self> {
+        let stream =
+            transport::ws::connect_with_timeout(base_url, api_key, model, call_id, timeout).await?;
+        Ok(Self {
+            stream,
+            trace: TraceConfig::default(),
+            outbound: Outbound::default(),
+            close: None,
+        })
+    }
+
+    /// Choose how event payloads are written to `tracing` at TRACE level.
+    pub fn with_trace_config(mut self, trace: TraceConfig) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Reject any event that serializes to more than `max` bytes with
+    /// `Error::EventTooLarge`, instead of sending it.
+    pub const fn with_max_event_bytes(mut self, max: usize) -> Self {
+        self.outbound.max_event_bytes = Some(max);
+        self
+    }
+
+    /// Count sends into `stats`, e.g. to keep one total across connections.
+    pub(crate) fn with_outbound_stats(mut self, stats: OutboundStats) -> Self {
+        self.outbound.stats = stats;
+        self
+    }
+
+    /// Bytes sent so far per event type, updated live and shared with split halves.
+    #[must_use]
+    pub fn outbound_stats(&self) -> OutboundStats {
+        self.outbound.stats.clone()
     }
 
     /// Send a client event to the server.
     ///
     /// # Errors
-    /// Returns an error if serialization fails or if the WebSocket send fails.
+    /// Returns an error if serialization fails, the event is over the maximum size set
+    /// with [`Self::with_max_event_bytes`], or the WebSocket send fails.
     pub async fn send(&mut self, event: ClientEvent) -> Result<()> {
         validate_client_event(&event)?;
         let json = serde_json::to_string(&event)?;
-        tracing::trace!(
-            "Sending event: {}",
-            safe_truncate(&json, TRACE_LOG_MAX_BYTES)
-        );
+        self.outbound.admit(event.event_type(), &json)?;
+        if let Some(line) = self.trace.render(Some(event.event_type()), &json) {
+            tracing::trace!("Sending event: {line}");
+        }
         self.stream.send(Message::Text(json.into())).await?;
         Ok(())
     }
@@ -83,14 +214,19 @@ impl RealtimeClient {
         while let Some(msg) = self.stream.next().await {
             match msg? {
                 Message::Text(text) => {
-                    tracing::trace!(
-                        "Received event: {}",
-                        safe_truncate(&text, TRACE_LOG_MAX_BYTES)
-                    );
+                    if let Some(line) = self.trace.render(None, &text) {
+                        tracing::trace!("Received event: {line}");
+                    }
                     return Ok(Some(from_str::<ServerEvent>(&text)?));
                 }
-                Message::Close(_) => {
-                    tracing::info!("WebSocket connection closed by server");
+                Message::Close(frame) => {
+                    let close = ServerClose::from_frame(frame.as_ref());
+                    tracing::info!(
+                        code = close.code,
+                        reason = %close.reason,
+                        "WebSocket connection closed by server"
+                    );
+                    self.close = Some(close);
                     return Ok(None);
                 }
                 Message::Ping(payload) => {
@@ -103,46 +239,58 @@ impl RealtimeClient {
         Ok(None)
     }
 
+    /// The close frame the server sent, once [`Self::next_event`] has returned `None`
+    /// for it.
+    #[must_use]
+    pub const fn close_frame(&self) -> Option<&ServerClose> {
+        self.close.as_ref()
+    }
+
     /// Split the client into a sender and a receiver for concurrent usage.
     pub fn split(self) -> (RealtimeSender, RealtimeReceiver) {
         let (write, read) = self.stream.split();
-        (RealtimeSender { write }, RealtimeReceiver { read })
+        (
+            RealtimeSender {
+                write,
+                trace: self.trace.clone(),
+                outbound: self.outbound,
+            },
+            RealtimeReceiver {
+                read,
+                trace: self.trace,
+            },
+        )
     }
 
     /// Re-unify a split client.
     ///
     /// # Errors
     /// Returns an error if the split halves don't match or cannot be reunited.
-    #[allow(clippy::result_large_err)]
     pub fn unsplit(sender: RealtimeSender, receiver: RealtimeReceiver) -> Result<Self> {
         let stream = receiver.read.reunite(sender.write)?;
-        Ok(Self { stream })
-    }
-}
-
-fn safe_truncate(s: &str, max_bytes: usize) -> std::borrow::Cow<'_, str> {
-    if s.len() <= max_bytes {
-        return std::borrow::Cow::Borrowed(s);
-    }
-
-    let mut end = max_bytes;
-    while end > 0 && !s.is_char_boundary(end) {
-        end -= 1;
+        Ok(Self {
+            stream,
+            trace: sender.trace,
+            outbound: sender.outbound,
+            close: None,
+        })
     }
-    std::borrow::Cow::Owned(format!(
-        "{} {} {} bytes",
-        &s[..end],
-        TRACE_TRUNCATE_SUFFIX,
-        s.len() - end
-    ))
 }
 
 /// The sending half of a split `RealtimeClient`.
 pub struct RealtimeSender {
     write: futures::stream::SplitSink<WsStream, Message>,
+    trace: TraceConfig,
+    outbound: Outbound,
 }
 
 impl RealtimeSender {
+    /// Bytes sent so far per event type, including those sent before the split.
+    #[must_use]
+    pub fn outbound_stats(&self) -> OutboundStats {
+        self.outbound.stats.clone()
+    }
+
     /// Send a client event.
     ///
     /// # Errors
@@ -150,16 +298,15 @@ impl RealtimeSender {
     pub async fn send(&mut self, event: ClientEvent) -> Result<()> {
         validate_client_event(&event)?;
         let json = serde_json::to_string(&event)?;
-        tracing::trace!(
-            "Sending event (split): {}",
-            safe_truncate(&json, TRACE_LOG_MAX_BYTES)
-        );
+        self.outbound.admit(event.event_type(), &json)?;
+        if let Some(line) = self.trace.render(Some(event.event_type()), &json) {
+            tracing::trace!("Sending event (split): {line}");
+        }
         self.write.send(Message::Text(json.into())).await?;
         Ok(())
     }
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_client_event(event: &ClientEvent) -> Result<()> {
     match event {
         ClientEvent::InputAudioBufferAppend { audio, .. } => {
@@ -184,9 +331,12 @@ fn validate_client_event(event: &ClientEvent) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_session_update(session: &models::SessionUpdate) -> Result<()> {
     let config = &session.config;
+    config.validate_immutable_fields()?;
+    if let Some(modalities) = &config.output_modalities {
+        modalities.validate()?;
+    }
     if let Some(format) = &config.input_audio_format {
         validate_audio_format(format)?;
     }
@@ -202,8 +352,10 @@ fn validate_session_update(session: &models::SessionUpdate) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_response_config(config: &models::ResponseConfig) -> Result<()> {
+    if let Some(modalities) = &config.output_modalities {
+        modalities.validate()?;
+    }
     if let Some(audio) = &config.audio {
         validate_audio_config(audio)?;
     }
@@ -228,7 +380,6 @@ fn validate_response_config(config: &models::ResponseConfig) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_audio_config(audio: &models::AudioConfig) -> Result<()> {
     if let Some(input) = &audio.input {
         validate_input_audio_config(input)?;
@@ -239,7 +390,6 @@ fn validate_audio_config(audio: &models::AudioConfig) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_input_audio_config(audio: &models::InputAudioConfig) -> Result<()> {
     if let Some(format) = &audio.format {
         validate_audio_format(format)?;
@@ -247,21 +397,21 @@ fn validate_input_audio_config(audio: &models::InputAudioConfig) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_output_audio_config(audio: &models::OutputAudioConfig) -> Result<()> {
     if let Some(format) = &audio.format {
         validate_audio_format(format)?;
     }
+    if let Some(speed) = audio.speed {
+        models::OutputAudioConfig::validate_speed(speed)?;
+    }
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_audio_format(format: &models::AudioFormat) -> Result<()> {
     format.validate()?;
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn validate_tools(tools: &[models::Tool]) -> Result<()> {
     for tool in tools {
         if let models::Tool::Mcp(config) = tool {
@@ -271,7 +421,6 @@ fn validate_tools(tools: &[models::Tool]) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::result_large_err)]
 fn estimate_base64_decoded_len(s: &str) -> Result<usize> {
     let bytes = s.as_bytes();
     if bytes.len() % 4 != 0 {
@@ -315,26 +464,37 @@ fn estimate_base64_decoded_len(s: &str) -> Result<usize> {
 /// The receiving half of a split `RealtimeClient`.
 pub struct RealtimeReceiver {
     read: futures::stream::SplitStream<WsStream>,
+    trace: TraceConfig,
 }
 
 impl RealtimeReceiver {
     /// Exposes an asynchronous stream of `Result<ServerEvent>` that preserves Errors.
     #[must_use]
     pub fn try_into_stream(self) -> BoxStream<'static, Result<ServerEvent>> {
+        self.try_into_raw_stream()
+            .map(|res| res.and_then(|text| from_str::<ServerEvent>(&text).map_err(Error::from)))
+            .boxed()
+    }
+
+    /// Like [`Self::try_into_stream`], yielding each text frame as received, e.g. to
+    /// record event types or fields this crate does not model.
+    #[must_use]
+    pub fn try_into_raw_stream(self) -> BoxStream<'static, Result<String>> {
+        let trace = self.trace;
         self.read
             .map(|res| res.map_err(Error::from))
-            .filter_map(|res| async move {
-                match res {
+            .filter_map(move |res| {
+                let frame = match res {
                     Ok(Message::Text(text)) => {
-                        tracing::trace!(
-                            "Received event (stream): {}",
-                            safe_truncate(&text, TRACE_LOG_MAX_BYTES)
-                        );
-                        Some(from_str::<ServerEvent>(&text).map_err(Error::from))
+                        if let Some(line) = trace.render(None, &text) {
+                            tracing::trace!("Received event (stream): {line}");
+                        }
+                        Some(Ok(text.to_string()))
                     }
                     Ok(_) => None,
                     Err(e) => Some(Err(e)),
-                }
+                };
+                futures::future::ready(frame)
             })
             .boxed()
     }
@@ -0,0 +1,625 @@
+//! A local stand-in for the Realtime server, for examples and tests.
+//!
+//! [`MockServer`] speaks enough of the protocol over a plain WebSocket to drive the
+//! SDK end to end without an API key: it sends `session.created`, acknowledges
+//! session updates and conversation items, answers `response.create` by echoing the
+//! last user input as text or as silent audio with a transcript, and turns inputs
+//! containing a scripted trigger into function calls. It does not run VAD or look
+//! at audio content.
+
+use crate::Result;
+use crate::error::{ApiErrorType, ServerError};
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{
+    ContentPart, ConversationMode, Item, ItemStatus, OutputModalities, Response, ResponseConfig,
+    ResponseStatus, Role, Session, SessionConfig, SessionKind, SessionUpdate,
+};
+use crate::protocol::server_events::ServerEvent;
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Model name reported in the mock session.
+const MOCK_MODEL: &str = "gpt-realtime-mock";
+/// Silent PCM16 @ 24kHz sent per transcript word: 20ms.
+const SILENCE_BYTES_PER_WORD: usize = 960;
+
+/// A function call the mock makes when the user's input contains `trigger`.
+#[derive(Debug, Clone)]
+pub struct ScriptedToolCall {
+    pub trigger: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+#[derive(Debug, Default)]
+pub struct MockServerBuilder {
+    tool_calls: Vec<ScriptedToolCall>,
+}
+
+impl MockServerBuilder {
+    /// Answer inputs containing `trigger` with a call to `name`. The next response
+    /// after the call's output arrives reads that output back.
+    #[must_use]
+    pub fn tool_call(
+        mut self,
+        trigger: impl Into<String>,
+        name: impl Into<String>,
+        arguments: Value,
+    ) -> Self {
+        self.tool_calls.push(ScriptedToolCall {
+            trigger: trigger.into(),
+            name: name.into(),
+            arguments,
+        });
+        self
+    }
+
+    /// Listen on an ephemeral port on the loopback interface.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn start(self) -> Result<MockServer> {
+        self.bind("127.0.0.1:0").await
+    }
+
+    /// Listen on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn bind(self, addr: impl ToSocketAddrs) -> Result<MockServer> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let script: Arc<[ScriptedToolCall]> = self.tool_calls.into();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, Arc::clone(&script)));
+            }
+        });
+        Ok(MockServer { addr, task })
+    }
+}
+
+/// Running mock server. Stops accepting connections when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    #[must_use]
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    /// Start a server without scripted tool calls.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL to pass to `RealtimeBuilder::base_url`.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("ws://{}/v1/realtime", self.addr)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(stream: TcpStream, script: Arc<[ScriptedToolCall]>) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let mut conn = Connection::new(script);
+    let mut pending = vec![conn.session_created()];
+    loop {
+        for event in std::mem::take(&mut pending) {
+            let Ok(json) = serde_json::to_string(&event) else {
+                continue;
+            };
+            if ws.send(Message::Text(json.into())).await.is_err() {
+                return;
+            }
+        }
+        let Some(Ok(msg)) = ws.next().await else {
+            return;
+        };
+        match msg {
+            Message::Text(text) => match serde_json::from_str::<ClientEvent>(&text) {
+                Ok(event) => pending = conn.handle(event),
+                Err(err) => pending.push(conn.error("invalid_json", &err.to_string(), None)),
+            },
+            Message::Close(_) => return,
+            _ => {}
+        }
+    }
+}
+
+/// Protocol state of one client connection.
+struct Connection {
+    session: Session,
+    items: Vec<Item>,
+    script: Arc<[ScriptedToolCall]>,
+    input_audio: Vec<u8>,
+    /// Latest user input not yet answered.
+    last_input: Option<String>,
+    /// Latest function call output not yet read back.
+    tool_output: Option<String>,
+    next_id: u64,
+}
+
+impl Connection {
+    fn new(script: Arc<[ScriptedToolCall]>) -> Self {
+        let mut conn = Self {
+            session: Session {
+                id: String::new(),
+                object: "realtime.session".to_string(),
+                expires_at: 0,
+                config: SessionConfig::new(
+                    SessionKind::Realtime,
+                    MOCK_MODEL,
+                    OutputModalities::Audio,
+                ),
+            },
+            items: Vec::new(),
+            script,
+            input_audio: Vec::new(),
+            last_input: None,
+            tool_output: None,
+            next_id: 0,
+        };
+        conn.session.id = conn.id("sess");
+        conn
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}_mock_{}", self.next_id)
+    }
+
+    fn session_created(&mut self) -> ServerEvent {
+        ServerEvent::SessionCreated {
+            event_id: self.id("event"),
+            session: self.session.clone(),
+        }
+    }
+
+    fn error(&mut self, code: &str, message: &str, event_id: Option<String>) -> ServerEvent {
+        ServerEvent::Error {
+            event_id: self.id("event"),
+            error: ServerError {
+                error_type: ApiErrorType::InvalidRequestError,
+                code: Some(code.to_string()),
+                message: message.to_string(),
+                param: None,
+                event_id,
+            },
+        }
+    }
+
+    fn handle(&mut self, event: ClientEvent) -> Vec<ServerEvent> {
+        match event {
+            ClientEvent::SessionUpdate { session, .. } => vec![self.update_session(*session)],
+            ClientEvent::ConversationItemCreate {
+                item,
+                previous_item_id,
+                ..
+            } => vec![self.add_item(*item, previous_item_id)],
+            ClientEvent::ConversationItemRetrieve {
+                event_id, item_id, ..
+            } => {
+                let item = self
+                    .items
+                    .iter()
+                    .find(|item| item.id() == Some(item_id.as_str()))
+                    .cloned();
+                vec![match item {
+                    Some(item) => ServerEvent::ConversationItemRetrieved {
+                        event_id: self.id("event"),
+                        item,
+                    },
+                    None => self.error("item_not_found", "Item not found", event_id),
+                }]
+            }
+            ClientEvent::ConversationItemDelete { item_id, .. } => {
+                self.items
+                    .retain(|item| item.id() != Some(item_id.as_str()));
+                vec![ServerEvent::ConversationItemDeleted {
+                    event_id: self.id("event"),
+                    item_id,
+                }]
+            }
+            ClientEvent::InputAudioBufferAppend { audio, .. } => {
+                let _ = general_purpose::STANDARD.decode_vec(audio, &mut self.input_audio);
+                Vec::new()
+            }
+            ClientEvent::InputAudioBufferCommit { .. } => self.commit_audio(),
+            ClientEvent::InputAudioBufferClear { .. } => {
+                self.input_audio.clear();
+                vec![ServerEvent::InputAudioBufferCleared {
+                    event_id: self.id("event"),
+                }]
+            }
+            ClientEvent::ResponseCreate { response, .. } => self.respond(response.as_deref()),
+            ClientEvent::ConversationItemTruncate { .. }
+            | ClientEvent::ResponseCancel { .. }
+            | ClientEvent::OutputAudioBufferClear { .. } => Vec::new(),
+        }
+    }
+
+    fn update_session(&mut self, update: SessionUpdate) -> ServerEvent {
+        let update = update.config;
+        let config = &mut self.session.config;
+        if let Some(modalities) = update.output_modalities {
+            config.output_modalities = modalities;
+        }
+        if update.instructions.is_some() {
+            config.instructions = update.instructions;
+        }
+        if update.tools.is_some() {
+            config.tools = update.tools;
+        }
+        if update.audio.is_some() {
+            config.audio = update.audio;
+        }
+        if update.tracing.is_some() {
+            config.tracing = update.tracing;
+        }
+        ServerEvent::SessionUpdated {
+            event_id: self.id("event"),
+            session: self.session.clone(),
+        }
+    }
+
+    fn add_item(&mut self, item: Item, previous_item_id: Option<String>) -> ServerEvent {
+        let item = match item.id() {
+            Some(_) => item,
+            None => item.with_id(self.id("item")),
+        };
+        match &item {
+            Item::Message {
+                role: Role::User,
+                content,
+                ..
+            } => {
+                let text: Vec<&str> = content
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::InputText { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                self.last_input = Some(text.join(" "));
+            }
+            Item::FunctionCallOutput { output, .. } => self.tool_output = Some(output.clone()),
+            _ => {}
+        }
+        self.items.push(item.clone());
+        ServerEvent::ConversationItemCreated {
+            event_id: self.id("event"),
+            previous_item_id,
+            item,
+        }
+    }
+
+    fn commit_audio(&mut self) -> Vec<ServerEvent> {
+        let audio = std::mem::take(&mut self.input_audio);
+        let millis = audio.len() / 48;
+        let item_id = self.id("item");
+        let item = Item::Message {
+            id: Some(item_id.clone()),
+            status: Some(ItemStatus::Completed),
+            role: Role::User,
+            content: vec![ContentPart::InputAudio {
+                audio: general_purpose::STANDARD.encode(&audio),
+                transcript: None,
+                format: None,
+            }],
+        };
+        let previous_item_id = self.items.last().and_then(Item::id).map(str::to_string);
+        self.items.push(item.clone());
+        self.last_input = Some(format!("{millis} milliseconds of audio"));
+        vec![
+            ServerEvent::InputAudioBufferCommitted {
+                event_id: self.id("event"),
+                previous_item_id: previous_item_id.clone(),
+                item_id,
+            },
+            ServerEvent::ConversationItemCreated {
+                event_id: self.id("event"),
+                previous_item_id,
+                item,
+            },
+        ]
+    }
+
+    fn respond(&mut self, config: Option<&ResponseConfig>) -> Vec<ServerEvent> {
+        let modalities = config
+            .and_then(|config| config.output_modalities)
+            .unwrap_or(self.session.config.output_modalities);
+        let in_conversation =
+            config.and_then(|config| config.conversation) != Some(ConversationMode::None);
+        let mut response = Response {
+            id: self.id("resp"),
+            object: "realtime.response".to_string(),
+            conversation_id: in_conversation.then(|| format!("conv_{}", self.session.id)),
+            status: ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: Some(modalities),
+            max_output_tokens: None,
+            audio: None,
+            metadata: config.and_then(|config| config.metadata.clone()),
+            usage: None,
+        };
+        let mut events = vec![ServerEvent::ResponseCreated {
+            event_id: self.id("event"),
+            response: response.clone(),
+        }];
+
+        let item = if let Some(call) = self.scripted_call() {
+            self.function_call(&response.id, &call, &mut events)
+        } else {
+            let text = self.reply_text();
+            self.message(&response.id, &text, modalities, &mut events)
+        };
+        if in_conversation {
+            self.items.push(item.clone());
+        }
+
+        response.status = ResponseStatus::Completed;
+        response.output = Some(vec![item]);
+        events.push(ServerEvent::ResponseDone {
+            event_id: self.id("event"),
+            response,
+        });
+        events
+    }
+
+    fn scripted_call(&mut self) -> Option<ScriptedToolCall> {
+        if self.tool_output.is_some() {
+            return None;
+        }
+        let input = self.last_input.as_deref()?;
+        let call = self
+            .script
+            .iter()
+            .find(|call| input.contains(&call.trigger))?
+            .clone();
+        self.last_input = None;
+        Some(call)
+    }
+
+    fn reply_text(&mut self) -> String {
+        if let Some(output) = self.tool_output.take() {
+            format!("The tool returned {output}.")
+        } else if let Some(input) = self.last_input.take() {
+            format!("You said: {input}")
+        } else {
+            "Hello from the mock server.".to_string()
+        }
+    }
+
+    fn function_call(
+        &mut self,
+        response_id: &str,
+        call: &ScriptedToolCall,
+        events: &mut Vec<ServerEvent>,
+    ) -> Item {
+        let item_id = self.id("item");
+        let call_id = self.id("call");
+        let arguments = call.arguments.to_string();
+        let item = |status, arguments: &str| Item::FunctionCall {
+            id: Some(item_id.clone()),
+            status: Some(status),
+            name: call.name.clone(),
+            call_id: call_id.clone(),
+            arguments: arguments.to_string(),
+        };
+        let done = item(ItemStatus::Completed, &arguments);
+        events.extend([
+            ServerEvent::ResponseOutputItemAdded {
+                event_id: self.id("event"),
+                response_id: response_id.to_string(),
+                output_index: 0,
+                item: item(ItemStatus::InProgress, ""),
+            },
+            ServerEvent::ResponseFunctionCallArgumentsDelta {
+                event_id: self.id("event"),
+                response_id: response_id.to_string(),
+                item_id: item_id.clone(),
+                output_index: 0,
+                call_id: call_id.clone(),
+                delta: arguments.clone(),
+            },
+            ServerEvent::ResponseFunctionCallArgumentsDone {
+                event_id: self.id("event"),
+                response_id: response_id.to_string(),
+                item_id: item_id.clone(),
+                output_index: 0,
+                call_id: call_id.clone(),
+                name: call.name.clone(),
+                arguments,
+            },
+            ServerEvent::ResponseOutputItemDone {
+                event_id: self.id("event"),
+                response_id: response_id.to_string(),
+                output_index: 0,
+                item: done.clone(),
+            },
+        ]);
+        done
+    }
+
+    fn message(
+        &mut self,
+        response_id: &str,
+        text: &str,
+        modalities: OutputModalities,
+        events: &mut Vec<ServerEvent>,
+    ) -> Item {
+        let item_id = self.id("item");
+        let audio = modalities != OutputModalities::Text;
+        let part = |text: &str| {
+            if audio {
+                ContentPart::OutputAudio {
+                    audio: None,
+                    transcript: Some(text.to_string()),
+                    format: None,
+                }
+            } else {
+                ContentPart::OutputText {
+                    text: text.to_string(),
+                }
+            }
+        };
+        let item = |status, content| Item::Message {
+            id: Some(item_id.clone()),
+            status: Some(status),
+            role: Role::Assistant,
+            content,
+        };
+
+        events.push(ServerEvent::ResponseOutputItemAdded {
+            event_id: self.id("event"),
+            response_id: response_id.to_string(),
+            output_index: 0,
+            item: item(ItemStatus::InProgress, Vec::new()),
+        });
+        let mut content = vec![Content::PartAdded(part(""))];
+        for word in text.split_inclusive(' ') {
+            if audio {
+                let silence = general_purpose::STANDARD.encode([0u8; SILENCE_BYTES_PER_WORD]);
+                content.push(Content::AudioDelta(silence));
+                content.push(Content::TranscriptDelta(word.to_string()));
+            } else {
+                content.push(Content::TextDelta(word.to_string()));
+            }
+        }
+        if audio {
+            content.push(Content::AudioDone);
+            content.push(Content::TranscriptDone(text.to_string()));
+        } else {
+            content.push(Content::TextDone(text.to_string()));
+        }
+        content.push(Content::PartDone(part(text)));
+        for content in content {
+            let event_id = self.id("event");
+            events.push(content.into_event(event_id, response_id, &item_id));
+        }
+
+        let done = item(ItemStatus::Completed, vec![part(text)]);
+        events.push(ServerEvent::ResponseOutputItemDone {
+            event_id: self.id("event"),
+            response_id: response_id.to_string(),
+            output_index: 0,
+            item: done.clone(),
+        });
+        done
+    }
+}
+
+/// Events about the single content part of a mock reply.
+enum Content {
+    PartAdded(ContentPart),
+    TextDelta(String),
+    TextDone(String),
+    AudioDelta(String),
+    AudioDone,
+    TranscriptDelta(String),
+    TranscriptDone(String),
+    PartDone(ContentPart),
+}
+
+impl Content {
+    fn into_event(self, event_id: String, response_id: &str, item_id: &str) -> ServerEvent {
+        let response_id = response_id.to_string();
+        let item_id = item_id.to_string();
+        let (output_index, content_index) = (0, 0);
+        match self {
+            Self::PartAdded(part) => ServerEvent::ResponseContentPartAdded {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                part,
+            },
+            Self::TextDelta(delta) => ServerEvent::ResponseOutputTextDelta {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                delta,
+            },
+            Self::TextDone(text) => ServerEvent::ResponseOutputTextDone {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                text,
+            },
+            Self::AudioDelta(delta) => ServerEvent::ResponseOutputAudioDelta {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                delta,
+            },
+            Self::AudioDone => ServerEvent::ResponseOutputAudioDone {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                item: None,
+            },
+            Self::TranscriptDelta(delta) => ServerEvent::ResponseOutputAudioTranscriptDelta {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                delta,
+            },
+            Self::TranscriptDone(transcript) => ServerEvent::ResponseOutputAudioTranscriptDone {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                transcript,
+            },
+            Self::PartDone(part) => ServerEvent::ResponseContentPartDone {
+                event_id,
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+                part,
+            },
+        }
+    }
+}
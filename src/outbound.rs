@@ -0,0 +1,122 @@
+//! Size accounting and an optional size limit for events sent to the server.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Serialized bytes sent, per event type, as of one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboundTotals {
+    pub by_type: BTreeMap<String, EventSizeStats>,
+    /// All events together.
+    pub total_bytes: u64,
+    pub largest: Option<LargestEvent>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSizeStats {
+    pub count: u64,
+    pub bytes: u64,
+    pub max_bytes: u64,
+}
+
+/// The biggest event sent so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargestEvent {
+    pub event_type: String,
+    pub bytes: u64,
+}
+
+/// Live counters shared by a `RealtimeClient` and its split halves.
+#[derive(Debug, Clone, Default)]
+pub struct OutboundStats(Arc<Mutex<OutboundTotals>>);
+
+impl OutboundStats {
+    /// The totals so far.
+    #[must_use]
+    pub fn snapshot(&self) -> OutboundTotals {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn record(&self, event_type: &str, bytes: usize) {
+        let bytes = bytes as u64;
+        let mut totals = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = totals.by_type.entry(event_type.to_string()).or_default();
+        stats.count += 1;
+        stats.bytes += bytes;
+        stats.max_bytes = stats.max_bytes.max(bytes);
+        totals.total_bytes += bytes;
+        if totals
+            .largest
+            .as_ref()
+            .is_none_or(|largest| bytes > largest.bytes)
+        {
+            totals.largest = Some(LargestEvent {
+                event_type: event_type.to_string(),
+                bytes,
+            });
+        }
+    }
+}
+
+/// The sending side's limit and counters.
+#[derive(Debug, Clone, Default)]
+pub struct Outbound {
+    pub stats: OutboundStats,
+    pub max_event_bytes: Option<usize>,
+}
+
+impl Outbound {
+    /// Reject `json` if it is over the limit, otherwise count it as sent.
+    pub fn admit(&self, event_type: &'static str, json: &str) -> Result<()> {
+        if self.max_event_bytes.is_some_and(|max| json.len() > max) {
+            return Err(Error::EventTooLarge {
+                event_type,
+                bytes: json.len(),
+            });
+        }
+        self.stats.record(event_type, json.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_events_are_rejected_and_not_counted() {
+        let outbound = Outbound {
+            max_event_bytes: Some(10),
+            ..Outbound::default()
+        };
+        outbound.admit("response.create", "{}").unwrap();
+        outbound.admit("response.create", "{\"a\":1}").unwrap();
+        let err = outbound
+            .admit("conversation.item.create", "{\"image\":\"AAAA\"}")
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::EventTooLarge {
+                event_type: "conversation.item.create",
+                bytes: 16,
+            }
+        ));
+
+        let totals = outbound.stats.snapshot();
+        assert_eq!(totals.total_bytes, 9);
+        assert_eq!(
+            totals.by_type["response.create"],
+            EventSizeStats {
+                count: 2,
+                bytes: 9,
+                max_bytes: 7,
+            }
+        );
+        assert_eq!(totals.largest.unwrap().bytes, 7);
+    }
+}
@@ -0,0 +1,20 @@
+//! The items most sessions use, for a single glob import:
+//!
+//! ```
+//! use oai_rt_rs::prelude::*;
+//! ```
+//!
+//! `Result` is left out so the import does not shadow `std::result::Result`; name it
+//! as `oai_rt_rs::Result`. Everything else lives under [`crate::sdk`] and
+//! [`crate::protocol`].
+
+pub use crate::error::Error;
+pub use crate::protocol::models::{
+    AudioFormat, ContentPart, Item, OutputModalities, Role, SessionConfig, Voice,
+};
+pub use crate::protocol::{ClientEvent, ServerEvent};
+pub use crate::sdk::{
+    AudioChunk, CloseReason, EventHandlers, Realtime, RealtimeBuilder, ResponseBuilder, SdkEvent,
+    Session, SessionHandle, ToolCall, ToolRegistry, ToolResult, TranscriptChunk, VoiceEvent,
+    VoiceSessionBuilder,
+};
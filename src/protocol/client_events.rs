@@ -1,6 +1,6 @@
-use super::models::{Item, ResponseConfig, SessionUpdate};
+use super::models::{Include, Item, ResponseConfig, SessionUpdate};
 use serde::{Deserialize, Serialize};
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientEvent {
     #[serde(rename = "session.update")]
@@ -38,6 +38,8 @@ pub enum ClientEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         event_id: Option<String>,
         item_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        include: Option<Vec<Include>>,
     },
     #[serde(rename = "conversation.item.truncate")]
     ConversationItemTruncate {
@@ -73,3 +75,62 @@ pub enum ClientEvent {
         event_id: Option<String>,
     },
 }
+
+impl ClientEvent {
+    /// Wire name of the event (the `type` field).
+    #[must_use]
+    pub const fn event_type(&self) -> &'static str {
+        match self {
+            Self::SessionUpdate { .. } => "session.update",
+            Self::InputAudioBufferAppend { .. } => "input_audio_buffer.append",
+            Self::InputAudioBufferCommit { .. } => "input_audio_buffer.commit",
+            Self::InputAudioBufferClear { .. } => "input_audio_buffer.clear",
+            Self::ConversationItemCreate { .. } => "conversation.item.create",
+            Self::ConversationItemRetrieve { .. } => "conversation.item.retrieve",
+            Self::ConversationItemTruncate { .. } => "conversation.item.truncate",
+            Self::ConversationItemDelete { .. } => "conversation.item.delete",
+            Self::ResponseCreate { .. } => "response.create",
+            Self::ResponseCancel { .. } => "response.cancel",
+            Self::OutputAudioBufferClear { .. } => "output_audio_buffer.clear",
+        }
+    }
+
+    /// Client-assigned event ID, echoed by the server in related `error` events.
+    #[must_use]
+    pub fn event_id(&self) -> Option<&str> {
+        match self {
+            Self::SessionUpdate { event_id, .. }
+            | Self::InputAudioBufferAppend { event_id, .. }
+            | Self::InputAudioBufferCommit { event_id }
+            | Self::InputAudioBufferClear { event_id }
+            | Self::ConversationItemCreate { event_id, .. }
+            | Self::ConversationItemRetrieve { event_id, .. }
+            | Self::ConversationItemTruncate { event_id, .. }
+            | Self::ConversationItemDelete { event_id, .. }
+            | Self::ResponseCreate { event_id, .. }
+            | Self::ResponseCancel { event_id, .. }
+            | Self::OutputAudioBufferClear { event_id } => event_id.as_deref(),
+        }
+    }
+
+    /// Set the client-assigned event ID.
+    pub fn set_event_id(&mut self, event_id: impl Into<String>) {
+        *self.event_id_slot_mut() = Some(event_id.into());
+    }
+
+    const fn event_id_slot_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Self::SessionUpdate { event_id, .. }
+            | Self::InputAudioBufferAppend { event_id, .. }
+            | Self::InputAudioBufferCommit { event_id }
+            | Self::InputAudioBufferClear { event_id }
+            | Self::ConversationItemCreate { event_id, .. }
+            | Self::ConversationItemRetrieve { event_id, .. }
+            | Self::ConversationItemTruncate { event_id, .. }
+            | Self::ConversationItemDelete { event_id, .. }
+            | Self::ResponseCreate { event_id, .. }
+            | Self::ResponseCancel { event_id, .. }
+            | Self::OutputAudioBufferClear { event_id } => event_id,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use super::common::open_enum;
 use super::{Eagerness, Nullable, Voice};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,9 +41,25 @@ impl AudioFormat {
         }
     }
 
+    /// Encoded bytes per second of mono audio: 16-bit PCM, or 8kHz G.711.
+    #[must_use]
+    pub const fn bytes_per_second(&self) -> u32 {
+        match self {
+            Self::Pcm { rate } => *rate * 2,
+            Self::Pcmu | Self::Pcma => 8_000,
+        }
+    }
+
+    /// Playback duration of `len` encoded bytes.
+    #[must_use]
+    pub fn duration_of(&self, len: usize) -> std::time::Duration {
+        let rate = u64::from(self.bytes_per_second().max(1));
+        let len = u64::try_from(len).unwrap_or(u64::MAX);
+        std::time::Duration::from_micros(len.saturating_mul(1_000_000) / rate)
+    }
+
     /// # Errors
     /// Returns an error if a PCM format is configured with a non-24kHz rate.
-    #[allow(clippy::result_large_err)]
     pub fn validate(&self) -> Result<(), crate::error::Error> {
         match self {
             Self::Pcm { rate } if *rate != PCM_24KHZ_RATE => {
@@ -55,13 +72,13 @@ impl AudioFormat {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AudioConfig {
     pub input: Option<InputAudioConfig>,
     pub output: Option<OutputAudioConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct InputAudioConfig {
     pub format: Option<AudioFormat>,
     pub turn_detection: Option<Nullable<TurnDetection>>,
@@ -69,28 +86,48 @@ pub struct InputAudioConfig {
     pub noise_reduction: Option<Nullable<NoiseReduction>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct NoiseReduction {
     #[serde(rename = "type")]
     pub kind: NoiseReductionType,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum NoiseReductionType {
-    #[default]
-    NearField,
-    FarField,
+open_enum! {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+    pub enum NoiseReductionType {
+        #[default]
+        NearField => "near_field",
+        FarField => "far_field",
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct OutputAudioConfig {
     pub format: Option<AudioFormat>,
     pub voice: Option<Voice>,
     pub speed: Option<f32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl OutputAudioConfig {
+    pub const MIN_SPEED: f32 = 0.25;
+    pub const MAX_SPEED: f32 = 1.5;
+
+    /// # Errors
+    /// Returns an error if `speed` is outside [`Self::MIN_SPEED`, `Self::MAX_SPEED`].
+    pub fn validate_speed(speed: f32) -> Result<(), crate::error::Error> {
+        if (Self::MIN_SPEED..=Self::MAX_SPEED).contains(&speed) {
+            Ok(())
+        } else {
+            Err(crate::error::Error::InvalidClientEvent(format!(
+                "audio.output.speed must be between {} and {}, got {speed}",
+                Self::MIN_SPEED,
+                Self::MAX_SPEED
+            )))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct InputAudioTranscription {
     pub model: Option<String>,
     pub language: Option<String>,
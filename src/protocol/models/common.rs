@@ -13,6 +13,75 @@ pub type JsonSchema = Value;
 /// Free-form JSON payloads where the spec is open-ended.
 pub type ArbitraryJson = Value;
 
+/// A string enum that keeps values it does not know as `Other` instead of failing.
+///
+/// `Other` holds the raw value and serializes back unchanged. A known value is never
+/// parsed into `Other`, so match on the named variants.
+macro_rules! open_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $wire:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[non_exhaustive]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value this version of the SDK does not know.
+            Other(String),
+        }
+
+        impl $name {
+            /// The value as it appears on the wire.
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $wire,)*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($wire => Self::$variant,)*
+                    _ => Self::Other(value.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $($wire => Self::$variant,)*
+                    _ => Self::Other(value),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+pub(crate) use open_enum;
+
 /// Tri-state helper for fields that can be omitted, set to null, or set to a value.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(untagged)]
@@ -40,13 +109,14 @@ pub enum Role {
     System,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum ItemStatus {
-    #[default]
-    InProgress,
-    Completed,
-    Incomplete,
+open_enum! {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+    pub enum ItemStatus {
+        #[default]
+        InProgress => "in_progress",
+        Completed => "completed",
+        Incomplete => "incomplete",
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -57,10 +127,56 @@ pub enum Modality {
     Text,
 }
 
+/// Set of output modalities, serialized as a list.
+///
+/// The GA API accepts exactly one modality (enforced by `validate`); `AudioAndText`
+/// keeps payloads from servers that allow both parseable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputModalities {
     Audio,
     Text,
+    AudioAndText,
+}
+
+impl OutputModalities {
+    /// Build the set from membership flags; `None` if empty.
+    #[must_use]
+    pub const fn from_set(audio: bool, text: bool) -> Option<Self> {
+        match (audio, text) {
+            (true, true) => Some(Self::AudioAndText),
+            (true, false) => Some(Self::Audio),
+            (false, true) => Some(Self::Text),
+            (false, false) => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn modalities(self) -> &'static [Modality] {
+        match self {
+            Self::Audio => &[Modality::Audio],
+            Self::Text => &[Modality::Text],
+            Self::AudioAndText => &[Modality::Audio, Modality::Text],
+        }
+    }
+
+    #[must_use]
+    pub const fn contains(self, modality: Modality) -> bool {
+        matches!(
+            (self, modality),
+            (Self::AudioAndText, _) | (Self::Audio, Modality::Audio) | (Self::Text, Modality::Text)
+        )
+    }
+
+    /// # Errors
+    /// Returns an error unless exactly one modality is set, as required by the GA API.
+    pub fn validate(&self) -> Result<(), crate::error::Error> {
+        match self {
+            Self::Audio | Self::Text => Ok(()),
+            Self::AudioAndText => Err(crate::error::Error::InvalidClientEvent(
+                "output_modalities must contain exactly one of: audio or text".to_string(),
+            )),
+        }
+    }
 }
 
 impl Serialize for OutputModalities {
@@ -68,11 +184,7 @@ impl Serialize for OutputModalities {
     where
         S: Serializer,
     {
-        let values = match self {
-            Self::Audio => vec![Modality::Audio],
-            Self::Text => vec![Modality::Text],
-        };
-        values.serialize(serializer)
+        self.modalities().serialize(serializer)
     }
 }
 
@@ -88,28 +200,37 @@ impl<'de> Deserialize<'de> for OutputModalities {
             Many(Vec<Modality>),
         }
 
-        match Repr::deserialize(deserializer)? {
-            Repr::Single(Modality::Audio) => Ok(Self::Audio),
-            Repr::Single(Modality::Text) => Ok(Self::Text),
-            Repr::Many(values) => match values.as_slice() {
-                [Modality::Audio] => Ok(Self::Audio),
-                [Modality::Text] => Ok(Self::Text),
-                _ => Err(serde::de::Error::custom(
-                    "output_modalities must contain exactly one of: audio or text",
-                )),
-            },
-        }
+        let values = match Repr::deserialize(deserializer)? {
+            Repr::Single(modality) => vec![modality],
+            Repr::Many(values) => values,
+        };
+        Self::from_set(
+            values.contains(&Modality::Audio),
+            values.contains(&Modality::Text),
+        )
+        .ok_or_else(|| serde::de::Error::custom("output_modalities must not be empty"))
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum Eagerness {
-    Auto,
-    Low,
-    #[default]
-    Medium,
-    High,
+open_enum! {
+    /// Extra data to include in server events, for the `include` fields of sessions,
+    /// responses and item retrieval.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Include {
+        /// Token logprobs on input audio transcriptions.
+        InputAudioTranscriptionLogprobs => "item.input_audio_transcription.logprobs",
+    }
+}
+
+open_enum! {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+    pub enum Eagerness {
+        Auto => "auto",
+        Low => "low",
+        #[default]
+        Medium => "medium",
+        High => "high",
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
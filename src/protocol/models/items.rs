@@ -5,7 +5,7 @@ use super::{ArbitraryJson, AudioFormat, ItemStatus, McpError, McpToolInfo, Role}
 
 /// Manual (de)serialization preserves unknown variants as raw JSON while keeping
 /// strong typing for known items.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Item {
     Message {
         id: Option<String>,
@@ -75,7 +75,7 @@ impl std::fmt::Display for Item {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum ItemRepr {
     Message {
@@ -129,6 +129,87 @@ enum ItemRepr {
     },
 }
 
+impl Item {
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::Message { id, .. }
+            | Self::FunctionCall { id, .. }
+            | Self::FunctionCallOutput { id, .. }
+            | Self::McpCall { id, .. }
+            | Self::McpListTools { id, .. }
+            | Self::McpApprovalRequest { id, .. }
+            | Self::McpApprovalResponse { id, .. } => id.as_deref(),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// The same item with its ID set, e.g. to name an item before creating it.
+    #[must_use]
+    pub fn with_id(mut self, item_id: impl Into<String>) -> Self {
+        self.set_id(item_id);
+        self
+    }
+
+    pub fn set_id(&mut self, item_id: impl Into<String>) {
+        let item_id = item_id.into();
+        match self {
+            Self::Message { id, .. }
+            | Self::FunctionCall { id, .. }
+            | Self::FunctionCallOutput { id, .. }
+            | Self::McpCall { id, .. }
+            | Self::McpListTools { id, .. }
+            | Self::McpApprovalRequest { id, .. }
+            | Self::McpApprovalResponse { id, .. } => *id = Some(item_id),
+            Self::Unknown(value) => {
+                if let Some(object) = value.as_object_mut() {
+                    object.insert("id".to_string(), ArbitraryJson::String(item_id));
+                }
+            }
+        }
+    }
+
+    /// Rewrite beta `text`/`audio` parts of a message as their GA equivalents, picked
+    /// by the message role. Other items are returned unchanged.
+    #[must_use]
+    pub fn into_ga(self) -> Self {
+        match self {
+            Self::Message {
+                id,
+                status,
+                role,
+                content,
+            } => Self::Message {
+                id,
+                status,
+                role,
+                content: content.into_iter().map(|part| part.into_ga(role)).collect(),
+            },
+            other => other,
+        }
+    }
+
+    /// Rewrite the GA text and audio parts of a message as beta `text`/`audio` parts.
+    /// Other items are returned unchanged.
+    #[must_use]
+    pub fn into_beta(self) -> Self {
+        match self {
+            Self::Message {
+                id,
+                status,
+                role,
+                content,
+            } => Self::Message {
+                id,
+                status,
+                role,
+                content: content.into_iter().map(ContentPart::into_beta).collect(),
+            },
+            other => other,
+        }
+    }
+}
+
 impl From<ItemRepr> for Item {
     fn from(repr: ItemRepr) -> Self {
         match repr {
@@ -403,16 +484,32 @@ impl<'de> Deserialize<'de> for Item {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum AudioPartFormat {
     Label(String),
     Config(AudioFormat),
 }
 
+impl AudioPartFormat {
+    /// The GA format, reading beta labels such as `pcm16` and `g711_ulaw`.
+    #[must_use]
+    pub fn to_format(&self) -> Option<AudioFormat> {
+        match self {
+            Self::Config(format) => Some(format.clone()),
+            Self::Label(label) => match label.as_str() {
+                "pcm16" | "audio/pcm" => Some(AudioFormat::pcm_24khz()),
+                "g711_ulaw" | "audio/pcmu" => Some(AudioFormat::Pcmu),
+                "g711_alaw" | "audio/pcma" => Some(AudioFormat::Pcma),
+                _ => None,
+            },
+        }
+    }
+}
+
 /// Manual (de)serialization preserves unknown variants as raw JSON while keeping
 /// strong typing for known parts.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ContentPart {
     InputText {
         text: String,
@@ -445,6 +542,81 @@ pub enum ContentPart {
     Unknown(ArbitraryJson),
 }
 
+impl ContentPart {
+    /// Base64 audio carried by this part, if any.
+    #[must_use]
+    pub fn audio(&self) -> Option<&str> {
+        match self {
+            Self::InputAudio { audio, .. } => Some(audio),
+            Self::OutputAudio { audio, .. } | Self::Audio { audio, .. } => audio.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The GA equivalent of a beta `text`/`audio` part in a message from `role`:
+    /// `input_*` for user and system messages, `output_*` for the assistant.
+    /// Other parts are returned unchanged.
+    ///
+    /// `input_audio` requires audio, so a transcript-only part gets an empty string.
+    #[must_use]
+    pub fn into_ga(self, role: Role) -> Self {
+        let output = role == Role::Assistant;
+        match self {
+            Self::Text { text } if output => Self::OutputText { text },
+            Self::Text { text } => Self::InputText { text },
+            Self::Audio {
+                audio,
+                transcript,
+                format,
+            } => {
+                let format = format.as_ref().and_then(AudioPartFormat::to_format);
+                if output {
+                    Self::OutputAudio {
+                        audio,
+                        transcript,
+                        format,
+                    }
+                } else {
+                    Self::InputAudio {
+                        audio: audio.unwrap_or_default(),
+                        transcript,
+                        format,
+                    }
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// The beta `text`/`audio` equivalent of a GA text or audio part. Other parts are
+    /// returned unchanged; an empty `input_audio` payload becomes no audio.
+    #[must_use]
+    pub fn into_beta(self) -> Self {
+        match self {
+            Self::InputText { text } | Self::OutputText { text } => Self::Text { text },
+            Self::InputAudio {
+                audio,
+                transcript,
+                format,
+            } => Self::Audio {
+                audio: (!audio.is_empty()).then_some(audio),
+                transcript,
+                format: format.map(AudioPartFormat::Config),
+            },
+            Self::OutputAudio {
+                audio,
+                transcript,
+                format,
+            } => Self::Audio {
+                audio,
+                transcript,
+                format: format.map(AudioPartFormat::Config),
+            },
+            other => other,
+        }
+    }
+}
+
 impl std::fmt::Display for ContentPart {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let label = match self {
@@ -461,7 +633,7 @@ impl std::fmt::Display for ContentPart {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum ContentPartRepr {
     #[serde(rename = "input_text")]
@@ -3,6 +3,8 @@ pub mod common;
 pub mod items;
 pub mod response;
 pub mod session;
+#[cfg(test)]
+mod strict;
 pub mod tools;
 pub mod usage;
 
@@ -11,23 +13,25 @@ pub use audio::{
     NoiseReductionType, OutputAudioConfig, TurnDetection,
 };
 pub use common::{
-    ArbitraryJson, DEFAULT_MODEL, Eagerness, Infinite, ItemStatus, JsonSchema, MaxTokens, Metadata,
-    Modality, Nullable, OutputModalities, PromptRef, Role, Temperature, TemperatureError, Voice,
+    ArbitraryJson, DEFAULT_MODEL, Eagerness, Include, Infinite, ItemStatus, JsonSchema, MaxTokens,
+    Metadata, Modality, Nullable, OutputModalities, PromptRef, Role, Temperature, TemperatureError,
+    Voice,
 };
 pub use items::{AudioPartFormat, ContentPart, Item};
 pub use response::{
-    ConversationMode, InputItem, Response, ResponseConfig, ResponseStatus, ResponseStatusDetails,
+    ConversationMode, FunctionCallRef, InputItem, McpCallRef, Response, ResponseConfig,
+    ResponseStatus, ResponseStatusDetails,
 };
 pub use session::{
     RetentionRatioTruncation, Session, SessionConfig, SessionKind, SessionUpdate,
-    SessionUpdateConfig, TokenLimits, Tracing, TracingAuto, TracingConfig, Truncation,
+    SessionUpdateConfig, TokenLimits, TraceInfo, Tracing, TracingAuto, TracingConfig, Truncation,
     TruncationStrategy, TruncationType,
 };
 pub use tools::{
     ApprovalFilter, ApprovalMode, McpError, McpToolConfig, McpToolInfo, RequireApproval, Tool,
     ToolChoice, ToolChoiceMode,
 };
-pub use usage::{CachedTokenDetails, InputTokenDetails, OutputTokenDetails, Usage};
+pub use usage::{CachedTokenDetails, InputTokenDetails, OutputTokenDetails, Usage, UsageKind};
 
 #[cfg(test)]
 mod tests {
@@ -41,4 +45,190 @@ mod tests {
         let deserialized: MaxTokens = serde_json::from_str(&serialized).unwrap();
         assert!(matches!(deserialized, MaxTokens::Infinite(Infinite::Inf)));
     }
+
+    #[test]
+    fn strict_fixtures_cover_every_field() {
+        use crate::protocol::server_events::ServerEvent;
+        use serde_json::json;
+        use strict::Strict;
+
+        fn strict<T: serde::de::DeserializeOwned + serde::Serialize>(
+            value: serde_json::Value,
+        ) -> serde_json::Result<T> {
+            serde_json::from_value::<Strict<T>>(value).map(|Strict(parsed)| parsed)
+        }
+
+        let item = json!({
+            "type": "message",
+            "id": "item_1",
+            "status": "completed",
+            "role": "assistant",
+            "content": [{ "type": "output_audio", "transcript": "Hi there." }],
+        });
+        let expected_item = Item::Message {
+            id: Some("item_1".to_string()),
+            status: Some(ItemStatus::Completed),
+            role: Role::Assistant,
+            content: vec![ContentPart::OutputAudio {
+                audio: None,
+                transcript: Some("Hi there.".to_string()),
+                format: None,
+            }],
+        };
+        assert_eq!(strict::<Item>(item.clone()).unwrap(), expected_item);
+
+        let done = json!({
+            "type": "response.done",
+            "event_id": "evt_1",
+            "response": {
+                "object": "realtime.response",
+                "id": "resp_1",
+                "conversation_id": "conv_1",
+                "status": "completed",
+                "status_details": null,
+                "output": [item],
+                "output_modalities": ["audio"],
+                "max_output_tokens": "inf",
+                "metadata": null,
+                "usage": null,
+            },
+        });
+        let ServerEvent::ResponseDone { response, .. } =
+            strict::<ServerEvent>(done.clone()).unwrap()
+        else {
+            panic!("expected response.done");
+        };
+        assert_eq!(response.output, Some(vec![expected_item]));
+        assert_eq!(
+            response.max_output_tokens,
+            Some(MaxTokens::Infinite(Infinite::Inf))
+        );
+
+        let mut extra = done;
+        extra["response"]["unmodeled"] = json!(true);
+        assert!(strict::<ServerEvent>(extra).is_err());
+        let mut extra = json!({ "type": "function_call_output", "call_id": "c", "output": "" });
+        assert!(strict::<Item>(extra.clone()).is_ok());
+        extra["unmodeled"] = json!(1);
+        assert!(strict::<Item>(extra).is_err());
+    }
+
+    #[test]
+    fn session_updates_apply_onto_configs() {
+        let mut config = SessionConfig::new(
+            SessionKind::Realtime,
+            "gpt-realtime",
+            OutputModalities::Audio,
+        );
+        config.instructions = Some("Be brief.".to_string());
+        config.audio = Some(AudioConfig {
+            input: Some(InputAudioConfig {
+                format: Some(AudioFormat::Pcmu),
+                ..InputAudioConfig::default()
+            }),
+            output: None,
+        });
+        let mut mirror = SessionConfig::new(
+            SessionKind::Realtime,
+            "gpt-realtime",
+            OutputModalities::Text,
+        );
+        config.to_update().apply_to(&mut mirror);
+        assert_eq!(mirror, config);
+
+        let update = SessionUpdateConfig {
+            audio: Some(AudioConfig {
+                input: None,
+                output: Some(OutputAudioConfig {
+                    speed: Some(1.2),
+                    ..OutputAudioConfig::default()
+                }),
+            }),
+            ..SessionUpdateConfig::default()
+        };
+        update.apply_to(&mut mirror);
+        let audio = mirror.audio.expect("audio");
+        assert_eq!(
+            audio.input.and_then(|input| input.format),
+            Some(AudioFormat::Pcmu)
+        );
+        assert_eq!(audio.output.and_then(|output| output.speed), Some(1.2));
+        assert_eq!(mirror.instructions.as_deref(), Some("Be brief."));
+    }
+
+    #[test]
+    fn beta_parts_normalize_to_ga_by_role_and_back() {
+        let audio = |format: &str| ContentPart::Audio {
+            audio: None,
+            transcript: Some("hello".to_string()),
+            format: Some(AudioPartFormat::Label(format.to_string())),
+        };
+        let message = |role, content| Item::Message {
+            id: Some("item_1".to_string()),
+            status: None,
+            role,
+            content,
+        };
+
+        let user = message(
+            Role::User,
+            vec![
+                ContentPart::Text {
+                    text: "hi".to_string(),
+                },
+                audio("g711_ulaw"),
+            ],
+        );
+        let Item::Message { content, .. } = user.into_ga() else {
+            panic!("expected a message");
+        };
+        assert_eq!(
+            content,
+            vec![
+                ContentPart::InputText {
+                    text: "hi".to_string()
+                },
+                ContentPart::InputAudio {
+                    audio: String::new(),
+                    transcript: Some("hello".to_string()),
+                    format: Some(AudioFormat::Pcmu),
+                },
+            ]
+        );
+
+        let assistant = message(Role::Assistant, vec![audio("pcm16")]).into_ga();
+        let Item::Message { content, .. } = &assistant else {
+            panic!("expected a message");
+        };
+        assert_eq!(
+            content[0],
+            ContentPart::OutputAudio {
+                audio: None,
+                transcript: Some("hello".to_string()),
+                format: Some(AudioFormat::pcm_24khz()),
+            }
+        );
+
+        let Item::Message { content, .. } = assistant.into_beta() else {
+            panic!("expected a message");
+        };
+        assert_eq!(
+            content[0],
+            ContentPart::Audio {
+                audio: None,
+                transcript: Some("hello".to_string()),
+                format: Some(AudioPartFormat::Config(AudioFormat::pcm_24khz())),
+            }
+        );
+        assert_eq!(
+            ContentPart::OutputText {
+                text: "ok".to_string()
+            }
+            .into_beta()
+            .into_ga(Role::Assistant),
+            ContentPart::OutputText {
+                text: "ok".to_string()
+            }
+        );
+    }
 }
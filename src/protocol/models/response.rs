@@ -1,7 +1,10 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use super::common::open_enum;
 use super::{
-    AudioConfig, Item, MaxTokens, Metadata, OutputModalities, Temperature, Tool, ToolChoice, Voice,
+    AudioConfig, ContentPart, Include, Item, MaxTokens, McpError, Metadata, OutputModalities, Role,
+    Temperature, Tool, ToolChoice, Tracing, Voice,
 };
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -12,7 +15,7 @@ pub enum ConversationMode {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ResponseConfig {
     pub conversation: Option<ConversationMode>,
     /// Free-form metadata for the response.
@@ -28,9 +31,15 @@ pub struct ResponseConfig {
     pub max_output_tokens: Option<MaxTokens>,
     pub tools: Option<Vec<Tool>>,
     pub tool_choice: Option<ToolChoice>,
+    /// Overrides the session's tracing for this response. Omitted when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracing: Option<Tracing>,
+    /// Extra data for this response's events. Omitted when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<Include>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum InputItem {
     ItemReference {
@@ -43,17 +52,18 @@ pub enum InputItem {
     },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub enum ResponseStatus {
-    InProgress,
-    Completed,
-    Cancelled,
-    Failed,
-    Incomplete,
+open_enum! {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum ResponseStatus {
+        InProgress => "in_progress",
+        Completed => "completed",
+        Cancelled => "cancelled",
+        Failed => "failed",
+        Incomplete => "incomplete",
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Response {
     pub id: String,
     pub object: String,
@@ -69,6 +79,130 @@ pub struct Response {
     pub usage: Option<super::Usage>,
 }
 
+impl Response {
+    /// Text of the assistant's text parts, concatenated; `None` if there are none.
+    #[must_use]
+    pub fn text(&self) -> Option<String> {
+        self.collect_parts(|part| match part {
+            ContentPart::OutputText { text } | ContentPart::Text { text } => Some(text),
+            _ => None,
+        })
+    }
+
+    /// Transcripts of the assistant's audio parts, concatenated; `None` if there are
+    /// none.
+    #[must_use]
+    pub fn audio_transcript(&self) -> Option<String> {
+        self.collect_parts(|part| match part {
+            ContentPart::OutputAudio { transcript, .. } | ContentPart::Audio { transcript, .. } => {
+                transcript.as_deref()
+            }
+            _ => None,
+        })
+    }
+
+    /// Function calls in output order.
+    pub fn function_calls(&self) -> impl Iterator<Item = FunctionCallRef<'_>> {
+        self.items().filter_map(|item| match item {
+            Item::FunctionCall {
+                id,
+                name,
+                call_id,
+                arguments,
+                ..
+            } => Some(FunctionCallRef {
+                item_id: id.as_deref(),
+                call_id,
+                name,
+                arguments,
+            }),
+            _ => None,
+        })
+    }
+
+    /// MCP tool calls in output order.
+    pub fn mcp_calls(&self) -> impl Iterator<Item = McpCallRef<'_>> {
+        self.items().filter_map(|item| match item {
+            Item::McpCall {
+                id,
+                call_id,
+                server_label,
+                name,
+                arguments,
+                output,
+                error,
+                ..
+            } => Some(McpCallRef {
+                item_id: id.as_deref(),
+                call_id,
+                server_label,
+                name,
+                arguments,
+                output: output.as_deref(),
+                error: error.as_ref(),
+            }),
+            _ => None,
+        })
+    }
+
+    fn items(&self) -> impl Iterator<Item = &Item> {
+        self.output.iter().flatten()
+    }
+
+    fn collect_parts<'a>(
+        &'a self,
+        pick: impl Fn(&'a ContentPart) -> Option<&'a str>,
+    ) -> Option<String> {
+        let mut parts = self
+            .items()
+            .filter_map(|item| match item {
+                Item::Message {
+                    role: Role::Assistant,
+                    content,
+                    ..
+                } => Some(content),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(pick)
+            .peekable();
+        parts.peek()?;
+        Some(parts.collect())
+    }
+}
+
+/// A function call in a [`Response`]'s output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionCallRef<'a> {
+    pub item_id: Option<&'a str>,
+    pub call_id: &'a str,
+    pub name: &'a str,
+    /// Arguments as the model wrote them, a JSON string.
+    pub arguments: &'a str,
+}
+
+impl FunctionCallRef<'_> {
+    /// Deserialize the arguments.
+    ///
+    /// # Errors
+    /// Returns an error if the arguments are not valid JSON for `T`.
+    pub fn parse_arguments<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_str(self.arguments)
+    }
+}
+
+/// An MCP tool call in a [`Response`]'s output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McpCallRef<'a> {
+    pub item_id: Option<&'a str>,
+    pub call_id: &'a str,
+    pub server_label: &'a str,
+    pub name: &'a str,
+    pub arguments: &'a str,
+    pub output: Option<&'a str>,
+    pub error: Option<&'a McpError>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ResponseStatusDetails {
     pub reason: Option<String>,
@@ -2,7 +2,7 @@ use serde::ser::SerializeMap;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    AudioConfig, AudioFormat, InputAudioTranscription, MaxTokens, Modality, Nullable,
+    AudioConfig, AudioFormat, Include, InputAudioTranscription, MaxTokens, Modality, Nullable,
     OutputModalities, PromptRef, Temperature, Tool, ToolChoice, TurnDetection, Voice,
 };
 
@@ -20,7 +20,7 @@ pub enum TracingAuto {
     Auto,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TracingConfig {
     pub workflow_name: Option<String>,
     pub group_id: Option<String>,
@@ -28,13 +28,46 @@ pub struct TracingConfig {
     pub metadata: Option<super::Metadata>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Tracing {
     Auto(TracingAuto),
     Config(TracingConfig),
 }
 
+impl Tracing {
+    /// Trace with the server's default workflow name and grouping.
+    #[must_use]
+    pub const fn auto() -> Self {
+        Self::Auto(TracingAuto::Auto)
+    }
+
+    /// Trace under `workflow_name`, optionally grouped with other traces by `group_id`.
+    #[must_use]
+    pub fn workflow(
+        workflow_name: impl Into<String>,
+        group_id: Option<String>,
+        metadata: Option<super::Metadata>,
+    ) -> Self {
+        Self::Config(TracingConfig {
+            workflow_name: Some(workflow_name.into()),
+            group_id,
+            metadata,
+        })
+    }
+}
+
+/// What the server reports about where a session's traces are recorded, for linking
+/// a session to the traces dashboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceInfo {
+    pub session_id: String,
+    /// `None` when tracing is `auto` or the server left it unset.
+    pub workflow_name: Option<String>,
+    pub group_id: Option<String>,
+    pub metadata: Option<super::Metadata>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum TruncationStrategy {
@@ -68,14 +101,14 @@ pub enum Truncation {
     RetentionRatio(RetentionRatioTruncation),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SessionConfig {
     #[serde(rename = "type")]
     pub kind: SessionKind,
     pub model: String,
     pub output_modalities: OutputModalities,
     pub modalities: Option<Vec<Modality>>,
-    pub include: Option<Vec<String>>,
+    pub include: Option<Vec<Include>>,
     pub prompt: Option<PromptRef>,
     pub truncation: Option<Truncation>,
     pub instructions: Option<String>,
@@ -121,16 +154,42 @@ impl SessionConfig {
             voice: None,
         }
     }
+
+    /// A `session.update` that sets every field of this config, e.g. to send a
+    /// locally built config as the initial update.
+    #[must_use]
+    pub fn to_update(&self) -> SessionUpdateConfig {
+        SessionUpdateConfig {
+            kind: Some(self.kind),
+            output_modalities: Some(self.output_modalities),
+            modalities: self.modalities.clone(),
+            include: self.include.clone(),
+            prompt: self.prompt.clone(),
+            truncation: self.truncation.clone(),
+            instructions: self.instructions.clone(),
+            input_audio_format: self.input_audio_format.clone(),
+            output_audio_format: self.output_audio_format.clone(),
+            input_audio_transcription: self.input_audio_transcription.clone(),
+            turn_detection: self.turn_detection.clone(),
+            tools: self.tools.clone(),
+            tool_choice: self.tool_choice.clone(),
+            temperature: self.temperature,
+            max_output_tokens: self.max_output_tokens.clone(),
+            audio: self.audio.clone(),
+            tracing: self.tracing.clone(),
+            extra: serde_json::Map::new(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct SessionUpdateConfig {
     /// Partial updates only; GA forbids changing `model` or session `type`.
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub kind: Option<SessionKind>,
     pub output_modalities: Option<OutputModalities>,
     pub modalities: Option<Vec<Modality>>,
-    pub include: Option<Vec<String>>,
+    pub include: Option<Vec<Include>>,
     pub prompt: Option<PromptRef>,
     pub truncation: Option<Truncation>,
     pub instructions: Option<String>,
@@ -144,9 +203,102 @@ pub struct SessionUpdateConfig {
     pub max_output_tokens: Option<MaxTokens>,
     pub audio: Option<AudioConfig>,
     pub tracing: Option<Tracing>,
+    /// Fields not modeled above, sent as-is after the typed fields. A key that is also a
+    /// typed field is sent twice, and which copy the server keeps is unspecified; set the
+    /// typed field instead.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SessionUpdateConfig {
+    /// Write the fields this update sets into `config`, leaving the others as they
+    /// are. `audio` is merged field by field, as the server does. Fields in `extra`
+    /// are not modeled and are skipped.
+    pub fn apply_to(&self, config: &mut SessionConfig) {
+        fn set<T: Clone>(field: &mut Option<T>, update: Option<&T>) {
+            if let Some(value) = update {
+                *field = Some(value.clone());
+            }
+        }
+        if let Some(kind) = self.kind {
+            config.kind = kind;
+        }
+        if let Some(output_modalities) = self.output_modalities {
+            config.output_modalities = output_modalities;
+        }
+        set(&mut config.modalities, self.modalities.as_ref());
+        set(&mut config.include, self.include.as_ref());
+        set(&mut config.prompt, self.prompt.as_ref());
+        set(&mut config.truncation, self.truncation.as_ref());
+        set(&mut config.instructions, self.instructions.as_ref());
+        set(
+            &mut config.input_audio_format,
+            self.input_audio_format.as_ref(),
+        );
+        set(
+            &mut config.output_audio_format,
+            self.output_audio_format.as_ref(),
+        );
+        set(
+            &mut config.input_audio_transcription,
+            self.input_audio_transcription.as_ref(),
+        );
+        set(&mut config.turn_detection, self.turn_detection.as_ref());
+        set(&mut config.tools, self.tools.as_ref());
+        set(&mut config.tool_choice, self.tool_choice.as_ref());
+        set(&mut config.temperature, self.temperature.as_ref());
+        set(
+            &mut config.max_output_tokens,
+            self.max_output_tokens.as_ref(),
+        );
+        set(&mut config.tracing, self.tracing.as_ref());
+        if let Some(update) = &self.audio {
+            let audio = config.audio.get_or_insert_with(AudioConfig::default);
+            if let Some(update) = &update.input {
+                let input = audio.input.get_or_insert_with(Default::default);
+                set(&mut input.format, update.format.as_ref());
+                set(&mut input.turn_detection, update.turn_detection.as_ref());
+                set(&mut input.transcription, update.transcription.as_ref());
+                set(&mut input.noise_reduction, update.noise_reduction.as_ref());
+            }
+            if let Some(update) = &update.output {
+                let output = audio.output.get_or_insert_with(Default::default);
+                set(&mut output.format, update.format.as_ref());
+                set(&mut output.voice, update.voice.as_ref());
+                set(&mut output.speed, update.speed.as_ref());
+            }
+        }
+    }
+
+    /// Reject fields GA sessions fix at creation, which can only reach an update
+    /// through `extra`, e.g. when a full session JSON is sent back as a patch.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidClientEvent` naming the first such field.
+    pub fn validate_immutable_fields(&self) -> Result<(), crate::error::Error> {
+        let Some(key) = self.extra.keys().find(|key| {
+            matches!(
+                key.as_str(),
+                "model" | "voice" | "id" | "object" | "expires_at"
+            )
+        }) else {
+            return Ok(());
+        };
+        let reason = match key.as_str() {
+            "model" => "it is fixed when the session is created",
+            "voice" => {
+                "it is fixed when the session is created; set `audio.output.voice` \
+                 before the model first outputs audio instead"
+            }
+            _ => "it is assigned by the server",
+        };
+        Err(crate::error::Error::InvalidClientEvent(format!(
+            "session.update cannot change `{key}`: {reason}"
+        )))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub object: String,
@@ -156,7 +308,24 @@ pub struct Session {
     pub config: SessionConfig,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+impl Session {
+    /// Trace identifiers from the session payload, or `None` if tracing is off.
+    #[must_use]
+    pub fn trace_info(&self) -> Option<TraceInfo> {
+        let config = match self.config.tracing.as_ref()? {
+            Tracing::Auto(_) => None,
+            Tracing::Config(config) => Some(config),
+        };
+        Some(TraceInfo {
+            session_id: self.id.clone(),
+            workflow_name: config.and_then(|c| c.workflow_name.clone()),
+            group_id: config.and_then(|c| c.group_id.clone()),
+            metadata: config.and_then(|c| c.metadata.clone()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
 pub struct SessionUpdate {
     /// Flattened to match the API's session.update JSON shape.
     #[serde(flatten)]
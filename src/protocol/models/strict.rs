@@ -0,0 +1,50 @@
+//! Parsing test fixtures strictly, so they fail on fields the models drop.
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// `T` parsed from JSON that it models completely: every non-null field of the input
+/// must come back when `T` is serialized again.
+#[derive(Debug)]
+pub struct Strict<T>(pub T);
+
+impl<'de, T: DeserializeOwned + Serialize> Deserialize<'de> for Strict<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let input = Value::deserialize(deserializer)?;
+        let parsed: T = serde_json::from_value(input.clone()).map_err(D::Error::custom)?;
+        let kept = serde_json::to_value(&parsed).map_err(D::Error::custom)?;
+        if let Some(path) = dropped(&input, &kept) {
+            return Err(D::Error::custom(format!("`{path}` is not modeled")));
+        }
+        Ok(Self(parsed))
+    }
+}
+
+/// The path of the first non-null field in `input` missing from `kept`.
+fn dropped(input: &Value, kept: &Value) -> Option<String> {
+    match (input, kept) {
+        (Value::Object(input), Value::Object(kept)) => input
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .find_map(|(key, value)| {
+                kept.get(key).map_or_else(
+                    || Some(key.clone()),
+                    |kept| dropped(value, kept).map(|path| format!("{key}.{path}")),
+                )
+            }),
+        (Value::Array(input), Value::Array(kept)) => {
+            input
+                .iter()
+                .zip(kept)
+                .enumerate()
+                .find_map(|(index, (value, kept))| {
+                    dropped(value, kept).map(|path| format!("{index}.{path}"))
+                })
+        }
+        _ => None,
+    }
+}
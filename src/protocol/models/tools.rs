@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::common::open_enum;
 use super::{ArbitraryJson, JsonSchema};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Tool {
     #[serde(rename = "function")]
@@ -13,12 +14,16 @@ pub enum Tool {
         description: Option<String>,
         /// JSON Schema for tool parameters (intentionally untyped).
         parameters: JsonSchema,
+        /// Whether the model must follow `parameters` exactly; they then need the
+        /// strict shape.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        strict: Option<bool>,
     },
     #[serde(rename = "mcp")]
     Mcp(McpToolConfig),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct McpToolConfig {
     pub server_label: String,
     pub server_url: Option<String>,
@@ -34,7 +39,6 @@ pub struct McpToolConfig {
 impl McpToolConfig {
     /// # Errors
     /// Returns an error if neither `server_url` nor `connector_id` is provided.
-    #[allow(clippy::result_large_err)]
     pub fn validate(&self) -> Result<(), crate::error::Error> {
         if self.server_url.is_none() && self.connector_id.is_none() {
             return Err(crate::error::Error::InvalidClientEvent(
@@ -65,15 +69,16 @@ pub enum RequireApproval {
     Filter(ApprovalFilter),
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub enum ToolChoiceMode {
-    Auto,
-    None,
-    Required,
+open_enum! {
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum ToolChoiceMode {
+        Auto => "auto",
+        None => "none",
+        Required => "required",
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ToolChoice {
     Mode(ToolChoiceMode),
@@ -85,7 +90,29 @@ pub enum ToolChoice {
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ToolChoice {
+    /// Force a call to the named function tool.
+    #[must_use]
+    pub fn function(name: impl Into<String>) -> Self {
+        Self::Specific {
+            kind: "function".to_string(),
+            name: Some(name.into()),
+            server_label: None,
+        }
+    }
+
+    /// Force a call to a specific tool on an MCP server.
+    #[must_use]
+    pub fn mcp(server_label: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::Specific {
+            kind: "mcp".to_string(),
+            name: Some(name.into()),
+            server_label: Some(server_label.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct McpToolInfo {
     pub name: String,
     pub description: Option<String>,
@@ -95,20 +122,32 @@ pub struct McpToolInfo {
     pub annotations: Option<ArbitraryJson>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
+/// Why an `mcp_call` failed, as reported in its `error` field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum McpError {
-    Protocol {
-        code: i32,
-        message: String,
-    },
-    ToolExecution {
-        message: String,
-    },
-    Http {
-        code: i32,
-        message: String,
-    },
+    /// The MCP server answered with a JSON-RPC error.
+    #[serde(rename = "protocol_error")]
+    Protocol { code: i32, message: String },
+    /// The tool ran and reported an error.
+    #[serde(rename = "tool_execution_error")]
+    ToolExecution { message: String },
+    /// The MCP server could not be reached or answered with an HTTP error status.
+    #[serde(rename = "http_error")]
+    Http { code: i32, message: String },
     #[serde(other)]
     Unknown,
 }
+
+impl McpError {
+    /// The error message, if the variant carries one.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Protocol { message, .. }
+            | Self::ToolExecution { message }
+            | Self::Http { message, .. } => Some(message),
+            Self::Unknown => None,
+        }
+    }
+}
@@ -1,17 +1,39 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Usage of a response or a transcription.
+///
+/// Response usage and token-billed transcription report token counts; duration-billed
+/// transcription (`"type": "duration"`) reports only `seconds`, leaving the counts at zero.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Usage {
+    /// Billing basis of transcription usage; absent on response usage.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<UsageKind>,
+    #[serde(default)]
     pub total_tokens: u32,
+    #[serde(default)]
     pub input_tokens: u32,
+    #[serde(default)]
     pub output_tokens: u32,
     pub input_token_details: Option<InputTokenDetails>,
     pub output_token_details: Option<OutputTokenDetails>,
     pub cached_tokens: Option<u32>,
     pub cached_tokens_details: Option<CachedTokenDetails>,
+    /// Seconds of audio billed, for duration-based usage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    Tokens,
+    Duration,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InputTokenDetails {
     pub cached_tokens: Option<u32>,
     pub text_tokens: Option<u32>,
@@ -20,13 +42,13 @@ pub struct InputTokenDetails {
     pub cached_tokens_details: Option<CachedTokenDetails>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OutputTokenDetails {
     pub text_tokens: Option<u32>,
     pub audio_tokens: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CachedTokenDetails {
     pub text_tokens: Option<u32>,
     pub audio_tokens: Option<u32>,
@@ -3,7 +3,7 @@ use crate::error::ServerError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ServerEvent {
     Error {
         event_id: String,
@@ -91,6 +91,8 @@ pub enum ServerEvent {
         item_id: String,
         content_index: u32,
         delta: String,
+        /// Random padding the server adds to even out event sizes; `delta` is the
+        /// real text.
         obfuscation: Option<Value>,
         logprobs: Option<Value>,
     },
@@ -242,6 +244,8 @@ pub enum ServerEvent {
         item_id: String,
         output_index: u32,
         delta: String,
+        /// Random padding the server adds to even out event sizes; `delta` is the
+        /// real text.
         obfuscation: Option<Value>,
     },
     ResponseMcpCallArgumentsDone {
@@ -273,7 +277,7 @@ pub enum ServerEvent {
     Unknown(ArbitraryJson),
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(tag = "type")]
 enum ServerEventRepr {
     #[serde(rename = "error")]
@@ -1507,6 +1511,86 @@ impl<'de> Deserialize<'de> for ServerEvent {
 }
 
 impl ServerEvent {
+    /// Wire name of the event (the `type` field); empty for an unknown event without
+    /// one.
+    #[must_use]
+    pub fn event_type(&self) -> &str {
+        match self {
+            Self::Error { .. } => "error",
+            Self::SessionCreated { .. } => "session.created",
+            Self::SessionUpdated { .. } => "session.updated",
+            Self::ConversationItemCreated { .. } => "conversation.item.created",
+            Self::ConversationItemAdded { .. } => "conversation.item.added",
+            Self::ConversationItemDone { .. } => "conversation.item.done",
+            Self::ConversationItemRetrieved { .. } => "conversation.item.retrieved",
+            Self::ConversationItemDeleted { .. } => "conversation.item.deleted",
+            Self::ConversationItemTruncated { .. } => "conversation.item.truncated",
+            Self::InputAudioBufferCommitted { .. } => "input_audio_buffer.committed",
+            Self::InputAudioBufferCleared { .. } => "input_audio_buffer.cleared",
+            Self::InputAudioBufferSpeechStarted { .. } => "input_audio_buffer.speech_started",
+            Self::InputAudioBufferSpeechStopped { .. } => "input_audio_buffer.speech_stopped",
+            Self::InputAudioBufferTimeoutTriggered { .. } => "input_audio_buffer.timeout_triggered",
+            Self::DtmfEventReceived { .. } => "input_audio_buffer.dtmf_event_received",
+            Self::OutputAudioBufferStarted { .. } => "output_audio_buffer.started",
+            Self::OutputAudioBufferStopped { .. } => "output_audio_buffer.stopped",
+            Self::OutputAudioBufferCleared { .. } => "output_audio_buffer.cleared",
+            Self::InputAudioTranscriptionDelta { .. } => {
+                "conversation.item.input_audio_transcription.delta"
+            }
+            Self::InputAudioTranscriptionSegment { .. } => {
+                "conversation.item.input_audio_transcription.segment"
+            }
+            Self::InputAudioTranscriptionFailed { .. } => {
+                "conversation.item.input_audio_transcription.failed"
+            }
+            Self::InputAudioTranscriptionCompleted { .. } => {
+                "conversation.item.input_audio_transcription.completed"
+            }
+            Self::McpListToolsInProgress { .. } => "mcp_list_tools.in_progress",
+            Self::McpListToolsCompleted { .. } => "mcp_list_tools.completed",
+            Self::McpListToolsFailed { .. } => "mcp_list_tools.failed",
+            Self::ResponseCreated { .. } => "response.created",
+            Self::ResponseDone { .. } => "response.done",
+            Self::ResponseCancelled { .. } => "response.cancelled",
+            Self::ResponseOutputItemAdded { .. } => "response.output_item.added",
+            Self::ResponseOutputItemDone { .. } => "response.output_item.done",
+            Self::ResponseContentPartAdded { .. } => "response.content_part.added",
+            Self::ResponseContentPartDone { .. } => "response.content_part.done",
+            Self::ResponseOutputTextDelta { .. } => "response.output_text.delta",
+            Self::ResponseOutputTextDone { .. } => "response.output_text.done",
+            Self::ResponseOutputAudioDelta { .. } => "response.output_audio.delta",
+            Self::ResponseOutputAudioDone { .. } => "response.output_audio.done",
+            Self::ResponseOutputAudioTranscriptDelta { .. } => {
+                "response.output_audio_transcript.delta"
+            }
+            Self::ResponseOutputAudioTranscriptDone { .. } => {
+                "response.output_audio_transcript.done"
+            }
+            Self::ResponseFunctionCallArgumentsDelta { .. } => {
+                "response.function_call_arguments.delta"
+            }
+            Self::ResponseFunctionCallArgumentsDone { .. } => {
+                "response.function_call_arguments.done"
+            }
+            Self::ResponseMcpCallArgumentsDelta { .. } => "response.mcp_call_arguments.delta",
+            Self::ResponseMcpCallArgumentsDone { .. } => "response.mcp_call_arguments.done",
+            Self::ResponseMcpCallInProgress { .. } => "response.mcp_call.in_progress",
+            Self::ResponseMcpCallCompleted { .. } => "response.mcp_call.completed",
+            Self::ResponseMcpCallFailed { .. } => "response.mcp_call.failed",
+            Self::RateLimitsUpdated { .. } => "rate_limits.updated",
+            Self::Unknown(value) => value
+                .get("type")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether the event is an incremental delta, such as `response.output_text.delta`.
+    #[must_use]
+    pub fn is_delta(&self) -> bool {
+        self.event_type().rsplit('.').next() == Some("delta")
+    }
+
     #[must_use]
     pub fn event_id(&self) -> Option<&str> {
         macro_rules! extract {
@@ -1568,7 +1652,7 @@ impl ServerEvent {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct RateLimit {
     pub name: String,
     pub limit: u32,
@@ -0,0 +1,155 @@
+//! Agents: instructions, tools and guardrails applied to a session as one unit.
+
+use crate::Result;
+use crate::protocol::models::{
+    MaxTokens, RequireApproval, SessionUpdate, SessionUpdateConfig, Temperature, Tool, ToolChoice,
+};
+use std::sync::Arc;
+
+use super::guardrails::{Guardrail, Guardrails};
+use super::session::Session;
+use super::tools::{ToolDispatcher, ToolRegistry};
+
+/// A persona the session can switch to mid-call, e.g. for a handoff from triage to billing.
+///
+/// Attaching an agent sends its instructions, tools and response defaults in one
+/// `session.update`; its tool calls and guardrails take effect immediately.
+#[derive(Clone)]
+pub struct Agent {
+    name: String,
+    instructions: String,
+    dispatcher: Arc<dyn ToolDispatcher>,
+    approval: Option<RequireApproval>,
+    guardrails: Guardrails,
+    tool_choice: Option<ToolChoice>,
+    temperature: Option<Temperature>,
+    max_output_tokens: Option<MaxTokens>,
+}
+
+impl Agent {
+    #[must_use]
+    pub fn new(name: impl Into<String>, instructions: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            instructions: instructions.into(),
+            dispatcher: Arc::new(ToolRegistry::new()),
+            approval: None,
+            guardrails: Guardrails::new(),
+            tool_choice: None,
+            temperature: None,
+            max_output_tokens: None,
+        }
+    }
+
+    #[must_use]
+    pub fn tools(self, registry: ToolRegistry) -> Self {
+        self.dispatcher(Arc::new(registry))
+    }
+
+    #[must_use]
+    pub fn dispatcher(mut self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
+        self.dispatcher = dispatcher;
+        self
+    }
+
+    /// Approval policy applied to every MCP server among the agent's tools.
+    #[must_use]
+    pub fn approval(mut self, approval: RequireApproval) -> Self {
+        self.approval = Some(approval);
+        self
+    }
+
+    /// Add a guardrail that runs after the session's own while this agent is active.
+    #[must_use]
+    pub fn guardrail(mut self, guardrail: impl Guardrail + 'static) -> Self {
+        self.guardrails.push(guardrail);
+        self
+    }
+
+    #[must_use]
+    pub fn tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
+    #[must_use]
+    pub const fn temperature(mut self, temperature: Temperature) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_output_tokens(mut self, max: MaxTokens) -> Self {
+        self.max_output_tokens = Some(max);
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn instructions(&self) -> &str {
+        &self.instructions
+    }
+
+    #[must_use]
+    pub const fn guardrails(&self) -> &Guardrails {
+        &self.guardrails
+    }
+
+    #[must_use]
+    pub fn tool_dispatcher(&self) -> Arc<dyn ToolDispatcher> {
+        Arc::clone(&self.dispatcher)
+    }
+
+    /// Configure `session` with this agent; same as `session.switch_agent(self)`.
+    ///
+    /// # Errors
+    /// Returns an error if tool schemas fail to serialize or the update fails.
+    pub async fn attach(&self, session: &Session) -> Result<()> {
+        session.switch_agent(self).await
+    }
+
+    /// The `session.update` that applies this agent. Tools are always sent, so an
+    /// agent without tools clears those of the previous one.
+    ///
+    /// # Errors
+    /// Returns an error if tool schemas fail to serialize.
+    pub fn session_update(&self) -> Result<SessionUpdate> {
+        let mut tools = self.dispatcher.try_tool_definitions()?;
+        if let Some(approval) = &self.approval {
+            for tool in &mut tools {
+                if let Tool::Mcp(config) = tool {
+                    config.require_approval = Some(approval.clone());
+                }
+            }
+        }
+        Ok(SessionUpdate {
+            config: SessionUpdateConfig {
+                instructions: Some(self.instructions.clone()),
+                tools: Some(tools),
+                tool_choice: self.tool_choice.clone(),
+                temperature: self.temperature,
+                max_output_tokens: self.max_output_tokens.clone(),
+                ..SessionUpdateConfig::default()
+            },
+        })
+    }
+}
+
+impl std::fmt::Debug for Agent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Agent")
+            .field("name", &self.name)
+            .field("instructions", &self.instructions)
+            .field("guardrails", &self.guardrails)
+            .finish_non_exhaustive()
+    }
+}
+
+/// System note added to the conversation when one agent hands off to another.
+pub fn handoff_note(from: &str, to: &str) -> String {
+    format!("Conversation handed off from agent `{from}` to agent `{to}`.")
+}
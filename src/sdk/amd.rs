@@ -0,0 +1,252 @@
+//! Answering machine detection (AMD) for outbound calls: is a person or a voicemail
+//! greeting on the line?
+
+use super::codec::AudioCodec;
+use super::voice::VoiceEvent;
+use crate::protocol::server_events::ServerEvent;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long inbound audio is analyzed by default.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Who answered the call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmdVerdict {
+    Human,
+    Machine,
+    /// The detection window passed without a decision.
+    Undetermined,
+}
+
+/// What a detector sees of the start of the call.
+#[derive(Debug, Clone, Copy)]
+pub enum AmdInput<'a> {
+    /// Inbound audio appended by this session, decoded from the input format to PCM16
+    /// samples at `sample_rate`. Calls whose audio goes straight to the server, such as
+    /// SIP, only see the other inputs.
+    Audio {
+        samples: &'a [i16],
+        sample_rate: u32,
+    },
+    /// Server VAD heard the caller start speaking, `audio_ms` into the input audio.
+    SpeechStarted { audio_ms: u32 },
+    /// Server VAD heard the caller stop speaking.
+    SpeechStopped { audio_ms: u32 },
+    /// A transcript of one caller utterance.
+    Transcript(&'a str),
+}
+
+/// Classifies the first seconds of a call as a person or an answering machine.
+pub trait AnsweringMachineDetector: Send + Sync {
+    /// Take in one input and return a verdict once it is clear.
+    fn observe(&mut self, input: AmdInput<'_>) -> Option<AmdVerdict>;
+
+    /// The verdict when the detection window ends without one.
+    fn finish(&mut self) -> AmdVerdict {
+        AmdVerdict::Undetermined
+    }
+}
+
+/// A detector and how much inbound audio it may analyze, for
+/// `RealtimeBuilder::answering_machine_detection`.
+pub struct AnsweringMachineDetection {
+    detector: Box<dyn AnsweringMachineDetector>,
+    window: Duration,
+}
+
+impl AnsweringMachineDetection {
+    /// Run `detector` over the first 10 seconds of inbound audio.
+    #[must_use]
+    pub fn new(detector: impl AnsweringMachineDetector + 'static) -> Self {
+        Self {
+            detector: Box::new(detector),
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Give up after `window` of inbound audio, or of time when no audio arrives,
+    /// asking the detector to `finish`.
+    #[must_use]
+    pub const fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+impl std::fmt::Debug for AnsweringMachineDetection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnsweringMachineDetection")
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Feeds a detector from the event loop until it reaches a verdict.
+#[derive(Debug, Default)]
+pub struct AmdMonitor {
+    /// `None` when detection is off or already decided.
+    detection: Option<AnsweringMachineDetection>,
+    /// Inbound audio seen so far, from appends or server VAD timestamps.
+    audio_ms: u64,
+    /// When the window ends even if no audio arrived, e.g. on a silent line.
+    due: Option<Instant>,
+}
+
+impl AmdMonitor {
+    /// A monitor whose window starts at `now`.
+    pub fn new(detection: Option<AnsweringMachineDetection>, now: Instant) -> Self {
+        let due = detection.as_ref().map(|detection| now + detection.window);
+        Self {
+            detection,
+            audio_ms: 0,
+            due,
+        }
+    }
+
+    /// When the window ends by time, while detection is still running.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.detection.as_ref().and(self.due)
+    }
+
+    /// Detection is still running and wants the input audio.
+    pub const fn is_detecting(&self) -> bool {
+        self.detection.is_some()
+    }
+
+    /// The window ended by time: ask the detector for its final verdict.
+    pub fn elapse(&mut self) -> Option<VoiceEvent> {
+        self.due = None;
+        let mut detection = self.detection.take()?;
+        Some(VoiceEvent::AnsweringMachineDetected {
+            verdict: detection.detector.finish(),
+            audio_ms: u32::try_from(self.audio_ms).unwrap_or(u32::MAX),
+        })
+    }
+
+    /// Feed appended audio, already base64-decoded, decoded with `codec`, the input
+    /// format's codec; audio in a format without one is skipped.
+    pub fn observe_input(
+        &mut self,
+        encoded: &[u8],
+        codec: Option<&dyn AudioCodec>,
+    ) -> Option<VoiceEvent> {
+        self.detection.as_ref()?;
+        let codec = codec?;
+        let samples = codec.decode(encoded).ok()?;
+        let sample_rate = codec.sample_rate();
+        self.audio_ms += samples.len() as u64 * 1000 / u64::from(sample_rate.max(1));
+        self.feed(AmdInput::Audio {
+            samples: &samples,
+            sample_rate,
+        })
+    }
+
+    pub fn observe_server(&mut self, evt: &ServerEvent) -> Option<VoiceEvent> {
+        self.detection.as_ref()?;
+        let input = match evt {
+            ServerEvent::InputAudioBufferSpeechStarted { audio_start_ms, .. } => {
+                AmdInput::SpeechStarted {
+                    audio_ms: *audio_start_ms,
+                }
+            }
+            ServerEvent::InputAudioBufferSpeechStopped { audio_end_ms, .. } => {
+                AmdInput::SpeechStopped {
+                    audio_ms: *audio_end_ms,
+                }
+            }
+            ServerEvent::InputAudioTranscriptionCompleted { transcript, .. } => {
+                AmdInput::Transcript(transcript)
+            }
+            _ => return None,
+        };
+        if let AmdInput::SpeechStarted { audio_ms } | AmdInput::SpeechStopped { audio_ms } = input {
+            self.audio_ms = self.audio_ms.max(u64::from(audio_ms));
+        }
+        self.feed(input)
+    }
+
+    fn feed(&mut self, input: AmdInput<'_>) -> Option<VoiceEvent> {
+        let detection = self.detection.as_mut()?;
+        let verdict = detection.detector.observe(input).or_else(|| {
+            let window = u64::try_from(detection.window.as_millis()).unwrap_or(u64::MAX);
+            (self.audio_ms >= window).then(|| detection.detector.finish())
+        })?;
+        self.detection = None;
+        Some(VoiceEvent::AnsweringMachineDetected {
+            verdict,
+            audio_ms: u32::try_from(self.audio_ms).unwrap_or(u32::MAX),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::codec::{G711Codec, Pcm16Codec};
+
+    /// Calls a machine after any transcript, and never decides on audio alone.
+    struct Transcripts;
+
+    impl AnsweringMachineDetector for Transcripts {
+        fn observe(&mut self, input: AmdInput<'_>) -> Option<AmdVerdict> {
+            matches!(input, AmdInput::Transcript(_)).then_some(AmdVerdict::Machine)
+        }
+    }
+
+    /// `ms` of audio in a format of `bytes_per_ms`.
+    fn append(ms: usize, bytes_per_ms: usize) -> Vec<u8> {
+        vec![0_u8; ms * bytes_per_ms]
+    }
+
+    #[test]
+    fn window_ends_detection_once() {
+        let pcm16 = Pcm16Codec::default();
+        let codec = Some(&pcm16 as &dyn AudioCodec);
+        let detection =
+            AnsweringMachineDetection::new(Transcripts).window(Duration::from_millis(200));
+        let mut monitor = AmdMonitor::new(Some(detection), Instant::now());
+        assert!(monitor.observe_input(&append(100, 48), codec).is_none());
+        assert!(matches!(
+            monitor.observe_input(&append(100, 48), codec),
+            Some(VoiceEvent::AnsweringMachineDetected {
+                verdict: AmdVerdict::Undetermined,
+                audio_ms: 200,
+            })
+        ));
+        assert!(monitor.observe_input(&append(100, 48), codec).is_none());
+    }
+
+    #[test]
+    fn g711_audio_is_timed_at_its_own_rate() {
+        let mu_law = G711Codec::mu_law();
+        let detection =
+            AnsweringMachineDetection::new(Transcripts).window(Duration::from_millis(200));
+        let mut monitor = AmdMonitor::new(Some(detection), Instant::now());
+        let codec = Some(&mu_law as &dyn AudioCodec);
+        assert!(monitor.observe_input(&append(100, 8), codec).is_none());
+        assert!(matches!(
+            monitor.observe_input(&append(100, 8), codec),
+            Some(VoiceEvent::AnsweringMachineDetected { audio_ms: 200, .. })
+        ));
+    }
+
+    #[test]
+    fn silent_line_ends_detection_at_the_deadline() {
+        let start = Instant::now();
+        let detection = AnsweringMachineDetection::new(Transcripts).window(Duration::from_secs(5));
+        let mut monitor = AmdMonitor::new(Some(detection), start);
+        assert_eq!(monitor.deadline(), Some(start + Duration::from_secs(5)));
+        assert!(matches!(
+            monitor.elapse(),
+            Some(VoiceEvent::AnsweringMachineDetected {
+                verdict: AmdVerdict::Undetermined,
+                audio_ms: 0,
+            })
+        ));
+        assert_eq!(monitor.deadline(), None);
+        assert!(monitor.elapse().is_none());
+    }
+}
@@ -0,0 +1,225 @@
+//! Reference answering machine detector built on greeting length, voicemail phrases
+//! and the beep that ends a voicemail greeting.
+
+use super::amd::{AmdInput, AmdVerdict, AnsweringMachineDetector};
+
+/// Frames analyzed per second: 20 ms each.
+const FRAMES_PER_SECOND: u32 = 50;
+/// Frames of steady tone that make a beep.
+const BEEP_FRAMES: u32 = 8;
+/// Quietest frame RMS that can be part of a beep.
+const BEEP_MIN_RMS: f64 = 1500.0;
+
+const MACHINE_PHRASES: &[&str] = &[
+    "leave a message",
+    "leave your message",
+    "record your message",
+    "after the tone",
+    "after the beep",
+    "not available",
+    "voicemail",
+    "voice mail",
+    "take your call",
+];
+
+/// Tells people from answering machines with simple heuristics:
+///
+/// - a first utterance longer than `max_human_greeting_ms` is a recorded greeting,
+///   a shorter one ("Hello?") is a person;
+/// - a transcript with a typical voicemail phrase is a machine;
+/// - a steady tone between 400 and 2500 Hz lasting 160 ms is a voicemail beep.
+#[derive(Debug, Clone)]
+pub struct HeuristicAmd {
+    max_human_greeting_ms: u32,
+    phrases: Vec<String>,
+    speech_started_ms: Option<u32>,
+    /// Inbound audio analyzed so far.
+    audio_ms: u32,
+    frame: Vec<i16>,
+    tone_frames: u32,
+    last_crossings: usize,
+}
+
+impl Default for HeuristicAmd {
+    fn default() -> Self {
+        Self {
+            max_human_greeting_ms: 2500,
+            phrases: MACHINE_PHRASES.iter().map(ToString::to_string).collect(),
+            speech_started_ms: None,
+            audio_ms: 0,
+            frame: Vec::new(),
+            tone_frames: 0,
+            last_crossings: 0,
+        }
+    }
+}
+
+impl HeuristicAmd {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Longest first utterance still taken for a person. Defaults to 2.5 seconds.
+    #[must_use]
+    pub const fn max_human_greeting_ms(mut self, ms: u32) -> Self {
+        self.max_human_greeting_ms = ms;
+        self
+    }
+
+    /// Another phrase that marks a voicemail greeting. Case-insensitive.
+    #[must_use]
+    pub fn machine_phrase(mut self, phrase: impl Into<String>) -> Self {
+        self.phrases.push(phrase.into().to_lowercase());
+        self
+    }
+
+    fn observe_audio(&mut self, samples: &[i16], sample_rate: u32) -> Option<AmdVerdict> {
+        let frame_samples = (sample_rate / FRAMES_PER_SECOND).max(1) as usize;
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() < frame_samples {
+                continue;
+            }
+            self.audio_ms += 20;
+            let frame = std::mem::take(&mut self.frame);
+            if self.observe_frame(&frame) {
+                return Some(AmdVerdict::Machine);
+            }
+            if let Some(started) = self.speech_started_ms {
+                if self.audio_ms.saturating_sub(started) > self.max_human_greeting_ms {
+                    return Some(AmdVerdict::Machine);
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether `frame` completes a beep.
+    fn observe_frame(&mut self, frame: &[i16]) -> bool {
+        #[allow(clippy::cast_precision_loss)]
+        let rms = (frame
+            .iter()
+            .map(|&s| f64::from(s) * f64::from(s))
+            .sum::<f64>()
+            / frame.len() as f64)
+            .sqrt();
+        let crossings = frame
+            .windows(2)
+            .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+            .count();
+        // Two crossings per cycle over 20 ms.
+        let hz = crossings * 25;
+        let steady = self.last_crossings.abs_diff(crossings) * 10 <= crossings;
+        self.last_crossings = crossings;
+        if rms >= BEEP_MIN_RMS && (400..=2500).contains(&hz) && steady {
+            self.tone_frames += 1;
+        } else {
+            self.tone_frames = 0;
+        }
+        self.tone_frames >= BEEP_FRAMES
+    }
+}
+
+impl AnsweringMachineDetector for HeuristicAmd {
+    fn observe(&mut self, input: AmdInput<'_>) -> Option<AmdVerdict> {
+        match input {
+            AmdInput::Audio {
+                samples,
+                sample_rate,
+            } => self.observe_audio(samples, sample_rate),
+            AmdInput::SpeechStarted { audio_ms } => {
+                self.speech_started_ms.get_or_insert(audio_ms);
+                None
+            }
+            AmdInput::SpeechStopped { audio_ms } => {
+                let started = self.speech_started_ms?;
+                Some(
+                    if audio_ms.saturating_sub(started) > self.max_human_greeting_ms {
+                        AmdVerdict::Machine
+                    } else {
+                        AmdVerdict::Human
+                    },
+                )
+            }
+            AmdInput::Transcript(text) => {
+                let text = text.to_lowercase();
+                self.phrases
+                    .iter()
+                    .any(|phrase| text.contains(phrase.as_str()))
+                    .then_some(AmdVerdict::Machine)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greeting_length_and_phrases_decide() {
+        let mut short = HeuristicAmd::new();
+        assert_eq!(
+            short.observe(AmdInput::SpeechStarted { audio_ms: 300 }),
+            None
+        );
+        assert_eq!(
+            short.observe(AmdInput::SpeechStopped { audio_ms: 1100 }),
+            Some(AmdVerdict::Human)
+        );
+
+        let mut long = HeuristicAmd::new();
+        long.observe(AmdInput::SpeechStarted { audio_ms: 200 });
+        assert_eq!(
+            long.observe(AmdInput::SpeechStopped { audio_ms: 5200 }),
+            Some(AmdVerdict::Machine)
+        );
+
+        assert_eq!(
+            HeuristicAmd::new().observe(AmdInput::Transcript(
+                "Hi, you've reached Sam. Please leave a message."
+            )),
+            Some(AmdVerdict::Machine)
+        );
+        assert_eq!(
+            HeuristicAmd::new().observe(AmdInput::Transcript("Hello, who is this?")),
+            None
+        );
+    }
+
+    #[test]
+    fn a_steady_tone_is_a_beep_but_noise_is_not() {
+        #[allow(clippy::cast_possible_truncation)]
+        let tone: Vec<i16> = (0..24_000 / 4)
+            .map(|n| {
+                let t = f64::from(n) / 24_000.0;
+                (8000.0 * (2.0 * std::f64::consts::PI * 1000.0 * t).sin()) as i16
+            })
+            .collect();
+        let mut detector = HeuristicAmd::new();
+        assert_eq!(
+            detector.observe(AmdInput::Audio {
+                samples: &tone,
+                sample_rate: 24_000
+            }),
+            Some(AmdVerdict::Machine)
+        );
+
+        let mut seed = 1_u32;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let noise: Vec<i16> = (0..24_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as i16
+            })
+            .collect();
+        assert_eq!(
+            HeuristicAmd::new().observe(AmdInput::Audio {
+                samples: &noise,
+                sample_rate: 24_000
+            }),
+            None
+        );
+    }
+}
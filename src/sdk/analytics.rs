@@ -0,0 +1,212 @@
+//! Time-sliced conversation analytics derived from voice events.
+
+use crate::protocol::server_events::ServerEvent;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// PCM16 mono @ 24kHz.
+const PCM_BYTES_PER_MS: u64 = 48;
+
+/// Summary of one analytics window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationAnalytics {
+    pub window: Duration,
+    /// User speech time, from server VAD speech start/stop.
+    pub user_talk_ms: u64,
+    /// Assistant audio time, from the length of output audio deltas.
+    pub assistant_talk_ms: u64,
+    /// User speech that started while a response was active.
+    pub interruptions: u32,
+    /// Average time from end of user speech to the first response output.
+    pub average_response_latency_ms: Option<u64>,
+}
+
+impl ConversationAnalytics {
+    /// Share of talk time taken by the user, in `0.0..=1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn user_talk_ratio(&self) -> Option<f64> {
+        let total = self.user_talk_ms + self.assistant_talk_ms;
+        (total > 0).then(|| self.user_talk_ms as f64 / total as f64)
+    }
+}
+
+/// Accumulates analytics between window snapshots.
+#[derive(Debug)]
+pub struct AnalyticsTracker {
+    window_start: Instant,
+    speech_started: Option<Instant>,
+    awaiting_response: Option<Instant>,
+    responding: bool,
+    user_talk: Duration,
+    assistant_talk_ms: u64,
+    interruptions: u32,
+    latency_total: Duration,
+    latency_count: u32,
+}
+
+impl AnalyticsTracker {
+    #[must_use]
+    pub const fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            speech_started: None,
+            awaiting_response: None,
+            responding: false,
+            user_talk: Duration::ZERO,
+            assistant_talk_ms: 0,
+            interruptions: 0,
+            latency_total: Duration::ZERO,
+            latency_count: 0,
+        }
+    }
+
+    /// Tracker plus the ticker that closes its windows, when analytics are enabled.
+    #[must_use]
+    pub fn with_interval(
+        interval: Option<Duration>,
+        now: Instant,
+    ) -> (Option<Self>, Option<tokio::time::Interval>) {
+        let Some(period) = interval else {
+            return (None, None);
+        };
+        let mut ticker = tokio::time::interval_at(now + period, period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        (Some(Self::new(now)), Some(ticker))
+    }
+
+    pub fn observe(&mut self, evt: &ServerEvent, now: Instant) {
+        match evt {
+            ServerEvent::InputAudioBufferSpeechStarted { .. } => {
+                if self.responding {
+                    self.interruptions += 1;
+                }
+                self.speech_started = Some(now);
+                self.awaiting_response = None;
+            }
+            ServerEvent::InputAudioBufferSpeechStopped { .. } => {
+                if let Some(started) = self.speech_started.take() {
+                    self.user_talk += now.saturating_duration_since(started);
+                }
+                self.awaiting_response = Some(now);
+            }
+            ServerEvent::ResponseCreated { .. } => self.responding = true,
+            ServerEvent::ResponseDone { .. } | ServerEvent::ResponseCancelled { .. } => {
+                self.responding = false;
+            }
+            ServerEvent::ResponseOutputAudioDelta { delta, .. } => {
+                self.assistant_talk_ms += decoded_len(delta) / PCM_BYTES_PER_MS;
+                self.record_latency(now);
+            }
+            ServerEvent::ResponseOutputTextDelta { .. }
+            | ServerEvent::ResponseOutputAudioTranscriptDelta { .. } => self.record_latency(now),
+            _ => {}
+        }
+    }
+
+    /// Close the current window and start a new one at `now`.
+    pub fn snapshot(&mut self, now: Instant) -> ConversationAnalytics {
+        // Speech that spans the boundary is split between windows.
+        if let Some(started) = self.speech_started.as_mut() {
+            self.user_talk += now.saturating_duration_since(*started);
+            *started = now;
+        }
+
+        let average_response_latency_ms =
+            (self.latency_count > 0).then(|| duration_ms(self.latency_total / self.latency_count));
+        let report = ConversationAnalytics {
+            window: now.saturating_duration_since(self.window_start),
+            user_talk_ms: duration_ms(self.user_talk),
+            assistant_talk_ms: self.assistant_talk_ms,
+            interruptions: self.interruptions,
+            average_response_latency_ms,
+        };
+
+        self.window_start = now;
+        self.user_talk = Duration::ZERO;
+        self.assistant_talk_ms = 0;
+        self.interruptions = 0;
+        self.latency_total = Duration::ZERO;
+        self.latency_count = 0;
+        report
+    }
+
+    fn record_latency(&mut self, now: Instant) {
+        if let Some(stopped) = self.awaiting_response.take() {
+            self.latency_total += now.saturating_duration_since(stopped);
+            self.latency_count += 1;
+        }
+    }
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Decoded byte length of a base64 payload without decoding it.
+fn decoded_len(encoded: &str) -> u64 {
+    let padding = encoded.bytes().rev().take_while(|b| *b == b'=').count();
+    ((encoded.len() / 4) * 3).saturating_sub(padding) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine as _;
+
+    fn speech_started() -> ServerEvent {
+        ServerEvent::InputAudioBufferSpeechStarted {
+            event_id: "evt".to_string(),
+            audio_start_ms: 0,
+            item_id: "item_user".to_string(),
+        }
+    }
+
+    fn speech_stopped() -> ServerEvent {
+        ServerEvent::InputAudioBufferSpeechStopped {
+            event_id: "evt".to_string(),
+            audio_end_ms: 0,
+            item_id: "item_user".to_string(),
+        }
+    }
+
+    #[test]
+    fn snapshot_summarizes_window() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut tracker = AnalyticsTracker::new(start);
+
+        tracker.observe(&speech_started(), at(0));
+        tracker.observe(&speech_stopped(), at(1_000));
+
+        // 500ms of PCM16 @ 24kHz.
+        let audio = base64::engine::general_purpose::STANDARD.encode(vec![0u8; 24_000]);
+        tracker.observe(
+            &ServerEvent::ResponseOutputAudioDelta {
+                event_id: "evt".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                delta: audio,
+            },
+            at(1_300),
+        );
+        tracker.responding = true;
+        tracker.observe(&speech_started(), at(1_500));
+
+        let report = tracker.snapshot(at(2_000));
+        assert_eq!(report.window, Duration::from_secs(2));
+        assert_eq!(report.user_talk_ms, 1_500);
+        assert_eq!(report.assistant_talk_ms, 500);
+        assert_eq!(report.interruptions, 1);
+        assert_eq!(report.average_response_latency_ms, Some(300));
+        assert_eq!(report.user_talk_ratio(), Some(0.75));
+
+        let next = tracker.snapshot(at(2_100));
+        assert_eq!(next.user_talk_ms, 100);
+        assert_eq!(next.interruptions, 0);
+        assert_eq!(next.average_response_latency_ms, None);
+    }
+}
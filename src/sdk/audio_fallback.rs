@@ -0,0 +1,144 @@
+//! Falling back to text output when the model's audio keeps failing to decode.
+
+use super::codec::AudioCodec;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{
+    OutputModalities, SessionConfig, SessionUpdate, SessionUpdateConfig,
+};
+use base64::Engine as _;
+use base64::engine::general_purpose;
+
+/// What the session does when output audio cannot be decoded. Every failure is
+/// reported as `VoiceEvent::DecodeError`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioFailurePolicy {
+    /// Only report the failure.
+    #[default]
+    Report,
+    /// After `after` failed audio deltas in a row, switch the session to text output
+    /// with a `session.update` and emit `SdkEvent::TextFallback`. Audio still in
+    /// flight is dropped from then on; the conversation carries on in text until the
+    /// session's output includes audio again. Deltas the session's codec rejects
+    /// count as failures too.
+    FallbackToText { after: u32 },
+}
+
+/// Consecutive output audio failures, for `AudioFailurePolicy::FallbackToText`.
+#[derive(Debug, Default)]
+pub struct AudioFailures {
+    consecutive: u32,
+    /// The session has fallen back to text; output audio is dropped.
+    text_only: bool,
+}
+
+impl AudioFailures {
+    pub const fn text_only(&self) -> bool {
+        self.text_only
+    }
+
+    /// Decode an output audio delta, counting it as a failure if it is not valid
+    /// base64 or, when `policy` falls back to text, `codec` rejects it. The bytes are
+    /// passed on as received.
+    pub fn decode(
+        &mut self,
+        delta: &str,
+        codec: Option<&dyn AudioCodec>,
+        policy: AudioFailurePolicy,
+    ) -> std::result::Result<Vec<u8>, String> {
+        let codec = codec.filter(|_| policy != AudioFailurePolicy::Report);
+        let decoded = match general_purpose::STANDARD.decode(delta.as_bytes()) {
+            Ok(encoded) => match codec.map(|codec| codec.decode(&encoded)) {
+                Some(Err(err)) => Err(err.to_string()),
+                _ => Ok(encoded),
+            },
+            Err(err) => Err(err.to_string()),
+        };
+        if decoded.is_ok() {
+            self.consecutive = 0;
+        } else {
+            self.consecutive = self.consecutive.saturating_add(1);
+        }
+        decoded
+    }
+
+    /// The failure count if `policy` says to fall back to text now. Falls back at most
+    /// once until audio output is restored.
+    pub fn fall_back(&mut self, policy: AudioFailurePolicy) -> Option<u32> {
+        let AudioFailurePolicy::FallbackToText { after } = policy else {
+            return None;
+        };
+        if self.text_only || self.consecutive < after.max(1) {
+            return None;
+        }
+        self.text_only = true;
+        Some(self.consecutive)
+    }
+
+    /// Play output audio again once `config`, as the server reported it, includes
+    /// audio output.
+    pub fn observe_session(&mut self, config: &SessionConfig) {
+        if self.text_only && config.output_modalities != OutputModalities::Text {
+            self.text_only = false;
+            self.consecutive = 0;
+        }
+    }
+}
+
+/// The `session.update` that switches the model to text output.
+pub fn text_output_update() -> ClientEvent {
+    ClientEvent::SessionUpdate {
+        event_id: None,
+        session: Box::new(SessionUpdate {
+            config: SessionUpdateConfig {
+                output_modalities: Some(OutputModalities::Text),
+                ..SessionUpdateConfig::default()
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::codec::Pcm16Codec;
+
+    #[test]
+    fn falls_back_once_after_consecutive_failures() {
+        let policy = AudioFailurePolicy::FallbackToText { after: 2 };
+        let mut failures = AudioFailures::default();
+
+        let pcm16 = Some(&Pcm16Codec::default() as &dyn AudioCodec);
+
+        assert!(failures.decode("!!", pcm16, policy).is_err());
+        assert_eq!(failures.fall_back(policy), None);
+        assert_eq!(failures.decode("AAA=", pcm16, policy).unwrap(), vec![0, 0]);
+        assert!(failures.decode("!!", pcm16, policy).is_err());
+        assert_eq!(failures.fall_back(policy), None);
+
+        // One byte is not a PCM16 sample, but passes in a format without a codec, and
+        // when failures are only reported.
+        assert_eq!(failures.decode("AA==", None, policy).unwrap(), vec![0]);
+        let report = AudioFailurePolicy::Report;
+        assert_eq!(failures.decode("AA==", pcm16, report).unwrap(), vec![0]);
+        assert!(failures.decode("!!", pcm16, policy).is_err());
+        assert!(failures.decode("AA==", pcm16, policy).is_err());
+        assert_eq!(failures.fall_back(policy), Some(2));
+        assert!(failures.text_only());
+        assert!(failures.decode("!!", pcm16, policy).is_err());
+        assert_eq!(failures.fall_back(policy), None);
+        assert_eq!(failures.fall_back(AudioFailurePolicy::Report), None);
+
+        let mut config = SessionConfig::new(
+            crate::protocol::models::SessionKind::Realtime,
+            "gpt-realtime",
+            OutputModalities::Text,
+        );
+        failures.observe_session(&config);
+        assert!(failures.text_only());
+        config.output_modalities = OutputModalities::Audio;
+        failures.observe_session(&config);
+        assert!(!failures.text_only());
+        assert!(failures.decode("!!", pcm16, policy).is_err());
+        assert_eq!(failures.fall_back(policy), None);
+    }
+}
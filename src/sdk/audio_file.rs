@@ -0,0 +1,155 @@
+//! Reading audio files for `Session::send_audio_file`.
+
+use crate::{Error, Result};
+use tokio::sync::mpsc;
+
+/// Bytes per `input_audio_buffer.append` when sending a file: well under the 15MB
+/// limit, and a whole number of PCM16 samples.
+pub const FILE_CHUNK_BYTES: usize = 1024 * 1024;
+
+const PCM_24KHZ_RATE: u32 = 24_000;
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// How far `Session::send_audio_file` has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioProgress {
+    /// Audio bytes appended so far.
+    pub sent: usize,
+    pub total: usize,
+}
+
+impl AudioProgress {
+    /// Share of the file sent, from 0.0 to 100.0.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.sent as f64 * 100.0 / self.total as f64
+    }
+}
+
+/// Where `Session::send_audio_file` reports progress.
+#[derive(Debug, Clone, Default)]
+pub enum Progress {
+    #[default]
+    None,
+    /// An `AudioProgress` after each chunk. A closed receiver does not stop the send.
+    Channel(mpsc::UnboundedSender<AudioProgress>),
+}
+
+impl Progress {
+    pub fn report(&self, sent: usize, total: usize) {
+        if let Self::Channel(tx) = self {
+            let _ = tx.send(AudioProgress { sent, total });
+        }
+    }
+}
+
+/// The PCM16 bytes of a file: the `data` chunk of a WAV file, or the whole file if
+/// it has no RIFF header.
+///
+/// # Errors
+/// Returns an error for a WAV file that is not 16-bit mono PCM at 24kHz, or audio
+/// that ends partway through a sample.
+pub fn pcm16(bytes: &[u8]) -> Result<&[u8]> {
+    let pcm = if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WAVE") {
+        wav_data(bytes)?
+    } else {
+        bytes
+    };
+    if pcm.len() % 2 != 0 {
+        return Err(invalid(
+            "PCM16 audio must be a whole number of 2-byte samples",
+        ));
+    }
+    Ok(pcm)
+}
+
+fn wav_data(bytes: &[u8]) -> Result<&[u8]> {
+    let mut format_checked = false;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let id = &rest[..4];
+        let size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]);
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        let body = &rest[8..];
+        // Writers that stream a WAV may leave the size unset; take what is there.
+        let body = &body[..size.min(body.len())];
+        match id {
+            b"fmt " => {
+                check_format(body)?;
+                format_checked = true;
+            }
+            b"data" if format_checked => return Ok(body),
+            b"data" => return Err(invalid("WAV data chunk comes before its fmt chunk")),
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        let next = 8 + body.len() + body.len() % 2;
+        rest = rest.get(next..).unwrap_or_default();
+    }
+    Err(invalid("WAV file has no data chunk"))
+}
+
+fn check_format(fmt: &[u8]) -> Result<()> {
+    if fmt.len() < 16 {
+        return Err(invalid("WAV fmt chunk is too short"));
+    }
+    let tag = u16::from_le_bytes([fmt[0], fmt[1]]);
+    let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+    let rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+    let bits = u16::from_le_bytes([fmt[14], fmt[15]]);
+    if !matches!(tag, WAVE_FORMAT_PCM | WAVE_FORMAT_EXTENSIBLE) || bits != 16 {
+        return Err(invalid("WAV audio must be 16-bit PCM"));
+    }
+    if channels != 1 {
+        return Err(Error::InvalidClientEvent(format!(
+            "WAV audio must be mono, got {channels} channels"
+        )));
+    }
+    if rate != PCM_24KHZ_RATE {
+        return Err(Error::InvalidClientEvent(format!(
+            "WAV audio must be {PCM_24KHZ_RATE}Hz, got {rate}Hz"
+        )));
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidClientEvent(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(channels: u16, rate: u32, data: &[u8]) -> Vec<u8> {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        wav.extend_from_slice(b"fmt \x10\0\0\0");
+        wav.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&rate.to_le_bytes());
+        wav.extend_from_slice(&(rate * 2 * u32::from(channels)).to_le_bytes());
+        wav.extend_from_slice(&(2 * channels).to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        wav.extend_from_slice(data);
+        wav
+    }
+
+    #[test]
+    fn wav_files_yield_their_data_chunk() {
+        let file = wav(1, 24_000, &[1, 2, 3, 4]);
+        assert_eq!(pcm16(&file).unwrap(), &[1, 2, 3, 4]);
+        assert_eq!(pcm16(&[5, 6]).unwrap(), &[5, 6]);
+
+        assert!(pcm16(&wav(2, 24_000, &[0; 4])).is_err());
+        assert!(pcm16(&wav(1, 16_000, &[0; 4])).is_err());
+        assert!(pcm16(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Single-writer access to the input audio buffer.
+
+use super::session::SessionHandle;
+use crate::protocol::client_events::ClientEvent;
+use crate::{Error, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::watch;
+
+/// Who may append input audio, shared by a session and all of its handles.
+#[derive(Clone)]
+pub struct AudioLeases {
+    holder: Arc<watch::Sender<Option<u64>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Default for AudioLeases {
+    fn default() -> Self {
+        Self {
+            holder: Arc::new(watch::channel(None).0),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+}
+
+impl AudioLeases {
+    pub fn try_acquire(&self) -> Result<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let acquired = self.holder.send_if_modified(|holder| {
+            let free = holder.is_none();
+            if free {
+                *holder = Some(id);
+            }
+            free
+        });
+        if acquired {
+            Ok(id)
+        } else {
+            Err(Error::AudioInputLeased)
+        }
+    }
+
+    /// Wait until no lease is held, then take it.
+    pub async fn acquire(&self) -> u64 {
+        let mut rx = self.holder.subscribe();
+        loop {
+            if let Ok(id) = self.try_acquire() {
+                return id;
+            }
+            // The sender lives as long as `self`, so this only returns once released.
+            let _ = rx.wait_for(Option::is_none).await;
+        }
+    }
+
+    pub fn release(&self, id: u64) {
+        self.holder.send_if_modified(|holder| {
+            let held = *holder == Some(id);
+            if held {
+                *holder = None;
+            }
+            held
+        });
+    }
+
+    /// Reject an audio append from anyone but the lease holder while a lease is held.
+    /// `lease` is the sender's lease, if it has one.
+    pub fn check(&self, event: &ClientEvent, lease: Option<u64>) -> Result<()> {
+        let ClientEvent::InputAudioBufferAppend { .. } = event else {
+            return Ok(());
+        };
+        let holder = *self.holder.borrow();
+        match holder {
+            Some(holder) if Some(holder) != lease => Err(Error::AudioInputLeased),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The right to append input audio, from `SessionHandle::lease_audio_input`.
+///
+/// While a lease is held, appends through anything but the lease fail with
+/// `Error::AudioInputLeased`, so two sources cannot interleave their audio in the
+/// input buffer. Only appends are guarded; commits and clears go through as usual.
+/// Move the lease to another task to hand it over, or drop it (or call
+/// [`Self::release`]) so the next waiting writer can take it.
+pub struct AudioInputLease {
+    session: SessionHandle,
+    leases: AudioLeases,
+    id: u64,
+}
+
+impl AudioInputLease {
+    pub(super) const fn new(session: SessionHandle, leases: AudioLeases, id: u64) -> Self {
+        Self {
+            session,
+            leases,
+            id,
+        }
+    }
+
+    /// Append PCM16 samples to the input buffer.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails.
+    pub async fn append_pcm16(&self, samples: &[i16]) -> Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.append_bytes(&bytes).await
+    }
+
+    /// Append PCM16 bytes to the input buffer.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails.
+    pub async fn append_bytes(&self, bytes: &[u8]) -> Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let event = ClientEvent::InputAudioBufferAppend {
+            event_id: None,
+            audio: general_purpose::STANDARD.encode(bytes),
+        };
+        self.session.send_leased(event, self.id).await
+    }
+
+    /// Commit the input buffer.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails.
+    pub async fn commit(&self) -> Result<()> {
+        self.session
+            .send_raw(ClientEvent::InputAudioBufferCommit { event_id: None })
+            .await
+    }
+
+    /// Give up the lease so another writer can take it.
+    pub fn release(self) {
+        drop(self);
+    }
+}
+
+impl Drop for AudioInputLease {
+    fn drop(&mut self) {
+        self.leases.release(self.id);
+    }
+}
+
+impl std::fmt::Debug for AudioInputLease {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioInputLease")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
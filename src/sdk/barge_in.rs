@@ -0,0 +1,200 @@
+//! Deciding when user speech should interrupt the model.
+
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// When detected user speech interrupts the model, for sessions with auto barge-in.
+///
+/// The default interrupts on every `speech_started`. With a threshold set, the server's
+/// own `interrupt_response` is turned off so the SDK decides; conditions combine, so
+/// speech must meet every threshold set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BargeInPolicy {
+    /// Interrupt only once speech has lasted this long. Shorter bursts such as coughs
+    /// are ignored.
+    pub min_speech: Option<Duration>,
+    /// Interrupt only once the user's transcription arrives with at least this
+    /// confidence (0.0 to 1.0, the geometric mean of its token probabilities).
+    ///
+    /// Needs input transcription, and `item.input_audio_transcription.logprobs` in the
+    /// session's `include` for confidences; without them any non-empty transcript counts.
+    pub min_transcript_confidence: Option<f32>,
+    /// Stop playback but let the response keep generating.
+    pub clear_audio_only: bool,
+}
+
+impl BargeInPolicy {
+    /// Whether speech interrupts as soon as it starts.
+    #[must_use]
+    pub const fn is_immediate(&self) -> bool {
+        self.min_speech.is_none() && self.min_transcript_confidence.is_none()
+    }
+}
+
+/// Speech that may still interrupt once it meets the policy.
+#[derive(Debug)]
+struct Pending {
+    item_id: String,
+    /// The response that was active when speech started; a barge-in never touches
+    /// a later one.
+    response_id: Option<String>,
+    /// When `min_speech` is met; `None` once it has been.
+    due: Option<Instant>,
+}
+
+/// Tracks speech that has started but not yet met a [`BargeInPolicy`].
+#[derive(Debug, Default)]
+pub struct BargeInGate {
+    pending: Option<Pending>,
+}
+
+/// A barge-in the gate has cleared, aimed at `response_id`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Interrupt {
+    pub response_id: Option<String>,
+}
+
+impl BargeInGate {
+    /// Speech started while `response_id` was active.
+    pub fn speech_started(
+        &mut self,
+        policy: &BargeInPolicy,
+        item_id: &str,
+        response_id: Option<String>,
+        now: Instant,
+    ) -> Option<Interrupt> {
+        if policy.is_immediate() {
+            self.pending = None;
+            return Some(Interrupt { response_id });
+        }
+        self.pending = Some(Pending {
+            item_id: item_id.to_string(),
+            response_id,
+            due: policy.min_speech.map(|min| now + min),
+        });
+        None
+    }
+
+    /// Speech ended; it is dropped if it was too short.
+    pub fn speech_stopped(&mut self, item_id: &str) {
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|p| p.item_id == item_id && p.due.is_some())
+        {
+            self.pending = None;
+        }
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().and_then(|p| p.due)
+    }
+
+    /// The `min_speech` deadline passed with the user still speaking.
+    pub fn elapse(&mut self, policy: &BargeInPolicy) -> Option<Interrupt> {
+        let pending = self.pending.as_mut()?;
+        pending.due = None;
+        if policy.min_transcript_confidence.is_some() {
+            return None;
+        }
+        self.pending.take().map(|p| Interrupt {
+            response_id: p.response_id,
+        })
+    }
+
+    /// The transcription of `item_id` completed.
+    pub fn transcribed(
+        &mut self,
+        policy: &BargeInPolicy,
+        item_id: &str,
+        transcript: &str,
+        logprobs: Option<&Value>,
+    ) -> Option<Interrupt> {
+        let min = policy.min_transcript_confidence?;
+        if !self
+            .pending
+            .as_ref()
+            .is_some_and(|p| p.item_id == item_id && p.due.is_none())
+        {
+            return None;
+        }
+        let pending = self.pending.take()?;
+        let accepted = logprobs
+            .and_then(confidence)
+            .map_or_else(|| !transcript.trim().is_empty(), |c| c >= min);
+        accepted.then_some(Interrupt {
+            response_id: pending.response_id,
+        })
+    }
+}
+
+/// Geometric mean token probability of a transcription's `logprobs`.
+fn confidence(logprobs: &Value) -> Option<f32> {
+    let values: Vec<f64> = logprobs
+        .as_array()?
+        .iter()
+        .filter_map(|entry| entry.get("logprob").and_then(Value::as_f64))
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    #[allow(clippy::cast_possible_truncation)]
+    Some(mean.exp() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(min_speech_ms: Option<u64>, confidence: Option<f32>) -> BargeInPolicy {
+        BargeInPolicy {
+            min_speech: min_speech_ms.map(Duration::from_millis),
+            min_transcript_confidence: confidence,
+            clear_audio_only: false,
+        }
+    }
+
+    #[test]
+    fn short_speech_is_ignored() {
+        let policy = policy(Some(300), None);
+        let mut gate = BargeInGate::default();
+        assert!(
+            gate.speech_started(&policy, "item_1", Some("resp_1".into()), Instant::now())
+                .is_none()
+        );
+        gate.speech_stopped("item_1");
+        assert!(gate.deadline().is_none());
+        assert!(gate.elapse(&policy).is_none());
+    }
+
+    #[test]
+    fn sustained_speech_then_confident_transcript_interrupts() {
+        let policy = policy(Some(300), Some(0.5));
+        let mut gate = BargeInGate::default();
+        gate.speech_started(&policy, "item_1", Some("resp_1".into()), Instant::now());
+        assert!(gate.elapse(&policy).is_none());
+        gate.speech_stopped("item_1");
+
+        let unsure = json!([{ "token": "uh", "logprob": -2.0 }]);
+        let mut retry = BargeInGate::default();
+        retry.speech_started(&policy, "item_2", None, Instant::now());
+        retry.elapse(&policy);
+        assert!(
+            retry
+                .transcribed(&policy, "item_2", "uh", Some(&unsure))
+                .is_none()
+        );
+
+        let sure = json!([{ "token": "stop", "logprob": -0.1 }]);
+        assert_eq!(
+            gate.transcribed(&policy, "item_1", "stop", Some(&sure)),
+            Some(Interrupt {
+                response_id: Some("resp_1".into())
+            })
+        );
+    }
+}
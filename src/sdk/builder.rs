@@ -1,14 +1,33 @@
 use crate::protocol::models::{
-    AudioConfig, AudioFormat, InputAudioConfig, InputAudioTranscription, MaxTokens, NoiseReduction,
-    OutputAudioConfig, OutputModalities, SessionConfig, SessionKind, Temperature, ToolChoice,
-    TurnDetection,
+    AudioConfig, AudioFormat, InputAudioConfig, InputAudioTranscription, MaxTokens, Metadata,
+    NoiseReduction, OutputAudioConfig, OutputModalities, SessionConfig, SessionKind, Temperature,
+    ToolChoice, Tracing, TurnDetection,
 };
 use crate::{Error, Result};
 use std::sync::Arc;
+use std::time::Duration;
 
 use super::EventHandlers;
-use super::session::SessionConfigSnapshot;
-use super::tools::{ToolDispatcher, ToolRegistry};
+use super::amd::AnsweringMachineDetection;
+use super::audio_fallback::AudioFailurePolicy;
+use super::barge_in::BargeInPolicy;
+use super::channel::{ChannelCapacities, OverflowPolicy};
+use super::clock::{Clock, SharedClock};
+use super::codec::CodecRegistry;
+use super::event_log::{EventLog, RotationPolicy};
+use super::expiry::ExpiryPolicy;
+use super::guardrails::{Guardrail, Guardrails};
+use super::key_pool::KeyPool;
+use super::resend::ResendQueue;
+use super::session::{SessionConfigSnapshot, SessionOptions, SessionRole};
+use super::speakable::SpeakableChunking;
+use super::token_budget::TokenBudget;
+use super::tool_context::{CancellationToken, ToolContext};
+use super::tools::{
+    ToolConcurrency, ToolDefinition, ToolDispatcher, ToolErrorPolicy, ToolMiddleware, ToolRegistry,
+};
+use super::transcription::TranscriptionFailurePolicy;
+use super::voice::IdleTimeoutPolicy;
 
 pub struct Realtime;
 
@@ -29,7 +48,10 @@ impl Realtime {
 
 pub struct RealtimeBuilder {
     api_key: Option<String>,
+    key_pool: Option<KeyPool>,
+    base_url: Option<String>,
     model: Option<String>,
+    call_id: Option<String>,
     voice: Option<String>,
     session_kind: SessionKind,
     output_modalities: Option<OutputModalities>,
@@ -38,8 +60,37 @@ pub struct RealtimeBuilder {
     temperature: Option<Temperature>,
     max_output_tokens: Option<MaxTokens>,
     audio: Option<AudioConfig>,
+    tracing: Option<Tracing>,
     auto_barge_in: bool,
+    barge_in: BargeInPolicy,
+    respond_on_speech_stop: bool,
     auto_tool_response: bool,
+    tool_error_policy: ToolErrorPolicy,
+    tool_concurrency: ToolConcurrency,
+    idle_timeout: IdleTimeoutPolicy,
+    transcription_failure: TranscriptionFailurePolicy,
+    audio_failure: AudioFailurePolicy,
+    codecs: CodecRegistry,
+    clock: SharedClock,
+    expiry: ExpiryPolicy,
+    token_budget: Option<TokenBudget>,
+    cancellation: CancellationToken,
+    speakable: Option<SpeakableChunking>,
+    answering_machine: Option<AnsweringMachineDetection>,
+    guardrails: Guardrails,
+    analytics_interval: Option<Duration>,
+    slow_handler: Option<Duration>,
+    audio_levels: Option<Duration>,
+    resend: Option<ResendQueue>,
+    channel_capacities: ChannelCapacities,
+    overflow_policy: OverflowPolicy,
+    role: SessionRole,
+    ready_timeout: Option<Duration>,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    max_event_bytes: Option<usize>,
+    event_log: Option<EventLog>,
+    trace: crate::TraceConfig,
     handlers: EventHandlers,
     tools: ToolRegistry,
     dispatcher: Option<Arc<dyn ToolDispatcher>>,
@@ -50,7 +101,10 @@ impl RealtimeBuilder {
     pub fn new() -> Self {
         Self {
             api_key: None,
+            key_pool: None,
+            base_url: None,
             model: None,
+            call_id: None,
             voice: None,
             session_kind: SessionKind::Realtime,
             output_modalities: None,
@@ -59,8 +113,37 @@ impl RealtimeBuilder {
             temperature: None,
             max_output_tokens: None,
             audio: None,
+            tracing: None,
             auto_barge_in: false,
+            barge_in: BargeInPolicy::default(),
+            respond_on_speech_stop: false,
             auto_tool_response: true,
+            tool_error_policy: ToolErrorPolicy::default(),
+            tool_concurrency: ToolConcurrency::default(),
+            idle_timeout: IdleTimeoutPolicy::default(),
+            transcription_failure: TranscriptionFailurePolicy::default(),
+            audio_failure: AudioFailurePolicy::default(),
+            codecs: CodecRegistry::new(),
+            clock: SharedClock::default(),
+            expiry: ExpiryPolicy::default(),
+            token_budget: None,
+            cancellation: CancellationToken::new(),
+            speakable: None,
+            answering_machine: None,
+            guardrails: Guardrails::new(),
+            analytics_interval: None,
+            slow_handler: None,
+            audio_levels: None,
+            resend: None,
+            channel_capacities: ChannelCapacities::default(),
+            overflow_policy: OverflowPolicy::Block,
+            role: SessionRole::Controller,
+            ready_timeout: None,
+            connect_timeout: crate::transport::ws::DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: super::session::DEFAULT_REQUEST_TIMEOUT,
+            max_event_bytes: None,
+            event_log: None,
+            trace: crate::TraceConfig::default(),
             handlers: EventHandlers::new(),
             tools: ToolRegistry::new(),
             dispatcher: None,
@@ -73,12 +156,45 @@ impl RealtimeBuilder {
         self
     }
 
+    /// Take the API key from `pool` instead, spreading sessions across its keys. The
+    /// session reports connect failures, dropped connections and rate-limit pressure
+    /// back to the pool.
+    #[must_use]
+    pub fn key_pool(mut self, pool: KeyPool) -> Self {
+        self.key_pool = Some(pool);
+        self
+    }
+
     #[must_use]
     pub fn model(mut self, model: impl Into<String>) -> Self {
         self.model = Some(model.into());
         self
     }
 
+    /// Attach to an existing call (e.g. SIP) instead of starting a new session.
+    #[must_use]
+    pub fn call_id(mut self, call_id: impl Into<String>) -> Self {
+        self.call_id = Some(call_id.into());
+        self
+    }
+
+    /// Listen in on the call set with [`call_id`](Self::call_id) without controlling it.
+    ///
+    /// The session skips its initial `session.update`, leaves tool calls to the
+    /// controlling session and rejects every send with `Error::ReadOnly`.
+    #[must_use]
+    pub const fn observer(mut self) -> Self {
+        self.role = SessionRole::Observer;
+        self
+    }
+
+    /// Connect to another WebSocket endpoint, e.g. a proxy or a local mock server.
+    #[must_use]
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
     #[must_use]
     pub fn voice(mut self, voice: impl Into<String>) -> Self {
         let voice = voice.into();
@@ -103,6 +219,69 @@ impl RealtimeBuilder {
         self
     }
 
+    /// Playback speed of the model's voice, from 0.25 to 1.5 (1.0 is normal).
+    ///
+    /// # Errors
+    /// Returns an error if `speed` is out of range.
+    pub fn speaking_speed(mut self, speed: f32) -> Result<Self> {
+        OutputAudioConfig::validate_speed(speed)?;
+        let audio = self.audio.get_or_insert_with(AudioConfig::default);
+        audio
+            .output
+            .get_or_insert_with(OutputAudioConfig::default)
+            .speed = Some(speed);
+        Ok(self)
+    }
+
+    /// Format of the audio sent to the model, for sessions not built with
+    /// [`voice_session`](Self::voice_session).
+    #[must_use]
+    pub fn input_audio(mut self, format: AudioFormat) -> Self {
+        self.input_audio_config().format = Some(format);
+        self
+    }
+
+    /// Transcribe input audio with `model`, reported as transcription events.
+    #[must_use]
+    pub fn input_transcription(mut self, model: impl Into<String>) -> Self {
+        self.input_audio_config().transcription = Some(crate::protocol::models::Nullable::Value(
+            InputAudioTranscription {
+                model: Some(model.into()),
+                language: None,
+                prompt: None,
+            },
+        ));
+        self
+    }
+
+    /// Format, voice and playback speed of the model's audio in one call. This only
+    /// configures the audio; [`output_audio`](Self::output_audio) picks audio as the
+    /// output modality.
+    ///
+    /// # Errors
+    /// Returns an error if `speed` is out of range.
+    pub fn output_audio_config(
+        mut self,
+        format: AudioFormat,
+        voice: impl Into<String>,
+        speed: f32,
+    ) -> Result<Self> {
+        self = self.voice(voice).speaking_speed(speed)?;
+        let audio = self.audio.get_or_insert_with(AudioConfig::default);
+        audio
+            .output
+            .get_or_insert_with(OutputAudioConfig::default)
+            .format = Some(format);
+        Ok(self)
+    }
+
+    fn input_audio_config(&mut self) -> &mut InputAudioConfig {
+        self.audio
+            .get_or_insert_with(AudioConfig::default)
+            .input
+            .get_or_insert_with(InputAudioConfig::default)
+    }
+
     #[must_use]
     pub const fn session_kind(mut self, kind: SessionKind) -> Self {
         self.session_kind = kind;
@@ -133,6 +312,26 @@ impl RealtimeBuilder {
         self
     }
 
+    /// Trace the session with the server's default workflow name.
+    #[must_use]
+    pub fn tracing_auto(mut self) -> Self {
+        self.tracing = Some(Tracing::auto());
+        self
+    }
+
+    /// Trace the session under `workflow_name`, grouped with related sessions by
+    /// `group_id`. See [`Session::trace_info`](super::Session::trace_info).
+    #[must_use]
+    pub fn tracing(
+        mut self,
+        workflow_name: impl Into<String>,
+        group_id: Option<String>,
+        metadata: Option<Metadata>,
+    ) -> Self {
+        self.tracing = Some(Tracing::workflow(workflow_name, group_id, metadata));
+        self
+    }
+
     #[must_use]
     pub const fn max_output_tokens(mut self, max_output_tokens: MaxTokens) -> Self {
         self.max_output_tokens = Some(max_output_tokens);
@@ -145,18 +344,251 @@ impl RealtimeBuilder {
         self
     }
 
+    /// Decide when speech triggers auto barge-in, e.g. only after a minimum duration.
+    ///
+    /// Any non-default policy turns off the server VAD's `interrupt_response` so the
+    /// server does not cancel responses first. Without turn detection configured, the
+    /// server's default `server_vad` is sent with that flag off.
+    #[must_use]
+    pub const fn barge_in_policy(mut self, policy: BargeInPolicy) -> Self {
+        self.barge_in = policy;
+        self
+    }
+
+    /// Send `response.create` as soon as server VAD reports the end of speech.
+    ///
+    /// Turns off the VAD's own `create_response` so only one response is created.
+    /// Without turn detection configured, the server's default `server_vad` is sent
+    /// with that flag off.
+    #[must_use]
+    pub const fn respond_on_speech_stop(mut self, enabled: bool) -> Self {
+        self.respond_on_speech_stop = enabled;
+        self
+    }
+
     #[must_use]
     pub const fn auto_tool_response(mut self, enabled: bool) -> Self {
         self.auto_tool_response = enabled;
         self
     }
 
+    /// Choose what the model is told when a tool handler fails.
+    #[must_use]
+    pub fn tool_error_policy(mut self, policy: ToolErrorPolicy) -> Self {
+        self.tool_error_policy = policy;
+        self
+    }
+
+    /// Choose whether tool calls run one at a time or as soon as they arrive.
+    #[must_use]
+    pub const fn tool_concurrency(mut self, concurrency: ToolConcurrency) -> Self {
+        self.tool_concurrency = concurrency;
+        self
+    }
+
+    #[must_use]
+    pub fn idle_timeout(mut self, policy: IdleTimeoutPolicy) -> Self {
+        self.idle_timeout = policy;
+        self
+    }
+
+    /// Choose what happens when the user's audio cannot be transcribed.
+    #[must_use]
+    pub fn transcription_failure_policy(mut self, policy: TranscriptionFailurePolicy) -> Self {
+        self.transcription_failure = policy;
+        self
+    }
+
+    /// Choose what happens when the model's audio cannot be decoded, e.g. fall back
+    /// to text output.
+    #[must_use]
+    pub const fn audio_failure_policy(mut self, policy: AudioFailurePolicy) -> Self {
+        self.audio_failure = policy;
+        self
+    }
+
+    /// Decode and meter audio with `codecs`, e.g. to support a format the SDK does
+    /// not know. Sessions built with the same registry share its codecs.
+    #[must_use]
+    pub fn codecs(mut self, codecs: CodecRegistry) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Read the time from `clock` for scheduled responses, barge-in delays, expiry,
+    /// deadlines and timing reports, e.g. a fake wall clock in tests.
+    #[must_use]
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = SharedClock::new(clock);
+        self
+    }
+
+    /// When to emit `SdkEvent::SessionExpiring`, and whether to renew the session on a
+    /// new connection before it expires.
+    #[must_use]
+    pub const fn session_expiry(mut self, policy: ExpiryPolicy) -> Self {
+        self.expiry = policy;
+        self
+    }
+
+    /// Cap `max_output_tokens` on every `response.create` sent through the session, by
+    /// a ceiling and by the tokens `rate_limits.updated` reports left; see
+    /// [`TokenBudget`] for the responses it does not cover.
+    #[must_use]
+    pub const fn token_budget(mut self, budget: TokenBudget) -> Self {
+        self.token_budget = Some(budget);
+        self
+    }
+
+    /// Tie the session to `token`, e.g. one the application cancels on shutdown.
+    /// Cancelling it abandons a connect in progress, fails `ask`, `stream_audio_*`,
+    /// `run_tool`, `wait_ready` and calls awaiting a server reply with
+    /// `Error::Cancelled`, and closes the session.
+    #[must_use]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Cut response text into clause-sized chunks for an external TTS, read with
+    /// `Session::speakable_text`.
+    #[must_use]
+    pub fn speakable_chunks(mut self, chunking: SpeakableChunking) -> Self {
+        self.speakable = Some(chunking);
+        self
+    }
+
+    /// Classify the start of the call as a person or an answering machine, reported
+    /// as `VoiceEvent::AnsweringMachineDetected`.
+    #[must_use]
+    pub fn answering_machine_detection(mut self, detection: AnsweringMachineDetection) -> Self {
+        self.answering_machine = Some(detection);
+        self
+    }
+
+    /// Make `connect_ws` wait for the server to acknowledge the initial session update.
+    #[must_use]
+    pub const fn wait_for_ready(mut self, timeout: Duration) -> Self {
+        self.ready_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up connecting after `timeout` instead of the default 30 seconds.
+    #[must_use]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Give up waiting for the server's reply to a request, such as `classify`,
+    /// `retrieve_item` or `cancel_active_response`, after `timeout` instead of the
+    /// default 60 seconds.
+    #[must_use]
+    pub const fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Fail sends of events larger than `max` bytes, such as a conversation item with
+    /// a huge base64 image, with `Error::EventTooLarge` instead of sending them.
+    #[must_use]
+    pub const fn max_event_bytes(mut self, max: usize) -> Self {
+        self.max_event_bytes = Some(max);
+        self
+    }
+
+    /// Add a guardrail for user text sent via `say` and assistant text output.
+    #[must_use]
+    pub fn guardrail(mut self, guardrail: impl Guardrail + 'static) -> Self {
+        self.guardrails.push(guardrail);
+        self
+    }
+
+    /// Emit `SdkEvent::Analytics` with talk-time and latency stats every `interval`.
+    #[must_use]
+    pub const fn analytics_interval(mut self, interval: Duration) -> Self {
+        self.analytics_interval = Some(interval);
+        self
+    }
+
+    /// Emit `SdkEvent::SlowHandler` when a handler, guardrail or tool call takes at
+    /// least `threshold`.
+    #[must_use]
+    pub const fn slow_handler_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_handler = Some(threshold);
+        self
+    }
+
+    /// Report the level of the audio sent and received as `VoiceEvent::InputLevel`
+    /// and `VoiceEvent::OutputLevel` every `interval`, e.g. to drive level meters.
+    #[must_use]
+    pub const fn audio_level_interval(mut self, interval: Duration) -> Self {
+        self.audio_levels = Some(interval);
+        self
+    }
+
+    /// Keep item creates and tool outputs until the server adds them, so the ones a
+    /// failed connection lost can be replayed with `Session::replay` on a new session.
+    #[must_use]
+    pub fn resumable_sends(mut self) -> Self {
+        self.resend = Some(ResendQueue::default());
+        self
+    }
+
+    /// Queue sizes for commands and for each reader (`next_text`, `events`, ...).
+    #[must_use]
+    pub const fn channel_capacities(mut self, capacities: ChannelCapacities) -> Self {
+        self.channel_capacities = capacities;
+        self
+    }
+
+    /// What to do when a reader falls behind and its queue is full.
+    #[must_use]
+    pub const fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Persist all raw client and server events as JSONL, written off the session task.
+    #[must_use]
+    pub fn event_log(
+        mut self,
+        path: impl Into<std::path::PathBuf>,
+        rotation: RotationPolicy,
+    ) -> Self {
+        self.event_log = Some(EventLog::new(path, rotation));
+        self
+    }
+
+    /// Like [`Self::event_log`], with custom redaction rules.
+    #[must_use]
+    pub fn event_log_config(mut self, log: EventLog) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+
+    /// How raw event payloads are traced: size limit, event types and formatting.
+    #[must_use]
+    pub fn trace_config(mut self, trace: crate::TraceConfig) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Run tool calls through `dispatcher` instead of the tools registered on this
+    /// builder, which are then ignored along with `tool_middleware`. Its definitions
+    /// are announced unless the session config already lists tools.
     #[must_use]
-    pub fn tool_dispatcher(mut self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
+    pub fn dispatcher(mut self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
         self.dispatcher = Some(dispatcher);
         self
     }
 
+    #[deprecated(since = "0.3.0", note = "use `dispatcher`")]
+    #[must_use]
+    pub fn tool_dispatcher(self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
+        self.dispatcher(dispatcher)
+    }
+
     #[must_use]
     pub fn voice_session(self) -> VoiceSessionBuilder {
         VoiceSessionBuilder::new(self)
@@ -226,15 +658,53 @@ impl RealtimeBuilder {
         self
     }
 
+    /// Register a tool whose handler also gets a `ToolContext`, e.g. to send events
+    /// while it runs or to stop when the call is cancelled.
+    #[must_use]
+    pub fn tool_with_context<TArgs, TResp, F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        TArgs: schemars::JsonSchema + serde::de::DeserializeOwned + Send + 'static,
+        TResp: serde::Serialize + Send + 'static,
+        F: FnMut(ToolContext, TArgs) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<TResp>> + Send + 'static,
+    {
+        self.tools.tool_with_context(name, handler);
+        self
+    }
+
     /// # Errors
     /// Returns an error if the MCP tool configuration is invalid.
-    // Keep a single public error type for the SDK surface.
-    #[allow(clippy::result_large_err)]
     pub fn mcp_tool(mut self, config: crate::protocol::models::McpToolConfig) -> Result<Self> {
         self.tools.mcp_tool(config)?;
         Ok(self)
     }
 
+    /// Adjust a registered tool's definition, e.g. `|def| { def.strict(); }`.
+    ///
+    /// # Errors
+    /// Returns an error if no tool named `name` is registered.
+    pub fn configure_tool(
+        mut self,
+        name: &str,
+        configure: impl FnOnce(&mut ToolDefinition),
+    ) -> Result<Self> {
+        let def = self
+            .tools
+            .definition_mut(name)
+            .ok_or_else(|| Error::InvalidClientEvent(format!("unknown tool: {name}")))?;
+        configure(def);
+        Ok(self)
+    }
+
+    /// Follow calls to the registered tools as their arguments stream in; see
+    /// `ToolMiddleware`. Like the registered tools, it is ignored when a
+    /// [`Self::dispatcher`] is set: that dispatcher's own `middleware` is used.
+    #[must_use]
+    pub fn tool_middleware(mut self, middleware: impl ToolMiddleware + 'static) -> Self {
+        self.tools.set_middleware(middleware);
+        self
+    }
+
     #[must_use]
     pub fn handlers(mut self, handlers: EventHandlers) -> Self {
         self.handlers = handlers;
@@ -271,10 +741,17 @@ impl RealtimeBuilder {
         self
     }
 
-    #[allow(clippy::result_large_err)]
     fn build(self) -> Result<SessionConfigSnapshot> {
-        let api_key = self
-            .api_key
+        let key_lease = match &self.key_pool {
+            Some(pool) => Some(pool.acquire().ok_or_else(|| {
+                Error::InvalidClientEvent("key pool has no usable key".to_string())
+            })?),
+            None => None,
+        };
+        let api_key = key_lease
+            .as_ref()
+            .map(|lease| lease.api_key().to_string())
+            .or(self.api_key)
             .ok_or_else(|| Error::InvalidClientEvent("api_key required".to_string()))?;
         let model = self.model.clone();
         let output_modalities = self.output_modalities.unwrap_or(OutputModalities::Audio);
@@ -287,9 +764,21 @@ impl RealtimeBuilder {
         session.tool_choice = self.tool_choice;
         session.temperature = self.temperature;
         session.max_output_tokens = self.max_output_tokens;
+        session.tracing = self.tracing;
         if let Some(audio) = self.audio {
             session.audio = Some(audio);
         }
+        let sdk_barge_in = self.auto_barge_in && self.barge_in != BargeInPolicy::default();
+        if let Some((create_response, interrupt_response)) =
+            server_vad_flags(&mut session, self.respond_on_speech_stop || sdk_barge_in)
+        {
+            if self.respond_on_speech_stop {
+                *create_response = Some(false);
+            }
+            if sdk_barge_in {
+                *interrupt_response = Some(false);
+            }
+        }
 
         let dispatcher = if let Some(d) = self.dispatcher {
             if session.tools.is_none() {
@@ -309,11 +798,46 @@ impl RealtimeBuilder {
         Ok(SessionConfigSnapshot {
             api_key,
             model,
+            call_id: self.call_id.clone(),
+            base_url: self.base_url,
             session,
+            ready_timeout: self.ready_timeout,
+            connect_timeout: self.connect_timeout,
+            max_event_bytes: self.max_event_bytes,
+            event_log: self.event_log,
+            trace: self.trace,
             handlers: self.handlers,
             dispatcher,
-            auto_barge_in: self.auto_barge_in,
-            auto_tool_response: self.auto_tool_response,
+            options: SessionOptions {
+                auto_barge_in: self.auto_barge_in,
+                barge_in: self.barge_in,
+                respond_on_speech_stop: self.respond_on_speech_stop,
+                auto_tool_response: self.auto_tool_response,
+                tool_error_policy: self.tool_error_policy,
+                tool_concurrency: self.tool_concurrency,
+                idle_timeout: self.idle_timeout,
+                transcription_failure: self.transcription_failure,
+                audio_failure: self.audio_failure,
+                codecs: self.codecs,
+                clock: self.clock,
+                speakable: self.speakable,
+                answering_machine: self.answering_machine,
+                guardrails: self.guardrails,
+                analytics_interval: self.analytics_interval,
+                slow_handler: self.slow_handler,
+                audio_levels: self.audio_levels,
+                resend: self.resend,
+                expiry: self.expiry,
+                reconnect: None,
+                call_id: self.call_id,
+                role: self.role,
+                channel_capacities: self.channel_capacities,
+                overflow_policy: self.overflow_policy,
+                token_budget: self.token_budget,
+                cancellation: self.cancellation,
+                key_lease,
+                request_timeout: self.request_timeout,
+            },
         })
     }
 
@@ -322,7 +846,58 @@ impl RealtimeBuilder {
     /// # Errors
     /// Returns an error if configuration is incomplete or the connection fails.
     pub async fn connect_ws(self) -> Result<super::Session> {
-        self.build()?.connect_ws().await
+        Box::pin(self.build()?.connect_ws()).await
+    }
+}
+
+/// The VAD's `create_response` and `interrupt_response` flags, when VAD is on. With
+/// `explicit`, unset turn detection is sent as the server's default `server_vad`, so
+/// flags set on it are not left to the server default.
+fn server_vad_flags(
+    session: &mut SessionConfig,
+    explicit: bool,
+) -> Option<(&mut Option<bool>, &mut Option<bool>)> {
+    let turn_detection = if explicit {
+        let input = session
+            .audio
+            .get_or_insert_with(AudioConfig::default)
+            .input
+            .get_or_insert_with(InputAudioConfig::default);
+        Some(
+            input
+                .turn_detection
+                .get_or_insert(crate::protocol::models::Nullable::Value(
+                    TurnDetection::ServerVad {
+                        threshold: None,
+                        prefix_padding_ms: None,
+                        silence_duration_ms: None,
+                        idle_timeout_ms: None,
+                        create_response: None,
+                        interrupt_response: None,
+                    },
+                )),
+        )
+    } else {
+        session
+            .audio
+            .as_mut()
+            .and_then(|audio| audio.input.as_mut())
+            .and_then(|input| input.turn_detection.as_mut())
+    };
+    match turn_detection {
+        Some(crate::protocol::models::Nullable::Value(
+            TurnDetection::ServerVad {
+                create_response,
+                interrupt_response,
+                ..
+            }
+            | TurnDetection::SemanticVad {
+                create_response,
+                interrupt_response,
+                ..
+            },
+        )) => Some((create_response, interrupt_response)),
+        _ => None,
     }
 }
 
@@ -336,6 +911,12 @@ pub struct VoiceSessionBuilder {
     inner: RealtimeBuilder,
 }
 
+impl From<VoiceSessionBuilder> for RealtimeBuilder {
+    fn from(builder: VoiceSessionBuilder) -> Self {
+        builder.inner
+    }
+}
+
 impl VoiceSessionBuilder {
     #[must_use]
     fn new(mut inner: RealtimeBuilder) -> Self {
@@ -374,12 +955,40 @@ impl VoiceSessionBuilder {
         self
     }
 
+    /// Take the API key from `pool` instead; see [`RealtimeBuilder::key_pool`].
+    #[must_use]
+    pub fn key_pool(mut self, pool: KeyPool) -> Self {
+        self.inner = self.inner.key_pool(pool);
+        self
+    }
+
     #[must_use]
     pub fn model(mut self, model: impl Into<String>) -> Self {
         self.inner = self.inner.model(model);
         self
     }
 
+    /// Attach to an existing call (e.g. SIP) instead of starting a new session.
+    #[must_use]
+    pub fn call_id(mut self, call_id: impl Into<String>) -> Self {
+        self.inner = self.inner.call_id(call_id);
+        self
+    }
+
+    /// Listen in on the call set with [`call_id`](Self::call_id) without controlling it.
+    #[must_use]
+    pub const fn observer(mut self) -> Self {
+        self.inner.role = SessionRole::Observer;
+        self
+    }
+
+    /// Connect to another WebSocket endpoint, e.g. a proxy or a local mock server.
+    #[must_use]
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.base_url(url);
+        self
+    }
+
     #[must_use]
     pub fn voice(mut self, voice: impl Into<String>) -> Self {
         self.inner = self.inner.voice(voice);
@@ -395,6 +1004,35 @@ impl VoiceSessionBuilder {
         self
     }
 
+    /// Playback speed of the model's voice, from 0.25 to 1.5 (1.0 is normal).
+    ///
+    /// # Errors
+    /// Returns an error if `speed` is out of range.
+    pub fn speaking_speed(mut self, speed: f32) -> Result<Self> {
+        self.inner = self.inner.speaking_speed(speed)?;
+        Ok(self)
+    }
+
+    /// Trace the session with the server's default workflow name.
+    #[must_use]
+    pub fn tracing_auto(mut self) -> Self {
+        self.inner = self.inner.tracing_auto();
+        self
+    }
+
+    /// Trace the session under `workflow_name`, grouped with related sessions by
+    /// `group_id`.
+    #[must_use]
+    pub fn tracing(
+        mut self,
+        workflow_name: impl Into<String>,
+        group_id: Option<String>,
+        metadata: Option<Metadata>,
+    ) -> Self {
+        self.inner = self.inner.tracing(workflow_name, group_id, metadata);
+        self
+    }
+
     #[must_use]
     pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
         self.inner = self.inner.instructions(instructions);
@@ -402,7 +1040,7 @@ impl VoiceSessionBuilder {
     }
 
     #[must_use]
-    pub const fn vad_server_default(self) -> Self {
+    pub fn vad_server_default(self) -> Self {
         let vad = TurnDetection::ServerVad {
             threshold: None,
             prefix_padding_ms: None,
@@ -415,7 +1053,7 @@ impl VoiceSessionBuilder {
     }
 
     #[must_use]
-    pub const fn set_turn_detection(mut self, vad: TurnDetection) -> Self {
+    pub fn set_turn_detection(mut self, vad: TurnDetection) -> Self {
         if let Some(audio) = self.inner.audio.as_mut() {
             if let Some(input) = audio.input.as_mut() {
                 input.turn_detection = Some(crate::protocol::models::Nullable::Value(vad));
@@ -424,6 +1062,23 @@ impl VoiceSessionBuilder {
         self
     }
 
+    /// Turn off server turn detection so the user's turn is whatever audio arrives
+    /// between [`Session::ptt_start`](super::Session::ptt_start) and
+    /// [`Session::ptt_stop`](super::Session::ptt_stop).
+    ///
+    /// Sends `turn_detection: null`, so the server neither commits audio nor creates
+    /// responses on its own.
+    #[must_use]
+    pub fn push_to_talk(mut self) -> Self {
+        if let Some(audio) = self.inner.audio.as_mut() {
+            if let Some(input) = audio.input.as_mut() {
+                input.turn_detection = Some(crate::protocol::models::Nullable::Null);
+            }
+        }
+        self.inner.respond_on_speech_stop = false;
+        self
+    }
+
     #[must_use]
     pub fn transcription(mut self, model: impl Into<String>) -> Self {
         let transcription = InputAudioTranscription {
@@ -440,7 +1095,7 @@ impl VoiceSessionBuilder {
     }
 
     #[must_use]
-    pub const fn noise_reduction(mut self, noise_reduction: NoiseReduction) -> Self {
+    pub fn noise_reduction(mut self, noise_reduction: NoiseReduction) -> Self {
         if let Some(audio) = self.inner.audio.as_mut() {
             if let Some(input) = audio.input.as_mut() {
                 input.noise_reduction =
@@ -456,24 +1111,273 @@ impl VoiceSessionBuilder {
         self
     }
 
+    /// Decide when speech triggers auto barge-in, e.g. only after a minimum duration.
+    ///
+    /// Any non-default policy turns off the server VAD's `interrupt_response` so the
+    /// server does not cancel responses first.
+    #[must_use]
+    pub fn barge_in_policy(mut self, policy: BargeInPolicy) -> Self {
+        self.inner = self.inner.barge_in_policy(policy);
+        self
+    }
+
+    /// Send `response.create` as soon as server VAD reports the end of speech.
+    ///
+    /// Turns off the VAD's own `create_response` so only one response is created.
+    #[must_use]
+    pub const fn respond_on_speech_stop(mut self, enabled: bool) -> Self {
+        self.inner.respond_on_speech_stop = enabled;
+        self
+    }
+
     #[must_use]
     pub const fn auto_tool_response(mut self, enabled: bool) -> Self {
         self.inner.auto_tool_response = enabled;
         self
     }
 
+    /// Choose what the model is told when a tool handler fails.
+    #[must_use]
+    pub fn tool_error_policy(mut self, policy: ToolErrorPolicy) -> Self {
+        self.inner.tool_error_policy = policy;
+        self
+    }
+
+    /// Choose whether tool calls run one at a time or as soon as they arrive.
+    #[must_use]
+    pub const fn tool_concurrency(mut self, concurrency: ToolConcurrency) -> Self {
+        self.inner.tool_concurrency = concurrency;
+        self
+    }
+
+    #[must_use]
+    pub fn idle_timeout(mut self, policy: IdleTimeoutPolicy) -> Self {
+        self.inner.idle_timeout = policy;
+        self
+    }
+
+    /// Choose what happens when the user's audio cannot be transcribed.
+    #[must_use]
+    pub fn transcription_failure_policy(mut self, policy: TranscriptionFailurePolicy) -> Self {
+        self.inner.transcription_failure = policy;
+        self
+    }
+
+    /// Choose what happens when the model's audio cannot be decoded, e.g. fall back
+    /// to text output.
+    #[must_use]
+    pub const fn audio_failure_policy(mut self, policy: AudioFailurePolicy) -> Self {
+        self.inner.audio_failure = policy;
+        self
+    }
+
+    /// Decode and meter audio with `codecs`, e.g. to support a format the SDK does
+    /// not know. Sessions built with the same registry share its codecs.
+    #[must_use]
+    pub fn codecs(mut self, codecs: CodecRegistry) -> Self {
+        self.inner = self.inner.codecs(codecs);
+        self
+    }
+
+    /// Read the time from `clock` for scheduled responses, barge-in delays, expiry,
+    /// deadlines and timing reports, e.g. a fake wall clock in tests.
+    #[must_use]
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.inner = self.inner.clock(clock);
+        self
+    }
+
+    /// When to emit `SdkEvent::SessionExpiring`, and whether to renew the session on a
+    /// new connection before it expires.
+    #[must_use]
+    pub const fn session_expiry(mut self, policy: ExpiryPolicy) -> Self {
+        self.inner.expiry = policy;
+        self
+    }
+
+    /// Cap `max_output_tokens` on every `response.create` sent through the session, by
+    /// a ceiling and by the tokens `rate_limits.updated` reports left; see
+    /// [`TokenBudget`] for the responses it does not cover.
+    #[must_use]
+    pub const fn token_budget(mut self, budget: TokenBudget) -> Self {
+        self.inner.token_budget = Some(budget);
+        self
+    }
+
+    /// Tie the session to `token`, e.g. one the application cancels on shutdown.
+    /// Cancelling it abandons a connect in progress, fails `ask`, `stream_audio_*`,
+    /// `run_tool`, `wait_ready` and calls awaiting a server reply with
+    /// `Error::Cancelled`, and closes the session.
     #[must_use]
-    pub fn tool_dispatcher(mut self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.inner = self.inner.cancellation(token);
+        self
+    }
+
+    /// Cut response text into clause-sized chunks for an external TTS, read with
+    /// `Session::speakable_text`.
+    #[must_use]
+    pub fn speakable_chunks(mut self, chunking: SpeakableChunking) -> Self {
+        self.inner.speakable = Some(chunking);
+        self
+    }
+
+    /// Classify the start of the call as a person or an answering machine, reported
+    /// as `VoiceEvent::AnsweringMachineDetected`.
+    #[must_use]
+    pub fn answering_machine_detection(mut self, detection: AnsweringMachineDetection) -> Self {
+        self.inner.answering_machine = Some(detection);
+        self
+    }
+
+    /// Make `connect_ws` wait for the server to acknowledge the initial session update.
+    #[must_use]
+    pub const fn wait_for_ready(mut self, timeout: Duration) -> Self {
+        self.inner.ready_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up connecting after `timeout` instead of the default 30 seconds.
+    #[must_use]
+    pub const fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.inner.connect_timeout = timeout;
+        self
+    }
+
+    /// Give up waiting for the server's reply to a request, such as `classify`,
+    /// `retrieve_item` or `cancel_active_response`, after `timeout` instead of the
+    /// default 60 seconds.
+    #[must_use]
+    pub const fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.inner.request_timeout = timeout;
+        self
+    }
+
+    /// Fail sends of events larger than `max` bytes, such as a conversation item with
+    /// a huge base64 image, with `Error::EventTooLarge` instead of sending them.
+    #[must_use]
+    pub const fn max_event_bytes(mut self, max: usize) -> Self {
+        self.inner.max_event_bytes = Some(max);
+        self
+    }
+
+    /// Add a guardrail for user text sent via `say` and assistant text output.
+    #[must_use]
+    pub fn guardrail(mut self, guardrail: impl Guardrail + 'static) -> Self {
+        self.inner.guardrails.push(guardrail);
+        self
+    }
+
+    /// Emit `SdkEvent::Analytics` with talk-time and latency stats every `interval`.
+    #[must_use]
+    pub const fn analytics_interval(mut self, interval: Duration) -> Self {
+        self.inner.analytics_interval = Some(interval);
+        self
+    }
+
+    /// Emit `SdkEvent::SlowHandler` when a handler, guardrail or tool call takes at
+    /// least `threshold`.
+    #[must_use]
+    pub const fn slow_handler_threshold(mut self, threshold: Duration) -> Self {
+        self.inner.slow_handler = Some(threshold);
+        self
+    }
+
+    /// Report the level of the audio sent and received as `VoiceEvent::InputLevel`
+    /// and `VoiceEvent::OutputLevel` every `interval`, e.g. to drive level meters.
+    #[must_use]
+    pub const fn audio_level_interval(mut self, interval: Duration) -> Self {
+        self.inner.audio_levels = Some(interval);
+        self
+    }
+
+    /// Keep item creates and tool outputs until the server adds them, so the ones a
+    /// failed connection lost can be replayed with `Session::replay` on a new session.
+    #[must_use]
+    pub fn resumable_sends(mut self) -> Self {
+        self.inner = self.inner.resumable_sends();
+        self
+    }
+
+    /// Queue sizes for commands and for each reader (`next_text`, `events`, ...).
+    #[must_use]
+    pub const fn channel_capacities(mut self, capacities: ChannelCapacities) -> Self {
+        self.inner.channel_capacities = capacities;
+        self
+    }
+
+    /// What to do when a reader falls behind and its queue is full.
+    #[must_use]
+    pub const fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.inner.overflow_policy = policy;
+        self
+    }
+
+    /// Persist all raw client and server events as JSONL, written off the session task.
+    #[must_use]
+    pub fn event_log(
+        mut self,
+        path: impl Into<std::path::PathBuf>,
+        rotation: RotationPolicy,
+    ) -> Self {
+        self.inner.event_log = Some(EventLog::new(path, rotation));
+        self
+    }
+
+    /// Like [`Self::event_log`], with custom redaction rules.
+    #[must_use]
+    pub fn event_log_config(mut self, log: EventLog) -> Self {
+        self.inner.event_log = Some(log);
+        self
+    }
+
+    /// How raw event payloads are traced: size limit, event types and formatting.
+    #[must_use]
+    pub fn trace_config(mut self, trace: crate::TraceConfig) -> Self {
+        self.inner.trace = trace;
+        self
+    }
+
+    #[must_use]
+    pub fn dispatcher(mut self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
         self.inner.dispatcher = Some(dispatcher);
         self
     }
 
+    #[deprecated(since = "0.3.0", note = "use `dispatcher`")]
+    #[must_use]
+    pub fn tool_dispatcher(self, dispatcher: Arc<dyn ToolDispatcher>) -> Self {
+        self.dispatcher(dispatcher)
+    }
+
     #[must_use]
     pub fn tools(mut self, tools: ToolRegistry) -> Self {
         self.inner = self.inner.tools(tools);
         self
     }
 
+    /// Adjust a registered tool's definition, e.g. `|def| { def.strict(); }`.
+    ///
+    /// # Errors
+    /// Returns an error if no tool named `name` is registered.
+    pub fn configure_tool(
+        mut self,
+        name: &str,
+        configure: impl FnOnce(&mut ToolDefinition),
+    ) -> Result<Self> {
+        self.inner = self.inner.configure_tool(name, configure)?;
+        Ok(self)
+    }
+
+    /// Follow calls to the registered tools as their arguments stream in; see
+    /// [`RealtimeBuilder::tool_middleware`], which is ignored with a custom
+    /// dispatcher.
+    #[must_use]
+    pub fn tool_middleware(mut self, middleware: impl ToolMiddleware + 'static) -> Self {
+        self.inner = self.inner.tool_middleware(middleware);
+        self
+    }
+
     #[must_use]
     pub fn tool<TArgs, TResp, F, Fut>(mut self, name: &str, handler: F) -> Self
     where
@@ -520,6 +1424,18 @@ impl VoiceSessionBuilder {
         self
     }
 
+    #[must_use]
+    pub fn tool_with_context<TArgs, TResp, F, Fut>(mut self, name: &str, handler: F) -> Self
+    where
+        TArgs: schemars::JsonSchema + serde::de::DeserializeOwned + Send + 'static,
+        TResp: serde::Serialize + Send + 'static,
+        F: FnMut(ToolContext, TArgs) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<TResp>> + Send + 'static,
+    {
+        self.inner = self.inner.tool_with_context(name, handler);
+        self
+    }
+
     #[must_use]
     pub fn on_text<F, Fut>(mut self, handler: F) -> Self
     where
@@ -558,3 +1474,53 @@ impl VoiceSessionBuilder {
         self.inner.connect_ws().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::Nullable;
+
+    fn turn_detection(builder: RealtimeBuilder) -> Option<Nullable<TurnDetection>> {
+        builder
+            .api_key("test")
+            .build()
+            .unwrap()
+            .session
+            .audio
+            .and_then(|audio| audio.input)
+            .and_then(|input| input.turn_detection)
+    }
+
+    #[test]
+    fn respond_on_speech_stop_sends_vad_without_create_response() {
+        assert_eq!(turn_detection(Realtime::builder()), None);
+        assert!(matches!(
+            turn_detection(Realtime::builder().respond_on_speech_stop(true)),
+            Some(Nullable::Value(TurnDetection::ServerVad {
+                create_response: Some(false),
+                interrupt_response: None,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn barge_in_policy_sends_vad_without_interrupt_response() {
+        let policy = BargeInPolicy {
+            min_speech: Some(Duration::from_millis(300)),
+            ..BargeInPolicy::default()
+        };
+        assert!(matches!(
+            turn_detection(
+                Realtime::builder()
+                    .auto_barge_in(true)
+                    .barge_in_policy(policy)
+            ),
+            Some(Nullable::Value(TurnDetection::ServerVad {
+                create_response: None,
+                interrupt_response: Some(false),
+                ..
+            }))
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Call state for sessions attached to a SIP or WebRTC call with `call_id`.
+
+use super::lifecycle::CloseReason;
+use crate::ServerClose;
+use crate::protocol::server_events::ServerEvent;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Where a call is, as reported by `Session::call_state` and
+/// `Session::next_call_event`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallLifecycle {
+    /// The session is connecting to the call.
+    Ringing,
+    /// The server accepted the session on the call.
+    Answered,
+    /// The server ended the call, e.g. because the caller hung up.
+    RemoteHangup,
+    /// `Session::hangup` ended the call.
+    LocalHangup,
+    /// The connection to the call failed or was closed with an error.
+    Failed { reason: String },
+}
+
+impl CallLifecycle {
+    /// Whether the call is over; no state follows this one.
+    #[must_use]
+    pub const fn is_ended(&self) -> bool {
+        matches!(
+            self,
+            Self::RemoteHangup | Self::LocalHangup | Self::Failed { .. }
+        )
+    }
+}
+
+/// The call state, shared by the session and its event loop. Holds `None` for
+/// sessions without a call.
+#[derive(Clone)]
+pub struct CallStatus(Arc<watch::Sender<Option<CallLifecycle>>>);
+
+impl CallStatus {
+    /// A status starting at `Ringing` for a call, and the receiver that reports it.
+    pub fn new(call: bool) -> (Self, watch::Receiver<Option<CallLifecycle>>) {
+        let (tx, mut rx) = watch::channel(call.then_some(CallLifecycle::Ringing));
+        if call {
+            // Report `Ringing` as the first call event.
+            rx.mark_changed();
+        }
+        (Self(Arc::new(tx)), rx)
+    }
+
+    pub fn observe(&self, evt: &ServerEvent) {
+        if matches!(evt, ServerEvent::SessionCreated { .. }) {
+            self.advance(|state| {
+                (*state == CallLifecycle::Ringing).then_some(CallLifecycle::Answered)
+            });
+        }
+    }
+
+    pub fn hung_up(&self) {
+        self.advance(|_| Some(CallLifecycle::LocalHangup));
+    }
+
+    /// Settle the call once the event loop exits for `reason`.
+    pub fn closed(&self, reason: &CloseReason) {
+        self.advance(|state| {
+            let reason = match reason {
+                CloseReason::ServerClosed(close) => {
+                    match close.as_ref().and_then(ServerClose::error) {
+                        Some(err) => err.to_string(),
+                        None if *state == CallLifecycle::Answered => {
+                            return Some(CallLifecycle::RemoteHangup);
+                        }
+                        None => "the call ended before it was answered".to_string(),
+                    }
+                }
+                CloseReason::Transport(message) => message.clone(),
+                CloseReason::IdleTimeout => "the session closed after idle timeouts".to_string(),
+                CloseReason::ChannelOverflow(channel) => {
+                    format!("the {channel} channel overflowed")
+                }
+                CloseReason::Panicked => "the event loop panicked".to_string(),
+                CloseReason::DeadlineExceeded | CloseReason::Cancelled => {
+                    return Some(CallLifecycle::LocalHangup);
+                }
+                // Nobody is left to hear about the call.
+                CloseReason::Dropped => return None,
+            };
+            Some(CallLifecycle::Failed { reason })
+        });
+    }
+
+    /// Move a call that has not ended to the state `next` picks, if any.
+    fn advance(&self, next: impl FnOnce(&CallLifecycle) -> Option<CallLifecycle>) {
+        self.0.send_if_modified(|state| {
+            let Some(current) = state.as_ref().filter(|current| !current.is_ended()) else {
+                return false;
+            };
+            let Some(next) = next(current) else {
+                return false;
+            };
+            *state = Some(next);
+            true
+        });
+    }
+}
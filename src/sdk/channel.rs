@@ -0,0 +1,604 @@
+//! Bounded queues from the event loop to the session's readers, with a configurable
+//! policy for readers that fall behind.
+
+use crate::{Error, Result};
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::task::{Context, Poll};
+use tokio::sync::Notify;
+
+/// What the event loop does when a reader's queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the reader. Server events and tool calls stall until it catches up.
+    #[default]
+    Block,
+    /// Discard the oldest queued message and count it in `Session::dropped_messages`.
+    DropOldest,
+    /// Close the session. Reads return `Error::ChannelOverflow` once the queue is drained.
+    CloseSession,
+}
+
+/// Queue sizes for the session's channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelCapacities {
+    /// Interrupting commands (cancel, clear) sent to the event loop.
+    pub control: usize,
+    /// All other commands sent to the event loop, such as audio appends.
+    pub bulk: usize,
+    pub text: usize,
+    pub events: usize,
+    pub voice: usize,
+    pub audio: usize,
+    pub transcripts: usize,
+    /// Chunks for `Session::speakable_text`, when enabled.
+    pub speakable: usize,
+    /// Transcripts for the stream opened with `Session::transcripts`.
+    pub transcript_stream: usize,
+    /// Client events held while outbound sends are paused.
+    pub paused_outbound: usize,
+}
+
+impl Default for ChannelCapacities {
+    fn default() -> Self {
+        Self {
+            control: 16,
+            bulk: 32,
+            text: 32,
+            events: 128,
+            voice: 128,
+            audio: 128,
+            transcripts: 128,
+            speakable: 128,
+            transcript_stream: 128,
+            paused_outbound: 256,
+        }
+    }
+}
+
+/// Messages discarded because a reader fell behind, per reader.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DroppedMessages {
+    pub text: u64,
+    pub events: u64,
+    pub voice: u64,
+    pub audio: u64,
+    pub transcripts: u64,
+    pub speakable: u64,
+}
+
+impl DroppedMessages {
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.text + self.events + self.voice + self.audio + self.transcripts + self.speakable
+    }
+}
+
+/// A reader that fell behind or went away, for the event loop to report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notice {
+    /// `dropped` messages discarded so far on `channel`.
+    Lagging {
+        channel: &'static str,
+        dropped: u64,
+    },
+    Closed {
+        channel: &'static str,
+    },
+}
+
+/// Records trouble on a session's channels: the first channel that overflowed under
+/// `OverflowPolicy::CloseSession`, and readers that fell behind or went away, which the
+/// event loop reports as `SdkEvent::ConsumerLagging` / `ConsumerClosed`.
+#[derive(Clone, Default)]
+pub struct ChannelMonitor(Arc<Mutex<Monitor>>);
+
+#[derive(Default)]
+struct Monitor {
+    overflowed: Option<&'static str>,
+    /// Messages sent after their reader went away.
+    undelivered: u64,
+    /// Not yet reported, at most one of each kind per channel.
+    notices: Vec<Notice>,
+}
+
+impl ChannelMonitor {
+    pub fn channel(&self) -> Option<&'static str> {
+        self.lock().overflowed
+    }
+
+    /// What a read returns once its channel has closed: `Ok(None)`, or the
+    /// overflow that closed the session.
+    pub fn closed<T>(&self) -> Result<Option<T>> {
+        self.channel()
+            .map_or(Ok(None), |name| Err(Error::ChannelOverflow(name)))
+    }
+
+    /// Messages discarded so far because their reader had gone away, on any channel.
+    pub fn undelivered(&self) -> u64 {
+        self.lock().undelivered
+    }
+
+    /// Notices not yet reported, oldest first.
+    pub fn take_notices(&self) -> Vec<Notice> {
+        std::mem::take(&mut self.lock().notices)
+    }
+
+    /// Hold back `notices`, which could not be reported yet, ahead of any recorded
+    /// since. A newer count for the same lagging channel replaces the held one.
+    pub fn hold_notices(&self, notices: Vec<Notice>) {
+        let mut monitor = self.lock();
+        let newer = std::mem::replace(&mut monitor.notices, notices);
+        for notice in newer {
+            monitor.record(notice);
+        }
+    }
+
+    fn trip(&self, name: &'static str) {
+        self.lock().overflowed.get_or_insert(name);
+    }
+
+    fn lagging(&self, name: &'static str, dropped: u64) {
+        self.lock().record(Notice::Lagging {
+            channel: name,
+            dropped,
+        });
+    }
+
+    /// Count an undelivered message; the first one on a channel is reported.
+    fn undeliverable(&self, name: &'static str, first: bool) {
+        let mut monitor = self.lock();
+        monitor.undelivered += 1;
+        if first {
+            monitor.record(Notice::Closed { channel: name });
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Monitor> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Monitor {
+    fn record(&mut self, notice: Notice) {
+        if let Notice::Lagging { channel, dropped } = notice {
+            let held = self.notices.iter_mut().find_map(|held| match held {
+                Notice::Lagging {
+                    channel: name,
+                    dropped,
+                } if *name == channel => Some(dropped),
+                _ => None,
+            });
+            if let Some(held) = held {
+                *held = dropped;
+                return;
+            }
+        }
+        self.notices.push(notice);
+    }
+}
+
+struct Shared<T> {
+    name: &'static str,
+    capacity: usize,
+    policy: OverflowPolicy,
+    monitor: ChannelMonitor,
+    dropped: AtomicU64,
+    /// Messages sent after the reader went away.
+    undelivered: AtomicU64,
+    state: Mutex<State<T>>,
+    /// Wakes blocked senders when the reader takes a message or goes away.
+    space: Notify,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    receiver_open: bool,
+    waker: Option<std::task::Waker>,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub fn channel<T>(
+    name: &'static str,
+    capacity: usize,
+    policy: OverflowPolicy,
+    monitor: &ChannelMonitor,
+) -> (Sender<T>, Receiver<T>) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Shared {
+        name,
+        capacity,
+        policy,
+        monitor: monitor.clone(),
+        dropped: AtomicU64::new(0),
+        undelivered: AtomicU64::new(0),
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(capacity),
+            senders: 1,
+            receiver_open: true,
+            waker: None,
+        }),
+        space: Notify::new(),
+    });
+    (
+        Sender {
+            shared: Arc::clone(&shared),
+        },
+        Receiver { shared },
+    )
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Queue `value`, applying the overflow policy if the queue is full.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionClosed` if the receiver is gone, or
+    /// `Error::ChannelOverflow` under `OverflowPolicy::CloseSession`.
+    pub async fn send(&self, mut value: T) -> Result<()> {
+        loop {
+            // Registered before checking, so a reader taking a message in between
+            // still wakes us.
+            let space = self.shared.space.notified();
+            tokio::pin!(space);
+            space.as_mut().enable();
+            match self.push(value)? {
+                None => return Ok(()),
+                Some(rejected) => value = rejected,
+            }
+            space.await;
+        }
+    }
+
+    /// Like [`Self::send`], but never waits: under `OverflowPolicy::Block` the
+    /// message is discarded and counted if the queue is full.
+    ///
+    /// # Errors
+    /// Same as [`Self::send`].
+    pub fn try_send(&self, value: T) -> Result<()> {
+        if self.offer(value)?.is_some() {
+            self.count_drop();
+        }
+        Ok(())
+    }
+
+    /// Queue `value` unless the queue is full under `OverflowPolicy::Block`, in which
+    /// case it is handed back rather than waited for or discarded.
+    ///
+    /// # Errors
+    /// Same as [`Self::send`].
+    pub fn offer(&self, value: T) -> Result<Option<T>> {
+        self.push(value)
+    }
+
+    /// Queue `value` as this sender's last message, past the capacity if the queue is
+    /// full: it is neither waited for nor discarded, whatever the policy.
+    ///
+    /// # Errors
+    /// Returns `Error::ConnectionClosed` if the receiver is gone.
+    pub fn send_last(self, value: T) -> Result<()> {
+        let mut state = self.shared.lock();
+        if !state.receiver_open {
+            drop(state);
+            self.count_undelivered();
+            return Err(Error::ConnectionClosed);
+        }
+        state.queue.push_back(value);
+        let waker = state.waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Queue `value`, or hand it back if the queue is full under `OverflowPolicy::Block`.
+    fn push(&self, value: T) -> Result<Option<T>> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        if !state.receiver_open {
+            drop(state);
+            self.count_undelivered();
+            return Err(Error::ConnectionClosed);
+        }
+        if state.queue.len() >= shared.capacity {
+            match shared.policy {
+                OverflowPolicy::Block => return Ok(Some(value)),
+                OverflowPolicy::DropOldest => {
+                    state.queue.pop_front();
+                    self.count_drop();
+                }
+                OverflowPolicy::CloseSession => {
+                    tracing::warn!(channel = shared.name, "channel overflowed");
+                    shared.monitor.trip(shared.name);
+                    return Err(Error::ChannelOverflow(shared.name));
+                }
+            }
+        }
+        state.queue.push_back(value);
+        let waker = state.waker.take();
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(None)
+    }
+
+    /// Count a message dropped for a slow reader, reporting the first drop and each
+    /// time the count doubles.
+    fn count_drop(&self) {
+        let dropped = self.shared.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped == 1 {
+            tracing::warn!(
+                channel = self.shared.name,
+                "reader fell behind; dropping messages"
+            );
+        }
+        if dropped.is_power_of_two() {
+            self.shared.monitor.lagging(self.shared.name, dropped);
+        }
+    }
+
+    fn count_undelivered(&self) {
+        let first = self.shared.undelivered.fetch_add(1, Ordering::Relaxed) == 0;
+        if first {
+            tracing::warn!(
+                channel = self.shared.name,
+                "reader is gone; discarding messages"
+            );
+        }
+        self.shared.monitor.undeliverable(self.shared.name, first);
+    }
+
+    /// Whether the reader has gone away.
+    pub fn is_closed(&self) -> bool {
+        !self.shared.lock().receiver_open
+    }
+
+    /// A handle that does not keep the channel open.
+    pub fn downgrade(&self) -> WeakSender<T> {
+        WeakSender {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        let waker = if state.senders == 0 {
+            state.waker.take()
+        } else {
+            None
+        };
+        drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+pub struct WeakSender<T> {
+    shared: Weak<Shared<T>>,
+}
+
+impl<T> WeakSender<T> {
+    /// A sender, unless every strong sender has been dropped.
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        let shared = self.shared.upgrade()?;
+        {
+            let mut state = shared.lock();
+            if state.senders == 0 {
+                return None;
+            }
+            state.senders += 1;
+        }
+        Some(Sender { shared })
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Weak::clone(&self.shared),
+        }
+    }
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// The next message, or `None` once every sender is gone and the queue is empty.
+    pub async fn recv(&self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    pub fn poll_recv(&self, cx: &Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.shared.lock();
+        if let Some(value) = state.queue.pop_front() {
+            drop(state);
+            self.shared.space.notify_one();
+            return Poll::Ready(Some(value));
+        }
+        if state.senders == 0 {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Messages discarded because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// What a borrowed event stream reads from: a session channel, or a tokio channel
+/// handed to the stream's public constructor.
+pub enum Source<'a, T> {
+    Session(&'a mut Receiver<T>),
+    Tokio(&'a mut tokio::sync::mpsc::Receiver<T>),
+}
+
+impl<T> Source<'_, T> {
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self {
+            Self::Session(rx) => rx.poll_recv(cx),
+            Self::Tokio(rx) => rx.poll_recv(cx),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.receiver_open = false;
+        state.queue.clear();
+        drop(state);
+        self.shared.space.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_newest_and_counts() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("events", 2, OverflowPolicy::DropOldest, &monitor);
+        for n in 0..5 {
+            tx.send(n).await.unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(rx.dropped(), 3);
+        assert_eq!(rx.recv().await, Some(3));
+        assert_eq!(rx.recv().await, Some(4));
+        assert_eq!(rx.recv().await, None);
+        assert!(monitor.closed::<()>().unwrap().is_none());
+        assert_eq!(
+            monitor.take_notices(),
+            [Notice::Lagging {
+                channel: "events",
+                dropped: 2
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn held_notices_stay_ahead_of_newer_ones() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("text", 1, OverflowPolicy::DropOldest, &monitor);
+        let (voice, voice_rx) = channel("voice", 1, OverflowPolicy::Block, &monitor);
+        drop(voice_rx);
+        tx.send(0).await.unwrap();
+        tx.send(1).await.unwrap();
+        assert!(voice.send(0).await.is_err());
+        let held = monitor.take_notices();
+
+        tx.send(2).await.unwrap();
+        monitor.hold_notices(held);
+
+        assert_eq!(
+            monitor.take_notices(),
+            [
+                Notice::Lagging {
+                    channel: "text",
+                    dropped: 2
+                },
+                Notice::Closed { channel: "voice" },
+            ]
+        );
+        assert_eq!(rx.recv().await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn closed_reader_is_reported_once_and_counted() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("voice", 4, OverflowPolicy::Block, &monitor);
+        drop(rx);
+        for n in 0..3 {
+            assert!(matches!(tx.send(n).await, Err(Error::ConnectionClosed)));
+        }
+        assert!(tx.try_send(3).is_err());
+
+        assert_eq!(monitor.undelivered(), 4);
+        assert_eq!(
+            monitor.take_notices(),
+            [Notice::Closed { channel: "voice" }]
+        );
+        assert!(monitor.take_notices().is_empty());
+    }
+
+    #[tokio::test]
+    async fn close_session_trips_flag() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("audio", 1, OverflowPolicy::CloseSession, &monitor);
+        tx.send(1).await.unwrap();
+        assert!(matches!(
+            tx.send(2).await,
+            Err(Error::ChannelOverflow("audio"))
+        ));
+        drop(tx);
+
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+        assert!(matches!(
+            monitor.closed::<()>(),
+            Err(Error::ChannelOverflow("audio"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_reader() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("text", 1, OverflowPolicy::Block, &monitor);
+        tx.send(1).await.unwrap();
+        let writer = tokio::spawn(async move { tx.send(2).await });
+        tokio::task::yield_now().await;
+        assert!(!writer.is_finished());
+
+        assert_eq!(rx.recv().await, Some(1));
+        writer.await.unwrap().unwrap();
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, None);
+        assert_eq!(rx.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn last_message_goes_past_a_full_queue() {
+        let monitor = ChannelMonitor::default();
+        let (tx, rx) = channel("events", 1, OverflowPolicy::Block, &monitor);
+        tx.send(1).await.unwrap();
+        tx.send_last(2).unwrap();
+
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, None);
+        assert_eq!(rx.dropped(), 0);
+    }
+}
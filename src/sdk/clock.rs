@@ -0,0 +1,73 @@
+//! The session's source of time, injected so time-based behavior can be tested with
+//! a paused or fake clock.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::time::Instant;
+
+/// Where the event loop reads the time for scheduled responses, barge-in delays,
+/// session expiry and deadlines, analytics and timing reports.
+///
+/// The loop sleeps with tokio timers, so `now` should follow tokio's clock: under
+/// `tokio::time::pause` everything then advances only with `tokio::time::advance`.
+pub trait Clock: Send + Sync {
+    /// The current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// The current wall-clock time, e.g. to compare with the server's `expires_at`.
+    fn system_time(&self) -> SystemTime;
+}
+
+/// Tokio's monotonic clock and the system's wall clock; the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Shared handle to the session's clock.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(TokioClock)
+    }
+}
+
+impl std::ops::Deref for SharedClock {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedClock")
+    }
+}
+
+/// Resolve once `deadline` passes; never resolves for `None`. The event loop's
+/// timers all wait through this.
+pub async fn wait_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
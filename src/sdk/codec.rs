@@ -0,0 +1,390 @@
+//! Encoding and decoding audio by format, so the event loop does not need to know
+//! each format the API speaks.
+
+use crate::error::{Error, Result};
+use crate::protocol::models::{AudioFormat, Session};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Converts between one audio format and PCM16 samples.
+pub trait AudioCodec: Send + Sync {
+    /// Samples per second of the decoded audio.
+    fn sample_rate(&self) -> u32;
+
+    /// PCM16 samples for `encoded` bytes.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidAudio` if `encoded` is not valid in this format.
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<i16>>;
+
+    /// `samples` encoded in this format.
+    fn encode(&self, samples: &[i16]) -> Vec<u8>;
+}
+
+/// Little-endian PCM16, `audio/pcm`; 24kHz unless made with [`Pcm16Codec::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pcm16Codec {
+    rate: u32,
+}
+
+impl Pcm16Codec {
+    /// PCM16 at `rate` samples per second.
+    #[must_use]
+    pub const fn new(rate: u32) -> Self {
+        Self { rate }
+    }
+}
+
+impl Default for Pcm16Codec {
+    fn default() -> Self {
+        Self::new(24_000)
+    }
+}
+
+impl AudioCodec for Pcm16Codec {
+    fn sample_rate(&self) -> u32 {
+        self.rate
+    }
+
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<i16>> {
+        if encoded.len() % 2 != 0 {
+            return Err(Error::InvalidAudio(format!(
+                "{} bytes are not a whole number of PCM16 samples",
+                encoded.len()
+            )));
+        }
+        Ok(encoded
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect())
+    }
+
+    fn encode(&self, samples: &[i16]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect()
+    }
+}
+
+/// 8kHz G.711, `audio/pcmu` (μ-law) or `audio/pcma` (A-law).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct G711Codec {
+    a_law: bool,
+}
+
+impl G711Codec {
+    /// μ-law, as used in North America and Japan.
+    #[must_use]
+    pub const fn mu_law() -> Self {
+        Self { a_law: false }
+    }
+
+    /// A-law, as used elsewhere.
+    #[must_use]
+    pub const fn a_law() -> Self {
+        Self { a_law: true }
+    }
+}
+
+impl AudioCodec for G711Codec {
+    fn sample_rate(&self) -> u32 {
+        8_000
+    }
+
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<i16>> {
+        let decode = if self.a_law {
+            a_law_decode
+        } else {
+            mu_law_decode
+        };
+        Ok(encoded.iter().map(|&byte| decode(byte)).collect())
+    }
+
+    fn encode(&self, samples: &[i16]) -> Vec<u8> {
+        let encode = if self.a_law {
+            a_law_encode
+        } else {
+            mu_law_encode
+        };
+        samples.iter().map(|&sample| encode(sample)).collect()
+    }
+}
+
+const MU_LAW_BIAS: i32 = 0x84;
+const MU_LAW_CLIP: i32 = 32_635;
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn mu_law_encode(sample: i16) -> u8 {
+    let sign = if sample < 0 { 0x80 } else { 0 };
+    let magnitude = i32::from(sample).abs().min(MU_LAW_CLIP) + MU_LAW_BIAS;
+    // The bias keeps `magnitude >> 7` at least 1.
+    let exponent = (magnitude >> 7).ilog2() as i32;
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0f;
+    !((sign | (exponent << 4) | mantissa) as u8)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn mu_law_decode(byte: u8) -> i16 {
+    let byte = i32::from(!byte);
+    let exponent = (byte >> 4) & 0x07;
+    let mantissa = byte & 0x0f;
+    let magnitude = (((mantissa << 3) + MU_LAW_BIAS) << exponent) - MU_LAW_BIAS;
+    (if byte & 0x80 == 0 {
+        magnitude
+    } else {
+        -magnitude
+    }) as i16
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn a_law_encode(sample: i16) -> u8 {
+    let sample = i32::from(sample);
+    let mut code = if sample < 0 {
+        !sample >> 4
+    } else {
+        sample >> 4
+    };
+    if code > 15 {
+        let mut exponent = 1;
+        while code > 31 {
+            code >>= 1;
+            exponent += 1;
+        }
+        code += (exponent << 4) - 16;
+    }
+    if sample >= 0 {
+        code |= 0x80;
+    }
+    (code as u8) ^ 0x55
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn a_law_decode(byte: u8) -> i16 {
+    let byte = i32::from(byte ^ 0x55);
+    let exponent = (byte & 0x70) >> 4;
+    let mut magnitude = byte & 0x0f;
+    if exponent > 0 {
+        magnitude += 16;
+    }
+    magnitude = (magnitude << 4) + 8;
+    if exponent > 1 {
+        magnitude <<= exponent - 1;
+    }
+    (if byte & 0x80 == 0 {
+        -magnitude
+    } else {
+        magnitude
+    }) as i16
+}
+
+/// Audio codecs by format name, e.g. `audio/pcmu`. PCM is named with its rate, as
+/// `audio/pcm;rate=24000`.
+///
+/// Starts with PCM16 and G.711; PCM at a rate with no codec registered is decoded as
+/// PCM16 at that rate. Register a codec to handle a format the SDK does not
+/// know, or to replace a built-in one. Clones share their codecs, so a codec
+/// registered through `Session::codecs` applies to the running session from its next
+/// audio.
+#[derive(Clone)]
+pub struct CodecRegistry {
+    codecs: Arc<RwLock<HashMap<String, Arc<dyn AudioCodec>>>>,
+}
+
+impl CodecRegistry {
+    /// A registry with the built-in codecs.
+    #[must_use]
+    pub fn new() -> Self {
+        let registry = Self {
+            codecs: Arc::default(),
+        };
+        registry.register(
+            format_name(&AudioFormat::pcm_24khz()),
+            Pcm16Codec::default(),
+        );
+        registry.register(format_name(&AudioFormat::Pcmu), G711Codec::mu_law());
+        registry.register(format_name(&AudioFormat::Pcma), G711Codec::a_law());
+        registry
+    }
+
+    /// Use `codec` for `format`, returning the codec it replaces.
+    pub fn register(
+        &self,
+        format: impl Into<String>,
+        codec: impl AudioCodec + 'static,
+    ) -> Option<Arc<dyn AudioCodec>> {
+        self.codecs
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(format.into(), Arc::new(codec))
+    }
+
+    /// The codec for the format named `format`, if any.
+    #[must_use]
+    pub fn get(&self, format: &str) -> Option<Arc<dyn AudioCodec>> {
+        self.codecs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(format)
+            .cloned()
+    }
+
+    /// The codec for `format`, if any.
+    #[must_use]
+    pub fn for_format(&self, format: &AudioFormat) -> Option<Arc<dyn AudioCodec>> {
+        self.get(&format_name(format)).or_else(|| match format {
+            AudioFormat::Pcm { rate } => Some(Arc::new(Pcm16Codec::new(*rate))),
+            _ => None,
+        })
+    }
+}
+
+/// The name `format` is registered under.
+fn format_name(format: &AudioFormat) -> String {
+    match format {
+        AudioFormat::Pcm { rate } => format!("{format};rate={rate}"),
+        _ => format.to_string(),
+    }
+}
+
+impl Default for CodecRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CodecRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut formats: Vec<String> = self
+            .codecs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        formats.sort();
+        f.debug_struct("CodecRegistry")
+            .field("formats", &formats)
+            .finish()
+    }
+}
+
+/// The codecs for the formats the server reports for the session.
+#[derive(Debug, Clone)]
+pub struct SessionCodecs {
+    registry: CodecRegistry,
+    input: AudioFormat,
+    output: AudioFormat,
+}
+
+impl SessionCodecs {
+    pub const fn new(registry: CodecRegistry) -> Self {
+        Self {
+            registry,
+            input: AudioFormat::pcm_24khz(),
+            output: AudioFormat::pcm_24khz(),
+        }
+    }
+
+    pub fn update_format(&mut self, session: &Session) {
+        let audio = session.config.audio.as_ref();
+        let input = audio
+            .and_then(|audio| audio.input.as_ref())
+            .and_then(|input| input.format.as_ref())
+            .or(session.config.input_audio_format.as_ref());
+        let output = audio
+            .and_then(|audio| audio.output.as_ref())
+            .and_then(|output| output.format.as_ref())
+            .or(session.config.output_audio_format.as_ref());
+        if let Some(format) = input {
+            self.input = format.clone();
+        }
+        if let Some(format) = output {
+            self.output = format.clone();
+        }
+    }
+
+    /// The codec for input audio; `None` if no codec handles its format.
+    pub fn input(&self) -> Option<Arc<dyn AudioCodec>> {
+        self.registry.for_format(&self.input)
+    }
+
+    /// The codec for output audio; `None` if no codec handles its format.
+    pub fn output(&self) -> Option<Arc<dyn AudioCodec>> {
+        self.registry.for_format(&self.output)
+    }
+}
+
+impl Default for SessionCodecs {
+    fn default() -> Self {
+        Self::new(CodecRegistry::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn g711_round_trips_within_a_quantization_step() {
+        for codec in [G711Codec::mu_law(), G711Codec::a_law()] {
+            let samples = [
+                0, 1, -1, 100, -100, 1_000, -1_000, 12_345, -20_000, 32_767, -32_768,
+            ];
+            let decoded = codec.decode(&codec.encode(&samples)).unwrap();
+            for (sample, decoded) in samples.iter().zip(&decoded) {
+                let error = (i32::from(*sample) - i32::from(*decoded)).abs();
+                let step = (i32::from(*sample).abs() / 16).max(16);
+                assert!(error <= step, "{codec:?}: {sample} decoded as {decoded}");
+            }
+        }
+        assert_eq!(G711Codec::mu_law().encode(&[0]), [0xff]);
+        assert_eq!(G711Codec::a_law().encode(&[0]), [0xd5]);
+    }
+
+    #[test]
+    fn registry_resolves_built_in_and_registered_codecs() {
+        struct Silence;
+        impl AudioCodec for Silence {
+            fn sample_rate(&self) -> u32 {
+                48_000
+            }
+            fn decode(&self, encoded: &[u8]) -> Result<Vec<i16>> {
+                Ok(vec![0; encoded.len()])
+            }
+            fn encode(&self, samples: &[i16]) -> Vec<u8> {
+                vec![0; samples.len()]
+            }
+        }
+
+        let registry = CodecRegistry::new();
+        let pcm = registry.for_format(&AudioFormat::pcm_24khz()).unwrap();
+        assert_eq!(pcm.sample_rate(), 24_000);
+        assert!(pcm.decode(&[0, 0, 0]).is_err());
+        assert_eq!(pcm.decode(&pcm.encode(&[-2, 300])).unwrap(), [-2, 300]);
+        assert_eq!(
+            registry
+                .for_format(&AudioFormat::Pcmu)
+                .unwrap()
+                .sample_rate(),
+            8_000
+        );
+        assert!(registry.get("audio/opus").is_none());
+        let narrow = registry
+            .for_format(&AudioFormat::Pcm { rate: 16_000 })
+            .unwrap();
+        assert_eq!(narrow.sample_rate(), 16_000);
+        assert!(registry.get("audio/pcm;rate=24000").is_some());
+
+        let shared = registry.clone();
+        assert!(shared.register("audio/opus", Silence).is_none());
+        let opus = registry.get("audio/opus").unwrap();
+        assert_eq!(opus.sample_rate(), 48_000);
+        assert_eq!(opus.decode(&[1, 2]).unwrap(), [0, 0]);
+    }
+}
@@ -0,0 +1,352 @@
+//! Local record of the conversation, kept so it can be replayed into another session.
+//!
+//! Items are ordered by the `previous_item_id` the server reports for them. An item
+//! whose predecessor was never seen, e.g. because events were lost in a disconnect, is
+//! reported as a [`ConversationGap`] so the missing item can be retrieved.
+
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{ContentPart, Item};
+use crate::protocol::server_events::ServerEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::retrieval;
+
+/// Conversation items and uncommitted input audio, as of one point in the event loop.
+#[derive(Debug, Clone, Default)]
+pub struct ConversationSnapshot {
+    /// Items in conversation order, with audio payloads dropped.
+    pub items: Vec<Item>,
+    /// Base64 chunks appended to the input buffer since it was last committed or cleared.
+    pub input_audio: Vec<String>,
+}
+
+/// An item whose `previous_item_id` is missing from the local history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationGap {
+    /// The item that arrived.
+    pub item_id: String,
+    /// Its predecessor, which was never seen.
+    pub missing_item_id: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ConversationLog {
+    snapshot: ConversationSnapshot,
+    /// Predecessors not seen yet, each with the IDs of the items that follow it.
+    missing: HashMap<String, Vec<String>>,
+    deleted: HashSet<String>,
+    /// Responses running outside the conversation, whose items are not part of it.
+    out_of_band: HashSet<String>,
+}
+
+impl ConversationLog {
+    /// Record `evt`, returning a gap the first time an item names an unknown
+    /// predecessor.
+    pub fn observe_server(&mut self, evt: &ServerEvent) -> Option<ConversationGap> {
+        match evt {
+            ServerEvent::ConversationItemCreated {
+                previous_item_id,
+                item,
+                ..
+            }
+            | ServerEvent::ConversationItemAdded {
+                previous_item_id,
+                item,
+                ..
+            }
+            | ServerEvent::ConversationItemDone {
+                previous_item_id,
+                item,
+                ..
+            } => return self.place(item, previous_item_id.as_deref()),
+            // The server reports no conversation for a response created with
+            // `conversation: "none"`.
+            ServerEvent::ResponseCreated { response, .. } if response.conversation_id.is_none() => {
+                self.out_of_band.insert(response.id.clone());
+            }
+            ServerEvent::ResponseDone { response, .. }
+            | ServerEvent::ResponseCancelled { response, .. } => {
+                self.out_of_band.remove(&response.id);
+            }
+            ServerEvent::ResponseOutputItemDone {
+                response_id, item, ..
+            } if !self.out_of_band.contains(response_id) => self.upsert(item),
+            ServerEvent::ConversationItemRetrieved { item, .. }
+                if item.id().is_some_and(|id| self.missing.contains_key(id)) =>
+            {
+                self.place(item, None);
+            }
+            ServerEvent::ConversationItemDeleted { item_id, .. } => {
+                self.snapshot
+                    .items
+                    .retain(|item| item.id() != Some(item_id.as_str()));
+                self.missing.remove(item_id);
+                self.deleted.insert(item_id.clone());
+            }
+            // The missing item could not be retrieved; its followers stay where they are.
+            ServerEvent::Error { error, .. } => {
+                if let Some(item_id) = error.event_id.as_deref().and_then(retrieval::item_id) {
+                    self.missing.remove(item_id);
+                }
+            }
+            ServerEvent::InputAudioTranscriptionCompleted {
+                item_id,
+                content_index,
+                transcript,
+                ..
+            } => self.set_transcript(item_id, *content_index, transcript),
+            ServerEvent::InputAudioBufferCommitted { .. }
+            | ServerEvent::InputAudioBufferCleared { .. } => self.snapshot.input_audio.clear(),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn observe_client(&mut self, event: &ClientEvent) {
+        match event {
+            ClientEvent::InputAudioBufferAppend { audio, .. } => {
+                self.snapshot.input_audio.push(audio.clone());
+            }
+            ClientEvent::InputAudioBufferCommit { .. }
+            | ClientEvent::InputAudioBufferClear { .. } => {
+                self.snapshot.input_audio.clear();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn snapshot(&self) -> ConversationSnapshot {
+        self.snapshot.clone()
+    }
+
+    /// Predecessors that items refer to but that have not arrived.
+    #[cfg(test)]
+    pub fn missing(&self) -> Vec<&str> {
+        let mut missing: Vec<&str> = self.missing.keys().map(String::as_str).collect();
+        missing.sort_unstable();
+        missing
+    }
+
+    /// Insert a new `item` after `previous`, or update it if it is already known.
+    fn place(&mut self, item: &Item, previous: Option<&str>) -> Option<ConversationGap> {
+        let Some(item_id) = item.id().filter(|id| self.position(id).is_none()) else {
+            self.upsert(item);
+            return None;
+        };
+        // A missing item goes right before the first item that follows it.
+        if let Some(followers) = self.missing.remove(item_id) {
+            let index = followers
+                .iter()
+                .filter_map(|id| self.position(id))
+                .min()
+                .unwrap_or(self.snapshot.items.len());
+            self.snapshot
+                .items
+                .insert(index, without_audio(item.clone()));
+            return None;
+        }
+        let Some(previous) = previous else {
+            self.upsert(item);
+            return None;
+        };
+        if let Some(index) = self.position(previous) {
+            self.snapshot
+                .items
+                .insert(index + 1, without_audio(item.clone()));
+            return None;
+        }
+        self.upsert(item);
+        if self.deleted.contains(previous) {
+            return None;
+        }
+        let followers = self.missing.entry(previous.to_string()).or_default();
+        followers.push(item_id.to_string());
+        (followers.len() == 1).then(|| ConversationGap {
+            item_id: item_id.to_string(),
+            missing_item_id: previous.to_string(),
+        })
+    }
+
+    fn position(&self, item_id: &str) -> Option<usize> {
+        self.snapshot
+            .items
+            .iter()
+            .position(|entry| entry.id() == Some(item_id))
+    }
+
+    /// Add `item`, or replace the entry with the same ID as it progresses.
+    fn upsert(&mut self, item: &Item) {
+        let item = without_audio(item.clone());
+        let existing = item.id().and_then(|id| {
+            self.snapshot
+                .items
+                .iter_mut()
+                .find(|entry| entry.id() == Some(id))
+        });
+        match existing {
+            // An earlier copy may already hold a transcript the new one lacks.
+            Some(entry) => *entry = merge_transcripts(entry, item),
+            None => self.snapshot.items.push(item),
+        }
+    }
+
+    fn set_transcript(&mut self, item_id: &str, content_index: u32, text: &str) {
+        let Some(Item::Message { content, .. }) = self
+            .snapshot
+            .items
+            .iter_mut()
+            .find(|entry| entry.id() == Some(item_id))
+        else {
+            return;
+        };
+        if let Some(ContentPart::InputAudio { transcript, .. }) =
+            content.get_mut(content_index as usize)
+        {
+            *transcript = Some(text.to_string());
+        }
+    }
+}
+
+fn without_audio(mut item: Item) -> Item {
+    if let Item::Message { content, .. } = &mut item {
+        for part in content {
+            match part {
+                ContentPart::InputAudio { audio, .. } => audio.clear(),
+                ContentPart::OutputAudio { audio, .. } => *audio = None,
+                _ => {}
+            }
+        }
+    }
+    item
+}
+
+fn merge_transcripts(old: &Item, mut new: Item) -> Item {
+    if let (
+        Item::Message { content: old, .. },
+        Item::Message {
+            content: new_parts, ..
+        },
+    ) = (old, &mut new)
+    {
+        for (old, new) in old.iter().zip(new_parts.iter_mut()) {
+            if let (
+                ContentPart::InputAudio {
+                    transcript: Some(text),
+                    ..
+                },
+                ContentPart::InputAudio {
+                    transcript: slot @ None,
+                    ..
+                },
+            ) = (old, new)
+            {
+                *slot = Some(text.clone());
+            }
+        }
+    }
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::Role;
+
+    fn added(item_id: &str, previous: Option<&str>) -> ServerEvent {
+        ServerEvent::ConversationItemAdded {
+            event_id: format!("evt_{item_id}"),
+            previous_item_id: previous.map(str::to_string),
+            item: Item::Message {
+                id: Some(item_id.to_string()),
+                status: None,
+                role: Role::User,
+                content: vec![ContentPart::InputText {
+                    text: item_id.to_string(),
+                }],
+            },
+        }
+    }
+
+    fn order(log: &ConversationLog) -> Vec<&str> {
+        log.snapshot.items.iter().filter_map(Item::id).collect()
+    }
+
+    #[test]
+    fn items_follow_previous_item_chain_and_gaps_are_filled() {
+        let mut log = ConversationLog::default();
+        assert_eq!(log.observe_server(&added("a", None)), None);
+        assert_eq!(log.observe_server(&added("c", Some("a"))), None);
+        assert_eq!(log.observe_server(&added("b", Some("a"))), None);
+        assert_eq!(order(&log), ["a", "b", "c"]);
+
+        // "e" was lost in a disconnect.
+        let gap = log.observe_server(&added("f", Some("e")));
+        assert_eq!(
+            gap,
+            Some(ConversationGap {
+                item_id: "f".to_string(),
+                missing_item_id: "e".to_string(),
+            })
+        );
+        assert_eq!(log.observe_server(&added("g", Some("e"))), None);
+        assert_eq!(log.missing(), ["e"]);
+
+        let ServerEvent::ConversationItemAdded { item, .. } = added("e", None) else {
+            unreachable!()
+        };
+        log.observe_server(&ServerEvent::ConversationItemRetrieved {
+            event_id: "evt_retrieved".to_string(),
+            item,
+        });
+        assert_eq!(order(&log), ["a", "b", "c", "e", "f", "g"]);
+        assert!(log.missing().is_empty());
+    }
+
+    #[test]
+    fn out_of_band_items_stay_out_of_the_conversation() {
+        let response =
+            |id: &str, conversation_id: Option<&str>| crate::protocol::models::Response {
+                id: id.to_string(),
+                object: "realtime.response".to_string(),
+                conversation_id: conversation_id.map(str::to_string),
+                status: crate::protocol::models::ResponseStatus::InProgress,
+                status_details: None,
+                output: None,
+                output_modalities: None,
+                max_output_tokens: None,
+                audio: None,
+                metadata: None,
+                usage: None,
+            };
+        let mut log = ConversationLog::default();
+        for (response_id, conversation_id) in [("resp_oob", None), ("resp_1", Some("conv_1"))] {
+            log.observe_server(&ServerEvent::ResponseCreated {
+                event_id: format!("evt_{response_id}"),
+                response: response(response_id, conversation_id),
+            });
+            let ServerEvent::ConversationItemAdded { item, .. } = added(response_id, None) else {
+                unreachable!()
+            };
+            log.observe_server(&ServerEvent::ResponseOutputItemDone {
+                event_id: format!("evt_{response_id}_item"),
+                response_id: response_id.to_string(),
+                output_index: 0,
+                item,
+            });
+        }
+        assert_eq!(order(&log), ["resp_1"]);
+    }
+
+    #[test]
+    fn deleted_predecessors_are_not_gaps() {
+        let mut log = ConversationLog::default();
+        log.observe_server(&added("a", None));
+        log.observe_server(&ServerEvent::ConversationItemDeleted {
+            event_id: "evt_deleted".to_string(),
+            item_id: "a".to_string(),
+        });
+        assert_eq!(log.observe_server(&added("b", Some("a"))), None);
+        assert!(log.missing().is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Correlation of server `error` events with the client events that caused them.
+
+use crate::protocol::client_events::ClientEvent;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+/// How many recent sends are remembered for correlation.
+const SENT_EVENT_CAPACITY: usize = 64;
+const SUMMARY_MAX_BYTES: usize = 256;
+
+/// A recently sent client event, as remembered by the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SentEvent {
+    pub event_id: String,
+    pub event_type: Cow<'static, str>,
+    /// Truncated JSON of the event; audio payloads are omitted.
+    pub summary: String,
+}
+
+/// Bounded log of recently sent client events, keyed by event ID.
+#[derive(Debug, Default)]
+pub struct SentEventLog {
+    next_id: u64,
+    order: VecDeque<String>,
+    events: HashMap<String, SentEvent>,
+}
+
+impl SentEventLog {
+    /// Assign an event ID if the event has none, then remember the event.
+    pub fn record(&mut self, event: &mut ClientEvent) {
+        let event_id = if let Some(id) = event.event_id() {
+            id.to_string()
+        } else {
+            self.next_id += 1;
+            let id = format!("evt_sdk_{}", self.next_id);
+            event.set_event_id(id.clone());
+            id
+        };
+
+        let summary = match event {
+            ClientEvent::InputAudioBufferAppend { audio, .. } => {
+                format!("{} base64 bytes of audio", audio.len())
+            }
+            _ => serde_json::to_string(event).map_or_else(|err| err.to_string(), truncate),
+        };
+
+        if self.events.contains_key(&event_id) {
+            self.order.retain(|id| id != &event_id);
+        } else if self.order.len() == SENT_EVENT_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.events.remove(&oldest);
+            }
+        }
+        self.order.push_back(event_id.clone());
+        self.events.insert(
+            event_id.clone(),
+            SentEvent {
+                event_id,
+                event_type: Cow::Borrowed(event.event_type()),
+                summary,
+            },
+        );
+    }
+
+    #[must_use]
+    pub fn get(&self, event_id: &str) -> Option<&SentEvent> {
+        self.events.get(event_id)
+    }
+}
+
+fn truncate(mut json: String) -> String {
+    if json.len() > SUMMARY_MAX_BYTES {
+        let mut end = SUMMARY_MAX_BYTES;
+        while !json.is_char_boundary(end) {
+            end -= 1;
+        }
+        json.truncate(end);
+        json.push_str("...");
+    }
+    json
+}
@@ -0,0 +1,173 @@
+//! A wall-clock budget for a whole session, set with `Session::with_deadline`.
+
+use crate::protocol::models::{ResponseConfig, ToolChoice, ToolChoiceMode};
+use crate::protocol::server_events::ServerEvent;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Metadata key marking the response that says the closing message.
+const CLOSING_METADATA_KEY: &str = "sdk_deadline";
+
+/// When a session must end, and what it says on the way out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+    closing_message: Option<String>,
+    grace: Duration,
+}
+
+impl Deadline {
+    /// End the session at `at`, cancelling whatever is playing, without a goodbye.
+    ///
+    /// `at` is on tokio's clock, like the session's `Clock::now`, so the deadline
+    /// follows `tokio::time::pause` and `advance`.
+    #[must_use]
+    pub const fn new(at: Instant) -> Self {
+        Self {
+            at,
+            closing_message: None,
+            grace: Duration::from_secs(10),
+        }
+    }
+
+    /// Have the model say `text` once the deadline passes, then close.
+    #[must_use]
+    pub fn closing_message(mut self, text: impl Into<String>) -> Self {
+        self.closing_message = Some(text.into());
+        self
+    }
+
+    /// How long the closing message may take before the session closes anyway; 10
+    /// seconds by default.
+    #[must_use]
+    pub const fn grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+}
+
+impl From<Instant> for Deadline {
+    fn from(at: Instant) -> Self {
+        Self::new(at)
+    }
+}
+
+/// What the session does when the deadline passes, and as its closing message plays.
+#[derive(Debug, PartialEq)]
+pub enum Expired {
+    /// Close now.
+    Close,
+    /// Send this closing response now.
+    Say(Box<ResponseConfig>),
+    /// Send the closing response once the cancelled response has finished.
+    AfterCancel,
+}
+
+#[derive(Debug, Default)]
+enum Phase {
+    #[default]
+    Running,
+    /// Waiting for the cancelled response to finish before the closing message.
+    Cancelling {
+        response_id: String,
+        message: String,
+    },
+    /// The closing message is being said.
+    Closing,
+}
+
+/// The event loop's side of the deadline.
+#[derive(Debug, Default)]
+pub struct DeadlineState {
+    deadline: Option<Deadline>,
+    phase: Phase,
+    /// When the closing message runs out of time.
+    grace_until: Option<Instant>,
+}
+
+impl DeadlineState {
+    /// Set or replace the deadline. Once it has passed, a new one is ignored.
+    pub fn set(&mut self, deadline: Deadline) {
+        if matches!(self.phase, Phase::Running) {
+            self.deadline = Some(deadline);
+        }
+    }
+
+    /// When the timer should next fire.
+    pub fn next(&self) -> Option<Instant> {
+        match self.phase {
+            Phase::Running => Some(self.deadline.as_ref()?.at),
+            Phase::Cancelling { .. } | Phase::Closing => self.grace_until,
+        }
+    }
+
+    /// The timer fired at `now` with `active` playing, which the caller cancels. A
+    /// second firing means the closing message ran out of time.
+    pub fn expire(&mut self, active: Option<String>, now: Instant) -> Expired {
+        let Some(deadline) = self
+            .deadline
+            .as_ref()
+            .filter(|_| matches!(self.phase, Phase::Running))
+        else {
+            return Expired::Close;
+        };
+        let Some(message) = deadline.closing_message.clone() else {
+            return Expired::Close;
+        };
+        self.grace_until = Some(now + deadline.grace);
+        if let Some(response_id) = active {
+            self.phase = Phase::Cancelling {
+                response_id,
+                message,
+            };
+            Expired::AfterCancel
+        } else {
+            self.phase = Phase::Closing;
+            Expired::Say(Box::new(closing_response(&message)))
+        }
+    }
+
+    /// What to do after `evt`: say the closing message once the cancelled response
+    /// has finished, and close once the closing message has.
+    pub fn observe(&mut self, evt: &ServerEvent) -> Option<Expired> {
+        let (ServerEvent::ResponseDone { response, .. }
+        | ServerEvent::ResponseCancelled { response, .. }) = evt
+        else {
+            return None;
+        };
+        match &self.phase {
+            Phase::Running => None,
+            Phase::Cancelling {
+                response_id,
+                message,
+            } => {
+                if *response_id != response.id {
+                    return None;
+                }
+                let config = closing_response(message);
+                self.phase = Phase::Closing;
+                Some(Expired::Say(Box::new(config)))
+            }
+            Phase::Closing => response
+                .metadata
+                .as_ref()
+                .is_some_and(|metadata| metadata.contains_key(CLOSING_METADATA_KEY))
+                .then_some(Expired::Close),
+        }
+    }
+}
+
+fn closing_response(message: &str) -> ResponseConfig {
+    ResponseConfig {
+        metadata: Some(HashMap::from([(
+            CLOSING_METADATA_KEY.to_string(),
+            serde_json::Value::from("closing"),
+        )])),
+        instructions: Some(format!(
+            "The conversation is out of time. Say exactly this and nothing else: {message}"
+        )),
+        tool_choice: Some(ToolChoice::Mode(ToolChoiceMode::None)),
+        ..ResponseConfig::default()
+    }
+}
@@ -0,0 +1,90 @@
+//! Suppression of server events delivered more than once, e.g. after a reconnect.
+
+use super::channel;
+use super::events::SdkEvent;
+use super::transport::Transport;
+use crate::Result;
+use crate::protocol::server_events::ServerEvent;
+use std::collections::{HashSet, VecDeque};
+
+/// How many recent server event IDs are remembered.
+const SEEN_EVENT_CAPACITY: usize = 1024;
+
+/// Sliding window of server event IDs already handled.
+#[derive(Debug, Default)]
+pub struct SeenEvents {
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl SeenEvents {
+    /// Remember `evt`, returning `false` if its ID is already in the window.
+    /// Events without an ID always count as new.
+    pub fn first_delivery(&mut self, evt: &ServerEvent) -> bool {
+        let Some(event_id) = evt.event_id() else {
+            return true;
+        };
+        if self.ids.contains(event_id) {
+            return false;
+        }
+        if self.order.len() == SEEN_EVENT_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        self.order.push_back(event_id.to_string());
+        self.ids.insert(event_id.to_string());
+        true
+    }
+}
+
+/// The next server event not seen before.
+///
+/// Re-delivered deltas are dropped silently; other re-delivered events are state
+/// transitions the app may have acted on, so they are reported as
+/// `SdkEvent::Replayed` instead of being handled again.
+pub async fn next_fresh_event(
+    transport: &mut Box<dyn Transport>,
+    seen: &mut SeenEvents,
+    event_tx: &channel::Sender<SdkEvent>,
+) -> Result<Option<ServerEvent>> {
+    loop {
+        let Some(evt) = transport.next_event().await? else {
+            return Ok(None);
+        };
+        if seen.first_delivery(&evt) {
+            return Ok(Some(evt));
+        }
+        let event_id = evt.event_id().unwrap_or_default().to_string();
+        let event_type = evt.event_type();
+        tracing::debug!(%event_id, event_type, "dropping re-delivered server event");
+        if !evt.is_delta() {
+            let _ = event_tx
+                .send(SdkEvent::Replayed {
+                    event_id,
+                    event_type: event_type.to_string(),
+                })
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_forgets_oldest_ids() {
+        let cleared = |n: usize| ServerEvent::InputAudioBufferCleared {
+            event_id: format!("evt_{n}"),
+        };
+        let mut seen = SeenEvents::default();
+        assert!(seen.first_delivery(&cleared(0)));
+        assert!(!seen.first_delivery(&cleared(0)));
+        for n in 1..=SEEN_EVENT_CAPACITY {
+            assert!(seen.first_delivery(&cleared(n)));
+        }
+        assert!(seen.first_delivery(&cleared(0)));
+        assert!(!seen.first_delivery(&cleared(SEEN_EVENT_CAPACITY)));
+    }
+}
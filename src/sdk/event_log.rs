@@ -0,0 +1,338 @@
+//! JSONL persistence of raw client and server events for post-hoc debugging.
+
+use super::transport::{BoxFuture, Transport};
+use crate::Result;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::server_events::ServerEvent;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Records buffered between the session loop and the writer thread.
+const EVENT_LOG_CAPACITY: usize = 1024;
+/// Keys whose values are replaced in every logged event by default.
+const DEFAULT_REDACTED_KEYS: [&str; 3] = ["audio", "authorization", "headers"];
+
+/// When the event log starts a new file. Periods are in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationPolicy {
+    /// Append to the configured path forever.
+    Never,
+    /// `events.jsonl` becomes `events.2026-01-31T13.jsonl`.
+    Hourly,
+    /// `events.jsonl` becomes `events.2026-01-31.jsonl`.
+    Daily,
+}
+
+/// Configuration for the JSONL event sink.
+///
+/// Each line is `{"ts_ms", "direction", "event"}`. Audio payloads, MCP
+/// `authorization` and `headers` are redacted by default.
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+    rotation: RotationPolicy,
+    redacted_keys: HashSet<String>,
+}
+
+impl EventLog {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, rotation: RotationPolicy) -> Self {
+        Self {
+            path: path.into(),
+            rotation,
+            redacted_keys: DEFAULT_REDACTED_KEYS.map(String::from).into(),
+        }
+    }
+
+    /// Also redact the value of every object key named `key`.
+    #[must_use]
+    pub fn redact(mut self, key: impl Into<String>) -> Self {
+        self.redacted_keys.insert(key.into());
+        self
+    }
+
+    /// Stop redacting `key`, including the defaults.
+    #[must_use]
+    pub fn unredact(mut self, key: &str) -> Self {
+        self.redacted_keys.remove(key);
+        self
+    }
+
+    /// Start the writer thread.
+    #[must_use]
+    pub(crate) fn spawn(self) -> EventSink {
+        let (tx, rx) = mpsc::channel(EVENT_LOG_CAPACITY);
+        let thread = std::thread::Builder::new()
+            .name("oai-rt-event-log".to_string())
+            .spawn(move || self.write_all(rx))
+            .map_err(|err| tracing::warn!("event log disabled: {err}"))
+            .ok();
+        EventSink {
+            tx: thread.is_some().then_some(tx),
+            #[cfg(test)]
+            thread,
+            dropped: 0,
+        }
+    }
+
+    fn write_all(self, mut rx: mpsc::Receiver<Record>) {
+        let mut writer: Option<(PathBuf, BufWriter<File>)> = None;
+        while let Some(record) = rx.blocking_recv() {
+            let path = rotated_path(&self.path, self.rotation, record.at);
+            if writer.as_ref().is_none_or(|(current, _)| *current != path) {
+                writer = match OpenOptions::new().create(true).append(true).open(&path) {
+                    Ok(file) => Some((path, BufWriter::new(file))),
+                    Err(err) => {
+                        tracing::warn!("failed to open event log {}: {err}", path.display());
+                        None
+                    }
+                };
+            }
+            let Some((_, out)) = writer.as_mut() else {
+                continue;
+            };
+            let line = self.render(record);
+            let result = writeln!(out, "{line}")
+                .and_then(|()| if rx.is_empty() { out.flush() } else { Ok(()) });
+            if let Err(err) = result {
+                tracing::warn!("failed to write event log: {err}");
+            }
+        }
+        if let Some((_, mut out)) = writer {
+            let _ = out.flush();
+        }
+    }
+
+    fn render(&self, record: Record) -> Value {
+        let (direction, event) = match record.event {
+            Logged::Client(event) => ("client", serde_json::to_value(event)),
+            Logged::Server(event) => ("server", serde_json::to_value(*event)),
+        };
+        let mut event = event.unwrap_or_else(|err| Value::String(err.to_string()));
+        if event.get("type").and_then(Value::as_str) == Some("response.output_audio.delta") {
+            if let Some(delta) = event.get_mut("delta") {
+                redact_value(delta);
+            }
+        }
+        redact_keys(&mut event, &self.redacted_keys);
+        serde_json::json!({
+            "ts_ms": record.at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis()),
+            "direction": direction,
+            "event": event,
+        })
+    }
+}
+
+struct Record {
+    at: SystemTime,
+    event: Logged,
+}
+
+enum Logged {
+    Client(ClientEvent),
+    Server(Box<ServerEvent>),
+}
+
+/// Handle to the writer thread. Records are dropped rather than blocking the
+/// session when the writer falls behind.
+pub struct EventSink {
+    tx: Option<mpsc::Sender<Record>>,
+    #[cfg(test)]
+    thread: Option<std::thread::JoinHandle<()>>,
+    dropped: u64,
+}
+
+impl EventSink {
+    fn push(&mut self, event: Logged) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let record = Record {
+            at: SystemTime::now(),
+            event,
+        };
+        if tx.try_send(record).is_err() {
+            self.dropped += 1;
+            if self.dropped.is_power_of_two() {
+                tracing::warn!("event log is behind; {} events dropped", self.dropped);
+            }
+        }
+    }
+
+    /// Flush remaining records and wait for the writer to finish.
+    #[cfg(test)]
+    fn close(mut self) {
+        self.tx = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Transport decorator that mirrors every event into an [`EventSink`].
+pub struct LoggedTransport {
+    inner: Box<dyn Transport>,
+    sink: EventSink,
+}
+
+impl LoggedTransport {
+    pub fn new(inner: Box<dyn Transport>, log: EventLog) -> Self {
+        Self {
+            inner,
+            sink: log.spawn(),
+        }
+    }
+}
+
+impl Transport for LoggedTransport {
+    fn send(&mut self, event: ClientEvent) -> BoxFuture<'_, Result<()>> {
+        self.sink.push(Logged::Client(event.clone()));
+        self.inner.send(event)
+    }
+
+    fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+        Box::pin(async move {
+            let event = self.inner.next_event().await?;
+            if let Some(event) = &event {
+                self.sink.push(Logged::Server(Box::new(event.clone())));
+            }
+            Ok(event)
+        })
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.inner.close_frame()
+    }
+}
+
+fn redact_keys(value: &mut Value, keys: &HashSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, inner) in map.iter_mut() {
+                if keys.contains(key) {
+                    redact_value(inner);
+                } else {
+                    redact_keys(inner, keys);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_keys(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    *value = match value {
+        Value::Null => return,
+        Value::String(text) => Value::String(format!("[redacted {} bytes]", text.len())),
+        _ => Value::String("[redacted]".to_string()),
+    };
+}
+
+fn rotated_path(path: &Path, rotation: RotationPolicy, at: SystemTime) -> PathBuf {
+    let secs = at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let suffix = match rotation {
+        RotationPolicy::Never => return path.to_path_buf(),
+        RotationPolicy::Daily => format!("{year:04}-{month:02}-{day:02}"),
+        RotationPolicy::Hourly => {
+            format!("{year:04}-{month:02}-{day:02}T{:02}", secs % 86_400 / 3_600)
+        }
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = path.extension().map_or_else(
+        || format!("{stem}.{suffix}"),
+        |ext| format!("{stem}.{suffix}.{}", ext.to_string_lossy()),
+    );
+    path.with_file_name(name)
+}
+
+/// Gregorian date for a count of days since 1970-01-01.
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rotation_inserts_utc_period_before_extension() {
+        // 2024-02-29T13:05:00Z
+        let at = UNIX_EPOCH + Duration::from_secs(1_709_211_900);
+        let path = Path::new("/var/log/events.jsonl");
+        assert_eq!(rotated_path(path, RotationPolicy::Never, at), path);
+        assert_eq!(
+            rotated_path(path, RotationPolicy::Daily, at),
+            Path::new("/var/log/events.2024-02-29.jsonl")
+        );
+        assert_eq!(
+            rotated_path(Path::new("events"), RotationPolicy::Hourly, at),
+            Path::new("events.2024-02-29T13")
+        );
+    }
+
+    #[test]
+    fn sink_writes_redacted_jsonl() {
+        let path = std::env::temp_dir().join(format!(
+            "oai-rt-event-log-{}-{}.jsonl",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let mut sink = EventLog::new(&path, RotationPolicy::Never)
+            .redact("instructions")
+            .spawn();
+        sink.push(Logged::Client(ClientEvent::InputAudioBufferAppend {
+            event_id: None,
+            audio: "AAAA".to_string(),
+        }));
+        sink.push(Logged::Server(Box::new(
+            serde_json::from_value(serde_json::json!({
+                "type": "response.output_audio.delta",
+                "event_id": "evt_1",
+                "response_id": "resp_1",
+                "item_id": "item_1",
+                "output_index": 0,
+                "content_index": 0,
+                "delta": "AAAAAAAA",
+            }))
+            .unwrap(),
+        )));
+        sink.close();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["direction"], "client");
+        assert_eq!(lines[0]["event"]["audio"], "[redacted 4 bytes]");
+        assert_eq!(lines[1]["direction"], "server");
+        assert_eq!(lines[1]["event"]["delta"], "[redacted 8 bytes]");
+        assert_eq!(lines[1]["event"]["response_id"], "resp_1");
+    }
+}
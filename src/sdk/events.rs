@@ -1,13 +1,30 @@
+use super::analytics::ConversationAnalytics;
+use super::channel::{Receiver, Source};
+use super::conversation::ConversationGap;
+use super::correlation::SentEvent;
+use super::guardrails::{GuardrailStage, GuardrailVerdict};
+use super::ordering::OrderingViolation;
 use crate::error::ServerError;
-use crate::protocol::models::{ContentPart, Item, Usage};
+use crate::protocol::models::{ContentPart, Item, McpError, Session, Usage};
 use crate::protocol::server_events::ServerEvent;
 use futures::Stream;
+use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tokio::sync::mpsc;
 
-#[derive(Debug, Clone)]
+/// High-level session event.
+///
+/// Serializes as `{"type": "text_delta", "data": {...}}` so events can be forwarded to
+/// other processes as-is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum SdkEvent {
+    SessionCreated {
+        session: Box<Session>,
+    },
+    SessionUpdated {
+        session: Box<Session>,
+    },
     TextDelta {
         response_id: String,
         item_id: String,
@@ -72,6 +89,13 @@ pub enum SdkEvent {
         name: String,
         arguments: String,
     },
+    ToolCallStarted {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        call_id: String,
+        name: String,
+    },
     ToolCallDelta {
         response_id: String,
         item_id: String,
@@ -79,6 +103,48 @@ pub enum SdkEvent {
         call_id: String,
         delta: String,
     },
+    /// An MCP tool call in a response failed; `error` says how.
+    McpCallFailed {
+        response_id: String,
+        item_id: String,
+        call_id: String,
+        server_label: String,
+        name: String,
+        error: McpError,
+    },
+    /// The server added the `function_call_output` the session sent for `call_id`.
+    ToolOutputAccepted {
+        call_id: String,
+        item_id: String,
+    },
+    /// Server VAD detected the start of user speech.
+    SpeechStarted {
+        item_id: String,
+        audio_start_ms: u32,
+    },
+    /// Server VAD detected the end of user speech.
+    SpeechStopped {
+        item_id: String,
+        audio_end_ms: u32,
+    },
+    /// The input audio buffer was committed as user item `item_id`.
+    InputAudioCommitted {
+        item_id: String,
+        previous_item_id: Option<String>,
+    },
+    InputAudioCleared,
+    /// Output audio playback started (WebRTC/SIP only).
+    OutputAudioStarted {
+        response_id: String,
+    },
+    /// Output audio playback finished (WebRTC/SIP only).
+    OutputAudioStopped {
+        response_id: String,
+    },
+    /// Buffered output audio was discarded (WebRTC/SIP only).
+    OutputAudioCleared {
+        response_id: String,
+    },
     InputTranscriptionDelta {
         item_id: String,
         content_index: u32,
@@ -90,21 +156,111 @@ pub enum SdkEvent {
         transcript: String,
         usage: Option<Usage>,
     },
+    /// The user's audio in `item_id` could not be transcribed; see
+    /// `TranscriptionFailurePolicy` for automatic recovery.
+    InputTranscriptionFailed {
+        item_id: String,
+        content_index: u32,
+        error: ServerError,
+    },
     Error {
         event_id: String,
         error: ServerError,
     },
+    /// An `error` event whose `event_id` matches a recent send from this session.
+    ClientEventRejected {
+        event: SentEvent,
+        error: ServerError,
+    },
+    /// The server sent a response event out of its documented order.
+    OrderingViolation(OrderingViolation),
+    /// An item arrived after one that was never seen. The session retrieves the
+    /// missing item and slots it into the conversation history.
+    ConversationGap(ConversationGap),
+    /// A user handler, guardrail or tool call took at least the configured
+    /// `slow_handler_threshold`. Handlers run on the session's event loop, so this is
+    /// latency added to every event behind them.
+    SlowHandler {
+        name: String,
+        elapsed: std::time::Duration,
+    },
+    /// A user handler panicked; the session keeps running.
+    HandlerPanicked {
+        context: String,
+        message: String,
+    },
+    /// A guardrail returned something other than `Allow`.
+    GuardrailTriggered {
+        stage: GuardrailStage,
+        response_id: Option<String>,
+        verdict: GuardrailVerdict,
+    },
+    /// Output audio failed `failures` times in a row under
+    /// `AudioFailurePolicy::FallbackToText`, so the session switched to text output.
+    /// `reason` is the last failure.
+    TextFallback {
+        reason: String,
+        failures: u32,
+    },
+    /// The session expires in `in_seconds`, per `ExpiryPolicy::warn_before`.
+    SessionExpiring {
+        in_seconds: u64,
+    },
+    /// The session moved to a new connection ahead of expiry, replaying `items`
+    /// conversation items onto it.
+    SessionRenewed {
+        items: usize,
+    },
+    /// Opening or seeding the renewed session failed; the current one carries on
+    /// until it expires.
+    SessionRenewFailed {
+        message: String,
+    },
+    /// Periodic summary, emitted when an analytics interval is configured.
+    Analytics(ConversationAnalytics),
+    /// The server re-delivered an event that was already handled, e.g. after a
+    /// reconnect. It was not handled again.
+    Replayed {
+        event_id: String,
+        event_type: String,
+    },
+    /// The reader of `channel` fell behind and `dropped` messages have been discarded
+    /// so far. Reported at the first drop and each time the count doubles.
+    ConsumerLagging {
+        channel: String,
+        dropped: u64,
+    },
+    /// The reader of `channel` went away while the session was still producing for
+    /// it, e.g. a dropped `ResponseRoute`; its messages are discarded from now on and
+    /// counted in `Session::undelivered_messages`.
+    ConsumerClosed {
+        channel: String,
+    },
+    /// The server closed the WebSocket with this close frame; the last event of the
+    /// session. See `ServerClose::error` for what known codes mean.
+    ConnectionClosed {
+        code: u16,
+        reason: String,
+    },
     Raw(Box<ServerEvent>),
 }
 
 pub struct EventStream<'a> {
-    rx: &'a mut mpsc::Receiver<SdkEvent>,
+    rx: Source<'a, SdkEvent>,
 }
 
 impl<'a> EventStream<'a> {
     #[must_use]
-    pub const fn new(rx: &'a mut mpsc::Receiver<SdkEvent>) -> Self {
-        Self { rx }
+    pub const fn new(rx: &'a mut tokio::sync::mpsc::Receiver<SdkEvent>) -> Self {
+        Self {
+            rx: Source::Tokio(rx),
+        }
+    }
+
+    pub(crate) const fn from_session(rx: &'a mut Receiver<SdkEvent>) -> Self {
+        Self {
+            rx: Source::Session(rx),
+        }
     }
 }
 
@@ -113,7 +269,7 @@ impl Stream for EventStream<'_> {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
-        Pin::new(&mut this.rx).poll_recv(cx)
+        this.rx.poll_recv(cx)
     }
 }
 
@@ -121,9 +277,15 @@ impl SdkEvent {
     #[must_use]
     pub fn from_server(event: ServerEvent) -> Option<Self> {
         let boxed = Box::new(event);
+        if let Some(mapped) = map_session_ref(&boxed) {
+            return Some(mapped);
+        }
         if let Some(mapped) = map_response_ref(&boxed) {
             return Some(mapped);
         }
+        if let Some(mapped) = map_audio_buffer_ref(&boxed) {
+            return Some(mapped);
+        }
         if let Some(mapped) = map_transcription_ref(&boxed) {
             return Some(mapped);
         }
@@ -134,6 +296,18 @@ impl SdkEvent {
     }
 }
 
+fn map_session_ref(event: &ServerEvent) -> Option<SdkEvent> {
+    match event {
+        ServerEvent::SessionCreated { session, .. } => Some(SdkEvent::SessionCreated {
+            session: Box::new(session.clone()),
+        }),
+        ServerEvent::SessionUpdated { session, .. } => Some(SdkEvent::SessionUpdated {
+            session: Box::new(session.clone()),
+        }),
+        _ => None,
+    }
+}
+
 fn map_response_ref(event: &ServerEvent) -> Option<SdkEvent> {
     map_response_text(event)
         .or_else(|| map_response_audio(event))
@@ -273,6 +447,20 @@ fn map_response_content(event: &ServerEvent) -> Option<SdkEvent> {
 
 fn map_response_tool(event: &ServerEvent) -> Option<SdkEvent> {
     match event {
+        ServerEvent::ResponseOutputItemAdded {
+            response_id,
+            output_index,
+            item: Item::FunctionCall {
+                id, call_id, name, ..
+            },
+            ..
+        } => Some(tool_call_started(
+            response_id.clone(),
+            id.clone().unwrap_or_default(),
+            *output_index,
+            call_id.clone(),
+            name.clone(),
+        )),
         ServerEvent::ResponseFunctionCallArgumentsDelta {
             response_id,
             item_id,
@@ -303,6 +491,72 @@ fn map_response_tool(event: &ServerEvent) -> Option<SdkEvent> {
             name.clone(),
             arguments.clone(),
         )),
+        ServerEvent::ResponseOutputItemDone {
+            response_id,
+            item:
+                Item::McpCall {
+                    id,
+                    call_id,
+                    server_label,
+                    name,
+                    error: Some(error),
+                    ..
+                },
+            ..
+        } => Some(SdkEvent::McpCallFailed {
+            response_id: response_id.clone(),
+            item_id: id.clone().unwrap_or_default(),
+            call_id: call_id.clone(),
+            server_label: server_label.clone(),
+            name: name.clone(),
+            error: error.clone(),
+        }),
+        _ => None,
+    }
+}
+
+fn map_audio_buffer_ref(event: &ServerEvent) -> Option<SdkEvent> {
+    match event {
+        ServerEvent::InputAudioBufferSpeechStarted {
+            item_id,
+            audio_start_ms,
+            ..
+        } => Some(SdkEvent::SpeechStarted {
+            item_id: item_id.clone(),
+            audio_start_ms: *audio_start_ms,
+        }),
+        ServerEvent::InputAudioBufferSpeechStopped {
+            item_id,
+            audio_end_ms,
+            ..
+        } => Some(SdkEvent::SpeechStopped {
+            item_id: item_id.clone(),
+            audio_end_ms: *audio_end_ms,
+        }),
+        ServerEvent::InputAudioBufferCommitted {
+            item_id,
+            previous_item_id,
+            ..
+        } => Some(SdkEvent::InputAudioCommitted {
+            item_id: item_id.clone(),
+            previous_item_id: previous_item_id.clone(),
+        }),
+        ServerEvent::InputAudioBufferCleared { .. } => Some(SdkEvent::InputAudioCleared),
+        ServerEvent::OutputAudioBufferStarted { response_id, .. } => {
+            Some(SdkEvent::OutputAudioStarted {
+                response_id: response_id.clone(),
+            })
+        }
+        ServerEvent::OutputAudioBufferStopped { response_id, .. } => {
+            Some(SdkEvent::OutputAudioStopped {
+                response_id: response_id.clone(),
+            })
+        }
+        ServerEvent::OutputAudioBufferCleared { response_id, .. } => {
+            Some(SdkEvent::OutputAudioCleared {
+                response_id: response_id.clone(),
+            })
+        }
         _ => None,
     }
 }
@@ -331,6 +585,16 @@ fn map_transcription_ref(event: &ServerEvent) -> Option<SdkEvent> {
             transcript.clone(),
             usage.clone(),
         )),
+        ServerEvent::InputAudioTranscriptionFailed {
+            item_id,
+            content_index,
+            error,
+            ..
+        } => Some(SdkEvent::InputTranscriptionFailed {
+            item_id: item_id.clone(),
+            content_index: *content_index,
+            error: error.clone(),
+        }),
         _ => None,
     }
 }
@@ -472,6 +736,22 @@ const fn content_part_done(
     }
 }
 
+const fn tool_call_started(
+    response_id: String,
+    item_id: String,
+    output_index: u32,
+    call_id: String,
+    name: String,
+) -> SdkEvent {
+    SdkEvent::ToolCallStarted {
+        response_id,
+        item_id,
+        output_index,
+        call_id,
+        name,
+    }
+}
+
 const fn tool_call_delta(
     response_id: String,
     item_id: String,
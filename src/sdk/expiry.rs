@@ -0,0 +1,297 @@
+//! Warning before the server's session time limit, and renewing the session on a new
+//! connection before it is reached.
+
+use super::clock::Clock;
+use super::tools::BoxFuture;
+use super::transport::Transport;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{Nullable, SessionConfig};
+use crate::protocol::server_events::ServerEvent;
+use crate::{Error, Result};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Opens the connection a session is renewed onto.
+pub type Reconnect = Arc<dyn Fn() -> BoxFuture<Result<Box<dyn Transport>>> + Send + Sync>;
+
+/// What the session does as the `expires_at` the server reported approaches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpiryPolicy {
+    /// Emit `SdkEvent::SessionExpiring` this long before the session expires.
+    pub warn_before: Duration,
+    /// Open a new session this long before expiry, carry the configuration and
+    /// conversation over, and continue on it. `None` lets the session expire.
+    pub renew_before: Option<Duration>,
+}
+
+impl Default for ExpiryPolicy {
+    fn default() -> Self {
+        Self {
+            warn_before: Duration::from_secs(60),
+            renew_before: None,
+        }
+    }
+}
+
+impl ExpiryPolicy {
+    /// Warn a minute ahead, and renew `before` expiry.
+    #[must_use]
+    pub const fn renew(before: Duration) -> Self {
+        Self {
+            warn_before: Duration::from_secs(60),
+            renew_before: Some(before),
+        }
+    }
+}
+
+/// The current session's expiry and what has been done about it.
+#[derive(Debug, Default)]
+pub struct ExpiryMonitor {
+    /// `expires_at` in Unix seconds, and the instant it falls on.
+    expires: Option<(u64, Instant)>,
+    warned: bool,
+    renewing: bool,
+}
+
+impl ExpiryMonitor {
+    /// Pick up `expires_at` from `session.created` and `session.updated`. A new
+    /// expiry, as a renewed session reports, starts over.
+    pub fn observe(&mut self, evt: &ServerEvent, clock: &dyn Clock) {
+        let (ServerEvent::SessionCreated { session, .. }
+        | ServerEvent::SessionUpdated { session, .. }) = evt
+        else {
+            return;
+        };
+        let expires_at = session.expires_at;
+        if expires_at == 0 || self.expires.is_some_and(|(at, _)| at == expires_at) {
+            return;
+        }
+        let now = clock
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let left = Duration::from_secs(expires_at).saturating_sub(now);
+        *self = Self {
+            expires: Some((expires_at, clock.now() + left)),
+            ..Self::default()
+        };
+    }
+
+    /// When the next warning or renewal is due.
+    pub fn deadline(&self, policy: &ExpiryPolicy) -> Option<Instant> {
+        let (_, expires) = self.expires?;
+        let warn = (!self.warned).then(|| before(expires, policy.warn_before));
+        let renew = policy
+            .renew_before
+            .filter(|_| !self.renewing)
+            .map(|lead| before(expires, lead));
+        warn.into_iter().chain(renew).min()
+    }
+
+    /// Whole seconds left, the first time the warning is due at `now`.
+    pub fn take_warning(&mut self, policy: &ExpiryPolicy, now: Instant) -> Option<u64> {
+        let (_, expires) = self.expires?;
+        if self.warned || now < before(expires, policy.warn_before) {
+            return None;
+        }
+        self.warned = true;
+        Some(expires.saturating_duration_since(now).as_secs())
+    }
+
+    /// Whether renewal is due at `now` and has not been started.
+    pub fn take_renewal(&mut self, policy: &ExpiryPolicy, now: Instant) -> bool {
+        let (Some((_, expires)), Some(lead)) = (self.expires, policy.renew_before) else {
+            return false;
+        };
+        if self.renewing || now < before(expires, lead) {
+            return false;
+        }
+        self.renewing = true;
+        true
+    }
+}
+
+fn before(instant: Instant, lead: Duration) -> Instant {
+    instant.checked_sub(lead).unwrap_or_else(Instant::now)
+}
+
+/// The connection a session is being renewed onto, while it opens and then until
+/// the session can switch to it.
+#[derive(Default)]
+pub struct Renewal {
+    opening: Option<JoinHandle<Result<Box<dyn Transport>>>>,
+    ready: Option<Box<dyn Transport>>,
+}
+
+impl Renewal {
+    pub fn start(&mut self, reconnect: &Reconnect) {
+        self.opening = Some(tokio::spawn(reconnect()));
+    }
+
+    /// Wait for the connection being opened; never returns while none is.
+    pub async fn opened(&mut self) -> Result<Box<dyn Transport>> {
+        let Some(opening) = self.opening.as_mut() else {
+            return std::future::pending().await;
+        };
+        let result = opening.await;
+        self.opening = None;
+        result.unwrap_or_else(|err| {
+            Err(if err.is_cancelled() {
+                Error::Cancelled
+            } else {
+                Error::Io(std::io::Error::other(err))
+            })
+        })
+    }
+
+    pub fn hold(&mut self, transport: Box<dyn Transport>) {
+        self.ready = Some(transport);
+    }
+
+    pub const fn is_ready(&self) -> bool {
+        self.ready.is_some()
+    }
+
+    pub const fn take(&mut self) -> Option<Box<dyn Transport>> {
+        self.ready.take()
+    }
+}
+
+/// Whether the user is in the middle of a turn, which a renewal must not cut off:
+/// speech server VAD detected and has not seen end, or, without turn detection, audio
+/// appended and not yet committed or cleared.
+#[derive(Debug, Default)]
+pub struct UserTurn {
+    speaking: bool,
+    uncommitted: bool,
+}
+
+impl UserTurn {
+    pub const fn observe_client(&mut self, event: &ClientEvent) {
+        if let ClientEvent::InputAudioBufferAppend { .. } = event {
+            self.uncommitted = true;
+        }
+    }
+
+    pub const fn observe_server(&mut self, evt: &ServerEvent) {
+        match evt {
+            ServerEvent::InputAudioBufferSpeechStarted { .. } => self.speaking = true,
+            ServerEvent::InputAudioBufferSpeechStopped { .. } => self.speaking = false,
+            ServerEvent::InputAudioBufferCommitted { .. }
+            | ServerEvent::InputAudioBufferCleared { .. } => {
+                self.speaking = false;
+                self.uncommitted = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether nothing the user said would be lost by switching connections. Under
+    /// turn detection the server commits speech itself, and audio it has not heard
+    /// speech in is silence.
+    pub fn is_idle(&self, config: Option<&SessionConfig>) -> bool {
+        !self.speaking && (!self.uncommitted || config.is_some_and(detects_turns))
+    }
+}
+
+fn detects_turns(config: &SessionConfig) -> bool {
+    let input = config
+        .audio
+        .as_ref()
+        .and_then(|audio| audio.input.as_ref())
+        .and_then(|input| input.turn_detection.as_ref());
+    matches!(
+        input.or(config.turn_detection.as_ref()),
+        Some(Nullable::Value(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::clock::TokioClock;
+    use std::time::SystemTime;
+
+    fn created(expires_at: u64) -> ServerEvent {
+        use crate::protocol::models::{OutputModalities, SessionConfig, SessionKind};
+        ServerEvent::SessionCreated {
+            event_id: "evt_1".to_string(),
+            session: crate::protocol::models::Session {
+                id: "sess_1".to_string(),
+                object: "realtime.session".to_string(),
+                expires_at,
+                config: SessionConfig::new(
+                    SessionKind::Realtime,
+                    "gpt-realtime",
+                    OutputModalities::Audio,
+                ),
+            },
+        }
+    }
+
+    #[test]
+    fn warns_and_renews_once_per_expiry() {
+        let policy = ExpiryPolicy::renew(Duration::from_secs(30));
+        let mut monitor = ExpiryMonitor::default();
+        assert_eq!(monitor.deadline(&policy), None);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        monitor.observe(&created(now.as_secs() + 600), &TokioClock);
+        let deadline = monitor.deadline(&policy).unwrap();
+        assert!(deadline > Instant::now() + Duration::from_secs(500));
+        assert_eq!(monitor.take_warning(&policy, Instant::now()), None);
+        assert!(!monitor.take_renewal(&policy, Instant::now()));
+
+        let late = Instant::now() + Duration::from_secs(590);
+        assert!(monitor.take_warning(&policy, late).unwrap() <= 10);
+        assert!(monitor.take_renewal(&policy, late));
+        assert_eq!(monitor.take_warning(&policy, late), None);
+        assert!(!monitor.take_renewal(&policy, late));
+        assert_eq!(monitor.deadline(&policy), None);
+
+        // The renewed session's expiry starts over.
+        monitor.observe(&created(now.as_secs() + 1200), &TokioClock);
+        assert!(monitor.deadline(&policy).is_some());
+    }
+
+    #[test]
+    fn user_turn_holds_renewal_until_speech_and_buffer_settle() {
+        use crate::protocol::models::{OutputModalities, SessionKind, TurnDetection};
+        let manual = SessionConfig::new(
+            SessionKind::Realtime,
+            "gpt-realtime",
+            OutputModalities::Audio,
+        );
+        let mut vad = manual.clone();
+        vad.turn_detection = Some(Nullable::Value(TurnDetection::SemanticVad {
+            eagerness: None,
+            create_response: None,
+            interrupt_response: None,
+        }));
+        let mut turn = UserTurn::default();
+        assert!(turn.is_idle(Some(&manual)));
+
+        turn.observe_client(&ClientEvent::InputAudioBufferAppend {
+            event_id: None,
+            audio: "AAAA".to_string(),
+        });
+        assert!(!turn.is_idle(Some(&manual)));
+        assert!(turn.is_idle(Some(&vad)));
+
+        turn.observe_server(&ServerEvent::InputAudioBufferSpeechStarted {
+            event_id: "evt_1".to_string(),
+            audio_start_ms: 0,
+            item_id: "item_1".to_string(),
+        });
+        assert!(!turn.is_idle(Some(&vad)));
+
+        turn.observe_server(&ServerEvent::InputAudioBufferCommitted {
+            event_id: "evt_2".to_string(),
+            previous_item_id: None,
+            item_id: "item_1".to_string(),
+        });
+        assert!(turn.is_idle(Some(&manual)));
+    }
+}
@@ -0,0 +1,121 @@
+//! What-if branches of the conversation, answered out of band.
+
+use super::handoff::part_text;
+use super::oob::{self, OOB_METADATA_KEY};
+use super::session::SessionHandle;
+use crate::Result;
+use crate::protocol::models::{
+    ContentPart, ConversationMode, InputItem, Item, OutputModalities, Response, ResponseConfig,
+    Role, ToolChoice, ToolChoiceMode,
+};
+use std::collections::HashMap;
+
+/// A copy of the conversation for speculative responses, from `Session::fork`.
+///
+/// The branch starts with references to every item the session had seen when it was
+/// forked. Turns added with [`Self::say`] and the replies from [`Self::respond`] only
+/// extend the branch: each response runs with `conversation: none`, so nothing is
+/// added to the live conversation. Clone a fork to try several continuations from the
+/// same point. To continue on a separate connection instead, use `handoff`.
+#[derive(Clone)]
+pub struct ConversationFork {
+    session: SessionHandle,
+    input: Vec<InputItem>,
+}
+
+impl ConversationFork {
+    pub(super) fn new(session: SessionHandle, items: &[Item]) -> Self {
+        let input = items
+            .iter()
+            .filter_map(Item::id)
+            .map(|id| InputItem::ItemReference { id: id.to_string() })
+            .collect();
+        Self { session, input }
+    }
+
+    /// The branch so far, as sent with each response.
+    #[must_use]
+    pub fn input(&self) -> &[InputItem] {
+        &self.input
+    }
+
+    /// Add a hypothetical user turn to the branch.
+    pub fn say(&mut self, text: impl Into<String>) -> &mut Self {
+        self.push(InputItem::Message {
+            id: None,
+            role: Role::User,
+            content: vec![ContentPart::InputText { text: text.into() }],
+        })
+    }
+
+    /// Add any input item to the branch.
+    pub fn push(&mut self, item: InputItem) -> &mut Self {
+        self.input.push(item);
+        self
+    }
+
+    /// Get a text response to the branch under the session's instructions, and add
+    /// its messages to the branch.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails, the session closes, or the response does
+    /// not complete.
+    pub async fn respond(&mut self) -> Result<Response> {
+        self.run(None).await
+    }
+
+    /// Like [`Self::respond`], with `instructions` in place of the session's.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails, the session closes, or the response does
+    /// not complete.
+    pub async fn respond_with(&mut self, instructions: &str) -> Result<Response> {
+        self.run(Some(instructions.to_string())).await
+    }
+
+    async fn run(&mut self, instructions: Option<String>) -> Result<Response> {
+        let id = oob::next_oob_id();
+        let config = ResponseConfig {
+            conversation: Some(ConversationMode::None),
+            metadata: Some(HashMap::from([(
+                OOB_METADATA_KEY.to_string(),
+                serde_json::Value::from(id.as_str()),
+            )])),
+            output_modalities: Some(OutputModalities::Text),
+            instructions,
+            tool_choice: Some(ToolChoice::Mode(ToolChoiceMode::None)),
+            input: Some(self.input.clone()),
+            ..ResponseConfig::default()
+        };
+        let response = oob::completed(self.session.out_of_band(id, config).await?)?;
+        self.input
+            .extend(response.output.iter().flatten().filter_map(branch_message));
+        Ok(response)
+    }
+}
+
+impl std::fmt::Debug for ConversationFork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConversationFork")
+            .field("input", &self.input)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An output message as branch input. Output items are not stored on the server, so
+/// they are copied as text.
+fn branch_message(item: &Item) -> Option<InputItem> {
+    let Item::Message { role, content, .. } = item else {
+        return None;
+    };
+    let content: Vec<ContentPart> = content
+        .iter()
+        .filter_map(part_text)
+        .map(|text| ContentPart::OutputText { text })
+        .collect();
+    (!content.is_empty()).then_some(InputItem::Message {
+        id: None,
+        role: *role,
+        content,
+    })
+}
@@ -0,0 +1,117 @@
+//! Guardrail hooks for outgoing user text and incoming assistant text.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Point in the conversation where a guardrail runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailStage {
+    /// User text sent through `say`.
+    UserInput,
+    /// A single assistant text delta.
+    AssistantDelta,
+    /// The complete assistant text of a content part.
+    AssistantDone,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailVerdict {
+    Allow,
+    /// Replace the text with the given content.
+    Redact(String),
+    /// Drop the text. Assistant output also cancels the response.
+    Block {
+        reason: String,
+    },
+    /// Let the text through and attach a note.
+    Annotate(String),
+}
+
+#[async_trait::async_trait]
+pub trait Guardrail: Send + Sync {
+    async fn check(&self, stage: GuardrailStage, text: &str) -> Result<GuardrailVerdict>;
+}
+
+/// Outcome of running every guardrail over a piece of text.
+#[derive(Debug, Clone)]
+pub struct GuardrailReport {
+    /// Text after redactions.
+    pub text: String,
+    /// Verdicts other than `Allow`, in the order they were produced.
+    pub verdicts: Vec<GuardrailVerdict>,
+}
+
+impl GuardrailReport {
+    /// Returns the block reason if a guardrail blocked the text.
+    #[must_use]
+    pub fn blocked(&self) -> Option<&str> {
+        self.verdicts.iter().find_map(|verdict| match verdict {
+            GuardrailVerdict::Block { reason } => Some(reason.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Ordered guardrail pipeline. Redactions feed into later guardrails and the
+/// first block short-circuits the rest.
+#[derive(Clone, Default)]
+pub struct Guardrails {
+    stages: Vec<Arc<dyn Guardrail>>,
+}
+
+impl Guardrails {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, guardrail: impl Guardrail + 'static) {
+        self.stages.push(Arc::new(guardrail));
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Append the guardrails of `other`, which run after the existing ones.
+    pub fn extend(&mut self, other: &Self) {
+        self.stages.extend(other.stages.iter().cloned());
+    }
+
+    /// Run all guardrails over `text`.
+    ///
+    /// # Errors
+    /// Returns the first error reported by a guardrail.
+    pub async fn run(&self, stage: GuardrailStage, text: String) -> Result<GuardrailReport> {
+        let mut report = GuardrailReport {
+            text,
+            verdicts: Vec::new(),
+        };
+        for guardrail in &self.stages {
+            let verdict = guardrail.check(stage, &report.text).await?;
+            match &verdict {
+                GuardrailVerdict::Allow => continue,
+                GuardrailVerdict::Redact(replacement) => report.text.clone_from(replacement),
+                GuardrailVerdict::Block { .. } => {
+                    report.verdicts.push(verdict);
+                    break;
+                }
+                GuardrailVerdict::Annotate(_) => {}
+            }
+            report.verdicts.push(verdict);
+        }
+        Ok(report)
+    }
+}
+
+impl std::fmt::Debug for Guardrails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Guardrails")
+            .field("len", &self.stages.len())
+            .finish()
+    }
+}
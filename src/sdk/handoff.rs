@@ -0,0 +1,240 @@
+//! Moving a conversation to a new session, e.g. escalating to a larger model.
+
+use crate::Result;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{ContentPart, Item, Role};
+
+use super::agent::Agent;
+use super::builder::RealtimeBuilder;
+use super::session::Session;
+
+const SUMMARY_INSTRUCTIONS: &str = "Summarize this conversation for the agent taking it \
+over: who the user is, what they asked for, what has been done and what is still open. \
+Reply with the summary only.";
+
+/// How much of the conversation the new session receives.
+#[derive(Debug, Clone, Default)]
+pub enum HandoffContext {
+    /// Replay every message and tool call, with audio replaced by its transcript.
+    #[default]
+    Full,
+    /// Replay only the last `n` items, reaching back further for the call of any tool
+    /// output among them.
+    Recent(usize),
+    /// Replay a summary written by the source session's model out of band.
+    Summary,
+    /// Start the new session without context.
+    None,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HandoffOptions {
+    /// Defaults to [`HandoffContext::Full`].
+    pub context: HandoffContext,
+    /// Agent to attach to the new session before the context is replayed.
+    pub agent: Option<Agent>,
+    /// Re-append input audio the source received since its last commit, so speech that
+    /// was in progress during the handoff is not lost.
+    pub bridge_audio: bool,
+}
+
+/// Open a session from `to` and carry the conversation of `from` over to it.
+///
+/// `from` keeps running; close it once the new session has taken over.
+///
+/// # Errors
+/// Returns an error if the summary fails, the new session cannot connect, or
+/// replaying the context fails.
+pub async fn handoff(
+    from: &Session,
+    to: impl Into<RealtimeBuilder>,
+    options: HandoffOptions,
+) -> Result<Session> {
+    let snapshot = from.conversation_snapshot().await?;
+    let items: Vec<Item> = snapshot.items.iter().filter_map(replay_item).collect();
+    let context = match options.context {
+        HandoffContext::Full => items,
+        HandoffContext::Recent(n) => recent(&items, n).to_vec(),
+        HandoffContext::Summary if items.is_empty() => Vec::new(),
+        HandoffContext::Summary => {
+            let summary: String = from
+                .classify(render_transcript(&items), SUMMARY_INSTRUCTIONS)
+                .await?;
+            vec![system_message(format!(
+                "Summary of the conversation so far:\n{summary}"
+            ))]
+        }
+        HandoffContext::None => Vec::new(),
+    };
+
+    // Boxed: the connect future is large and would otherwise inflate every caller's.
+    let session = Box::pin(to.into().connect_ws()).await?;
+    if let Some(agent) = &options.agent {
+        agent.attach(&session).await?;
+    }
+    for item in context {
+        session
+            .send_raw(ClientEvent::ConversationItemCreate {
+                event_id: None,
+                previous_item_id: None,
+                item: Box::new(item),
+            })
+            .await?;
+    }
+    if options.bridge_audio {
+        for audio in snapshot.input_audio {
+            session
+                .send_raw(ClientEvent::InputAudioBufferAppend {
+                    event_id: None,
+                    audio,
+                })
+                .await?;
+        }
+    }
+    Ok(session)
+}
+
+/// A copy of `item` that can be created in another conversation, or `None` if it
+/// carries nothing worth replaying.
+pub fn replay_item(item: &Item) -> Option<Item> {
+    match item {
+        Item::Message { role, content, .. } => {
+            let content: Vec<ContentPart> = content
+                .iter()
+                .filter_map(part_text)
+                .map(|text| match role {
+                    Role::Assistant => ContentPart::OutputText { text },
+                    _ => ContentPart::InputText { text },
+                })
+                .collect();
+            (!content.is_empty()).then_some(Item::Message {
+                id: None,
+                status: None,
+                role: *role,
+                content,
+            })
+        }
+        Item::FunctionCall {
+            name,
+            call_id,
+            arguments,
+            ..
+        } => Some(Item::FunctionCall {
+            id: None,
+            status: None,
+            name: name.clone(),
+            call_id: call_id.clone(),
+            arguments: arguments.clone(),
+        }),
+        Item::FunctionCallOutput {
+            call_id, output, ..
+        } => Some(Item::FunctionCallOutput {
+            id: None,
+            call_id: call_id.clone(),
+            output: output.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// The last `n` of `items`, widened so that no tool output is cut off from its call.
+fn recent(items: &[Item], n: usize) -> &[Item] {
+    let mut start = items.len().saturating_sub(n);
+    let call_of = |output: &Item, before: usize| {
+        let Item::FunctionCallOutput { call_id, .. } = output else {
+            return None;
+        };
+        items[..before].iter().position(
+            |item| matches!(item, Item::FunctionCall { call_id: id, .. } if id == call_id),
+        )
+    };
+    while let Some(call) = items[start..].iter().find_map(|item| call_of(item, start)) {
+        start = call;
+    }
+    &items[start..]
+}
+
+pub(super) fn part_text(part: &ContentPart) -> Option<String> {
+    match part {
+        ContentPart::InputText { text }
+        | ContentPart::OutputText { text }
+        | ContentPart::Text { text } => Some(text.clone()),
+        ContentPart::InputAudio { transcript, .. }
+        | ContentPart::OutputAudio { transcript, .. } => transcript.clone(),
+        _ => None,
+    }
+}
+
+fn render_transcript(items: &[Item]) -> String {
+    let mut lines = Vec::new();
+    for item in items {
+        match item {
+            Item::Message { role, content, .. } => {
+                let speaker = match role {
+                    Role::User => "user",
+                    Role::Assistant => "assistant",
+                    Role::System => "system",
+                };
+                for text in content.iter().filter_map(part_text) {
+                    lines.push(format!("{speaker}: {text}"));
+                }
+            }
+            Item::FunctionCall {
+                name, arguments, ..
+            } => lines.push(format!("tool call: {name}({arguments})")),
+            Item::FunctionCallOutput { output, .. } => lines.push(format!("tool output: {output}")),
+            _ => {}
+        }
+    }
+    lines.join("\n")
+}
+
+fn system_message(text: String) -> Item {
+    Item::Message {
+        id: None,
+        status: None,
+        role: Role::System,
+        content: vec![ContentPart::InputText { text }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Item {
+        system_message(text.to_string())
+    }
+
+    fn call(call_id: &str) -> Item {
+        Item::FunctionCall {
+            id: None,
+            status: None,
+            name: "lookup".to_string(),
+            call_id: call_id.to_string(),
+            arguments: "{}".to_string(),
+        }
+    }
+
+    fn output(call_id: &str) -> Item {
+        Item::FunctionCallOutput {
+            id: None,
+            call_id: call_id.to_string(),
+            output: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn recent_keeps_tool_outputs_with_their_calls() {
+        let items = [
+            text("hi"),
+            call("call_1"),
+            text("checking"),
+            output("call_1"),
+            text("done"),
+        ];
+        assert_eq!(recent(&items, 1), &items[4..]);
+        assert_eq!(recent(&items, 2), &items[1..]);
+        assert_eq!(recent(&items, 9), &items[..]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Temporary instruction changes that can be rolled back, and the response language
+//! appended to them.
+
+use crate::Result;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::{SessionUpdate, SessionUpdateConfig};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::session::SessionHandle;
+
+/// Common ISO 639-1 codes and the language names used in instructions.
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+/// Instructions applied through `Session::set_instructions`, oldest first, and the
+/// language from `Session::set_response_language`.
+#[derive(Clone, Default)]
+pub struct InstructionHistory(Arc<Mutex<History>>);
+
+#[derive(Default)]
+struct History {
+    /// Each entry with the generation its guard was given.
+    entries: Vec<(u64, String)>,
+    next_generation: u64,
+    /// What was active before the oldest entry, when known.
+    base: Option<String>,
+    /// The instructions the server reported in `session.created`.
+    default: Option<String>,
+    language: Option<String>,
+}
+
+impl InstructionHistory {
+    /// The most recently applied instructions.
+    pub fn current(&self) -> Option<String> {
+        self.lock().entries.last().map(|(_, text)| text.clone())
+    }
+
+    pub fn snapshot(&self) -> Vec<String> {
+        self.lock()
+            .entries
+            .iter()
+            .map(|(_, text)| text.clone())
+            .collect()
+    }
+
+    /// Record `text`, applied over `previous`, and return the generation that
+    /// identifies it.
+    pub fn push(&self, text: String, previous: Option<String>) -> u64 {
+        let mut history = self.lock();
+        if history.entries.is_empty() {
+            history.base = previous;
+        }
+        let generation = history.next_generation;
+        history.next_generation += 1;
+        history.entries.push((generation, text));
+        generation
+    }
+
+    /// Forget the instructions; the response language and session default stay.
+    pub fn clear(&self) {
+        let mut history = self.lock();
+        history.entries.clear();
+        history.base = None;
+    }
+
+    /// Note the instructions the session was created with, restored when everything
+    /// set since is rolled back.
+    pub fn set_default(&self, instructions: Option<String>) {
+        self.lock().default = instructions;
+    }
+
+    pub fn language(&self) -> Option<String> {
+        self.lock().language.clone()
+    }
+
+    pub fn set_language(&self, language: Option<String>) {
+        self.lock().language = language;
+    }
+
+    /// `text` with the active response language appended, as sent to the server.
+    pub fn with_language(&self, text: &str) -> String {
+        with_language(text, self.lock().language.as_deref())
+    }
+
+    /// What rolling back `generation` restores: the entry below it, else what was
+    /// active before the first, else the session default. `None` once rolled back.
+    fn restored(&self, generation: u64) -> Option<String> {
+        let history = self.lock();
+        let index = history.position(generation)?;
+        let text = index.checked_sub(1).map_or_else(
+            || {
+                history
+                    .base
+                    .clone()
+                    .or_else(|| history.default.clone())
+                    .unwrap_or_default()
+            },
+            |below| history.entries[below].1.clone(),
+        );
+        drop(history);
+        Some(text)
+    }
+
+    /// Drop the entry for `generation` and everything applied after it.
+    fn truncate(&self, generation: u64) {
+        let mut history = self.lock();
+        if let Some(index) = history.position(generation) {
+            history.entries.truncate(index);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, History> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl History {
+    fn position(&self, generation: u64) -> Option<usize> {
+        self.entries.iter().position(|(id, _)| *id == generation)
+    }
+}
+
+/// The sentence that pins responses to `language`, e.g. `"es"` or `"pt-BR"`. Codes
+/// without a known name are used as given.
+fn language_sentence(language: &str) -> String {
+    let code = transcription_language(language);
+    let name = LANGUAGE_NAMES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map_or(language, |(_, name)| name);
+    format!("Always respond in {name}, even if the user speaks another language.")
+}
+
+/// The primary subtag of `language`, as input transcription expects it.
+pub fn transcription_language(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// `text` followed by the sentence for `language`, if any.
+pub fn with_language(text: &str, language: Option<&str>) -> String {
+    let Some(language) = language else {
+        return text.to_string();
+    };
+    let sentence = language_sentence(language);
+    if text.is_empty() {
+        sentence
+    } else {
+        format!("{text}\n\n{sentence}")
+    }
+}
+
+/// `text` without the sentence [`with_language`] added for `language`.
+pub fn without_language(text: &str, language: Option<&str>) -> String {
+    let Some(language) = language else {
+        return text.to_string();
+    };
+    let sentence = language_sentence(language);
+    text.strip_suffix(sentence.as_str())
+        .map_or(text, |rest| rest.trim_end_matches('\n'))
+        .to_string()
+}
+
+/// Restores the instructions that were active before `Session::set_instructions`.
+///
+/// Dropping the guard keeps the new instructions. Rolling back an outer guard also
+/// discards instructions set after it, so inner guards become no-ops, even once new
+/// instructions are set.
+pub struct InstructionGuard {
+    handle: SessionHandle,
+    history: InstructionHistory,
+    generation: u64,
+    previous: Option<String>,
+}
+
+impl InstructionGuard {
+    pub(super) const fn new(
+        handle: SessionHandle,
+        history: InstructionHistory,
+        generation: u64,
+        previous: Option<String>,
+    ) -> Self {
+        Self {
+            handle,
+            history,
+            generation,
+            previous,
+        }
+    }
+
+    /// Instructions that `rollback` restores; `None` restores the ones the session was
+    /// created with.
+    #[must_use]
+    pub fn previous(&self) -> Option<&str> {
+        self.previous.as_deref()
+    }
+
+    /// Send the previous instructions back to the server.
+    ///
+    /// # Errors
+    /// Returns an error if the session update fails.
+    pub async fn rollback(self) -> Result<()> {
+        let Some(restored) = self.history.restored(self.generation) else {
+            return Ok(());
+        };
+        self.handle
+            .send_raw(instructions_update(self.history.with_language(&restored)))
+            .await?;
+        self.history.truncate(self.generation);
+        Ok(())
+    }
+}
+
+pub fn instructions_update(instructions: String) -> ClientEvent {
+    ClientEvent::SessionUpdate {
+        event_id: None,
+        session: Box::new(SessionUpdate {
+            config: SessionUpdateConfig {
+                instructions: Some(instructions),
+                ..SessionUpdateConfig::default()
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_sentence_is_appended_and_stripped() {
+        let text = with_language("Be brief.", Some("pt-BR"));
+        assert_eq!(
+            text,
+            "Be brief.\n\nAlways respond in Portuguese, even if the user speaks another language."
+        );
+        assert_eq!(without_language(&text, Some("pt-BR")), "Be brief.");
+        assert_eq!(without_language(&text, Some("es")), text);
+        assert_eq!(
+            with_language("", Some("tlh")),
+            "Always respond in tlh, even if the user speaks another language."
+        );
+        assert_eq!(transcription_language("pt-BR"), "pt");
+    }
+}
@@ -0,0 +1,39 @@
+//! `conversation.item.create` requests that wait for the server to add the item.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix of the client event ID used for create requests.
+const CREATE_EVENT_PREFIX: &str = "sdk_create_";
+
+/// Event ID for creating `item_id`, so a server error can be routed back to it.
+pub fn event_id(item_id: &str) -> String {
+    format!("{CREATE_EVENT_PREFIX}{item_id}")
+}
+
+/// The item a create request was for, given the event ID echoed in a server error.
+pub fn item_id(event_id: &str) -> Option<&str> {
+    event_id.strip_prefix(CREATE_EVENT_PREFIX)
+}
+
+/// A new item ID, unique within the process and short enough for the server's
+/// 32-character limit.
+pub fn new_item_id() -> String {
+    unique_id("item_sdk")
+}
+
+/// A new call ID for a function call the SDK creates itself.
+pub fn new_call_id() -> String {
+    unique_id("call_sdk")
+}
+
+fn unique_id(prefix: &str) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    format!(
+        "{prefix}_{millis:x}_{}",
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
@@ -0,0 +1,502 @@
+//! Spreading new sessions across several API keys by weight and health.
+
+use super::clock::{Clock, SharedClock};
+use super::lifecycle::CloseReason;
+use super::transport::{BoxFuture, Transport};
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::server_events::ServerEvent;
+use crate::{Error, Result};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Rate-limit headroom below which a key's share is not reduced any further, so a key
+/// under pressure still gets the odd session and can report that it has recovered.
+const MIN_HEADROOM: f64 = 0.05;
+
+/// API keys, e.g. of several projects, that new sessions are spread across.
+///
+/// Sessions go to keys in smooth weighted round-robin. A key's weight is scaled by the
+/// rate-limit headroom its sessions last reported, and a key that fails `errors` times
+/// in a row, to connect or with the server dropping the session, is skipped for the
+/// cooldown. A session renewing onto a new connection takes the key the pool picks
+/// then. Clones share the same keys and health.
+#[derive(Clone)]
+pub struct KeyPool(Arc<Mutex<Pool>>);
+
+struct Pool {
+    keys: Vec<PooledKey>,
+    max_errors: u32,
+    cooldown: Duration,
+    clock: SharedClock,
+}
+
+struct PooledKey {
+    name: String,
+    api_key: String,
+    weight: u32,
+    /// Smooth weighted round-robin state.
+    current: f64,
+    active: usize,
+    sessions: u64,
+    errors: u64,
+    consecutive_errors: u32,
+    cooling_until: Option<Instant>,
+    /// Lowest remaining fraction of a rate limit, until that limit resets.
+    headroom: Option<(f64, Instant)>,
+}
+
+/// How a key in a [`KeyPool`] is doing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyStats {
+    pub name: String,
+    pub weight: u32,
+    /// Sessions on the key that are still open.
+    pub active_sessions: usize,
+    /// Sessions ever given the key.
+    pub sessions: u64,
+    pub errors: u64,
+    pub consecutive_errors: u32,
+    /// Whether the key is skipped after too many errors in a row.
+    pub cooling_down: bool,
+    /// The lowest remaining fraction of any rate limit the key's sessions last
+    /// reported, until that limit resets.
+    pub headroom: Option<f64>,
+}
+
+impl KeyPool {
+    /// An empty pool that skips a key for 30 seconds after 3 errors in a row.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Pool {
+            keys: Vec::new(),
+            max_errors: 3,
+            cooldown: Duration::from_secs(30),
+            clock: SharedClock::default(),
+        })))
+    }
+
+    /// Add `api_key`, reported as `name`, taking `weight` shares of new sessions.
+    /// A weight of zero keeps the key out of rotation.
+    #[must_use]
+    pub fn key(self, name: impl Into<String>, api_key: impl Into<String>, weight: u32) -> Self {
+        self.lock().keys.push(PooledKey {
+            name: name.into(),
+            api_key: api_key.into(),
+            weight,
+            current: 0.0,
+            active: 0,
+            sessions: 0,
+            errors: 0,
+            consecutive_errors: 0,
+            cooling_until: None,
+            headroom: None,
+        });
+        self
+    }
+
+    /// Skip a key for `cooldown` after `errors` failures in a row.
+    #[must_use]
+    pub fn ejection(self, errors: u32, cooldown: Duration) -> Self {
+        {
+            let mut pool = self.lock();
+            pool.max_errors = errors.max(1);
+            pool.cooldown = cooldown;
+        }
+        self
+    }
+
+    /// Read the time for cooldowns and rate-limit resets from `clock`.
+    #[must_use]
+    pub fn clock(self, clock: impl Clock + 'static) -> Self {
+        self.lock().clock = SharedClock::new(clock);
+        self
+    }
+
+    /// The key for the next session. Keys cooling down are used only when every key
+    /// is; `None` if no key has a weight.
+    #[must_use]
+    pub fn acquire(&self) -> Option<KeyLease> {
+        let mut pool = self.lock();
+        let now = pool.clock.now();
+        let healthy = pool
+            .keys
+            .iter()
+            .any(|key| key.weight > 0 && !key.cooling(now));
+        let shares: Vec<f64> = pool
+            .keys
+            .iter()
+            .map(|key| {
+                if key.weight == 0 || (healthy && key.cooling(now)) {
+                    0.0
+                } else {
+                    f64::from(key.weight) * key.headroom(now).max(MIN_HEADROOM)
+                }
+            })
+            .collect();
+        let total: f64 = shares.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        for (key, share) in pool.keys.iter_mut().zip(&shares) {
+            key.current += share;
+        }
+        // Reversed so ties go to the key added first.
+        let (index, _) = pool
+            .keys
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(index, _)| shares[*index] > 0.0)
+            .max_by(|(_, a), (_, b)| a.current.total_cmp(&b.current))?;
+        let key = &mut pool.keys[index];
+        key.current -= total;
+        key.active += 1;
+        key.sessions += 1;
+        let lease = Lease {
+            pool: Arc::clone(&self.0),
+            index,
+            name: key.name.clone(),
+            api_key: key.api_key.clone(),
+        };
+        drop(pool);
+        Some(KeyLease(Arc::new(lease)))
+    }
+
+    /// Health of each key, in the order they were added.
+    #[must_use]
+    pub fn stats(&self) -> Vec<KeyStats> {
+        let pool = self.lock();
+        let now = pool.clock.now();
+        pool.keys
+            .iter()
+            .map(|key| KeyStats {
+                name: key.name.clone(),
+                weight: key.weight,
+                active_sessions: key.active,
+                sessions: key.sessions,
+                errors: key.errors,
+                consecutive_errors: key.consecutive_errors,
+                cooling_down: key.cooling(now),
+                headroom: key
+                    .headroom
+                    .filter(|(_, until)| now < *until)
+                    .map(|(h, _)| h),
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Pool> {
+        lock(&self.0)
+    }
+}
+
+impl Default for KeyPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for KeyPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self
+            .lock()
+            .keys
+            .iter()
+            .map(|key| key.name.clone())
+            .collect();
+        f.debug_struct("KeyPool").field("keys", &names).finish()
+    }
+}
+
+impl PooledKey {
+    fn cooling(&self, now: Instant) -> bool {
+        self.cooling_until.is_some_and(|until| now < until)
+    }
+
+    fn headroom(&self, now: Instant) -> f64 {
+        self.headroom
+            .filter(|(_, until)| now < *until)
+            .map_or(1.0, |(headroom, _)| headroom)
+    }
+}
+
+/// One session's use of a key from a [`KeyPool`]. The session reports the key's
+/// health through it, and the key counts as active until every clone is dropped.
+#[derive(Clone)]
+pub struct KeyLease(Arc<Lease>);
+
+struct Lease {
+    pool: Arc<Mutex<Pool>>,
+    index: usize,
+    name: String,
+    api_key: String,
+}
+
+impl KeyLease {
+    /// The name the key was added under.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    #[must_use]
+    pub fn api_key(&self) -> &str {
+        &self.0.api_key
+    }
+
+    /// Count a failure against the key; enough in a row start its cooldown.
+    pub fn record_error(&self) {
+        self.update(
+            |Ejection {
+                 max_errors,
+                 cooldown,
+                 now,
+             },
+             key| {
+                key.errors += 1;
+                key.consecutive_errors += 1;
+                if key.consecutive_errors >= max_errors {
+                    tracing::warn!(key = key.name, "API key failing; cooling down");
+                    key.cooling_until = Some(now + cooldown);
+                    key.consecutive_errors = 0;
+                }
+            },
+        );
+    }
+
+    /// Clear the key's run of errors.
+    pub fn record_success(&self) {
+        self.update(|_, key| key.consecutive_errors = 0);
+    }
+
+    /// Pick up rate-limit headroom from `rate_limits.updated`.
+    pub(crate) fn observe(&self, evt: &ServerEvent) {
+        let ServerEvent::RateLimitsUpdated { rate_limits, .. } = evt else {
+            return;
+        };
+        self.update(|Ejection { now, .. }, key| {
+            let tightest = rate_limits
+                .iter()
+                .filter(|limit| limit.limit > 0)
+                .map(|limit| {
+                    let headroom = f64::from(limit.remaining) / f64::from(limit.limit);
+                    let resets_in =
+                        Duration::try_from_secs_f32(limit.reset_seconds).unwrap_or_default();
+                    (headroom.min(1.0), now + resets_in)
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if tightest.is_some() {
+                key.headroom = tightest;
+            }
+        });
+    }
+
+    /// Count the session ending for `reason` against the key if the connection or
+    /// credentials failed. A close without a close frame says nothing about the key.
+    pub(crate) fn closed(&self, reason: &CloseReason) {
+        let failed = match reason {
+            CloseReason::Transport(_) => true,
+            CloseReason::ServerClosed(close) => close.as_ref().is_some_and(|close| {
+                matches!(
+                    close.error(),
+                    Some(Error::AuthExpired(_) | Error::ServerClosed { .. })
+                )
+            }),
+            _ => false,
+        };
+        if failed {
+            self.record_error();
+        }
+    }
+
+    /// The pool the key came from.
+    pub(crate) fn pool(&self) -> KeyPool {
+        KeyPool(Arc::clone(&self.0.pool))
+    }
+
+    /// Wrap `transport`, opened with this key, so the key's health follows what
+    /// arrives on it. The lease lasts as long as the connection.
+    pub(crate) fn wrap(&self, transport: Box<dyn Transport>) -> Box<dyn Transport> {
+        Box::new(LeasedTransport {
+            inner: transport,
+            lease: self.clone(),
+        })
+    }
+
+    /// Run `f` on the pool's ejection settings and this key.
+    fn update(&self, f: impl FnOnce(Ejection, &mut PooledKey)) {
+        let mut pool = lock(&self.0.pool);
+        let settings = Ejection {
+            max_errors: pool.max_errors,
+            cooldown: pool.cooldown,
+            now: pool.clock.now(),
+        };
+        f(settings, &mut pool.keys[self.0.index]);
+    }
+}
+
+/// The pool's ejection settings, and the time by its clock.
+struct Ejection {
+    max_errors: u32,
+    cooldown: Duration,
+    now: Instant,
+}
+
+/// A connection on a pooled key, picking up rate limits and failures for the key.
+struct LeasedTransport {
+    inner: Box<dyn Transport>,
+    lease: KeyLease,
+}
+
+impl Transport for LeasedTransport {
+    fn send(&mut self, event: ClientEvent) -> BoxFuture<'_, Result<()>> {
+        self.inner.send(event)
+    }
+
+    fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+        Box::pin(async move {
+            let next = self.inner.next_event().await;
+            match &next {
+                Ok(Some(evt)) => self.lease.observe(evt),
+                Ok(None) => self
+                    .lease
+                    .closed(&CloseReason::ServerClosed(self.inner.close_frame())),
+                Err(err) => self.lease.closed(&CloseReason::Transport(err.to_string())),
+            }
+            next
+        })
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.inner.close_frame()
+    }
+}
+
+impl fmt::Debug for KeyLease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyLease")
+            .field("name", &self.0.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        lock(&self.pool).keys[self.index].active -= 1;
+    }
+}
+
+fn lock(pool: &Mutex<Pool>) -> MutexGuard<'_, Pool> {
+    pool.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::server_events::RateLimit;
+
+    fn names(pool: &KeyPool, sessions: usize) -> Vec<String> {
+        (0..sessions)
+            .map(|_| pool.acquire().unwrap().name().to_string())
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sessions_follow_weights_and_skip_failing_keys() {
+        let pool = KeyPool::new()
+            .key("a", "sk-a", 2)
+            .key("b", "sk-b", 1)
+            .key("off", "sk-off", 0)
+            .ejection(2, Duration::from_secs(30));
+        assert_eq!(names(&pool, 6), ["a", "b", "a", "a", "b", "a"]);
+
+        let lease = pool.acquire().unwrap();
+        assert_eq!(lease.api_key(), "sk-a");
+        lease.record_error();
+        // A close without a close frame is not the key's fault.
+        lease.closed(&CloseReason::ServerClosed(None));
+        lease.closed(&CloseReason::Transport("reset".to_string()));
+        let stats = pool.stats();
+        assert!(stats[0].cooling_down);
+        assert_eq!(stats[0].active_sessions, 1);
+        assert_eq!(stats[0].errors, 2);
+        assert_eq!(names(&pool, 3), ["b", "b", "b"]);
+
+        drop(lease);
+        assert_eq!(pool.stats()[0].active_sessions, 0);
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert!(names(&pool, 3).contains(&"a".to_string()));
+        assert!(KeyPool::new().key("off", "sk-off", 0).acquire().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_pressure_shrinks_a_keys_share() {
+        let pool = KeyPool::new().key("a", "sk-a", 1).key("b", "sk-b", 1);
+        let lease = pool.acquire().unwrap();
+        assert_eq!(lease.name(), "a");
+        lease.observe(&ServerEvent::RateLimitsUpdated {
+            event_id: "evt_1".to_string(),
+            rate_limits: vec![RateLimit {
+                name: "tokens".to_string(),
+                limit: 1_000,
+                remaining: 100,
+                reset_seconds: 60.0,
+            }],
+        });
+        assert_eq!(pool.stats()[0].headroom, Some(0.1));
+
+        // A tenth of the headroom gets roughly a tenth of b's sessions.
+        let picked = names(&pool, 22);
+        let on_a = picked.iter().filter(|name| *name == "a").count();
+        assert!((1..=2).contains(&on_a), "{picked:?}");
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert_eq!(pool.stats()[0].headroom, None);
+    }
+
+    /// Hands out `events`, then fails.
+    struct Scripted(Vec<ServerEvent>);
+
+    impl Transport for Scripted {
+        fn send(&mut self, _: ClientEvent) -> BoxFuture<'_, Result<()>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+            Box::pin(async move {
+                if self.0.is_empty() {
+                    return Err(Error::ConnectionClosed);
+                }
+                Ok(Some(self.0.remove(0)))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn a_leased_connection_reports_the_keys_health() {
+        let pool = KeyPool::new().key("a", "sk-a", 1);
+        let lease = pool.acquire().unwrap();
+        let mut connection = lease.wrap(Box::new(Scripted(vec![ServerEvent::RateLimitsUpdated {
+            event_id: "evt_1".to_string(),
+            rate_limits: vec![RateLimit {
+                name: "requests".to_string(),
+                limit: 10,
+                remaining: 5,
+                reset_seconds: 60.0,
+            }],
+        }])));
+        drop(lease);
+
+        assert!(connection.next_event().await.unwrap().is_some());
+        assert!(connection.next_event().await.is_err());
+        let stats = &pool.stats()[0];
+        assert_eq!(stats.headroom, Some(0.5));
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.active_sessions, 1);
+
+        drop(connection);
+        assert_eq!(pool.stats()[0].active_sessions, 0);
+    }
+}
@@ -0,0 +1,146 @@
+//! Input and output audio levels for meters, reported as `VoiceEvent::InputLevel`
+//! and `VoiceEvent::OutputLevel`.
+
+use super::codec::AudioCodec;
+use super::voice::VoiceEvent;
+use std::time::Duration;
+
+/// Loudness of the audio in one direction since the last report.
+#[derive(Default)]
+struct Level {
+    sum_squares: f64,
+    samples: u64,
+    peak: u16,
+    /// Audio has been reported since the direction last went quiet.
+    active: bool,
+}
+
+impl Level {
+    /// Meter `encoded` audio; audio without a codec, or that fails to decode, is
+    /// skipped.
+    fn observe(&mut self, encoded: &[u8], codec: Option<&dyn AudioCodec>) {
+        let Some(samples) = codec.and_then(|codec| codec.decode(encoded).ok()) else {
+            return;
+        };
+        for sample in samples {
+            self.sum_squares += f64::from(sample).powi(2);
+            self.samples += 1;
+            self.peak = self.peak.max(sample.unsigned_abs());
+        }
+    }
+
+    /// RMS and peak as fractions of full scale, or `None` if there is nothing new to
+    /// report. A direction that goes quiet reports zero once.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn take(&mut self) -> Option<(f32, f32)> {
+        const FULL_SCALE: f64 = 32_768.0;
+        if self.samples == 0 {
+            return std::mem::take(&mut self.active).then_some((0.0, 0.0));
+        }
+        let rms = (self.sum_squares / self.samples as f64).sqrt() / FULL_SCALE;
+        let peak = f64::from(self.peak) / FULL_SCALE;
+        *self = Self {
+            active: true,
+            ..Self::default()
+        };
+        Some((rms as f32, peak.min(1.0) as f32))
+    }
+}
+
+/// Levels of the audio sent to and received from the model.
+pub struct LevelMeter {
+    input: Level,
+    output: Level,
+}
+
+impl LevelMeter {
+    /// A meter and its report ticker, or `None` for both when `interval` is `None`.
+    pub fn with_interval(
+        interval: Option<Duration>,
+        now: tokio::time::Instant,
+    ) -> (Option<Self>, Option<tokio::time::Interval>) {
+        let Some(period) = interval else {
+            return (None, None);
+        };
+        let mut ticker = tokio::time::interval_at(now + period, period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let meter = Self {
+            input: Level::default(),
+            output: Level::default(),
+        };
+        (Some(meter), Some(ticker))
+    }
+
+    /// Meter audio sent to the model, already base64-decoded from its append;
+    /// `codec` is the input format's.
+    pub fn observe_input(&mut self, encoded: &[u8], codec: Option<&dyn AudioCodec>) {
+        self.input.observe(encoded, codec);
+    }
+
+    /// Meter audio received from the model, already base64-decoded from its delta;
+    /// `codec` is the output format's.
+    pub fn observe_output(&mut self, encoded: &[u8], codec: Option<&dyn AudioCodec>) {
+        self.output.observe(encoded, codec);
+    }
+
+    /// Level events for the audio since the last call.
+    pub fn take(&mut self) -> Vec<VoiceEvent> {
+        let input = self
+            .input
+            .take()
+            .map(|(rms, peak)| VoiceEvent::InputLevel { rms, peak });
+        let output = self
+            .output
+            .take()
+            .map(|(rms, peak)| VoiceEvent::OutputLevel { rms, peak });
+        input.into_iter().chain(output).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::codec::{G711Codec, Pcm16Codec};
+
+    fn pcm(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    #[tokio::test]
+    async fn levels_report_rms_and_peak_then_zero_once() {
+        let (meter, _) =
+            LevelMeter::with_interval(Some(Duration::from_millis(50)), tokio::time::Instant::now());
+        let mut meter = meter.unwrap();
+        meter.observe_input(
+            &pcm(&[16_384, -16_384, 16_384, -16_384]),
+            Some(&Pcm16Codec::default()),
+        );
+        let mu_law = G711Codec::mu_law();
+        meter.observe_output(&mu_law.encode(&[0, -32_768]), Some(&mu_law));
+        // Audio in a format without a codec is not metered.
+        meter.observe_output(&[0x7f; 4], None);
+
+        let events = meter.take();
+        let [
+            VoiceEvent::InputLevel { rms, peak },
+            VoiceEvent::OutputLevel {
+                peak: output_peak, ..
+            },
+        ] = events.as_slice()
+        else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert!((rms - 0.5).abs() < 1e-6);
+        assert!((peak - 0.5).abs() < 1e-6);
+        // μ-law's loudest step is a little under full scale.
+        assert!((output_peak - 0.98).abs() < 0.01);
+
+        let quiet = meter.take();
+        assert!(matches!(
+            quiet.as_slice(),
+            [VoiceEvent::InputLevel { rms, .. }, VoiceEvent::OutputLevel { .. }]
+                if rms.abs() < f32::EPSILON
+        ));
+        assert!(meter.take().is_empty());
+    }
+}
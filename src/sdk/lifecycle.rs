@@ -0,0 +1,95 @@
+//! Session liveness as seen from outside the event loop.
+
+use crate::ServerClose;
+use crate::protocol::server_events::ServerEvent;
+use tokio::sync::watch;
+
+/// Where a session is in its life, as reported by `SessionHandle::state`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    /// Waiting for the server's `session.created`.
+    Connecting,
+    /// The server accepted the session.
+    Active,
+    /// The event loop has stopped and is releasing the connection.
+    Closing,
+    /// The event loop has exited; nothing more will be sent or received.
+    Closed { reason: CloseReason },
+}
+
+impl SessionState {
+    /// Whether the session can no longer send or receive.
+    #[must_use]
+    pub const fn is_closed(&self) -> bool {
+        matches!(self, Self::Closing | Self::Closed { .. })
+    }
+}
+
+/// Why a session's event loop exited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The server closed the connection, with its close frame over WebSocket.
+    ServerClosed(Option<ServerClose>),
+    /// Reading from the connection failed.
+    Transport(String),
+    /// `IdleTimeoutPolicy::close_after` consecutive idle timeouts.
+    IdleTimeout,
+    /// An event channel overflowed under `OverflowPolicy::CloseSession`.
+    ChannelOverflow(&'static str),
+    /// The session and all of its handles were dropped.
+    Dropped,
+    /// The event loop panicked.
+    Panicked,
+    /// The deadline set with `Session::with_deadline` passed.
+    DeadlineExceeded,
+    /// The token given to `RealtimeBuilder::cancellation` was cancelled.
+    Cancelled,
+}
+
+/// The event loop's side of the session state.
+pub struct StateReporter(watch::Sender<SessionState>);
+
+impl StateReporter {
+    pub fn new() -> (Self, StateWatch) {
+        let (tx, rx) = watch::channel(SessionState::Connecting);
+        (Self(tx), StateWatch(rx))
+    }
+
+    pub fn observe(&self, evt: &ServerEvent) {
+        if matches!(evt, ServerEvent::SessionCreated { .. }) {
+            self.0.send_if_modified(|state| {
+                let connecting = *state == SessionState::Connecting;
+                if connecting {
+                    *state = SessionState::Active;
+                }
+                connecting
+            });
+        }
+    }
+
+    pub fn closing(&self) {
+        self.0.send_replace(SessionState::Closing);
+    }
+
+    pub fn closed(self, reason: CloseReason) {
+        self.0.send_replace(SessionState::Closed { reason });
+    }
+}
+
+/// The handles' side of the session state.
+#[derive(Clone)]
+pub struct StateWatch(watch::Receiver<SessionState>);
+
+impl StateWatch {
+    /// The latest state. A loop that went away without reporting a reason panicked.
+    pub fn current(&self) -> SessionState {
+        let state = self.0.borrow().clone();
+        match state {
+            SessionState::Closed { .. } => state,
+            _ if self.0.has_changed().is_err() => SessionState::Closed {
+                reason: CloseReason::Panicked,
+            },
+            _ => state,
+        }
+    }
+}
@@ -3,22 +3,114 @@
 //! The SDK exposes a simple async callback interface while keeping the low-level
 //! protocol types accessible through `crate::protocol` when you need full control.
 
+mod agent;
+mod amd;
+#[cfg(feature = "amd")]
+mod amd_heuristic;
+mod analytics;
+mod audio_fallback;
+mod audio_file;
+mod audio_lease;
+mod barge_in;
 mod builder;
+mod call;
+mod channel;
+mod clock;
+mod codec;
+mod conversation;
+mod correlation;
+mod deadline;
+mod dedup;
+mod event_log;
 pub mod events;
+mod expiry;
+mod fork;
+mod guardrails;
 mod handlers;
+mod handoff;
+mod instructions;
+mod item_create;
+mod key_pool;
+mod level;
+mod lifecycle;
+#[cfg(feature = "moderation")]
+mod moderation;
+mod oob;
+mod ordering;
+mod outbound_gate;
+mod report;
+mod resend;
 mod response;
+mod retrieval;
+mod routing;
+mod samples;
+mod schedule;
+mod schema;
 mod session;
+mod speakable;
+mod timeline;
+mod token_budget;
+mod tool_context;
+mod tool_stream;
 mod tools;
+mod transcription;
+mod transcripts;
 mod transport;
 mod voice;
 
+pub use agent::Agent;
+pub use amd::{AmdInput, AmdVerdict, AnsweringMachineDetection, AnsweringMachineDetector};
+#[cfg(feature = "amd")]
+pub use amd_heuristic::HeuristicAmd;
+pub use analytics::ConversationAnalytics;
+pub use audio_fallback::AudioFailurePolicy;
+pub use audio_file::{AudioProgress, Progress};
+pub use audio_lease::AudioInputLease;
+pub use barge_in::BargeInPolicy;
 pub use builder::{Realtime, RealtimeBuilder, VoiceSessionBuilder};
+pub use call::CallLifecycle;
+pub use channel::{ChannelCapacities, DroppedMessages, OverflowPolicy};
+pub use clock::{Clock, TokioClock};
+pub use codec::{AudioCodec, CodecRegistry, G711Codec, Pcm16Codec};
+pub use conversation::ConversationGap;
+pub use correlation::SentEvent;
+pub use deadline::Deadline;
+pub use event_log::{EventLog, RotationPolicy};
 pub use events::{EventStream, SdkEvent};
+pub use expiry::ExpiryPolicy;
+pub use fork::ConversationFork;
+pub use guardrails::{Guardrail, GuardrailReport, GuardrailStage, GuardrailVerdict, Guardrails};
 pub use handlers::{EventHandlers, RawEventHandler, TextHandler, ToolCallHandler};
+pub use handoff::{HandoffContext, HandoffOptions, handoff};
+pub use instructions::InstructionGuard;
+pub use key_pool::{KeyLease, KeyPool, KeyStats};
+pub use lifecycle::{CloseReason, SessionState};
+#[cfg(feature = "moderation")]
+pub use moderation::OpenAiModeration;
+pub use oob::ClassifyInput;
+pub use ordering::{OrderingRule, OrderingViolation};
+pub use report::{CallReport, ErrorCounts, TranscriptLine, UsageTotals};
 pub use response::ResponseBuilder;
+pub use routing::{AudioRoute, AudioRouteMode, ResponseRoute, ResponseTextReader};
+pub use samples::{Clipping, F32Conversion};
+pub use schedule::ScheduledResponse;
 pub use session::AudioIn;
-pub use session::{Session, SessionHandle};
+pub use session::{Session, SessionHandle, SessionRole};
+pub use speakable::{SpeakableChunk, SpeakableChunking, SpeakableStream};
+pub use timeline::{chrome_trace, export_chrome_trace};
+pub use token_budget::TokenBudget;
+pub use tool_context::{CancellationToken, ToolContext};
+pub use tool_stream::ToolCallStream;
 pub use tools::{
-    BoxFuture as ToolFuture, ToolCall, ToolDefinition, ToolRegistry, ToolResult, ToolSpec,
+    BoxFuture as ToolFuture, SchemaHook, ToolCall, ToolConcurrency, ToolDefinition, ToolDispatcher,
+    ToolErrorHandler, ToolErrorPolicy, ToolFailure, ToolMiddleware, ToolOutputOptions,
+    ToolRegistry, ToolResult, ToolSpec,
+};
+pub use transcription::{
+    TranscriptionFailure, TranscriptionFailureHandler, TranscriptionFailurePolicy,
+};
+pub use transcripts::{Speaker, Transcript, TranscriptStream};
+pub use voice::{
+    AudioChunk, AudioSamples, IdleTimeoutPolicy, LocalAudioPosition, TranscriptChunk, VoiceEvent,
+    VoiceEventStream,
 };
-pub use voice::{AudioChunk, TranscriptChunk, VoiceEvent, VoiceEventStream};
@@ -0,0 +1,102 @@
+//! Reference guardrail backed by the `OpenAI` moderation endpoint.
+
+use super::guardrails::{Guardrail, GuardrailStage, GuardrailVerdict};
+use crate::Result;
+use reqwest::Client;
+use reqwest::header::{AUTHORIZATION, HeaderValue};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const MODERATION_URL: &str = "https://api.openai.com/v1/moderations";
+const DEFAULT_MODEL: &str = "omni-moderation-latest";
+
+#[derive(Debug, Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModerationResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: HashMap<String, bool>,
+}
+
+/// Blocks text flagged by the moderation endpoint.
+///
+/// Assistant deltas are skipped by default since each check is an HTTP round trip;
+/// complete assistant text and user input are always checked.
+#[derive(Debug, Clone)]
+pub struct OpenAiModeration {
+    client: Client,
+    auth_header: HeaderValue,
+    model: String,
+    check_deltas: bool,
+}
+
+impl OpenAiModeration {
+    /// # Errors
+    /// Returns an error if the API key results in an invalid header.
+    pub fn new(api_key: &str) -> Result<Self> {
+        Ok(Self {
+            client: Client::new(),
+            auth_header: HeaderValue::from_str(&format!("Bearer {api_key}"))?,
+            model: DEFAULT_MODEL.to_string(),
+            check_deltas: false,
+        })
+    }
+
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn check_deltas(mut self, enabled: bool) -> Self {
+        self.check_deltas = enabled;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Guardrail for OpenAiModeration {
+    async fn check(&self, stage: GuardrailStage, text: &str) -> Result<GuardrailVerdict> {
+        if text.trim().is_empty() || (stage == GuardrailStage::AssistantDelta && !self.check_deltas)
+        {
+            return Ok(GuardrailVerdict::Allow);
+        }
+
+        let body = serde_json::json!({ "model": self.model, "input": text });
+        let response: ModerationResponse = self
+            .client
+            .post(MODERATION_URL)
+            .header(AUTHORIZATION, self.auth_header.clone())
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let flagged: Vec<ModerationResult> = response
+            .results
+            .into_iter()
+            .filter(|result| result.flagged)
+            .collect();
+        if flagged.is_empty() {
+            return Ok(GuardrailVerdict::Allow);
+        }
+
+        let mut categories: Vec<String> = flagged
+            .into_iter()
+            .flat_map(|result| result.categories)
+            .filter_map(|(category, hit)| hit.then_some(category))
+            .collect();
+        categories.sort();
+        categories.dedup();
+        Ok(GuardrailVerdict::Block {
+            reason: format!("moderation flagged: {}", categories.join(", ")),
+        })
+    }
+}
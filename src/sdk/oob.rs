@@ -0,0 +1,124 @@
+//! Out-of-band responses that run alongside the main conversation.
+
+use crate::protocol::models::{
+    ContentPart, ConversationMode, InputItem, Item, OutputModalities, Response, ResponseConfig,
+    ResponseStatus, Role, ToolChoice, ToolChoiceMode,
+};
+use crate::{Error, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Metadata key used to correlate out-of-band responses with their callers.
+pub const OOB_METADATA_KEY: &str = "sdk_oob_id";
+
+static NEXT_OOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Input for an out-of-band classification request.
+#[derive(Debug, Clone)]
+pub enum ClassifyInput {
+    Text(String),
+    /// PCM16 mono samples @ 24kHz.
+    Pcm16(Vec<i16>),
+}
+
+impl From<&str> for ClassifyInput {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for ClassifyInput {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Vec<i16>> for ClassifyInput {
+    fn from(samples: Vec<i16>) -> Self {
+        Self::Pcm16(samples)
+    }
+}
+
+pub fn next_oob_id() -> String {
+    format!("oob_{}", NEXT_OOB_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Build a text-only `response.create` config that stays out of the default conversation.
+pub fn classify_request(id: &str, input: ClassifyInput, instructions: &str) -> ResponseConfig {
+    let content = match input {
+        ClassifyInput::Text(text) => ContentPart::InputText { text },
+        ClassifyInput::Pcm16(samples) => {
+            let mut buf = Vec::with_capacity(samples.len() * 2);
+            for sample in samples {
+                buf.extend_from_slice(&sample.to_le_bytes());
+            }
+            ContentPart::InputAudio {
+                audio: general_purpose::STANDARD.encode(buf),
+                transcript: None,
+                format: None,
+            }
+        }
+    };
+
+    ResponseConfig {
+        conversation: Some(ConversationMode::None),
+        metadata: Some(HashMap::from([(
+            OOB_METADATA_KEY.to_string(),
+            serde_json::Value::from(id),
+        )])),
+        output_modalities: Some(OutputModalities::Text),
+        instructions: Some(instructions.to_string()),
+        tool_choice: Some(ToolChoice::Mode(ToolChoiceMode::None)),
+        input: Some(vec![InputItem::Message {
+            id: None,
+            role: Role::User,
+            content: vec![content],
+        }]),
+        ..ResponseConfig::default()
+    }
+}
+
+/// Returns the out-of-band correlation ID attached to a response, if any.
+pub fn oob_id(response: &Response) -> Option<&str> {
+    response.metadata.as_ref()?.get(OOB_METADATA_KEY)?.as_str()
+}
+
+/// `response` if it completed, otherwise the error it failed with.
+pub fn completed(response: Response) -> Result<Response> {
+    if response.status == ResponseStatus::Completed {
+        return Ok(response);
+    }
+    Err(match response.status_details.and_then(|d| d.error) {
+        Some(error) => Error::from(error),
+        None => Error::ResponseIncomplete(response.status),
+    })
+}
+
+/// Decode the text output of a completed out-of-band response.
+///
+/// JSON output is deserialized directly; anything else is treated as a JSON string so
+/// plain labels can be read into `String` or unit enum variants.
+pub fn parse_output<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let text: String = completed(response)?
+        .output
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Message { content, .. } => Some(content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|part| match part {
+            ContentPart::OutputText { text } | ContentPart::Text { text } => Some(text),
+            _ => None,
+        })
+        .collect();
+
+    let trimmed = text.trim();
+    serde_json::from_str(trimmed)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(trimmed.to_string())))
+        .map_err(Error::from)
+}
@@ -0,0 +1,387 @@
+//! Checks the documented ordering of response events.
+//!
+//! Within a response the server sends `response.created`, then for each content part
+//! `response.content_part.added`, its deltas and `*.done`, `response.content_part.done`,
+//! and finally `response.done`. Events that break this order are reported once and
+//! the tracked state is repaired so a single slip does not cascade.
+
+use crate::protocol::server_events::ServerEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How many finished responses are remembered to detect late events.
+const FINISHED_RESPONSE_CAPACITY: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderingRule {
+    /// A response event arrived before `response.created`.
+    ResponseNotCreated,
+    /// A response event arrived after `response.done` or `response.cancelled`.
+    ResponseAlreadyDone,
+    /// Content arrived before `response.content_part.added`.
+    ContentPartNotAdded,
+    /// Content arrived after `response.content_part.done`.
+    ContentPartAlreadyDone,
+}
+
+/// A server event that broke an [`OrderingRule`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderingViolation {
+    pub rule: OrderingRule,
+    pub response_id: String,
+    pub item_id: Option<String>,
+    pub event: Box<ServerEvent>,
+}
+
+/// Per-response ordering state.
+#[derive(Debug, Default)]
+struct ResponseState {
+    /// Content parts by `(item_id, content_index)`; `true` once done.
+    parts: HashMap<(String, u32), bool>,
+}
+
+#[derive(Debug, Default)]
+pub struct OrderingMonitor {
+    active: HashMap<String, ResponseState>,
+    finished: VecDeque<String>,
+    finished_set: HashSet<String>,
+}
+
+enum Scope<'a> {
+    Created(&'a str),
+    Finished(&'a str),
+    Response(&'a str),
+    PartAdded(&'a str, &'a str, u32),
+    PartDone(&'a str, &'a str, u32),
+    Content(&'a str, &'a str, u32),
+}
+
+impl OrderingMonitor {
+    /// Record `evt` and report it if it breaks the documented order.
+    pub fn observe(&mut self, evt: &ServerEvent) -> Option<OrderingViolation> {
+        let (rule, response_id, item_id) = match scope(evt)? {
+            Scope::Created(response_id) => {
+                let rule = if self.finished_set.contains(response_id) {
+                    Some(OrderingRule::ResponseAlreadyDone)
+                } else {
+                    self.state(response_id);
+                    None
+                };
+                (rule, response_id, None)
+            }
+            Scope::Finished(response_id) => {
+                let rule = self.check_response(response_id);
+                if rule != Some(OrderingRule::ResponseAlreadyDone) {
+                    self.finish(response_id);
+                }
+                (rule, response_id, None)
+            }
+            Scope::Response(response_id) => (self.check_response(response_id), response_id, None),
+            Scope::PartAdded(response_id, item_id, index) => {
+                let rule = self.check_response(response_id);
+                if rule != Some(OrderingRule::ResponseAlreadyDone) {
+                    self.set_part(response_id, item_id, index, false);
+                }
+                (rule, response_id, Some(item_id))
+            }
+            Scope::PartDone(response_id, item_id, index) => {
+                let rule = self.check_content(response_id, item_id, index);
+                if rule != Some(OrderingRule::ResponseAlreadyDone) {
+                    self.set_part(response_id, item_id, index, true);
+                }
+                (rule, response_id, Some(item_id))
+            }
+            Scope::Content(response_id, item_id, index) => (
+                self.check_content(response_id, item_id, index),
+                response_id,
+                Some(item_id),
+            ),
+        };
+        rule.map(|rule| OrderingViolation {
+            rule,
+            response_id: response_id.to_string(),
+            item_id: item_id.map(str::to_string),
+            event: Box::new(evt.clone()),
+        })
+    }
+
+    /// Reports a problem with the response or part once, then treats the part as open.
+    fn check_content(
+        &mut self,
+        response_id: &str,
+        item_id: &str,
+        index: u32,
+    ) -> Option<OrderingRule> {
+        let response_rule = self.check_response(response_id);
+        if response_rule == Some(OrderingRule::ResponseAlreadyDone) {
+            return response_rule;
+        }
+        let key = (item_id.to_string(), index);
+        let parts = &mut self.state(response_id).parts;
+        let part_rule = match parts.get(&key) {
+            Some(false) => None,
+            Some(true) => Some(OrderingRule::ContentPartAlreadyDone),
+            None => Some(OrderingRule::ContentPartNotAdded),
+        };
+        parts.insert(key, false);
+        response_rule.or(part_rule)
+    }
+
+    fn set_part(&mut self, response_id: &str, item_id: &str, index: u32, done: bool) {
+        self.state(response_id)
+            .parts
+            .insert((item_id.to_string(), index), done);
+    }
+
+    fn check_response(&mut self, response_id: &str) -> Option<OrderingRule> {
+        if self.active.contains_key(response_id) {
+            None
+        } else if self.finished_set.contains(response_id) {
+            Some(OrderingRule::ResponseAlreadyDone)
+        } else {
+            self.active
+                .insert(response_id.to_string(), ResponseState::default());
+            Some(OrderingRule::ResponseNotCreated)
+        }
+    }
+
+    fn state(&mut self, response_id: &str) -> &mut ResponseState {
+        self.active.entry(response_id.to_string()).or_default()
+    }
+
+    fn finish(&mut self, response_id: &str) {
+        self.active.remove(response_id);
+        if self.finished_set.insert(response_id.to_string()) {
+            self.finished.push_back(response_id.to_string());
+            if self.finished.len() > FINISHED_RESPONSE_CAPACITY {
+                if let Some(oldest) = self.finished.pop_front() {
+                    self.finished_set.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+fn scope(evt: &ServerEvent) -> Option<Scope<'_>> {
+    let scope = match evt {
+        ServerEvent::ResponseCreated { response, .. } => Scope::Created(&response.id),
+        ServerEvent::ResponseDone { response, .. }
+        | ServerEvent::ResponseCancelled { response, .. } => Scope::Finished(&response.id),
+        ServerEvent::ResponseOutputItemAdded { response_id, .. }
+        | ServerEvent::ResponseOutputItemDone { response_id, .. }
+        | ServerEvent::ResponseFunctionCallArgumentsDelta { response_id, .. }
+        | ServerEvent::ResponseFunctionCallArgumentsDone { response_id, .. }
+        | ServerEvent::ResponseMcpCallArgumentsDelta { response_id, .. }
+        | ServerEvent::ResponseMcpCallArgumentsDone { response_id, .. } => {
+            Scope::Response(response_id)
+        }
+        ServerEvent::ResponseContentPartAdded {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        } => Scope::PartAdded(response_id, item_id, *content_index),
+        ServerEvent::ResponseContentPartDone {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        } => Scope::PartDone(response_id, item_id, *content_index),
+        ServerEvent::ResponseOutputTextDelta {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        }
+        | ServerEvent::ResponseOutputTextDone {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        }
+        | ServerEvent::ResponseOutputAudioDelta {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        }
+        | ServerEvent::ResponseOutputAudioDone {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        }
+        | ServerEvent::ResponseOutputAudioTranscriptDelta {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        }
+        | ServerEvent::ResponseOutputAudioTranscriptDone {
+            response_id,
+            item_id,
+            content_index,
+            ..
+        } => Scope::Content(response_id, item_id, *content_index),
+        _ => return None,
+    };
+    Some(scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::{ContentPart, Response};
+
+    const RESPONSE: &str = "resp_1";
+
+    fn response() -> Response {
+        serde_json::from_value(serde_json::json!({
+            "id": RESPONSE,
+            "object": "realtime.response",
+            "status": "in_progress",
+            "output": [],
+        }))
+        .unwrap()
+    }
+
+    fn part(added: bool, content_index: u32) -> ServerEvent {
+        let part = ContentPart::Text {
+            text: String::new(),
+        };
+        let (event_id, response_id, item_id) = (
+            "evt".to_string(),
+            RESPONSE.to_string(),
+            "item_1".to_string(),
+        );
+        if added {
+            ServerEvent::ResponseContentPartAdded {
+                event_id,
+                response_id,
+                item_id,
+                output_index: 0,
+                content_index,
+                part,
+            }
+        } else {
+            ServerEvent::ResponseContentPartDone {
+                event_id,
+                response_id,
+                item_id,
+                output_index: 0,
+                content_index,
+                part,
+            }
+        }
+    }
+
+    fn delta(content_index: u32) -> ServerEvent {
+        ServerEvent::ResponseOutputTextDelta {
+            event_id: "evt".to_string(),
+            response_id: RESPONSE.to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index,
+            delta: "x".to_string(),
+        }
+    }
+
+    /// A well-ordered response with two content parts, tagged by role.
+    fn fixture() -> Vec<(Role, ServerEvent)> {
+        let mut events = vec![(
+            Role::Created,
+            ServerEvent::ResponseCreated {
+                event_id: "evt".to_string(),
+                response: response(),
+            },
+        )];
+        for index in 0..2 {
+            events.push((Role::PartAdded(index), part(true, index)));
+            events.push((Role::Delta(index), delta(index)));
+            events.push((Role::Delta(index), delta(index)));
+            events.push((Role::PartDone(index), part(false, index)));
+        }
+        events.push((
+            Role::Done,
+            ServerEvent::ResponseDone {
+                event_id: "evt".to_string(),
+                response: response(),
+            },
+        ));
+        events
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Role {
+        Created,
+        PartAdded(u32),
+        Delta(u32),
+        PartDone(u32),
+        Done,
+    }
+
+    /// Independent statement of the rules: which rule, if any, `events[at]` breaks
+    /// given only what came before it.
+    fn expected(events: &[(Role, ServerEvent)], at: usize) -> Option<OrderingRule> {
+        let before = &events[..at];
+        let seen = |role: Role| before.iter().any(|(r, _)| *r == role);
+        let role = events[at].0;
+        if seen(Role::Done) {
+            return Some(OrderingRule::ResponseAlreadyDone);
+        }
+        if role == Role::Created {
+            return None;
+        }
+        // Only the first event ahead of `response.created` is reported.
+        if before.is_empty() {
+            return Some(OrderingRule::ResponseNotCreated);
+        }
+        let (Role::Delta(index) | Role::PartDone(index)) = role else {
+            return None;
+        };
+        // Any content for the part leaves it open, whether or not it was reported.
+        let last = before.iter().rev().find_map(|(r, _)| match *r {
+            Role::PartAdded(i) | Role::Delta(i) | Role::PartDone(i) if i == index => Some(*r),
+            _ => None,
+        });
+        match last {
+            None => Some(OrderingRule::ContentPartNotAdded),
+            Some(Role::PartDone(_)) => Some(OrderingRule::ContentPartAlreadyDone),
+            Some(_) => None,
+        }
+    }
+
+    #[test]
+    fn documented_order_has_no_violations() {
+        let mut monitor = OrderingMonitor::default();
+        for (_, evt) in fixture() {
+            assert!(monitor.observe(&evt).is_none());
+        }
+    }
+
+    #[test]
+    fn shuffled_fixtures_report_exactly_the_broken_events() {
+        // Small LCG so the permutations are reproducible without extra dependencies.
+        let mut seed: u64 = 0x5eed;
+        let mut next = move |bound: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            usize::try_from(seed >> 33).unwrap() % bound
+        };
+
+        for _ in 0..500 {
+            let mut events = fixture();
+            for i in (1..events.len()).rev() {
+                events.swap(i, next(i + 1));
+            }
+
+            let mut monitor = OrderingMonitor::default();
+            for at in 0..events.len() {
+                let got = monitor.observe(&events[at].1).map(|v| v.rule);
+                assert_eq!(got, expected(&events, at), "event {at}");
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Holding client events back while outbound sends are paused, e.g. during a
+//! reconnect or while an upstream gateway asks for flow control.
+
+use super::transport::{BoxFuture, Transport};
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::server_events::ServerEvent;
+use crate::{Error, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::Notify;
+
+/// Whether outbound sends are paused, and the events held meanwhile. Clones share
+/// the same state.
+#[derive(Clone)]
+pub struct OutboundGate(Arc<Shared>);
+
+struct Shared {
+    state: Mutex<Gate>,
+    resumed: Notify,
+}
+
+struct Gate {
+    paused: bool,
+    limit: usize,
+    queued: VecDeque<ClientEvent>,
+}
+
+impl OutboundGate {
+    /// An open gate that holds up to `limit` events while paused.
+    pub fn new(limit: usize) -> Self {
+        Self(Arc::new(Shared {
+            state: Mutex::new(Gate {
+                paused: false,
+                limit,
+                queued: VecDeque::new(),
+            }),
+            resumed: Notify::new(),
+        }))
+    }
+
+    pub fn pause(&self) {
+        self.lock().paused = true;
+    }
+
+    /// Let sends through again; the event loop then sends what was held.
+    pub fn resume(&self) {
+        self.lock().paused = false;
+        self.0.resumed.notify_one();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    /// Events held until the gate opens.
+    pub fn queued(&self) -> usize {
+        self.lock().queued.len()
+    }
+
+    /// The held events, unless the gate has been paused again.
+    pub fn take_queued(&self) -> VecDeque<ClientEvent> {
+        let mut gate = self.lock();
+        if gate.paused {
+            return VecDeque::new();
+        }
+        std::mem::take(&mut gate.queued)
+    }
+
+    /// Hold `event` if the gate is paused, or hand it back to be sent.
+    fn hold(&self, event: ClientEvent) -> Result<Option<ClientEvent>> {
+        let mut gate = self.lock();
+        if !gate.paused {
+            return Ok(Some(event));
+        }
+        if gate.queued.len() >= gate.limit {
+            return Err(Error::OutboundQueueFull(gate.limit));
+        }
+        gate.queued.push_back(event);
+        drop(gate);
+        Ok(None)
+    }
+
+    /// Put `events`, which were taken but could not be sent, back ahead of anything
+    /// held since.
+    pub fn requeue(&self, events: VecDeque<ClientEvent>) {
+        let mut gate = self.lock();
+        for event in events.into_iter().rev() {
+            gate.queued.push_front(event);
+        }
+    }
+
+    /// Send the held events in order, unless the gate has been paused again. Those not
+    /// sent when one fails stay held, the failed one first.
+    ///
+    /// # Errors
+    /// Returns the first send error.
+    pub async fn flush(&self, transport: &mut dyn Transport) -> Result<()> {
+        let mut held = self.take_queued();
+        while let Some(event) = held.pop_front() {
+            if let Err(err) = transport.send(event.clone()).await {
+                held.push_front(event);
+                self.requeue(held);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait for the gate to be reopened.
+    pub async fn resumed(&self) {
+        self.0.resumed.notified().await;
+    }
+
+    /// Wrap `transport` so its sends go through this gate.
+    pub fn wrap(&self, transport: Box<dyn Transport>) -> Box<dyn Transport> {
+        Box::new(GatedTransport {
+            inner: transport,
+            gate: self.clone(),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Gate> {
+        self.0.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for OutboundGate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gate = self.lock();
+        f.debug_struct("OutboundGate")
+            .field("paused", &gate.paused)
+            .field("queued", &gate.queued.len())
+            .finish()
+    }
+}
+
+/// Holds sends while the gate is paused and sends them, in order, ahead of the next
+/// send once it is open.
+struct GatedTransport {
+    inner: Box<dyn Transport>,
+    gate: OutboundGate,
+}
+
+impl Transport for GatedTransport {
+    fn send(&mut self, event: ClientEvent) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let Some(event) = self.gate.hold(event)? else {
+                return Ok(());
+            };
+            // Held events go out first so nothing overtakes them.
+            self.gate.flush(&mut *self.inner).await?;
+            self.inner.send(event).await
+        })
+    }
+
+    fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+        self.inner.next_event()
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.inner.close_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Accepts `room` sends, then fails.
+    struct Flaky {
+        room: usize,
+        sent: Vec<ClientEvent>,
+    }
+
+    impl Transport for Flaky {
+        fn send(&mut self, event: ClientEvent) -> BoxFuture<'_, Result<()>> {
+            Box::pin(async move {
+                if self.sent.len() == self.room {
+                    return Err(Error::ConnectionClosed);
+                }
+                self.sent.push(event);
+                Ok(())
+            })
+        }
+
+        fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    fn commit(id: &str) -> ClientEvent {
+        ClientEvent::InputAudioBufferCommit {
+            event_id: Some(id.to_string()),
+        }
+    }
+
+    fn ids<'a>(events: impl IntoIterator<Item = &'a ClientEvent>) -> Vec<&'a str> {
+        events
+            .into_iter()
+            .filter_map(ClientEvent::event_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn events_not_sent_stay_held_in_order() {
+        let gate = OutboundGate::new(8);
+        let mut gated = gate.wrap(Box::new(Flaky {
+            room: 1,
+            sent: Vec::new(),
+        }));
+        gate.pause();
+        for id in ["evt_1", "evt_2", "evt_3"] {
+            gated.send(commit(id)).await.unwrap();
+        }
+        gate.resume();
+
+        assert!(gated.send(commit("evt_4")).await.is_err());
+        assert_eq!(ids(&gate.lock().queued), ["evt_2", "evt_3"]);
+
+        let mut retry = Flaky {
+            room: 8,
+            sent: Vec::new(),
+        };
+        gate.flush(&mut retry).await.unwrap();
+        assert_eq!(ids(&retry.sent), ["evt_2", "evt_3"]);
+        assert_eq!(gate.queued(), 0);
+    }
+}
@@ -0,0 +1,282 @@
+//! End-of-call summary for billing and QA.
+
+use crate::protocol::models::{Item, ResponseStatus, Role, Usage};
+use crate::protocol::server_events::ServerEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::analytics::{AnalyticsTracker, ConversationAnalytics};
+use super::conversation::ConversationSnapshot;
+use super::handoff::part_text;
+
+/// Summary of a whole call, returned by `Session::finish`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallReport {
+    /// Time from connecting to `finish`.
+    pub duration: Duration,
+    /// User messages the server added to the conversation, spoken or typed, whether
+    /// or not they were transcribed.
+    pub user_turns: u32,
+    /// Responses the server completed, whatever their status.
+    pub responses: u32,
+    /// Token usage summed over all responses.
+    pub usage: UsageTotals,
+    /// Usage summed over all input audio transcriptions.
+    pub transcription_usage: UsageTotals,
+    /// Function and MCP tool calls the model made, counted by tool name.
+    pub tool_calls: BTreeMap<String, u32>,
+    pub errors: ErrorCounts,
+    /// Talk time, interruptions and latency over the whole call.
+    pub analytics: ConversationAnalytics,
+    /// Messages in conversation order, with audio replaced by its transcript.
+    pub transcript: Vec<TranscriptLine>,
+}
+
+/// Usage added up across a call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub input_audio_tokens: u64,
+    pub output_audio_tokens: u64,
+    /// Seconds of audio billed by duration rather than tokens.
+    pub audio_seconds: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &Usage) {
+        self.input_tokens += u64::from(usage.input_tokens);
+        self.output_tokens += u64::from(usage.output_tokens);
+        self.total_tokens += u64::from(usage.total_tokens);
+        let input = usage.input_token_details.as_ref();
+        self.cached_input_tokens += u64::from(
+            input
+                .and_then(|details| details.cached_tokens)
+                .or(usage.cached_tokens)
+                .unwrap_or_default(),
+        );
+        self.input_audio_tokens += u64::from(
+            input
+                .and_then(|details| details.audio_tokens)
+                .unwrap_or_default(),
+        );
+        self.output_audio_tokens += u64::from(
+            usage
+                .output_token_details
+                .as_ref()
+                .and_then(|details| details.audio_tokens)
+                .unwrap_or_default(),
+        );
+        self.audio_seconds += usage.seconds.unwrap_or_default();
+    }
+}
+
+/// Errors seen during a call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCounts {
+    /// `error` events from the server.
+    pub server: u32,
+    /// Responses that finished with status `failed`.
+    pub failed_responses: u32,
+    /// Input audio the server could not transcribe.
+    pub transcription_failures: u32,
+}
+
+/// One message of the final transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptLine {
+    pub role: Role,
+    pub text: String,
+}
+
+/// Accumulates a `CallReport` in the event loop.
+#[derive(Debug)]
+pub struct CallReportTracker {
+    started: Instant,
+    analytics: AnalyticsTracker,
+    user_turns: u32,
+    responses: u32,
+    usage: UsageTotals,
+    transcription_usage: UsageTotals,
+    tool_calls: BTreeMap<String, u32>,
+    errors: ErrorCounts,
+}
+
+impl Default for CallReportTracker {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl CallReportTracker {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            analytics: AnalyticsTracker::new(started),
+            user_turns: 0,
+            responses: 0,
+            usage: UsageTotals::default(),
+            transcription_usage: UsageTotals::default(),
+            tool_calls: BTreeMap::new(),
+            errors: ErrorCounts::default(),
+        }
+    }
+}
+
+impl CallReportTracker {
+    pub fn observe(&mut self, evt: &ServerEvent, now: Instant) {
+        self.analytics.observe(evt, now);
+        match evt {
+            // Counted as added rather than from the transcript, which a turn may never
+            // get.
+            ServerEvent::ConversationItemAdded { item, .. }
+            | ServerEvent::ConversationItemCreated { item, .. } => {
+                if matches!(
+                    item,
+                    Item::Message {
+                        role: Role::User,
+                        ..
+                    }
+                ) {
+                    self.user_turns += 1;
+                }
+            }
+            ServerEvent::ResponseDone { response, .. } => {
+                self.responses += 1;
+                if let Some(usage) = &response.usage {
+                    self.usage.add(usage);
+                }
+                if response.status == ResponseStatus::Failed {
+                    self.errors.failed_responses += 1;
+                }
+                let names = response
+                    .function_calls()
+                    .map(|call| call.name)
+                    .chain(response.mcp_calls().map(|call| call.name));
+                for name in names {
+                    *self.tool_calls.entry(name.to_string()).or_default() += 1;
+                }
+            }
+            ServerEvent::InputAudioTranscriptionCompleted {
+                usage: Some(usage), ..
+            } => self.transcription_usage.add(usage),
+            ServerEvent::InputAudioTranscriptionFailed { .. } => {
+                self.errors.transcription_failures += 1;
+            }
+            ServerEvent::Error { .. } => self.errors.server += 1,
+            _ => {}
+        }
+    }
+
+    /// The report as of `now`, with the transcript taken from `conversation`.
+    pub fn report(&mut self, conversation: &ConversationSnapshot, now: Instant) -> CallReport {
+        let transcript: Vec<TranscriptLine> = conversation
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Message { role, content, .. } => Some((role, content)),
+                _ => None,
+            })
+            .filter_map(|(role, content)| {
+                let text: Vec<String> = content.iter().filter_map(part_text).collect();
+                (!text.is_empty()).then(|| TranscriptLine {
+                    role: *role,
+                    text: text.join(" "),
+                })
+            })
+            .collect();
+        CallReport {
+            duration: now.saturating_duration_since(self.started),
+            user_turns: self.user_turns,
+            responses: self.responses,
+            usage: self.usage.clone(),
+            transcription_usage: self.transcription_usage.clone(),
+            tool_calls: self.tool_calls.clone(),
+            errors: self.errors,
+            analytics: self.analytics.snapshot(now),
+            transcript,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::ContentPart;
+    use serde_json::json;
+
+    #[test]
+    fn report_sums_usage_and_counts_tool_calls() {
+        let mut tracker = CallReportTracker::default();
+        let done: ServerEvent = serde_json::from_value(json!({
+            "type": "response.done",
+            "event_id": "evt_1",
+            "response": {
+                "id": "resp_1",
+                "object": "realtime.response",
+                "status": "completed",
+                "output": [{
+                    "type": "function_call",
+                    "id": "item_2",
+                    "call_id": "call_1",
+                    "name": "lookup",
+                    "arguments": "{}"
+                }],
+                "usage": {
+                    "total_tokens": 30,
+                    "input_tokens": 20,
+                    "output_tokens": 10,
+                    "input_token_details": { "cached_tokens": 5, "audio_tokens": 12 }
+                }
+            }
+        }))
+        .unwrap();
+        let now = Instant::now();
+        tracker.observe(&done, now);
+        tracker.observe(&done, now);
+
+        let spoken = |id: &str, transcript: Option<&str>| Item::Message {
+            id: Some(id.to_string()),
+            status: None,
+            role: Role::User,
+            content: vec![ContentPart::InputAudio {
+                audio: String::new(),
+                transcript: transcript.map(str::to_string),
+                format: None,
+            }],
+        };
+        let items = vec![
+            spoken("item_1", Some("Where is my order?")),
+            // Never transcribed, but still a turn.
+            spoken("item_3", None),
+        ];
+        for item in &items {
+            tracker.observe(
+                &ServerEvent::ConversationItemAdded {
+                    event_id: "evt_2".to_string(),
+                    previous_item_id: None,
+                    item: item.clone(),
+                },
+                now,
+            );
+        }
+
+        let conversation = ConversationSnapshot {
+            items,
+            input_audio: Vec::new(),
+        };
+        let report = tracker.report(&conversation, now);
+        assert_eq!(report.responses, 2);
+        assert_eq!(report.usage.total_tokens, 60);
+        assert_eq!(report.usage.cached_input_tokens, 10);
+        assert_eq!(report.usage.input_audio_tokens, 24);
+        assert_eq!(report.tool_calls.get("lookup"), Some(&2));
+        assert_eq!(report.user_turns, 2);
+        assert_eq!(report.transcript.len(), 1);
+        assert_eq!(report.transcript[0].text, "Where is my order?");
+    }
+}
@@ -0,0 +1,213 @@
+//! Sends the server has not confirmed yet, kept so they can be replayed on a new
+//! connection after the old one fails.
+
+use super::item_create;
+use super::transport::{BoxFuture, Transport};
+use crate::Result;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::server_events::ServerEvent;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Prefix of the client event ID given to queued events that were sent without one.
+const RESEND_EVENT_PREFIX: &str = "sdk_resend_";
+
+/// Unacknowledged creates kept; past this the oldest is forgotten. The server adds
+/// items within a round trip, so a longer backlog means the connection is gone.
+const RESEND_LIMIT: usize = 256;
+
+/// Item creates, including tool outputs, that the server has not added yet, oldest
+/// first. Shared between the session and its `ResendTransport`.
+#[derive(Clone, Default)]
+pub struct ResendQueue(Arc<Mutex<VecDeque<ClientEvent>>>);
+
+impl ResendQueue {
+    pub fn events(&self) -> Vec<ClientEvent> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Give a create the IDs it needs to be matched and deduplicated, then queue it.
+    /// Other events are not kept.
+    fn push(&self, event: &mut ClientEvent) {
+        let ClientEvent::ConversationItemCreate { event_id, item, .. } = event else {
+            return;
+        };
+        if item.id().is_none() {
+            item.set_id(item_create::new_item_id());
+        }
+        let item_id = item.id().unwrap_or_default();
+        event_id.get_or_insert_with(|| format!("{RESEND_EVENT_PREFIX}{item_id}"));
+        let mut queue = self.lock();
+        if queue.len() == RESEND_LIMIT {
+            let forgotten = queue.pop_front();
+            tracing::warn!(
+                event_id = forgotten.as_ref().and_then(ClientEvent::event_id),
+                "too many unacknowledged item creates; forgetting the oldest"
+            );
+        }
+        queue.push_back(event.clone());
+    }
+
+    /// Drop the create the server added, or rejected with an error naming it.
+    fn observe(&self, evt: &ServerEvent) {
+        match acknowledged(evt) {
+            Some(Ack::Added(item_id)) => self
+                .lock()
+                .retain(|event| created_item_id(event) != Some(item_id)),
+            Some(Ack::Rejected(event_id)) => self
+                .lock()
+                .retain(|event| event.event_id() != Some(event_id)),
+            None => {}
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<ClientEvent>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Event IDs a session replayed whose creates the server has not acknowledged, so
+/// replaying them again sends nothing. Once acknowledged, the item exists and the
+/// server rejects another create under its ID.
+#[derive(Debug, Default)]
+pub struct Replayed(HashMap<String, Option<String>>);
+
+impl Replayed {
+    /// Note `event` as replayed; `false` if it already was.
+    pub fn insert(&mut self, event: &ClientEvent) -> bool {
+        let Some(event_id) = event.event_id() else {
+            return true;
+        };
+        if self.0.contains_key(event_id) {
+            return false;
+        }
+        let item_id = created_item_id(event).map(str::to_string);
+        self.0.insert(event_id.to_string(), item_id);
+        true
+    }
+
+    /// Forget the replayed event the server added or rejected.
+    pub fn observe(&mut self, evt: &ServerEvent) {
+        match acknowledged(evt) {
+            Some(Ack::Added(added)) => self
+                .0
+                .retain(|_, item_id| item_id.as_deref() != Some(added)),
+            Some(Ack::Rejected(event_id)) => {
+                self.0.remove(event_id);
+            }
+            None => {}
+        }
+    }
+}
+
+/// The server's answer to an item create.
+enum Ack<'a> {
+    /// The item with this ID was added.
+    Added(&'a str),
+    /// The event with this ID failed.
+    Rejected(&'a str),
+}
+
+fn acknowledged(evt: &ServerEvent) -> Option<Ack<'_>> {
+    match evt {
+        ServerEvent::ConversationItemAdded { item, .. }
+        | ServerEvent::ConversationItemCreated { item, .. } => item.id().map(Ack::Added),
+        ServerEvent::Error { error, .. } => error.event_id.as_deref().map(Ack::Rejected),
+        _ => None,
+    }
+}
+
+fn created_item_id(event: &ClientEvent) -> Option<&str> {
+    match event {
+        ClientEvent::ConversationItemCreate { item, .. } => item.id(),
+        _ => None,
+    }
+}
+
+/// Transport decorator that queues item creates until the server adds them.
+pub struct ResendTransport {
+    inner: Box<dyn Transport>,
+    queue: ResendQueue,
+}
+
+impl ResendTransport {
+    pub const fn new(inner: Box<dyn Transport>, queue: ResendQueue) -> Self {
+        Self { inner, queue }
+    }
+}
+
+impl Transport for ResendTransport {
+    fn send(&mut self, mut event: ClientEvent) -> BoxFuture<'_, Result<()>> {
+        // Queued before sending, so a create that fails to send is kept as well.
+        self.queue.push(&mut event);
+        self.inner.send(event)
+    }
+
+    fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>> {
+        Box::pin(async move {
+            let event = self.inner.next_event().await?;
+            if let Some(event) = &event {
+                self.queue.observe(event);
+            }
+            Ok(event)
+        })
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.inner.close_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::{ContentPart, Item, Role};
+
+    fn create(id: &str) -> ClientEvent {
+        ClientEvent::ConversationItemCreate {
+            event_id: None,
+            previous_item_id: None,
+            item: Box::new(Item::Message {
+                id: Some(id.to_string()),
+                status: None,
+                role: Role::User,
+                content: vec![ContentPart::InputText {
+                    text: id.to_string(),
+                }],
+            }),
+        }
+    }
+
+    fn added(id: &str) -> ServerEvent {
+        let ClientEvent::ConversationItemCreate { item, .. } = create(id) else {
+            unreachable!()
+        };
+        ServerEvent::ConversationItemAdded {
+            event_id: format!("evt_{id}"),
+            previous_item_id: None,
+            item: *item,
+        }
+    }
+
+    #[test]
+    fn queue_forgets_the_oldest_past_its_limit() {
+        let queue = ResendQueue::default();
+        for n in 0..=RESEND_LIMIT {
+            queue.push(&mut create(&format!("item_{n}")));
+        }
+        let events = queue.events();
+        assert_eq!(events.len(), RESEND_LIMIT);
+        assert_eq!(created_item_id(&events[0]), Some("item_1"));
+    }
+
+    #[test]
+    fn replayed_events_are_forgotten_once_acknowledged() {
+        let mut replayed = Replayed::default();
+        let mut event = create("item_1");
+        ResendQueue::default().push(&mut event);
+        assert!(replayed.insert(&event));
+        assert!(!replayed.insert(&event));
+        replayed.observe(&added("item_1"));
+        assert!(replayed.0.is_empty());
+    }
+}
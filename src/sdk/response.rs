@@ -1,8 +1,8 @@
 use crate::Result;
 use crate::protocol::models::{
-    ContentPart, InputItem, OutputModalities, ResponseConfig, Role, ToolChoice,
+    ContentPart, InputItem, McpToolConfig, OutputModalities, ResponseConfig, Role, Tool, ToolChoice,
 };
-use crate::protocol::models::{MaxTokens, Metadata, Temperature, Voice};
+use crate::protocol::models::{Include, MaxTokens, Metadata, Temperature, Tracing, Voice};
 
 use super::Session;
 use super::ToolRegistry;
@@ -55,12 +55,41 @@ impl ResponseBuilder {
         self
     }
 
+    /// Ask for extra data in this response's events. May be called repeatedly.
+    #[must_use]
+    pub fn include(mut self, include: impl Into<Include>) -> Self {
+        self.config
+            .include
+            .get_or_insert_with(Vec::new)
+            .push(include.into());
+        self
+    }
+
     #[must_use]
     pub fn metadata(mut self, metadata: Metadata) -> Self {
         self.config.metadata = Some(metadata);
         self
     }
 
+    /// Trace this response with the server's default workflow name.
+    #[must_use]
+    pub fn tracing_auto(mut self) -> Self {
+        self.config.tracing = Some(Tracing::auto());
+        self
+    }
+
+    /// Trace this response under `workflow_name`, grouped by `group_id`.
+    #[must_use]
+    pub fn tracing(
+        mut self,
+        workflow_name: impl Into<String>,
+        group_id: Option<String>,
+        metadata: Option<Metadata>,
+    ) -> Self {
+        self.config.tracing = Some(Tracing::workflow(workflow_name, group_id, metadata));
+        self
+    }
+
     #[must_use]
     pub fn tool_choice(mut self, choice: ToolChoice) -> Self {
         self.config.tool_choice = Some(choice);
@@ -69,8 +98,6 @@ impl ResponseBuilder {
 
     /// # Errors
     /// Returns an error if tool schema serialization fails.
-    // Keep a single public error type for the SDK surface.
-    #[allow(clippy::result_large_err)]
     pub fn tools(mut self, registry: &ToolRegistry) -> Result<Self> {
         if !registry.is_empty() {
             self.config.tools = Some(registry.try_as_tools()?);
@@ -78,6 +105,71 @@ impl ResponseBuilder {
         Ok(self)
     }
 
+    /// Add an MCP server to the tools available for this response.
+    ///
+    /// # Errors
+    /// Returns an error if the MCP config is invalid.
+    pub fn mcp_tool(mut self, config: McpToolConfig) -> Result<Self> {
+        config.validate()?;
+        self.config
+            .tools
+            .get_or_insert_with(Vec::new)
+            .push(Tool::Mcp(config));
+        Ok(self)
+    }
+
+    /// Restrict an MCP server to the given tools for this response only.
+    ///
+    /// The server must already be among the response tools (via [`Self::tools`] or
+    /// [`Self::mcp_tool`]). If it already has an allowlist, the result is the
+    /// intersection of both.
+    ///
+    /// # Errors
+    /// Returns an error if no MCP tool with `server_label` is configured or if no
+    /// allowed tool remains.
+    pub fn allow_mcp_tools<I, S>(mut self, server_label: &str, names: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut names: Vec<String> = names.into_iter().map(Into::into).collect();
+        let config = self
+            .config
+            .tools
+            .iter_mut()
+            .flatten()
+            .find_map(|tool| match tool {
+                Tool::Mcp(config) if config.server_label == server_label => Some(config),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                crate::Error::InvalidClientEvent(format!(
+                    "no mcp tool with server_label `{server_label}` in response tools"
+                ))
+            })?;
+        if let Some(existing) = &config.allowed_tools {
+            names.retain(|name| existing.contains(name));
+        }
+        if names.is_empty() {
+            return Err(crate::Error::InvalidClientEvent(format!(
+                "no allowed tools left for mcp server `{server_label}`"
+            )));
+        }
+        config.allowed_tools = Some(names);
+        Ok(self)
+    }
+
+    /// Force the model to call `name` on the MCP server `server_label`.
+    #[must_use]
+    pub fn tool_choice_mcp(
+        mut self,
+        server_label: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        self.config.tool_choice = Some(ToolChoice::mcp(server_label, name));
+        self
+    }
+
     #[must_use]
     pub fn input_text(mut self, text: impl Into<String>) -> Self {
         let item = InputItem::Message {
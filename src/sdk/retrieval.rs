@@ -0,0 +1,36 @@
+//! `conversation.item.retrieve` requests and decoding of the returned audio.
+
+use crate::protocol::models::Item;
+use crate::{Error, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose;
+
+/// Prefix of the client event ID used for retrieve requests.
+const RETRIEVE_EVENT_PREFIX: &str = "sdk_retrieve_";
+
+/// Event ID for retrieving `item_id`, so a server error can be routed back to it.
+pub fn event_id(item_id: &str) -> String {
+    format!("{RETRIEVE_EVENT_PREFIX}{item_id}")
+}
+
+/// The item a retrieve request was for, given the event ID echoed in a server error.
+pub fn item_id(event_id: &str) -> Option<&str> {
+    event_id.strip_prefix(RETRIEVE_EVENT_PREFIX)
+}
+
+/// Decode and concatenate the audio of every content part of `item`, in order.
+///
+/// # Errors
+/// Returns an error if a part holds invalid base64.
+pub fn decode_audio(item: &Item) -> Result<Vec<u8>> {
+    let Item::Message { content, .. } = item else {
+        return Ok(Vec::new());
+    };
+    let mut pcm = Vec::new();
+    for audio in content.iter().filter_map(|part| part.audio()) {
+        general_purpose::STANDARD
+            .decode_vec(audio, &mut pcm)
+            .map_err(|e| Error::InvalidAudio(e.to_string()))?;
+    }
+    Ok(pcm)
+}
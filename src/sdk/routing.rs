@@ -0,0 +1,573 @@
+//! Separate event and audio streams for background responses, picked by response
+//! metadata.
+
+use super::channel::{self, ChannelMonitor, OverflowPolicy, Receiver, Sender};
+use super::events::{EventStream, SdkEvent};
+use super::voice::{AudioChunk, AudioTimeline};
+use crate::Result;
+use crate::protocol::models::Response;
+use crate::protocol::server_events::ServerEvent;
+use base64::Engine;
+use base64::engine::general_purpose;
+use bytes::Bytes;
+use futures::Stream;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The events of responses matched by `Session::route_responses`, kept out of the
+/// session's own streams. The stream ends when the session closes.
+pub struct ResponseRoute {
+    rx: Receiver<SdkEvent>,
+    monitor: ChannelMonitor,
+}
+
+impl ResponseRoute {
+    /// Await the next event of a matched response.
+    ///
+    /// # Errors
+    /// Returns `Error::ChannelOverflow` if a reader fell behind under
+    /// `OverflowPolicy::CloseSession`.
+    pub async fn next_event(&mut self) -> Result<Option<SdkEvent>> {
+        match self.rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Stream the events of matched responses.
+    #[must_use]
+    pub const fn events(&mut self) -> EventStream<'_> {
+        EventStream::from_session(&mut self.rx)
+    }
+
+    /// Events discarded because this reader fell behind.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.rx.dropped()
+    }
+
+    /// Read the text of the next matched response as bytes, e.g. to copy it into a
+    /// file, an HTTP response body or a subprocess.
+    #[must_use]
+    pub const fn text_reader(self) -> ResponseTextReader {
+        ResponseTextReader {
+            route: self,
+            cursor: ResponseCursor {
+                response_id: None,
+                done: false,
+            },
+            unread: Bytes::new(),
+        }
+    }
+
+    /// Write the text of the next matched response, or the transcript of its audio,
+    /// into `writer` as it arrives, e.g. to archive a long response without holding it
+    /// in memory. A slow writer holds up the route like a slow reader would. Returns
+    /// the bytes written once the response is done or cancelled.
+    ///
+    /// # Errors
+    /// Returns `Error::Io` if writing fails, and `Error::ChannelOverflow` if the route
+    /// fell behind under `OverflowPolicy::CloseSession`.
+    pub async fn pipe_text<W: AsyncWrite + Unpin>(&mut self, writer: W) -> Result<u64> {
+        self.pipe(writer, text_delta).await
+    }
+
+    /// Write the output audio of the next matched response into `writer` as it
+    /// arrives, as raw bytes in the session's output audio format. Like
+    /// [`Self::pipe_text`] otherwise; undecodable audio is skipped.
+    ///
+    /// # Errors
+    /// Returns `Error::Io` if writing fails, and `Error::ChannelOverflow` if the route
+    /// fell behind under `OverflowPolicy::CloseSession`.
+    pub async fn pipe_audio<W: AsyncWrite + Unpin>(&mut self, writer: W) -> Result<u64> {
+        self.pipe(writer, |event| match event {
+            SdkEvent::AudioDelta {
+                response_id, delta, ..
+            } => match general_purpose::STANDARD.decode(delta.as_bytes()) {
+                Ok(pcm) => Some((response_id, pcm)),
+                Err(err) => {
+                    tracing::warn!(response_id, "skipping undecodable piped audio: {err}");
+                    None
+                }
+            },
+            _ => None,
+        })
+        .await
+    }
+
+    /// Write what `part` takes from each event of the next matched response.
+    async fn pipe<W: AsyncWrite + Unpin, T: AsRef<[u8]>>(
+        &self,
+        mut writer: W,
+        part: fn(SdkEvent) -> Option<(String, T)>,
+    ) -> Result<u64> {
+        let mut cursor = ResponseCursor::default();
+        let mut written = 0;
+        while !cursor.done {
+            let Some(event) = self.rx.recv().await else {
+                self.monitor.closed::<()>()?;
+                break;
+            };
+            if let Some(bytes) = cursor.accept(event, part) {
+                let bytes = bytes.as_ref();
+                write_all(&mut writer, bytes).await?;
+                written += bytes.len() as u64;
+            }
+        }
+        poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx)).await?;
+        Ok(written)
+    }
+}
+
+/// Follows one response among the events a route delivers.
+#[derive(Debug, Default)]
+struct ResponseCursor {
+    /// The response followed, set by its first event.
+    response_id: Option<String>,
+    done: bool,
+}
+
+impl ResponseCursor {
+    /// What `part` takes from `event`, if it is non-empty and belongs to the response
+    /// followed.
+    fn accept<T: AsRef<[u8]>>(
+        &mut self,
+        event: SdkEvent,
+        part: fn(SdkEvent) -> Option<(String, T)>,
+    ) -> Option<T> {
+        if let SdkEvent::Raw(evt) = &event {
+            match &**evt {
+                ServerEvent::ResponseCreated { response, .. } => {
+                    self.response_id.get_or_insert_with(|| response.id.clone());
+                }
+                // Before the cursor has locked on, this is the end of an earlier
+                // response, not of the one it is waiting for.
+                ServerEvent::ResponseDone { response, .. }
+                | ServerEvent::ResponseCancelled { response, .. } => {
+                    self.done = self.response_id.as_deref() == Some(response.id.as_str());
+                }
+                _ => {}
+            }
+            return None;
+        }
+        let (response_id, value) = part(event)?;
+        let current = self.response_id.get_or_insert_with(|| response_id.clone());
+        (*current == response_id && !value.as_ref().is_empty()).then_some(value)
+    }
+}
+
+/// The text output of one routed response, or the transcript of its audio.
+///
+/// Readable as an [`AsyncRead`] or a [`Stream`] of deltas. It ends when the response
+/// is done or cancelled; events of other responses the route matches meanwhile are
+/// skipped.
+pub struct ResponseTextReader {
+    route: ResponseRoute,
+    cursor: ResponseCursor,
+    /// The rest of a delta a read had no room for.
+    unread: Bytes,
+}
+
+impl ResponseTextReader {
+    /// The response being read, once its first event has arrived.
+    #[must_use]
+    pub fn response_id(&self) -> Option<&str> {
+        self.cursor.response_id.as_deref()
+    }
+
+    fn poll_delta(&mut self, cx: &Context<'_>) -> Poll<Option<Bytes>> {
+        while !self.cursor.done {
+            let Some(event) = ready!(self.route.rx.poll_recv(cx)) else {
+                self.cursor.done = true;
+                break;
+            };
+            if let Some(delta) = self.cursor.accept(event, text_delta) {
+                return Poll::Ready(Some(Bytes::from(delta)));
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+/// The text a text or transcript delta adds to its response.
+fn text_delta(event: SdkEvent) -> Option<(String, String)> {
+    match event {
+        SdkEvent::TextDelta {
+            response_id, delta, ..
+        }
+        | SdkEvent::TranscriptDelta {
+            response_id, delta, ..
+        } => Some((response_id, delta)),
+        _ => None,
+    }
+}
+
+impl Stream for ResponseTextReader {
+    type Item = Bytes;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if !this.unread.is_empty() {
+            return Poll::Ready(Some(std::mem::take(&mut this.unread)));
+        }
+        this.poll_delta(cx)
+    }
+}
+
+impl AsyncRead for ResponseTextReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if this.unread.is_empty() {
+            match ready!(this.poll_delta(cx)) {
+                Some(delta) => this.unread = delta,
+                // A reader that fell behind under `OverflowPolicy::CloseSession` lost
+                // part of the text, so this is not a clean end.
+                None => {
+                    return Poll::Ready(
+                        this.route
+                            .monitor
+                            .closed::<()>()
+                            .map(|_| ())
+                            .map_err(std::io::Error::other),
+                    );
+                }
+            }
+        }
+        let n = this.unread.len().min(buf.remaining());
+        buf.put_slice(&this.unread.split_to(n));
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl std::fmt::Debug for ResponseTextReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseTextReader")
+            .field("response_id", &self.cursor.response_id)
+            .field("done", &self.cursor.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What happens to the audio of responses an audio route matches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AudioRouteMode {
+    /// Deliver it on the route only.
+    #[default]
+    Separate,
+    /// Also overlay it onto the session's own output audio, like
+    /// `LocalAudioPosition::Mix`, or play it there directly while no other response is
+    /// speaking.
+    Mix,
+}
+
+/// The output audio of responses matched by `Session::route_audio`. The stream ends
+/// when the session closes.
+pub struct AudioRoute {
+    rx: Receiver<AudioChunk>,
+    monitor: ChannelMonitor,
+}
+
+impl AudioRoute {
+    /// Await the next audio chunk of a matched response.
+    ///
+    /// # Errors
+    /// Returns `Error::ChannelOverflow` if a reader fell behind under
+    /// `OverflowPolicy::CloseSession`.
+    pub async fn next_chunk(&mut self) -> Result<Option<AudioChunk>> {
+        match self.rx.recv().await {
+            Some(chunk) => Ok(Some(chunk)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Chunks discarded because this reader fell behind.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.rx.dropped()
+    }
+}
+
+impl Stream for AudioRoute {
+    type Item = AudioChunk;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl std::fmt::Debug for AudioRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioRoute").finish_non_exhaustive()
+    }
+}
+
+/// A chunk of a routed response's audio, and where it goes.
+pub struct RoutedChunk {
+    pub tx: Sender<AudioChunk>,
+    pub mode: AudioRouteMode,
+    pub chunk: AudioChunk,
+}
+
+impl std::fmt::Debug for ResponseRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseRoute").finish_non_exhaustive()
+    }
+}
+
+struct Route<T> {
+    key: String,
+    value: Value,
+    tx: Sender<T>,
+}
+
+struct AudioRouteEntry {
+    route: Route<AudioChunk>,
+    mode: AudioRouteMode,
+}
+
+/// A running response whose audio an audio route matched.
+struct RoutedAudio {
+    tx: Sender<AudioChunk>,
+    mode: AudioRouteMode,
+    timeline: AudioTimeline,
+}
+
+#[derive(Default)]
+struct Routes {
+    capacity: usize,
+    audio_capacity: usize,
+    policy: OverflowPolicy,
+    monitor: ChannelMonitor,
+    registered: Vec<Route<SdkEvent>>,
+    /// Routed responses still running, by response ID.
+    responses: HashMap<String, Sender<SdkEvent>>,
+    audio: Vec<AudioRouteEntry>,
+    /// Responses with routed audio still running, by response ID.
+    audio_responses: HashMap<String, RoutedAudio>,
+}
+
+/// The event loop's side of the routes. It holds the only strong reference, so route
+/// streams end when the loop exits.
+#[derive(Default)]
+pub struct ResponseRoutes(Arc<Mutex<Routes>>);
+
+/// The session's side of the routes.
+pub struct RouteTable {
+    routes: Weak<Mutex<Routes>>,
+    monitor: ChannelMonitor,
+}
+
+impl ResponseRoutes {
+    /// Routes whose streams hold up to `capacity` events, or `audio_capacity` audio
+    /// chunks, each under `policy`.
+    pub fn new(
+        capacity: usize,
+        audio_capacity: usize,
+        policy: OverflowPolicy,
+        monitor: &ChannelMonitor,
+    ) -> (Self, RouteTable) {
+        let routes = Arc::new(Mutex::new(Routes {
+            capacity,
+            audio_capacity,
+            policy,
+            monitor: monitor.clone(),
+            ..Routes::default()
+        }));
+        let table = RouteTable {
+            routes: Arc::downgrade(&routes),
+            monitor: monitor.clone(),
+        };
+        (Self(routes), table)
+    }
+
+    /// Where `evt` goes instead of the session's event stream, if it belongs to a
+    /// routed response. A response is matched once, on `response.created`.
+    pub fn observe(&self, evt: &ServerEvent) -> Option<Sender<SdkEvent>> {
+        let mut routes = lock(&self.0);
+        match evt {
+            ServerEvent::ResponseCreated { response, .. } => {
+                routes.registered.retain(|route| !route.tx.is_closed());
+                let tx = routes
+                    .registered
+                    .iter()
+                    .find(|route| matches(route, response))?
+                    .tx
+                    .clone();
+                routes.responses.insert(response.id.clone(), tx.clone());
+                Some(tx)
+            }
+            ServerEvent::ResponseDone { response, .. }
+            | ServerEvent::ResponseCancelled { response, .. } => {
+                routes.responses.remove(&response.id)
+            }
+            _ => routes.responses.get(response_id(evt)?).cloned(),
+        }
+    }
+
+    /// Start and stop routing the audio of responses audio routes match, timing their
+    /// chunks in the format `timeline` follows. True when `evt` belongs to such a
+    /// response.
+    pub fn observe_audio(&self, evt: &ServerEvent, timeline: &AudioTimeline) -> bool {
+        let mut routes = lock(&self.0);
+        match evt {
+            ServerEvent::ResponseCreated { response, .. } => {
+                routes.audio.retain(|entry| !entry.route.tx.is_closed());
+                let Some(entry) = routes
+                    .audio
+                    .iter()
+                    .find(|entry| matches(&entry.route, response))
+                else {
+                    return false;
+                };
+                let audio = RoutedAudio {
+                    tx: entry.route.tx.clone(),
+                    mode: entry.mode,
+                    timeline: timeline.clone(),
+                };
+                routes.audio_responses.insert(response.id.clone(), audio);
+                true
+            }
+            ServerEvent::ResponseDone { response, .. }
+            | ServerEvent::ResponseCancelled { response, .. } => {
+                routes.audio_responses.remove(&response.id).is_some()
+            }
+            _ => response_id(evt).is_some_and(|id| routes.audio_responses.contains_key(id)),
+        }
+    }
+
+    /// Whether the audio of `response_id` goes to an audio route.
+    pub fn routes_audio(&self, response_id: &str) -> bool {
+        lock(&self.0).audio_responses.contains_key(response_id)
+    }
+
+    /// The chunk an audio delta of a routed response makes, received at `received_at`.
+    /// Undecodable audio is dropped; it never reaches the session's failure counting.
+    pub fn audio_chunk(
+        &self,
+        evt: &ServerEvent,
+        received_at: std::time::SystemTime,
+    ) -> Option<RoutedChunk> {
+        let ServerEvent::ResponseOutputAudioDelta {
+            response_id, delta, ..
+        } = evt
+        else {
+            return None;
+        };
+        let pcm = match general_purpose::STANDARD.decode(delta.as_bytes()) {
+            Ok(pcm) => pcm,
+            Err(err) => {
+                tracing::warn!(response_id, "dropping undecodable routed audio: {err}");
+                return None;
+            }
+        };
+        let mut routes = lock(&self.0);
+        let audio = routes.audio_responses.get_mut(response_id)?;
+        let chunk = audio.timeline.chunk(evt, pcm, received_at)?;
+        let (tx, mode) = (audio.tx.clone(), audio.mode);
+        drop(routes);
+        Some(RoutedChunk { tx, mode, chunk })
+    }
+}
+
+impl RouteTable {
+    /// A stream for responses created with `metadata[key] == value`.
+    pub fn add(&self, key: String, value: Value) -> ResponseRoute {
+        let Some(routes) = self.routes.upgrade() else {
+            // The session is closed: hand back a stream that has already ended.
+            let (_, rx) = channel::channel("route", 1, OverflowPolicy::Block, &self.monitor);
+            return self.route(rx);
+        };
+        let mut routes = lock(&routes);
+        let (tx, rx) = channel::channel("route", routes.capacity, routes.policy, &routes.monitor);
+        routes.registered.push(Route { key, value, tx });
+        drop(routes);
+        self.route(rx)
+    }
+
+    /// An audio stream for responses created with `metadata[key] == value`.
+    pub fn add_audio(&self, key: String, value: Value, mode: AudioRouteMode) -> AudioRoute {
+        let Some(routes) = self.routes.upgrade() else {
+            let (_, rx) = channel::channel("audio route", 1, OverflowPolicy::Block, &self.monitor);
+            return self.audio_route(rx);
+        };
+        let mut routes = lock(&routes);
+        let (tx, rx) = channel::channel(
+            "audio route",
+            routes.audio_capacity,
+            routes.policy,
+            &routes.monitor,
+        );
+        let route = Route { key, value, tx };
+        routes.audio.push(AudioRouteEntry { route, mode });
+        drop(routes);
+        self.audio_route(rx)
+    }
+
+    fn audio_route(&self, rx: Receiver<AudioChunk>) -> AudioRoute {
+        AudioRoute {
+            rx,
+            monitor: self.monitor.clone(),
+        }
+    }
+
+    fn route(&self, rx: Receiver<SdkEvent>) -> ResponseRoute {
+        ResponseRoute {
+            rx,
+            monitor: self.monitor.clone(),
+        }
+    }
+}
+
+fn lock(routes: &Mutex<Routes>) -> MutexGuard<'_, Routes> {
+    routes.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn matches<T>(route: &Route<T>, response: &Response) -> bool {
+    response
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get(&route.key))
+        == Some(&route.value)
+}
+
+/// The response a streamed response event belongs to.
+fn response_id(evt: &ServerEvent) -> Option<&str> {
+    match evt {
+        ServerEvent::ResponseOutputItemAdded { response_id, .. }
+        | ServerEvent::ResponseOutputItemDone { response_id, .. }
+        | ServerEvent::ResponseContentPartAdded { response_id, .. }
+        | ServerEvent::ResponseContentPartDone { response_id, .. }
+        | ServerEvent::ResponseOutputTextDelta { response_id, .. }
+        | ServerEvent::ResponseOutputTextDone { response_id, .. }
+        | ServerEvent::ResponseOutputAudioDelta { response_id, .. }
+        | ServerEvent::ResponseOutputAudioDone { response_id, .. }
+        | ServerEvent::ResponseOutputAudioTranscriptDelta { response_id, .. }
+        | ServerEvent::ResponseOutputAudioTranscriptDone { response_id, .. }
+        | ServerEvent::ResponseFunctionCallArgumentsDelta { response_id, .. }
+        | ServerEvent::ResponseFunctionCallArgumentsDone { response_id, .. }
+        | ServerEvent::ResponseMcpCallArgumentsDelta { response_id, .. }
+        | ServerEvent::ResponseMcpCallArgumentsDone { response_id, .. } => Some(response_id),
+        _ => None,
+    }
+}
+
+/// Write all of `bytes`, as `AsyncWriteExt::write_all` would.
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut bytes: &[u8]) -> std::io::Result<()> {
+    while !bytes.is_empty() {
+        let n = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, bytes)).await?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        bytes = &bytes[n..];
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Converting between PCM16, which the API speaks, and the `f32` samples most audio
+//! frameworks capture and play.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Level above which [`Clipping::Soft`] starts to compress.
+const SOFT_KNEE: f32 = 0.8;
+
+/// Dither noise state, shared so consecutive appends do not repeat the same noise.
+static DITHER_STATE: AtomicU32 = AtomicU32::new(0x9e37_79b9);
+
+/// What happens to `f32` samples beyond full scale (`-1.0..=1.0`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clipping {
+    /// Clamp to full scale.
+    #[default]
+    Hard,
+    /// Leave samples up to 0.8 alone and bend louder ones smoothly toward full
+    /// scale, which sounds less harsh than clamping.
+    Soft,
+}
+
+/// How `Session::audio_in_append_f32_with` turns `f32` samples into PCM16.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct F32Conversion {
+    pub clipping: Clipping,
+    /// Add triangular dither of one PCM16 step before rounding, trading the
+    /// distortion of quantizing quiet passages for a little steady noise.
+    pub dither: bool,
+}
+
+/// Little-endian PCM16 bytes for `samples`.
+pub fn f32_to_pcm16(samples: &[f32], conversion: F32Conversion) -> Vec<u8> {
+    let mut pcm = Vec::with_capacity(samples.len() * 2);
+    for &sample in samples {
+        let sample = if sample.is_nan() { 0.0 } else { sample };
+        let sample = match conversion.clipping {
+            Clipping::Hard => sample,
+            Clipping::Soft => soft_clip(sample),
+        };
+        let mut scaled = sample * f32::from(i16::MAX);
+        if conversion.dither {
+            scaled += triangular_noise();
+        }
+        // `as` saturates, which is the hard clip.
+        #[allow(clippy::cast_possible_truncation)]
+        let value = scaled.round() as i16;
+        pcm.extend_from_slice(&value.to_le_bytes());
+    }
+    pcm
+}
+
+/// `f32` samples in `-1.0..1.0` for PCM16 `samples`.
+pub fn i16_to_f32(samples: &[i16]) -> Vec<f32> {
+    samples
+        .iter()
+        .map(|&sample| f32::from(sample) / 32768.0)
+        .collect()
+}
+
+fn soft_clip(sample: f32) -> f32 {
+    let level = sample.abs();
+    if level <= SOFT_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - SOFT_KNEE;
+    let bent = headroom.mul_add(((level - SOFT_KNEE) / headroom).tanh(), SOFT_KNEE);
+    bent.copysign(sample)
+}
+
+/// Noise in `-1.0..1.0` with a triangular distribution: the sum of two uniform draws.
+fn triangular_noise() -> f32 {
+    uniform() - uniform()
+}
+
+/// A draw in `0.0..1.0` from a xorshift generator; good enough for dither.
+fn uniform() -> f32 {
+    let mut next = 0;
+    let _ = DITHER_STATE.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |mut x| {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        next = x;
+        Some(x)
+    });
+    #[allow(clippy::cast_precision_loss)]
+    let draw = (next >> 8) as f32 / (1 << 24) as f32;
+    draw
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(pcm: &[u8]) -> Vec<i16> {
+        pcm.chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn converts_and_clips_f32_samples() {
+        let hard = F32Conversion::default();
+        assert_eq!(
+            samples(&f32_to_pcm16(&[0.0, 0.5, -1.0, 1.5, -3.0, f32::NAN], hard)),
+            [0, 16384, -32767, 32767, -32768, 0]
+        );
+
+        let soft = F32Conversion {
+            clipping: Clipping::Soft,
+            ..F32Conversion::default()
+        };
+        let soft = samples(&f32_to_pcm16(&[0.5, 0.9, 1.5, -4.0], soft));
+        assert_eq!(soft[0], 16384);
+        assert!(soft[1] > 26214 && soft[1] < 29491, "{soft:?}");
+        assert!(soft[2] > soft[1] && soft[2] < 32767, "{soft:?}");
+        assert!(soft[3] < -32000, "{soft:?}");
+
+        let dithered = F32Conversion {
+            dither: true,
+            ..F32Conversion::default()
+        };
+        let quiet = samples(&f32_to_pcm16(&[0.25; 64], dithered));
+        assert!(quiet.iter().all(|&s| (s - 8192).abs() <= 1), "{quiet:?}");
+
+        let back = i16_to_f32(&samples(&f32_to_pcm16(&[0.25, -0.5], hard)));
+        assert!((back[0] - 0.25).abs() < 1e-4 && (back[1] + 0.5).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,115 @@
+//! Responses scheduled to start after a delay unless the user takes their turn first.
+
+use crate::Result;
+use crate::protocol::models::{Item, ResponseConfig, Role};
+use crate::protocol::server_events::ServerEvent;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::time::Instant;
+
+use super::session::SessionHandle;
+
+static NEXT_SCHEDULE_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_schedule_id() -> u64 {
+    NEXT_SCHEDULE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Scheduled responses owned by the event loop.
+#[derive(Default)]
+pub struct ResponseSchedule {
+    entries: Vec<ScheduledEntry>,
+}
+
+struct ScheduledEntry {
+    id: u64,
+    due: Instant,
+    config: ResponseConfig,
+}
+
+impl ResponseSchedule {
+    pub fn insert(&mut self, id: u64, due: Instant, config: ResponseConfig) {
+        self.entries.push(ScheduledEntry { id, due, config });
+    }
+
+    /// Drop the entry `id`; `false` if it already fired or was cancelled.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        self.take(id).is_some()
+    }
+
+    pub fn take(&mut self, id: u64) -> Option<ResponseConfig> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.swap_remove(index).config)
+    }
+
+    /// The entry that fires first.
+    pub fn next_due(&self) -> Option<(u64, Instant)> {
+        self.entries
+            .iter()
+            .min_by_key(|entry| entry.due)
+            .map(|entry| (entry.id, entry.due))
+    }
+
+    /// Cancel everything once the user speaks or sends a message.
+    pub fn observe(&mut self, evt: &ServerEvent) {
+        if !self.entries.is_empty() && is_user_turn(evt) {
+            tracing::debug!(
+                count = self.entries.len(),
+                "user took their turn; cancelling scheduled responses"
+            );
+            self.entries.clear();
+        }
+    }
+}
+
+/// Resolve with the ID of `next` once it is due; never resolves for `None`.
+pub async fn wait_until_due(next: Option<(u64, Instant)>) -> u64 {
+    match next {
+        Some((id, due)) => {
+            tokio::time::sleep_until(due).await;
+            id
+        }
+        None => std::future::pending().await,
+    }
+}
+
+const fn is_user_turn(evt: &ServerEvent) -> bool {
+    match evt {
+        ServerEvent::InputAudioBufferSpeechStarted { .. }
+        | ServerEvent::InputAudioBufferCommitted { .. } => true,
+        ServerEvent::ConversationItemCreated { item, .. }
+        | ServerEvent::ConversationItemAdded { item, .. } => matches!(
+            item,
+            Item::Message {
+                role: Role::User,
+                ..
+            }
+        ),
+        _ => false,
+    }
+}
+
+/// A response queued by `Session::respond_after`.
+///
+/// It is cancelled automatically if the user speaks or sends a message before it is
+/// due, and skipped if another response is still running at that point. Dropping the
+/// handle keeps it scheduled.
+pub struct ScheduledResponse {
+    handle: SessionHandle,
+    id: u64,
+}
+
+impl ScheduledResponse {
+    pub(super) const fn new(handle: SessionHandle, id: u64) -> Self {
+        Self { handle, id }
+    }
+
+    /// Cancel the response if it has not started yet.
+    ///
+    /// Returns `false` if it already fired or was cancelled.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn cancel(&self) -> Result<bool> {
+        self.handle.cancel_scheduled(self.id).await
+    }
+}
@@ -0,0 +1,114 @@
+//! Rewriting generated tool schemas into the strict function-calling shape.
+
+use serde_json::{Map, Value, json};
+
+/// Nesting depth at which `$ref` inlining gives up, assuming a recursive type.
+const MAX_DEPTH: usize = 32;
+
+/// Rewrite `schema` in place so every object lists all of its properties as required
+/// and sets `additionalProperties: false`.
+///
+/// `$ref`s are inlined and the root `definitions` dropped. Properties that were
+/// optional become nullable instead.
+pub fn make_strict(schema: &mut Value) -> Result<(), String> {
+    let definitions = schema
+        .as_object_mut()
+        .and_then(|root| {
+            root.remove("$schema");
+            root.remove("definitions")
+        })
+        .and_then(|defs| match defs {
+            Value::Object(defs) => Some(defs),
+            _ => None,
+        })
+        .unwrap_or_default();
+    rewrite(schema, &definitions, 0)
+}
+
+fn rewrite(
+    schema: &mut Value,
+    definitions: &Map<String, Value>,
+    depth: usize,
+) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err("schema is recursive or too deeply nested for strict mode".to_string());
+    }
+    let Some(object) = schema.as_object_mut() else {
+        return Ok(());
+    };
+
+    if let Some(Value::String(reference)) = object.remove("$ref") {
+        let name = reference
+            .strip_prefix("#/definitions/")
+            .ok_or_else(|| format!("unsupported $ref `{reference}`"))?;
+        let Some(Value::Object(target)) = definitions.get(name) else {
+            return Err(format!("unresolved $ref `{reference}`"));
+        };
+        // Sibling keywords such as `description` win over the referenced schema's.
+        for (key, value) in target {
+            object.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(Value::Array(variants)) = object.get_mut(key) {
+            for variant in variants {
+                rewrite(variant, definitions, depth + 1)?;
+            }
+        }
+    }
+    if let Some(items) = object.get_mut("items") {
+        rewrite(items, definitions, depth + 1)?;
+    }
+
+    let required: Vec<String> = match object.get("required") {
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let Some(Value::Object(properties)) = object.get_mut("properties") else {
+        return Ok(());
+    };
+    for (name, property) in properties.iter_mut() {
+        rewrite(property, definitions, depth + 1)?;
+        if !required.contains(name) {
+            make_nullable(property);
+        }
+    }
+    let all: Vec<Value> = properties.keys().cloned().map(Value::String).collect();
+    object.insert("required".to_string(), Value::Array(all));
+    object.insert("additionalProperties".to_string(), Value::Bool(false));
+    Ok(())
+}
+
+fn make_nullable(schema: &mut Value) {
+    if accepts_null(schema) {
+        return;
+    }
+    match schema.get_mut("type") {
+        Some(Value::String(ty)) => {
+            let ty = std::mem::take(ty);
+            schema["type"] = json!([ty, "null"]);
+        }
+        Some(Value::Array(types)) => types.push(json!("null")),
+        _ => {
+            let inner = std::mem::take(schema);
+            *schema = json!({ "anyOf": [inner, { "type": "null" }] });
+        }
+    }
+}
+
+fn accepts_null(schema: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::String(ty)) => ty == "null",
+        Some(Value::Array(types)) => types.iter().any(|ty| ty == "null"),
+        _ => ["anyOf", "oneOf"].iter().any(|key| {
+            schema
+                .get(key)
+                .and_then(Value::as_array)
+                .is_some_and(|variants| variants.iter().any(accepts_null))
+        }),
+    }
+}
@@ -1,28 +1,90 @@
 use crate::protocol::client_events::ClientEvent;
 use crate::protocol::models::{
-    ContentPart, Item, ItemStatus, ResponseConfig, SessionConfig, SessionUpdate,
-    SessionUpdateConfig,
+    AudioConfig, ContentPart, Include, InputAudioConfig, InputAudioTranscription, Item, ItemStatus,
+    Nullable, OutputAudioConfig, Response, ResponseConfig, ResponseStatus, SessionConfig,
+    SessionUpdate, SessionUpdateConfig,
 };
 use crate::protocol::server_events::ServerEvent;
-use crate::{Error, Result};
-
+use crate::{Error, Result, ServerClose};
+
+use super::agent::{self, Agent};
+use super::amd::{AmdMonitor, AnsweringMachineDetection};
+use super::analytics::AnalyticsTracker;
+use super::audio_fallback::{self, AudioFailurePolicy, AudioFailures};
+use super::audio_file::{self, Progress};
+use super::audio_lease::{AudioInputLease, AudioLeases};
+use super::barge_in::{BargeInGate, BargeInPolicy, Interrupt};
+use super::call::{CallLifecycle, CallStatus};
+use super::channel::{
+    self, ChannelCapacities, ChannelMonitor, DroppedMessages, Notice, OverflowPolicy, Receiver,
+};
+use super::clock::{self, Clock, SharedClock};
+use super::codec::{AudioCodec, CodecRegistry, Pcm16Codec, SessionCodecs};
+use super::conversation::{ConversationGap, ConversationLog, ConversationSnapshot};
+use super::correlation::SentEventLog;
+use super::deadline::{Deadline, DeadlineState, Expired};
+use super::dedup::{self, SeenEvents};
+use super::event_log::{EventLog, LoggedTransport};
 use super::events::{EventStream, SdkEvent};
+use super::expiry::{ExpiryMonitor, ExpiryPolicy, Reconnect, Renewal, UserTurn};
+use super::fork::ConversationFork;
+use super::guardrails::{GuardrailStage, Guardrails};
 use super::handlers::EventHandlers;
+use super::handoff;
+use super::instructions::{self, InstructionGuard, InstructionHistory, instructions_update};
+use super::item_create;
+use super::key_pool::{KeyLease, KeyPool};
+use super::level::LevelMeter;
+use super::lifecycle::{CloseReason, SessionState, StateReporter, StateWatch};
+use super::oob::{self, ClassifyInput};
+use super::ordering::OrderingMonitor;
+use super::outbound_gate::OutboundGate;
+use super::report::{CallReport, CallReportTracker};
+use super::resend::{Replayed, ResendQueue, ResendTransport};
 use super::response::ResponseBuilder;
-use super::tools::{ToolCall, ToolDispatcher, ToolResult};
+use super::retrieval;
+use super::routing::{
+    AudioRoute, AudioRouteMode, ResponseRoute, ResponseRoutes, RouteTable, RoutedChunk,
+};
+use super::samples::{self, F32Conversion};
+use super::schedule::{self, ResponseSchedule, ScheduledResponse};
+use super::speakable::{SpeakableChunk, SpeakableChunking, SpeakableSplitter, SpeakableStream};
+use super::token_budget::{TokenBudget, TokenBudgetState};
+use super::tool_context::{self, CancellationToken, ToolContext};
+use super::tools::{
+    self, BoxFuture, ToolCall, ToolConcurrency, ToolDispatcher, ToolErrorPolicy, ToolFailure,
+    ToolResult,
+};
+use super::transcription::{RecommitBuffer, TranscriptionFailure, TranscriptionFailurePolicy};
+use super::transcripts::{TranscriptFeed, TranscriptStream, TranscriptTap};
 use super::transport::Transport;
-use super::voice::{VoiceEvent, VoiceEventStream};
+use super::voice::{
+    AudioSamples, AudioTimeline, IdleTimeoutPolicy, LocalAudioPosition, LocalAudioQueue,
+    VoiceEvent, VoiceEventStream,
+};
 use base64::Engine as _;
 use base64::engine::general_purpose;
+use futures::FutureExt;
 use futures::Stream;
 use futures::StreamExt;
-use std::collections::HashMap;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::ControlFlow;
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc, oneshot};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc, oneshot, watch};
 
 #[derive(Clone)]
 pub struct SessionHandle {
-    sender: mpsc::Sender<Command>,
+    sender: CommandSender,
+    input_guardrails: InputGuardrails,
+    state: StateWatch,
+    outbound_gate: OutboundGate,
+    pending: PendingRequests,
+    cancellation: CancellationToken,
+    request_timeout: Duration,
 }
 
 pub struct AudioIn<'a> {
@@ -30,13 +92,127 @@ pub struct AudioIn<'a> {
 }
 
 pub struct Session {
-    sender: mpsc::Sender<Command>,
-    text_rx: mpsc::Receiver<String>,
-    event_rx: mpsc::Receiver<SdkEvent>,
-    voice_rx: mpsc::Receiver<VoiceEvent>,
-    audio_rx: mpsc::Receiver<super::voice::AudioChunk>,
-    transcript_rx: mpsc::Receiver<super::voice::TranscriptChunk>,
+    sender: CommandSender,
+    text_rx: Receiver<String>,
+    event_rx: Receiver<SdkEvent>,
+    voice_rx: Receiver<VoiceEvent>,
+    audio_rx: Receiver<super::voice::AudioChunk>,
+    transcript_rx: Receiver<super::voice::TranscriptChunk>,
+    speakable_rx: Receiver<SpeakableChunk>,
+    monitor: ChannelMonitor,
     active_response_id: Arc<Mutex<Option<String>>>,
+    negotiated: Arc<Mutex<Option<crate::protocol::models::Session>>>,
+    readiness: watch::Receiver<Readiness>,
+    pending: PendingRequests,
+    input_guardrails: InputGuardrails,
+    connection: Arc<std::sync::Mutex<ConnectionInfo>>,
+    instructions: InstructionHistory,
+    state: StateWatch,
+    outbound: crate::OutboundStats,
+    routes: RouteTable,
+    transcripts: TranscriptTap,
+    outbound_gate: OutboundGate,
+    resend: ResendQueue,
+    call: CallStatus,
+    call_rx: watch::Receiver<Option<CallLifecycle>>,
+    codecs: CodecRegistry,
+    cancellation: CancellationToken,
+    request_timeout: Duration,
+}
+
+/// Identifiers of the underlying connection, for logging and correlation.
+#[derive(Debug, Clone, Default)]
+struct ConnectionInfo {
+    session_id: Option<String>,
+    model: Option<String>,
+    call_id: Option<String>,
+}
+
+/// Requests awaiting a reply from the event loop.
+#[derive(Default)]
+struct Pending {
+    /// Out-of-band responses keyed by their correlation ID.
+    responses: HashMap<String, oneshot::Sender<crate::protocol::models::Response>>,
+    /// `conversation.item.retrieve` callers keyed by item ID.
+    items: HashMap<String, Vec<oneshot::Sender<Result<Item>>>>,
+    /// `create_item` callers keyed by the ID of the item they create.
+    created: HashMap<String, oneshot::Sender<Result<Item>>>,
+    /// `cancel_active_response` callers keyed by response ID.
+    cancels: HashMap<String, Vec<oneshot::Sender<Result<crate::protocol::models::Response>>>>,
+    /// Call IDs of sent tool outputs the server has not added to the conversation yet,
+    /// oldest first; a rejected output is never added, so the oldest are forgotten.
+    tool_outputs: VecDeque<String>,
+}
+
+impl Pending {
+    /// Fail every waiter with `Error::ConnectionClosed` once the event loop has exited;
+    /// no reply can arrive any more.
+    fn close(&mut self) {
+        // Dropping a sender resolves its waiter with `Error::ConnectionClosed`.
+        self.responses.clear();
+        for tx in self.items.drain().flat_map(|(_, waiters)| waiters) {
+            let _ = tx.send(Err(Error::ConnectionClosed));
+        }
+        for (_, tx) in self.created.drain() {
+            let _ = tx.send(Err(Error::ConnectionClosed));
+        }
+        for tx in self.cancels.drain().flat_map(|(_, waiters)| waiters) {
+            let _ = tx.send(Err(Error::ConnectionClosed));
+        }
+        self.tool_outputs.clear();
+    }
+}
+
+/// Forget the waiters for `key` that stopped waiting, leaving any others in place.
+fn forget_closed<T>(waiters: &mut HashMap<String, Vec<oneshot::Sender<T>>>, key: &str) {
+    if let Some(list) = waiters.get_mut(key) {
+        list.retain(|tx| !tx.is_closed());
+        if list.is_empty() {
+            waiters.remove(key);
+        }
+    }
+}
+
+/// Report the monitor's notices as SDK events. Those the events reader has no room
+/// for are held for the next turn rather than discarded, since the reader may be the
+/// one falling behind.
+fn report_notices(monitor: &ChannelMonitor, events: &channel::Sender<SdkEvent>) {
+    let mut notices = monitor.take_notices().into_iter();
+    while let Some(notice) = notices.next() {
+        let event = match notice {
+            Notice::Lagging { channel, dropped } => SdkEvent::ConsumerLagging {
+                channel: channel.to_string(),
+                dropped,
+            },
+            Notice::Closed { channel } => SdkEvent::ConsumerClosed {
+                channel: channel.to_string(),
+            },
+        };
+        if let Ok(Some(_)) = events.offer(event) {
+            monitor.hold_notices(std::iter::once(notice).chain(notices).collect());
+            return;
+        }
+    }
+}
+
+/// How long a request waits for the server's reply unless configured otherwise.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Prefix of the client event ID used for `cancel_active_response`.
+const CANCEL_EVENT_PREFIX: &str = "sdk_cancel_";
+
+/// Sent tool outputs remembered until the server adds them.
+const PENDING_TOOL_OUTPUTS: usize = 64;
+
+type PendingRequests = Arc<Mutex<Pending>>;
+
+/// Progress of the initial `session.created` / `session.updated` handshake.
+#[derive(Debug, Clone)]
+enum Readiness {
+    Pending,
+    Created,
+    Ready(Box<crate::protocol::models::Session>),
+    Failed(crate::error::ServerError),
 }
 
 impl Session {
@@ -44,9 +220,29 @@ impl Session {
     pub fn handle(&self) -> SessionHandle {
         SessionHandle {
             sender: self.sender.clone(),
+            input_guardrails: self.input_guardrails.clone(),
+            state: self.state.clone(),
+            outbound_gate: self.outbound_gate.clone(),
+            pending: Arc::clone(&self.pending),
+            cancellation: self.cancellation.clone(),
+            request_timeout: self.request_timeout,
         }
     }
 
+    /// Take the input audio lease, waiting for the current holder to release it; see
+    /// [`SessionHandle::lease_audio_input`].
+    pub async fn lease_audio_input(&self) -> AudioInputLease {
+        self.handle().lease_audio_input().await
+    }
+
+    /// Take the input audio lease if nobody holds it.
+    ///
+    /// # Errors
+    /// Returns `Error::AudioInputLeased` if another writer holds the lease.
+    pub fn try_lease_audio_input(&self) -> Result<AudioInputLease> {
+        self.handle().try_lease_audio_input()
+    }
+
     /// Convenience audio input helper.
     #[must_use]
     pub const fn audio(&self) -> AudioIn<'_> {
@@ -58,30 +254,153 @@ impl Session {
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the send fails.
     pub async fn say(&self, text: &str) -> Result<()> {
-        let item = Item::Message {
-            id: None,
-            status: None,
-            role: crate::protocol::models::Role::User,
-            content: vec![ContentPart::InputText {
-                text: text.to_string(),
-            }],
-        };
-
+        let text = self.input_guardrails.apply(text.to_string()).await?;
         let event = ClientEvent::ConversationItemCreate {
             event_id: None,
             previous_item_id: None,
-            item: Box::new(item),
+            item: Box::new(user_text(text)),
         };
 
         self.send_event(event).await
     }
 
+    /// Like [`say`](Self::say), but wait for the server to add the message and return
+    /// it with its ID.
+    ///
+    /// # Errors
+    /// Returns an error if a guardrail blocks the text, the send fails or the server
+    /// rejects the item.
+    pub async fn say_item(&self, text: &str) -> Result<Item> {
+        let text = self.input_guardrails.apply(text.to_string()).await?;
+        self.create_item(user_text(text)).await
+    }
+
+    /// Add `item` to the conversation with `conversation.item.create` and wait for the
+    /// server to add it, returning the item as the server stored it.
+    ///
+    /// An item without an ID is given one, so the returned item can be named in later
+    /// truncate, delete or retrieve requests.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails or the server rejects the item, and
+    /// `Error::InvalidClientEvent` if an item with the same ID is still being created.
+    pub async fn create_item(&self, item: Item) -> Result<Item> {
+        self.create_item_after(item, None).await
+    }
+
+    /// Add a past tool call and its output to the conversation, so the model answers
+    /// as if it had called `name` with `arguments` and got `output` back.
+    ///
+    /// Useful for few-shot examples and for restoring a resumed session. The call gets
+    /// a fresh call ID and the output is placed right after it. Nothing is run and no
+    /// response is requested. Returns the call and output items as the server stored
+    /// them. If the output cannot be added, the call is deleted again rather than left
+    /// without an answer.
+    ///
+    /// # Errors
+    /// Returns an error if a send fails or the server rejects either item.
+    pub async fn inject_tool_exchange(
+        &self,
+        name: &str,
+        arguments: &serde_json::Value,
+        output: &serde_json::Value,
+    ) -> Result<(Item, Item)> {
+        let call_id = item_create::new_call_id();
+        let call = self
+            .create_item(Item::FunctionCall {
+                id: None,
+                status: None,
+                name: name.to_string(),
+                call_id: call_id.clone(),
+                arguments: arguments.to_string(),
+            })
+            .await?;
+        let output = self
+            .create_item_after(
+                Item::FunctionCallOutput {
+                    id: None,
+                    call_id,
+                    output: output.to_string(),
+                },
+                call.id().map(str::to_string),
+            )
+            .await;
+        match (output, call.id()) {
+            (Ok(output), _) => Ok((call, output)),
+            (Err(err), Some(item_id)) => {
+                let delete = ClientEvent::ConversationItemDelete {
+                    event_id: None,
+                    item_id: item_id.to_string(),
+                };
+                if let Err(delete_err) = self.send_event(delete).await {
+                    tracing::warn!(
+                        item_id,
+                        "failed to delete the orphaned tool call: {delete_err}"
+                    );
+                }
+                Err(err)
+            }
+            (Err(err), None) => Err(err),
+        }
+    }
+
+    async fn create_item_after(
+        &self,
+        item: Item,
+        previous_item_id: Option<String>,
+    ) -> Result<Item> {
+        let item_id = item
+            .id()
+            .map_or_else(item_create::new_item_id, str::to_string);
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().await;
+            if pending
+                .created
+                .get(&item_id)
+                .is_some_and(|waiter| !waiter.is_closed())
+            {
+                return Err(Error::InvalidClientEvent(format!(
+                    "item `{item_id}` is already being created"
+                )));
+            }
+            pending.created.insert(item_id.clone(), tx);
+        }
+
+        let event = ClientEvent::ConversationItemCreate {
+            event_id: Some(item_create::event_id(&item_id)),
+            previous_item_id,
+            item: Box::new(item.with_id(item_id.clone())),
+        };
+        let reply = match self.send_event(event).await {
+            Ok(()) => self.await_reply(rx, "the created item").await,
+            Err(err) => {
+                drop(rx);
+                Err(err)
+            }
+        };
+        if reply.is_err() {
+            let mut pending = self.pending.lock().await;
+            if pending
+                .created
+                .get(&item_id)
+                .is_some_and(oneshot::Sender::is_closed)
+            {
+                pending.created.remove(&item_id);
+            }
+        }
+        reply?
+    }
+
     /// Await the next completed text response, if any.
     ///
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the stream fails.
     pub async fn next_text(&mut self) -> Result<Option<String>> {
-        Ok(self.text_rx.recv().await)
+        match self.text_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
     }
 
     /// Await the next SDK event.
@@ -89,13 +408,17 @@ impl Session {
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the stream fails.
     pub async fn next_event(&mut self) -> Result<Option<SdkEvent>> {
-        Ok(self.event_rx.recv().await)
+        match self.event_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
     }
 
-    /// Stream SDK events.
+    /// Stream SDK events. The stream ends when the session closes; use
+    /// [`Self::next_event`] to tell an overflow apart from a normal close.
     #[must_use]
     pub const fn events(&mut self) -> EventStream<'_> {
-        EventStream::new(&mut self.event_rx)
+        EventStream::from_session(&mut self.event_rx)
     }
 
     /// Await the next voice event.
@@ -103,13 +426,70 @@ impl Session {
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the stream fails.
     pub async fn next_voice_event(&mut self) -> Result<Option<VoiceEvent>> {
-        Ok(self.voice_rx.recv().await)
+        match self.voice_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
     }
 
     /// Stream voice events.
     #[must_use]
     pub const fn voice_events(&mut self) -> VoiceEventStream<'_> {
-        VoiceEventStream::new(&mut self.voice_rx)
+        VoiceEventStream::from_session(&mut self.voice_rx)
+    }
+
+    /// Where the session is in its life; see [`SessionHandle::state`].
+    #[must_use]
+    pub fn state(&self) -> SessionState {
+        self.state.current()
+    }
+
+    /// Why the session closed, or `None` while it is still open. For a server close
+    /// over WebSocket this carries the close frame; `ServerClose::error` maps it to a
+    /// typed error.
+    #[must_use]
+    pub fn closed_reason(&self) -> Option<CloseReason> {
+        match self.state() {
+            SessionState::Closed { reason } => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// The codecs the session decodes audio with. Codecs registered here apply from
+    /// the next audio, and can also encode or decode audio for a relay.
+    #[must_use]
+    pub const fn codecs(&self) -> &CodecRegistry {
+        &self.codecs
+    }
+
+    /// Serialized bytes sent so far per event type, and the largest event, over every
+    /// connection the session renewed onto. Empty for sessions not connected over
+    /// WebSocket.
+    #[must_use]
+    pub fn outbound_stats(&self) -> crate::OutboundTotals {
+        self.outbound.snapshot()
+    }
+
+    /// Messages discarded so far because their reader had gone away, such as the
+    /// events of a dropped `ResponseRoute`. Each such reader is reported once as
+    /// `SdkEvent::ConsumerClosed`.
+    #[must_use]
+    pub fn undelivered_messages(&self) -> u64 {
+        self.monitor.undelivered()
+    }
+
+    /// Messages discarded so far because a reader fell behind, e.g. under
+    /// `OverflowPolicy::DropOldest`.
+    #[must_use]
+    pub fn dropped_messages(&self) -> DroppedMessages {
+        DroppedMessages {
+            text: self.text_rx.dropped(),
+            events: self.event_rx.dropped(),
+            voice: self.voice_rx.dropped(),
+            audio: self.audio_rx.dropped(),
+            transcripts: self.transcript_rx.dropped(),
+            speakable: self.speakable_rx.dropped(),
+        }
     }
 
     /// Returns the ID of the currently active response, if any.
@@ -122,12 +502,167 @@ impl Session {
         self.active_response_id.lock().await.is_some()
     }
 
+    /// Returns the latest session configuration accepted by the server.
+    ///
+    /// Updated from `session.created` and `session.updated`; `None` until the first arrives.
+    pub async fn server_session(&self) -> Option<crate::protocol::models::Session> {
+        self.negotiated.lock().await.clone()
+    }
+
+    /// The local mirror of the session's effective configuration.
+    ///
+    /// Every field comes from the server: the configuration of the last
+    /// `session.created` or `session.updated`, which reports the whole session. Updates
+    /// sent but not yet confirmed are not included. `None` until the first session
+    /// event arrives.
+    pub async fn effective_config(&self) -> Option<SessionConfig> {
+        self.negotiated
+            .lock()
+            .await
+            .as_ref()
+            .map(|session| session.config.clone())
+    }
+
+    /// Trace identifiers from the latest session payload, e.g. to link this session
+    /// to the traces dashboard. `None` until the server reports tracing as enabled.
+    pub async fn trace_info(&self) -> Option<crate::protocol::models::TraceInfo> {
+        self.negotiated.lock().await.as_ref()?.trace_info()
+    }
+
+    /// Server-assigned session ID, known once `session.created` arrives.
+    #[must_use]
+    pub fn id(&self) -> Option<String> {
+        self.connection_info().session_id
+    }
+
+    /// Model in use: the one reported by the server, else the one requested at connect.
+    #[must_use]
+    pub fn model(&self) -> Option<String> {
+        self.connection_info().model
+    }
+
+    /// Call ID this session was attached to, if any.
+    #[must_use]
+    pub fn call_id(&self) -> Option<String> {
+        self.connection_info().call_id
+    }
+
+    /// Where the call this session is attached to stands, or `None` for a session
+    /// without a `call_id`.
+    ///
+    /// A call starts `Ringing`, is `Answered` once the server accepts the session, and
+    /// ends in `RemoteHangup` when the server closes the connection cleanly, in
+    /// `LocalHangup` after [`Self::hangup`], or in `Failed` when the connection drops
+    /// with an error.
+    #[must_use]
+    pub fn call_state(&self) -> Option<CallLifecycle> {
+        self.call_rx.borrow().clone()
+    }
+
+    /// Await the next change of [`Self::call_state`], starting with `Ringing`.
+    ///
+    /// Changes in quick succession are reported as the latest one. Returns `None` for a
+    /// session without a call, and once the call's end has been reported.
+    pub async fn next_call_event(&mut self) -> Option<CallLifecycle> {
+        let current = self.call_rx.borrow().clone()?;
+        if current.is_ended() && !self.call_rx.has_changed().unwrap_or(false) {
+            return None;
+        }
+        // The session holds the sender, so this only fails once the call is over.
+        self.call_rx.changed().await.ok()?;
+        self.call_rx.borrow_and_update().clone()
+    }
+
+    /// Hang up the call this session is attached to through the REST API, ending it
+    /// for every participant. The call state becomes `LocalHangup` once the request
+    /// succeeds.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidClientEvent` if the session has no `call_id`, or an error
+    /// if the request fails.
+    pub async fn hangup(&self, rest: &crate::transport::rest::RealtimeRestAdapter) -> Result<()> {
+        let Some(call_id) = self.call_id() else {
+            return Err(Error::InvalidClientEvent(
+                "hangup requires a session attached to a call_id".to_string(),
+            ));
+        };
+        rest.hangup(&call_id).await?;
+        self.call.hung_up();
+        Ok(())
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        self.connection_info_mut().clone()
+    }
+
+    fn connection_info_mut(&self) -> std::sync::MutexGuard<'_, ConnectionInfo> {
+        self.connection
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Wait until the server has acknowledged the session configuration.
+    ///
+    /// Resolves with the accepted session once `session.updated` arrives, or with the
+    /// server's error if one is reported first.
+    ///
+    /// # Errors
+    /// Returns `Error::Api` if the server rejects the configuration, `Error::Timeout`
+    /// if no acknowledgement arrives in time, `Error::Cancelled` if the session's
+    /// cancellation token fires first, or `Error::ConnectionClosed`.
+    pub async fn wait_ready(&self, timeout: Duration) -> Result<crate::protocol::models::Session> {
+        let mut rx = self.readiness.clone();
+        let settled =
+            rx.wait_for(|state| matches!(state, Readiness::Ready(_) | Readiness::Failed(_)));
+        let state = self
+            .until_cancelled(None, tokio::time::timeout(timeout, settled))
+            .await?
+            .map_err(|_| Error::Timeout("session.updated"))?
+            .map_err(|_| Error::ConnectionClosed)?
+            .clone();
+        match state {
+            Readiness::Ready(session) => Ok(*session),
+            Readiness::Failed(error) => Err(Error::from(error)),
+            // `wait_for` only returns a settled state; treat anything else as the
+            // connection having gone away rather than panicking.
+            Readiness::Pending | Readiness::Created => Err(Error::ConnectionClosed),
+        }
+    }
+
     /// Await the next decoded audio chunk.
     ///
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the stream fails.
     pub async fn next_audio_chunk(&mut self) -> Result<Option<super::voice::AudioChunk>> {
-        Ok(self.audio_rx.recv().await)
+        match self.audio_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Await the next audio chunk with its audio decoded to `f32` samples by the codec
+    /// of the session's output format.
+    ///
+    /// # Errors
+    /// As `next_audio_chunk`, and `Error::InvalidAudio` if no codec handles the output
+    /// format or the chunk is not valid in it.
+    pub async fn next_audio_samples(&mut self) -> Result<Option<AudioSamples>> {
+        let Some(chunk) = self.next_audio_chunk().await? else {
+            return Ok(None);
+        };
+        let codec: Arc<dyn AudioCodec> = if chunk.local {
+            Arc::new(Pcm16Codec::default())
+        } else {
+            let mut codecs = SessionCodecs::new(self.codecs.clone());
+            if let Some(session) = self.negotiated.lock().await.as_ref() {
+                codecs.update_format(session);
+            }
+            codecs.output().ok_or_else(|| {
+                Error::InvalidAudio("no codec for the session's output format".to_string())
+            })?
+        };
+        let samples = chunk.to_f32(&*codec)?;
+        Ok(Some(AudioSamples { chunk, samples }))
     }
 
     /// Await the next transcript chunk.
@@ -135,7 +670,38 @@ impl Session {
     /// # Errors
     /// Returns an error if the SDK is not fully initialized or the stream fails.
     pub async fn next_transcript(&mut self) -> Result<Option<super::voice::TranscriptChunk>> {
-        Ok(self.transcript_rx.recv().await)
+        match self.transcript_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Open a stream of what the user and the assistant say, merged from input audio
+    /// transcription and output audio transcript events in the order they arrive.
+    ///
+    /// Transcripts are only kept while a stream is open; opening another replaces it.
+    #[must_use]
+    pub fn transcripts(&self) -> TranscriptStream {
+        self.transcripts.open()
+    }
+
+    /// Await the next chunk of response text ready to speak. Only produced when the
+    /// session was built with `speakable_chunks`.
+    ///
+    /// # Errors
+    /// Returns an error if the SDK is not fully initialized or the stream fails.
+    pub async fn next_speakable_chunk(&mut self) -> Result<Option<SpeakableChunk>> {
+        match self.speakable_rx.recv().await {
+            Some(value) => Ok(Some(value)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Stream response text in speakable chunks, in order, with the rest of each text
+    /// part flushed when it is done.
+    #[must_use]
+    pub const fn speakable_text(&mut self) -> SpeakableStream<'_> {
+        SpeakableStream::new(&mut self.speakable_rx)
     }
 
     /// Send a raw protocol event.
@@ -171,6 +737,30 @@ impl Session {
         self.audio_in_commit().await
     }
 
+    /// Append `f32` samples in `-1.0..=1.0` to the input audio buffer, clamping any
+    /// beyond full scale.
+    ///
+    /// # Errors
+    /// Returns an error if encoding or send fails.
+    pub async fn audio_in_append_f32(&self, samples: &[f32]) -> Result<()> {
+        self.audio_in_append_f32_with(samples, F32Conversion::default())
+            .await
+    }
+
+    /// Like [`audio_in_append_f32`](Self::audio_in_append_f32), with a choice of
+    /// clipping and dither.
+    ///
+    /// # Errors
+    /// Returns an error if encoding or send fails.
+    pub async fn audio_in_append_f32_with(
+        &self,
+        samples: &[f32],
+        conversion: F32Conversion,
+    ) -> Result<()> {
+        self.audio_in_append_bytes(&samples::f32_to_pcm16(samples, conversion))
+            .await
+    }
+
     /// Append raw PCM16 bytes to the input audio buffer.
     ///
     /// # Errors
@@ -196,32 +786,132 @@ impl Session {
         self.audio_in_commit().await
     }
 
+    /// Send a PCM16 or WAV file as input audio and commit it.
+    ///
+    /// A WAV file must be 16-bit mono PCM at 24kHz; any other file is read as raw
+    /// PCM16. The audio goes out in 1MB appends, each queued only once the previous
+    /// one is, so a long file is paced by the connection rather than buffered whole.
+    /// `progress` hears about each append.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, is not PCM16 audio, or a send fails.
+    pub async fn send_audio_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        progress: Progress,
+    ) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let bytes = tokio::task::spawn_blocking(move || std::fs::read(path))
+            .await
+            .map_err(std::io::Error::other)??;
+        let pcm = audio_file::pcm16(&bytes)?;
+        if pcm.is_empty() {
+            return Ok(());
+        }
+        let mut sent = 0;
+        for chunk in pcm.chunks(audio_file::FILE_CHUNK_BYTES) {
+            self.audio_in_append_bytes(chunk).await?;
+            sent += chunk.len();
+            progress.report(sent, pcm.len());
+        }
+        self.audio_in_commit().await
+    }
+
     /// Stream PCM16 audio chunks into the input buffer, committing after each chunk.
     ///
     /// # Errors
-    /// Returns an error if encoding or send fails.
-    pub async fn stream_audio_pcm16<S>(&self, mut stream: S) -> Result<()>
+    /// Returns an error if encoding or send fails, or `Error::Cancelled` if the
+    /// session's cancellation token fires first.
+    pub async fn stream_audio_pcm16<S>(&self, stream: S) -> Result<()>
     where
         S: Stream<Item = Vec<i16>> + Unpin,
     {
-        while let Some(chunk) = stream.next().await {
-            self.send_audio_pcm16(&chunk).await?;
-        }
-        Ok(())
+        self.stream_audio_pcm16_inner(stream, None).await
+    }
+
+    /// [`Self::stream_audio_pcm16`], stopping early when `token` is cancelled.
+    ///
+    /// # Errors
+    /// As `stream_audio_pcm16`, with `Error::Cancelled` also when `token` fires.
+    pub async fn stream_audio_pcm16_cancellable<S>(
+        &self,
+        stream: S,
+        token: &CancellationToken,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Vec<i16>> + Unpin,
+    {
+        self.stream_audio_pcm16_inner(stream, Some(token)).await
+    }
+
+    async fn stream_audio_pcm16_inner<S>(
+        &self,
+        mut stream: S,
+        token: Option<&CancellationToken>,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Vec<i16>> + Unpin,
+    {
+        self.until_cancelled(token, async {
+            while let Some(chunk) = stream.next().await {
+                self.send_audio_pcm16(&chunk).await?;
+            }
+            Ok(())
+        })
+        .await?
     }
 
     /// Stream raw PCM16 byte chunks into the input buffer, committing after each chunk.
     ///
     /// # Errors
-    /// Returns an error if encoding or send fails.
-    pub async fn stream_audio_bytes<S>(&self, mut stream: S) -> Result<()>
+    /// Returns an error if encoding or send fails, or `Error::Cancelled` if the
+    /// session's cancellation token fires first.
+    pub async fn stream_audio_bytes<S>(&self, stream: S) -> Result<()>
     where
         S: Stream<Item = Vec<u8>> + Unpin,
     {
-        while let Some(chunk) = stream.next().await {
-            self.send_audio_bytes(&chunk).await?;
-        }
-        Ok(())
+        self.stream_audio_bytes_inner(stream, None).await
+    }
+
+    /// [`Self::stream_audio_bytes`], stopping early when `token` is cancelled.
+    ///
+    /// # Errors
+    /// As `stream_audio_bytes`, with `Error::Cancelled` also when `token` fires.
+    pub async fn stream_audio_bytes_cancellable<S>(
+        &self,
+        stream: S,
+        token: &CancellationToken,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Vec<u8>> + Unpin,
+    {
+        self.stream_audio_bytes_inner(stream, Some(token)).await
+    }
+
+    async fn stream_audio_bytes_inner<S>(
+        &self,
+        mut stream: S,
+        token: Option<&CancellationToken>,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Vec<u8>> + Unpin,
+    {
+        self.until_cancelled(token, async {
+            while let Some(chunk) = stream.next().await {
+                self.send_audio_bytes(&chunk).await?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Run `future` unless the session's token, or `token` if given, fires first.
+    async fn until_cancelled<F: Future>(
+        &self,
+        token: Option<&CancellationToken>,
+        future: F,
+    ) -> Result<F::Output> {
+        tool_context::run_until_cancelled(&self.cancellation, token, future).await
     }
 
     /// Commit the current input audio buffer.
@@ -242,32 +932,318 @@ impl Session {
         self.send_event(event).await
     }
 
-    /// Dispatch a tool call to the registry.
+    /// Start a push-to-talk turn: interrupt the model and discard any buffered input.
+    ///
+    /// Meant for sessions built with
+    /// [`VoiceSessionBuilder::push_to_talk`](super::VoiceSessionBuilder::push_to_talk).
     ///
     /// # Errors
-    /// Returns an error if the tool is missing or execution fails.
-    pub async fn run_tool(&self, call: ToolCall) -> Result<ToolResult> {
-        let (tx, rx) = oneshot::channel();
-        self.sender
-            .send(Command::RunTool { call, respond: tx })
-            .await
-            .map_err(|_| Error::ConnectionClosed)?;
-        rx.await.map_err(|_| Error::ConnectionClosed)?
+    /// Returns an error if a send fails.
+    pub async fn ptt_start(&self) -> Result<()> {
+        self.barge_in().await?;
+        self.audio_in_clear().await
     }
 
-    /// Apply a session update.
+    /// End a push-to-talk turn: commit the audio sent since
+    /// [`ptt_start`](Self::ptt_start) and ask for a response.
     ///
     /// # Errors
-    /// Returns an error if the SDK is not fully initialized or the update fails.
-    pub async fn update_session(&self, update: SessionUpdate) -> Result<()> {
-        let event = ClientEvent::SessionUpdate {
-            event_id: None,
-            session: Box::new(update),
-        };
-        self.send_event(event).await
+    /// Returns an error if a send fails.
+    pub async fn ptt_stop(&self) -> Result<()> {
+        self.audio_in_commit().await?;
+        self.respond().await
     }
 
-    /// Create a response builder.
+    /// Inject locally generated PCM16 audio (e.g. a disclosure beep or hold music) into
+    /// the `AudioChunk` stream.
+    ///
+    /// Local chunks have `local` set. `LocalAudioPosition::Mix` overlays the samples onto
+    /// model audio instead, so those chunks keep the model response IDs.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn play_local_audio(&self, pcm: &[i16], position: LocalAudioPosition) -> Result<()> {
+        if pcm.is_empty() {
+            return Ok(());
+        }
+        let mut buf = Vec::with_capacity(pcm.len() * 2);
+        for sample in pcm {
+            buf.extend_from_slice(&sample.to_le_bytes());
+        }
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::PlayLocalAudio {
+                pcm: buf,
+                position,
+                respond: tx,
+            })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)
+    }
+
+    /// Dispatch a tool call to the registry.
+    ///
+    /// # Errors
+    /// Returns an error if the tool is missing or execution fails, or
+    /// `Error::Cancelled` if the session's cancellation token fires first.
+    pub async fn run_tool(&self, call: ToolCall) -> Result<ToolResult> {
+        self.run_tool_inner(call, None).await
+    }
+
+    /// [`Self::run_tool`], giving up when `token` is cancelled. The tool's
+    /// `ToolContext` token is a child of `token`, so the tool sees the cancellation.
+    ///
+    /// # Errors
+    /// As `run_tool`, with `Error::Cancelled` also when `token` fires.
+    pub async fn run_tool_cancellable(
+        &self,
+        call: ToolCall,
+        token: &CancellationToken,
+    ) -> Result<ToolResult> {
+        self.run_tool_inner(call, Some(token)).await
+    }
+
+    async fn run_tool_inner(
+        &self,
+        call: ToolCall,
+        token: Option<&CancellationToken>,
+    ) -> Result<ToolResult> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::RunTool {
+                call: Box::new(call),
+                cancellation: token.cloned(),
+                respond: tx,
+            })
+            .await?;
+        self.until_cancelled(token, rx)
+            .await?
+            .map_err(|_| Error::ConnectionClosed)?
+    }
+
+    /// Deliver the result of a call answered with a placeholder after
+    /// [`ToolContext::defer`]; see
+    /// [`SessionHandle::complete_tool`].
+    ///
+    /// # Errors
+    /// Returns an error if no deferred call has this ID, `output` fails to serialize
+    /// or a send fails.
+    pub async fn complete_tool(&self, call_id: &str, output: impl Serialize) -> Result<()> {
+        self.handle().complete_tool(call_id, output).await
+    }
+
+    /// Apply a session update.
+    ///
+    /// # Errors
+    /// Returns an error if the SDK is not fully initialized or the update fails.
+    pub async fn update_session(&self, update: SessionUpdate) -> Result<()> {
+        let event = ClientEvent::SessionUpdate {
+            event_id: None,
+            session: Box::new(update),
+        };
+        self.send_event(event).await
+    }
+
+    /// Replace the session instructions, returning a guard that can restore the old ones.
+    ///
+    /// The previous instructions are the last ones set through this method, else those
+    /// in the latest `session.updated`.
+    ///
+    /// # Errors
+    /// Returns an error if the session update fails.
+    pub async fn set_instructions(&self, text: impl Into<String>) -> Result<InstructionGuard> {
+        let text = text.into();
+        let previous = match self.instructions.current() {
+            Some(current) => Some(current),
+            None => self.server_instructions().await,
+        };
+        self.send_event(instructions_update(self.instructions.with_language(&text)))
+            .await?;
+        let generation = self.instructions.push(text, previous.clone());
+        Ok(InstructionGuard::new(
+            self.handle(),
+            self.instructions.clone(),
+            generation,
+            previous,
+        ))
+    }
+
+    /// Have the model respond in `language`, an ISO 639-1 code such as `"es"`, optionally
+    /// with a region (`"pt-BR"`).
+    ///
+    /// A sentence naming the language is appended to the current instructions, replacing
+    /// the one from an earlier call, and kept across `set_instructions` and
+    /// `switch_agent`. When input transcription is on, its language hint is set too.
+    ///
+    /// # Errors
+    /// Returns an error if the session update fails.
+    pub async fn set_response_language(&self, language: &str) -> Result<()> {
+        self.apply_response_language(Some(language.to_string()))
+            .await
+    }
+
+    /// Drop the language sentence and transcription hint added by
+    /// [`set_response_language`](Self::set_response_language).
+    ///
+    /// # Errors
+    /// Returns an error if the session update fails.
+    pub async fn clear_response_language(&self) -> Result<()> {
+        self.apply_response_language(None).await
+    }
+
+    /// The language set with [`set_response_language`](Self::set_response_language).
+    #[must_use]
+    pub fn response_language(&self) -> Option<String> {
+        self.instructions.language()
+    }
+
+    async fn apply_response_language(&self, language: Option<String>) -> Result<()> {
+        let base = match self.instructions.current() {
+            Some(current) => current,
+            None => self.server_instructions().await.unwrap_or_default(),
+        };
+        let transcription = self
+            .server_session()
+            .await
+            .and_then(|session| session.config.audio?.input?.transcription)
+            .and_then(|transcription| match transcription {
+                Nullable::Value(transcription) => Some(transcription),
+                Nullable::Null => None,
+            });
+        let audio = transcription.map(|transcription| AudioConfig {
+            input: Some(InputAudioConfig {
+                transcription: Some(Nullable::Value(InputAudioTranscription {
+                    language: language
+                        .as_deref()
+                        .map(instructions::transcription_language),
+                    ..transcription
+                })),
+                ..InputAudioConfig::default()
+            }),
+            output: None,
+        });
+        self.update_session(SessionUpdate {
+            config: SessionUpdateConfig {
+                instructions: Some(instructions::with_language(&base, language.as_deref())),
+                audio,
+                ..SessionUpdateConfig::default()
+            },
+        })
+        .await?;
+        self.instructions.set_language(language);
+        Ok(())
+    }
+
+    /// Instructions from the latest `session.updated`, without the language sentence.
+    async fn server_instructions(&self) -> Option<String> {
+        let instructions = self.server_session().await?.config.instructions?;
+        Some(instructions::without_language(
+            &instructions,
+            self.instructions.language().as_deref(),
+        ))
+    }
+
+    /// Change how fast the model speaks, from 0.25 to 1.5 (1.0 is normal). Applies
+    /// from the next response onwards.
+    ///
+    /// # Errors
+    /// Returns an error if `speed` is out of range or the session update fails.
+    pub async fn set_speaking_speed(&self, speed: f32) -> Result<()> {
+        OutputAudioConfig::validate_speed(speed)?;
+        self.update_session(SessionUpdate {
+            config: SessionUpdateConfig {
+                audio: Some(AudioConfig {
+                    input: None,
+                    output: Some(OutputAudioConfig {
+                        speed: Some(speed),
+                        ..OutputAudioConfig::default()
+                    }),
+                }),
+                ..SessionUpdateConfig::default()
+            },
+        })
+        .await
+    }
+
+    /// Switch to `agent`: send its configuration, route tool calls to its tools and run
+    /// its guardrails. When another agent was active, a system message recording the
+    /// handoff is added to the conversation.
+    ///
+    /// Instructions set with [`set_instructions`](Self::set_instructions) are forgotten,
+    /// so outstanding guards no longer roll back.
+    ///
+    /// # Errors
+    /// Returns an error if tool schemas fail to serialize or a send fails.
+    pub async fn switch_agent(&self, agent: &Agent) -> Result<()> {
+        let mut update = agent.session_update()?;
+        update.config.instructions = update
+            .config
+            .instructions
+            .map(|text| self.instructions.with_language(&text));
+        self.update_session(update).await?;
+        self.instructions.clear();
+        let Some(previous) = ActiveAgent::switch(&self.input_guardrails.agent, agent) else {
+            return Ok(());
+        };
+        let item = Item::Message {
+            id: None,
+            status: None,
+            role: crate::protocol::models::Role::System,
+            content: vec![ContentPart::InputText {
+                text: agent::handoff_note(&previous, agent.name()),
+            }],
+        };
+        self.send_event(ClientEvent::ConversationItemCreate {
+            event_id: None,
+            previous_item_id: None,
+            item: Box::new(item),
+        })
+        .await
+    }
+
+    /// Name of the agent last attached with [`switch_agent`](Self::switch_agent).
+    #[must_use]
+    pub fn agent(&self) -> Option<String> {
+        ActiveAgent::current(&self.input_guardrails.agent)
+            .name
+            .clone()
+    }
+
+    /// Instructions applied with [`set_instructions`](Self::set_instructions) and not
+    /// rolled back, oldest first.
+    #[must_use]
+    pub fn instruction_history(&self) -> Vec<String> {
+        self.instructions.snapshot()
+    }
+
+    /// Apply a session update from a raw JSON object.
+    ///
+    /// Known fields are parsed into `SessionUpdateConfig` and validated as usual; keys it
+    /// does not model are passed through unchanged, with a warning.
+    ///
+    /// # Errors
+    /// Returns an error if `patch` is not an object, a known field has the wrong shape,
+    /// it tries to change a field fixed at session creation such as `model`, or the
+    /// send fails.
+    pub async fn update_session_json(&self, patch: serde_json::Value) -> Result<()> {
+        if !patch.is_object() {
+            return Err(Error::InvalidClientEvent(
+                "session patch must be a JSON object".to_string(),
+            ));
+        }
+        let config: SessionUpdateConfig = serde_json::from_value(patch)?;
+        config.validate_immutable_fields()?;
+        if !config.extra.is_empty() {
+            let keys: Vec<&str> = config.extra.keys().map(String::as_str).collect();
+            tracing::warn!(
+                ?keys,
+                "sending session fields unknown to SessionUpdateConfig"
+            );
+        }
+        self.update_session(SessionUpdate { config }).await
+    }
+
+    /// Create a response builder.
     #[must_use]
     pub fn response(&self) -> ResponseBuilder {
         ResponseBuilder::new()
@@ -297,6 +1273,38 @@ impl Session {
         self.send_event(event).await
     }
 
+    /// Start a response after `delay`, e.g. to ask "Are you still there?".
+    ///
+    /// The event loop cancels it if the user speaks or sends a message first, and skips
+    /// it if another response is still running when it comes due.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn respond_after(
+        &self,
+        delay: Duration,
+        config: ResponseConfig,
+    ) -> Result<ScheduledResponse> {
+        self.handle().respond_after(delay, config).await
+    }
+
+    /// Give the whole session a wall-clock budget, e.g.
+    /// `Deadline::new(start + Duration::from_secs(300)).closing_message("...")` with
+    /// `start` from `tokio::time::Instant::now()`.
+    ///
+    /// When it passes, the event loop cancels the active response and clears output
+    /// audio. With a closing message, the model then says it and the session closes
+    /// once it finishes, or after the deadline's grace period. Then
+    /// `SessionHandle::await_closed` resolves and `closed_reason` reports
+    /// `CloseReason::DeadlineExceeded`. A new deadline replaces the old one until it
+    /// has passed.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn with_deadline(&self, deadline: impl Into<Deadline>) -> Result<()> {
+        self.handle().with_deadline(deadline).await
+    }
+
     /// Clear output audio and cancel any active response (barge-in).
     ///
     /// # Errors
@@ -314,6 +1322,46 @@ impl Session {
         Ok(())
     }
 
+    /// Cancel the active response and wait until the server has finished it.
+    ///
+    /// Resolves with the final response from `response.done`, whose status is
+    /// `cancelled` unless the response completed before the cancel arrived. Returns
+    /// `None` if no response was active.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails or the server rejects the cancel.
+    pub async fn cancel_active_response(
+        &self,
+    ) -> Result<Option<crate::protocol::models::Response>> {
+        let (tx, rx) = oneshot::channel();
+        let response_id = {
+            // Holding `pending` while reading the active ID keeps `response.done` from
+            // slipping in between: the loop clears the ID before resolving waiters.
+            let mut pending = self.pending.lock().await;
+            let Some(id) = self.active_response_id.lock().await.clone() else {
+                return Ok(None);
+            };
+            pending.cancels.entry(id.clone()).or_default().push(tx);
+            id
+        };
+
+        let event = ClientEvent::ResponseCancel {
+            event_id: Some(format!("{CANCEL_EVENT_PREFIX}{response_id}")),
+            response_id: Some(response_id.clone()),
+        };
+        let reply = match self.send_event(event).await {
+            Ok(()) => self.await_reply(rx, "the cancelled response").await,
+            Err(err) => {
+                drop(rx);
+                Err(err)
+            }
+        };
+        if reply.is_err() {
+            forget_closed(&mut self.pending.lock().await.cancels, &response_id);
+        }
+        reply?.map(Some)
+    }
+
     /// Clear the output audio buffer.
     ///
     /// # Errors
@@ -326,11 +1374,241 @@ impl Session {
     /// Send a user message and await the next completed text response.
     ///
     /// # Errors
-    /// Returns an error if the SDK is not fully initialized or the stream fails.
+    /// Returns an error if the SDK is not fully initialized or the stream fails, or
+    /// `Error::Cancelled` if the session's cancellation token fires first.
     pub async fn ask(&mut self, text: &str) -> Result<Option<String>> {
-        self.say(text).await?;
-        self.respond().await?;
-        self.next_text().await
+        self.ask_inner(text, None).await
+    }
+
+    /// [`Self::ask`], giving up when `token` is cancelled.
+    ///
+    /// # Errors
+    /// As `ask`, with `Error::Cancelled` also when `token` fires.
+    pub async fn ask_cancellable(
+        &mut self,
+        text: &str,
+        token: &CancellationToken,
+    ) -> Result<Option<String>> {
+        self.ask_inner(text, Some(token)).await
+    }
+
+    async fn ask_inner(
+        &mut self,
+        text: &str,
+        token: Option<&CancellationToken>,
+    ) -> Result<Option<String>> {
+        let cancellation = self.cancellation.clone();
+        tool_context::run_until_cancelled(&cancellation, token, async {
+            self.say(text).await?;
+            self.respond().await?;
+            self.next_text().await
+        })
+        .await?
+    }
+
+    /// Run an out-of-band, text-only response and decode its output as `T`.
+    ///
+    /// The response is created with `conversation: none`, so it neither reads nor writes
+    /// the default conversation and does not affect barge-in or `next_text`. Useful for
+    /// guardrail and intent-classification calls on the same connection.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails, the response does not complete, or its
+    /// output cannot be decoded as `T`.
+    pub async fn classify<T: DeserializeOwned>(
+        &self,
+        input: impl Into<ClassifyInput>,
+        instructions: &str,
+    ) -> Result<T> {
+        let id = oob::next_oob_id();
+        let config = oob::classify_request(&id, input.into(), instructions);
+        oob::parse_output(self.out_of_band(id, config).await?)
+    }
+
+    /// Start a what-if branch of the conversation as seen so far. Responses in the
+    /// branch run out of band, so the live conversation is left as it is.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn fork(&self) -> Result<ConversationFork> {
+        let snapshot = self.conversation_snapshot().await?;
+        Ok(ConversationFork::new(self.handle(), &snapshot.items))
+    }
+
+    /// Item creates and tool outputs sent but not yet added by the server, oldest
+    /// first, with the item and event IDs they went out with.
+    ///
+    /// Empty unless the session was built with `resumable_sends`. Still readable after
+    /// the session closes, so the sends a failed connection lost can be passed to
+    /// [`Self::replay`] on a new session.
+    #[must_use]
+    pub fn unacknowledged_events(&self) -> Vec<ClientEvent> {
+        self.resend.events()
+    }
+
+    /// Send events from another session's [`Self::unacknowledged_events`], returning
+    /// how many went out.
+    ///
+    /// The events go out in order, ahead of anything sent after this call. An event
+    /// this session replayed and the server has not acknowledged yet is skipped, so
+    /// replaying the same events twice does not create duplicate items. User text
+    /// goes through the input guardrails, and audio appends respect the input audio
+    /// lease, as when sent any other way.
+    ///
+    /// # Errors
+    /// Returns `Error::GuardrailBlocked` or `Error::AudioInputLeased` before anything
+    /// is sent, or an error if a send fails; the events before it were sent.
+    pub async fn replay(&self, mut events: Vec<ClientEvent>) -> Result<usize> {
+        for event in &mut events {
+            self.sender.audio.check(event, None)?;
+            self.input_guardrails.apply_to_event(event).await?;
+        }
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::Replay {
+                events,
+                respond: tx,
+            })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)?
+    }
+
+    /// Send the events of responses created with `metadata[key] == value` to their own
+    /// stream instead of [`Self::next_event`], e.g. to keep background guardrail or
+    /// summary responses apart from the main conversation.
+    ///
+    /// Matched responses are handled like out-of-band ones: they stay off the text,
+    /// voice and speakable streams, never become the active response, and their tool
+    /// calls are not run. Routes are tried in the order they were added; dropping a
+    /// route's stream stops it matching.
+    pub fn route_responses(
+        &self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> ResponseRoute {
+        self.routes.add(key.into(), value.into())
+    }
+
+    /// Deliver the output audio of responses created with `metadata[key] == value` on
+    /// their own stream, e.g. background music or an out-of-band announcement playing
+    /// while the main response speaks.
+    ///
+    /// Matched responses are handled like out-of-band ones and never become the
+    /// active response, so any number of them can stream audio next to the main one.
+    /// Their audio stays off [`Self::next_audio_chunk`] and the voice events unless
+    /// `mode` is `AudioRouteMode::Mix`; their other events still reach
+    /// [`Self::next_event`] unless [`Self::route_responses`] takes them. Routes are
+    /// tried in the order they were added; dropping a route's stream stops it matching.
+    pub fn route_audio(
+        &self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+        mode: AudioRouteMode,
+    ) -> AudioRoute {
+        self.routes.add_audio(key.into(), value.into(), mode)
+    }
+
+    /// Send an out-of-band `config` tagged with `id` and wait for its `response.done`.
+    async fn out_of_band(&self, id: String, config: ResponseConfig) -> Result<Response> {
+        self.handle().out_of_band(id, config).await
+    }
+
+    /// Wait for the event loop to answer a pending request, failing with
+    /// `Error::Timeout` after the session's request timeout, or `Error::Cancelled` if
+    /// the session's token fires first. The waiter's sender reports `is_closed` once
+    /// this returns without a reply.
+    async fn await_reply<T>(&self, rx: oneshot::Receiver<T>, what: &'static str) -> Result<T> {
+        self.until_cancelled(None, tokio::time::timeout(self.request_timeout, rx))
+            .await?
+            .map_err(|_| Error::Timeout(what))?
+            .map_err(|_| Error::ConnectionClosed)
+    }
+
+    /// Conversation items seen so far, oldest first, with audio payloads dropped.
+    ///
+    /// Built locally from item events; input transcripts are filled in as they arrive.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn conversation(&self) -> Result<Vec<Item>> {
+        Ok(self.conversation_snapshot().await?.items)
+    }
+
+    /// Close the session and summarize the call: duration, turns, usage, tool calls,
+    /// errors and the final transcript. The connection stays open while any
+    /// `SessionHandle` is still alive.
+    ///
+    /// # Errors
+    /// Returns an error if the session had already closed.
+    pub async fn finish(self) -> Result<CallReport> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::GetCallReport { respond: tx })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)
+    }
+
+    pub(super) async fn conversation_snapshot(&self) -> Result<ConversationSnapshot> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::GetConversation { respond: tx })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)
+    }
+
+    /// Fetch an item from the server with `conversation.item.retrieve`.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails or the server rejects the request, e.g.
+    /// because the item does not exist.
+    pub async fn retrieve_item(&self, item_id: &str) -> Result<Item> {
+        self.retrieve_item_with(item_id, Vec::new()).await
+    }
+
+    /// Like [`Self::retrieve_item`], asking for extra data in the retrieved item.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails or the server rejects the request.
+    pub async fn retrieve_item_with(&self, item_id: &str, include: Vec<Include>) -> Result<Item> {
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .await
+            .items
+            .entry(item_id.to_string())
+            .or_default()
+            .push(tx);
+
+        let event = ClientEvent::ConversationItemRetrieve {
+            event_id: Some(retrieval::event_id(item_id)),
+            item_id: item_id.to_string(),
+            include: (!include.is_empty()).then_some(include),
+        };
+        let reply = match self.send_event(event).await {
+            Ok(()) => self.await_reply(rx, "a retrieved item").await,
+            Err(err) => {
+                drop(rx);
+                Err(err)
+            }
+        };
+        if reply.is_err() {
+            // Other callers may be waiting for the same item.
+            forget_closed(&mut self.pending.lock().await.items, item_id);
+        }
+        reply?
+    }
+
+    /// Fetch an item and decode the audio of all its content parts into one buffer.
+    ///
+    /// Retrieved items always carry their audio (user input after noise reduction and
+    /// VAD trimming, or model output), so no `include` option is needed. Items
+    /// without audio yield an empty buffer.
+    ///
+    /// # Errors
+    /// Returns an error if retrieval fails or the audio is not valid base64.
+    pub async fn retrieve_item_audio(&self, item_id: &str) -> Result<Vec<u8>> {
+        let item = self.retrieve_item(item_id).await?;
+        retrieval::decode_audio(&item)
     }
 
     /// Approve an MCP tool request.
@@ -375,82 +1653,307 @@ impl Session {
         let (tx, rx) = oneshot::channel();
         self.sender
             .send(Command::SendWithResponse { event, respond: tx })
-            .await
-            .map_err(|_| Error::ConnectionClosed)?;
+            .await?;
         rx.await.map_err(|_| Error::ConnectionClosed)??;
         Ok(())
     }
 
+    #[allow(clippy::too_many_lines)]
     pub(crate) fn from_transport(
-        mut transport: Box<dyn Transport>,
+        transport: Box<dyn Transport>,
         handlers: EventHandlers,
         dispatcher: Arc<dyn ToolDispatcher>,
-        auto_barge_in: bool,
-        auto_tool_response: bool,
+        mut options: SessionOptions,
     ) -> Self {
-        let (sender_tx, mut sender_rx) = mpsc::channel(32);
-        let (text_tx, text_rx) = mpsc::channel(32);
-        let (event_tx, event_rx) = mpsc::channel(128);
-        let (voice_tx, voice_rx) = mpsc::channel(128);
-        let (audio_tx, audio_rx) = mpsc::channel(128);
-        let (transcript_tx, transcript_rx) = mpsc::channel(128);
+        // The key's health follows the connection, which holds the lease.
+        let transport = match options.key_lease.take() {
+            Some(lease) => lease.wrap(transport),
+            None => transport,
+        };
+        let resend_enabled = options.resend.is_some();
+        let (resend, transport) = match options.resend.take() {
+            Some(queue) => {
+                let transport: Box<dyn Transport> =
+                    Box::new(ResendTransport::new(transport, queue.clone()));
+                (queue, transport)
+            }
+            None => (ResendQueue::default(), transport),
+        };
+        let outbound_gate = OutboundGate::new(options.channel_capacities.paused_outbound);
+        let outbound_gate_loop = outbound_gate.clone();
+        let mut transport = outbound_gate.wrap(options.role.wrap(transport));
+        let amd = AmdMonitor::new(options.answering_machine.take(), options.clock.now());
+        let (caps, policy) = (options.channel_capacities, options.overflow_policy);
+        let monitor = ChannelMonitor::default();
+        let monitor_loop = monitor.clone();
+        let (sender, mut control_rx, mut bulk_rx) = CommandSender::new(&caps);
+        let (text_tx, text_rx) = channel::channel("text", caps.text, policy, &monitor);
+        let (event_tx, event_rx) = channel::channel("events", caps.events, policy, &monitor);
+        let (voice_tx, voice_rx) = channel::channel("voice", caps.voice, policy, &monitor);
+        let (audio_tx, audio_rx) = channel::channel("audio", caps.audio, policy, &monitor);
+        let (transcript_tx, transcript_rx) =
+            channel::channel("transcripts", caps.transcripts, policy, &monitor);
+        let (speakable_tx, speakable_rx) =
+            channel::channel("speakable", caps.speakable, policy, &monitor);
 
         let active_response_id = Arc::new(Mutex::new(None));
         let active_response_id_loop = Arc::clone(&active_response_id);
+        let negotiated = Arc::new(Mutex::new(None));
+        let negotiated_loop = Arc::clone(&negotiated);
+        let instructions = InstructionHistory::default();
+        let instructions_loop = instructions.clone();
+        let (readiness_tx, readiness) = watch::channel(Readiness::Pending);
+        let pending: PendingRequests = Arc::default();
+        let pending_loop = Arc::clone(&pending);
+        let connection = Arc::new(std::sync::Mutex::new(ConnectionInfo {
+            call_id: options.call_id.clone(),
+            ..ConnectionInfo::default()
+        }));
+        let connection_loop = Arc::clone(&connection);
+        let agent = ActiveAgent::slot(dispatcher, options.guardrails.clone());
+        let agent_loop = Arc::clone(&agent);
+        let input_guardrails = InputGuardrails::new(&agent, &event_tx);
+        let (reporter, session_state) = StateReporter::new();
+        let (call, call_rx) = CallStatus::new(options.call_id.is_some());
+        let codecs = options.codecs.clone();
+        let cancellation = options.cancellation.clone();
+        let request_timeout = options.request_timeout;
+        let cancellation_loop = cancellation.clone();
+        let call_loop = call.clone();
+        let resend_loop = resend_enabled.then(|| resend.clone());
+        let (routes_loop, routes) = ResponseRoutes::new(caps.events, caps.audio, policy, &monitor);
+        let (mut transcripts_loop, transcripts) =
+            TranscriptFeed::new(caps.transcript_stream, policy, &monitor);
+        let (tools, mut tool_done_rx) = ToolRuns::new(
+            WeakHandle {
+                sender: sender.downgrade(),
+                input_guardrails: input_guardrails.clone(),
+                state: session_state.clone(),
+                outbound_gate: outbound_gate.clone(),
+                pending: Arc::clone(&pending),
+                cancellation: cancellation.clone(),
+                request_timeout,
+            },
+            options.clock.clone(),
+            options.tool_concurrency,
+        );
+        let handlers = Arc::new(handlers);
 
         tokio::spawn(async move {
-            let mut buffers = HashMap::new();
-            loop {
+            let clock = options.clock.clone();
+            let (levels, mut level_ticker) =
+                LevelMeter::with_interval(options.audio_levels, clock.now());
+            let mut state = LoopState {
+                recommit: RecommitBuffer::for_policy(&options.transcription_failure),
+                amd,
+                tools,
+                levels,
+                codecs: SessionCodecs::new(options.codecs.clone()),
+                report: CallReportTracker::new(clock.now()),
+                token_budget: TokenBudgetState::new(options.token_budget),
+                clock,
+                ..LoopState::default()
+            };
+            let (mut analytics, mut analytics_ticker) =
+                AnalyticsTracker::with_interval(options.analytics_interval, state.clock.now());
+            let reason = loop {
+                // A channel overflowing under `OverflowPolicy::CloseSession` ends the loop.
+                if let Some(channel) = monitor_loop.channel() {
+                    break CloseReason::ChannelOverflow(channel);
+                }
+                report_notices(&monitor_loop, &event_tx);
+                // A renewed connection takes over between turns: no response active,
+                // no tool running, and the user neither speaking nor mid-buffer.
+                if state.renewal.is_ready()
+                    && state.tools.running.is_empty()
+                    && active_response_id_loop.lock().await.is_none()
+                {
+                    let config =
+                        negotiated_loop.lock().await.as_ref().map(
+                            |session: &crate::protocol::models::Session| session.config.clone(),
+                        );
+                    if state.user_turn.is_idle(config.as_ref()) {
+                        let wrap = |next| outbound_gate_loop.wrap(options.role.wrap(next));
+                        switch_to_renewed(
+                            &mut transport,
+                            &mut state,
+                            config,
+                            Renewed {
+                                resend: resend_loop.as_ref(),
+                                pending: &pending_loop,
+                                event_tx: &event_tx,
+                            },
+                            wrap,
+                        )
+                        .await;
+                    }
+                }
                 let mut ctx = EventContext {
                     handlers: &handlers,
-                    dispatcher: dispatcher.as_ref(),
-                    buffers: &mut buffers,
+                    agent: &agent_loop,
+                    buffers: &mut state.buffers,
                     event_tx: &event_tx,
                     text_tx: &text_tx,
                     voice_tx: &voice_tx,
                     audio_tx: &audio_tx,
                     transcript_tx: &transcript_tx,
+                    speakable_tx: &speakable_tx,
+                    speakable: &mut state.speakable,
+                    amd: &mut state.amd,
                     active_response_id: &active_response_id_loop,
-                    auto_barge_in,
-                    auto_tool_response,
+                    negotiated: &negotiated_loop,
+                    instructions: &instructions_loop,
+                    readiness: &readiness_tx,
+                    pending: &pending_loop,
+                    connection: &connection_loop,
+                    oob_responses: &mut state.oob_responses,
+                    blocked_responses: &mut state.blocked_responses,
+                    local_audio: &mut state.local_audio,
+                    audio_timeline: &mut state.audio_timeline,
+                    sent_events: &mut state.sent_events,
+                    ordering: &mut state.ordering,
+                    conversation: &mut state.conversation,
+                    options: &options,
+                    idle_timeouts: &mut state.idle_timeouts,
+                    barge_in: &mut state.barge_in,
+                    recommit: &mut state.recommit,
+                    tools: &mut state.tools,
+                    routes: &routes_loop,
+                    transcripts: &mut transcripts_loop,
+                    levels: &mut state.levels,
+                    audio_failures: &mut state.audio_failures,
+                    codecs: &mut state.codecs,
+                    clock: &state.clock,
                 };
 
+                // Control commands (cancel/clear) jump ahead of queued bulk sends such as
+                // audio appends, and server events are drained before more bulk work.
                 tokio::select! {
-                    cmd = sender_rx.recv() => {
-                        match cmd {
-                            Some(Command::SendWithResponse { event, respond }) => {
-                                let _ = respond.send(transport.send(event).await);
-                            }
-                            Some(Command::RunTool { call, respond }) => {
-                                let res = dispatcher.dispatch(call).await;
-                                let _ = respond.send(res);
-                            }
-                            Some(Command::GetActiveResponseId { respond }) => {
-                                let _ = respond.send(active_response_id_loop.lock().await.clone());
+                    biased;
+                    () = cancellation_loop.cancelled() => break CloseReason::Cancelled,
+                    () = outbound_gate_loop.resumed() => {
+                        send_held_events(&outbound_gate_loop, &mut transport).await;
+                    }
+                    cmd = control_rx.recv() => {
+                        let Some(cmd) = cmd else { break CloseReason::Dropped };
+                        run_command(cmd, &mut transport, &ActiveAgent::current(&agent_loop).dispatcher, &active_response_id_loop, &mut state, &audio_tx, &voice_tx).await;
+                    }
+                    res = dedup::next_fresh_event(&mut transport, &mut state.seen_events, &event_tx) => {
+                        let evt = match res {
+                            Ok(Some(evt)) => evt,
+                            Ok(None) => break CloseReason::ServerClosed(transport.close_frame()),
+                            Err(err) => break CloseReason::Transport(err.to_string()),
+                        };
+                        let now = state.clock.now();
+                        observe_analytics(analytics.as_mut(), &evt, now);
+                        state.report.observe(&evt, now);
+                        state.schedule.observe(&evt);
+                        reporter.observe(&evt);
+                        call_loop.observe(&evt);
+                        state.expiry.observe(&evt, &*state.clock);
+                        state.user_turn.observe_server(&evt);
+                        state.replayed.observe(&evt);
+                        state.token_budget.observe(&evt, now);
+                        ctx.tools.observe(&evt);
+                        let deadline = state.deadline.observe(&evt);
+                        if handle_server_event(evt, &mut ctx, &mut transport).await.is_break() {
+                            break CloseReason::IdleTimeout;
+                        }
+                        if let Some(expired) = deadline {
+                            if run_deadline(expired, &mut transport, &mut state.sent_events).await.is_break() {
+                                break CloseReason::DeadlineExceeded;
                             }
-                            None => break,
                         }
                     }
-                    res = transport.next_event() => {
-                        match res {
-                            Ok(Some(evt)) => {
-                                handle_server_event(evt, &mut ctx, &mut transport).await;
-                            }
-                            Ok(None) | Err(_) => break,
+                    Some(done) = tool_done_rx.recv() => {
+                        finish_tool_call(done, &mut ctx, &mut transport).await;
+                    }
+                    id = schedule::wait_until_due(state.schedule.next_due()) => {
+                        start_scheduled(id, &mut transport, &active_response_id_loop, &mut state).await;
+                    }
+                    () = clock::wait_until(ctx.barge_in.deadline()) => {
+                        barge_in_after_min_speech(&mut ctx, &mut transport).await;
+                    }
+                    () = clock::wait_until(ctx.amd.deadline()) => {
+                        if let Some(detected) = ctx.amd.elapse() {
+                            let _ = ctx.voice_tx.send(detected).await;
+                        }
+                    }
+                    () = clock::wait_until(state.deadline.next()) => {
+                        if deadline_passed(&mut transport, &active_response_id_loop, &mut state).await.is_break() {
+                            break CloseReason::DeadlineExceeded;
                         }
                     }
+                    () = clock::wait_until(state.expiry.deadline(&options.expiry)) => {
+                        expiry_due(&mut state, &options, &event_tx).await;
+                    }
+                    opened = state.renewal.opened() => match opened {
+                        Ok(next) => state.renewal.hold(next),
+                        Err(err) => renewal_failed(&event_tx, &err.to_string()).await,
+                    },
+                    () = next_tick(&mut analytics_ticker) => {
+                        emit_analytics(analytics.as_mut(), &event_tx, state.clock.now()).await;
+                    }
+                    () = next_tick(&mut level_ticker) => {
+                        for level in ctx.levels.as_mut().map(LevelMeter::take).unwrap_or_default() {
+                            let _ = voice_tx.send(level).await;
+                        }
+                    }
+                    cmd = bulk_rx.recv() => {
+                        let Some(cmd) = cmd else { break CloseReason::Dropped };
+                        run_command(cmd, &mut transport, &ActiveAgent::current(&agent_loop).dispatcher, &active_response_id_loop, &mut state, &audio_tx, &voice_tx).await;
+                    }
                 }
+            };
+            tracing::debug!(?reason, "session event loop exited");
+            state.tools.close();
+            pending_loop.lock().await.close();
+            let unsent = outbound_gate_loop.queued();
+            if unsent > 0 {
+                tracing::warn!(unsent, "session closed with paused outbound events unsent");
+            }
+            reporter.closing();
+            drop(transport);
+            let close = match &reason {
+                CloseReason::ServerClosed(Some(close)) => Some(close.clone()),
+                _ => None,
+            };
+            call_loop.closed(&reason);
+            reporter.closed(reason);
+            // Never wait here: nobody may be reading events any more. Nor drop it when
+            // the queue is full; it is the last event, so it may go past the capacity.
+            if let Some(ServerClose { code, reason }) = close {
+                let _ = event_tx.send_last(SdkEvent::ConnectionClosed { code, reason });
             }
         });
 
         Self {
-            sender: sender_tx,
+            sender,
             text_rx,
             event_rx,
             voice_rx,
             audio_rx,
             transcript_rx,
+            speakable_rx,
+            monitor,
             active_response_id,
+            negotiated,
+            readiness,
+            pending,
+            connection,
+            input_guardrails,
+            instructions,
+            outbound: crate::OutboundStats::default(),
+            state: session_state,
+            routes,
+            transcripts,
+            outbound_gate,
+            resend,
+            call,
+            call_rx,
+            codecs,
+            cancellation,
+            request_timeout,
         }
     }
 }
@@ -505,35 +2008,130 @@ impl AudioIn<'_> {
     }
 }
 
+/// Loop-local state borrowed by each iteration's `EventContext`.
+#[derive(Default)]
+struct LoopState {
+    buffers: HashMap<(String, u32), String>,
+    idle_timeouts: u32,
+    oob_responses: HashSet<String>,
+    blocked_responses: HashSet<String>,
+    local_audio: LocalAudioQueue,
+    audio_timeline: AudioTimeline,
+    sent_events: SentEventLog,
+    ordering: OrderingMonitor,
+    conversation: ConversationLog,
+    schedule: ResponseSchedule,
+    barge_in: BargeInGate,
+    seen_events: SeenEvents,
+    recommit: RecommitBuffer,
+    report: CallReportTracker,
+    speakable: SpeakableSplitter,
+    amd: AmdMonitor,
+    tools: ToolRuns,
+    /// Events sent by `Session::replay` and not yet acknowledged.
+    replayed: Replayed,
+    levels: Option<LevelMeter>,
+    audio_failures: AudioFailures,
+    codecs: SessionCodecs,
+    clock: SharedClock,
+    expiry: ExpiryMonitor,
+    renewal: Renewal,
+    user_turn: UserTurn,
+    deadline: DeadlineState,
+    token_budget: TokenBudgetState,
+}
+
 struct EventContext<'a> {
-    handlers: &'a EventHandlers,
-    dispatcher: &'a dyn ToolDispatcher,
+    handlers: &'a Arc<EventHandlers>,
+    agent: &'a AgentSlot,
     buffers: &'a mut HashMap<(String, u32), String>,
-    event_tx: &'a mpsc::Sender<SdkEvent>,
-    text_tx: &'a mpsc::Sender<String>,
-    voice_tx: &'a mpsc::Sender<VoiceEvent>,
-    audio_tx: &'a mpsc::Sender<super::voice::AudioChunk>,
-    transcript_tx: &'a mpsc::Sender<super::voice::TranscriptChunk>,
+    event_tx: &'a channel::Sender<SdkEvent>,
+    text_tx: &'a channel::Sender<String>,
+    voice_tx: &'a channel::Sender<VoiceEvent>,
+    audio_tx: &'a channel::Sender<super::voice::AudioChunk>,
+    transcript_tx: &'a channel::Sender<super::voice::TranscriptChunk>,
+    speakable_tx: &'a channel::Sender<SpeakableChunk>,
+    speakable: &'a mut SpeakableSplitter,
+    amd: &'a mut AmdMonitor,
     active_response_id: &'a Arc<Mutex<Option<String>>>,
-    auto_barge_in: bool,
-    auto_tool_response: bool,
+    negotiated: &'a Arc<Mutex<Option<crate::protocol::models::Session>>>,
+    instructions: &'a InstructionHistory,
+    connection: &'a std::sync::Mutex<ConnectionInfo>,
+    readiness: &'a watch::Sender<Readiness>,
+    pending: &'a PendingRequests,
+    oob_responses: &'a mut HashSet<String>,
+    blocked_responses: &'a mut HashSet<String>,
+    local_audio: &'a mut LocalAudioQueue,
+    audio_timeline: &'a mut AudioTimeline,
+    sent_events: &'a mut SentEventLog,
+    ordering: &'a mut OrderingMonitor,
+    conversation: &'a mut ConversationLog,
+    options: &'a SessionOptions,
+    idle_timeouts: &'a mut u32,
+    barge_in: &'a mut BargeInGate,
+    recommit: &'a mut RecommitBuffer,
+    tools: &'a mut ToolRuns,
+    routes: &'a ResponseRoutes,
+    transcripts: &'a mut TranscriptFeed,
+    levels: &'a mut Option<LevelMeter>,
+    audio_failures: &'a mut AudioFailures,
+    codecs: &'a mut SessionCodecs,
+    clock: &'a SharedClock,
 }
 
 async fn handle_server_event(
     evt: ServerEvent,
     ctx: &mut EventContext<'_>,
     transport: &mut Box<dyn Transport>,
-) {
-    handle_voice_events(&evt, ctx, transport).await;
-    handle_lifecycle_events(&evt, ctx).await;
+) -> ControlFlow<()> {
+    if let Some(gap) = ctx.conversation.observe_server(&evt) {
+        resync_missing_item(gap, ctx, transport).await;
+    }
+    ctx.recommit.observe_server(&evt);
+    // Events of routed responses go to their route and are otherwise treated as out of band.
+    let route = ctx.routes.observe(&evt);
+    let audio_routed = ctx.routes.observe_audio(&evt, ctx.audio_timeline);
+    let out_of_band = handle_out_of_band_events(&evt, ctx).await || route.is_some() || audio_routed;
+    handle_retrieval_events(&evt, ctx).await;
+    handle_item_create_events(&evt, ctx).await;
+    handle_tool_output_events(&evt, ctx).await;
+    if !handle_routed_audio(&evt, ctx).await && route.is_none() {
+        handle_voice_events(&evt, ctx, transport).await;
+    }
+    if !out_of_band {
+        handle_lifecycle_events(&evt, ctx).await;
+    }
+    handle_cancel_events(&evt, ctx).await;
     handle_user_transcript_events(&evt, ctx).await;
+    handle_transcription_failure(&evt, ctx, transport).await;
+    let flow = handle_idle_events(&evt, ctx, transport).await;
+    let evt = if out_of_band {
+        evt
+    } else {
+        match apply_output_guardrails(evt, ctx, transport).await {
+            Some(evt) => evt,
+            None => return flow,
+        }
+    };
 
     if let Some(mapped) = SdkEvent::from_server(evt.clone()) {
-        let _ = ctx.event_tx.send(mapped).await;
+        let _ = route.as_ref().unwrap_or(ctx.event_tx).send(mapped).await;
+    }
+    if let Some(violation) = ctx.ordering.observe(&evt) {
+        tracing::debug!("out-of-order server event: {:?}", violation.rule);
+        let _ = ctx
+            .event_tx
+            .send(SdkEvent::OrderingViolation(violation))
+            .await;
     }
     if let Some(handler) = &ctx.handlers.on_raw_event {
-        let _ = handler(evt.clone()).await;
+        let _ = contain_panic(ctx, "on_raw_event", async { handler(evt.clone()).await }).await;
+    }
+    if out_of_band {
+        return flow;
     }
+    handle_speakable_text(&evt, ctx).await;
+    observe_tool_call_progress(&evt, ctx);
 
     match evt {
         ServerEvent::ResponseOutputTextDelta {
@@ -556,7 +2154,7 @@ async fn handle_server_event(
             ctx.buffers.remove(&key);
             let _ = ctx.text_tx.send(text.clone()).await;
             if let Some(handler) = &ctx.handlers.on_text {
-                let _ = handler(text).await;
+                let _ = contain_panic(ctx, "on_text", async { handler(text).await }).await;
             }
         }
         ServerEvent::ResponseFunctionCallArgumentsDone {
@@ -572,69 +2170,686 @@ async fn handle_server_event(
                 serde_json::from_str(&arguments).unwrap_or(serde_json::Value::String(arguments));
             let call = ToolCall {
                 name,
-                call_id: call_id.clone(),
+                call_id,
                 arguments,
                 response_id: Some(response_id),
                 item_id: Some(item_id),
                 output_index: Some(output_index),
             };
+            // Observers leave tool calls to the controlling session.
+            if ctx.options.role == SessionRole::Controller {
+                handle_tool_call(call, ctx);
+            }
+        }
+        _ => {}
+    }
+    flow
+}
 
-            let result = if let Some(handler) = &ctx.handlers.on_tool_call {
-                handler(call).await
-            } else {
-                ctx.dispatcher.dispatch(call).await
+/// Run user code, reporting a panic as `SdkEvent::HandlerPanicked` instead of
+/// letting it take down the event loop.
+async fn contain_panic<T>(
+    ctx: &EventContext<'_>,
+    context: &str,
+    fut: impl std::future::Future<Output = T>,
+) -> Option<T> {
+    let started = ctx.clock.now();
+    let outcome = std::panic::AssertUnwindSafe(fut).catch_unwind().await;
+    report_slow(ctx, context, ctx.clock.now() - started).await;
+    match outcome {
+        Ok(value) => Some(value),
+        Err(payload) => {
+            report_panic(ctx, context, panic_message(&*payload)).await;
+            None
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+/// Report user code that held things up for longer than `SessionOptions::slow_handler`.
+async fn report_slow(ctx: &EventContext<'_>, name: &str, elapsed: Duration) {
+    if ctx
+        .options
+        .slow_handler
+        .is_none_or(|threshold| elapsed < threshold)
+    {
+        return;
+    }
+    tracing::warn!(handler = name, ?elapsed, "slow handler");
+    let _ = ctx
+        .event_tx
+        .send(SdkEvent::SlowHandler {
+            name: name.to_string(),
+            elapsed,
+        })
+        .await;
+}
+
+async fn report_panic(ctx: &EventContext<'_>, context: &str, message: String) {
+    tracing::error!("{context} handler panicked: {message}");
+    let _ = ctx
+        .event_tx
+        .send(SdkEvent::HandlerPanicked {
+            context: context.to_string(),
+            message,
+        })
+        .await;
+}
+
+/// Start `call` alongside the event loop; `finish_tool_call` sends its output.
+fn handle_tool_call(call: ToolCall, ctx: &mut EventContext<'_>) {
+    let handlers = Arc::clone(ctx.handlers);
+    let dispatcher = ActiveAgent::current(ctx.agent).dispatcher.clone();
+    ctx.tools.spawn(call, None, None, move |call, context| {
+        Box::pin(async move {
+            if let Some(middleware) = dispatcher.middleware() {
+                middleware.on_tool_call_ready(&call)?;
+            }
+            match &handlers.on_tool_call {
+                Some(handler) => handler(call).await,
+                None => dispatcher.dispatch_with_context(call, context).await,
+            }
+        })
+    });
+}
+
+/// Pass a tool call's start and argument deltas to the dispatcher's middleware. Its
+/// `on_tool_call_ready` runs with the call itself.
+fn observe_tool_call_progress(evt: &ServerEvent, ctx: &EventContext<'_>) {
+    if ctx.options.role != SessionRole::Controller {
+        return;
+    }
+    let agent = ActiveAgent::current(ctx.agent);
+    let Some(middleware) = agent.dispatcher.middleware() else {
+        return;
+    };
+    match evt {
+        ServerEvent::ResponseOutputItemAdded {
+            item: Item::FunctionCall { call_id, name, .. },
+            ..
+        } => middleware.on_tool_call_started(call_id, name),
+        ServerEvent::ResponseFunctionCallArgumentsDelta { call_id, delta, .. } => {
+            middleware.on_arguments_delta(call_id, delta);
+        }
+        _ => {}
+    }
+}
+
+/// Send the output of a finished tool call, or apply the error policy. Results of
+/// `Session::run_tool` go back to its caller instead.
+async fn finish_tool_call(
+    done: ToolDone,
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    let end = ctx.tools.finish(&done.call_id);
+    let context = format!("tool `{}`", done.name);
+    report_slow(ctx, &context, done.elapsed).await;
+    let result = match done.outcome {
+        Ok(result) => result,
+        Err(message) => {
+            report_panic(ctx, &context, message).await;
+            if let Some(respond) = done.respond {
+                let message = format!("{context} panicked");
+                let _ = respond.send(Err(Error::InvalidClientEvent(message)));
+                return;
+            }
+            let failure = ToolFailure {
+                call_id: done.call_id,
+                name: done.name,
+                message: "tool handler panicked".to_string(),
+                panicked: true,
+            };
+            return send_tool_error(failure, ctx, transport).await;
+        }
+    };
+    if let Some(respond) = done.respond {
+        let _ = respond.send(result);
+        return;
+    }
+    match result {
+        // A cancelled call still gets its output, but no new response.
+        Ok(tool_result) => {
+            let output = ActiveAgent::current(ctx.agent)
+                .dispatcher
+                .output_options(&done.name);
+            let follow_up =
+                output.send_output && output.follow_up.unwrap_or(ctx.options.auto_tool_response);
+            // A cancelled call's response is gone, so its result is no longer wanted.
+            if end.deferred && !end.cancelled {
+                ctx.tools.deferred.insert(
+                    done.call_id,
+                    DeferredCall {
+                        name: done.name.clone(),
+                        response_id: end.response_id,
+                        send_output: output.send_output,
+                        follow_up,
+                    },
+                );
+            }
+            if !output.send_output {
+                tracing::debug!("discarding output of tool `{}`", done.name);
+                return;
+            }
+            send_tool_output(tool_result, follow_up && !end.cancelled, ctx, transport).await;
+        }
+        Err(err) => {
+            let failure = ToolFailure {
+                call_id: done.call_id,
+                name: done.name,
+                message: err.to_string(),
+                panicked: false,
             };
+            send_tool_error(failure, ctx, transport).await;
+        }
+    }
+}
+
+async fn send_tool_output(
+    tool_result: ToolResult,
+    follow_up: bool,
+    ctx: &EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    let output = serde_json::to_string(&tool_result.output).unwrap_or_else(|_| String::new());
+    send_function_call_output(tool_result.call_id, output, ctx, transport).await;
+    if follow_up {
+        let follow_up = ClientEvent::ResponseCreate {
+            event_id: None,
+            response: None,
+        };
+        let _ = transport.send(follow_up).await;
+    }
+}
+
+async fn send_tool_error(
+    failure: ToolFailure,
+    ctx: &EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    let output = contain_panic(ctx, "tool error policy", async {
+        ctx.options.tool_error_policy.output(&failure)
+    })
+    .await
+    .flatten();
+    let Some(output) = output else {
+        tracing::debug!("tool `{}` failed: {}", failure.name, failure.message);
+        return;
+    };
+    send_function_call_output(failure.call_id, output.to_string(), ctx, transport).await;
+}
+
+/// Send a `function_call_output` item and remember its call ID until the server adds it.
+async fn send_function_call_output(
+    call_id: String,
+    output: String,
+    ctx: &EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    let item = Item::FunctionCallOutput {
+        id: None,
+        call_id: call_id.clone(),
+        output,
+    };
+    let event = ClientEvent::ConversationItemCreate {
+        event_id: None,
+        previous_item_id: None,
+        item: Box::new(item),
+    };
+    if transport.send(event).await.is_ok() {
+        let outputs = &mut ctx.pending.lock().await.tool_outputs;
+        if outputs.len() == PENDING_TOOL_OUTPUTS {
+            outputs.pop_front();
+        }
+        outputs.push_back(call_id);
+    }
+}
 
-            match result {
-                Ok(tool_result) => {
-                    let output = serde_json::to_string(&tool_result.output)
-                        .unwrap_or_else(|_| String::new());
-                    let item = Item::FunctionCallOutput {
-                        id: None,
-                        call_id: tool_result.call_id,
-                        output,
-                    };
-                    let event = ClientEvent::ConversationItemCreate {
+/// Apply the `TranscriptionFailurePolicy` to a failed input transcription.
+async fn handle_transcription_failure(
+    evt: &ServerEvent,
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    let ServerEvent::InputAudioTranscriptionFailed {
+        item_id,
+        content_index,
+        error,
+        ..
+    } = evt
+    else {
+        return;
+    };
+    match &ctx.options.transcription_failure {
+        TranscriptionFailurePolicy::Report => {}
+        TranscriptionFailurePolicy::Recommit { max_attempts } => {
+            let Some(audio) = ctx.recommit.retry(item_id, *max_attempts) else {
+                return;
+            };
+            tracing::debug!(%item_id, "committing audio again after failed transcription");
+            let _ = transport
+                .send(ClientEvent::ConversationItemDelete {
+                    event_id: None,
+                    item_id: item_id.clone(),
+                })
+                .await;
+            for chunk in audio {
+                let _ = transport
+                    .send(ClientEvent::InputAudioBufferAppend {
                         event_id: None,
-                        previous_item_id: None,
-                        item: Box::new(item),
-                    };
-                    let _ = transport.send(event).await;
-                    if ctx.auto_tool_response {
-                        let follow_up = ClientEvent::ResponseCreate {
-                            event_id: None,
-                            response: None,
-                        };
-                        let _ = transport.send(follow_up).await;
-                    }
-                }
-                Err(err) => {
-                    let output = serde_json::json!({ "error": err.to_string() }).to_string();
-                    let item = Item::FunctionCallOutput {
-                        id: None,
-                        call_id,
-                        output,
-                    };
-                    let event = ClientEvent::ConversationItemCreate {
+                        audio: chunk,
+                    })
+                    .await;
+            }
+            let _ = transport
+                .send(ClientEvent::InputAudioBufferCommit { event_id: None })
+                .await;
+        }
+        TranscriptionFailurePolicy::Custom(handler) => {
+            let failure = TranscriptionFailure {
+                item_id: item_id.clone(),
+                content_index: *content_index,
+                error: error.clone(),
+            };
+            contain_panic(ctx, "transcription failure", async { handler(&failure) }).await;
+        }
+    }
+}
+
+async fn handle_idle_events(
+    evt: &ServerEvent,
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) -> ControlFlow<()> {
+    match evt {
+        ServerEvent::InputAudioBufferSpeechStarted { .. } => {
+            *ctx.idle_timeouts = 0;
+        }
+        ServerEvent::InputAudioBufferTimeoutTriggered {
+            item_id,
+            audio_start_ms,
+            audio_end_ms,
+            ..
+        } => {
+            *ctx.idle_timeouts += 1;
+            let consecutive = *ctx.idle_timeouts;
+            let _ = ctx
+                .voice_tx
+                .send(VoiceEvent::IdleTimeout {
+                    item_id: item_id.clone(),
+                    audio_start_ms: *audio_start_ms,
+                    audio_end_ms: *audio_end_ms,
+                    consecutive,
+                })
+                .await;
+
+            if ctx
+                .options
+                .idle_timeout
+                .close_after
+                .is_some_and(|limit| consecutive >= limit)
+            {
+                tracing::info!(consecutive, "closing session after idle timeouts");
+                return ControlFlow::Break(());
+            }
+            if let Some(instructions) = &ctx.options.idle_timeout.reprompt_instructions {
+                let config = ResponseConfig {
+                    instructions: Some(instructions.clone()),
+                    ..ResponseConfig::default()
+                };
+                let _ = transport
+                    .send(ClientEvent::ResponseCreate {
                         event_id: None,
-                        previous_item_id: None,
-                        item: Box::new(item),
-                    };
-                    let _ = transport.send(event).await;
-                }
+                        response: Some(Box::new(config)),
+                    })
+                    .await;
             }
         }
         _ => {}
     }
+    ControlFlow::Continue(())
+}
+
+/// Run assistant text through the guardrails before it reaches the SDK streams.
+///
+/// Returns `None` when the text was blocked; the response is cancelled once and its
+/// remaining text is dropped. Guardrail errors are logged and the text passes through.
+async fn apply_output_guardrails(
+    mut evt: ServerEvent,
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) -> Option<ServerEvent> {
+    let (stage, response_id, text) = match &evt {
+        ServerEvent::ResponseOutputTextDelta {
+            response_id, delta, ..
+        } => (GuardrailStage::AssistantDelta, response_id, delta),
+        ServerEvent::ResponseOutputTextDone {
+            response_id, text, ..
+        } => (GuardrailStage::AssistantDone, response_id, text),
+        ServerEvent::ResponseDone { response, .. } => {
+            ctx.blocked_responses.remove(&response.id);
+            return Some(evt);
+        }
+        _ => return Some(evt),
+    };
+    let guardrails = ActiveAgent::current(ctx.agent).guardrails.clone();
+    if guardrails.is_empty() {
+        return Some(evt);
+    }
+    if ctx.blocked_responses.contains(response_id) {
+        return None;
+    }
+
+    let response_id = response_id.clone();
+    let started = ctx.clock.now();
+    let report = guardrails.run(stage, text.clone()).await;
+    report_slow(ctx, "output guardrails", ctx.clock.now() - started).await;
+    let report = match report {
+        Ok(report) => report,
+        Err(err) => {
+            tracing::warn!(error = %err, "guardrail failed, passing assistant text through");
+            return Some(evt);
+        }
+    };
+    for verdict in &report.verdicts {
+        let _ = ctx
+            .event_tx
+            .send(SdkEvent::GuardrailTriggered {
+                stage,
+                response_id: Some(response_id.clone()),
+                verdict: verdict.clone(),
+            })
+            .await;
+    }
+    if report.blocked().is_some() {
+        ctx.blocked_responses.insert(response_id.clone());
+        let cancel = ClientEvent::ResponseCancel {
+            event_id: None,
+            response_id: Some(response_id),
+        };
+        if let Err(err) = transport.send(cancel).await {
+            tracing::warn!(error = %err, "failed to cancel response blocked by guardrail");
+        }
+        return None;
+    }
+
+    if let ServerEvent::ResponseOutputTextDelta { delta: text, .. }
+    | ServerEvent::ResponseOutputTextDone { text, .. } = &mut evt
+    {
+        *text = report.text;
+    }
+    Some(evt)
+}
+
+/// Route finished out-of-band responses to their callers.
+///
+/// Returns true if `evt` belongs to an out-of-band response, which keeps it away from
+/// the active-response tracking and the text stream of the main conversation.
+async fn handle_out_of_band_events(evt: &ServerEvent, ctx: &mut EventContext<'_>) -> bool {
+    match evt {
+        ServerEvent::ResponseCreated { response, .. } => {
+            let is_oob = oob::oob_id(response).is_some();
+            if is_oob {
+                ctx.oob_responses.insert(response.id.clone());
+            }
+            is_oob
+        }
+        ServerEvent::ResponseDone { response, .. }
+        | ServerEvent::ResponseCancelled { response, .. } => {
+            ctx.oob_responses.remove(&response.id);
+            let Some(id) = oob::oob_id(response) else {
+                return false;
+            };
+            let pending = ctx.pending.lock().await.responses.remove(id);
+            if let Some(tx) = pending {
+                let _ = tx.send(response.clone());
+            }
+            true
+        }
+        ServerEvent::ResponseOutputTextDelta { response_id, .. }
+        | ServerEvent::ResponseOutputTextDone { response_id, .. }
+        | ServerEvent::ResponseOutputItemAdded { response_id, .. }
+        | ServerEvent::ResponseOutputItemDone { response_id, .. }
+        | ServerEvent::ResponseContentPartAdded { response_id, .. }
+        | ServerEvent::ResponseContentPartDone { response_id, .. } => {
+            ctx.oob_responses.contains(response_id)
+        }
+        _ => false,
+    }
+}
+
+/// Cut response text into speakable chunks when `speakable_chunks` is on.
+async fn handle_speakable_text(evt: &ServerEvent, ctx: &mut EventContext<'_>) {
+    let chunks = match (evt, &ctx.options.speakable) {
+        (
+            ServerEvent::ResponseOutputTextDelta {
+                response_id,
+                item_id,
+                content_index,
+                delta,
+                ..
+            },
+            Some(chunking),
+        ) => ctx
+            .speakable
+            .push(chunking, response_id, item_id, *content_index, delta),
+        (
+            ServerEvent::ResponseOutputTextDone {
+                response_id,
+                item_id,
+                content_index,
+                ..
+            },
+            Some(_),
+        ) => ctx
+            .speakable
+            .flush(response_id, item_id, *content_index)
+            .into_iter()
+            .collect(),
+        (ServerEvent::ResponseDone { response, .. }, Some(_)) => {
+            ctx.speakable.discard(&response.id);
+            return;
+        }
+        _ => return,
+    };
+    for chunk in chunks {
+        let _ = ctx.speakable_tx.send(chunk).await;
+    }
+}
+
+/// Ask the server for an item the local history skipped over.
+async fn resync_missing_item(
+    gap: ConversationGap,
+    ctx: &EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
+    tracing::debug!(
+        "item {} follows unknown item {}; retrieving it",
+        gap.item_id,
+        gap.missing_item_id
+    );
+    let retrieve = ClientEvent::ConversationItemRetrieve {
+        event_id: Some(retrieval::event_id(&gap.missing_item_id)),
+        item_id: gap.missing_item_id.clone(),
+        include: None,
+    };
+    if let Err(err) = transport.send(retrieve).await {
+        tracing::debug!("could not retrieve missing item: {err}");
+    }
+    let _ = ctx.event_tx.send(SdkEvent::ConversationGap(gap)).await;
+}
+
+/// Hand retrieved items, or the error for their request, to `retrieve_item` callers.
+async fn handle_retrieval_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+    let (item_id, result) = match evt {
+        ServerEvent::ConversationItemRetrieved { item, .. } => {
+            let Some(item_id) = item.id() else { return };
+            (item_id, Ok(item))
+        }
+        ServerEvent::Error { error, .. } => {
+            let Some(item_id) = error.event_id.as_deref().and_then(retrieval::item_id) else {
+                return;
+            };
+            (item_id, Err(error))
+        }
+        _ => return,
+    };
+    let waiters = ctx.pending.lock().await.items.remove(item_id);
+    for tx in waiters.unwrap_or_default() {
+        let _ = tx.send(result.cloned().map_err(|e| Error::from(e.clone())));
+    }
+}
+
+/// Hand items added by `create_item`, or the error for their request, to its caller.
+async fn handle_item_create_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+    let (item_id, result) = match evt {
+        ServerEvent::ConversationItemAdded { item, .. }
+        | ServerEvent::ConversationItemCreated { item, .. } => {
+            let Some(item_id) = item.id() else { return };
+            (item_id, Ok(item))
+        }
+        ServerEvent::Error { error, .. } => {
+            let Some(item_id) = error.event_id.as_deref().and_then(item_create::item_id) else {
+                return;
+            };
+            (item_id, Err(error))
+        }
+        _ => return,
+    };
+    let waiter = ctx.pending.lock().await.created.remove(item_id);
+    if let Some(tx) = waiter {
+        let _ = tx.send(result.cloned().map_err(|e| Error::from(e.clone())));
+    }
+}
+
+/// Report tool outputs sent by this session once the server adds them.
+async fn handle_tool_output_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+    let ServerEvent::ConversationItemAdded {
+        item:
+            Item::FunctionCallOutput {
+                id: Some(item_id),
+                call_id,
+                ..
+            },
+        ..
+    } = evt
+    else {
+        return;
+    };
+    let sent = {
+        let outputs = &mut ctx.pending.lock().await.tool_outputs;
+        let position = outputs.iter().position(|id| id == call_id);
+        position.and_then(|index| outputs.remove(index)).is_some()
+    };
+    if sent {
+        let _ = ctx
+            .event_tx
+            .send(SdkEvent::ToolOutputAccepted {
+                call_id: call_id.clone(),
+                item_id: item_id.clone(),
+            })
+            .await;
+    }
+}
+
+/// Resolve `cancel_active_response` callers once their response is finished or the
+/// cancel is rejected. Runs after the active response ID has been cleared.
+async fn handle_cancel_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+    let (response_id, result) = match evt {
+        ServerEvent::ResponseDone { response, .. }
+        | ServerEvent::ResponseCancelled { response, .. } => (response.id.as_str(), Ok(response)),
+        ServerEvent::Error { error, .. } => {
+            let response_id = error
+                .event_id
+                .as_deref()
+                .and_then(|id| id.strip_prefix(CANCEL_EVENT_PREFIX));
+            let Some(response_id) = response_id else {
+                return;
+            };
+            (response_id, Err(error))
+        }
+        _ => return,
+    };
+    let waiters = ctx.pending.lock().await.cancels.remove(response_id);
+    for tx in waiters.unwrap_or_default() {
+        let _ = tx.send(result.cloned().map_err(|e| Error::from(e.clone())));
+    }
+}
+
+fn record_connection(
+    connection: &std::sync::Mutex<ConnectionInfo>,
+    session: &crate::protocol::models::Session,
+) {
+    let mut info = connection
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    info.session_id = Some(session.id.clone());
+    info.model = Some(session.config.model.clone());
 }
 
-async fn handle_lifecycle_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+async fn handle_lifecycle_events(evt: &ServerEvent, ctx: &mut EventContext<'_>) {
     match evt {
+        ServerEvent::SessionCreated { session, .. } => {
+            *ctx.negotiated.lock().await = Some(session.clone());
+            ctx.instructions
+                .set_default(session.config.instructions.clone());
+            record_connection(ctx.connection, session);
+            ctx.audio_timeline.update_format(session);
+            ctx.codecs.update_format(session);
+            ctx.readiness.send_if_modified(|state| {
+                let pending = matches!(state, Readiness::Pending);
+                if pending {
+                    *state = Readiness::Created;
+                }
+                pending
+            });
+        }
+        ServerEvent::SessionUpdated { session, .. } => {
+            *ctx.negotiated.lock().await = Some(session.clone());
+            ctx.audio_failures.observe_session(&session.config);
+            record_connection(ctx.connection, session);
+            ctx.audio_timeline.update_format(session);
+            ctx.codecs.update_format(session);
+            ctx.readiness
+                .send_replace(Readiness::Ready(Box::new(session.clone())));
+        }
+        ServerEvent::Error { error, .. } => {
+            ctx.readiness.send_if_modified(|state| {
+                let settled = matches!(state, Readiness::Ready(_) | Readiness::Failed(_));
+                if !settled {
+                    *state = Readiness::Failed(error.clone());
+                }
+                !settled
+            });
+            let rejected = error
+                .event_id
+                .as_deref()
+                .and_then(|id| ctx.sent_events.get(id));
+            if let Some(event) = rejected {
+                let _ = ctx
+                    .event_tx
+                    .send(SdkEvent::ClientEventRejected {
+                        event: event.clone(),
+                        error: error.clone(),
+                    })
+                    .await;
+            }
+        }
         ServerEvent::ResponseCreated { response, .. } => {
             {
                 let mut guard = ctx.active_response_id.lock().await;
                 *guard = Some(response.id.clone());
             }
+            for pcm in ctx.local_audio.take_before_next() {
+                emit_local_audio(ctx.audio_tx, pcm, &**ctx.clock).await;
+            }
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::ResponseCreated {
@@ -647,6 +2862,9 @@ async fn handle_lifecycle_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
                 let mut guard = ctx.active_response_id.lock().await;
                 *guard = None;
             }
+            for pcm in ctx.local_audio.take_after_current() {
+                emit_local_audio(ctx.audio_tx, pcm, &**ctx.clock).await;
+            }
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::ResponseDone {
@@ -659,6 +2877,9 @@ async fn handle_lifecycle_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
                 let mut guard = ctx.active_response_id.lock().await;
                 *guard = None;
             }
+            for pcm in ctx.local_audio.take_after_current() {
+                emit_local_audio(ctx.audio_tx, pcm, &**ctx.clock).await;
+            }
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::ResponseCancelled {
@@ -670,7 +2891,8 @@ async fn handle_lifecycle_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
     }
 }
 
-async fn handle_user_transcript_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+async fn handle_user_transcript_events(evt: &ServerEvent, ctx: &mut EventContext<'_>) {
+    ctx.transcripts.user(evt, ctx.clock.system_time()).await;
     if let ServerEvent::InputAudioTranscriptionCompleted {
         item_id,
         content_index,
@@ -691,44 +2913,111 @@ async fn handle_user_transcript_events(evt: &ServerEvent, ctx: &EventContext<'_>
 
 async fn handle_voice_events(
     evt: &ServerEvent,
-    ctx: &EventContext<'_>,
+    ctx: &mut EventContext<'_>,
     transport: &mut Box<dyn Transport>,
 ) {
     handle_speech_events(evt, ctx, transport).await;
-    handle_audio_events(evt, ctx).await;
+    handle_audio_events(evt, ctx, transport).await;
     handle_transcript_events(evt, ctx).await;
+    if let Some(detected) = ctx.amd.observe_server(evt) {
+        let _ = ctx.voice_tx.send(detected).await;
+    }
 }
 
 async fn handle_speech_events(
     evt: &ServerEvent,
-    ctx: &EventContext<'_>,
+    ctx: &mut EventContext<'_>,
     transport: &mut Box<dyn Transport>,
 ) {
     match evt {
-        ServerEvent::InputAudioBufferSpeechStarted { audio_start_ms, .. } => {
+        ServerEvent::InputAudioBufferSpeechStarted {
+            audio_start_ms,
+            item_id,
+            ..
+        } => {
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::SpeechStarted {
                     audio_start_ms: Some(*audio_start_ms),
                 })
                 .await;
-            if ctx.auto_barge_in {
-                send_barge_in(ctx, transport).await;
+            if ctx.options.auto_barge_in {
+                let active = ctx.active_response_id.lock().await.clone();
+                if let Some(interrupt) = ctx.barge_in.speech_started(
+                    &ctx.options.barge_in,
+                    item_id,
+                    active,
+                    ctx.clock.now(),
+                ) {
+                    send_barge_in(ctx, transport, interrupt).await;
+                }
             }
         }
-        ServerEvent::InputAudioBufferSpeechStopped { audio_end_ms, .. } => {
-            let _ = ctx
-                .voice_tx
-                .send(VoiceEvent::SpeechStopped {
-                    audio_end_ms: Some(*audio_end_ms),
-                })
+        ServerEvent::InputAudioTranscriptionCompleted {
+            item_id,
+            transcript,
+            logprobs,
+            ..
+        } => {
+            let interrupt = ctx.barge_in.transcribed(
+                &ctx.options.barge_in,
+                item_id,
+                transcript,
+                logprobs.as_ref(),
+            );
+            if let Some(interrupt) = interrupt {
+                send_barge_in(ctx, transport, interrupt).await;
+            }
+        }
+        ServerEvent::InputAudioBufferSpeechStopped {
+            audio_end_ms,
+            item_id,
+            ..
+        } => {
+            ctx.barge_in.speech_stopped(item_id);
+            let _ = ctx
+                .voice_tx
+                .send(VoiceEvent::SpeechStopped {
+                    audio_end_ms: Some(*audio_end_ms),
+                })
                 .await;
+            let idle = ctx.active_response_id.lock().await.is_none();
+            if ctx.options.respond_on_speech_stop && idle {
+                let create = ClientEvent::ResponseCreate {
+                    event_id: None,
+                    response: None,
+                };
+                let _ = transport.send(create).await;
+            }
         }
         _ => {}
     }
 }
 
-async fn handle_audio_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
+/// Switch the session to text output after repeated audio failures.
+async fn fall_back_to_text(
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+    reason: String,
+    failures: u32,
+) {
+    tracing::warn!(failures, %reason, "output audio keeps failing; falling back to text");
+    let mut update = audio_fallback::text_output_update();
+    ctx.sent_events.record(&mut update);
+    if let Err(err) = transport.send(update).await {
+        tracing::warn!(error = %err, "failed to switch the session to text output");
+    }
+    let _ = ctx
+        .event_tx
+        .send(SdkEvent::TextFallback { reason, failures })
+        .await;
+}
+
+async fn handle_audio_events(
+    evt: &ServerEvent,
+    ctx: &mut EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+) {
     match evt {
         ServerEvent::ResponseOutputAudioDelta {
             response_id,
@@ -738,24 +3027,27 @@ async fn handle_audio_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
             delta,
             ..
         } => {
-            if !should_accept_response(ctx.active_response_id, response_id).await {
+            if ctx.audio_failures.text_only()
+                || !should_accept_response(ctx.active_response_id, response_id).await
+            {
                 return;
             }
-            match general_purpose::STANDARD.decode(delta.as_bytes()) {
-                Ok(pcm) => {
+            let codec = ctx.codecs.output();
+            match ctx
+                .audio_failures
+                .decode(delta, codec.as_deref(), ctx.options.audio_failure)
+            {
+                Ok(mut pcm) => {
+                    ctx.local_audio.mix_into(&mut pcm);
+                    if let Some(levels) = ctx.levels.as_mut() {
+                        levels.observe_output(&pcm, codec.as_deref());
+                    }
+                    let chunk = ctx
+                        .audio_timeline
+                        .chunk(evt, pcm.clone(), ctx.clock.system_time());
                     let _ = ctx
                         .voice_tx
                         .send(VoiceEvent::AudioDelta {
-                            response_id: response_id.clone(),
-                            item_id: item_id.clone(),
-                            output_index: *output_index,
-                            content_index: *content_index,
-                            pcm: pcm.clone(),
-                        })
-                        .await;
-                    let _ = ctx
-                        .audio_tx
-                        .send(super::voice::AudioChunk {
                             response_id: response_id.clone(),
                             item_id: item_id.clone(),
                             output_index: *output_index,
@@ -763,14 +3055,21 @@ async fn handle_audio_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
                             pcm,
                         })
                         .await;
+                    if let Some(chunk) = chunk {
+                        let _ = ctx.audio_tx.send(chunk).await;
+                    }
                 }
-                Err(err) => {
+                Err(message) => {
                     let _ = ctx
                         .voice_tx
                         .send(VoiceEvent::DecodeError {
-                            message: err.to_string(),
+                            message: message.clone(),
                         })
                         .await;
+                    if let Some(failures) = ctx.audio_failures.fall_back(ctx.options.audio_failure)
+                    {
+                        fall_back_to_text(ctx, transport, message, failures).await;
+                    }
                 }
             }
         }
@@ -798,6 +3097,44 @@ async fn handle_audio_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
     }
 }
 
+/// Deliver audio of responses matched by `Session::route_audio` to their route, and
+/// for `AudioRouteMode::Mix` into the output audio. True when `evt` was such audio.
+async fn handle_routed_audio(evt: &ServerEvent, ctx: &mut EventContext<'_>) -> bool {
+    match evt {
+        ServerEvent::ResponseOutputAudioDelta { response_id, .. } => {
+            if !ctx.routes.routes_audio(response_id) {
+                return false;
+            }
+            let Some(RoutedChunk { tx, mode, chunk }) =
+                ctx.routes.audio_chunk(evt, ctx.clock.system_time())
+            else {
+                return true;
+            };
+            if mode == AudioRouteMode::Mix {
+                // Overlay onto whichever other response is speaking, else play it as is.
+                let speaking = ctx
+                    .active_response_id
+                    .lock()
+                    .await
+                    .as_ref()
+                    .is_some_and(|active| active != response_id);
+                if speaking {
+                    ctx.local_audio
+                        .push(chunk.pcm.clone(), LocalAudioPosition::Mix);
+                } else {
+                    let _ = ctx.audio_tx.send(chunk.clone()).await;
+                }
+            }
+            let _ = tx.send(chunk).await;
+            true
+        }
+        ServerEvent::ResponseOutputAudioDone { response_id, .. } => {
+            ctx.routes.routes_audio(response_id)
+        }
+        _ => false,
+    }
+}
+
 async fn handle_transcript_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
     match evt {
         ServerEvent::ResponseOutputAudioTranscriptDelta {
@@ -811,6 +3148,9 @@ async fn handle_transcript_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
             if !should_accept_response(ctx.active_response_id, response_id).await {
                 return;
             }
+            ctx.transcripts
+                .assistant(evt, ctx.clock.system_time())
+                .await;
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::TranscriptDelta {
@@ -844,6 +3184,9 @@ async fn handle_transcript_events(evt: &ServerEvent, ctx: &EventContext<'_>) {
             if !should_accept_response(ctx.active_response_id, response_id).await {
                 return;
             }
+            ctx.transcripts
+                .assistant(evt, ctx.clock.system_time())
+                .await;
             let _ = ctx
                 .voice_tx
                 .send(VoiceEvent::TranscriptDone {
@@ -877,10 +3220,29 @@ async fn should_accept_response(active: &Arc<Mutex<Option<String>>>, response_id
         .is_none_or(|active_id| active_id == response_id)
 }
 
-async fn send_barge_in(ctx: &EventContext<'_>, transport: &mut Box<dyn Transport>) {
+async fn barge_in_after_min_speech(ctx: &mut EventContext<'_>, transport: &mut Box<dyn Transport>) {
+    if let Some(interrupt) = ctx.barge_in.elapse(&ctx.options.barge_in) {
+        send_barge_in(ctx, transport, interrupt).await;
+    }
+}
+
+async fn send_barge_in(
+    ctx: &EventContext<'_>,
+    transport: &mut Box<dyn Transport>,
+    interrupt: Interrupt,
+) {
     let response_id = {
         let mut guard = ctx.active_response_id.lock().await;
-        guard.take()
+        // A deferred barge-in leaves alone a response started after the speech began,
+        // which is likely the reply to it.
+        if *guard != interrupt.response_id {
+            return;
+        }
+        if ctx.options.barge_in.clear_audio_only {
+            None
+        } else {
+            guard.take()
+        }
     };
     let _ = transport
         .send(ClientEvent::OutputAudioBufferClear { event_id: None })
@@ -896,22 +3258,62 @@ async fn send_barge_in(ctx: &EventContext<'_>, transport: &mut Box<dyn Transport
 }
 
 impl SessionHandle {
+    /// Whether the session's event loop is still running.
+    ///
+    /// Turns `false` once the transport closes or the loop panics.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Where the session is in its life, fed from the event loop.
+    ///
+    /// Unlike a failed send, this tells long-lived holders that the session is gone
+    /// and why, without attempting one.
+    #[must_use]
+    pub fn state(&self) -> SessionState {
+        self.state.current()
+    }
+
+    /// Wait until the session's event loop has exited.
+    pub async fn await_closed(&self) {
+        self.sender.closed().await;
+    }
+
+    /// Hold client events instead of sending them, e.g. while reconnecting or while an
+    /// upstream gateway asks for flow control. Sends made meanwhile return once the
+    /// event is queued, and fail with `Error::OutboundQueueFull` beyond
+    /// `ChannelCapacities::paused_outbound` held events. Server events are still read.
+    pub fn pause_outbound(&self) {
+        self.outbound_gate.pause();
+    }
+
+    /// Send the held events in order and let sends through again.
+    pub fn resume_outbound(&self) {
+        self.outbound_gate.resume();
+    }
+
+    #[must_use]
+    pub fn is_outbound_paused(&self) -> bool {
+        self.outbound_gate.is_paused()
+    }
+
+    /// Client events held by [`Self::pause_outbound`].
+    #[must_use]
+    pub fn queued_outbound(&self) -> usize {
+        self.outbound_gate.queued()
+    }
+
     /// Send a user text message.
     ///
     /// # Errors
     /// Returns an error if the send fails.
     pub async fn say(&self, text: impl Into<String>) -> Result<()> {
-        let item = Item::Message {
-            id: None,
-            status: None,
-            role: crate::protocol::models::Role::User,
-            content: vec![ContentPart::InputText { text: text.into() }],
-        };
-
+        let text = self.input_guardrails.apply(text.into()).await?;
         let event = ClientEvent::ConversationItemCreate {
             event_id: None,
             previous_item_id: None,
-            item: Box::new(item),
+            item: Box::new(user_text(text)),
         };
 
         self.send_event(event).await
@@ -927,8 +3329,7 @@ impl SessionHandle {
         let (tx, rx) = oneshot::channel();
         self.sender
             .send(Command::GetActiveResponseId { respond: tx })
-            .await
-            .map_err(|_| Error::ConnectionClosed)?;
+            .await?;
         let response_id = rx.await.map_err(|_| Error::ConnectionClosed)?;
 
         if let Some(id) = response_id {
@@ -941,6 +3342,46 @@ impl SessionHandle {
         Ok(())
     }
 
+    /// Start a response after `delay`; see `Session::respond_after`.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn respond_after(
+        &self,
+        delay: Duration,
+        config: ResponseConfig,
+    ) -> Result<ScheduledResponse> {
+        let id = schedule::next_schedule_id();
+        self.sender
+            .send(Command::ScheduleResponse {
+                id,
+                delay,
+                config: Box::new(config),
+            })
+            .await?;
+        Ok(ScheduledResponse::new(self.clone(), id))
+    }
+
+    /// End the session at a deadline; see `Session::with_deadline`.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn with_deadline(&self, deadline: impl Into<Deadline>) -> Result<()> {
+        self.sender
+            .send(Command::SetDeadline {
+                deadline: Box::new(deadline.into()),
+            })
+            .await
+    }
+
+    pub(super) async fn cancel_scheduled(&self, id: u64) -> Result<bool> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::CancelScheduledResponse { id, respond: tx })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)
+    }
+
     /// Send raw PCM16 bytes to the input buffer.
     ///
     /// # Errors
@@ -982,774 +3423,6219 @@ impl SessionHandle {
         self.send_event(event).await
     }
 
-    async fn send_event(&self, event: ClientEvent) -> Result<()> {
+    /// Conversation items seen so far; see `Session::conversation`.
+    ///
+    /// # Errors
+    /// Returns an error if the session has closed.
+    pub async fn conversation(&self) -> Result<Vec<Item>> {
         let (tx, rx) = oneshot::channel();
         self.sender
-            .send(Command::SendWithResponse { event, respond: tx })
-            .await
-            .map_err(|_| Error::ConnectionClosed)?;
-        rx.await.map_err(|_| Error::ConnectionClosed)?
+            .send(Command::GetConversation { respond: tx })
+            .await?;
+        Ok(rx.await.map_err(|_| Error::ConnectionClosed)?.items)
     }
-}
 
-enum Command {
-    SendWithResponse {
-        event: ClientEvent,
-        respond: oneshot::Sender<Result<()>>,
-    },
-    RunTool {
-        call: ToolCall,
-        respond: oneshot::Sender<Result<ToolResult>>,
-    },
-    GetActiveResponseId {
-        respond: oneshot::Sender<Option<String>>,
-    },
-}
+    /// Take the input audio lease, waiting for the current holder to release it. See
+    /// [`AudioInputLease`].
+    pub async fn lease_audio_input(&self) -> AudioInputLease {
+        let audio = self.sender.audio.clone();
+        let id = audio.acquire().await;
+        AudioInputLease::new(self.clone(), audio, id)
+    }
 
-pub struct SessionConfigSnapshot {
-    pub api_key: String,
-    pub model: Option<String>,
-    pub session: SessionConfig,
-    pub handlers: EventHandlers,
-    pub dispatcher: Arc<dyn ToolDispatcher>,
-    pub auto_barge_in: bool,
-    pub auto_tool_response: bool,
-}
+    /// Take the input audio lease if nobody holds it.
+    ///
+    /// # Errors
+    /// Returns `Error::AudioInputLeased` if another writer holds the lease.
+    pub fn try_lease_audio_input(&self) -> Result<AudioInputLease> {
+        let audio = self.sender.audio.clone();
+        let id = audio.try_acquire()?;
+        Ok(AudioInputLease::new(self.clone(), audio, id))
+    }
 
-impl SessionConfigSnapshot {
-    /// Connect via WebSocket.
+    /// Send `event` as the holder of input audio lease `lease`.
+    pub(super) async fn send_leased(&self, event: ClientEvent, lease: u64) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send_as(
+                Command::SendWithResponse { event, respond: tx },
+                Some(lease),
+            )
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)?
+    }
+
+    /// Deliver the result of a call answered with a placeholder after
+    /// [`ToolContext::defer`].
+    ///
+    /// The placeholder already answered the call, so the result is added as a system
+    /// message naming the tool and call, followed by a response when the tool's
+    /// output would have been. The result of a tool whose output is discarded is
+    /// dropped too. A call can no longer be completed once the response that made it
+    /// is cancelled.
     ///
     /// # Errors
-    /// Returns an error if the connection fails.
-    pub async fn connect_ws(self) -> Result<Session> {
-        let client =
-            crate::RealtimeClient::connect(&self.api_key, self.model.as_deref(), None).await?;
+    /// Returns an error if no deferred call has this ID, `output` fails to serialize
+    /// or a send fails.
+    pub async fn complete_tool(&self, call_id: &str, output: impl Serialize) -> Result<()> {
+        let output = serde_json::to_value(output)?;
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::TakeDeferredCall {
+                call_id: call_id.to_string(),
+                respond: tx,
+            })
+            .await?;
+        let deferred = rx
+            .await
+            .map_err(|_| Error::ConnectionClosed)?
+            .ok_or_else(|| {
+                Error::InvalidClientEvent(format!("no deferred tool call `{call_id}`"))
+            })?;
+        if !deferred.send_output {
+            return Ok(());
+        }
+        let item = Item::Message {
+            id: None,
+            status: None,
+            role: crate::protocol::models::Role::System,
+            content: vec![ContentPart::InputText {
+                text: tools::deferred_result_note(&deferred.name, call_id, &output),
+            }],
+        };
+        self.send_event(ClientEvent::ConversationItemCreate {
+            event_id: None,
+            previous_item_id: None,
+            item: Box::new(item),
+        })
+        .await?;
+        if deferred.follow_up {
+            self.send_event(ClientEvent::ResponseCreate {
+                event_id: None,
+                response: None,
+            })
+            .await?;
+        }
+        Ok(())
+    }
 
-        let transport = Box::new(WsTransport { client });
-        let session = Session::from_transport(
-            transport,
-            self.handlers,
-            self.dispatcher,
-            self.auto_barge_in,
-            self.auto_tool_response,
-        );
-        let update = session_update_from_config(&self.session);
-        session.update_session(update).await?;
-        Ok(session)
+    async fn send_event(&self, event: ClientEvent) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Command::SendWithResponse { event, respond: tx })
+            .await?;
+        rx.await.map_err(|_| Error::ConnectionClosed)?
     }
-}
 
-fn session_update_from_config(config: &SessionConfig) -> SessionUpdate {
-    SessionUpdate {
-        config: SessionUpdateConfig {
-            kind: Some(config.kind),
-            output_modalities: Some(config.output_modalities),
-            modalities: config.modalities.clone(),
-            include: config.include.clone(),
-            prompt: config.prompt.clone(),
-            truncation: config.truncation.clone(),
-            instructions: config.instructions.clone(),
-            input_audio_format: config.input_audio_format.clone(),
-            output_audio_format: config.output_audio_format.clone(),
-            input_audio_transcription: config.input_audio_transcription.clone(),
-            turn_detection: config.turn_detection.clone(),
-            tools: config.tools.clone(),
-            tool_choice: config.tool_choice.clone(),
-            temperature: config.temperature,
-            max_output_tokens: config.max_output_tokens.clone(),
-            audio: config.audio.clone(),
-            tracing: config.tracing.clone(),
-        },
+    /// Send an out-of-band `config` tagged with `id` and wait for its `response.done`,
+    /// failing like `Session`'s requests on timeout or cancellation.
+    pub(super) async fn out_of_band(&self, id: String, config: ResponseConfig) -> Result<Response> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.responses.insert(id.clone(), tx);
+        let event = ClientEvent::ResponseCreate {
+            event_id: None,
+            response: Some(Box::new(config)),
+        };
+        let reply = match self.send_event(event).await {
+            Ok(()) => tool_context::run_until_cancelled(
+                &self.cancellation,
+                None,
+                tokio::time::timeout(self.request_timeout, rx),
+            )
+            .await
+            .and_then(|reply| reply.map_err(|_| Error::Timeout("an out-of-band response")))
+            .and_then(|reply| reply.map_err(|_| Error::ConnectionClosed)),
+            Err(err) => Err(err),
+        };
+        if reply.is_err() {
+            self.pending.lock().await.responses.remove(&id);
+        }
+        reply
     }
 }
 
-struct WsTransport {
-    client: crate::RealtimeClient,
+fn observe_analytics(
+    tracker: Option<&mut AnalyticsTracker>,
+    evt: &ServerEvent,
+    now: tokio::time::Instant,
+) {
+    if let Some(tracker) = tracker {
+        tracker.observe(evt, now);
+    }
 }
 
-impl Transport for WsTransport {
-    fn send(&mut self, event: ClientEvent) -> super::transport::BoxFuture<'_, Result<()>> {
-        Box::pin(async move { self.client.send(event).await })
+async fn emit_analytics(
+    tracker: Option<&mut AnalyticsTracker>,
+    event_tx: &channel::Sender<SdkEvent>,
+    now: tokio::time::Instant,
+) {
+    if let Some(tracker) = tracker {
+        let report = tracker.snapshot(now);
+        let _ = event_tx.send(SdkEvent::Analytics(report)).await;
     }
+}
 
-    fn next_event(&mut self) -> super::transport::BoxFuture<'_, Result<Option<ServerEvent>>> {
-        Box::pin(async move { self.client.next_event().await })
+/// Warn that the session is about to expire, and start opening its replacement when
+/// renewal is due.
+async fn expiry_due(
+    state: &mut LoopState,
+    options: &SessionOptions,
+    event_tx: &channel::Sender<SdkEvent>,
+) {
+    let now = state.clock.now();
+    if let Some(in_seconds) = state.expiry.take_warning(&options.expiry, now) {
+        let _ = event_tx
+            .send(SdkEvent::SessionExpiring { in_seconds })
+            .await;
+    }
+    if state.expiry.take_renewal(&options.expiry, now) {
+        if let Some(reconnect) = &options.reconnect {
+            state.renewal.start(reconnect);
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ToolRegistry;
-    use crate::protocol::server_events::ServerEvent;
-    use base64::engine::general_purpose;
-    use futures::StreamExt;
-    use tokio::sync::mpsc;
+async fn renewal_failed(event_tx: &channel::Sender<SdkEvent>, message: &str) {
+    tracing::warn!(error = message, "session renewal failed");
+    let _ = event_tx
+        .send(SdkEvent::SessionRenewFailed {
+            message: message.to_string(),
+        })
+        .await;
+}
 
-    struct MockTransport {
-        incoming: mpsc::Receiver<ServerEvent>,
-        outgoing: mpsc::Sender<ClientEvent>,
-    }
+/// What a renewal hands over to, or settles on, the session it renews.
+struct Renewed<'a> {
+    resend: Option<&'a ResendQueue>,
+    pending: &'a PendingRequests,
+    event_tx: &'a channel::Sender<SdkEvent>,
+}
 
-    impl Transport for MockTransport {
-        fn send(
-            &mut self,
-            event: ClientEvent,
-        ) -> super::super::transport::BoxFuture<'_, Result<()>> {
-            let outgoing = self.outgoing.clone();
-            Box::pin(async move {
-                outgoing
-                    .send(event)
-                    .await
-                    .map_err(|_| Error::ConnectionClosed)?;
-                Ok(())
+/// Carry the configuration and conversation over to the renewed connection and
+/// continue on it. Text and tool items are replayed under their IDs; audio is not.
+/// `wrap` puts the new connection behind the same role and outbound gate as the old
+/// one.
+///
+/// Requests still waiting on the old connection fail with `Error::ConnectionClosed`,
+/// and deferred tool calls are forgotten: their replies cannot arrive on the new one.
+async fn switch_to_renewed(
+    transport: &mut Box<dyn Transport>,
+    state: &mut LoopState,
+    config: Option<SessionConfig>,
+    renewed: Renewed<'_>,
+    wrap: impl FnOnce(Box<dyn Transport>) -> Box<dyn Transport>,
+) {
+    let Renewed {
+        resend,
+        pending,
+        event_tx,
+    } = renewed;
+    let Some(mut next) = state.renewal.take() else {
+        return;
+    };
+    let items: Vec<Item> = state
+        .conversation
+        .snapshot()
+        .items
+        .iter()
+        .filter_map(|item| {
+            let replay = handoff::replay_item(item)?;
+            Some(match item.id() {
+                Some(id) => replay.with_id(id),
+                None => replay,
             })
-        }
-
-        fn next_event(
-            &mut self,
-        ) -> super::super::transport::BoxFuture<'_, Result<Option<ServerEvent>>> {
-            Box::pin(async move { Ok(self.incoming.recv().await) })
+        })
+        .collect();
+    let mut events: Vec<ClientEvent> = config
+        .map(|config| ClientEvent::SessionUpdate {
+            event_id: None,
+            session: Box::new(SessionUpdate {
+                config: config.to_update(),
+            }),
+        })
+        .into_iter()
+        .collect();
+    let count = items.len();
+    events.extend(
+        items
+            .into_iter()
+            .map(|item| ClientEvent::ConversationItemCreate {
+                event_id: None,
+                previous_item_id: None,
+                item: Box::new(item),
+            }),
+    );
+    events.extend(resend.map(ResendQueue::events).unwrap_or_default());
+    for event in events {
+        if let Err(err) = next.send(event).await {
+            renewal_failed(event_tx, &err.to_string()).await;
+            return;
         }
     }
+    if let Some(queue) = resend {
+        next = Box::new(ResendTransport::new(next, queue.clone()));
+    }
+    drop(std::mem::replace(transport, wrap(next)));
+    pending.lock().await.close();
+    state.tools.close();
+    state.conversation = ConversationLog::default();
+    let _ = event_tx
+        .send(SdkEvent::SessionRenewed { items: count })
+        .await;
+}
+
+async fn next_tick(ticker: &mut Option<tokio::time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn run_command(
+    cmd: Command,
+    transport: &mut Box<dyn Transport>,
+    dispatcher: &Arc<dyn ToolDispatcher>,
+    active_response_id: &Arc<Mutex<Option<String>>>,
+    state: &mut LoopState,
+    audio_tx: &channel::Sender<super::voice::AudioChunk>,
+    voice_tx: &channel::Sender<VoiceEvent>,
+) {
+    match cmd {
+        Command::SendWithResponse { mut event, respond } => {
+            state.token_budget.apply(&mut event, state.clock.now());
+            state.sent_events.record(&mut event);
+            state.conversation.observe_client(&event);
+            state.recommit.observe_client(&event);
+            state.user_turn.observe_client(&event);
+            // Decoded once for the meter and the detector, and only if either wants it.
+            let listening = state.levels.is_some() || state.amd.is_detecting();
+            let appended = match &event {
+                ClientEvent::InputAudioBufferAppend { audio, .. } if listening => {
+                    general_purpose::STANDARD.decode(audio).ok()
+                }
+                _ => None,
+            };
+            if let Some(encoded) = appended {
+                let codec = state.codecs.input();
+                if let Some(levels) = state.levels.as_mut() {
+                    levels.observe_input(&encoded, codec.as_deref());
+                }
+                if let Some(detected) = state.amd.observe_input(&encoded, codec.as_deref()) {
+                    let _ = voice_tx.send(detected).await;
+                }
+            }
+            let _ = respond.send(transport.send(event).await);
+        }
+        Command::GetConversation { respond } => {
+            let _ = respond.send(state.conversation.snapshot());
+        }
+        Command::GetCallReport { respond } => {
+            let conversation = state.conversation.snapshot();
+            let _ = respond.send(state.report.report(&conversation, state.clock.now()));
+        }
+        Command::ScheduleResponse { id, delay, config } => {
+            let due = state.clock.now() + delay;
+            state.schedule.insert(id, due, *config);
+        }
+        Command::SetDeadline { deadline } => state.deadline.set(*deadline),
+        Command::CancelScheduledResponse { id, respond } => {
+            let _ = respond.send(state.schedule.cancel(id));
+        }
+        Command::RunTool {
+            call,
+            cancellation,
+            respond,
+        } => {
+            let dispatcher = Arc::clone(dispatcher);
+            state
+                .tools
+                .spawn(*call, Some(respond), cancellation, move |call, ctx| {
+                    Box::pin(async move { dispatcher.dispatch_with_context(call, ctx).await })
+                });
+        }
+        Command::TakeDeferredCall { call_id, respond } => {
+            let _ = respond.send(state.tools.deferred.remove(&call_id));
+        }
+        Command::GetActiveResponseId { respond } => {
+            let _ = respond.send(active_response_id.lock().await.clone());
+        }
+        Command::PlayLocalAudio {
+            pcm,
+            position,
+            respond,
+        } => {
+            let responding = active_response_id.lock().await.is_some();
+            match position {
+                LocalAudioPosition::Now => emit_local_audio(audio_tx, pcm, &*state.clock).await,
+                LocalAudioPosition::AfterCurrentResponse | LocalAudioPosition::Mix
+                    if !responding =>
+                {
+                    emit_local_audio(audio_tx, pcm, &*state.clock).await;
+                }
+                _ => state.local_audio.push(pcm, position),
+            }
+            let _ = respond.send(());
+        }
+        Command::Replay { events, respond } => {
+            let _ = respond.send(replay(events, transport, state).await);
+        }
+    }
+}
+
+/// Send what was held while outbound sends were paused, once they resume. What
+/// fails to go out stays held for the next send.
+async fn send_held_events(gate: &OutboundGate, transport: &mut Box<dyn Transport>) {
+    if let Err(err) = gate.flush(&mut **transport).await {
+        tracing::warn!(queued = gate.queued(), "failed to send held events: {err}");
+    }
+}
+
+/// Send `events` in order, skipping any this session replayed and the server has
+/// not acknowledged yet.
+async fn replay(
+    events: Vec<ClientEvent>,
+    transport: &mut Box<dyn Transport>,
+    state: &mut LoopState,
+) -> Result<usize> {
+    let mut sent = 0;
+    for mut event in events {
+        if !state.replayed.insert(&event) {
+            continue;
+        }
+        state.sent_events.record(&mut event);
+        state.user_turn.observe_client(&event);
+        transport.send(event).await?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Send the scheduled response `id`, unless another response is still running.
+async fn start_scheduled(
+    id: u64,
+    transport: &mut Box<dyn Transport>,
+    active_response_id: &Arc<Mutex<Option<String>>>,
+    state: &mut LoopState,
+) {
+    let Some(config) = state.schedule.take(id) else {
+        return;
+    };
+    if active_response_id.lock().await.is_some() {
+        tracing::debug!(id, "skipping scheduled response while another is active");
+        return;
+    }
+    let mut event = ClientEvent::ResponseCreate {
+        event_id: None,
+        response: Some(Box::new(config)),
+    };
+    state.sent_events.record(&mut event);
+    if let Err(err) = transport.send(event).await {
+        tracing::warn!(id, "failed to start scheduled response: {err}");
+    }
+}
+
+/// Cancel whatever is playing once the session deadline passes, and start the
+/// closing message if there is one and nothing needs to finish first.
+async fn deadline_passed(
+    transport: &mut Box<dyn Transport>,
+    active_response_id: &Arc<Mutex<Option<String>>>,
+    state: &mut LoopState,
+) -> ControlFlow<()> {
+    let active = active_response_id.lock().await.take();
+    if let Err(err) = transport
+        .send(ClientEvent::OutputAudioBufferClear { event_id: None })
+        .await
+    {
+        tracing::warn!("failed to clear output audio at the session deadline: {err}");
+    }
+    if let Some(id) = &active {
+        let cancel = ClientEvent::ResponseCancel {
+            event_id: None,
+            response_id: Some(id.clone()),
+        };
+        if let Err(err) = transport.send(cancel).await {
+            tracing::warn!("failed to cancel response at the session deadline: {err}");
+        }
+    }
+    let expired = state.deadline.expire(active, state.clock.now());
+    run_deadline(expired, transport, &mut state.sent_events).await
+}
+
+/// Act on the deadline: close, start the closing message, or keep waiting.
+async fn run_deadline(
+    expired: Expired,
+    transport: &mut Box<dyn Transport>,
+    sent_events: &mut SentEventLog,
+) -> ControlFlow<()> {
+    match expired {
+        Expired::Close => return ControlFlow::Break(()),
+        Expired::Say(config) => {
+            let mut event = ClientEvent::ResponseCreate {
+                event_id: None,
+                response: Some(config),
+            };
+            sent_events.record(&mut event);
+            if let Err(err) = transport.send(event).await {
+                tracing::warn!("failed to start the closing message: {err}");
+                return ControlFlow::Break(());
+            }
+        }
+        Expired::AfterCancel => {}
+    }
+    ControlFlow::Continue(())
+}
+
+async fn emit_local_audio(
+    audio_tx: &channel::Sender<super::voice::AudioChunk>,
+    pcm: Vec<u8>,
+    clock: &dyn Clock,
+) {
+    let _ = audio_tx
+        .send(super::voice::AudioChunk::local(pcm, clock.system_time()))
+        .await;
+}
+
+/// Tools and guardrails in effect, swapped by `Session::switch_agent`.
+struct ActiveAgent {
+    name: Option<String>,
+    dispatcher: Arc<dyn ToolDispatcher>,
+    /// Guardrails configured on the session; an agent's own run after them.
+    base_guardrails: Guardrails,
+    guardrails: Guardrails,
+}
+
+type AgentSlot = Arc<std::sync::RwLock<Arc<ActiveAgent>>>;
+
+impl ActiveAgent {
+    fn slot(dispatcher: Arc<dyn ToolDispatcher>, guardrails: Guardrails) -> AgentSlot {
+        Arc::new(std::sync::RwLock::new(Arc::new(Self {
+            name: None,
+            dispatcher,
+            base_guardrails: guardrails.clone(),
+            guardrails,
+        })))
+    }
+
+    fn current(slot: &AgentSlot) -> Arc<Self> {
+        Arc::clone(
+            &slot
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+
+    /// Install `agent`, returning the name of the one it replaced.
+    fn switch(slot: &AgentSlot, agent: &Agent) -> Option<String> {
+        let mut current = slot
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut guardrails = current.base_guardrails.clone();
+        guardrails.extend(agent.guardrails());
+        let previous = current.name.clone();
+        *current = Arc::new(Self {
+            name: Some(agent.name().to_string()),
+            dispatcher: agent.tool_dispatcher(),
+            base_guardrails: current.base_guardrails.clone(),
+            guardrails,
+        });
+        previous
+    }
+}
+
+/// User-input guardrails shared by `Session` and `SessionHandle`.
+#[derive(Clone)]
+struct InputGuardrails {
+    agent: AgentSlot,
+    // Weak so the event stream still ends when the event loop exits.
+    events: channel::WeakSender<SdkEvent>,
+}
+
+impl InputGuardrails {
+    fn new(agent: &AgentSlot, events: &channel::Sender<SdkEvent>) -> Self {
+        Self {
+            agent: Arc::clone(agent),
+            events: events.downgrade(),
+        }
+    }
+
+    /// Returns the text to send, or `Error::GuardrailBlocked`.
+    async fn apply(&self, text: String) -> Result<String> {
+        let active = ActiveAgent::current(&self.agent);
+        if active.guardrails.is_empty() {
+            return Ok(text);
+        }
+        let report = active
+            .guardrails
+            .run(GuardrailStage::UserInput, text)
+            .await?;
+        if let Some(events) = self.events.upgrade() {
+            for verdict in &report.verdicts {
+                // Never stall the caller on a full event stream.
+                let _ = events.try_send(SdkEvent::GuardrailTriggered {
+                    stage: GuardrailStage::UserInput,
+                    response_id: None,
+                    verdict: verdict.clone(),
+                });
+            }
+        }
+        if let Some(reason) = report.blocked() {
+            return Err(Error::GuardrailBlocked(reason.to_string()));
+        }
+        Ok(report.text)
+    }
+
+    /// Run the user text in an item create through `apply`, in place.
+    async fn apply_to_event(&self, event: &mut ClientEvent) -> Result<()> {
+        let ClientEvent::ConversationItemCreate { item, .. } = event else {
+            return Ok(());
+        };
+        let Item::Message {
+            role: crate::protocol::models::Role::User,
+            content,
+            ..
+        } = &mut **item
+        else {
+            return Ok(());
+        };
+        for part in content {
+            if let ContentPart::InputText { text } = part {
+                *text = self.apply(std::mem::take(text)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Routes commands onto the control or bulk lane of the session event loop.
+#[derive(Clone)]
+struct CommandSender {
+    control: mpsc::Sender<Command>,
+    bulk: mpsc::Sender<Command>,
+    audio: AudioLeases,
+}
+
+impl CommandSender {
+    fn new(
+        capacities: &ChannelCapacities,
+    ) -> (Self, mpsc::Receiver<Command>, mpsc::Receiver<Command>) {
+        let (control, control_rx) = mpsc::channel(capacities.control.max(1));
+        let (bulk, bulk_rx) = mpsc::channel(capacities.bulk.max(1));
+        let sender = Self {
+            control,
+            bulk,
+            audio: AudioLeases::default(),
+        };
+        (sender, control_rx, bulk_rx)
+    }
+
+    async fn send(&self, cmd: Command) -> Result<()> {
+        self.send_as(cmd, None).await
+    }
+
+    /// Send `cmd` on behalf of the holder of input audio lease `lease`, if any.
+    async fn send_as(&self, cmd: Command, lease: Option<u64>) -> Result<()> {
+        if let Command::SendWithResponse { event, .. } = &cmd {
+            self.audio.check(event, lease)?;
+        }
+        let lane = if cmd.is_control() {
+            &self.control
+        } else {
+            &self.bulk
+        };
+        lane.send(cmd).await.map_err(|_| Error::ConnectionClosed)
+    }
+
+    /// The event loop owns the receivers, so they close when it exits or panics.
+    fn is_closed(&self) -> bool {
+        self.control.is_closed()
+    }
+
+    async fn closed(&self) {
+        self.control.closed().await;
+    }
+
+    fn downgrade(&self) -> WeakCommandSender {
+        WeakCommandSender {
+            control: self.control.downgrade(),
+            bulk: self.bulk.downgrade(),
+            audio: self.audio.clone(),
+        }
+    }
+}
+
+/// A `CommandSender` that does not keep the event loop alive.
+struct WeakCommandSender {
+    control: mpsc::WeakSender<Command>,
+    bulk: mpsc::WeakSender<Command>,
+    audio: AudioLeases,
+}
+
+impl WeakCommandSender {
+    fn upgrade(&self) -> Option<CommandSender> {
+        Some(CommandSender {
+            control: self.control.upgrade()?,
+            bulk: self.bulk.upgrade()?,
+            audio: self.audio.clone(),
+        })
+    }
+}
+
+/// The parts of a `SessionHandle`, held by the event loop without keeping it alive.
+struct WeakHandle {
+    sender: WeakCommandSender,
+    input_guardrails: InputGuardrails,
+    state: StateWatch,
+    outbound_gate: OutboundGate,
+    pending: PendingRequests,
+    cancellation: CancellationToken,
+    request_timeout: Duration,
+}
+
+impl WeakHandle {
+    fn upgrade(&self) -> Option<SessionHandle> {
+        Some(SessionHandle {
+            sender: self.sender.upgrade()?,
+            input_guardrails: self.input_guardrails.clone(),
+            state: self.state.clone(),
+            outbound_gate: self.outbound_gate.clone(),
+            pending: Arc::clone(&self.pending),
+            cancellation: self.cancellation.clone(),
+            request_timeout: self.request_timeout,
+        })
+    }
+}
+
+/// A tool call that finished on its own task.
+struct ToolDone {
+    call_id: String,
+    name: String,
+    elapsed: Duration,
+    /// `Err` holds the message of a panic.
+    outcome: std::result::Result<Result<ToolResult>, String>,
+    /// Where `Session::run_tool` waits for the result.
+    respond: Option<oneshot::Sender<Result<ToolResult>>>,
+}
+
+/// Tool calls running alongside the event loop, so tools can use the session while
+/// they run.
+#[derive(Default)]
+struct ToolRuns {
+    /// `None` only in `LoopState::default()`.
+    handle: Option<WeakHandle>,
+    done_tx: Option<mpsc::UnboundedSender<ToolDone>>,
+    /// Calls still running, by call ID.
+    running: HashMap<String, RunningCall>,
+    /// Calls answered with a placeholder, by call ID, until `complete_tool`.
+    deferred: HashMap<String, DeferredCall>,
+    clock: SharedClock,
+    concurrency: ToolConcurrency,
+    /// Closes when the last call started finishes, so the next can start.
+    last: Option<oneshot::Receiver<()>>,
+}
+
+/// A tool call on its own task.
+struct RunningCall {
+    /// The response that made the call.
+    response_id: Option<String>,
+    cancellation: CancellationToken,
+    /// Set by `ToolContext::defer`.
+    deferred: Arc<AtomicBool>,
+}
+
+/// How a tool call ended, as far as the loop cares.
+#[derive(Default)]
+struct RunEnd {
+    response_id: Option<String>,
+    cancelled: bool,
+    deferred: bool,
+}
+
+/// A tool call answered with a placeholder, waiting for its result.
+struct DeferredCall {
+    name: String,
+    /// The response that made the call.
+    response_id: Option<String>,
+    /// Whether the result is added to the conversation.
+    send_output: bool,
+    /// Whether a response follows the result.
+    follow_up: bool,
+}
+
+impl ToolRuns {
+    fn new(
+        handle: WeakHandle,
+        clock: SharedClock,
+        concurrency: ToolConcurrency,
+    ) -> (Self, mpsc::UnboundedReceiver<ToolDone>) {
+        let (done_tx, done_rx) = mpsc::unbounded_channel();
+        let runs = Self {
+            handle: Some(handle),
+            done_tx: Some(done_tx),
+            running: HashMap::new(),
+            deferred: HashMap::new(),
+            clock,
+            concurrency,
+            last: None,
+        };
+        (runs, done_rx)
+    }
+
+    /// Run `call` on its own task, with a `ToolContext` whose token is a child of
+    /// `parent`, if given. Under `ToolConcurrency::Sequential` it starts once the
+    /// call before it has finished.
+    fn spawn(
+        &mut self,
+        call: ToolCall,
+        respond: Option<oneshot::Sender<Result<ToolResult>>>,
+        parent: Option<CancellationToken>,
+        run: impl FnOnce(ToolCall, ToolContext) -> BoxFuture<Result<ToolResult>>,
+    ) {
+        let Some(done_tx) = self.done_tx.clone() else {
+            if let Some(respond) = respond {
+                let _ = respond.send(Err(Error::ConnectionClosed));
+            }
+            return;
+        };
+        // Every handle is gone, so the loop is about to exit; the call still gets an
+        // answer.
+        let Some(session) = self.handle.as_ref().and_then(WeakHandle::upgrade) else {
+            let _ = done_tx.send(ToolDone {
+                call_id: call.call_id,
+                name: call.name,
+                elapsed: Duration::ZERO,
+                outcome: Ok(Err(Error::ConnectionClosed)),
+                respond,
+            });
+            return;
+        };
+        let cancellation =
+            parent.map_or_else(CancellationToken::new, |parent| parent.child_token());
+        let deferred = Arc::new(AtomicBool::new(false));
+        self.running.insert(
+            call.call_id.clone(),
+            RunningCall {
+                response_id: call.response_id.clone(),
+                cancellation: cancellation.clone(),
+                deferred: Arc::clone(&deferred),
+            },
+        );
+        let context = ToolContext::new(session, &call, cancellation, deferred);
+        let (call_id, name) = (call.call_id.clone(), call.name.clone());
+        let run = run(call, context);
+        let clock = self.clock.clone();
+        let (finished, next) = oneshot::channel::<()>();
+        let before = match self.concurrency {
+            ToolConcurrency::Sequential => self.last.replace(next),
+            ToolConcurrency::Concurrent => None,
+        };
+        tokio::spawn(async move {
+            // Dropped when this call ends, letting the next one start.
+            let _finished = finished;
+            if let Some(before) = before {
+                let _ = before.await;
+            }
+            let started = clock.now();
+            let outcome = std::panic::AssertUnwindSafe(run)
+                .catch_unwind()
+                .await
+                .map_err(|payload| panic_message(&*payload));
+            let _ = done_tx.send(ToolDone {
+                call_id,
+                name,
+                elapsed: clock.now() - started,
+                outcome,
+                respond,
+            });
+        });
+    }
+
+    /// Cancel the calls made by a response that ended as cancelled, and forget its
+    /// deferred calls.
+    fn observe(&mut self, evt: &ServerEvent) {
+        let ServerEvent::ResponseDone { response, .. } = evt else {
+            return;
+        };
+        if response.status != ResponseStatus::Cancelled {
+            return;
+        }
+        let made_by = |response_id: &Option<String>| response_id.as_deref() == Some(&response.id);
+        for call in self.running.values() {
+            if made_by(&call.response_id) {
+                call.cancellation.cancel();
+            }
+        }
+        self.deferred.retain(|_, call| !made_by(&call.response_id));
+    }
+
+    /// Forget a finished call, returning how it ended.
+    fn finish(&mut self, call_id: &str) -> RunEnd {
+        self.running
+            .remove(call_id)
+            .map(|call| RunEnd {
+                response_id: call.response_id,
+                cancelled: call.cancellation.is_cancelled(),
+                deferred: call.deferred.load(Ordering::Relaxed),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Cancel every running call and forget the deferred ones, as the session closes.
+    fn close(&mut self) {
+        for call in self.running.values() {
+            call.cancellation.cancel();
+        }
+        self.deferred.clear();
+    }
+}
+
+enum Command {
+    SendWithResponse {
+        event: ClientEvent,
+        respond: oneshot::Sender<Result<()>>,
+    },
+    RunTool {
+        call: Box<ToolCall>,
+        /// Parent of the call's token, from `run_tool_cancellable`.
+        cancellation: Option<CancellationToken>,
+        respond: oneshot::Sender<Result<ToolResult>>,
+    },
+    TakeDeferredCall {
+        call_id: String,
+        respond: oneshot::Sender<Option<DeferredCall>>,
+    },
+    GetActiveResponseId {
+        respond: oneshot::Sender<Option<String>>,
+    },
+    GetConversation {
+        respond: oneshot::Sender<ConversationSnapshot>,
+    },
+    GetCallReport {
+        respond: oneshot::Sender<CallReport>,
+    },
+    ScheduleResponse {
+        id: u64,
+        delay: Duration,
+        config: Box<ResponseConfig>,
+    },
+    CancelScheduledResponse {
+        id: u64,
+        respond: oneshot::Sender<bool>,
+    },
+    PlayLocalAudio {
+        pcm: Vec<u8>,
+        position: LocalAudioPosition,
+        respond: oneshot::Sender<()>,
+    },
+    Replay {
+        events: Vec<ClientEvent>,
+        respond: oneshot::Sender<Result<usize>>,
+    },
+    SetDeadline {
+        deadline: Box<Deadline>,
+    },
+}
+
+impl Command {
+    /// Interruption-related commands that must not wait behind bulk sends.
+    const fn is_control(&self) -> bool {
+        match self {
+            Self::SendWithResponse { event, .. } => matches!(
+                event,
+                ClientEvent::ResponseCancel { .. }
+                    | ClientEvent::OutputAudioBufferClear { .. }
+                    | ClientEvent::InputAudioBufferClear { .. }
+            ),
+            // Scheduling is timing-sensitive: a schedule must not land after the user
+            // speech that should have cancelled it.
+            Self::GetActiveResponseId { .. }
+            | Self::ScheduleResponse { .. }
+            | Self::CancelScheduledResponse { .. }
+            | Self::SetDeadline { .. } => true,
+            Self::RunTool { .. }
+            | Self::TakeDeferredCall { .. }
+            | Self::PlayLocalAudio { .. }
+            | Self::GetConversation { .. }
+            | Self::GetCallReport { .. }
+            | Self::Replay { .. } => false,
+        }
+    }
+}
+
+/// Event loop behavior configured through the builders.
+pub struct SessionOptions {
+    pub auto_barge_in: bool,
+    /// When speech triggers an auto barge-in.
+    pub barge_in: BargeInPolicy,
+    /// Issue `response.create` on `input_audio_buffer.speech_stopped`.
+    pub respond_on_speech_stop: bool,
+    pub auto_tool_response: bool,
+    pub tool_error_policy: ToolErrorPolicy,
+    pub tool_concurrency: ToolConcurrency,
+    pub idle_timeout: IdleTimeoutPolicy,
+    pub transcription_failure: TranscriptionFailurePolicy,
+    pub audio_failure: AudioFailurePolicy,
+    /// Codecs for the session's audio formats.
+    pub codecs: CodecRegistry,
+    /// Where time-based behavior reads the time.
+    pub clock: SharedClock,
+    /// Emit response text in speakable chunks; off when `None`.
+    pub speakable: Option<SpeakableChunking>,
+    pub answering_machine: Option<AnsweringMachineDetection>,
+    pub guardrails: Guardrails,
+    pub analytics_interval: Option<Duration>,
+    /// Emit `SdkEvent::SlowHandler` for handler and tool calls at least this slow.
+    pub slow_handler: Option<Duration>,
+    /// Report `VoiceEvent::InputLevel` / `OutputLevel` at this interval; off when `None`.
+    pub audio_levels: Option<Duration>,
+    /// Where item creates wait for the server to add them; see
+    /// `Session::unacknowledged_events`. Off when `None`.
+    pub resend: Option<ResendQueue>,
+    /// Warning before, and renewal ahead of, the session time limit.
+    pub expiry: ExpiryPolicy,
+    /// Opens the connection a session renews onto; renewal needs one.
+    pub reconnect: Option<Reconnect>,
+    /// The SIP or WebRTC call the session is attached to; see `Session::call_state`.
+    pub call_id: Option<String>,
+    pub role: SessionRole,
+    pub channel_capacities: ChannelCapacities,
+    pub overflow_policy: OverflowPolicy,
+    /// Caps `max_output_tokens` on every `response.create` sent through the session,
+    /// not on those the event loop starts on its own.
+    pub token_budget: Option<TokenBudget>,
+    /// Closes the session, and fails its long-running calls with `Error::Cancelled`,
+    /// once cancelled.
+    pub cancellation: CancellationToken,
+    /// The pooled key the session connected with, which it reports health to.
+    pub key_lease: Option<KeyLease>,
+    /// How long requests awaiting a server reply wait before failing with
+    /// `Error::Timeout`.
+    pub request_timeout: Duration,
+}
+
+/// Whether a session controls its call or only listens in on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SessionRole {
+    #[default]
+    Controller,
+    /// Read-only: skips tool calls and rejects every send with `Error::ReadOnly`.
+    Observer,
+}
+
+impl SessionRole {
+    fn wrap(self, transport: Box<dyn Transport>) -> Box<dyn Transport> {
+        match self {
+            Self::Controller => transport,
+            Self::Observer => Box::new(ReadOnlyTransport { inner: transport }),
+        }
+    }
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            auto_barge_in: false,
+            barge_in: BargeInPolicy::default(),
+            respond_on_speech_stop: false,
+            auto_tool_response: true,
+            tool_error_policy: ToolErrorPolicy::default(),
+            tool_concurrency: ToolConcurrency::default(),
+            idle_timeout: IdleTimeoutPolicy::default(),
+            transcription_failure: TranscriptionFailurePolicy::default(),
+            audio_failure: AudioFailurePolicy::default(),
+            codecs: CodecRegistry::new(),
+            clock: SharedClock::default(),
+            speakable: None,
+            answering_machine: None,
+            guardrails: Guardrails::new(),
+            analytics_interval: None,
+            slow_handler: None,
+            audio_levels: None,
+            resend: None,
+            expiry: ExpiryPolicy::default(),
+            reconnect: None,
+            call_id: None,
+            role: SessionRole::Controller,
+            channel_capacities: ChannelCapacities::default(),
+            overflow_policy: OverflowPolicy::default(),
+            token_budget: None,
+            cancellation: CancellationToken::new(),
+            key_lease: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+pub struct SessionConfigSnapshot {
+    pub api_key: String,
+    pub model: Option<String>,
+    pub call_id: Option<String>,
+    pub base_url: Option<String>,
+    pub session: SessionConfig,
+    pub ready_timeout: Option<Duration>,
+    pub connect_timeout: Duration,
+    pub max_event_bytes: Option<usize>,
+    pub event_log: Option<EventLog>,
+    pub trace: crate::TraceConfig,
+    pub handlers: EventHandlers,
+    pub dispatcher: Arc<dyn ToolDispatcher>,
+    pub options: SessionOptions,
+}
+
+impl SessionConfigSnapshot {
+    /// Connect via WebSocket.
+    ///
+    /// # Errors
+    /// Returns an error if the connection fails, or `Error::Cancelled` if
+    /// `SessionOptions::cancellation` fires before the session is ready.
+    pub async fn connect_ws(self) -> Result<Session> {
+        let cancellation = self.options.cancellation.clone();
+        let lease = self.options.key_lease.clone();
+        let result =
+            tool_context::run_until_cancelled(&cancellation, None, Box::pin(self.open_ws()))
+                .await
+                .and_then(|opened| opened);
+        if let Some(lease) = lease {
+            match &result {
+                Ok(_) => lease.record_success(),
+                Err(Error::Cancelled) => {}
+                Err(_) => lease.record_error(),
+            }
+        }
+        result
+    }
+
+    async fn open_ws(mut self) -> Result<Session> {
+        if self.options.role == SessionRole::Observer && self.call_id.is_none() {
+            return Err(Error::InvalidClientEvent(
+                "observer sessions require a call_id".to_string(),
+            ));
+        }
+        let connect = WsConnect {
+            url: self
+                .base_url
+                .unwrap_or_else(|| crate::transport::ws::WS_BASE_URL.to_string()),
+            api_key: self.api_key,
+            model: self.model,
+            call_id: self.call_id,
+            timeout: self.connect_timeout,
+            max_event_bytes: self.max_event_bytes,
+            trace: self.trace,
+            event_log: self.event_log,
+            outbound: crate::OutboundStats::default(),
+        };
+        let transport = connect.clone().open().await?;
+        // Renewal opens a fresh session; a SIP call's session cannot be re-opened.
+        let renews = self.options.expiry.renew_before.is_some()
+            && self.options.role == SessionRole::Controller
+            && connect.call_id.is_none();
+        if renews && self.options.reconnect.is_none() {
+            let connect = connect.clone();
+            // Only the pool: the lease on the first key ends with its connection.
+            let pool = self.options.key_lease.as_ref().map(KeyLease::pool);
+            self.options.reconnect = Some(Arc::new(move || {
+                let connect = connect.clone();
+                let pool = pool.clone();
+                Box::pin(async move {
+                    match pool {
+                        Some(pool) => connect.open_pooled(&pool).await,
+                        None => connect.open().await,
+                    }
+                })
+            }));
+        }
+        let role = self.options.role;
+        let mut session =
+            Session::from_transport(transport, self.handlers, self.dispatcher, self.options);
+        session.outbound = connect.outbound;
+        {
+            let mut info = session.connection_info_mut();
+            info.model.get_or_insert_with(|| self.session.model.clone());
+        }
+        // The controlling session owns the configuration of an observed call.
+        if role == SessionRole::Observer {
+            return Ok(session);
+        }
+        let update = SessionUpdate {
+            config: self.session.to_update(),
+        };
+        session.update_session(update).await?;
+        if let Some(timeout) = self.ready_timeout {
+            session.wait_ready(timeout).await?;
+        }
+        Ok(session)
+    }
+}
+
+/// A user message holding `text`.
+fn user_text(text: String) -> Item {
+    Item::Message {
+        id: None,
+        status: None,
+        role: crate::protocol::models::Role::User,
+        content: vec![ContentPart::InputText { text }],
+    }
+}
+
+/// What it takes to open a session's WebSocket, kept so a renewal can open another.
+#[derive(Clone)]
+struct WsConnect {
+    url: String,
+    api_key: String,
+    model: Option<String>,
+    call_id: Option<String>,
+    timeout: Duration,
+    max_event_bytes: Option<usize>,
+    trace: crate::TraceConfig,
+    event_log: Option<EventLog>,
+    /// Counts what every connection opened sends, so a renewed session's totals
+    /// carry on.
+    outbound: crate::OutboundStats,
+}
+
+impl WsConnect {
+    async fn open(self) -> Result<Box<dyn Transport>> {
+        let mut client = crate::RealtimeClient::connect_with_timeout(
+            &self.url,
+            &self.api_key,
+            self.model.as_deref(),
+            self.call_id.as_deref(),
+            self.timeout,
+        )
+        .await?
+        .with_trace_config(self.trace)
+        .with_outbound_stats(self.outbound);
+        if let Some(max) = self.max_event_bytes {
+            client = client.with_max_event_bytes(max);
+        }
+
+        let mut transport: Box<dyn Transport> = Box::new(WsTransport { client });
+        if let Some(log) = self.event_log {
+            transport = Box::new(LoggedTransport::new(transport, log));
+        }
+        Ok(transport)
+    }
+
+    /// Open a renewed connection on the key `pool` picks now, which is not
+    /// necessarily the one the session started on.
+    async fn open_pooled(self, pool: &KeyPool) -> Result<Box<dyn Transport>> {
+        let lease = pool
+            .acquire()
+            .ok_or_else(|| Error::InvalidClientEvent("key pool has no usable key".to_string()))?;
+        let connect = Self {
+            api_key: lease.api_key().to_string(),
+            ..self
+        };
+        match connect.open().await {
+            Ok(transport) => {
+                lease.record_success();
+                Ok(lease.wrap(transport))
+            }
+            Err(err) => {
+                lease.record_error();
+                Err(err)
+            }
+        }
+    }
+}
+
+struct WsTransport {
+    client: crate::RealtimeClient,
+}
+
+impl Transport for WsTransport {
+    fn send(&mut self, event: ClientEvent) -> super::transport::BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.client.send(event).await })
+    }
+
+    fn next_event(&mut self) -> super::transport::BoxFuture<'_, Result<Option<ServerEvent>>> {
+        Box::pin(async move { self.client.next_event().await })
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.client.close_frame().cloned()
+    }
+}
+
+/// Rejects every send so an observer cannot affect the call it is listening to.
+struct ReadOnlyTransport {
+    inner: Box<dyn Transport>,
+}
+
+impl Transport for ReadOnlyTransport {
+    fn send(&mut self, event: ClientEvent) -> super::transport::BoxFuture<'_, Result<()>> {
+        Box::pin(async move { Err(Error::ReadOnly(event.event_type())) })
+    }
+
+    fn next_event(&mut self) -> super::transport::BoxFuture<'_, Result<Option<ServerEvent>>> {
+        self.inner.next_event()
+    }
+
+    fn close_frame(&self) -> Option<crate::ServerClose> {
+        self.inner.close_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::server_events::ServerEvent;
+    use crate::sdk::{Speaker, ToolRegistry};
+    use base64::engine::general_purpose;
+    use futures::StreamExt;
+    use tokio::sync::mpsc;
+
+    struct MockTransport {
+        incoming: mpsc::Receiver<ServerEvent>,
+        outgoing: mpsc::Sender<ClientEvent>,
+    }
+
+    impl Transport for MockTransport {
+        fn send(
+            &mut self,
+            event: ClientEvent,
+        ) -> super::super::transport::BoxFuture<'_, Result<()>> {
+            let outgoing = self.outgoing.clone();
+            Box::pin(async move {
+                outgoing
+                    .send(event)
+                    .await
+                    .map_err(|_| Error::ConnectionClosed)?;
+                Ok(())
+            })
+        }
+
+        fn next_event(
+            &mut self,
+        ) -> super::super::transport::BoxFuture<'_, Result<Option<ServerEvent>>> {
+            Box::pin(async move { Ok(self.incoming.recv().await) })
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_call_sends_output() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut tools = ToolRegistry::new();
+        tools.tool("echo", |args: serde_json::Value| async move { Ok(args) });
+
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let evt = ServerEvent::ResponseFunctionCallArgumentsDone {
+            event_id: "evt_1".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            call_id: "call_1".to_string(),
+            name: "echo".to_string(),
+            arguments: r#"{"hello":"world"}"#.to_string(),
+        };
+
+        event_tx.send(evt).await.unwrap();
+
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        match sent {
+            ClientEvent::ConversationItemCreate { item, .. } => match *item {
+                Item::FunctionCallOutput {
+                    call_id, output, ..
+                } => {
+                    assert_eq!(call_id, "call_1");
+                    assert!(output.contains("hello"));
+                }
+                other => panic!("unexpected item: {other:?}"),
+            },
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        let follow_up = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(follow_up, ClientEvent::ResponseCreate { .. }));
+
+        drop(session);
+    }
+
+    fn call_args_done(call_id: &str, name: &str) -> ServerEvent {
+        ServerEvent::ResponseFunctionCallArgumentsDone {
+            event_id: format!("evt_{call_id}"),
+            response_id: "resp_1".to_string(),
+            item_id: format!("item_{call_id}"),
+            output_index: 0,
+            call_id: call_id.to_string(),
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn added_tool_output_is_reported_and_forgotten() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut tools = ToolRegistry::new();
+        tools.tool("echo", |args: serde_json::Value| async move { Ok(args) });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        event_tx
+            .send(call_args_done("call_1", "echo"))
+            .await
+            .unwrap();
+        next_sent(&mut out_rx).await;
+
+        let added = |call_id: &str| ServerEvent::ConversationItemAdded {
+            event_id: format!("evt_{call_id}_added"),
+            previous_item_id: None,
+            item: Item::FunctionCallOutput {
+                id: Some(format!("item_{call_id}")),
+                call_id: call_id.to_string(),
+                output: "{}".to_string(),
+            },
+        };
+        event_tx.send(added("call_other")).await.unwrap();
+        event_tx.send(added("call_1")).await.unwrap();
+        let accepted = loop {
+            match session.next_event().await.unwrap() {
+                Some(SdkEvent::ToolOutputAccepted { call_id, item_id }) => {
+                    break (call_id, item_id);
+                }
+                Some(_) => {}
+                None => panic!("session closed"),
+            }
+        };
+        assert_eq!(accepted, ("call_1".to_string(), "item_call_1".to_string()));
+        assert!(session.pending.lock().await.tool_outputs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn context_tools_can_use_the_session_while_running() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut tools = ToolRegistry::new();
+        tools.tool_with_context("lookup", |ctx, _: serde_json::Value| async move {
+            ctx.session().say("One moment.").await?;
+            Ok(serde_json::json!({
+                "call": ctx.call_id(),
+                "response": ctx.response_id(),
+            }))
+        });
+        let _session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        event_tx
+            .send(call_args_done("call_1", "lookup"))
+            .await
+            .unwrap();
+        let mut sent = Vec::new();
+        for _ in 0..3 {
+            sent.push(
+                tokio::time::timeout(Duration::from_secs(1), out_rx.recv())
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+        assert!(matches!(
+            &sent[0],
+            ClientEvent::ConversationItemCreate { item, .. }
+                if matches!(&**item, Item::Message { content, .. }
+                    if matches!(content.as_slice(), [ContentPart::InputText { text }] if text == "One moment."))
+        ));
+        let ClientEvent::ConversationItemCreate { item, .. } = &sent[1] else {
+            panic!("expected function_call_output");
+        };
+        let Item::FunctionCallOutput { output, .. } = &**item else {
+            panic!("expected function_call_output");
+        };
+        assert_eq!(output, r#"{"call":"call_1","response":"resp_1"}"#);
+        assert!(matches!(sent[2], ClientEvent::ResponseCreate { .. }));
+    }
+
+    #[tokio::test]
+    async fn cancelled_response_cancels_its_tool_calls() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut tools = ToolRegistry::new();
+        tools.tool_with_context("slow", |ctx, _: serde_json::Value| async move {
+            ctx.cancellation().cancelled().await;
+            Ok("stopped")
+        });
+        let _session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        event_tx
+            .send(call_args_done("call_1", "slow"))
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_done".to_string(),
+                response: crate::protocol::models::Response {
+                    id: "resp_1".to_string(),
+                    object: "response".to_string(),
+                    conversation_id: None,
+                    status: ResponseStatus::Cancelled,
+                    status_details: None,
+                    output: None,
+                    output_modalities: None,
+                    max_output_tokens: None,
+                    audio: None,
+                    metadata: None,
+                    usage: None,
+                },
+            })
+            .await
+            .unwrap();
+
+        let sent = tokio::time::timeout(Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            sent,
+            ClientEvent::ConversationItemCreate { item, .. }
+                if matches!(*item, Item::FunctionCallOutput { ref output, .. } if output == "\"stopped\"")
+        ));
+        // No follow-up response for a cancelled call.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), out_rx.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_calls_run_in_order_unless_concurrent() {
+        for (concurrency, first) in [
+            (ToolConcurrency::Sequential, "1"),
+            (ToolConcurrency::Concurrent, "2"),
+        ] {
+            let (event_tx, event_rx) = mpsc::channel(8);
+            let (out_tx, mut out_rx) = mpsc::channel(8);
+            let transport = Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            });
+            let release = Arc::new(tokio::sync::Notify::new());
+            let gate = Arc::clone(&release);
+            let mut calls = 0;
+            let mut tools = ToolRegistry::new();
+            tools.tool_with_context("step", move |_, _: serde_json::Value| {
+                calls += 1;
+                let (call, gate) = (calls, Arc::clone(&gate));
+                async move {
+                    if call == 1 {
+                        gate.notified().await;
+                    }
+                    Ok(call)
+                }
+            });
+            let _session = Session::from_transport(
+                transport,
+                EventHandlers::new(),
+                Arc::new(tools),
+                SessionOptions {
+                    auto_tool_response: false,
+                    tool_concurrency: concurrency,
+                    ..SessionOptions::default()
+                },
+            );
+
+            for call_id in ["call_1", "call_2"] {
+                event_tx
+                    .send(call_args_done(call_id, "step"))
+                    .await
+                    .unwrap();
+            }
+            if concurrency == ToolConcurrency::Sequential {
+                // The second call waits for the first, which waits for the release.
+                assert!(
+                    tokio::time::timeout(Duration::from_millis(50), out_rx.recv())
+                        .await
+                        .is_err()
+                );
+                release.notify_one();
+            }
+            let mut outputs = Vec::new();
+            for _ in 0..2 {
+                let ClientEvent::ConversationItemCreate { item, .. } = next_sent(&mut out_rx).await
+                else {
+                    panic!("expected function_call_output");
+                };
+                let Item::FunctionCallOutput { output, .. } = *item else {
+                    panic!("expected function_call_output");
+                };
+                outputs.push(output);
+                release.notify_one();
+            }
+            assert_eq!(outputs[0], first, "{concurrency:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn set_speaking_speed_sends_nested_output_update() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        assert!(matches!(
+            session.set_speaking_speed(2.0).await,
+            Err(Error::InvalidClientEvent(_))
+        ));
+        session.set_speaking_speed(1.25).await.unwrap();
+
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let json = serde_json::to_value(&sent).unwrap();
+        assert_eq!(json["type"], "session.update");
+        assert_eq!(json["session"]["audio"]["output"]["speed"], 1.25);
+        assert!(json["session"]["audio"]["input"].is_null());
+        assert!(json["session"]["instructions"].is_null());
+    }
+
+    #[tokio::test]
+    async fn tool_error_policy_decides_failure_output() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut tools = ToolRegistry::new();
+        for name in ["quiet", "flaky"] {
+            tools.tool(name, |_: serde_json::Value| async move {
+                Err::<serde_json::Value, _>(Error::Timeout("backend"))
+            });
+        }
+        let options = SessionOptions {
+            tool_error_policy: ToolErrorPolicy::custom(|failure| {
+                (failure.name != "quiet").then(|| serde_json::json!({ "retry": true }))
+            }),
+            ..SessionOptions::default()
+        };
+        let _session =
+            Session::from_transport(transport, EventHandlers::new(), Arc::new(tools), options);
+
+        for (call_id, name) in [("call_1", "quiet"), ("call_2", "flaky")] {
+            event_tx
+                .send(ServerEvent::ResponseFunctionCallArgumentsDone {
+                    event_id: format!("evt_{call_id}"),
+                    response_id: "resp_1".to_string(),
+                    item_id: "item_1".to_string(),
+                    output_index: 0,
+                    call_id: call_id.to_string(),
+                    name: name.to_string(),
+                    arguments: "{}".to_string(),
+                })
+                .await
+                .unwrap();
+        }
+
+        // Nothing is sent for the ignored failure.
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match sent {
+            ClientEvent::ConversationItemCreate { item, .. } => match *item {
+                Item::FunctionCallOutput {
+                    call_id, output, ..
+                } => {
+                    assert_eq!(call_id, "call_2");
+                    assert_eq!(output, r#"{"retry":true}"#);
+                }
+                other => panic!("unexpected item: {other:?}"),
+            },
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn next_event_maps_sdk_event() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let evt = ServerEvent::ResponseOutputTextDelta {
+            event_id: "evt_1".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta: "hello".to_string(),
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let mapped = session.next_event().await.unwrap().expect("sdk event");
+        match mapped {
+            SdkEvent::TextDelta { delta, .. } => assert_eq!(delta, "hello"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn event_stream_yields_sdk_event() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let evt = ServerEvent::ResponseOutputTextDone {
+            event_id: "evt_1".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            text: "done".to_string(),
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let mut stream = session.events();
+        let mapped = stream.next().await.expect("sdk event");
+        match mapped {
+            SdkEvent::TextDone { text, .. } => assert_eq!(text, "done"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn redelivered_events_are_not_handled_twice() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        let delta = ServerEvent::ResponseOutputTextDelta {
+            event_id: "evt_1".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta: "hi".to_string(),
+        };
+        let done = ServerEvent::ResponseOutputTextDone {
+            event_id: "evt_2".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            text: "hi".to_string(),
+        };
+        for evt in [delta.clone(), delta, done.clone(), done] {
+            event_tx.send(evt).await.unwrap();
+        }
+        drop(event_tx);
+
+        let mut reported = Vec::new();
+        while let Some(evt) = next_reported(&mut session).await {
+            reported.push(evt);
+        }
+        assert!(matches!(
+            reported.as_slice(),
+            [
+                SdkEvent::TextDelta { .. },
+                SdkEvent::TextDone { .. },
+                SdkEvent::Replayed { event_id, event_type },
+            ] if event_id == "evt_2" && event_type == "response.output_text.done"
+        ));
+    }
+
+    #[tokio::test]
+    async fn send_response_emits_response_create() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let config = crate::protocol::models::ResponseConfig {
+            instructions: Some("Respond.".to_string()),
+            ..Default::default()
+        };
+
+        session.send_response(config).await.unwrap();
+
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        match sent {
+            ClientEvent::ResponseCreate { response, .. } => {
+                let response = response.expect("response config");
+                assert_eq!(response.instructions.as_deref(), Some("Respond."));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        drop(event_tx);
+    }
+
+    #[tokio::test]
+    async fn approve_mcp_sends_item() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        session.approve_mcp("req_1", Some("ok")).await.unwrap();
+
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        match sent {
+            ClientEvent::ConversationItemCreate { item, .. } => match *item {
+                Item::McpApprovalResponse {
+                    approval_request_id,
+                    approve,
+                    reason,
+                    ..
+                } => {
+                    assert_eq!(approval_request_id, "req_1");
+                    assert!(approve);
+                    assert_eq!(reason.as_deref(), Some("ok"));
+                }
+                other => panic!("unexpected item: {other:?}"),
+            },
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        drop(event_tx);
+    }
+
+    #[tokio::test]
+    async fn ask_sends_and_returns_text() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let event_tx_clone = event_tx.clone();
+        let send_evt = async move {
+            let evt = ServerEvent::ResponseOutputTextDone {
+                event_id: "evt_1".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                text: "hello".to_string(),
+            };
+            event_tx_clone.send(evt).await.unwrap();
+        };
+        tokio::spawn(send_evt);
+
+        let text = session.ask("hi").await.unwrap().expect("text");
+        assert_eq!(text, "hello");
+
+        // Ensure we sent both the item and the response.create.
+        let first = out_rx.recv().await.unwrap();
+        let second = out_rx.recv().await.unwrap();
+        assert!(
+            matches!(first, ClientEvent::ConversationItemCreate { .. })
+                || matches!(second, ClientEvent::ConversationItemCreate { .. })
+        );
+        assert!(
+            matches!(first, ClientEvent::ResponseCreate { .. })
+                || matches!(second, ClientEvent::ResponseCreate { .. })
+        );
+
+        drop(event_tx);
+    }
+
+    #[tokio::test]
+    async fn voice_event_audio_delta_decodes() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let pcm = vec![1u8, 2u8, 3u8, 4u8];
+        let delta = general_purpose::STANDARD.encode(&pcm);
+        let evt = ServerEvent::ResponseOutputAudioDelta {
+            event_id: "evt_1".to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let voice = session
+            .next_voice_event()
+            .await
+            .unwrap()
+            .expect("voice event");
+        match voice {
+            VoiceEvent::AudioDelta {
+                response_id,
+                pcm: decoded,
+                ..
+            } => {
+                assert_eq!(response_id, "resp_1");
+                assert_eq!(decoded, pcm);
+            }
+            other => panic!("unexpected voice event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_audio_failures_fall_back_to_text() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                audio_failure: AudioFailurePolicy::FallbackToText { after: 2 },
+                ..SessionOptions::default()
+            },
+        );
+
+        let delta = |event_id: &str, delta: &str| ServerEvent::ResponseOutputAudioDelta {
+            event_id: event_id.to_string(),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta: delta.to_string(),
+        };
+        event_tx.send(delta("evt_1", "not base64!")).await.unwrap();
+        event_tx.send(delta("evt_2", "AA==")).await.unwrap();
+        // Audio after the fallback is dropped.
+        event_tx.send(delta("evt_3", "AAA=")).await.unwrap();
+
+        let Some(ClientEvent::SessionUpdate {
+            session: update, ..
+        }) = out_rx.recv().await
+        else {
+            panic!("expected session.update");
+        };
+        assert_eq!(
+            update.config.output_modalities,
+            Some(crate::protocol::models::OutputModalities::Text)
+        );
+        let failures = loop {
+            if let SdkEvent::TextFallback { failures, .. } =
+                session.next_event().await.unwrap().expect("event")
+            {
+                break failures;
+            }
+        };
+        assert_eq!(failures, 2);
+
+        drop(event_tx);
+        let mut voice = Vec::new();
+        while let Some(event) = session.next_voice_event().await.unwrap() {
+            voice.push(event);
+        }
+        assert!(matches!(
+            voice.as_slice(),
+            [
+                VoiceEvent::DecodeError { .. },
+                VoiceEvent::DecodeError { .. }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn voice_event_audio_done_propagates_response_id() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let evt = ServerEvent::ResponseOutputAudioDone {
+            event_id: "evt_2".to_string(),
+            response_id: "resp_42".to_string(),
+            item_id: "item_2".to_string(),
+            output_index: 1,
+            content_index: 0,
+            item: None,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let voice = session
+            .next_voice_event()
+            .await
+            .unwrap()
+            .expect("voice event");
+        match voice {
+            VoiceEvent::AudioDone {
+                response_id,
+                item_id,
+                output_index,
+                content_index,
+            } => {
+                assert_eq!(response_id, "resp_42");
+                assert_eq!(item_id, "item_2");
+                assert_eq!(output_index, 1);
+                assert_eq!(content_index, 0);
+            }
+            other => panic!("unexpected voice event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_audio_pcm16_appends_and_commits() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let pcm = vec![0i16; 4];
+        session.send_audio_pcm16(&pcm).await.unwrap();
+
+        let first = out_rx.recv().await.unwrap();
+        let second = out_rx.recv().await.unwrap();
+
+        assert!(
+            matches!(first, ClientEvent::InputAudioBufferAppend { .. })
+                || matches!(second, ClientEvent::InputAudioBufferAppend { .. })
+        );
+        assert!(
+            matches!(first, ClientEvent::InputAudioBufferCommit { .. })
+                || matches!(second, ClientEvent::InputAudioBufferCommit { .. })
+        );
+    }
+
+    #[tokio::test]
+    async fn send_audio_file_chunks_and_reports_progress() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let path = std::env::temp_dir().join(format!("oai-rt-audio-{}.pcm", std::process::id()));
+        let total = audio_file::FILE_CHUNK_BYTES + 100;
+        std::fs::write(&path, vec![0u8; total]).unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sent = session.send_audio_file(&path, Progress::Channel(tx)).await;
+        std::fs::remove_file(&path).unwrap();
+        sent.unwrap();
+
+        let mut appended = Vec::new();
+        loop {
+            match out_rx.recv().await.unwrap() {
+                ClientEvent::InputAudioBufferAppend { audio, .. } => {
+                    appended.push(general_purpose::STANDARD.decode(audio).unwrap().len());
+                }
+                ClientEvent::InputAudioBufferCommit { .. } => break,
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        assert_eq!(appended, vec![audio_file::FILE_CHUNK_BYTES, 100]);
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.sent, audio_file::FILE_CHUNK_BYTES);
+        let last = rx.recv().await.unwrap();
+        assert_eq!((last.sent, last.total), (total, total));
+        assert!((last.percent() - 100.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn audio_handle_push_and_commit() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let pcm = vec![0i16; 4];
+        session.audio().push_pcm16(&pcm).await.unwrap();
+        session.audio().commit().await.unwrap();
+
+        let first = out_rx.recv().await.unwrap();
+        let second = out_rx.recv().await.unwrap();
+
+        assert!(
+            matches!(first, ClientEvent::InputAudioBufferAppend { .. })
+                || matches!(second, ClientEvent::InputAudioBufferAppend { .. })
+        );
+        assert!(
+            matches!(first, ClientEvent::InputAudioBufferCommit { .. })
+                || matches!(second, ClientEvent::InputAudioBufferCommit { .. })
+        );
+    }
+
+    #[tokio::test]
+    async fn audio_input_lease_admits_only_its_holder() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let other = session.handle();
+
+        let lease = session.try_lease_audio_input().unwrap();
+        assert!(matches!(
+            other.try_lease_audio_input(),
+            Err(Error::AudioInputLeased)
+        ));
+        let err = other.send_audio_pcm16(&[1, 2]).await.unwrap_err();
+        assert!(matches!(err, Error::AudioInputLeased));
+        assert!(err.is_validation());
+        assert!(matches!(
+            session.audio_in_append_bytes(&[0, 0]).await,
+            Err(Error::AudioInputLeased)
+        ));
+        // Commits are not guarded.
+        session.audio_in_commit().await.unwrap();
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::InputAudioBufferCommit { .. })
+        ));
+
+        lease.append_pcm16(&[1, 2]).await.unwrap();
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::InputAudioBufferAppend { audio, .. }) if audio == "AQACAA=="
+        ));
+
+        let waiting = tokio::spawn(async move { other.lease_audio_input().await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiting.is_finished());
+        lease.release();
+        let handed_over = waiting.await.unwrap();
+        handed_over.append_bytes(&[0, 0]).await.unwrap();
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::InputAudioBufferAppend { .. })
+        ));
+        drop(handed_over);
+        session.audio_in_append_bytes(&[0, 0]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stream_audio_pcm16_sends_chunks() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let stream = futures::stream::iter(vec![vec![0i16; 2], vec![1i16; 2]]);
+        session.stream_audio_pcm16(stream).await.unwrap();
+
+        let mut saw_append = 0;
+        let mut saw_commit = 0;
+        for _ in 0..4 {
+            let evt = out_rx.recv().await.unwrap();
+            match evt {
+                ClientEvent::InputAudioBufferAppend { .. } => saw_append += 1,
+                ClientEvent::InputAudioBufferCommit { .. } => saw_commit += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(saw_append, 2);
+        assert_eq!(saw_commit, 2);
+    }
+
+    #[tokio::test]
+    async fn barge_in_sends_clear_and_cancel() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let resp = crate::protocol::models::Response {
+            id: "resp_1".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: None,
+            usage: None,
+        };
+        let evt = ServerEvent::ResponseCreated {
+            event_id: "evt_1".to_string(),
+            response: resp,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let _ = session.next_voice_event().await.unwrap();
+        session.barge_in().await.unwrap();
+
+        let first = out_rx.recv().await.unwrap();
+        let second = out_rx.recv().await.unwrap();
+
+        assert!(
+            matches!(first, ClientEvent::OutputAudioBufferClear { .. })
+                || matches!(second, ClientEvent::OutputAudioBufferClear { .. })
+        );
+        assert!(
+            matches!(first, ClientEvent::ResponseCancel { .. })
+                || matches!(second, ClientEvent::ResponseCancel { .. })
+        );
+    }
+
+    #[tokio::test]
+    async fn respond_on_speech_stop_issues_response_create() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                respond_on_speech_stop: true,
+                ..SessionOptions::default()
+            },
+        );
+
+        event_tx
+            .send(ServerEvent::InputAudioBufferSpeechStopped {
+                event_id: "evt_1".to_string(),
+                audio_end_ms: 1_200,
+                item_id: "item_1".to_string(),
+            })
+            .await
+            .unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::ResponseCreate { response: None, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn barge_in_policy_ignores_short_speech() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                auto_barge_in: true,
+                barge_in: BargeInPolicy {
+                    min_speech: Some(Duration::from_millis(40)),
+                    ..BargeInPolicy::default()
+                },
+                ..SessionOptions::default()
+            },
+        );
+        let started = |item_id: &str| ServerEvent::InputAudioBufferSpeechStarted {
+            event_id: format!("evt_start_{item_id}"),
+            audio_start_ms: 0,
+            item_id: item_id.to_string(),
+        };
+        let stopped = |item_id: &str| ServerEvent::InputAudioBufferSpeechStopped {
+            event_id: format!("evt_stop_{item_id}"),
+            audio_end_ms: 10,
+            item_id: item_id.to_string(),
+        };
+        *session.active_response_id.lock().await = Some("resp_1".to_string());
+
+        // A cough: speech stops before the minimum duration.
+        event_tx.send(started("item_1")).await.unwrap();
+        event_tx.send(stopped("item_1")).await.unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(out_rx.try_recv().is_err());
+
+        // Sustained speech interrupts once the minimum has passed.
+        event_tx.send(started("item_2")).await.unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+        assert!(out_rx.try_recv().is_err());
+        let clear = tokio::time::timeout(Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(clear, ClientEvent::OutputAudioBufferClear { .. }));
+        let cancel = out_rx.recv().await.unwrap();
+        assert!(matches!(
+            cancel,
+            ClientEvent::ResponseCancel { response_id: Some(ref id), .. } if id == "resp_1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn auto_barge_in_on_speech_started() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions {
+                auto_barge_in: true,
+                ..SessionOptions::default()
+            },
+        );
+
+        let resp = crate::protocol::models::Response {
+            id: "resp_1".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: None,
+            usage: None,
+        };
+        let created = ServerEvent::ResponseCreated {
+            event_id: "evt_1".to_string(),
+            response: resp,
+        };
+        event_tx.send(created).await.unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        let speech = ServerEvent::InputAudioBufferSpeechStarted {
+            event_id: "evt_2".to_string(),
+            audio_start_ms: 0,
+            item_id: "item_1".to_string(),
+        };
+        event_tx.send(speech).await.unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        let first = out_rx.recv().await.unwrap();
+        let second = out_rx.recv().await.unwrap();
+
+        assert!(
+            matches!(first, ClientEvent::OutputAudioBufferClear { .. })
+                || matches!(second, ClientEvent::OutputAudioBufferClear { .. })
+        );
+        assert!(
+            matches!(first, ClientEvent::ResponseCancel { .. })
+                || matches!(second, ClientEvent::ResponseCancel { .. })
+        );
+    }
+
+    #[tokio::test]
+    async fn audio_deltas_gate_on_active_response() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let resp = crate::protocol::models::Response {
+            id: "resp_1".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: None,
+            usage: None,
+        };
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: resp,
+            })
+            .await
+            .unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        let pcm = vec![1u8, 2u8];
+        let delta = general_purpose::STANDARD.encode(&pcm);
+        let evt = ServerEvent::ResponseOutputAudioDelta {
+            event_id: "evt_2".to_string(),
+            response_id: "resp_2".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        // Should not receive audio chunk for different response_id.
+        let chunk = tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            session.next_audio_chunk(),
+        )
+        .await;
+        assert!(chunk.is_err());
+    }
+
+    #[tokio::test]
+    async fn cancel_active_response_waits_for_response_done() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        let mut response = crate::protocol::models::Response {
+            id: "resp_1".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: None,
+            usage: None,
+        };
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: response.clone(),
+            })
+            .await
+            .unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        let server = async {
+            let Some(ClientEvent::ResponseCancel { response_id, .. }) = out_rx.recv().await else {
+                panic!("expected response.cancel");
+            };
+            assert_eq!(response_id.as_deref(), Some("resp_1"));
+            response.status = crate::protocol::models::ResponseStatus::Cancelled;
+            event_tx
+                .send(ServerEvent::ResponseDone {
+                    event_id: "evt_2".to_string(),
+                    response,
+                })
+                .await
+                .unwrap();
+        };
+        let (cancelled, ()) = tokio::join!(session.cancel_active_response(), server);
+        let cancelled = cancelled.unwrap().expect("active response");
+        assert_eq!(
+            cancelled.status,
+            crate::protocol::models::ResponseStatus::Cancelled
+        );
+        assert!(session.active_response_id().await.is_none());
+        assert!(session.cancel_active_response().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn cancel_waiter_fails_when_the_connection_closes() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: routing_response("resp_1", None),
+            })
+            .await
+            .unwrap();
+        let _ = session.next_voice_event().await.unwrap();
+
+        let server = async {
+            let Some(ClientEvent::ResponseCancel { .. }) = out_rx.recv().await else {
+                panic!("expected response.cancel");
+            };
+            drop(event_tx);
+        };
+        let (cancelled, ()) = tokio::join!(session.cancel_active_response(), server);
+        assert!(matches!(cancelled, Err(Error::ConnectionClosed)));
+    }
+
+    #[tokio::test]
+    async fn session_loop_exits_when_sender_closed() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        drop(session);
+
+        let closed = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .expect("session loop did not exit");
+        assert!(closed.is_none());
+    }
+
+    #[tokio::test]
+    async fn user_transcript_event_includes_content_index() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+
+        let evt = ServerEvent::InputAudioTranscriptionCompleted {
+            event_id: "evt_1".to_string(),
+            item_id: "item_1".to_string(),
+            content_index: 2,
+            transcript: "hello".to_string(),
+            logprobs: None,
+            usage: None,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let voice = session
+            .next_voice_event()
+            .await
+            .unwrap()
+            .expect("voice event");
+        match voice {
+            VoiceEvent::UserTranscriptDone {
+                item_id,
+                content_index,
+                transcript,
+            } => {
+                assert_eq!(item_id, "item_1");
+                assert_eq!(content_index, 2);
+                assert_eq!(transcript, "hello");
+            }
+            other => panic!("unexpected voice event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_reprompts_with_instructions() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions {
+                idle_timeout: IdleTimeoutPolicy {
+                    reprompt_instructions: Some("Ask if they are still there.".to_string()),
+                    close_after: None,
+                },
+                ..SessionOptions::default()
+            },
+        );
+
+        let evt = ServerEvent::InputAudioBufferTimeoutTriggered {
+            event_id: "evt_1".to_string(),
+            item_id: "item_1".to_string(),
+            audio_start_ms: 1000,
+            audio_end_ms: 6000,
+        };
+        event_tx.send(evt).await.unwrap();
+
+        let voice = session
+            .next_voice_event()
+            .await
+            .unwrap()
+            .expect("voice event");
+        assert!(matches!(
+            voice,
+            VoiceEvent::IdleTimeout { consecutive: 1, .. }
+        ));
+
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match sent {
+            ClientEvent::ResponseCreate { response, .. } => {
+                let response = response.expect("response config");
+                assert_eq!(
+                    response.instructions.as_deref(),
+                    Some("Ask if they are still there.")
+                );
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_transcription_recommits_audio() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                transcription_failure: TranscriptionFailurePolicy::Recommit { max_attempts: 1 },
+                ..SessionOptions::default()
+            },
+        );
+        let mut next_sent = async || {
+            tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        session.send_audio_bytes(&[1, 2, 3, 4]).await.unwrap();
+        let ClientEvent::InputAudioBufferAppend { audio, .. } = next_sent().await else {
+            panic!("expected audio append");
+        };
+        assert!(matches!(
+            next_sent().await,
+            ClientEvent::InputAudioBufferCommit { .. }
+        ));
+        event_tx
+            .send(ServerEvent::InputAudioBufferCommitted {
+                event_id: "evt_1".to_string(),
+                previous_item_id: None,
+                item_id: "item_1".to_string(),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::InputAudioTranscriptionFailed {
+                event_id: "evt_2".to_string(),
+                item_id: "item_1".to_string(),
+                content_index: 0,
+                error: crate::error::ServerError {
+                    error_type: crate::error::ApiErrorType::ServerError,
+                    code: None,
+                    message: "Transcription failed".to_string(),
+                    param: None,
+                    event_id: None,
+                },
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            next_sent().await,
+            ClientEvent::ConversationItemDelete { item_id, .. } if item_id == "item_1"
+        ));
+        assert!(matches!(
+            next_sent().await,
+            ClientEvent::InputAudioBufferAppend { audio: resent, .. } if resent == audio
+        ));
+        assert!(matches!(
+            next_sent().await,
+            ClientEvent::InputAudioBufferCommit { .. }
+        ));
+        loop {
+            if let SdkEvent::InputTranscriptionFailed { item_id, .. } =
+                session.next_event().await.unwrap().expect("event")
+            {
+                assert_eq!(item_id, "item_1");
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn scheduled_responses_fire_unless_user_speaks() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let prompt = |text: &str| ResponseConfig {
+            instructions: Some(text.to_string()),
+            ..ResponseConfig::default()
+        };
+
+        let first = session
+            .respond_after(Duration::from_millis(20), prompt("Are you still there?"))
+            .await
+            .unwrap();
+        let sent = tokio::time::timeout(Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match sent {
+            ClientEvent::ResponseCreate { response, .. } => assert_eq!(
+                response.expect("response config").instructions.as_deref(),
+                Some("Are you still there?")
+            ),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(!first.cancel().await.unwrap());
+
+        let interrupted = session
+            .respond_after(Duration::from_millis(50), prompt("interrupted"))
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::InputAudioBufferSpeechStarted {
+                event_id: "evt_1".to_string(),
+                audio_start_ms: 0,
+                item_id: "item_1".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            session.next_voice_event().await.unwrap(),
+            Some(VoiceEvent::SpeechStarted { .. })
+        ));
+        assert!(!interrupted.cancel().await.unwrap());
+
+        let cancelled = session
+            .respond_after(Duration::from_millis(50), prompt("cancelled"))
+            .await
+            .unwrap();
+        assert!(cancelled.cancel().await.unwrap());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(150), out_rx.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    async fn next_sent(out_rx: &mut mpsc::Receiver<ClientEvent>) -> ClientEvent {
+        tokio::time::timeout(Duration::from_secs(1), out_rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deadline_cancels_says_goodbye_and_closes() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let response = |id: &str, metadata: serde_json::Value| -> Response {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "object": "realtime.response",
+                "status": "completed",
+                "metadata": metadata,
+            }))
+            .unwrap()
+        };
+
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: response("resp_1", serde_json::Value::Null),
+            })
+            .await
+            .unwrap();
+        session
+            .with_deadline(
+                Deadline::new(tokio::time::Instant::now() + Duration::from_secs(300))
+                    .closing_message("We're out of time, goodbye!"),
+            )
+            .await
+            .unwrap();
+        // The deadline follows tokio's paused clock.
+        tokio::time::advance(Duration::from_secs(300)).await;
+
+        assert!(matches!(
+            next_sent(&mut out_rx).await,
+            ClientEvent::OutputAudioBufferClear { .. }
+        ));
+        match next_sent(&mut out_rx).await {
+            ClientEvent::ResponseCancel { response_id, .. } => {
+                assert_eq!(response_id.as_deref(), Some("resp_1"));
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        // The goodbye waits for the cancelled response to finish.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), out_rx.recv())
+                .await
+                .is_err()
+        );
+
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_2".to_string(),
+                response: response("resp_1", serde_json::Value::Null),
+            })
+            .await
+            .unwrap();
+        let closing = match next_sent(&mut out_rx).await {
+            ClientEvent::ResponseCreate { response, .. } => response.expect("response config"),
+            other => panic!("unexpected event: {other:?}"),
+        };
+        assert!(
+            closing
+                .instructions
+                .as_deref()
+                .is_some_and(|text| text.contains("We're out of time, goodbye!"))
+        );
+        assert_eq!(session.closed_reason(), None);
+
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_3".to_string(),
+                response: response("resp_2", serde_json::to_value(closing.metadata).unwrap()),
+            })
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), session.handle().await_closed())
+            .await
+            .unwrap();
+        assert_eq!(session.closed_reason(), Some(CloseReason::DeadlineExceeded));
+    }
+
+    #[tokio::test]
+    async fn cancellation_fails_ask_and_closes_session() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let token = CancellationToken::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                cancellation: token.clone(),
+                ..SessionOptions::default()
+            },
+        );
+
+        let cancel = tokio::spawn(async move {
+            // The message and response request go out before the answer is awaited.
+            assert!(matches!(
+                next_sent(&mut out_rx).await,
+                ClientEvent::ConversationItemCreate { .. }
+            ));
+            assert!(matches!(
+                next_sent(&mut out_rx).await,
+                ClientEvent::ResponseCreate { .. }
+            ));
+            token.cancel();
+        });
+        assert!(matches!(session.ask("hello").await, Err(Error::Cancelled)));
+        cancel.await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), session.handle().await_closed())
+            .await
+            .unwrap();
+        assert_eq!(session.closed_reason(), Some(CloseReason::Cancelled));
+        assert!(matches!(
+            session.wait_ready(Duration::from_secs(1)).await,
+            Err(Error::Cancelled)
+        ));
+    }
+
+    #[tokio::test]
+    async fn operation_tokens_cancel_one_call_and_leave_the_session_open() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut tools = ToolRegistry::new();
+        tools.tool_with_context("wait", |ctx, _: serde_json::Value| async move {
+            ctx.cancellation().cancelled().await;
+            Ok(serde_json::json!({ "cancelled": true }))
+        });
+        let session_token = CancellationToken::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions {
+                cancellation: session_token.clone(),
+                request_timeout: Duration::from_secs(30),
+                ..SessionOptions::default()
+            },
+        );
+
+        let token = session_token.child_token();
+        let cancel = token.clone();
+        let ask = tokio::spawn(async move {
+            assert!(matches!(
+                next_sent(&mut out_rx).await,
+                ClientEvent::ConversationItemCreate { .. }
+            ));
+            assert!(matches!(
+                next_sent(&mut out_rx).await,
+                ClientEvent::ResponseCreate { .. }
+            ));
+            cancel.cancel();
+            out_rx
+        });
+        assert!(matches!(
+            session.ask_cancellable("hello", &token).await,
+            Err(Error::Cancelled)
+        ));
+        let mut out_rx = ask.await.unwrap();
+        assert!(session.handle().is_alive());
+
+        // The tool's own token is a child of the call's, so the tool sees the cancel.
+        let call = ToolCall {
+            name: "wait".to_string(),
+            call_id: "call_1".to_string(),
+            arguments: serde_json::json!({}),
+            response_id: None,
+            item_id: None,
+            output_index: None,
+        };
+        let token = CancellationToken::new();
+        token.cancel();
+        assert!(matches!(
+            session.run_tool_cancellable(call, &token).await,
+            Err(Error::Cancelled)
+        ));
+        assert!(session.handle().is_alive());
+
+        // Calls awaiting a server reply give up when the session's token fires.
+        let classify = session.classify::<serde_json::Value>("hi", "Classify.");
+        let cancel = async {
+            assert!(matches!(
+                next_sent(&mut out_rx).await,
+                ClientEvent::ResponseCreate { .. }
+            ));
+            session_token.cancel();
+        };
+        let (classified, ()) = tokio::join!(classify, cancel);
+        assert!(matches!(classified, Err(Error::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn paused_outbound_events_are_held_then_sent_in_order() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                channel_capacities: ChannelCapacities {
+                    paused_outbound: 2,
+                    ..ChannelCapacities::default()
+                },
+                ..SessionOptions::default()
+            },
+        );
+        let handle = session.handle();
+        let commit = |id: &str| ClientEvent::InputAudioBufferCommit {
+            event_id: Some(id.to_string()),
+        };
+
+        handle.pause_outbound();
+        assert!(handle.is_outbound_paused());
+        session.send_raw(commit("evt_1")).await.unwrap();
+        session.send_raw(commit("evt_2")).await.unwrap();
+        let err = session.send_raw(commit("evt_3")).await.unwrap_err();
+        assert!(matches!(err, Error::OutboundQueueFull(2)));
+        assert_eq!(handle.queued_outbound(), 2);
+        assert!(out_rx.try_recv().is_err());
+
+        handle.resume_outbound();
+        for id in ["evt_1", "evt_2"] {
+            assert_eq!(next_sent(&mut out_rx).await.event_id(), Some(id));
+        }
+        session.send_raw(commit("evt_4")).await.unwrap();
+        assert_eq!(next_sent(&mut out_rx).await.event_id(), Some("evt_4"));
+        assert_eq!(handle.queued_outbound(), 0);
+    }
+
+    #[tokio::test]
+    async fn event_overflow_closes_session() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                channel_capacities: ChannelCapacities {
+                    events: 1,
+                    ..ChannelCapacities::default()
+                },
+                overflow_policy: OverflowPolicy::CloseSession,
+                ..SessionOptions::default()
+            },
+        );
+
+        for n in 0..3 {
+            event_tx
+                .send(ServerEvent::ConversationItemDeleted {
+                    event_id: format!("evt_{n}"),
+                    item_id: format!("item_{n}"),
+                })
+                .await
+                .unwrap();
+        }
+        session.handle().await_closed().await;
+
+        assert!(matches!(
+            session.next_event().await,
+            Ok(Some(SdkEvent::Raw(_)))
+        ));
+        assert!(matches!(
+            session.next_event().await,
+            Err(Error::ChannelOverflow("events"))
+        ));
+        assert_eq!(session.dropped_messages(), DroppedMessages::default());
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_closes_after_limit() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions {
+                idle_timeout: IdleTimeoutPolicy {
+                    reprompt_instructions: None,
+                    close_after: Some(2),
+                },
+                ..SessionOptions::default()
+            },
+        );
+
+        for id in ["evt_1", "evt_2"] {
+            event_tx
+                .send(ServerEvent::InputAudioBufferTimeoutTriggered {
+                    event_id: id.to_string(),
+                    item_id: "item_1".to_string(),
+                    audio_start_ms: 0,
+                    audio_end_ms: 5000,
+                })
+                .await
+                .unwrap();
+        }
+
+        let first = session.next_voice_event().await.unwrap();
+        assert!(matches!(
+            first,
+            Some(VoiceEvent::IdleTimeout { consecutive: 1, .. })
+        ));
+        let second = session.next_voice_event().await.unwrap();
+        assert!(matches!(
+            second,
+            Some(VoiceEvent::IdleTimeout { consecutive: 2, .. })
+        ));
+        let closed = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            session.next_voice_event(),
+        )
+        .await
+        .expect("session loop did not exit")
+        .unwrap();
+        assert!(closed.is_none());
+    }
+
+    #[tokio::test]
+    async fn control_commands_use_priority_lane() {
+        let (control_tx, mut control_rx) = mpsc::channel(4);
+        let (bulk_tx, mut bulk_rx) = mpsc::channel(4);
+        let sender = CommandSender {
+            control: control_tx,
+            bulk: bulk_tx,
+            audio: AudioLeases::default(),
+        };
+
+        let (tx, _rx) = oneshot::channel();
+        sender
+            .send(Command::SendWithResponse {
+                event: ClientEvent::InputAudioBufferAppend {
+                    event_id: None,
+                    audio: "AAAA".to_string(),
+                },
+                respond: tx,
+            })
+            .await
+            .unwrap();
+        let (tx, _rx) = oneshot::channel();
+        sender
+            .send(Command::SendWithResponse {
+                event: ClientEvent::ResponseCancel {
+                    event_id: None,
+                    response_id: None,
+                },
+                respond: tx,
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            control_rx.try_recv(),
+            Ok(Command::SendWithResponse {
+                event: ClientEvent::ResponseCancel { .. },
+                ..
+            })
+        ));
+        assert!(matches!(
+            bulk_rx.try_recv(),
+            Ok(Command::SendWithResponse {
+                event: ClientEvent::InputAudioBufferAppend { .. },
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn session_updated_tracks_server_session() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let tools = ToolRegistry::new();
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(tools),
+            SessionOptions::default(),
+        );
+        assert!(session.server_session().await.is_none());
+
+        let mut config = SessionConfig::new(
+            crate::protocol::models::SessionKind::Realtime,
+            "gpt-realtime",
+            crate::protocol::models::OutputModalities::Audio,
+        );
+        config.instructions = Some("Be brief.".to_string());
+        let accepted = crate::protocol::models::Session {
+            id: "sess_1".to_string(),
+            object: "realtime.session".to_string(),
+            expires_at: 0,
+            config,
+        };
+        event_tx
+            .send(ServerEvent::SessionUpdated {
+                event_id: "evt_1".to_string(),
+                session: accepted,
+            })
+            .await
+            .unwrap();
+
+        match session.next_event().await.unwrap().expect("sdk event") {
+            SdkEvent::SessionUpdated { session } => assert_eq!(session.id, "sess_1"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        let stored = session.server_session().await.expect("server session");
+        assert_eq!(stored.config.instructions.as_deref(), Some("Be brief."));
+    }
+
+    #[tokio::test]
+    async fn wait_ready_resolves_after_session_updated() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        let server_session = |id: &str| crate::protocol::models::Session {
+            id: id.to_string(),
+            object: "realtime.session".to_string(),
+            expires_at: 0,
+            config: SessionConfig::new(
+                crate::protocol::models::SessionKind::Realtime,
+                "gpt-realtime",
+                crate::protocol::models::OutputModalities::Audio,
+            ),
+        };
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_1".to_string(),
+                session: server_session("sess_created"),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::SessionUpdated {
+                event_id: "evt_2".to_string(),
+                session: server_session("sess_updated"),
+            })
+            .await
+            .unwrap();
+
+        let ready = session
+            .wait_ready(Duration::from_secs(1))
+            .await
+            .expect("ready");
+        assert_eq!(ready.id, "sess_updated");
+        assert_eq!(session.state(), SessionState::Active);
+        assert_eq!(session.id().as_deref(), Some("sess_updated"));
+        assert_eq!(session.model().as_deref(), Some("gpt-realtime"));
+        assert_eq!(session.call_id(), None);
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_creates_replay_once_on_a_new_session() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let options = SessionOptions {
+            resend: Some(ResendQueue::default()),
+            ..SessionOptions::default()
+        };
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            options,
+        );
+        session.say("first").await.unwrap();
+        session.say("second").await.unwrap();
+        session.audio_in_append_bytes(&[0; 4]).await.unwrap();
+        for _ in 0..3 {
+            out_rx.recv().await.unwrap();
+        }
+
+        let queued = session.unacknowledged_events();
+        assert_eq!(queued.len(), 2);
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = queued.first() else {
+            panic!("expected an item create");
+        };
+        event_tx
+            .send(ServerEvent::ConversationItemAdded {
+                event_id: "evt_1".to_string(),
+                previous_item_id: None,
+                item: (**item).clone(),
+            })
+            .await
+            .unwrap();
+        // The queue drops the create before the event is passed on.
+        assert!(matches!(
+            session.next_event().await.unwrap(),
+            Some(SdkEvent::Raw(event)) if matches!(*event, ServerEvent::ConversationItemAdded { .. })
+        ));
+        let left = session.unacknowledged_events();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].event_id(), queued[1].event_id());
+
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let resumed = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        assert_eq!(resumed.replay(left.clone()).await.unwrap(), 1);
+        assert_eq!(resumed.replay(left.clone()).await.unwrap(), 0);
+        let replayed = out_rx.recv().await.unwrap();
+        assert_eq!(replayed.event_id(), left[0].event_id());
+        assert!(out_rx.try_recv().is_err());
+
+        // Replayed audio waits its turn like any other append.
+        let _lease = resumed.handle().lease_audio_input().await;
+        let append = ClientEvent::InputAudioBufferAppend {
+            event_id: None,
+            audio: String::new(),
+        };
+        assert!(matches!(
+            resumed.replay(vec![append]).await,
+            Err(Error::AudioInputLeased)
+        ));
+    }
+
+    #[tokio::test]
+    async fn effective_config_is_the_last_reported_session() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        assert!(session.effective_config().await.is_none());
+
+        let server_session =
+            |instructions: &str, tools: Option<Vec<crate::protocol::models::Tool>>| {
+                let mut config = SessionConfig::new(
+                    crate::protocol::models::SessionKind::Realtime,
+                    "gpt-realtime",
+                    crate::protocol::models::OutputModalities::Audio,
+                );
+                config.instructions = Some(instructions.to_string());
+                config.tools = tools;
+                crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at: 0,
+                    config,
+                }
+            };
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_1".to_string(),
+                session: server_session("Be brief.", Some(Vec::new())),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::SessionUpdated {
+                event_id: "evt_2".to_string(),
+                session: server_session("Be thorough.", None),
+            })
+            .await
+            .unwrap();
+        session.wait_ready(Duration::from_secs(1)).await.unwrap();
+
+        let config = session.effective_config().await.expect("effective config");
+        assert_eq!(config.instructions.as_deref(), Some("Be thorough."));
+        assert_eq!(config.tools, None);
+    }
+
+    #[tokio::test]
+    async fn call_lifecycle_follows_answer_and_remote_hangup() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                call_id: Some("rtc_1".to_string()),
+                ..SessionOptions::default()
+            },
+        );
+        assert_eq!(session.call_id().as_deref(), Some("rtc_1"));
+        assert_eq!(
+            session.next_call_event().await,
+            Some(CallLifecycle::Ringing)
+        );
+
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_1".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at: 0,
+                    config: SessionConfig::new(
+                        crate::protocol::models::SessionKind::Realtime,
+                        "gpt-realtime",
+                        crate::protocol::models::OutputModalities::Audio,
+                    ),
+                },
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            session.next_call_event().await,
+            Some(CallLifecycle::Answered)
+        );
+
+        // The server closing the connection cleanly is the caller hanging up.
+        drop(event_tx);
+        assert_eq!(
+            session.next_call_event().await,
+            Some(CallLifecycle::RemoteHangup)
+        );
+        assert_eq!(session.next_call_event().await, None);
+        assert_eq!(session.call_state(), Some(CallLifecycle::RemoteHangup));
+
+        let plain = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: mpsc::channel(1).1,
+                outgoing: mpsc::channel(1).0,
+            }),
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        assert_eq!(plain.call_state(), None);
+    }
+
+    #[tokio::test]
+    async fn wait_ready_surfaces_server_error() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        event_tx
+            .send(ServerEvent::Error {
+                event_id: "evt_1".to_string(),
+                error: crate::error::ServerError {
+                    error_type: crate::error::ApiErrorType::InvalidRequestError,
+                    code: Some("invalid_value".to_string()),
+                    message: "Invalid voice".to_string(),
+                    param: Some("session.audio.output.voice".to_string()),
+                    event_id: None,
+                },
+            })
+            .await
+            .unwrap();
+
+        match session.wait_ready(Duration::from_secs(1)).await {
+            Err(Error::Api(error)) => assert_eq!(error.message, "Invalid voice"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_reports_event_loop_exit() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let handle = session.handle();
+        assert!(handle.is_alive());
+        assert_eq!(handle.state(), SessionState::Connecting);
+
+        drop(event_tx);
+        tokio::time::timeout(Duration::from_secs(1), handle.await_closed())
+            .await
+            .expect("event loop exits when the transport closes");
+        assert!(!handle.is_alive());
+        assert_eq!(
+            handle.state(),
+            SessionState::Closed {
+                reason: CloseReason::ServerClosed(None)
+            }
+        );
+        assert!(matches!(
+            handle.say("hi").await,
+            Err(Error::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn wait_ready_times_out_without_ack() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        assert!(matches!(
+            session.wait_ready(Duration::from_millis(20)).await,
+            Err(Error::Timeout(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn server_error_is_correlated_with_offending_send() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        session.say("hello").await.unwrap();
+        let sent = out_rx.recv().await.expect("item create");
+        assert_eq!(sent.event_type(), "conversation.item.create");
+        let sent_id = sent.event_id().expect("assigned event id").to_string();
+
+        event_tx
+            .send(ServerEvent::Error {
+                event_id: "evt_err".to_string(),
+                error: crate::error::ServerError {
+                    error_type: crate::error::ApiErrorType::InvalidRequestError,
+                    code: None,
+                    message: "Invalid item".to_string(),
+                    param: None,
+                    event_id: Some(sent_id.clone()),
+                },
+            })
+            .await
+            .unwrap();
+
+        let mut rejected = None;
+        while let Some(evt) = session.next_event().await.unwrap() {
+            if let SdkEvent::ClientEventRejected { event, error } = evt {
+                rejected = Some((event, error));
+                break;
+            }
+        }
+        let (event, error) = rejected.expect("rejection event");
+        assert_eq!(event.event_id, sent_id);
+        assert_eq!(event.event_type, "conversation.item.create");
+        assert!(event.summary.contains("hello"));
+        assert_eq!(error.message, "Invalid item");
+    }
+
+    #[tokio::test]
+    async fn classify_runs_out_of_band_and_decodes_output() {
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Intent {
+            Billing,
+            Support,
+        }
+
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let session = Arc::new(Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        ));
+        let classifier = Arc::clone(&session);
+        let task = tokio::spawn(async move {
+            classifier
+                .classify::<Intent>("I was charged twice", "Reply with the intent label.")
+                .await
+        });
+
+        let Some(ClientEvent::ResponseCreate {
+            response: Some(config),
+            ..
+        }) = out_rx.recv().await
+        else {
+            panic!("expected response.create");
+        };
+        assert!(matches!(
+            config.conversation,
+            Some(crate::protocol::models::ConversationMode::None)
+        ));
+        let metadata = config.metadata.expect("oob metadata");
+
+        let mut response = crate::protocol::models::Response {
+            id: "resp_oob".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: Some(metadata),
+            usage: None,
+        };
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: response.clone(),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::ResponseOutputTextDone {
+                event_id: "evt_2".to_string(),
+                response_id: "resp_oob".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                text: "billing".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(!session.is_responding().await);
+
+        response.status = crate::protocol::models::ResponseStatus::Completed;
+        response.output = Some(vec![Item::Message {
+            id: Some("item_1".to_string()),
+            status: Some(ItemStatus::Completed),
+            role: crate::protocol::models::Role::Assistant,
+            content: vec![ContentPart::OutputText {
+                text: "billing".to_string(),
+            }],
+        }]);
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_3".to_string(),
+                response,
+            })
+            .await
+            .unwrap();
+
+        let intent = task.await.unwrap().expect("classification");
+        assert_eq!(intent, Intent::Billing);
+    }
+
+    #[tokio::test]
+    async fn waiters_fail_when_the_connection_closes() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        let (classified, retrieved, ()) = tokio::join!(
+            session.classify::<String>("hello", "Reply with the intent label."),
+            session.retrieve_item("item_1"),
+            async {
+                next_sent(&mut out_rx).await;
+                next_sent(&mut out_rx).await;
+                drop(event_tx);
+            },
+        );
+        assert!(matches!(classified, Err(Error::ConnectionClosed)));
+        assert!(matches!(retrieved, Err(Error::ConnectionClosed)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unanswered_out_of_band_response_times_out() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                request_timeout: Duration::from_secs(5),
+                ..SessionOptions::default()
+            },
+        );
+
+        let err = session
+            .classify::<String>("hello", "Reply with the intent label.")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)));
+        assert!(session.pending.lock().await.responses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fork_responds_out_of_band_over_a_copy_of_the_conversation() {
+        use crate::protocol::models::{InputItem, ResponseStatus, Role};
+
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        event_tx
+            .send(ServerEvent::ConversationItemAdded {
+                event_id: "evt_1".to_string(),
+                previous_item_id: None,
+                item: Item::Message {
+                    id: Some("item_1".to_string()),
+                    status: None,
+                    role: Role::User,
+                    content: vec![ContentPart::InputText {
+                        text: "I'd like a refund.".to_string(),
+                    }],
+                },
+            })
+            .await
+            .unwrap();
+        // The item reaches the conversation before its event is passed on.
+        assert!(matches!(
+            session.next_event().await.unwrap(),
+            Some(SdkEvent::Raw(event)) if matches!(*event, ServerEvent::ConversationItemAdded { .. })
+        ));
+
+        let server_tx = event_tx.clone();
+        let server = tokio::spawn(async move {
+            let Some(ClientEvent::ResponseCreate {
+                response: Some(config),
+                ..
+            }) = out_rx.recv().await
+            else {
+                panic!("expected response.create");
+            };
+            let response = crate::protocol::models::Response {
+                id: "resp_fork".to_string(),
+                object: "response".to_string(),
+                conversation_id: None,
+                status: ResponseStatus::Completed,
+                status_details: None,
+                output: Some(vec![Item::Message {
+                    id: Some("item_out".to_string()),
+                    status: Some(ItemStatus::Completed),
+                    role: Role::Assistant,
+                    content: vec![ContentPart::OutputText {
+                        text: "Sorry, no refunds.".to_string(),
+                    }],
+                }]),
+                output_modalities: None,
+                max_output_tokens: None,
+                audio: None,
+                metadata: config.metadata.clone(),
+                usage: None,
+            };
+            server_tx
+                .send(ServerEvent::ResponseDone {
+                    event_id: "evt_2".to_string(),
+                    response,
+                })
+                .await
+                .unwrap();
+            config
+        });
+
+        let mut fork = session.fork().await.unwrap();
+        fork.say("Even for a damaged item?");
+        let response = fork.respond_with("Be strict.").await.unwrap();
+        assert_eq!(response.text().as_deref(), Some("Sorry, no refunds."));
+
+        let config = server.await.unwrap();
+        assert!(matches!(
+            config.conversation,
+            Some(crate::protocol::models::ConversationMode::None)
+        ));
+        assert_eq!(config.instructions.as_deref(), Some("Be strict."));
+        let input = config.input.unwrap();
+        assert!(matches!(&input[0], InputItem::ItemReference { id } if id == "item_1"));
+        assert!(matches!(
+            &input[1],
+            InputItem::Message {
+                role: Role::User,
+                ..
+            }
+        ));
+        assert_eq!(input.len(), 2);
+        // The reply extends the branch only.
+        assert_eq!(fork.input().len(), 3);
+        assert_eq!(session.conversation().await.unwrap().len(), 1);
+    }
+
+    fn routing_response(id: &str, purpose: Option<&str>) -> Response {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "object": "realtime.response",
+            "status": "completed",
+            "metadata": purpose.map(|purpose| serde_json::json!({ "purpose": purpose })),
+        }))
+        .unwrap()
+    }
+
+    fn routing_text_delta(response_id: &str, delta: &str) -> ServerEvent {
+        ServerEvent::ResponseOutputTextDelta {
+            event_id: format!("evt_{response_id}_{delta}"),
+            response_id: response_id.to_string(),
+            item_id: format!("item_{response_id}"),
+            output_index: 0,
+            content_index: 0,
+            delta: delta.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn routed_responses_stay_off_the_main_event_stream() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut route = session.route_responses("purpose", "guardrail");
+
+        // The two responses interleave on the wire.
+        let events = [
+            ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: routing_response("resp_bg", Some("guardrail")),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_2".to_string(),
+                response: routing_response("resp_main", None),
+            },
+            routing_text_delta("resp_bg", "safe"),
+            routing_text_delta("resp_main", "Hello"),
+            ServerEvent::ResponseDone {
+                event_id: "evt_3".to_string(),
+                response: routing_response("resp_bg", Some("guardrail")),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let mut routed = Vec::new();
+        while routed.len() < 3 {
+            routed.push(route.next_event().await.unwrap().unwrap());
+        }
+        assert!(
+            matches!(&routed[0], SdkEvent::Raw(evt) if matches!(**evt, ServerEvent::ResponseCreated { .. }))
+        );
+        assert!(matches!(&routed[1], SdkEvent::TextDelta { delta, .. } if delta == "safe"));
+        assert!(
+            matches!(&routed[2], SdkEvent::Raw(evt) if matches!(**evt, ServerEvent::ResponseDone { .. }))
+        );
+
+        // Ordering violations are reported on the main stream for every response.
+        loop {
+            let event = session.next_event().await.unwrap().unwrap();
+            if !matches!(event, SdkEvent::OrderingViolation(_)) {
+                assert!(!format!("{event:?}").contains("resp_bg"), "{event:?}");
+            }
+            if matches!(event, SdkEvent::TextDelta { .. }) {
+                break;
+            }
+        }
+        assert_eq!(
+            session.active_response_id().await.as_deref(),
+            Some("resp_main")
+        );
+
+        drop(session);
+        drop(event_tx);
+        assert!(route.next_event().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn dropped_route_is_reported_as_closed_consumer() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut route = session.route_responses("purpose", "guardrail");
+
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: routing_response("resp_bg", Some("guardrail")),
+            })
+            .await
+            .unwrap();
+        // The response is routed once its creation reaches the route.
+        route.next_event().await.unwrap().unwrap();
+        drop(route);
+        event_tx
+            .send(routing_text_delta("resp_bg", "safe"))
+            .await
+            .unwrap();
+
+        let event = loop {
+            let event = session.next_event().await.unwrap().unwrap();
+            if !matches!(event, SdkEvent::OrderingViolation(_)) {
+                break event;
+            }
+        };
+        assert!(matches!(event, SdkEvent::ConsumerClosed { channel } if channel == "route"));
+        assert_eq!(session.undelivered_messages(), 1);
+    }
+
+    #[tokio::test]
+    async fn transcripts_merge_user_and_assistant_speech() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut transcripts = session.transcripts();
+
+        let events = [
+            ServerEvent::InputAudioBufferSpeechStarted {
+                event_id: "evt_1".to_string(),
+                audio_start_ms: 100,
+                item_id: "item_user".to_string(),
+            },
+            ServerEvent::InputAudioBufferSpeechStopped {
+                event_id: "evt_2".to_string(),
+                audio_end_ms: 900,
+                item_id: "item_user".to_string(),
+            },
+            ServerEvent::ResponseOutputAudioTranscriptDelta {
+                event_id: "evt_3".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_reply".to_string(),
+                output_index: 0,
+                content_index: 0,
+                delta: "Hi".to_string(),
+            },
+            ServerEvent::InputAudioTranscriptionCompleted {
+                event_id: "evt_4".to_string(),
+                item_id: "item_user".to_string(),
+                content_index: 0,
+                transcript: "Hello".to_string(),
+                logprobs: None,
+                usage: None,
+            },
+            ServerEvent::ResponseOutputAudioTranscriptDone {
+                event_id: "evt_5".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_reply".to_string(),
+                output_index: 0,
+                content_index: 0,
+                transcript: "Hi there".to_string(),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let reply = transcripts.next_transcript().await.unwrap().unwrap();
+        assert_eq!(reply.speaker, Speaker::Assistant);
+        assert_eq!(reply.response_id.as_deref(), Some("resp_1"));
+        assert_eq!((reply.text.as_str(), reply.is_final), ("Hi", false));
+        let user = transcripts.next_transcript().await.unwrap().unwrap();
+        assert_eq!(user.speaker, Speaker::User);
+        assert_eq!(
+            (user.item_id.as_str(), user.text.as_str()),
+            ("item_user", "Hello")
+        );
+        assert!(user.is_final);
+        assert_eq!(
+            (user.audio_start_ms, user.audio_end_ms),
+            (Some(100), Some(900))
+        );
+        let done = transcripts.next_transcript().await.unwrap().unwrap();
+        assert_eq!((done.text.as_str(), done.is_final), ("Hi there", true));
+
+        drop(event_tx);
+        session.handle().await_closed().await;
+        assert!(transcripts.next_transcript().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn text_reader_reads_one_routed_response() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut route = session.route_responses("purpose", "summary");
+        // A response that started before the reader was opened ends after it.
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_0".to_string(),
+                response: routing_response("resp_old", Some("summary")),
+            })
+            .await
+            .unwrap();
+        assert!(route.next_event().await.unwrap().is_some());
+        let mut reader = route.text_reader();
+
+        let events = [
+            ServerEvent::ResponseDone {
+                event_id: "evt_old_done".to_string(),
+                response: routing_response("resp_old", Some("summary")),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: routing_response("resp_a", Some("summary")),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_2".to_string(),
+                response: routing_response("resp_b", Some("summary")),
+            },
+            routing_text_delta("resp_a", "Hello, "),
+            routing_text_delta("resp_b", "other"),
+            routing_text_delta("resp_a", "world"),
+            ServerEvent::ResponseDone {
+                event_id: "evt_3".to_string(),
+                response: routing_response("resp_a", Some("summary")),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        // A small buffer takes deltas over several reads.
+        let mut text = Vec::new();
+        loop {
+            let mut buf = [0; 4];
+            let mut read = tokio::io::ReadBuf::new(&mut buf);
+            std::future::poll_fn(|cx| {
+                tokio::io::AsyncRead::poll_read(std::pin::Pin::new(&mut reader), cx, &mut read)
+            })
+            .await
+            .unwrap();
+            if read.filled().is_empty() {
+                break;
+            }
+            text.extend_from_slice(read.filled());
+        }
+        assert_eq!(text, b"Hello, world");
+        assert_eq!(reader.response_id(), Some("resp_a"));
+        assert!(reader.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn pipes_write_one_routed_response_each() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut route = session.route_responses("purpose", "archive");
+
+        let mut events = Vec::new();
+        for id in ["resp_a", "resp_b"] {
+            events.push(ServerEvent::ResponseCreated {
+                event_id: format!("evt_{id}_created"),
+                response: routing_response(id, Some("archive")),
+            });
+            events.push(routing_text_delta(id, "Hello"));
+            events.push(ServerEvent::ResponseOutputAudioDelta {
+                event_id: format!("evt_{id}_audio"),
+                response_id: id.to_string(),
+                item_id: format!("item_{id}"),
+                output_index: 0,
+                content_index: 0,
+                delta: general_purpose::STANDARD.encode([1, 2, 3, 4]),
+            });
+            events.push(routing_text_delta(id, ", world"));
+            events.push(ServerEvent::ResponseDone {
+                event_id: format!("evt_{id}_done"),
+                response: routing_response(id, Some("archive")),
+            });
+        }
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let mut audio = Vec::new();
+        assert_eq!(route.pipe_audio(&mut audio).await.unwrap(), 4);
+        assert_eq!(audio, [1, 2, 3, 4]);
+        let mut text = Vec::new();
+        assert_eq!(route.pipe_text(&mut text).await.unwrap(), 12);
+        assert_eq!(text, b"Hello, world");
+    }
+
+    #[tokio::test]
+    async fn pipe_opened_while_a_response_finishes_writes_the_next() {
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut route = session.route_responses("purpose", "archive");
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_old_created".to_string(),
+                response: routing_response("resp_old", Some("archive")),
+            })
+            .await
+            .unwrap();
+        assert!(route.next_event().await.unwrap().is_some());
+
+        let events = [
+            ServerEvent::ResponseDone {
+                event_id: "evt_old_done".to_string(),
+                response: routing_response("resp_old", Some("archive")),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_a_created".to_string(),
+                response: routing_response("resp_a", Some("archive")),
+            },
+            routing_text_delta("resp_a", "Hello"),
+            ServerEvent::ResponseDone {
+                event_id: "evt_a_done".to_string(),
+                response: routing_response("resp_a", Some("archive")),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let mut text = Vec::new();
+        assert_eq!(route.pipe_text(&mut text).await.unwrap(), 5);
+        assert_eq!(text, b"Hello");
+    }
+
+    struct KeywordGuardrail;
+
+    #[async_trait::async_trait]
+    impl crate::sdk::Guardrail for KeywordGuardrail {
+        async fn check(
+            &self,
+            _stage: GuardrailStage,
+            text: &str,
+        ) -> Result<crate::sdk::GuardrailVerdict> {
+            use crate::sdk::GuardrailVerdict;
+            Ok(if text.contains("password") {
+                GuardrailVerdict::Block {
+                    reason: "credentials".to_string(),
+                }
+            } else if text.contains("555-0100") {
+                GuardrailVerdict::Redact(text.replace("555-0100", "[redacted]"))
+            } else {
+                GuardrailVerdict::Allow
+            })
+        }
+    }
+
+    fn guarded_options() -> SessionOptions {
+        let mut guardrails = Guardrails::new();
+        guardrails.push(KeywordGuardrail);
+        SessionOptions {
+            guardrails,
+            ..SessionOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn routed_audio_streams_next_to_the_main_response() {
+        fn audio_delta(response_id: &str, samples: &[i16]) -> ServerEvent {
+            let pcm: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+            ServerEvent::ResponseOutputAudioDelta {
+                event_id: format!("evt_{response_id}_audio"),
+                response_id: response_id.to_string(),
+                item_id: format!("item_{response_id}"),
+                output_index: 0,
+                content_index: 0,
+                delta: general_purpose::STANDARD.encode(pcm),
+            }
+        }
+        fn samples(chunk: &super::super::voice::AudioChunk) -> Vec<i16> {
+            chunk
+                .pcm
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect()
+        }
+
+        let (event_tx, event_rx) = mpsc::channel(16);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let mut session = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            }),
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut music = session.route_audio("purpose", "music", AudioRouteMode::Separate);
+        let mut chime = session.route_audio("purpose", "chime", AudioRouteMode::Mix);
+
+        let events = [
+            ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: routing_response("resp_main", None),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_2".to_string(),
+                response: routing_response("resp_music", Some("music")),
+            },
+            ServerEvent::ResponseCreated {
+                event_id: "evt_3".to_string(),
+                response: routing_response("resp_chime", Some("chime")),
+            },
+            audio_delta("resp_music", &[3, 3]),
+            audio_delta("resp_chime", &[2, 2]),
+            audio_delta("resp_main", &[1, 1, 1]),
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let music_chunk = music.next_chunk().await.unwrap().unwrap();
+        assert_eq!(music_chunk.response_id, "resp_music");
+        assert_eq!(samples(&music_chunk), vec![3, 3]);
+        let chime_chunk = chime.next_chunk().await.unwrap().unwrap();
+        assert_eq!(samples(&chime_chunk), vec![2, 2]);
+
+        // The main response kept the gate, with the chime mixed in and no music.
+        let main = session.next_audio_chunk().await.unwrap().unwrap();
+        assert_eq!(main.response_id, "resp_main");
+        assert_eq!(samples(&main), vec![3, 3, 1]);
+        assert_eq!(
+            session.active_response_id().await.as_deref(),
+            Some("resp_main")
+        );
+
+        drop(session);
+        drop(event_tx);
+        assert!(music.next_chunk().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn panicking_handler_is_reported_and_loop_survives() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let handlers = EventHandlers::new().on_text(|text: String| async move {
+            assert!(text != "boom", "handler exploded");
+            Ok(())
+        });
+        let mut session = Session::from_transport(
+            transport,
+            handlers,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+
+        let text_done = |text: &str| ServerEvent::ResponseOutputTextDone {
+            event_id: format!("evt_{text}"),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            text: text.to_string(),
+        };
+        event_tx.send(text_done("boom")).await.unwrap();
+        event_tx.send(text_done("fine")).await.unwrap();
+
+        let mut panicked = None;
+        while let Some(evt) = session.next_event().await.unwrap() {
+            match evt {
+                SdkEvent::HandlerPanicked { context, message } => {
+                    panicked = Some((context, message));
+                }
+                SdkEvent::TextDone { text, .. } if text == "fine" => break,
+                _ => {}
+            }
+        }
+        let (context, message) = panicked.expect("panic reported");
+        assert_eq!(context, "on_text");
+        assert_eq!(message, "handler exploded");
+        assert!(session.handle().is_alive());
+    }
+
+    #[tokio::test]
+    async fn slow_handlers_are_reported() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let handlers = EventHandlers::new().on_text(|text: String| async move {
+            if text == "slow" {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+            }
+            Ok(())
+        });
+        let mut session = Session::from_transport(
+            transport,
+            handlers,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                slow_handler: Some(Duration::from_millis(20)),
+                ..SessionOptions::default()
+            },
+        );
+
+        let text_done = |text: &str| ServerEvent::ResponseOutputTextDone {
+            event_id: format!("evt_{text}"),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            text: text.to_string(),
+        };
+        event_tx.send(text_done("slow")).await.unwrap();
+        event_tx.send(text_done("fast")).await.unwrap();
+
+        let mut slow = Vec::new();
+        while let Some(evt) = session.next_event().await.unwrap() {
+            match evt {
+                SdkEvent::SlowHandler { name, elapsed } => slow.push((name, elapsed)),
+                SdkEvent::TextDone { text, .. } if text == "fast" => break,
+                _ => {}
+            }
+        }
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].0, "on_text");
+        assert!(slow[0].1 >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn guardrail_blocks_and_redacts_user_input() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            guarded_options(),
+        );
+
+        let err = session.say("my password is hunter2").await.unwrap_err();
+        assert!(matches!(err, Error::GuardrailBlocked(reason) if reason == "credentials"));
+        assert!(matches!(
+            session.next_event().await.unwrap(),
+            Some(SdkEvent::GuardrailTriggered {
+                stage: GuardrailStage::UserInput,
+                ..
+            })
+        ));
+
+        session.handle().say("call me at 555-0100").await.unwrap();
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected conversation.item.create");
+        };
+        let Item::Message { content, .. } = *item else {
+            panic!("expected message item");
+        };
+        assert!(matches!(
+            content.as_slice(),
+            [ContentPart::InputText { text }] if text == "call me at [redacted]"
+        ));
+    }
+
+    /// Next event, skipping ordering diagnostics for fixtures that omit
+    /// `response.created` and `response.content_part.added`.
+    async fn next_reported(session: &mut Session) -> Option<SdkEvent> {
+        loop {
+            match session.next_event().await.unwrap()? {
+                SdkEvent::OrderingViolation(_) => {}
+                evt => return Some(evt),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn guardrail_redacts_and_blocks_assistant_text() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            guarded_options(),
+        );
+
+        let delta = |response_id: &str, delta: &str| ServerEvent::ResponseOutputTextDelta {
+            event_id: format!("evt_{delta}"),
+            response_id: response_id.to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta: delta.to_string(),
+        };
+        event_tx
+            .send(delta("resp_1", "dial 555-0100"))
+            .await
+            .unwrap();
+        event_tx
+            .send(delta("resp_1", "the password is"))
+            .await
+            .unwrap();
+        event_tx.send(delta("resp_1", "anything")).await.unwrap();
+
+        match next_reported(&mut session).await.expect("event") {
+            SdkEvent::GuardrailTriggered { .. } => {}
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match next_reported(&mut session).await.expect("event") {
+            SdkEvent::TextDelta { delta, .. } => assert_eq!(delta, "dial [redacted]"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        match next_reported(&mut session).await.expect("event") {
+            SdkEvent::GuardrailTriggered { verdict, .. } => assert!(matches!(
+                verdict,
+                crate::sdk::GuardrailVerdict::Block { .. }
+            )),
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        match out_rx.recv().await {
+            Some(ClientEvent::ResponseCancel { response_id, .. }) => {
+                assert_eq!(response_id.as_deref(), Some("resp_1"));
+            }
+            other => panic!("unexpected client event: {other:?}"),
+        }
+        drop(event_tx);
+        assert!(next_reported(&mut session).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn answering_machine_verdict_is_a_voice_event() {
+        struct FirstUtterance;
+
+        impl crate::sdk::AnsweringMachineDetector for FirstUtterance {
+            fn observe(
+                &mut self,
+                input: crate::sdk::AmdInput<'_>,
+            ) -> Option<crate::sdk::AmdVerdict> {
+                matches!(input, crate::sdk::AmdInput::SpeechStopped { audio_ms } if audio_ms > 3000)
+                    .then_some(crate::sdk::AmdVerdict::Machine)
+            }
+        }
+
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                answering_machine: Some(AnsweringMachineDetection::new(FirstUtterance)),
+                ..SessionOptions::default()
+            },
+        );
+
+        event_tx
+            .send(ServerEvent::InputAudioBufferSpeechStarted {
+                event_id: "evt_1".to_string(),
+                audio_start_ms: 100,
+                item_id: "item_1".to_string(),
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(ServerEvent::InputAudioBufferSpeechStopped {
+                event_id: "evt_2".to_string(),
+                audio_end_ms: 6400,
+                item_id: "item_1".to_string(),
+            })
+            .await
+            .unwrap();
+
+        loop {
+            if let VoiceEvent::AnsweringMachineDetected { verdict, audio_ms } =
+                session.next_voice_event().await.unwrap().unwrap()
+            {
+                assert_eq!(verdict, crate::sdk::AmdVerdict::Machine);
+                assert_eq!(audio_ms, 6400);
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn speakable_text_streams_clauses_and_flushes_on_done() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let mut session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                speakable: Some(SpeakableChunking::new()),
+                ..SessionOptions::default()
+            },
+        );
+
+        for (n, delta) in ["Sure, Mr. Jones", " will call you back", " shortly. Bye"]
+            .into_iter()
+            .enumerate()
+        {
+            event_tx
+                .send(ServerEvent::ResponseOutputTextDelta {
+                    event_id: format!("evt_{n}"),
+                    response_id: "resp_1".to_string(),
+                    item_id: "item_1".to_string(),
+                    output_index: 0,
+                    content_index: 0,
+                    delta: delta.to_string(),
+                })
+                .await
+                .unwrap();
+        }
+        event_tx
+            .send(ServerEvent::ResponseOutputTextDone {
+                event_id: "evt_done".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                text: "Sure, Mr. Jones will call you back shortly. Bye".to_string(),
+            })
+            .await
+            .unwrap();
+        drop(event_tx);
+
+        let chunks: Vec<SpeakableChunk> = session.speakable_text().collect().await;
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["Sure, Mr. Jones will call you back shortly.", "Bye"]
+        );
+        assert_eq!(chunks[1].index, 1);
+        assert_eq!(chunks[1].response_id, "resp_1");
+    }
 
     #[tokio::test]
-    async fn tool_call_sends_output() {
+    async fn local_audio_is_placed_around_and_mixed_into_model_audio() {
+        fn samples(chunk: &super::super::voice::AudioChunk) -> Vec<i16> {
+            chunk
+                .pcm
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect()
+        }
+
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
 
-        let mut tools = ToolRegistry::new();
-        tools.tool("echo", |args: serde_json::Value| async move { Ok(args) });
-
-        let session = Session::from_transport(
+        let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        let evt = ServerEvent::ResponseFunctionCallArgumentsDone {
-            event_id: "evt_1".to_string(),
-            response_id: "resp_1".to_string(),
-            item_id: "item_1".to_string(),
-            output_index: 0,
-            call_id: "call_1".to_string(),
-            name: "echo".to_string(),
-            arguments: r#"{"hello":"world"}"#.to_string(),
+        session
+            .play_local_audio(&[100, 100], LocalAudioPosition::BeforeNextResponse)
+            .await
+            .unwrap();
+        let mut response = crate::protocol::models::Response {
+            id: "resp_1".to_string(),
+            object: "response".to_string(),
+            conversation_id: None,
+            status: crate::protocol::models::ResponseStatus::InProgress,
+            status_details: None,
+            output: None,
+            output_modalities: None,
+            max_output_tokens: None,
+            audio: None,
+            metadata: None,
+            usage: None,
         };
-
-        event_tx.send(evt).await.unwrap();
-
-        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: response.clone(),
+            })
             .await
-            .unwrap()
             .unwrap();
+        let beep = session.next_audio_chunk().await.unwrap().expect("beep");
+        assert!(beep.local);
+        assert_eq!(samples(&beep), vec![100, 100]);
 
-        match sent {
-            ClientEvent::ConversationItemCreate { item, .. } => match *item {
-                Item::FunctionCallOutput {
-                    call_id, output, ..
-                } => {
-                    assert_eq!(call_id, "call_1");
-                    assert!(output.contains("hello"));
-                }
-                other => panic!("unexpected item: {other:?}"),
-            },
-            other => panic!("unexpected event: {other:?}"),
-        }
-
-        let follow_up = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+        session
+            .play_local_audio(&[5, 5], LocalAudioPosition::Mix)
             .await
-            .unwrap()
             .unwrap();
-        assert!(matches!(follow_up, ClientEvent::ResponseCreate { .. }));
+        session
+            .play_local_audio(&[7], LocalAudioPosition::AfterCurrentResponse)
+            .await
+            .unwrap();
+        let model_pcm: Vec<u8> = [1i16, 1, 1].iter().flat_map(|s| s.to_le_bytes()).collect();
+        event_tx
+            .send(ServerEvent::ResponseOutputAudioDelta {
+                event_id: "evt_2".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                delta: general_purpose::STANDARD.encode(model_pcm),
+            })
+            .await
+            .unwrap();
+        let mixed = session.next_audio_chunk().await.unwrap().expect("mixed");
+        assert!(!mixed.local);
+        assert_eq!(samples(&mixed), vec![6, 6, 1]);
 
-        drop(session);
+        response.status = crate::protocol::models::ResponseStatus::Completed;
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_3".to_string(),
+                response,
+            })
+            .await
+            .unwrap();
+        let tail = session.next_audio_chunk().await.unwrap().expect("tail");
+        assert!(tail.local);
+        assert_eq!(samples(&tail), vec![7]);
     }
 
     #[tokio::test]
-    async fn next_event_maps_sdk_event() {
+    async fn audio_chunks_carry_duration_and_item_offset() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, _out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
         let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        let evt = ServerEvent::ResponseOutputTextDelta {
-            event_id: "evt_1".to_string(),
-            response_id: "resp_1".to_string(),
-            item_id: "item_1".to_string(),
-            output_index: 0,
-            content_index: 0,
-            delta: "hello".to_string(),
-        };
-        event_tx.send(evt).await.unwrap();
+        event_tx
+            .send(ServerEvent::ResponseCreated {
+                event_id: "evt_1".to_string(),
+                response: crate::protocol::models::Response {
+                    id: "resp_1".to_string(),
+                    object: "response".to_string(),
+                    conversation_id: None,
+                    status: crate::protocol::models::ResponseStatus::InProgress,
+                    status_details: None,
+                    output: None,
+                    output_modalities: None,
+                    max_output_tokens: None,
+                    audio: None,
+                    metadata: None,
+                    usage: None,
+                },
+            })
+            .await
+            .unwrap();
+        // 48 bytes of 24kHz PCM16 is one millisecond.
+        for (n, item_id) in ["item_1", "item_1", "item_2"].into_iter().enumerate() {
+            event_tx
+                .send(ServerEvent::ResponseOutputAudioDelta {
+                    event_id: format!("evt_delta_{n}"),
+                    response_id: "resp_1".to_string(),
+                    item_id: item_id.to_string(),
+                    output_index: 0,
+                    content_index: 0,
+                    delta: general_purpose::STANDARD.encode([0u8; 48]),
+                })
+                .await
+                .unwrap();
+        }
 
-        let mapped = session.next_event().await.unwrap().expect("sdk event");
-        match mapped {
-            SdkEvent::TextDelta { delta, .. } => assert_eq!(delta, "hello"),
-            other => panic!("unexpected event: {other:?}"),
+        let mut offsets = Vec::new();
+        for _ in 0..3 {
+            let chunk = session.next_audio_chunk().await.unwrap().expect("chunk");
+            assert_eq!(chunk.duration, Duration::from_millis(1));
+            assert!(chunk.received_at <= std::time::SystemTime::now());
+            offsets.push(chunk.offset);
         }
+        assert_eq!(
+            offsets,
+            vec![Duration::ZERO, Duration::from_millis(1), Duration::ZERO]
+        );
     }
 
     #[tokio::test]
-    async fn event_stream_yields_sdk_event() {
+    async fn f32_audio_goes_in_as_pcm16_and_comes_out_as_samples() {
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
         let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        let evt = ServerEvent::ResponseOutputTextDone {
-            event_id: "evt_1".to_string(),
-            response_id: "resp_1".to_string(),
-            item_id: "item_1".to_string(),
-            output_index: 0,
-            content_index: 0,
-            text: "done".to_string(),
+        session.audio_in_append_f32(&[0.5, -2.0]).await.unwrap();
+        let Some(ClientEvent::InputAudioBufferAppend { audio, .. }) = out_rx.recv().await else {
+            panic!("expected input_audio_buffer.append");
         };
-        event_tx.send(evt).await.unwrap();
-
-        let mut stream = session.events();
-        let mapped = stream.next().await.expect("sdk event");
-        match mapped {
-            SdkEvent::TextDone { text, .. } => assert_eq!(text, "done"),
-            other => panic!("unexpected event: {other:?}"),
+        let expected: Vec<u8> = [16384i16, -32768]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        assert_eq!(general_purpose::STANDARD.decode(audio).unwrap(), expected);
+
+        session
+            .play_local_audio(&[16384, -32768], LocalAudioPosition::Now)
+            .await
+            .unwrap();
+        let local = session.next_audio_samples().await.unwrap().expect("chunk");
+        assert_eq!(local.samples, [0.5, -1.0]);
+        assert_eq!(local.chunk.duration, Duration::from_micros(83));
+
+        // Model audio is decoded in the session's output format.
+        let mut config = SessionConfig::new(
+            crate::protocol::models::SessionKind::Realtime,
+            "gpt-realtime",
+            crate::protocol::models::OutputModalities::Audio,
+        );
+        config.audio = Some(AudioConfig {
+            input: None,
+            output: Some(OutputAudioConfig {
+                format: Some(crate::protocol::models::AudioFormat::Pcmu),
+                ..OutputAudioConfig::default()
+            }),
+        });
+        let events = [
+            ServerEvent::SessionUpdated {
+                event_id: "evt_1".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at: 0,
+                    config,
+                },
+            },
+            ServerEvent::ResponseOutputAudioDelta {
+                event_id: "evt_2".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                content_index: 0,
+                delta: general_purpose::STANDARD.encode([0xff, 0x80, 0x00]),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
         }
+        let model = session.next_audio_samples().await.unwrap().expect("chunk");
+        assert_eq!(model.chunk.pcm, [0xff, 0x80, 0x00]);
+        assert_eq!(model.samples.len(), 3);
+        assert!(model.samples[0].abs() < 1e-6, "{:?}", model.samples);
+        assert!(model.samples[1] > 0.9 && model.samples[2] < -0.9);
     }
 
     #[tokio::test]
-    async fn send_response_emits_response_create() {
+    async fn observer_session_rejects_sends_and_skips_tools() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let session = Session::from_transport(
+        let mut tools = ToolRegistry::new();
+        tools.tool("echo", |args: serde_json::Value| async move { Ok(args) });
+        let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
             Arc::new(tools),
-            false,
-            true,
+            SessionOptions {
+                role: SessionRole::Observer,
+                ..SessionOptions::default()
+            },
         );
 
-        let config = crate::protocol::models::ResponseConfig {
-            instructions: Some("Respond.".to_string()),
-            ..Default::default()
-        };
-
-        session.send_response(config).await.unwrap();
+        let err = session.say("hello").await.unwrap_err();
+        assert!(matches!(err, Error::ReadOnly("conversation.item.create")));
 
-        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+        event_tx
+            .send(ServerEvent::ResponseFunctionCallArgumentsDone {
+                event_id: "evt_1".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                call_id: "call_1".to_string(),
+                name: "echo".to_string(),
+                arguments: "{}".to_string(),
+            })
             .await
-            .unwrap()
             .unwrap();
+        let evt = session.next_event().await.unwrap();
+        assert!(matches!(evt, Some(SdkEvent::ToolCall { call_id, .. }) if call_id == "call_1"));
+        assert!(out_rx.try_recv().is_err());
+    }
 
-        match sent {
-            ClientEvent::ResponseCreate { response, .. } => {
-                let response = response.expect("response config");
-                assert_eq!(response.instructions.as_deref(), Some("Respond."));
-            }
-            other => panic!("unexpected event: {other:?}"),
-        }
+    #[tokio::test]
+    async fn instruction_guards_roll_back_in_order() {
+        let (_event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut sent = move || {
+            let Ok(ClientEvent::SessionUpdate { session, .. }) = out_rx.try_recv() else {
+                return None;
+            };
+            session.config.instructions
+        };
 
-        drop(event_tx);
+        let calm = session.set_instructions("Be calm.").await.unwrap();
+        let escalate = session.set_instructions("Escalate.").await.unwrap();
+        assert_eq!(sent().as_deref(), Some("Be calm."));
+        assert_eq!(sent().as_deref(), Some("Escalate."));
+        assert_eq!(escalate.previous(), Some("Be calm."));
+
+        escalate.rollback().await.unwrap();
+        assert_eq!(sent().as_deref(), Some("Be calm."));
+        assert_eq!(session.instruction_history(), vec!["Be calm.".to_string()]);
+
+        let inner = session.set_instructions("Escalate.").await.unwrap();
+        sent();
+        calm.rollback().await.unwrap();
+        assert_eq!(sent().as_deref(), Some(""));
+        inner.rollback().await.unwrap();
+        assert_eq!(sent(), None);
+        assert!(session.instruction_history().is_empty());
     }
 
     #[tokio::test]
-    async fn approve_mcp_sends_item() {
+    async fn stale_instruction_guards_stay_no_ops_and_restore_the_session_default() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
         let session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
+        let mut sent = move || {
+            let Ok(ClientEvent::SessionUpdate { session, .. }) = out_rx.try_recv() else {
+                return None;
+            };
+            session.config.instructions
+        };
 
-        session.approve_mcp("req_1", Some("ok")).await.unwrap();
+        // Set before the server reported anything, so nothing earlier is known.
+        let first = session.set_instructions("A").await.unwrap();
+        let second = session.set_instructions("B").await.unwrap();
+        assert_eq!(first.previous(), None);
+        sent();
+        sent();
+
+        let mut server_session = crate::protocol::models::Session {
+            id: "sess_1".to_string(),
+            object: "realtime.session".to_string(),
+            expires_at: 0,
+            config: SessionConfig::new(
+                crate::protocol::models::SessionKind::Realtime,
+                "gpt-realtime",
+                crate::protocol::models::OutputModalities::Audio,
+            ),
+        };
+        server_session.config.instructions = Some("Be helpful.".to_string());
+        for (event_id, created) in [("evt_1", true), ("evt_2", false)] {
+            let event_id = event_id.to_string();
+            let session = server_session.clone();
+            let event = if created {
+                ServerEvent::SessionCreated { event_id, session }
+            } else {
+                ServerEvent::SessionUpdated { event_id, session }
+            };
+            event_tx.send(event).await.unwrap();
+        }
+        session.wait_ready(Duration::from_secs(1)).await.unwrap();
+
+        first.rollback().await.unwrap();
+        assert_eq!(sent().as_deref(), Some("Be helpful."));
+
+        session.set_instructions("C").await.unwrap();
+        session.set_instructions("D").await.unwrap();
+        sent();
+        sent();
+        second.rollback().await.unwrap();
+        assert_eq!(sent(), None);
+        assert_eq!(
+            session.instruction_history(),
+            vec!["C".to_string(), "D".to_string()]
+        );
+    }
 
-        let sent = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+    #[tokio::test]
+    async fn response_language_follows_instruction_changes() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
+        let mut config = SessionConfig::new(
+            crate::protocol::models::SessionKind::Realtime,
+            "gpt-realtime",
+            crate::protocol::models::OutputModalities::Audio,
+        );
+        config.instructions = Some("Be brief.".to_string());
+        config.audio = Some(AudioConfig {
+            input: Some(InputAudioConfig {
+                transcription: Some(Nullable::Value(InputAudioTranscription {
+                    model: Some("gpt-4o-transcribe".to_string()),
+                    ..InputAudioTranscription::default()
+                })),
+                ..InputAudioConfig::default()
+            }),
+            output: None,
+        });
+        event_tx
+            .send(ServerEvent::SessionUpdated {
+                event_id: "evt_1".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at: 0,
+                    config,
+                },
+            })
             .await
-            .unwrap()
             .unwrap();
+        session.wait_ready(Duration::from_secs(1)).await.unwrap();
+        let mut sent = move || {
+            let Ok(ClientEvent::SessionUpdate { session, .. }) = out_rx.try_recv() else {
+                panic!("expected session.update");
+            };
+            session.config
+        };
 
-        match sent {
-            ClientEvent::ConversationItemCreate { item, .. } => match *item {
-                Item::McpApprovalResponse {
-                    approval_request_id,
-                    approve,
-                    reason,
-                    ..
-                } => {
-                    assert_eq!(approval_request_id, "req_1");
-                    assert!(approve);
-                    assert_eq!(reason.as_deref(), Some("ok"));
-                }
-                other => panic!("unexpected item: {other:?}"),
-            },
-            other => panic!("unexpected event: {other:?}"),
-        }
+        session.set_response_language("es-MX").await.unwrap();
+        let update = sent();
+        assert_eq!(
+            update.instructions.as_deref(),
+            Some(
+                "Be brief.\n\nAlways respond in Spanish, even if the user speaks another language."
+            )
+        );
+        let transcription = update.audio.and_then(|audio| audio.input?.transcription);
+        assert_eq!(
+            transcription,
+            Some(Nullable::Value(InputAudioTranscription {
+                model: Some("gpt-4o-transcribe".to_string()),
+                language: Some("es".to_string()),
+                prompt: None,
+            }))
+        );
+        assert_eq!(session.response_language().as_deref(), Some("es-MX"));
 
-        drop(event_tx);
+        let guard = session.set_instructions("Be calm.").await.unwrap();
+        assert!(
+            sent()
+                .instructions
+                .unwrap()
+                .starts_with("Be calm.\n\nAlways respond in Spanish")
+        );
+        assert_eq!(guard.previous(), Some("Be brief."));
+
+        session.set_response_language("fr").await.unwrap();
+        assert!(
+            sent()
+                .instructions
+                .unwrap()
+                .ends_with("respond in French, even if the user speaks another language.")
+        );
+        session.clear_response_language().await.unwrap();
+        assert_eq!(sent().instructions.as_deref(), Some("Be calm."));
+        assert_eq!(session.response_language(), None);
     }
 
     #[tokio::test]
-    async fn ask_sends_and_returns_text() {
+    async fn switch_agent_swaps_tools_and_notes_handoff() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let mut session = Session::from_transport(
+        let session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                auto_tool_response: false,
+                ..SessionOptions::default()
+            },
+        );
+        let agent = |name: &str, tool: &str| {
+            let mut tools = ToolRegistry::new();
+            let reply = format!("{tool} ok");
+            tools.tool(tool, move |_: serde_json::Value| {
+                let reply = reply.clone();
+                async move { Ok(reply) }
+            });
+            Agent::new(name, format!("You are {name}.")).tools(tools)
+        };
+
+        agent("triage", "lookup").attach(&session).await.unwrap();
+        let Some(ClientEvent::SessionUpdate {
+            session: update, ..
+        }) = out_rx.recv().await
+        else {
+            panic!("expected session.update");
+        };
+        assert_eq!(
+            update.config.instructions.as_deref(),
+            Some("You are triage.")
         );
+        assert!(matches!(
+            update.config.tools.as_deref(),
+            Some([crate::protocol::models::Tool::Function { name, .. }]) if name == "lookup"
+        ));
 
-        let event_tx_clone = event_tx.clone();
-        let send_evt = async move {
-            let evt = ServerEvent::ResponseOutputTextDone {
+        session
+            .switch_agent(&agent("billing", "refund"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::SessionUpdate { .. })
+        ));
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected handoff note");
+        };
+        let Item::Message { role, content, .. } = *item else {
+            panic!("expected message");
+        };
+        assert_eq!(role, crate::protocol::models::Role::System);
+        assert_eq!(
+            content,
+            vec![ContentPart::InputText {
+                text: agent::handoff_note("triage", "billing")
+            }]
+        );
+        assert_eq!(session.agent().as_deref(), Some("billing"));
+
+        event_tx
+            .send(ServerEvent::ResponseFunctionCallArgumentsDone {
                 event_id: "evt_1".to_string(),
                 response_id: "resp_1".to_string(),
                 item_id: "item_1".to_string(),
                 output_index: 0,
-                content_index: 0,
-                text: "hello".to_string(),
-            };
-            event_tx_clone.send(evt).await.unwrap();
+                call_id: "call_1".to_string(),
+                name: "refund".to_string(),
+                arguments: "{}".to_string(),
+            })
+            .await
+            .unwrap();
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected tool output");
         };
-        tokio::spawn(send_evt);
-
-        let text = session.ask("hi").await.unwrap().expect("text");
-        assert_eq!(text, "hello");
-
-        // Ensure we sent both the item and the response.create.
-        let first = out_rx.recv().await.unwrap();
-        let second = out_rx.recv().await.unwrap();
-        assert!(
-            matches!(first, ClientEvent::ConversationItemCreate { .. })
-                || matches!(second, ClientEvent::ConversationItemCreate { .. })
-        );
         assert!(
-            matches!(first, ClientEvent::ResponseCreate { .. })
-                || matches!(second, ClientEvent::ResponseCreate { .. })
+            matches!(*item, Item::FunctionCallOutput { output, .. } if output == "\"refund ok\"")
         );
-
-        drop(event_tx);
     }
 
     #[tokio::test]
-    async fn voice_event_audio_delta_decodes() {
+    async fn tool_output_options_override_auto_tool_response() {
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let mut session = Session::from_transport(
+        let mut tools = ToolRegistry::new();
+        for name in ["log", "lookup", "quote"] {
+            tools.tool(name, |_: serde_json::Value| async { Ok("ok") });
+        }
+        tools.definition_mut("log").unwrap().discard_output();
+        tools.definition_mut("lookup").unwrap().follow_up(false);
+        let _session = Session::from_transport(
             transport,
             EventHandlers::new(),
             Arc::new(tools),
-            false,
-            true,
+            SessionOptions::default(),
         );
-
-        let pcm = vec![1u8, 2u8, 3u8, 4u8];
-        let delta = general_purpose::STANDARD.encode(&pcm);
-        let evt = ServerEvent::ResponseOutputAudioDelta {
-            event_id: "evt_1".to_string(),
+        let call = |call_id: &str, name: &str| ServerEvent::ResponseFunctionCallArgumentsDone {
+            event_id: format!("evt_{call_id}"),
             response_id: "resp_1".to_string(),
-            item_id: "item_1".to_string(),
+            item_id: format!("item_{call_id}"),
             output_index: 0,
-            content_index: 0,
-            delta,
+            call_id: call_id.to_string(),
+            name: name.to_string(),
+            arguments: "{}".to_string(),
+        };
+        let output_call_id = |event: Option<ClientEvent>| match event {
+            Some(ClientEvent::ConversationItemCreate { item, .. }) => match *item {
+                Item::FunctionCallOutput { call_id, .. } => call_id,
+                other => panic!("expected function_call_output, got {other:?}"),
+            },
+            other => panic!("expected conversation.item.create, got {other:?}"),
         };
-        event_tx.send(evt).await.unwrap();
 
-        let voice = session
-            .next_voice_event()
-            .await
-            .unwrap()
-            .expect("voice event");
-        match voice {
-            VoiceEvent::AudioDelta {
-                response_id,
-                pcm: decoded,
-                ..
-            } => {
-                assert_eq!(response_id, "resp_1");
-                assert_eq!(decoded, pcm);
-            }
-            other => panic!("unexpected voice event: {other:?}"),
-        }
+        event_tx.send(call("call_1", "log")).await.unwrap();
+        event_tx.send(call("call_2", "lookup")).await.unwrap();
+        assert_eq!(output_call_id(out_rx.recv().await), "call_2");
+        event_tx.send(call("call_3", "quote")).await.unwrap();
+        assert_eq!(output_call_id(out_rx.recv().await), "call_3");
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::ResponseCreate { .. })
+        ));
+        assert!(out_rx.try_recv().is_err());
     }
 
     #[tokio::test]
-    async fn voice_event_audio_done_propagates_response_id() {
+    async fn deferred_tool_answers_with_placeholder_then_completes() {
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let mut session = Session::from_transport(
+        let mut tools = ToolRegistry::new();
+        tools.tool_with_context(
+            "book_flight",
+            |ctx: ToolContext, _: serde_json::Value| async move {
+                ctx.defer();
+                Ok("working on it")
+            },
+        );
+        let session = Session::from_transport(
             transport,
             EventHandlers::new(),
             Arc::new(tools),
-            false,
-            true,
+            SessionOptions::default(),
         );
 
-        let evt = ServerEvent::ResponseOutputAudioDone {
-            event_id: "evt_2".to_string(),
-            response_id: "resp_42".to_string(),
-            item_id: "item_2".to_string(),
-            output_index: 1,
-            content_index: 0,
-            item: None,
+        event_tx
+            .send(ServerEvent::ResponseFunctionCallArgumentsDone {
+                event_id: "evt_1".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                call_id: "call_1".to_string(),
+                name: "book_flight".to_string(),
+                arguments: "{}".to_string(),
+            })
+            .await
+            .unwrap();
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected placeholder output");
         };
-        event_tx.send(evt).await.unwrap();
-
-        let voice = session
-            .next_voice_event()
+        assert!(matches!(
+            *item,
+            Item::FunctionCallOutput { output, .. } if output == "\"working on it\""
+        ));
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::ResponseCreate { .. })
+        ));
+
+        session
+            .complete_tool("call_1", serde_json::json!({ "confirmation": "ABC123" }))
             .await
-            .unwrap()
-            .expect("voice event");
-        match voice {
-            VoiceEvent::AudioDone {
-                response_id,
-                item_id,
-                output_index,
-                content_index,
-            } => {
-                assert_eq!(response_id, "resp_42");
-                assert_eq!(item_id, "item_2");
-                assert_eq!(output_index, 1);
-                assert_eq!(content_index, 0);
-            }
-            other => panic!("unexpected voice event: {other:?}"),
-        }
+            .unwrap();
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected deferred result");
+        };
+        let Item::Message { role, content, .. } = *item else {
+            panic!("expected message");
+        };
+        assert_eq!(role, crate::protocol::models::Role::System);
+        assert_eq!(
+            content,
+            vec![ContentPart::InputText {
+                text: tools::deferred_result_note(
+                    "book_flight",
+                    "call_1",
+                    &serde_json::json!({ "confirmation": "ABC123" })
+                )
+            }]
+        );
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(ClientEvent::ResponseCreate { .. })
+        ));
+        assert!(session.complete_tool("call_1", "again").await.is_err());
     }
 
     #[tokio::test]
-    async fn send_audio_pcm16_appends_and_commits() {
-        let (_event_tx, event_rx) = mpsc::channel(8);
+    async fn deferred_calls_survive_discarded_output_but_not_a_cancelled_response() {
+        let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
+        // `lookup` finishes after `log`, so its output shows `log` was handled.
+        let logged = Arc::new(tokio::sync::Notify::new());
+        let mut tools = ToolRegistry::new();
+        let notify = Arc::clone(&logged);
+        tools.tool_with_context("log", move |ctx: ToolContext, _: serde_json::Value| {
+            ctx.defer();
+            notify.notify_one();
+            async { Ok("logging") }
+        });
+        tools.tool("lookup", move |_: serde_json::Value| {
+            let logged = Arc::clone(&logged);
+            async move {
+                logged.notified().await;
+                Ok("ok")
+            }
+        });
+        tools.definition_mut("log").unwrap().discard_output();
+        tools.definition_mut("lookup").unwrap().follow_up(false);
         let session = Session::from_transport(
             transport,
             EventHandlers::new(),
             Arc::new(tools),
-            false,
-            true,
+            SessionOptions::default(),
         );
+        let call = |call_id: &str, name: &str, response_id: &str| {
+            ServerEvent::ResponseFunctionCallArgumentsDone {
+                event_id: format!("evt_{call_id}"),
+                response_id: response_id.to_string(),
+                item_id: format!("item_{call_id}"),
+                output_index: 0,
+                call_id: call_id.to_string(),
+                name: name.to_string(),
+                arguments: "{}".to_string(),
+            }
+        };
+        let lookup_output = |event: ClientEvent| {
+            matches!(
+                event,
+                ClientEvent::ConversationItemCreate { item, .. }
+                    if matches!(&*item, Item::FunctionCallOutput { output, .. } if output == "\"ok\"")
+            )
+        };
 
-        let pcm = vec![0i16; 4];
-        session.send_audio_pcm16(&pcm).await.unwrap();
+        event_tx
+            .send(call("call_1", "log", "resp_1"))
+            .await
+            .unwrap();
+        event_tx
+            .send(call("call_2", "lookup", "resp_1"))
+            .await
+            .unwrap();
+        assert!(lookup_output(next_sent(&mut out_rx).await));
+        session.complete_tool("call_1", "logged").await.unwrap();
+        assert!(out_rx.try_recv().is_err());
 
-        let first = out_rx.recv().await.unwrap();
-        let second = out_rx.recv().await.unwrap();
+        event_tx
+            .send(call("call_3", "log", "resp_2"))
+            .await
+            .unwrap();
+        let cancelled: Response = serde_json::from_value(serde_json::json!({
+            "id": "resp_2",
+            "object": "realtime.response",
+            "status": "cancelled",
+        }))
+        .unwrap();
+        event_tx
+            .send(ServerEvent::ResponseDone {
+                event_id: "evt_done".to_string(),
+                response: cancelled,
+            })
+            .await
+            .unwrap();
+        event_tx
+            .send(call("call_4", "lookup", "resp_3"))
+            .await
+            .unwrap();
+        assert!(lookup_output(next_sent(&mut out_rx).await));
+        assert!(session.complete_tool("call_3", "logged").await.is_err());
+    }
 
-        assert!(
-            matches!(first, ClientEvent::InputAudioBufferAppend { .. })
-                || matches!(second, ClientEvent::InputAudioBufferAppend { .. })
+    #[tokio::test]
+    async fn tool_middleware_follows_arguments_and_rejects_calls() {
+        struct Progress(Arc<std::sync::Mutex<Vec<String>>>);
+
+        impl tools::ToolMiddleware for Progress {
+            fn on_tool_call_started(&self, call_id: &str, name: &str) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("started {call_id} {name}"));
+            }
+            fn on_arguments_delta(&self, call_id: &str, chunk: &str) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("delta {call_id} {chunk}"));
+            }
+            fn on_tool_call_ready(&self, call: &ToolCall) -> Result<()> {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("ready {}", call.call_id));
+                if call.arguments["to"].as_str().is_some_and(str::is_empty) {
+                    return Err(Error::InvalidClientEvent("`to` is empty".to_string()));
+                }
+                Ok(())
+            }
+        }
+
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ran = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut registry = ToolRegistry::new();
+        let ran_tool = Arc::clone(&ran);
+        registry.tool("book_flight", move |_: serde_json::Value| {
+            ran_tool.store(true, std::sync::atomic::Ordering::SeqCst);
+            async move { Ok(serde_json::json!("booked")) }
+        });
+        registry.set_middleware(Progress(Arc::clone(&log)));
+
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let _session = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            }),
+            EventHandlers::new(),
+            Arc::new(registry),
+            SessionOptions::default(),
         );
-        assert!(
-            matches!(first, ClientEvent::InputAudioBufferCommit { .. })
-                || matches!(second, ClientEvent::InputAudioBufferCommit { .. })
+
+        let delta = |chunk: &str| ServerEvent::ResponseFunctionCallArgumentsDelta {
+            event_id: format!("evt_{chunk}"),
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            call_id: "call_1".to_string(),
+            delta: chunk.to_string(),
+        };
+        let events = [
+            ServerEvent::ResponseOutputItemAdded {
+                event_id: "evt_1".to_string(),
+                response_id: "resp_1".to_string(),
+                output_index: 0,
+                item: Item::FunctionCall {
+                    id: Some("item_1".to_string()),
+                    status: None,
+                    name: "book_flight".to_string(),
+                    call_id: "call_1".to_string(),
+                    arguments: String::new(),
+                },
+            },
+            delta("{\"to\""),
+            delta(":\"\"}"),
+            ServerEvent::ResponseFunctionCallArgumentsDone {
+                event_id: "evt_2".to_string(),
+                response_id: "resp_1".to_string(),
+                item_id: "item_1".to_string(),
+                output_index: 0,
+                call_id: "call_1".to_string(),
+                name: "book_flight".to_string(),
+                arguments: "{\"to\":\"\"}".to_string(),
+            },
+        ];
+        for event in events {
+            event_tx.send(event).await.unwrap();
+        }
+
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = out_rx.recv().await else {
+            panic!("expected tool error output");
+        };
+        assert!(matches!(
+            *item,
+            Item::FunctionCallOutput { ref output, .. } if output.contains("`to` is empty")
+        ));
+        assert!(!ran.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "started call_1 book_flight",
+                "delta call_1 {\"to\"",
+                "delta call_1 :\"\"}",
+                "ready call_1",
+            ]
         );
     }
 
     #[tokio::test]
-    async fn audio_handle_push_and_commit() {
-        let (_event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, mut out_rx) = mpsc::channel(8);
-        let transport = Box::new(MockTransport {
-            incoming: event_rx,
-            outgoing: out_tx,
+    async fn expiring_session_warns_then_renews_with_its_conversation() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (_next_event_tx, next_event_rx) = mpsc::channel(8);
+        let (next_out_tx, mut next_out_rx) = mpsc::channel(8);
+        let next: Box<dyn Transport> = Box::new(MockTransport {
+            incoming: next_event_rx,
+            outgoing: next_out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let session = Session::from_transport(
-            transport,
+        let next = Arc::new(std::sync::Mutex::new(Some(next)));
+        let reconnect: Reconnect = Arc::new(move || {
+            let next = next.lock().unwrap().take();
+            Box::pin(async move { next.ok_or(Error::ConnectionClosed) })
+        });
+        let mut session = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            }),
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                expiry: ExpiryPolicy::renew(Duration::from_secs(30)),
+                reconnect: Some(reconnect),
+                ..SessionOptions::default()
+            },
         );
 
-        let pcm = vec![0i16; 4];
-        session.audio().push_pcm16(&pcm).await.unwrap();
-        session.audio().commit().await.unwrap();
+        event_tx
+            .send(ServerEvent::ConversationItemAdded {
+                event_id: "evt_1".to_string(),
+                previous_item_id: None,
+                item: Item::Message {
+                    id: Some("item_1".to_string()),
+                    status: None,
+                    role: crate::protocol::models::Role::User,
+                    content: vec![ContentPart::InputText {
+                        text: "hello".to_string(),
+                    }],
+                },
+            })
+            .await
+            .unwrap();
+        let expires_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 20;
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_2".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at,
+                    config: SessionConfig::new(
+                        crate::protocol::models::SessionKind::Realtime,
+                        "gpt-realtime",
+                        crate::protocol::models::OutputModalities::Audio,
+                    ),
+                },
+            })
+            .await
+            .unwrap();
 
-        let first = out_rx.recv().await.unwrap();
-        let second = out_rx.recv().await.unwrap();
+        let mut expiring = None;
+        let renewed = loop {
+            match session.next_event().await.unwrap() {
+                Some(SdkEvent::SessionExpiring { in_seconds }) => expiring = Some(in_seconds),
+                Some(SdkEvent::SessionRenewed { items }) => break items,
+                Some(SdkEvent::SessionRenewFailed { message }) => panic!("{message}"),
+                Some(_) => {}
+                None => panic!("session closed"),
+            }
+        };
+        assert!(expiring.is_some_and(|secs| secs <= 20), "{expiring:?}");
+        assert_eq!(renewed, 1);
+        assert!(matches!(
+            next_out_rx.recv().await,
+            Some(ClientEvent::SessionUpdate { .. })
+        ));
+        let Some(ClientEvent::ConversationItemCreate { item, .. }) = next_out_rx.recv().await
+        else {
+            panic!("expected replayed item");
+        };
+        assert!(matches!(
+            *item,
+            Item::Message { ref id, ref content, .. }
+                if id.as_deref() == Some("item_1")
+                    && content == &vec![ContentPart::InputText { text: "hello".to_string() }]
+        ));
+    }
 
-        assert!(
-            matches!(first, ClientEvent::InputAudioBufferAppend { .. })
-                || matches!(second, ClientEvent::InputAudioBufferAppend { .. })
+    #[tokio::test]
+    async fn renewed_connection_stays_behind_the_outbound_gate() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (_next_event_tx, next_event_rx) = mpsc::channel(8);
+        let (next_out_tx, mut next_out_rx) = mpsc::channel(8);
+        let next: Box<dyn Transport> = Box::new(MockTransport {
+            incoming: next_event_rx,
+            outgoing: next_out_tx,
+        });
+        let next = Arc::new(std::sync::Mutex::new(Some(next)));
+        let reconnect: Reconnect = Arc::new(move || {
+            let next = next.lock().unwrap().take();
+            Box::pin(async move { next.ok_or(Error::ConnectionClosed) })
+        });
+        let mut session = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            }),
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                expiry: ExpiryPolicy::renew(Duration::from_secs(30)),
+                reconnect: Some(reconnect),
+                ..SessionOptions::default()
+            },
+        );
+        let expires_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 20;
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_1".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at,
+                    config: SessionConfig::new(
+                        crate::protocol::models::SessionKind::Realtime,
+                        "gpt-realtime",
+                        crate::protocol::models::OutputModalities::Audio,
+                    ),
+                },
+            })
+            .await
+            .unwrap();
+        loop {
+            match session.next_event().await.unwrap() {
+                Some(SdkEvent::SessionRenewed { .. }) => break,
+                Some(SdkEvent::SessionRenewFailed { message }) => panic!("{message}"),
+                Some(_) => {}
+                None => panic!("session closed"),
+            }
+        }
+        assert!(matches!(
+            next_sent(&mut next_out_rx).await,
+            ClientEvent::SessionUpdate { .. }
+        ));
+
+        let handle = session.handle();
+        handle.pause_outbound();
+        let commit = ClientEvent::InputAudioBufferCommit {
+            event_id: Some("evt_held".to_string()),
+        };
+        session.send_raw(commit).await.unwrap();
+        assert_eq!(handle.queued_outbound(), 1);
+        assert!(next_out_rx.try_recv().is_err());
+        handle.resume_outbound();
+        assert_eq!(
+            next_sent(&mut next_out_rx).await.event_id(),
+            Some("evt_held")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paused_time_drives_scheduled_responses_and_expiry() {
+        struct FixedWallClock(std::time::SystemTime);
+        impl Clock for FixedWallClock {
+            fn now(&self) -> tokio::time::Instant {
+                tokio::time::Instant::now()
+            }
+            fn system_time(&self) -> std::time::SystemTime {
+                self.0
+            }
+        }
+
+        const WALL_SECS: u64 = 1_700_000_000;
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let mut session = Session::from_transport(
+            Box::new(MockTransport {
+                incoming: event_rx,
+                outgoing: out_tx,
+            }),
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                clock: SharedClock::new(FixedWallClock(
+                    std::time::UNIX_EPOCH + Duration::from_secs(WALL_SECS),
+                )),
+                ..SessionOptions::default()
+            },
         );
+        let start = tokio::time::Instant::now();
+
+        event_tx
+            .send(ServerEvent::SessionCreated {
+                event_id: "evt_1".to_string(),
+                session: crate::protocol::models::Session {
+                    id: "sess_1".to_string(),
+                    object: "realtime.session".to_string(),
+                    expires_at: WALL_SECS + 120,
+                    config: SessionConfig::new(
+                        crate::protocol::models::SessionKind::Realtime,
+                        "gpt-realtime",
+                        crate::protocol::models::OutputModalities::Audio,
+                    ),
+                },
+            })
+            .await
+            .unwrap();
+        session
+            .respond_after(Duration::from_secs(30), ResponseConfig::default())
+            .await
+            .unwrap();
+
+        // Time only moves as the runtime advances it, so the delay holds exactly.
         assert!(
-            matches!(first, ClientEvent::InputAudioBufferCommit { .. })
-                || matches!(second, ClientEvent::InputAudioBufferCommit { .. })
+            tokio::time::timeout(Duration::from_secs(29), out_rx.recv())
+                .await
+                .is_err()
         );
+        assert!(matches!(
+            tokio::time::timeout(Duration::from_secs(5), out_rx.recv()).await,
+            Ok(Some(ClientEvent::ResponseCreate { .. }))
+        ));
+
+        // By the fixed wall clock two minutes are left, so the warning comes a minute in.
+        let in_seconds = loop {
+            match session.next_event().await.unwrap() {
+                Some(SdkEvent::SessionExpiring { in_seconds }) => break in_seconds,
+                Some(_) => {}
+                None => panic!("session closed"),
+            }
+        };
+        assert!((59..=60).contains(&in_seconds), "{in_seconds}");
+        assert!(start.elapsed() >= Duration::from_secs(60));
     }
 
     #[tokio::test]
-    async fn stream_audio_pcm16_sends_chunks() {
-        let (_event_tx, event_rx) = mpsc::channel(8);
+    async fn missing_predecessor_is_retrieved_and_slotted_in() {
+        let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let session = Session::from_transport(
+        let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
+        let message = |id: &str| Item::Message {
+            id: Some(id.to_string()),
+            status: None,
+            role: crate::protocol::models::Role::User,
+            content: vec![ContentPart::InputText {
+                text: id.to_string(),
+            }],
+        };
 
-        let stream = futures::stream::iter(vec![vec![0i16; 2], vec![1i16; 2]]);
-        session.stream_audio_pcm16(stream).await.unwrap();
+        for (id, previous) in [("item_1", None), ("item_3", Some("item_2"))] {
+            event_tx
+                .send(ServerEvent::ConversationItemAdded {
+                    event_id: format!("evt_{id}"),
+                    previous_item_id: previous.map(str::to_string),
+                    item: message(id),
+                })
+                .await
+                .unwrap();
+        }
+        let Some(ClientEvent::ConversationItemRetrieve { item_id, .. }) = out_rx.recv().await
+        else {
+            panic!("expected conversation.item.retrieve");
+        };
+        assert_eq!(item_id, "item_2");
+        loop {
+            if let SdkEvent::ConversationGap(gap) = session.next_event().await.unwrap().unwrap() {
+                assert_eq!(gap.item_id, "item_3");
+                assert_eq!(gap.missing_item_id, "item_2");
+                break;
+            }
+        }
 
-        let mut saw_append = 0;
-        let mut saw_commit = 0;
-        for _ in 0..4 {
-            let evt = out_rx.recv().await.unwrap();
-            match evt {
-                ClientEvent::InputAudioBufferAppend { .. } => saw_append += 1,
-                ClientEvent::InputAudioBufferCommit { .. } => saw_commit += 1,
-                _ => {}
+        event_tx
+            .send(ServerEvent::ConversationItemRetrieved {
+                event_id: "evt_retrieved".to_string(),
+                item: message("item_2"),
+            })
+            .await
+            .unwrap();
+        // The item is slotted in before its event is passed on.
+        loop {
+            if let SdkEvent::Raw(event) = session.next_event().await.unwrap().unwrap() {
+                if matches!(*event, ServerEvent::ConversationItemRetrieved { .. }) {
+                    break;
+                }
             }
         }
-        assert_eq!(saw_append, 2);
-        assert_eq!(saw_commit, 2);
+        let items = session.conversation().await.unwrap();
+        let ids: Vec<_> = items.iter().filter_map(Item::id).collect();
+        assert_eq!(ids, ["item_1", "item_2", "item_3"]);
     }
 
     #[tokio::test]
-    async fn barge_in_sends_clear_and_cancel() {
+    async fn retrieve_item_audio_joins_parts_and_surfaces_errors() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let mut session = Session::from_transport(
+        let session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        let resp = crate::protocol::models::Response {
-            id: "resp_1".to_string(),
-            object: "response".to_string(),
-            conversation_id: None,
-            status: crate::protocol::models::ResponseStatus::InProgress,
-            status_details: None,
-            output: None,
-            output_modalities: None,
-            max_output_tokens: None,
-            audio: None,
-            metadata: None,
-            usage: None,
-        };
-        let evt = ServerEvent::ResponseCreated {
-            event_id: "evt_1".to_string(),
-            response: resp,
+        let server = async {
+            let Some(ClientEvent::ConversationItemRetrieve { item_id, .. }) = out_rx.recv().await
+            else {
+                panic!("expected conversation.item.retrieve");
+            };
+            let part = |pcm: &[u8]| ContentPart::InputAudio {
+                audio: general_purpose::STANDARD.encode(pcm),
+                transcript: None,
+                format: None,
+            };
+            event_tx
+                .send(ServerEvent::ConversationItemRetrieved {
+                    event_id: "evt_1".to_string(),
+                    item: Item::Message {
+                        id: Some(item_id),
+                        status: Some(ItemStatus::Completed),
+                        role: crate::protocol::models::Role::User,
+                        content: vec![part(&[1, 2]), part(&[3, 4])],
+                    },
+                })
+                .await
+                .unwrap();
         };
-        event_tx.send(evt).await.unwrap();
-
-        let _ = session.next_voice_event().await.unwrap();
-        session.barge_in().await.unwrap();
-
-        let first = out_rx.recv().await.unwrap();
-        let second = out_rx.recv().await.unwrap();
+        let (audio, ()) = tokio::join!(session.retrieve_item_audio("item_1"), server);
+        assert_eq!(audio.unwrap(), vec![1, 2, 3, 4]);
 
+        let server = async {
+            let Some(ClientEvent::ConversationItemRetrieve { event_id, .. }) = out_rx.recv().await
+            else {
+                panic!("expected conversation.item.retrieve");
+            };
+            event_tx
+                .send(ServerEvent::Error {
+                    event_id: "evt_2".to_string(),
+                    error: crate::error::ServerError {
+                        error_type: crate::error::ApiErrorType::InvalidRequestError,
+                        code: Some("item_not_found".to_string()),
+                        message: "Item not found".to_string(),
+                        param: None,
+                        event_id,
+                    },
+                })
+                .await
+                .unwrap();
+        };
+        let (missing, ()) = tokio::join!(session.retrieve_item_audio("item_2"), server);
         assert!(
-            matches!(first, ClientEvent::OutputAudioBufferClear { .. })
-                || matches!(second, ClientEvent::OutputAudioBufferClear { .. })
-        );
-        assert!(
-            matches!(first, ClientEvent::ResponseCancel { .. })
-                || matches!(second, ClientEvent::ResponseCancel { .. })
+            matches!(missing, Err(Error::Api(err)) if err.code.as_deref() == Some("item_not_found"))
         );
     }
 
-    #[tokio::test]
-    async fn auto_barge_in_on_speech_started() {
+    #[tokio::test(start_paused = true)]
+    async fn timed_out_retrieve_leaves_other_waiters_for_the_item() {
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let (out_tx, _out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions {
+                request_timeout: Duration::from_secs(5),
+                ..SessionOptions::default()
+            },
+        );
 
-        let tools = ToolRegistry::new();
-        let mut session =
-            Session::from_transport(transport, EventHandlers::new(), Arc::new(tools), true, true);
-
-        let resp = crate::protocol::models::Response {
-            id: "resp_1".to_string(),
-            object: "response".to_string(),
-            conversation_id: None,
-            status: crate::protocol::models::ResponseStatus::InProgress,
-            status_details: None,
-            output: None,
-            output_modalities: None,
-            max_output_tokens: None,
-            audio: None,
-            metadata: None,
-            usage: None,
-        };
-        let created = ServerEvent::ResponseCreated {
-            event_id: "evt_1".to_string(),
-            response: resp,
+        let later = async {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            session.retrieve_item("item_1").await
         };
-        event_tx.send(created).await.unwrap();
-        let _ = session.next_voice_event().await.unwrap();
-
-        let speech = ServerEvent::InputAudioBufferSpeechStarted {
-            event_id: "evt_2".to_string(),
-            audio_start_ms: 0,
-            item_id: "item_1".to_string(),
+        let server = async {
+            tokio::time::sleep(Duration::from_secs(6)).await;
+            assert_eq!(session.pending.lock().await.items["item_1"].len(), 1);
+            event_tx
+                .send(ServerEvent::ConversationItemRetrieved {
+                    event_id: "evt_1".to_string(),
+                    item: Item::Message {
+                        id: Some("item_1".to_string()),
+                        status: Some(ItemStatus::Completed),
+                        role: crate::protocol::models::Role::User,
+                        content: Vec::new(),
+                    },
+                })
+                .await
+                .unwrap();
         };
-        event_tx.send(speech).await.unwrap();
-        let _ = session.next_voice_event().await.unwrap();
+        let (first, second, ()) = tokio::join!(session.retrieve_item("item_1"), later, server);
+        assert!(matches!(first, Err(Error::Timeout(_))));
+        assert_eq!(second.unwrap().id(), Some("item_1"));
+        assert!(session.pending.lock().await.items.is_empty());
+    }
 
-        let first = out_rx.recv().await.unwrap();
-        let second = out_rx.recv().await.unwrap();
+    #[tokio::test]
+    async fn rejected_tool_output_deletes_its_call() {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
+        let transport = Box::new(MockTransport {
+            incoming: event_rx,
+            outgoing: out_tx,
+        });
+        let session = Session::from_transport(
+            transport,
+            EventHandlers::new(),
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
+        );
 
-        assert!(
-            matches!(first, ClientEvent::OutputAudioBufferClear { .. })
-                || matches!(second, ClientEvent::OutputAudioBufferClear { .. })
+        let server = async {
+            let ClientEvent::ConversationItemCreate { item, .. } = next_sent(&mut out_rx).await
+            else {
+                panic!("expected the call to be created");
+            };
+            let call_id = item.id().unwrap().to_string();
+            event_tx
+                .send(ServerEvent::ConversationItemAdded {
+                    event_id: "evt_1".to_string(),
+                    previous_item_id: None,
+                    item: *item,
+                })
+                .await
+                .unwrap();
+            let ClientEvent::ConversationItemCreate { event_id, .. } = next_sent(&mut out_rx).await
+            else {
+                panic!("expected the output to be created");
+            };
+            event_tx
+                .send(ServerEvent::Error {
+                    event_id: "evt_2".to_string(),
+                    error: crate::error::ServerError {
+                        error_type: crate::error::ApiErrorType::InvalidRequestError,
+                        code: None,
+                        message: "Invalid output".to_string(),
+                        param: None,
+                        event_id,
+                    },
+                })
+                .await
+                .unwrap();
+            let deleted = next_sent(&mut out_rx).await;
+            assert!(matches!(
+                deleted,
+                ClientEvent::ConversationItemDelete { item_id, .. } if item_id == call_id
+            ));
+        };
+        let (arguments, output) = (
+            serde_json::json!({ "q": "x" }),
+            serde_json::json!({ "ok": true }),
         );
-        assert!(
-            matches!(first, ClientEvent::ResponseCancel { .. })
-                || matches!(second, ClientEvent::ResponseCancel { .. })
+        let (injected, ()) = tokio::join!(
+            session.inject_tool_exchange("lookup", &arguments, &output),
+            server
         );
+        assert!(matches!(injected, Err(Error::Api(_))));
     }
 
     #[tokio::test]
-    async fn audio_deltas_gate_on_active_response() {
+    async fn duplicate_in_flight_create_is_rejected() {
         let (event_tx, event_rx) = mpsc::channel(8);
-        let (out_tx, _out_rx) = mpsc::channel(8);
+        let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
-        let mut session = Session::from_transport(
+        let session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
-
-        let resp = crate::protocol::models::Response {
-            id: "resp_1".to_string(),
-            object: "response".to_string(),
-            conversation_id: None,
-            status: crate::protocol::models::ResponseStatus::InProgress,
-            status_details: None,
-            output: None,
-            output_modalities: None,
-            max_output_tokens: None,
-            audio: None,
-            metadata: None,
-            usage: None,
+        let item = Item::Message {
+            id: Some("item_1".to_string()),
+            status: None,
+            role: crate::protocol::models::Role::User,
+            content: vec![ContentPart::InputText {
+                text: "hello".to_string(),
+            }],
         };
-        event_tx
-            .send(ServerEvent::ResponseCreated {
-                event_id: "evt_1".to_string(),
-                response: resp,
-            })
-            .await
-            .unwrap();
-        let _ = session.next_voice_event().await.unwrap();
 
-        let pcm = vec![1u8, 2u8];
-        let delta = general_purpose::STANDARD.encode(&pcm);
-        let evt = ServerEvent::ResponseOutputAudioDelta {
-            event_id: "evt_2".to_string(),
-            response_id: "resp_2".to_string(),
-            item_id: "item_1".to_string(),
-            output_index: 0,
-            content_index: 0,
-            delta,
+        let server = async {
+            next_sent(&mut out_rx).await;
+            let duplicate = session.create_item(item.clone()).await;
+            assert!(matches!(duplicate, Err(Error::InvalidClientEvent(_))));
+            event_tx
+                .send(ServerEvent::ConversationItemAdded {
+                    event_id: "evt_1".to_string(),
+                    previous_item_id: None,
+                    item: item.clone(),
+                })
+                .await
+                .unwrap();
         };
-        event_tx.send(evt).await.unwrap();
-
-        // Should not receive audio chunk for different response_id.
-        let chunk = tokio::time::timeout(
-            std::time::Duration::from_millis(100),
-            session.next_audio_chunk(),
-        )
-        .await;
-        assert!(chunk.is_err());
+        let (created, ()) = tokio::join!(session.create_item(item.clone()), server);
+        assert_eq!(created.unwrap().id(), Some("item_1"));
     }
 
     #[tokio::test]
-    async fn session_loop_exits_when_sender_closed() {
+    async fn update_session_json_forwards_unknown_fields() {
         let (_event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, mut out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
@@ -1757,67 +9643,98 @@ mod tests {
             outgoing: out_tx,
         });
 
-        let tools = ToolRegistry::new();
         let session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        drop(session);
-
-        let closed = tokio::time::timeout(std::time::Duration::from_secs(1), out_rx.recv())
+        assert!(matches!(
+            session
+                .update_session_json(serde_json::json!(["not", "an", "object"]))
+                .await,
+            Err(Error::InvalidClientEvent(_))
+        ));
+        assert!(matches!(
+            session
+                .update_session_json(serde_json::json!({ "model": "gpt-realtime-mini" }))
+                .await,
+            Err(Error::InvalidClientEvent(msg)) if msg.contains("`model`")
+        ));
+
+        session
+            .update_session_json(serde_json::json!({
+                "instructions": "Be brief.",
+                "future_field": 3
+            }))
             .await
-            .expect("session loop did not exit");
-        assert!(closed.is_none());
+            .unwrap();
+        let Some(ClientEvent::SessionUpdate {
+            session: update, ..
+        }) = out_rx.recv().await
+        else {
+            panic!("expected session.update");
+        };
+        assert_eq!(update.config.instructions.as_deref(), Some("Be brief."));
+        assert_eq!(
+            update.config.extra.get("future_field"),
+            Some(&serde_json::json!(3))
+        );
     }
 
     #[tokio::test]
-    async fn user_transcript_event_includes_content_index() {
+    async fn finish_reports_turns_errors_and_transcript() {
         let (event_tx, event_rx) = mpsc::channel(8);
         let (out_tx, _out_rx) = mpsc::channel(8);
         let transport = Box::new(MockTransport {
             incoming: event_rx,
             outgoing: out_tx,
         });
-
-        let tools = ToolRegistry::new();
         let mut session = Session::from_transport(
             transport,
             EventHandlers::new(),
-            Arc::new(tools),
-            false,
-            true,
+            Arc::new(ToolRegistry::new()),
+            SessionOptions::default(),
         );
 
-        let evt = ServerEvent::InputAudioTranscriptionCompleted {
-            event_id: "evt_1".to_string(),
-            item_id: "item_1".to_string(),
-            content_index: 2,
-            transcript: "hello".to_string(),
-            logprobs: None,
-            usage: None,
-        };
-        event_tx.send(evt).await.unwrap();
-
-        let voice = session
-            .next_voice_event()
+        event_tx
+            .send(ServerEvent::ConversationItemAdded {
+                event_id: "evt_1".to_string(),
+                previous_item_id: None,
+                item: Item::Message {
+                    id: Some("item_1".to_string()),
+                    status: None,
+                    role: crate::protocol::models::Role::User,
+                    content: vec![ContentPart::InputText {
+                        text: "Cancel my order".to_string(),
+                    }],
+                },
+            })
             .await
-            .unwrap()
-            .expect("voice event");
-        match voice {
-            VoiceEvent::UserTranscriptDone {
-                item_id,
-                content_index,
-                transcript,
-            } => {
-                assert_eq!(item_id, "item_1");
-                assert_eq!(content_index, 2);
-                assert_eq!(transcript, "hello");
-            }
-            other => panic!("unexpected voice event: {other:?}"),
-        }
+            .unwrap();
+        event_tx
+            .send(ServerEvent::Error {
+                event_id: "evt_2".to_string(),
+                error: crate::error::ServerError {
+                    error_type: crate::error::ApiErrorType::InvalidRequestError,
+                    code: None,
+                    message: "Unknown parameter".to_string(),
+                    param: None,
+                    event_id: None,
+                },
+            })
+            .await
+            .unwrap();
+        while !matches!(
+            session.next_event().await.unwrap(),
+            Some(SdkEvent::Error { .. })
+        ) {}
+
+        let report = session.finish().await.unwrap();
+        assert_eq!(report.user_turns, 1);
+        assert_eq!(report.responses, 0);
+        assert_eq!(report.errors.server, 1);
+        assert_eq!(report.transcript[0].text, "Cancel my order");
     }
 }
@@ -0,0 +1,307 @@
+//! Splitting streamed response text into clause-sized pieces for an external TTS.
+
+use super::channel::Receiver;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Words that end in a period without ending a clause.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "approx", "inc", "ltd",
+];
+
+/// Closing quotes and brackets that stay with the punctuation before them.
+const CLOSERS: &[char] = &['"', '\'', ')', ']', '}', '\u{201d}', '\u{2019}'];
+
+/// How response text is cut into speakable chunks for `Session::speakable_text`.
+///
+/// Text is cut after clause punctuation (`. ! ? ; : ,`, dashes and ellipses) once a
+/// chunk holds at least `min_chars` characters. A clause longer than `max_chars` is cut
+/// at its last space instead. A period after a known abbreviation or after initials
+/// such as `J.` or `e.g.` never ends a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakableChunking {
+    min_chars: usize,
+    max_chars: usize,
+    abbreviations: Vec<String>,
+}
+
+impl Default for SpeakableChunking {
+    fn default() -> Self {
+        Self {
+            min_chars: 12,
+            max_chars: 120,
+            abbreviations: ABBREVIATIONS.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl SpeakableChunking {
+    /// Chunks of 12 to 120 characters, with common English abbreviations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold back clauses until a chunk has at least `min_chars` characters.
+    #[must_use]
+    pub const fn min_chars(mut self, min_chars: usize) -> Self {
+        self.min_chars = min_chars;
+        self
+    }
+
+    /// Cut a chunk once it reaches `max_chars` characters, even mid-clause.
+    #[must_use]
+    pub const fn max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Treat `word.` as an abbreviation, e.g. `"approx"` or `"z.B"`. Case-insensitive.
+    #[must_use]
+    pub fn abbreviation(mut self, word: impl Into<String>) -> Self {
+        self.abbreviations.push(word.into().to_lowercase());
+        self
+    }
+
+    /// Where the first chunk of `text` ends, if a complete one is buffered.
+    fn next_cut(&self, text: &str) -> Option<usize> {
+        let max_chars = self.max_chars.max(1);
+        let mut last_space = None;
+        let mut chars = text.char_indices().enumerate().peekable();
+        while let Some((count, (index, c))) = chars.next() {
+            if count == max_chars {
+                return Some(last_space.unwrap_or(index));
+            }
+            if c.is_whitespace() {
+                if count > 0 {
+                    last_space = Some(index);
+                }
+                continue;
+            }
+            if !is_clause_end(c) || (c == '.' && self.is_abbreviation(&text[..index])) {
+                continue;
+            }
+            while chars
+                .next_if(|(_, (_, next))| CLOSERS.contains(next))
+                .is_some()
+            {}
+            let &(_, (cut, next)) = chars.peek()?;
+            if next.is_whitespace() && text[..cut].trim().chars().count() >= self.min_chars {
+                return Some(cut);
+            }
+        }
+        None
+    }
+
+    fn is_abbreviation(&self, before: &str) -> bool {
+        let word = before
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        let initials = word
+            .split('.')
+            .all(|part| part.chars().count() == 1 && part.chars().all(char::is_alphabetic));
+        initials || self.abbreviations.contains(&word)
+    }
+}
+
+const fn is_clause_end(c: char) -> bool {
+    matches!(
+        c,
+        '.' | '!' | '?' | ';' | ':' | ',' | '\u{2026}' | '\u{2013}' | '\u{2014}'
+    )
+}
+
+/// A piece of response text that can be spoken on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpeakableChunk {
+    pub response_id: String,
+    pub item_id: String,
+    pub content_index: u32,
+    /// Position of this chunk within its content part, from 0.
+    pub index: u32,
+    pub text: String,
+}
+
+/// Per-content-part text not yet emitted as a chunk.
+#[derive(Debug, Default)]
+pub struct SpeakableSplitter {
+    parts: HashMap<(String, u32), Part>,
+}
+
+/// A content part being cut: its response, the text held back and the next index.
+#[derive(Debug, Default)]
+struct Part {
+    response_id: String,
+    buffer: String,
+    next_index: u32,
+}
+
+impl SpeakableSplitter {
+    /// Add a text delta and return the chunks it completes.
+    pub fn push(
+        &mut self,
+        chunking: &SpeakableChunking,
+        response_id: &str,
+        item_id: &str,
+        content_index: u32,
+        delta: &str,
+    ) -> Vec<SpeakableChunk> {
+        let Part {
+            buffer, next_index, ..
+        } = self
+            .parts
+            .entry((item_id.to_string(), content_index))
+            .or_insert_with(|| Part {
+                response_id: response_id.to_string(),
+                ..Part::default()
+            });
+        buffer.push_str(delta);
+        let mut chunks = Vec::new();
+        while let Some(cut) = chunking.next_cut(buffer) {
+            let text = buffer[..cut].trim().to_string();
+            buffer.drain(..cut);
+            let spaces = buffer.len() - buffer.trim_start().len();
+            buffer.drain(..spaces);
+            if !text.is_empty() {
+                chunks.push(SpeakableChunk {
+                    response_id: response_id.to_string(),
+                    item_id: item_id.to_string(),
+                    content_index,
+                    index: *next_index,
+                    text,
+                });
+                *next_index += 1;
+            }
+        }
+        chunks
+    }
+
+    /// The rest of a finished content part, however short.
+    pub fn flush(
+        &mut self,
+        response_id: &str,
+        item_id: &str,
+        content_index: u32,
+    ) -> Option<SpeakableChunk> {
+        let part = self.parts.remove(&(item_id.to_string(), content_index))?;
+        let text = part.buffer.trim();
+        (!text.is_empty()).then(|| SpeakableChunk {
+            response_id: response_id.to_string(),
+            item_id: item_id.to_string(),
+            content_index,
+            index: part.next_index,
+            text: text.to_string(),
+        })
+    }
+
+    /// Drop what is held for a response that is done, e.g. cancelled before its text
+    /// parts finished.
+    pub fn discard(&mut self, response_id: &str) {
+        self.parts.retain(|_, part| part.response_id != response_id);
+    }
+}
+
+/// Response text in speakable chunks, from `Session::speakable_text`. Ends when the
+/// session closes.
+pub struct SpeakableStream<'a> {
+    rx: &'a mut Receiver<SpeakableChunk>,
+}
+
+impl<'a> SpeakableStream<'a> {
+    #[must_use]
+    pub(crate) const fn new(rx: &'a mut Receiver<SpeakableChunk>) -> Self {
+        Self { rx }
+    }
+}
+
+impl Stream for SpeakableStream<'_> {
+    type Item = SpeakableChunk;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunking: &SpeakableChunking, deltas: &[&str]) -> Vec<String> {
+        let mut splitter = SpeakableSplitter::default();
+        let mut chunks: Vec<SpeakableChunk> = deltas
+            .iter()
+            .flat_map(|delta| splitter.push(chunking, "resp_1", "item_1", 0, delta))
+            .collect();
+        chunks.extend(splitter.flush("resp_1", "item_1", 0));
+        assert!(
+            chunks
+                .iter()
+                .enumerate()
+                .all(|(i, c)| c.index as usize == i)
+        );
+        chunks.into_iter().map(|chunk| chunk.text).collect()
+    }
+
+    #[test]
+    fn text_is_cut_at_clauses_but_not_abbreviations() {
+        let chunking = SpeakableChunking::new();
+        assert_eq!(
+            split(
+                &chunking,
+                &[
+                    "Dr. Smith will s",
+                    "ee you at 3 p.m. tomorrow, ",
+                    "room 4. Is that ok?"
+                ]
+            ),
+            [
+                "Dr. Smith will see you at 3 p.m. tomorrow,",
+                "room 4. Is that ok?"
+            ]
+        );
+        assert_eq!(
+            split(&chunking, &["He said \"stop.\" Then he left."]),
+            ["He said \"stop.\"", "Then he left."]
+        );
+    }
+
+    #[test]
+    fn parts_of_a_done_response_are_discarded() {
+        let chunking = SpeakableChunking::new();
+        let mut splitter = SpeakableSplitter::default();
+        assert!(
+            splitter
+                .push(&chunking, "resp_1", "item_1", 0, "Half a sen")
+                .is_empty()
+        );
+        splitter.push(&chunking, "resp_2", "item_2", 0, "Still going");
+        splitter.discard("resp_1");
+        assert!(splitter.flush("resp_1", "item_1", 0).is_none());
+        assert_eq!(
+            splitter
+                .flush("resp_2", "item_2", 0)
+                .map(|chunk| chunk.text),
+            Some("Still going".to_string())
+        );
+    }
+
+    #[test]
+    fn long_clauses_are_cut_at_the_last_space() {
+        let chunking = SpeakableChunking::new().min_chars(1).max_chars(12);
+        assert_eq!(
+            split(&chunking, &["one two three four. Five"]),
+            ["one two", "three four.", "Five"]
+        );
+        assert_eq!(
+            split(&chunking.abbreviation("Ca"), &["Ca. ok. yes"]),
+            ["Ca. ok.", "yes"]
+        );
+    }
+}
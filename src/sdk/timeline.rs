@@ -0,0 +1,133 @@
+//! Export of a recorded event log as a Chrome Trace Event timeline.
+
+use crate::Result;
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+const PID: u32 = 1;
+const CLIENT_TID: u32 = 1;
+const SERVER_TID: u32 = 2;
+
+/// Convert JSONL written by an [`EventLog`](super::EventLog) into a Chrome Trace
+/// Event document, which both `chrome://tracing` and Perfetto open.
+///
+/// Every event becomes an instant on a `client` or `server` track, categorized by
+/// the prefix of its type (`response`, `input_audio_buffer`, ...) with the logged
+/// event as its args. Responses (`response.created` to `response.done`) and user
+/// speech (`speech_started` to `speech_stopped`) also become spans, so gaps and
+/// latencies can be measured directly.
+///
+/// # Errors
+/// Returns an error if reading fails or a line is not valid JSON.
+pub fn chrome_trace(log: impl BufRead) -> Result<Value> {
+    let mut trace_events = vec![
+        thread_name(CLIENT_TID, "client"),
+        thread_name(SERVER_TID, "server"),
+    ];
+    for line in log.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = serde_json::from_str(&line)?;
+        let ts = record["ts_ms"].as_u64().unwrap_or_default() * 1_000;
+        let event = &record["event"];
+        let name = event["type"].as_str().unwrap_or("unknown");
+        let (direction, tid) = match record["direction"].as_str() {
+            Some("client") => ("client", CLIENT_TID),
+            _ => ("server", SERVER_TID),
+        };
+        let category = name.split('.').next().unwrap_or(name);
+        trace_events.push(json!({
+            "name": name,
+            "cat": format!("{direction}.{category}"),
+            "ph": "i",
+            "s": "t",
+            "ts": ts,
+            "pid": PID,
+            "tid": tid,
+            "args": { "event": event },
+        }));
+        if let Some(span) = span_edge(name, event, ts) {
+            trace_events.push(span);
+        }
+    }
+    Ok(json!({ "traceEvents": trace_events, "displayTimeUnit": "ms" }))
+}
+
+/// Write the [`chrome_trace`] of the event log at `log` to `trace`.
+///
+/// # Errors
+/// Returns an error if either file cannot be opened, or the log is not valid JSONL.
+pub fn export_chrome_trace(log: impl AsRef<Path>, trace: impl AsRef<Path>) -> Result<()> {
+    let timeline = chrome_trace(BufReader::new(File::open(log)?))?;
+    let mut out = BufWriter::new(File::create(trace)?);
+    serde_json::to_writer(&mut out, &timeline)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn thread_name(tid: u32, name: &str) -> Value {
+    json!({
+        "name": "thread_name",
+        "ph": "M",
+        "pid": PID,
+        "tid": tid,
+        "args": { "name": name },
+    })
+}
+
+/// The begin or end of an async span, for server events that open or close one.
+fn span_edge(name: &str, event: &Value, ts: u64) -> Option<Value> {
+    let (span, phase, id) = match name {
+        "response.created" => ("response", "b", &event["response"]["id"]),
+        "response.done" => ("response", "e", &event["response"]["id"]),
+        "input_audio_buffer.speech_started" => ("user speech", "b", &event["item_id"]),
+        "input_audio_buffer.speech_stopped" => ("user speech", "e", &event["item_id"]),
+        _ => return None,
+    };
+    Some(json!({
+        "name": span,
+        "cat": span,
+        "ph": phase,
+        "id": id.as_str()?,
+        "ts": ts,
+        "pid": PID,
+        "tid": SERVER_TID,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_lines_become_instants_and_spans() {
+        let log = [
+            json!({ "ts_ms": 1, "direction": "client", "event": { "type": "response.create" } }),
+            json!({ "ts_ms": 2, "direction": "server", "event": {
+                "type": "response.created", "response": { "id": "resp_1" } } }),
+            json!({ "ts_ms": 9, "direction": "server", "event": {
+                "type": "response.done", "response": { "id": "resp_1" } } }),
+        ]
+        .map(|line| line.to_string())
+        .join("\n");
+
+        let trace = chrome_trace(log.as_bytes()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2 + 3 + 2);
+        assert_eq!(events[2]["cat"], "client.response");
+        assert_eq!(events[2]["ts"], 1_000);
+        assert_eq!(events[2]["tid"], CLIENT_TID);
+        let span: Vec<_> = events
+            .iter()
+            .filter(|e| e["name"] == "response")
+            .map(|e| (e["ph"].as_str().unwrap(), e["ts"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(span, [("b", 2_000), ("e", 9_000)]);
+
+        assert!(chrome_trace(&b"not json"[..]).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Capping `max_output_tokens` per response by a cost ceiling and the tokens the rate
+//! limit has left.
+
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::models::MaxTokens;
+use crate::protocol::server_events::ServerEvent;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Name of the rate limit counting tokens in `rate_limits.updated`.
+const TOKENS_LIMIT: &str = "tokens";
+
+/// How many output tokens a single response may use.
+///
+/// It applies to every `response.create` sent through the session: `respond`,
+/// `send_response`, out-of-band responses and `send_raw` alike. Responses the event
+/// loop starts on its own, the follow-up after a tool it ran, a scheduled response or
+/// the deadline's closing message, are not capped.
+///
+/// A response is never given more than the tokens the rate limit has left, nor more
+/// than the configured ceilings. A smaller `max_output_tokens` on the response itself
+/// is kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenBudget {
+    max_per_response: Option<u32>,
+    /// Below this many remaining tokens, cap responses at the second value.
+    when_low: Option<(u32, u32)>,
+}
+
+impl TokenBudget {
+    /// Cap responses only by the tokens the rate limit has left.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_per_response: None,
+            when_low: None,
+        }
+    }
+
+    /// Never let a single response exceed `tokens`.
+    #[must_use]
+    pub const fn max_per_response(mut self, tokens: u32) -> Self {
+        self.max_per_response = Some(tokens);
+        self
+    }
+
+    /// Once fewer than `remaining` rate-limit tokens are left, cap each response at
+    /// `tokens`.
+    #[must_use]
+    pub const fn when_low(mut self, remaining: u32, tokens: u32) -> Self {
+        self.when_low = Some((remaining, tokens));
+        self
+    }
+
+    /// The cap for a response with `remaining` rate-limit tokens left, if known; `None`
+    /// when nothing limits it.
+    #[must_use]
+    pub fn limit(&self, remaining: Option<u32>) -> Option<u32> {
+        let low = self.when_low.and_then(|(below, tokens)| {
+            remaining
+                .is_some_and(|remaining| remaining < below)
+                .then_some(tokens)
+        });
+        [self.max_per_response, low, remaining]
+            .into_iter()
+            .flatten()
+            .min()
+            // The API rejects a limit of zero; one token fails fast instead.
+            .map(|limit| limit.max(1))
+    }
+}
+
+/// The event loop's side of the budget: the remaining tokens the server last reported.
+#[derive(Debug, Default)]
+pub struct TokenBudgetState {
+    budget: Option<TokenBudget>,
+    /// Remaining tokens, and when the rate limit resets.
+    remaining: Option<(u32, Instant)>,
+}
+
+impl TokenBudgetState {
+    pub const fn new(budget: Option<TokenBudget>) -> Self {
+        Self {
+            budget,
+            remaining: None,
+        }
+    }
+
+    /// Pick up the remaining tokens from `rate_limits.updated`, received at `now`.
+    pub fn observe(&mut self, evt: &ServerEvent, now: Instant) {
+        let ServerEvent::RateLimitsUpdated { rate_limits, .. } = evt else {
+            return;
+        };
+        if let Some(limit) = rate_limits.iter().find(|limit| limit.name == TOKENS_LIMIT) {
+            let resets_in = Duration::try_from_secs_f32(limit.reset_seconds).unwrap_or_default();
+            self.remaining = Some((limit.remaining, now + resets_in));
+        }
+    }
+
+    /// Cap `max_output_tokens` on a `response.create` sent at `now`. The remaining
+    /// count no longer applies once the rate limit has reset.
+    pub fn apply(&self, event: &mut ClientEvent, now: Instant) {
+        let (Some(budget), ClientEvent::ResponseCreate { response, .. }) = (self.budget, event)
+        else {
+            return;
+        };
+        let remaining = self
+            .remaining
+            .filter(|(_, resets_at)| now < *resets_at)
+            .map(|(remaining, _)| remaining);
+        let Some(limit) = budget.limit(remaining) else {
+            return;
+        };
+        let config = response.get_or_insert_with(Box::default);
+        let limit = match config.max_output_tokens {
+            Some(MaxTokens::Count(requested)) => requested.min(limit),
+            _ => limit,
+        };
+        config.max_output_tokens = Some(MaxTokens::Count(limit));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::models::{Infinite, ResponseConfig};
+    use crate::protocol::server_events::RateLimit;
+
+    fn rate_limits(remaining: u32, reset_seconds: f32) -> ServerEvent {
+        ServerEvent::RateLimitsUpdated {
+            event_id: "evt_1".to_string(),
+            rate_limits: vec![
+                RateLimit {
+                    name: "requests".to_string(),
+                    limit: 100,
+                    remaining: 0,
+                    reset_seconds,
+                },
+                RateLimit {
+                    name: TOKENS_LIMIT.to_string(),
+                    limit: 50_000,
+                    remaining,
+                    reset_seconds,
+                },
+            ],
+        }
+    }
+
+    fn capped(
+        state: &TokenBudgetState,
+        response: Option<ResponseConfig>,
+        now: Instant,
+    ) -> Option<MaxTokens> {
+        let mut event = ClientEvent::ResponseCreate {
+            event_id: None,
+            response: response.map(Box::new),
+        };
+        state.apply(&mut event, now);
+        let ClientEvent::ResponseCreate { response, .. } = event else {
+            unreachable!();
+        };
+        response.and_then(|response| response.max_output_tokens)
+    }
+
+    #[test]
+    fn limit_takes_the_tightest_cap() {
+        let budget = TokenBudget::new()
+            .max_per_response(4_000)
+            .when_low(10_000, 500);
+        assert_eq!(budget.limit(None), Some(4_000));
+        assert_eq!(budget.limit(Some(20_000)), Some(4_000));
+        assert_eq!(budget.limit(Some(9_000)), Some(500));
+        assert_eq!(budget.limit(Some(200)), Some(200));
+        assert_eq!(budget.limit(Some(0)), Some(1));
+        assert_eq!(TokenBudget::new().limit(None), None);
+    }
+
+    #[test]
+    fn responses_are_capped_until_the_rate_limit_resets() {
+        let now = Instant::now();
+        let mut state = TokenBudgetState::new(Some(TokenBudget::new().when_low(10_000, 500)));
+        assert_eq!(capped(&state, None, now), None);
+
+        state.observe(&rate_limits(8_000, 30.0), now);
+        assert_eq!(capped(&state, None, now), Some(MaxTokens::Count(500)));
+        let requested = ResponseConfig {
+            max_output_tokens: Some(MaxTokens::Count(100)),
+            ..ResponseConfig::default()
+        };
+        assert_eq!(
+            capped(&state, Some(requested), now),
+            Some(MaxTokens::Count(100))
+        );
+        let unlimited = ResponseConfig {
+            max_output_tokens: Some(MaxTokens::Infinite(Infinite::Inf)),
+            ..ResponseConfig::default()
+        };
+        assert_eq!(
+            capped(&state, Some(unlimited), now),
+            Some(MaxTokens::Count(500))
+        );
+
+        let later = now + Duration::from_secs(31);
+        assert_eq!(capped(&state, None, later), None);
+        assert_eq!(capped(&TokenBudgetState::default(), None, now), None);
+    }
+}
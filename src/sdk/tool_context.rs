@@ -0,0 +1,140 @@
+//! What a tool handler can reach besides its arguments: the session, the call's IDs
+//! and a cancellation signal.
+
+use super::session::SessionHandle;
+use super::tools::ToolCall;
+use crate::protocol::client_events::ClientEvent;
+use crate::{Error, Result};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+/// Tells a running tool, or a session given it with `RealtimeBuilder::cancellation`,
+/// that its result is no longer wanted: tokio-util's token, so a session or a single
+/// call can hang off an application's own token with `child_token`.
+///
+/// The session cancels a tool call when the response that made it ends as cancelled,
+/// e.g. after a barge-in, and when the session closes.
+pub use tokio_util::sync::CancellationToken;
+
+/// Run `future` to completion unless `session`, or `operation` if given, is cancelled
+/// first, in which case it is dropped.
+///
+/// # Errors
+/// Returns `Error::Cancelled` if either token is, or becomes, cancelled before
+/// `future` completes.
+pub async fn run_until_cancelled<F: Future>(
+    session: &CancellationToken,
+    operation: Option<&CancellationToken>,
+    future: F,
+) -> Result<F::Output> {
+    let operation = operation.cloned().unwrap_or_default();
+    tokio::select! {
+        biased;
+        () = session.cancelled() => Err(Error::Cancelled),
+        () = operation.cancelled() => Err(Error::Cancelled),
+        output = future => Ok(output),
+    }
+}
+
+/// The session side of a tool call, passed to tools registered with
+/// `ToolRegistry::tool_with_context`.
+///
+/// Tools run alongside the session's event loop, so the handle can be used freely,
+/// e.g. to send an interim message while a slow lookup runs.
+#[derive(Clone)]
+pub struct ToolContext {
+    session: SessionHandle,
+    call_id: String,
+    response_id: Option<String>,
+    item_id: Option<String>,
+    cancellation: CancellationToken,
+    deferred: Arc<AtomicBool>,
+}
+
+impl ToolContext {
+    pub(crate) fn new(
+        session: SessionHandle,
+        call: &ToolCall,
+        cancellation: CancellationToken,
+        deferred: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            session,
+            call_id: call.call_id.clone(),
+            response_id: call.response_id.clone(),
+            item_id: call.item_id.clone(),
+            cancellation,
+            deferred,
+        }
+    }
+
+    #[must_use]
+    pub const fn session(&self) -> &SessionHandle {
+        &self.session
+    }
+
+    #[must_use]
+    pub fn call_id(&self) -> &str {
+        &self.call_id
+    }
+
+    /// The response that made the call.
+    #[must_use]
+    pub fn response_id(&self) -> Option<&str> {
+        self.response_id.as_deref()
+    }
+
+    /// The `function_call` item.
+    #[must_use]
+    pub fn item_id(&self) -> Option<&str> {
+        self.item_id.as_deref()
+    }
+
+    #[must_use]
+    pub const fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Answer the call with a placeholder: the output the tool returns, e.g.
+    /// `"working on it"`, is sent now and the real result later with
+    /// `SessionHandle::complete_tool`, so a slow tool does not hold up the
+    /// conversation.
+    pub fn defer(&self) {
+        self.deferred.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`ToolContext::defer`] was called.
+    #[must_use]
+    pub fn is_deferred(&self) -> bool {
+        self.deferred.load(Ordering::Relaxed)
+    }
+
+    /// Cancel the response that made the call, if it is still running.
+    ///
+    /// # Errors
+    /// Returns an error if the send fails.
+    pub async fn cancel_response(&self) -> Result<()> {
+        self.session
+            .send_raw(ClientEvent::ResponseCancel {
+                event_id: None,
+                response_id: self.response_id.clone(),
+            })
+            .await
+    }
+}
+
+impl std::fmt::Debug for ToolContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolContext")
+            .field("call_id", &self.call_id)
+            .field("response_id", &self.response_id)
+            .field("item_id", &self.item_id)
+            .field("cancelled", &self.is_cancelled())
+            .field("deferred", &self.is_deferred())
+            .finish_non_exhaustive()
+    }
+}
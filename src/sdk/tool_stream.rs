@@ -0,0 +1,268 @@
+//! Incremental view of function call arguments as they stream in.
+
+use super::events::SdkEvent;
+use serde_json::Value;
+
+/// Accumulates `response.function_call_arguments.delta` payloads for one call.
+///
+/// Feed it every [`SdkEvent`] with [`ToolCallStream::observe`]; events for other
+/// calls are ignored.
+#[derive(Debug, Clone)]
+pub struct ToolCallStream {
+    call_id: String,
+    name: String,
+    arguments: String,
+    done: bool,
+}
+
+impl ToolCallStream {
+    #[must_use]
+    pub fn new(call_id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            call_id: call_id.into(),
+            name: name.into(),
+            arguments: String::new(),
+            done: false,
+        }
+    }
+
+    /// Start tracking the call announced by a `ToolCallStarted` event.
+    #[must_use]
+    pub fn from_started(event: &SdkEvent) -> Option<Self> {
+        match event {
+            SdkEvent::ToolCallStarted { call_id, name, .. } => Some(Self::new(call_id, name)),
+            _ => None,
+        }
+    }
+
+    /// Apply an event to this call. Returns `true` if the event belonged to it.
+    pub fn observe(&mut self, event: &SdkEvent) -> bool {
+        match event {
+            SdkEvent::ToolCallDelta { call_id, delta, .. } if *call_id == self.call_id => {
+                if !self.done {
+                    self.arguments.push_str(delta);
+                }
+                true
+            }
+            SdkEvent::ToolCall {
+                call_id, arguments, ..
+            } if *call_id == self.call_id => {
+                self.arguments.clone_from(arguments);
+                self.done = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn push_delta(&mut self, delta: &str) {
+        self.arguments.push_str(delta);
+    }
+
+    #[must_use]
+    pub fn call_id(&self) -> &str {
+        &self.call_id
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Raw argument text received so far.
+    #[must_use]
+    pub fn arguments(&self) -> &str {
+        &self.arguments
+    }
+
+    /// Whether the final arguments have been received.
+    #[must_use]
+    pub const fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Best-effort parse of the arguments received so far.
+    ///
+    /// Open objects and arrays are closed and a trailing string value is kept as
+    /// far as it has streamed. Keys without a value and unfinished numbers or
+    /// literals are left out until they complete.
+    #[must_use]
+    pub fn partial_json(&self) -> Option<Value> {
+        parse_partial(&self.arguments)
+    }
+}
+
+/// Parse a JSON prefix, closing whatever is still open.
+#[must_use]
+pub fn parse_partial(input: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(input) {
+        return Some(value);
+    }
+    let scan = Scan::run(input);
+    scan.open_string
+        .and_then(|candidate| serde_json::from_str(&candidate).ok())
+        .or_else(|| {
+            let (end, closers) = scan.safe?;
+            serde_json::from_str(&format!("{}{closers}", &input[..end])).ok()
+        })
+}
+
+struct Frame {
+    closer: char,
+    expecting_key: bool,
+}
+
+#[derive(Default)]
+struct Scan {
+    /// Repaired input ending in an unterminated string value, if any.
+    open_string: Option<String>,
+    /// Last position after a complete value, with the closers needed there.
+    safe: Option<(usize, String)>,
+}
+
+impl Scan {
+    fn run(input: &str) -> Self {
+        let mut scan = Self::default();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut in_string = false;
+        let mut string_is_key = false;
+        let mut escape_start: Option<usize> = None;
+        let mut unicode_remaining = 0u8;
+        let mut in_scalar = false;
+
+        for (i, b) in input.bytes().enumerate() {
+            if in_string {
+                if unicode_remaining > 0 {
+                    unicode_remaining -= 1;
+                    if unicode_remaining == 0 {
+                        escape_start = None;
+                    }
+                } else if escape_start.is_some() {
+                    if b == b'u' {
+                        unicode_remaining = 4;
+                    } else {
+                        escape_start = None;
+                    }
+                } else if b == b'\\' {
+                    escape_start = Some(i);
+                } else if b == b'"' {
+                    in_string = false;
+                    if !string_is_key {
+                        scan.safe = Some((i + 1, closers(&stack)));
+                    }
+                }
+                continue;
+            }
+
+            if in_scalar && matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                in_scalar = false;
+                scan.safe = Some((i, closers(&stack)));
+            }
+            match b {
+                b'{' | b'[' => {
+                    stack.push(Frame {
+                        closer: if b == b'{' { '}' } else { ']' },
+                        expecting_key: b == b'{',
+                    });
+                    scan.safe = Some((i + 1, closers(&stack)));
+                }
+                b'}' | b']' => {
+                    stack.pop();
+                    scan.safe = Some((i + 1, closers(&stack)));
+                }
+                b'"' => {
+                    in_string = true;
+                    string_is_key = stack.last().is_some_and(|frame| frame.expecting_key);
+                }
+                b':' => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.expecting_key = false;
+                    }
+                }
+                b',' => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.expecting_key = frame.closer == '}';
+                    }
+                }
+                b' ' | b'\t' | b'\n' | b'\r' => {}
+                _ => in_scalar = true,
+            }
+        }
+
+        if in_string && !string_is_key {
+            let end = escape_start.unwrap_or(input.len());
+            scan.open_string = Some(format!("{}\"{}", &input[..end], closers(&stack)));
+        }
+        scan
+    }
+}
+
+fn closers(stack: &[Frame]) -> String {
+    stack.iter().rev().map(|frame| frame.closer).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn partial_prefixes_parse_best_effort() {
+        let cases = [
+            ("", None),
+            ("{", Some(json!({}))),
+            ("{\"query\": \"wea", Some(json!({"query": "wea"}))),
+            (
+                "{\"query\": \"weather\", \"ci",
+                Some(json!({"query": "weather"})),
+            ),
+            ("{\"limit\": 1", Some(json!({}))),
+            ("{\"limit\": 10,", Some(json!({"limit": 10}))),
+            ("{\"ok\": tru", Some(json!({}))),
+            ("{\"tags\": [\"a\", \"b", Some(json!({"tags": ["a", "b"]}))),
+            ("{\"a\": {\"b\": [1, 2", Some(json!({"a": {"b": [1]}}))),
+            ("{\"q\": \"line\\", Some(json!({"q": "line"}))),
+            ("{\"q\": \"caf\\u00", Some(json!({"q": "caf"}))),
+            ("{\"q\": \"caf\\u00e9\"}", Some(json!({"q": "café"}))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_partial(input), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn stream_tracks_only_its_call() {
+        let started = SdkEvent::ToolCallStarted {
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            call_id: "call_1".to_string(),
+            name: "search".to_string(),
+        };
+        let delta = |call_id: &str, delta: &str| SdkEvent::ToolCallDelta {
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            call_id: call_id.to_string(),
+            delta: delta.to_string(),
+        };
+
+        let mut stream = ToolCallStream::from_started(&started).expect("started");
+        assert_eq!(stream.name(), "search");
+        assert!(stream.observe(&delta("call_1", "{\"query\":\"rust")));
+        assert!(!stream.observe(&delta("call_2", "{\"other\":1}")));
+        assert_eq!(stream.partial_json(), Some(json!({"query": "rust"})));
+        assert!(!stream.is_done());
+
+        assert!(stream.observe(&SdkEvent::ToolCall {
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            call_id: "call_1".to_string(),
+            name: "search".to_string(),
+            arguments: "{\"query\":\"rust sdk\"}".to_string(),
+        }));
+        assert!(stream.is_done());
+        assert_eq!(stream.partial_json(), Some(json!({"query": "rust sdk"})));
+    }
+}
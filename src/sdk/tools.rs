@@ -8,42 +8,220 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::schema;
+use super::tool_context::ToolContext;
 
 pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
 
-type ToolHandler = Box<dyn Fn(Value) -> BoxFuture<Result<Value>> + Send + Sync>;
+type ToolHandler =
+    Box<dyn Fn(Value, Option<ToolContext>) -> BoxFuture<Result<Value>> + Send + Sync>;
 
+/// Runs the session's tool calls.
+///
+/// `ToolRegistry` is the built-in implementation. Implement this to route calls
+/// elsewhere, e.g. to a remote execution service or a per-tenant registry, and pass it
+/// to `RealtimeBuilder::dispatcher`.
 #[async_trait::async_trait]
 pub trait ToolDispatcher: Send + Sync {
+    /// Run `call`. An error is reported to the model according to the session's
+    /// `ToolErrorPolicy`.
     async fn dispatch(&self, call: ToolCall) -> Result<ToolResult>;
+    /// Run `call` made in a session, with its `ToolContext`. Defaults to `dispatch`,
+    /// ignoring the context.
+    async fn dispatch_with_context(&self, call: ToolCall, ctx: ToolContext) -> Result<ToolResult> {
+        let _ = ctx;
+        self.dispatch(call).await
+    }
+    /// Tools announced in the session config.
     fn tool_definitions(&self) -> Vec<crate::protocol::models::Tool>;
-    #[allow(clippy::result_large_err)]
+    /// Like `tool_definitions`, reporting definitions that cannot be built.
+    ///
+    /// # Errors
+    /// Returns an error if a definition cannot be built.
     fn try_tool_definitions(&self) -> Result<Vec<crate::protocol::models::Tool>> {
         Ok(self.tool_definitions())
     }
+    /// What the session does with the successful output of tool `name`.
+    fn output_options(&self, name: &str) -> ToolOutputOptions {
+        let _ = name;
+        ToolOutputOptions::default()
+    }
+    /// Hooks that follow each call's arguments as they stream in.
+    fn middleware(&self) -> Option<&dyn ToolMiddleware> {
+        None
+    }
 }
 
-#[derive(Clone, Debug)]
+/// Follows a tool call from its `response.output_item.added` through the streamed
+/// arguments to the point it is about to run.
+///
+/// Use it to drive a progress UI or to check arguments before the tool sees them.
+/// Every hook defaults to doing nothing. `on_tool_call_started` and
+/// `on_arguments_delta` run on the session's event loop and must not block;
+/// `on_tool_call_ready` runs on the call's own task, just before the tool.
+pub trait ToolMiddleware: Send + Sync {
+    /// The model started a call to tool `name`.
+    fn on_tool_call_started(&self, call_id: &str, name: &str) {
+        let _ = (call_id, name);
+    }
+    /// The next piece of a call's argument JSON.
+    fn on_arguments_delta(&self, call_id: &str, chunk: &str) {
+        let _ = (call_id, chunk);
+    }
+    /// The arguments are complete and the call is about to run. An error stops it,
+    /// and is reported to the model like a failed call under the session's
+    /// `ToolErrorPolicy`.
+    ///
+    /// # Errors
+    /// Returns an error to reject the call.
+    fn on_tool_call_ready(&self, call: &ToolCall) -> Result<()> {
+        let _ = call;
+        Ok(())
+    }
+}
+
+/// What the session does with a tool's successful output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToolOutputOptions {
+    /// Send `response.create` after the output. `None` follows the session's
+    /// `auto_tool_response`.
+    pub follow_up: Option<bool>,
+    /// Add the output to the conversation as a `function_call_output` item. When
+    /// off, no response follows either.
+    pub send_output: bool,
+}
+
+impl Default for ToolOutputOptions {
+    fn default() -> Self {
+        Self {
+            follow_up: None,
+            send_output: true,
+        }
+    }
+}
+
+/// Post-processing applied to a tool's serialized parameter schema.
+pub type SchemaHook = Arc<dyn Fn(&mut Value) + Send + Sync>;
+
+#[derive(Clone)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: Option<String>,
     pub schema: RootSchema,
+    /// Send the strict schema shape: `$ref`s inlined, every property required (optional
+    /// ones nullable) and `additionalProperties: false` on every object.
+    strict: bool,
+    /// Runs on the serialized schema after the strict rewrite.
+    schema_hook: Option<SchemaHook>,
+    output: ToolOutputOptions,
 }
 
 impl ToolDefinition {
-    #[allow(clippy::result_large_err)]
-    pub(crate) fn try_as_tool(&self) -> Result<Tool> {
-        let parameters = serde_json::to_value(&self.schema)
+    #[must_use]
+    pub const fn new(name: String, description: Option<String>, schema: RootSchema) -> Self {
+        Self {
+            name,
+            description,
+            schema,
+            strict: false,
+            schema_hook: None,
+            output: ToolOutputOptions {
+                follow_up: None,
+                send_output: true,
+            },
+        }
+    }
+
+    /// Have the model follow the schema exactly, sending it in the strict shape.
+    pub const fn strict(&mut self) -> &mut Self {
+        self.strict = true;
+        self
+    }
+
+    /// Whether [`strict`](Self::strict) was set.
+    #[must_use]
+    pub const fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The hook set with [`map_schema`](Self::map_schema), if any.
+    #[must_use]
+    pub const fn schema_hook(&self) -> Option<&SchemaHook> {
+        self.schema_hook.as_ref()
+    }
+
+    /// Whether a `response.create` follows this tool's output, overriding the
+    /// session's `auto_tool_response`.
+    pub const fn follow_up(&mut self, enabled: bool) -> &mut Self {
+        self.output.follow_up = Some(enabled);
+        self
+    }
+
+    /// Run the tool for its side effects only: its output is not added to the
+    /// conversation and no response follows. Failures are still reported according
+    /// to the session's `ToolErrorPolicy`.
+    pub const fn discard_output(&mut self) -> &mut Self {
+        self.output.send_output = false;
+        self
+    }
+
+    /// What the session does with this tool's output, as set by
+    /// [`follow_up`](Self::follow_up) and [`discard_output`](Self::discard_output).
+    #[must_use]
+    pub const fn output(&self) -> ToolOutputOptions {
+        self.output
+    }
+
+    /// Edit the generated schema before it is sent, e.g. to reword a property
+    /// description or drop keywords the API rejects.
+    pub fn map_schema(&mut self, hook: impl Fn(&mut Value) + Send + Sync + 'static) -> &mut Self {
+        self.schema_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// The parameter schema as sent to the server.
+    ///
+    /// # Errors
+    /// Returns an error if the schema fails to serialize or cannot be made strict.
+    pub fn parameters(&self) -> Result<Value> {
+        let mut parameters = serde_json::to_value(&self.schema)
             .map_err(|e| crate::Error::InvalidClientEvent(e.to_string()))?;
+        if self.strict {
+            schema::make_strict(&mut parameters).map_err(|e| {
+                crate::Error::InvalidClientEvent(format!("tool {}: {e}", self.name))
+            })?;
+        }
+        if let Some(hook) = &self.schema_hook {
+            hook(&mut parameters);
+        }
+        Ok(parameters)
+    }
+
+    pub(crate) fn try_as_tool(&self) -> Result<Tool> {
         Ok(Tool::Function {
             name: self.name.clone(),
             description: self.description.clone(),
-            parameters,
+            parameters: self.parameters()?,
+            strict: self.strict.then_some(true),
         })
     }
 }
 
+impl std::fmt::Debug for ToolDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolDefinition")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("schema", &self.schema)
+            .field("strict", &self.strict)
+            .field("schema_hook", &self.schema_hook.as_ref().map(|_| ".."))
+            .field("output", &self.output)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ToolCall {
     pub name: String,
@@ -60,11 +238,81 @@ pub struct ToolResult {
     pub output: Value,
 }
 
+/// System note that carries a deferred tool result into the conversation.
+pub fn deferred_result_note(name: &str, call_id: &str, output: &Value) -> String {
+    format!("The `{name}` tool call `{call_id}` has finished with result: {output}")
+}
+
+/// A failed tool call, as passed to [`ToolErrorPolicy::Custom`].
+#[derive(Clone, Debug)]
+pub struct ToolFailure {
+    pub call_id: String,
+    pub name: String,
+    pub message: String,
+    /// The handler panicked instead of returning an error.
+    pub panicked: bool,
+}
+
+/// How the session runs tool calls that arrive while another is still running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToolConcurrency {
+    /// One at a time, in the order they were made.
+    #[default]
+    Sequential,
+    /// Each as soon as it arrives.
+    Concurrent,
+}
+
+pub type ToolErrorHandler = Arc<dyn Fn(&ToolFailure) -> Option<Value> + Send + Sync>;
+
+/// What the model is told when a tool handler fails.
+#[derive(Clone, Default)]
+pub enum ToolErrorPolicy {
+    /// Send `{"error": message}` as the function call output.
+    #[default]
+    SendError,
+    /// Send nothing; the application reports the failure itself.
+    Ignore,
+    /// Send the returned value as the output, or nothing for `None`.
+    Custom(ToolErrorHandler),
+}
+
+impl ToolErrorPolicy {
+    #[must_use]
+    pub fn custom<F>(handler: F) -> Self
+    where
+        F: Fn(&ToolFailure) -> Option<Value> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(handler))
+    }
+
+    /// The function call output to send for `failure`, if any.
+    #[must_use]
+    pub fn output(&self, failure: &ToolFailure) -> Option<Value> {
+        match self {
+            Self::SendError => Some(serde_json::json!({ "error": failure.message })),
+            Self::Ignore => None,
+            Self::Custom(handler) => handler(failure),
+        }
+    }
+}
+
+impl std::fmt::Debug for ToolErrorPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SendError => f.write_str("SendError"),
+            Self::Ignore => f.write_str("Ignore"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct ToolRegistry {
     defs: Vec<ToolDefinition>,
     handlers: HashMap<String, ToolHandler>,
     mcp: Vec<McpToolConfig>,
+    middleware: Option<Box<dyn ToolMiddleware>>,
 }
 
 impl ToolRegistry {
@@ -78,11 +326,23 @@ impl ToolRegistry {
         &self.defs
     }
 
+    /// The definition of tool `name`, to adjust its schema after registration.
+    pub fn definition_mut(&mut self, name: &str) -> Option<&mut ToolDefinition> {
+        self.defs.iter_mut().find(|def| def.name == name)
+    }
+
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.defs.is_empty() && self.mcp.is_empty()
     }
 
+    /// Follow calls to this registry's tools with `middleware`, replacing any set
+    /// before.
+    pub fn set_middleware(&mut self, middleware: impl ToolMiddleware + 'static) -> &mut Self {
+        self.middleware = Some(Box::new(middleware));
+        self
+    }
+
     pub fn tool<TArgs, TResp, F, Fut>(&mut self, name: &str, handler: F)
     where
         TArgs: DeserializeOwned + JsonSchema + Send + 'static,
@@ -132,17 +392,13 @@ impl ToolRegistry {
         F: Fn(TArgs) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Result<TResp>> + Send + 'static,
     {
-        let schema = schemars::schema_for!(TArgs);
         let name = name.to_string();
-        let entry = ToolDefinition {
-            name: name.clone(),
-            description,
-            schema,
-        };
-        self.defs.push(entry);
+        let schema = schemars::schema_for!(TArgs);
+        self.defs
+            .push(ToolDefinition::new(name.clone(), description, schema));
 
         let user_handler = Arc::new(handler);
-        let handler = move |value: Value| -> BoxFuture<Result<Value>> {
+        let handler = move |value: Value, _: Option<ToolContext>| -> BoxFuture<Result<Value>> {
             let user_handler = Arc::clone(&user_handler);
             Box::pin(async move {
                 let args: TArgs = serde_json::from_value(value)
@@ -156,17 +412,62 @@ impl ToolRegistry {
         self.handlers.insert(name, Box::new(handler));
     }
 
+    /// Register a tool whose handler also gets a [`ToolContext`]: a `SessionHandle`,
+    /// the call's IDs and a cancellation token. Calls dispatched outside a session
+    /// fail, as there is no context to give.
+    ///
+    /// The handler may keep state between calls; it is called under a lock, and the
+    /// future it returns runs after the lock is released.
+    pub fn tool_with_context<TArgs, TResp, F, Fut>(&mut self, name: &str, handler: F)
+    where
+        TArgs: DeserializeOwned + JsonSchema + Send + 'static,
+        TResp: Serialize + Send + 'static,
+        F: FnMut(ToolContext, TArgs) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<TResp>> + Send + 'static,
+    {
+        let name = name.to_string();
+        let schema = schemars::schema_for!(TArgs);
+        self.defs
+            .push(ToolDefinition::new(name.clone(), None, schema));
+
+        let user_handler = Arc::new(Mutex::new(handler));
+        let tool = name.clone();
+        let handler = move |value: Value,
+                            context: Option<ToolContext>|
+              -> BoxFuture<Result<Value>> {
+            let user_handler = Arc::clone(&user_handler);
+            let tool = tool.clone();
+            Box::pin(async move {
+                let context = context.ok_or_else(|| {
+                    crate::Error::InvalidClientEvent(format!(
+                        "tool `{tool}` needs a session context"
+                    ))
+                })?;
+                let args: TArgs = serde_json::from_value(value)
+                    .map_err(|e| crate::Error::InvalidClientEvent(e.to_string()))?;
+                let future = {
+                    let mut handler = user_handler.lock().unwrap_or_else(PoisonError::into_inner);
+                    handler(context, args)
+                };
+                let resp = future.await?;
+                serde_json::to_value(resp)
+                    .map_err(|e| crate::Error::InvalidClientEvent(e.to_string()))
+            })
+        };
+
+        self.handlers.insert(name, Box::new(handler));
+    }
+
     pub fn register<T: ToolSpec>(&mut self, tool: T) {
         let schema = schemars::schema_for!(T::Args);
-        let entry = ToolDefinition {
-            name: T::NAME.to_string(),
-            description: T::DESCRIPTION.map(ToString::to_string),
+        self.defs.push(ToolDefinition::new(
+            T::NAME.to_string(),
+            T::DESCRIPTION.map(ToString::to_string),
             schema,
-        };
-        self.defs.push(entry);
+        ));
 
         let tool = Arc::new(tool);
-        let handler = move |value: Value| -> BoxFuture<Result<Value>> {
+        let handler = move |value: Value, _: Option<ToolContext>| -> BoxFuture<Result<Value>> {
             let tool = Arc::clone(&tool);
             Box::pin(async move {
                 let args: T::Args = serde_json::from_value(value)
@@ -184,8 +485,6 @@ impl ToolRegistry {
     ///
     /// # Errors
     /// Returns an error if the MCP config is invalid.
-    // Keep a single public error type for the SDK surface.
-    #[allow(clippy::result_large_err)]
     pub fn mcp_tool(&mut self, config: McpToolConfig) -> Result<()> {
         config.validate()?;
         self.mcp.push(config);
@@ -196,8 +495,6 @@ impl ToolRegistry {
     ///
     /// # Errors
     /// Returns an error if schema serialization fails.
-    // Keep a single public error type for the SDK surface.
-    #[allow(clippy::result_large_err)]
     pub fn try_as_tools(&self) -> Result<Vec<Tool>> {
         let mut tools = Vec::with_capacity(self.defs.len() + self.mcp.len());
         for def in &self.defs {
@@ -210,18 +507,28 @@ impl ToolRegistry {
     }
 }
 
-#[async_trait::async_trait]
-impl ToolDispatcher for ToolRegistry {
-    async fn dispatch(&self, call: ToolCall) -> Result<ToolResult> {
+impl ToolRegistry {
+    async fn run(&self, call: ToolCall, ctx: Option<ToolContext>) -> Result<ToolResult> {
         let handler = self.handlers.get(&call.name).ok_or_else(|| {
             crate::Error::InvalidClientEvent(format!("unknown tool: {}", call.name))
         })?;
-        let output = handler(call.arguments).await?;
+        let output = handler(call.arguments, ctx).await?;
         Ok(ToolResult {
             call_id: call.call_id,
             output,
         })
     }
+}
+
+#[async_trait::async_trait]
+impl ToolDispatcher for ToolRegistry {
+    async fn dispatch(&self, call: ToolCall) -> Result<ToolResult> {
+        self.run(call, None).await
+    }
+
+    async fn dispatch_with_context(&self, call: ToolCall, ctx: ToolContext) -> Result<ToolResult> {
+        self.run(call, Some(ctx)).await
+    }
 
     fn tool_definitions(&self) -> Vec<crate::protocol::models::Tool> {
         self.try_as_tools().unwrap_or_else(|err| {
@@ -233,6 +540,17 @@ impl ToolDispatcher for ToolRegistry {
     fn try_tool_definitions(&self) -> Result<Vec<crate::protocol::models::Tool>> {
         self.try_as_tools()
     }
+
+    fn output_options(&self, name: &str) -> ToolOutputOptions {
+        self.defs
+            .iter()
+            .find(|def| def.name == name)
+            .map_or_else(ToolOutputOptions::default, ToolDefinition::output)
+    }
+
+    fn middleware(&self) -> Option<&dyn ToolMiddleware> {
+        self.middleware.as_deref()
+    }
 }
 
 pub trait ToolSpec: Send + Sync + 'static {
@@ -259,13 +577,13 @@ macro_rules! realtime_tool {
         $(#[$meta])*
         pub struct $name;
 
-        impl $crate::ToolSpec for $name {
+        impl $crate::sdk::ToolSpec for $name {
             type Args = $args;
             type Output = $resp;
             const NAME: &'static str = $tool_name;
             const DESCRIPTION: Option<&'static str> = Some($desc);
 
-            fn call(&self, args: Self::Args) -> $crate::ToolFuture<$crate::Result<Self::Output>> {
+            fn call(&self, args: Self::Args) -> $crate::sdk::ToolFuture<$crate::Result<Self::Output>> {
                 let fut = $body;
                 Box::pin(fut(args))
             }
@@ -313,4 +631,57 @@ mod tests {
             other @ Tool::Mcp(_) => panic!("unexpected tool: {other:?}"),
         }
     }
+
+    #[derive(serde::Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[derive(serde::Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct ShipArgs {
+        order: u32,
+        address: Address,
+        note: Option<String>,
+    }
+
+    #[test]
+    fn strict_schema_inlines_refs_and_requires_everything() {
+        let mut tools = ToolRegistry::new();
+        tools.tool("ship", |_: ShipArgs| async move { Ok(()) });
+        tools
+            .definition_mut("ship")
+            .unwrap()
+            .strict()
+            .map_schema(|schema| {
+                schema["properties"]["order"]["description"] = "Order number".into();
+            });
+
+        let def = &tools.definitions()[0];
+        assert!(def.is_strict());
+        let tool = serde_json::to_value(def.try_as_tool().unwrap()).unwrap();
+        assert_eq!(tool["strict"], true);
+        let parameters = def.parameters().unwrap();
+        assert!(parameters.get("definitions").is_none());
+        assert_eq!(parameters["additionalProperties"], false);
+        assert_eq!(
+            parameters["required"],
+            serde_json::json!(["address", "note", "order"])
+        );
+        assert_eq!(
+            parameters["properties"]["note"]["type"],
+            serde_json::json!(["string", "null"])
+        );
+        assert_eq!(
+            parameters["properties"]["order"]["description"],
+            "Order number"
+        );
+
+        let address = &parameters["properties"]["address"];
+        assert!(address.get("$ref").is_none());
+        assert_eq!(address["additionalProperties"], false);
+        assert_eq!(address["required"], serde_json::json!(["city", "zip"]));
+    }
 }
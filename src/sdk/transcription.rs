@@ -0,0 +1,179 @@
+//! Recovery from failed input audio transcriptions.
+
+use crate::error::ServerError;
+use crate::protocol::client_events::ClientEvent;
+use crate::protocol::server_events::ServerEvent;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Committed items whose audio is kept in case their transcription fails.
+const RETAINED_ITEMS: usize = 8;
+
+/// A failed transcription of user audio, as passed to a custom policy.
+#[derive(Debug, Clone)]
+pub struct TranscriptionFailure {
+    pub item_id: String,
+    pub content_index: u32,
+    pub error: ServerError,
+}
+
+pub type TranscriptionFailureHandler = Arc<dyn Fn(&TranscriptionFailure) + Send + Sync>;
+
+/// What the session does when `conversation.item.input_audio_transcription.failed`
+/// arrives. `SdkEvent::InputTranscriptionFailed` is emitted in every case.
+#[derive(Clone, Default)]
+pub enum TranscriptionFailurePolicy {
+    /// Only report the failure.
+    #[default]
+    Report,
+    /// Delete the failed item and commit its audio again, up to `max_attempts` times
+    /// for the same utterance.
+    ///
+    /// The session keeps the audio appended since the last commit for this. It suits
+    /// push-to-talk and manual commits: audio appended after the failed commit is
+    /// committed along with the retry.
+    Recommit { max_attempts: u32 },
+    /// Call the handler, e.g. to have the agent ask the user to repeat themselves.
+    Custom(TranscriptionFailureHandler),
+}
+
+impl TranscriptionFailurePolicy {
+    #[must_use]
+    pub fn custom<F>(handler: F) -> Self
+    where
+        F: Fn(&TranscriptionFailure) + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(handler))
+    }
+}
+
+impl std::fmt::Debug for TranscriptionFailurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Report => f.write_str("Report"),
+            Self::Recommit { max_attempts } => f
+                .debug_struct("Recommit")
+                .field("max_attempts", max_attempts)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Audio of recently committed items, for `TranscriptionFailurePolicy::Recommit`.
+#[derive(Debug, Default)]
+pub struct RecommitBuffer {
+    /// Audio is only kept under `Recommit`.
+    enabled: bool,
+    /// Base64 chunks appended since the last commit or clear.
+    pending: Vec<String>,
+    /// Retries already spent on the audio in `pending`.
+    pending_attempts: u32,
+    committed: HashMap<String, (Vec<String>, u32)>,
+    order: VecDeque<String>,
+}
+
+impl RecommitBuffer {
+    pub fn for_policy(policy: &TranscriptionFailurePolicy) -> Self {
+        Self {
+            enabled: matches!(policy, TranscriptionFailurePolicy::Recommit { .. }),
+            ..Self::default()
+        }
+    }
+
+    pub fn observe_client(&mut self, event: &ClientEvent) {
+        if !self.enabled {
+            return;
+        }
+        match event {
+            ClientEvent::InputAudioBufferAppend { audio, .. } => self.pending.push(audio.clone()),
+            ClientEvent::InputAudioBufferClear { .. } => self.reset_pending(),
+            _ => {}
+        }
+    }
+
+    pub fn observe_server(&mut self, evt: &ServerEvent) {
+        if !self.enabled {
+            return;
+        }
+        match evt {
+            ServerEvent::InputAudioBufferCommitted { item_id, .. } => {
+                let audio = std::mem::take(&mut self.pending);
+                let attempts = std::mem::take(&mut self.pending_attempts);
+                if self.order.len() == RETAINED_ITEMS {
+                    if let Some(oldest) = self.order.pop_front() {
+                        self.committed.remove(&oldest);
+                    }
+                }
+                self.order.push_back(item_id.clone());
+                self.committed.insert(item_id.clone(), (audio, attempts));
+            }
+            ServerEvent::InputAudioBufferCleared { .. } => self.reset_pending(),
+            ServerEvent::InputAudioTranscriptionCompleted { item_id, .. } => {
+                self.forget(item_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Forget `item_id` and return its audio for the caller to append and commit
+    /// again, or `None` if the item is unknown, had no audio, or has used up
+    /// `max_attempts`. The audio is also recorded at the front of the pending chunks,
+    /// so the item the next commit creates carries the attempt count on.
+    pub fn retry(&mut self, item_id: &str, max_attempts: u32) -> Option<Vec<String>> {
+        let (audio, attempts) = self.forget(item_id)?;
+        if attempts >= max_attempts || audio.is_empty() {
+            return None;
+        }
+        self.pending_attempts = attempts + 1;
+        self.pending.splice(0..0, audio.iter().cloned());
+        Some(audio)
+    }
+
+    fn forget(&mut self, item_id: &str) -> Option<(Vec<String>, u32)> {
+        self.order.retain(|id| id != item_id);
+        self.committed.remove(item_id)
+    }
+
+    fn reset_pending(&mut self) {
+        self.pending.clear();
+        self.pending_attempts = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(audio: &str) -> ClientEvent {
+        ClientEvent::InputAudioBufferAppend {
+            event_id: None,
+            audio: audio.to_string(),
+        }
+    }
+
+    fn committed(item_id: &str) -> ServerEvent {
+        ServerEvent::InputAudioBufferCommitted {
+            event_id: format!("evt_{item_id}"),
+            previous_item_id: None,
+            item_id: item_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn retries_stop_after_max_attempts() {
+        let mut buffer =
+            RecommitBuffer::for_policy(&TranscriptionFailurePolicy::Recommit { max_attempts: 1 });
+        buffer.observe_client(&append("AAAA"));
+        buffer.observe_client(&append("BBBB"));
+        buffer.observe_server(&committed("item_1"));
+
+        assert_eq!(
+            buffer.retry("item_1", 1),
+            Some(vec!["AAAA".into(), "BBBB".into()])
+        );
+        buffer.observe_server(&committed("item_2"));
+        assert_eq!(buffer.retry("item_2", 1), None);
+        assert_eq!(buffer.retry("item_unknown", 1), None);
+    }
+}
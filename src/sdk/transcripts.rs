@@ -0,0 +1,277 @@
+//! One stream of what both sides said, merged from input transcription and output
+//! audio transcript events.
+
+use super::channel::{self, ChannelMonitor, OverflowPolicy, Receiver, Sender};
+use crate::Result;
+use crate::protocol::server_events::ServerEvent;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+/// Input items whose speech span is kept for their transcript, the oldest forgotten
+/// first; items are not always transcribed.
+const RETAINED_SPANS: usize = 16;
+
+/// Who a [`Transcript`] is of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Speaker {
+    User,
+    Assistant,
+}
+
+/// A piece of transcript from [`TranscriptStream`]: a delta, or with `is_final` the
+/// whole transcript of the item's content part.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub speaker: Speaker,
+    pub item_id: String,
+    pub content_index: u32,
+    /// The response that said it; `None` for the user.
+    pub response_id: Option<String>,
+    pub text: String,
+    pub is_final: bool,
+    /// When the event arrived, by the session's clock.
+    pub received_at: SystemTime,
+    /// Where the user's speech starts in the input audio, when server VAD detected it.
+    pub audio_start_ms: Option<u32>,
+    /// Where the user's speech ends in the input audio, once server VAD detected it.
+    pub audio_end_ms: Option<u32>,
+}
+
+/// Transcripts of the user and the assistant in the order their events arrived,
+/// opened with `Session::transcripts`. The stream ends when the session closes.
+///
+/// A user's transcript often completes after the assistant has started answering;
+/// `item_id` places it in the conversation.
+pub struct TranscriptStream {
+    rx: Receiver<Transcript>,
+    monitor: ChannelMonitor,
+}
+
+impl TranscriptStream {
+    /// Await the next transcript delta or final transcript.
+    ///
+    /// # Errors
+    /// Returns `Error::ChannelOverflow` if a reader fell behind under
+    /// `OverflowPolicy::CloseSession`.
+    pub async fn next_transcript(&mut self) -> Result<Option<Transcript>> {
+        match self.rx.recv().await {
+            Some(transcript) => Ok(Some(transcript)),
+            None => self.monitor.closed(),
+        }
+    }
+
+    /// Transcripts discarded because this reader fell behind.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.rx.dropped()
+    }
+}
+
+impl Stream for TranscriptStream {
+    type Item = Transcript;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl std::fmt::Debug for TranscriptStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranscriptStream").finish_non_exhaustive()
+    }
+}
+
+struct Reader {
+    capacity: usize,
+    policy: OverflowPolicy,
+    monitor: ChannelMonitor,
+    tx: Option<Sender<Transcript>>,
+}
+
+/// The event loop's side of the transcript stream. Nothing is built or sent until a
+/// stream is open, so a session nobody reads transcripts from never waits on one.
+pub struct TranscriptFeed {
+    reader: Arc<Mutex<Reader>>,
+    /// Speech detected by server VAD: input item, start and end.
+    spans: VecDeque<(String, u32, Option<u32>)>,
+}
+
+/// The session's side, opening the stream.
+pub struct TranscriptTap {
+    reader: Weak<Mutex<Reader>>,
+    monitor: ChannelMonitor,
+}
+
+impl TranscriptFeed {
+    /// A feed whose stream holds up to `capacity` transcripts under `policy`.
+    pub fn new(
+        capacity: usize,
+        policy: OverflowPolicy,
+        monitor: &ChannelMonitor,
+    ) -> (Self, TranscriptTap) {
+        let reader = Arc::new(Mutex::new(Reader {
+            capacity,
+            policy,
+            monitor: monitor.clone(),
+            tx: None,
+        }));
+        let tap = TranscriptTap {
+            reader: Arc::downgrade(&reader),
+            monitor: monitor.clone(),
+        };
+        let feed = Self {
+            reader,
+            spans: VecDeque::new(),
+        };
+        (feed, tap)
+    }
+
+    /// Pass on the user's side of `evt`, received at `at`, and note where their speech
+    /// starts and ends.
+    pub async fn user(&mut self, evt: &ServerEvent, at: SystemTime) {
+        let (item_id, content_index, text, is_final) = match evt {
+            ServerEvent::InputAudioBufferSpeechStarted {
+                item_id,
+                audio_start_ms,
+                ..
+            } => {
+                if self.spans.len() == RETAINED_SPANS {
+                    self.spans.pop_front();
+                }
+                self.spans
+                    .push_back((item_id.clone(), *audio_start_ms, None));
+                return;
+            }
+            ServerEvent::InputAudioBufferSpeechStopped {
+                item_id,
+                audio_end_ms,
+                ..
+            } => {
+                if let Some(span) = self.spans.iter_mut().find(|span| span.0 == *item_id) {
+                    span.2 = Some(*audio_end_ms);
+                }
+                return;
+            }
+            ServerEvent::InputAudioTranscriptionDelta {
+                item_id,
+                content_index,
+                delta,
+                ..
+            } => (item_id, *content_index, delta, false),
+            ServerEvent::InputAudioTranscriptionCompleted {
+                item_id,
+                content_index,
+                transcript,
+                ..
+            } => (item_id, *content_index, transcript, true),
+            _ => return,
+        };
+        let position = self.spans.iter().position(|span| span.0 == *item_id);
+        let span = position.and_then(|index| {
+            if is_final {
+                self.spans.remove(index)
+            } else {
+                self.spans.get(index).cloned()
+            }
+        });
+        let Some(tx) = self.sender() else {
+            return;
+        };
+        let transcript = Transcript {
+            speaker: Speaker::User,
+            item_id: item_id.clone(),
+            content_index,
+            response_id: None,
+            text: text.clone(),
+            is_final,
+            received_at: at,
+            audio_start_ms: span.as_ref().map(|(_, start, _)| *start),
+            audio_end_ms: span.and_then(|(_, _, end)| end),
+        };
+        let _ = tx.send(transcript).await;
+    }
+
+    /// Pass on the assistant's output audio transcript in `evt`, received at `at`.
+    pub async fn assistant(&self, evt: &ServerEvent, at: SystemTime) {
+        let (response_id, item_id, content_index, text, is_final) = match evt {
+            ServerEvent::ResponseOutputAudioTranscriptDelta {
+                response_id,
+                item_id,
+                content_index,
+                delta,
+                ..
+            } => (response_id, item_id, *content_index, delta, false),
+            ServerEvent::ResponseOutputAudioTranscriptDone {
+                response_id,
+                item_id,
+                content_index,
+                transcript,
+                ..
+            } => (response_id, item_id, *content_index, transcript, true),
+            _ => return,
+        };
+        let Some(tx) = self.sender() else {
+            return;
+        };
+        let transcript = Transcript {
+            speaker: Speaker::Assistant,
+            item_id: item_id.clone(),
+            content_index,
+            response_id: Some(response_id.clone()),
+            text: text.clone(),
+            is_final,
+            received_at: at,
+            audio_start_ms: None,
+            audio_end_ms: None,
+        };
+        let _ = tx.send(transcript).await;
+    }
+
+    /// The open stream's sender, forgetting it once the stream is dropped.
+    fn sender(&self) -> Option<Sender<Transcript>> {
+        let mut reader = lock(&self.reader);
+        if reader.tx.as_ref().is_some_and(Sender::is_closed) {
+            reader.tx = None;
+        }
+        reader.tx.clone()
+    }
+}
+
+impl TranscriptTap {
+    /// Open the stream, replacing any open before.
+    pub fn open(&self) -> TranscriptStream {
+        let Some(reader) = self.reader.upgrade() else {
+            // The session is closed: hand back a stream that has already ended.
+            let (_, rx) =
+                channel::channel("transcript stream", 1, OverflowPolicy::Block, &self.monitor);
+            return self.stream(rx);
+        };
+        let mut reader = lock(&reader);
+        let (tx, rx) = channel::channel(
+            "transcript stream",
+            reader.capacity,
+            reader.policy,
+            &reader.monitor,
+        );
+        reader.tx = Some(tx);
+        drop(reader);
+        self.stream(rx)
+    }
+
+    fn stream(&self, rx: Receiver<Transcript>) -> TranscriptStream {
+        TranscriptStream {
+            rx,
+            monitor: self.monitor.clone(),
+        }
+    }
+}
+
+fn lock(reader: &Mutex<Reader>) -> MutexGuard<'_, Reader> {
+    reader.lock().unwrap_or_else(PoisonError::into_inner)
+}
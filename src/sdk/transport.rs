@@ -1,6 +1,6 @@
-use crate::Result;
 use crate::protocol::client_events::ClientEvent;
 use crate::protocol::server_events::ServerEvent;
+use crate::{Result, ServerClose};
 use std::future::Future;
 use std::pin::Pin;
 
@@ -9,4 +9,9 @@ pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 pub trait Transport: Send {
     fn send(&mut self, event: ClientEvent) -> BoxFuture<'_, Result<()>>;
     fn next_event(&mut self) -> BoxFuture<'_, Result<Option<ServerEvent>>>;
+
+    /// The close frame the server ended the connection with, if any.
+    fn close_frame(&self) -> Option<ServerClose> {
+        None
+    }
 }
@@ -1,9 +1,20 @@
+use super::amd::AmdVerdict;
+use super::channel::{Receiver, Source};
+use super::codec::AudioCodec;
+use super::samples;
+use crate::protocol::models::{AudioFormat, Session};
+use crate::protocol::server_events::ServerEvent;
 use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tokio::sync::mpsc;
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Clone)]
+/// Voice-specific session event, serialized like [`SdkEvent`](super::SdkEvent).
+/// PCM payloads serialize as base64 strings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum VoiceEvent {
     SpeechStarted {
         audio_start_ms: Option<u32>,
@@ -11,11 +22,18 @@ pub enum VoiceEvent {
     SpeechStopped {
         audio_end_ms: Option<u32>,
     },
+    IdleTimeout {
+        item_id: String,
+        audio_start_ms: u32,
+        audio_end_ms: u32,
+        consecutive: u32,
+    },
     AudioDelta {
         response_id: String,
         item_id: String,
         output_index: u32,
         content_index: u32,
+        #[serde(with = "base64_pcm")]
         pcm: Vec<u8>,
     },
     AudioDone {
@@ -55,18 +73,239 @@ pub enum VoiceEvent {
     DecodeError {
         message: String,
     },
+    /// The answering machine detector decided who picked up, `audio_ms` into the
+    /// inbound audio.
+    AnsweringMachineDetected {
+        verdict: AmdVerdict,
+        audio_ms: u32,
+    },
+    /// Level of the audio sent since the last report, as fractions of full scale.
+    /// Reported at the interval set with `audio_level_interval`.
+    InputLevel {
+        rms: f32,
+        peak: f32,
+    },
+    /// Level of the model's audio received since the last report.
+    OutputLevel {
+        rms: f32,
+        peak: f32,
+    },
 }
 
-#[derive(Debug, Clone)]
+/// Automatic behavior when server VAD reports `input_audio_buffer.timeout_triggered`.
+///
+/// Requires `idle_timeout_ms` to be set on the server VAD turn detection config.
+#[derive(Debug, Clone, Default)]
+pub struct IdleTimeoutPolicy {
+    /// Send a `response.create` with these instructions on every idle timeout.
+    pub reprompt_instructions: Option<String>,
+    /// Close the session after this many consecutive idle timeouts.
+    pub close_after: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioChunk {
     pub response_id: String,
     pub item_id: String,
     pub output_index: u32,
     pub content_index: u32,
+    #[serde(with = "base64_pcm")]
     pub pcm: Vec<u8>,
+    /// True for audio injected with `Session::play_local_audio`; IDs are empty.
+    pub local: bool,
+    /// Playback length of `pcm` in the session's output format.
+    pub duration: Duration,
+    /// Start of this chunk relative to the beginning of its content part.
+    pub offset: Duration,
+    /// When the chunk arrived from the server (or was queued, for local audio).
+    pub received_at: SystemTime,
+}
+
+impl AudioChunk {
+    /// Local PCM16 audio queued at `received_at`, outside any response.
+    pub(crate) fn local(pcm: Vec<u8>, received_at: SystemTime) -> Self {
+        Self {
+            response_id: String::new(),
+            item_id: String::new(),
+            output_index: 0,
+            content_index: 0,
+            duration: AudioFormat::pcm_24khz().duration_of(pcm.len()),
+            offset: Duration::ZERO,
+            received_at,
+            pcm,
+            local: true,
+        }
+    }
+
+    /// The audio as `f32` samples, decoded with `codec`: the codec of the session's
+    /// output format, or PCM16 for local audio.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidAudio` if `pcm` is not valid in the codec's format.
+    pub fn to_f32(&self, codec: &dyn AudioCodec) -> crate::Result<Vec<f32>> {
+        Ok(samples::i16_to_f32(&codec.decode(&self.pcm)?))
+    }
+}
+
+/// An [`AudioChunk`] with its audio decoded to `f32` samples, from
+/// `Session::next_audio_samples`.
+#[derive(Debug, Clone)]
+pub struct AudioSamples {
+    pub chunk: AudioChunk,
+    /// `chunk.pcm` as fractions of full scale.
+    pub samples: Vec<f32>,
 }
 
+/// Output format and running playback offset used to time `AudioChunk`s.
 #[derive(Debug, Clone)]
+pub struct AudioTimeline {
+    format: AudioFormat,
+    part: Option<(String, u32)>,
+    offset: Duration,
+}
+
+impl Default for AudioTimeline {
+    fn default() -> Self {
+        Self {
+            format: AudioFormat::pcm_24khz(),
+            part: None,
+            offset: Duration::ZERO,
+        }
+    }
+}
+
+impl AudioTimeline {
+    /// Follow the output format the server reports for the session.
+    pub fn update_format(&mut self, session: &Session) {
+        let format = session
+            .config
+            .audio
+            .as_ref()
+            .and_then(|audio| audio.output.as_ref())
+            .and_then(|output| output.format.clone())
+            .or_else(|| session.config.output_audio_format.clone());
+        if let Some(format) = format {
+            self.format = format;
+        }
+    }
+
+    /// Duration and start offset of `len` bytes of model audio for a content part.
+    pub fn advance(
+        &mut self,
+        item_id: &str,
+        content_index: u32,
+        len: usize,
+    ) -> (Duration, Duration) {
+        let same_part = self
+            .part
+            .as_ref()
+            .is_some_and(|(id, index)| id == item_id && *index == content_index);
+        if !same_part {
+            self.part = Some((item_id.to_string(), content_index));
+            self.offset = Duration::ZERO;
+        }
+        let duration = self.format.duration_of(len);
+        let offset = self.offset;
+        self.offset += duration;
+        (duration, offset)
+    }
+
+    /// `pcm`, decoded from the `response.output_audio.delta` `evt` received at
+    /// `received_at`, as the next chunk of its content part.
+    pub fn chunk(
+        &mut self,
+        evt: &ServerEvent,
+        pcm: Vec<u8>,
+        received_at: SystemTime,
+    ) -> Option<AudioChunk> {
+        let ServerEvent::ResponseOutputAudioDelta {
+            response_id,
+            item_id,
+            output_index,
+            content_index,
+            ..
+        } = evt
+        else {
+            return None;
+        };
+        let (duration, offset) = self.advance(item_id, *content_index, pcm.len());
+        Some(AudioChunk {
+            response_id: response_id.clone(),
+            item_id: item_id.clone(),
+            output_index: *output_index,
+            content_index: *content_index,
+            pcm,
+            local: false,
+            duration,
+            offset,
+            received_at,
+        })
+    }
+}
+
+/// Where locally generated audio lands relative to model audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalAudioPosition {
+    /// Emit immediately.
+    Now,
+    /// Emit right before the next response starts.
+    BeforeNextResponse,
+    /// Emit once the current response finishes (immediately when idle).
+    AfterCurrentResponse,
+    /// Overlay onto model audio as it arrives (immediately when idle).
+    Mix,
+}
+
+/// Local PCM16 audio waiting for its slot in the output stream.
+#[derive(Debug, Default)]
+pub struct LocalAudioQueue {
+    before_next: Vec<Vec<u8>>,
+    after_current: Vec<Vec<u8>>,
+    mix: VecDeque<i16>,
+}
+
+impl LocalAudioQueue {
+    /// Queue audio for a position that waits on response lifecycle events.
+    pub fn push(&mut self, pcm: Vec<u8>, position: LocalAudioPosition) {
+        match position {
+            LocalAudioPosition::BeforeNextResponse => self.before_next.push(pcm),
+            LocalAudioPosition::AfterCurrentResponse => self.after_current.push(pcm),
+            LocalAudioPosition::Mix => self.mix.extend(
+                pcm.chunks_exact(2)
+                    .map(|pair| i16::from_le_bytes([pair[0], pair[1]])),
+            ),
+            LocalAudioPosition::Now => {}
+        }
+    }
+
+    /// Overlay pending mix samples onto a model audio delta (PCM16 LE).
+    pub fn mix_into(&mut self, pcm: &mut [u8]) {
+        for pair in pcm.chunks_exact_mut(2) {
+            let Some(local) = self.mix.pop_front() else {
+                break;
+            };
+            let model = i16::from_le_bytes([pair[0], pair[1]]);
+            pair.copy_from_slice(&model.saturating_add(local).to_le_bytes());
+        }
+    }
+
+    /// Audio to play before the response that is starting now.
+    pub fn take_before_next(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.before_next)
+    }
+
+    /// Audio to play once the current response has finished, including unmixed samples.
+    pub fn take_after_current(&mut self) -> Vec<Vec<u8>> {
+        let mut out = Vec::with_capacity(self.after_current.len() + 1);
+        if !self.mix.is_empty() {
+            out.push(self.mix.drain(..).flat_map(i16::to_le_bytes).collect());
+        }
+        out.append(&mut self.after_current);
+        out
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptChunk {
     pub response_id: String,
     pub item_id: String,
@@ -77,13 +316,21 @@ pub struct TranscriptChunk {
 }
 
 pub struct VoiceEventStream<'a> {
-    rx: &'a mut mpsc::Receiver<VoiceEvent>,
+    rx: Source<'a, VoiceEvent>,
 }
 
 impl<'a> VoiceEventStream<'a> {
     #[must_use]
-    pub const fn new(rx: &'a mut mpsc::Receiver<VoiceEvent>) -> Self {
-        Self { rx }
+    pub const fn new(rx: &'a mut tokio::sync::mpsc::Receiver<VoiceEvent>) -> Self {
+        Self {
+            rx: Source::Tokio(rx),
+        }
+    }
+
+    pub(crate) const fn from_session(rx: &'a mut Receiver<VoiceEvent>) -> Self {
+        Self {
+            rx: Source::Session(rx),
+        }
     }
 }
 
@@ -92,6 +339,24 @@ impl Stream for VoiceEventStream<'_> {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
-        Pin::new(&mut this.rx).poll_recv(cx)
+        this.rx.poll_recv(cx)
+    }
+}
+
+/// Serde adapter writing PCM bytes as a base64 string, as the API does for audio.
+mod base64_pcm {
+    use base64::Engine;
+    use base64::engine::general_purpose;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(pcm: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&general_purpose::STANDARD.encode(pcm))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(serde::de::Error::custom)
     }
 }
@@ -0,0 +1,184 @@
+//! What `RealtimeClient` writes to `tracing` at TRACE level for each event.
+
+use serde::Deserialize;
+use std::borrow::Cow;
+
+const DEFAULT_MAX_BYTES: usize = 1024;
+const TRUNCATE_SUFFIX: &str = "... (truncated)";
+
+/// Trace logging of raw event payloads.
+///
+/// This only shapes local `tracing` output; server-side traces are configured with
+/// `Tracing` in the session config. Events are filtered by wire type prefix, so
+/// `"response.function_call_arguments"` matches both its `delta` and `done` events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceConfig {
+    max_bytes: Option<usize>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    pretty: bool,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(DEFAULT_MAX_BYTES),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            pretty: false,
+        }
+    }
+}
+
+impl TraceConfig {
+    /// 1 KB per payload, every event type, compact JSON.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Truncate each logged payload to `max_bytes`.
+    #[must_use]
+    pub const fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Log payloads in full, however large.
+    #[must_use]
+    pub const fn full_payloads(mut self) -> Self {
+        self.max_bytes = None;
+        self
+    }
+
+    /// Only log events whose type starts with `prefix`. Can be called repeatedly.
+    #[must_use]
+    pub fn include(mut self, prefix: impl Into<String>) -> Self {
+        self.include.push(prefix.into());
+        self
+    }
+
+    /// Skip events whose type starts with `prefix`, e.g. `input_audio_buffer.append`.
+    /// Takes precedence over `include`.
+    #[must_use]
+    pub fn exclude(mut self, prefix: impl Into<String>) -> Self {
+        self.exclude.push(prefix.into());
+        self
+    }
+
+    /// Indent logged JSON. Applied before truncation.
+    #[must_use]
+    pub const fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// The line to log for `json`, or `None` if TRACE is off or the event is filtered
+    /// out. `event_type` is read from the payload when not given.
+    pub(crate) fn render<'a>(
+        &self,
+        event_type: Option<&str>,
+        json: &'a str,
+    ) -> Option<Cow<'a, str>> {
+        if !tracing::enabled!(tracing::Level::TRACE) {
+            return None;
+        }
+        self.format(event_type, json)
+    }
+
+    fn format<'a>(&self, event_type: Option<&str>, json: &'a str) -> Option<Cow<'a, str>> {
+        if !self.include.is_empty() || !self.exclude.is_empty() {
+            let parsed = event_type.is_none().then(|| wire_type(json)).flatten();
+            let event_type = event_type.or(parsed.as_deref()).unwrap_or_default();
+            if !self.allows(event_type) {
+                return None;
+            }
+        }
+        let text = if self.pretty {
+            serde_json::from_str::<serde_json::Value>(json)
+                .and_then(|value| serde_json::to_string_pretty(&value))
+                .map_or(Cow::Borrowed(json), Cow::Owned)
+        } else {
+            Cow::Borrowed(json)
+        };
+        Some(match self.max_bytes {
+            Some(max_bytes) => truncate(text, max_bytes),
+            None => text,
+        })
+    }
+
+    fn allows(&self, event_type: &str) -> bool {
+        let matches = |prefix: &String| event_type.starts_with(prefix.as_str());
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+fn wire_type(json: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Typed {
+        #[serde(rename = "type")]
+        kind: String,
+    }
+    serde_json::from_str::<Typed>(json)
+        .ok()
+        .map(|typed| typed.kind)
+}
+
+fn truncate(s: Cow<'_, str>, max_bytes: usize) -> Cow<'_, str> {
+    if s.len() <= max_bytes {
+        return s;
+    }
+
+    let mut end = max_bytes;
+    while end > 0 && !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!(
+        "{} {} {} bytes",
+        &s[..end],
+        TRUNCATE_SUFFIX,
+        s.len() - end
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA: &str = r#"{"type":"response.function_call_arguments.delta","delta":"{\"city\":"}"#;
+
+    #[test]
+    fn payloads_are_filtered_truncated_and_pretty_printed() {
+        let default = TraceConfig::new();
+        assert_eq!(default.format(None, DELTA).as_deref(), Some(DELTA));
+        let long = "x".repeat(2000);
+        assert!(
+            default
+                .format(None, &long)
+                .unwrap()
+                .ends_with("... (truncated) 976 bytes")
+        );
+        assert_eq!(
+            TraceConfig::new()
+                .full_payloads()
+                .format(None, &long)
+                .unwrap(),
+            long
+        );
+
+        let tools_only = TraceConfig::new().include("response.function_call_arguments");
+        assert!(tools_only.format(None, DELTA).is_some());
+        assert!(
+            tools_only
+                .format(Some("response.output_audio.delta"), "{}")
+                .is_none()
+        );
+        let excluded = tools_only.exclude("response.function_call_arguments.delta");
+        assert!(excluded.format(None, DELTA).is_none());
+
+        let pretty = TraceConfig::new().pretty(true).max_bytes(4);
+        let line = pretty.format(None, DELTA).unwrap();
+        assert!(line.starts_with("{\n   ... (truncated)"), "{line}");
+    }
+}
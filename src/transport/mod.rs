@@ -1,2 +1,3 @@
 pub mod rest;
+pub mod sdp;
 pub mod ws;
@@ -1,11 +1,13 @@
 use crate::error::Result;
 use crate::protocol::models::{Session, SessionConfig, SessionKind};
+use crate::transport::sdp::{SdpAnswer, SdpOffer};
 use reqwest::{
-    Client,
-    header::{AUTHORIZATION, HeaderValue, LOCATION},
+    Client, Method, Proxy, RequestBuilder, Response,
+    header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue, LOCATION},
     multipart,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,19 +32,201 @@ struct CreateClientSecretRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallCreationResponse {
-    pub sdp: String,
+    pub sdp: SdpAnswer,
     pub call_id: Option<String>,
 }
 
-const BASE_URL: &str = "https://api.openai.com/v1/realtime";
+/// Rate-limit state reported in a REST response's `x-ratelimit-*` headers.
+///
+/// Fields are `None` when the response did not carry the matching header.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestRateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Time until the request budget is fully restored.
+    pub reset_requests: Option<Duration>,
+    /// Time until the token budget is fully restored.
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RestRateLimitInfo {
+    /// Read the `x-ratelimit-*` headers, or `None` if there are none.
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(format!("x-ratelimit-{name}"))
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let count = |name: &str| header(name).and_then(|value| value.parse().ok());
+        let reset = |name: &str| header(name).and_then(parse_reset);
+        let info = Self {
+            limit_requests: count("limit-requests"),
+            limit_tokens: count("limit-tokens"),
+            remaining_requests: count("remaining-requests"),
+            remaining_tokens: count("remaining-tokens"),
+            reset_requests: reset("reset-requests"),
+            reset_tokens: reset("reset-tokens"),
+        };
+        (info != Self::default()).then_some(info)
+    }
+}
+
+const BASE_URL: &str = "https://api.openai.com/v1";
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
 
+/// Configures a [`RestClient`]: one HTTP client, with its timeouts, proxy and headers,
+/// for every REST surface that shares it.
+#[derive(Debug)]
+#[must_use]
+pub struct RestClientBuilder {
+    api_key: String,
+    base_url: String,
+    timeout: Duration,
+    pool_idle_timeout: Duration,
+    proxy: Option<Proxy>,
+    headers: HeaderMap,
+    http: Option<Client>,
+}
+
+impl RestClientBuilder {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: BASE_URL.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            pool_idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            proxy: None,
+            headers: HeaderMap::new(),
+            http: None,
+        }
+    }
+
+    /// API root that request paths are appended to, `https://api.openai.com/v1` by
+    /// default.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub const fn pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = pool_idle_timeout;
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Send `name: value` with every request, e.g. `OpenAI-Project`.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Use an HTTP client built elsewhere. Timeouts, proxy and default headers set on
+    /// this builder are then ignored.
+    pub fn http_client(mut self, http: Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// # Errors
+    /// Returns an error if the API key is not a valid header value or the HTTP client
+    /// fails to build.
+    pub fn build(self) -> Result<RestClient> {
+        let auth_header = HeaderValue::from_str(&format!("Bearer {}", self.api_key))?;
+        let http = if let Some(http) = self.http {
+            http
+        } else {
+            let mut builder = Client::builder()
+                .timeout(self.timeout)
+                .pool_idle_timeout(self.pool_idle_timeout)
+                .default_headers(self.headers);
+            if let Some(proxy) = self.proxy {
+                builder = builder.proxy(proxy);
+            }
+            builder.build()?
+        };
+        Ok(RestClient {
+            http,
+            auth_header,
+            base_url: self.base_url.into(),
+            rate_limit: Arc::default(),
+        })
+    }
+}
+
+/// An authenticated connection pool for the `OpenAI` REST API.
+///
+/// Clones share connections and the rate-limit state reported by
+/// [`Self::rate_limit`], so one client can back [`RealtimeRestAdapter`] and any other
+/// REST surface.
+#[derive(Clone, Debug)]
+pub struct RestClient {
+    http: Client,
+    auth_header: HeaderValue,
+    base_url: Arc<str>,
+    rate_limit: Arc<Mutex<Option<RestRateLimitInfo>>>,
+}
+
+impl RestClient {
+    pub fn builder(api_key: impl Into<String>) -> RestClientBuilder {
+        RestClientBuilder::new(api_key)
+    }
+
+    /// A request for `path` below the base URL, e.g. `/realtime/calls`.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{path}", self.base_url))
+    }
+
+    /// Send `request` with the API key, recording its rate-limit headers before
+    /// checking the status.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the response has an error status.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let res = request
+            .header(AUTHORIZATION, &self.auth_header)
+            .send()
+            .await?;
+        if let Some(info) = RestRateLimitInfo::from_headers(res.headers()) {
+            *self
+                .rate_limit
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(info);
+        }
+        Ok(res.error_for_status()?)
+    }
+
+    /// Rate-limit headers from the most recent response that carried them, including
+    /// error responses such as `429 Too Many Requests`.
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<RestRateLimitInfo> {
+        self.rate_limit
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
 /// An adapter for the `OpenAI` Realtime REST API.
+///
+/// Clones share the rate-limit state reported by [`Self::rate_limit`].
 #[derive(Clone, Debug)]
 pub struct RealtimeRestAdapter {
-    client: Client,
-    auth_header: HeaderValue,
+    client: RestClient,
 }
 
 impl RealtimeRestAdapter {
@@ -50,32 +234,50 @@ impl RealtimeRestAdapter {
     ///
     /// # Errors
     /// Returns an error if the API key results in an invalid header or client build fails.
-    #[allow(clippy::result_large_err)]
     pub fn new(api_key: &str) -> Result<Self> {
-        Self::new_with_timeouts(api_key, DEFAULT_TIMEOUT, DEFAULT_POOL_IDLE_TIMEOUT)
+        Ok(Self::with_client(RestClient::builder(api_key).build()?))
     }
 
     /// Create a new adapter with custom timeouts.
     ///
     /// # Errors
     /// Returns an error if the API key results in an invalid header or client build fails.
-    #[allow(clippy::result_large_err)]
     pub fn new_with_timeouts(
         api_key: &str,
         timeout: Duration,
         pool_idle_timeout: Duration,
     ) -> Result<Self> {
-        let client = Client::builder()
+        let client = RestClient::builder(api_key)
             .timeout(timeout)
             .pool_idle_timeout(pool_idle_timeout)
             .build()?;
+        Ok(Self::with_client(client))
+    }
 
-        let auth_header = HeaderValue::from_str(&format!("Bearer {api_key}"))?;
+    /// Create an adapter on a shared [`RestClient`].
+    #[must_use]
+    pub const fn with_client(client: RestClient) -> Self {
+        Self { client }
+    }
 
-        Ok(Self {
-            client,
-            auth_header,
-        })
+    /// The client this adapter sends through, for sharing with other REST surfaces.
+    #[must_use]
+    pub const fn client(&self) -> &RestClient {
+        &self.client
+    }
+
+    /// Rate-limit headers from the most recent response that carried them, including
+    /// error responses such as `429 Too Many Requests`.
+    ///
+    /// Batch callers can use this to pace client secret and SDP requests.
+    #[must_use]
+    pub fn rate_limit(&self) -> Option<RestRateLimitInfo> {
+        self.client.rate_limit()
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.client
+            .request(Method::POST, &format!("/realtime{path}"))
     }
 
     /// Create an ephemeral client secret for browser usage (GA).
@@ -106,15 +308,14 @@ impl RealtimeRestAdapter {
 
         let res = self
             .client
-            .post(format!("{BASE_URL}/client_secrets"))
-            .header(AUTHORIZATION, &self.auth_header)
-            .json(&CreateClientSecretRequest {
-                session,
-                expires_after,
-            })
-            .send()
-            .await?
-            .error_for_status()?;
+            .send(
+                self.post("/client_secrets")
+                    .json(&CreateClientSecretRequest {
+                        session,
+                        expires_after,
+                    }),
+            )
+            .await?;
 
         Ok(res.json().await?)
     }
@@ -123,7 +324,7 @@ impl RealtimeRestAdapter {
     ///
     /// # Errors
     /// Returns an error if the HTTP request fails.
-    pub async fn post_sdp_offer_raw(&self, sdp_offer: String) -> Result<String> {
+    pub async fn post_sdp_offer_raw(&self, sdp_offer: SdpOffer) -> Result<SdpAnswer> {
         Ok(self.post_sdp_offer_raw_with_call_id(sdp_offer).await?.sdp)
     }
 
@@ -133,23 +334,20 @@ impl RealtimeRestAdapter {
     /// Returns an error if the HTTP request fails.
     pub async fn post_sdp_offer_raw_with_call_id(
         &self,
-        sdp_offer: String,
+        sdp_offer: SdpOffer,
     ) -> Result<CallCreationResponse> {
-        let url = format!("{BASE_URL}/calls");
-
         let res = self
             .client
-            .post(url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .header("Content-Type", "application/sdp")
-            .body(sdp_offer)
-            .send()
-            .await?
-            .error_for_status()?;
+            .send(
+                self.post("/calls")
+                    .header("Content-Type", "application/sdp")
+                    .body(sdp_offer.into_string()),
+            )
+            .await?;
 
         let call_id = res.headers().get(LOCATION).and_then(extract_call_id);
         Ok(CallCreationResponse {
-            sdp: res.text().await?,
+            sdp: SdpAnswer::new(res.text().await?),
             call_id,
         })
     }
@@ -160,9 +358,9 @@ impl RealtimeRestAdapter {
     /// Returns an error if the HTTP request fails.
     pub async fn post_sdp_offer_multipart(
         &self,
-        sdp_offer: String,
+        sdp_offer: SdpOffer,
         session: Option<SessionConfig>,
-    ) -> Result<String> {
+    ) -> Result<SdpAnswer> {
         Ok(self
             .post_sdp_offer_multipart_with_call_id(sdp_offer, session)
             .await?
@@ -175,12 +373,10 @@ impl RealtimeRestAdapter {
     /// Returns an error if the HTTP request fails.
     pub async fn post_sdp_offer_multipart_with_call_id(
         &self,
-        sdp_offer: String,
+        sdp_offer: SdpOffer,
         session: Option<SessionConfig>,
     ) -> Result<CallCreationResponse> {
-        let url = format!("{BASE_URL}/calls");
-
-        let sdp_part = multipart::Part::text(sdp_offer)
+        let sdp_part = multipart::Part::text(sdp_offer.into_string())
             .mime_str("application/sdp")
             .map_err(|e| crate::error::Error::Mime(e.to_string()))?;
         let mut form = multipart::Form::new().part("sdp", sdp_part);
@@ -194,16 +390,12 @@ impl RealtimeRestAdapter {
 
         let res = self
             .client
-            .post(url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .multipart(form)
-            .send()
-            .await?
-            .error_for_status()?;
+            .send(self.post("/calls").multipart(form))
+            .await?;
 
         let call_id = res.headers().get(LOCATION).and_then(extract_call_id);
         Ok(CallCreationResponse {
-            sdp: res.text().await?,
+            sdp: SdpAnswer::new(res.text().await?),
             call_id,
         })
     }
@@ -213,7 +405,7 @@ impl RealtimeRestAdapter {
     /// # Errors
     /// Returns an error if the HTTP request fails or returns a non-success status.
     pub async fn sip_accept(&self, call_id: &str, session: SessionConfig) -> Result<()> {
-        let url = format!("{BASE_URL}/calls/{call_id}/accept");
+        let path = format!("/calls/{call_id}/accept");
 
         if session.kind != SessionKind::Realtime {
             return Err(crate::error::Error::InvalidClientEvent(
@@ -221,13 +413,7 @@ impl RealtimeRestAdapter {
             ));
         }
 
-        self.client
-            .post(&url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .json(&session)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.client.send(self.post(&path).json(&session)).await?;
         Ok(())
     }
 
@@ -236,13 +422,8 @@ impl RealtimeRestAdapter {
     /// # Errors
     /// Returns an error if the HTTP request fails.
     pub async fn sip_reject(&self, call_id: &str) -> Result<()> {
-        let url = format!("{BASE_URL}/calls/{call_id}/reject");
-        self.client
-            .post(&url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .send()
-            .await?
-            .error_for_status()?;
+        let path = format!("/calls/{call_id}/reject");
+        self.client.send(self.post(&path)).await?;
         Ok(())
     }
 
@@ -251,13 +432,8 @@ impl RealtimeRestAdapter {
     /// # Errors
     /// Returns an error if the HTTP request fails.
     pub async fn hangup(&self, call_id: &str) -> Result<()> {
-        let url = format!("{BASE_URL}/calls/{call_id}/hangup");
-        self.client
-            .post(&url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .send()
-            .await?
-            .error_for_status()?;
+        let path = format!("/calls/{call_id}/hangup");
+        self.client.send(self.post(&path)).await?;
         Ok(())
     }
 
@@ -266,18 +442,12 @@ impl RealtimeRestAdapter {
     /// # Errors
     /// Returns an error if the HTTP request fails.
     pub async fn sip_refer(&self, call_id: &str, target_uri: impl Into<String>) -> Result<()> {
-        let url = format!("{BASE_URL}/calls/{call_id}/refer");
+        let path = format!("/calls/{call_id}/refer");
         let body = SipReferRequest {
             target_uri: target_uri.into(),
         };
 
-        self.client
-            .post(&url)
-            .header(AUTHORIZATION, &self.auth_header)
-            .json(&body)
-            .send()
-            .await?
-            .error_for_status()?;
+        self.client.send(self.post(&path).json(&body)).await?;
         Ok(())
     }
 }
@@ -300,3 +470,86 @@ fn extract_call_id(location: &HeaderValue) -> Option<String> {
         .find(|segment| !segment.is_empty())
         .map(str::to_owned)
 }
+
+/// Parse a reset interval such as `1s`, `6m0s`, `20ms` or `1h2m3.5s`.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&i| i > 0)?;
+        let amount: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += amount * seconds;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_headers_are_parsed() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("x-ratelimit-reset-tokens", "6m0.5s"),
+        ] {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+        let info = RestRateLimitInfo::from_headers(&headers).expect("rate limit info");
+        assert_eq!(info.limit_requests, Some(500));
+        assert_eq!(info.remaining_requests, Some(499));
+        assert_eq!(info.limit_tokens, None);
+        assert_eq!(info.reset_requests, Some(Duration::from_millis(120)));
+        assert_eq!(info.reset_tokens, Some(Duration::from_millis(360_500)));
+
+        assert!(RestRateLimitInfo::from_headers(&HeaderMap::new()).is_none());
+        assert_eq!(parse_reset("1h2m3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_reset("soon"), None);
+    }
+
+    #[test]
+    fn adapters_share_one_rest_client() {
+        let client = RestClient::builder("sk-test")
+            .base_url("http://localhost:8080/v1/")
+            .timeout(Duration::from_secs(5))
+            .default_header(
+                HeaderName::from_static("openai-project"),
+                HeaderValue::from_static("proj_1"),
+            )
+            .build()
+            .unwrap();
+        let calls = RealtimeRestAdapter::with_client(client.clone());
+
+        let request = calls.post("/calls").build().unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8080/v1/realtime/calls"
+        );
+
+        *client.rate_limit.lock().unwrap() = Some(RestRateLimitInfo {
+            remaining_requests: Some(7),
+            ..RestRateLimitInfo::default()
+        });
+        assert_eq!(
+            calls.rate_limit().and_then(|info| info.remaining_requests),
+            Some(7)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Light SDP helpers for callers doing their own WebRTC.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Bitrates RFC 7587 allows for `maxaveragebitrate`.
+const OPUS_BITRATE_RANGE: std::ops::RangeInclusive<u32> = 6_000..=510_000;
+
+/// Opus `a=fmtp` parameters the Realtime API honours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpusParams {
+    /// `maxaveragebitrate`, in bits per second.
+    pub max_average_bitrate: Option<u32>,
+    /// `stereo`: whether the receiver prefers two channels.
+    pub stereo: Option<bool>,
+}
+
+/// A validated SDP offer, with CRLF line endings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct SdpOffer(String);
+
+impl SdpOffer {
+    /// Validate `sdp` as an offer: it must start with `v=0`, carry `o=` and `s=`
+    /// lines, and offer an audio media section. Bare `\n` line endings are
+    /// normalized to the CRLF SDP requires.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidSdp` if the offer is malformed.
+    pub fn new(sdp: impl Into<String>) -> Result<Self> {
+        let sdp = sdp.into();
+        let lines: Vec<&str> = sdp_lines(&sdp).collect();
+        if lines.first() != Some(&"v=0") {
+            return Err(invalid("offer must start with `v=0`"));
+        }
+        if let Some(line) = lines.iter().find(|line| !is_sdp_line(line)) {
+            return Err(invalid(format!("malformed line `{line}`")));
+        }
+        for prefix in ["o=", "s="] {
+            if !lines.iter().any(|line| line.starts_with(prefix)) {
+                return Err(invalid(format!("offer has no `{prefix}` line")));
+            }
+        }
+        if !lines.iter().any(|line| line.starts_with("m=audio ")) {
+            return Err(invalid("offer has no audio media section"));
+        }
+        Ok(Self(join_lines(lines)))
+    }
+
+    /// The Opus parameters of the audio section, or `None` if it does not offer Opus.
+    #[must_use]
+    pub fn opus_params(&self) -> Option<OpusParams> {
+        opus_params(&self.0)
+    }
+
+    /// Set the given Opus parameters on the audio section, keeping any others.
+    /// Parameters left as `None` are not touched.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidSdp` if the offer does not include Opus or the bitrate is
+    /// outside 6000 to 510000.
+    pub fn force_opus_params(self, params: OpusParams) -> Result<Self> {
+        if let Some(bitrate) = params.max_average_bitrate {
+            if !OPUS_BITRATE_RANGE.contains(&bitrate) {
+                return Err(invalid(format!(
+                    "maxaveragebitrate {bitrate} is outside 6000 to 510000"
+                )));
+            }
+        }
+        let lines: Vec<&str> = sdp_lines(&self.0).collect();
+        let payload_type =
+            opus_payload_type(&lines).ok_or_else(|| invalid("offer does not include Opus"))?;
+        let fmtp_prefix = format!("a=fmtp:{payload_type} ");
+        let rtpmap_prefix = format!("a=rtpmap:{payload_type} ");
+
+        let mut out: Vec<String> = Vec::with_capacity(lines.len() + 1);
+        let mut has_fmtp = false;
+        for line in &lines {
+            if let Some(existing) = line.strip_prefix(&fmtp_prefix) {
+                has_fmtp = true;
+                out.push(format!("{fmtp_prefix}{}", merge_fmtp(existing, params)));
+            } else {
+                out.push((*line).to_string());
+            }
+        }
+        if !has_fmtp {
+            let at = out
+                .iter()
+                .position(|line| line.starts_with(&rtpmap_prefix))
+                .map_or(out.len(), |i| i + 1);
+            out.insert(at, format!("{fmtp_prefix}{}", merge_fmtp("", params)));
+        }
+        Ok(Self(join_lines(out.iter().map(String::as_str))))
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for SdpOffer {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Self::new(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for SdpOffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for SdpOffer {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The SDP answer the server returned for an offer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SdpAnswer(String);
+
+impl SdpAnswer {
+    pub(crate) const fn new(sdp: String) -> Self {
+        Self(sdp)
+    }
+
+    /// The Opus parameters the server chose, or `None` if the answer has no Opus.
+    #[must_use]
+    pub fn opus_params(&self) -> Option<OpusParams> {
+        opus_params(&self.0)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for SdpAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for SdpAnswer {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::InvalidSdp(reason.into())
+}
+
+fn sdp_lines(sdp: &str) -> impl Iterator<Item = &str> {
+    sdp.lines().filter(|line| !line.is_empty())
+}
+
+fn join_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    lines.into_iter().fold(String::new(), |mut sdp, line| {
+        sdp.push_str(line);
+        sdp.push_str("\r\n");
+        sdp
+    })
+}
+
+/// `<type>=<value>` with a single lowercase letter type.
+fn is_sdp_line(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_lowercase() && bytes[1] == b'='
+}
+
+/// The payload type mapped to Opus in the first audio section.
+fn opus_payload_type<'a>(lines: &[&'a str]) -> Option<&'a str> {
+    lines
+        .iter()
+        .skip_while(|line| !line.starts_with("m=audio "))
+        .skip(1)
+        .take_while(|line| !line.starts_with("m="))
+        .find_map(|line| {
+            let (payload_type, codec) = line.strip_prefix("a=rtpmap:")?.split_once(' ')?;
+            codec
+                .to_ascii_lowercase()
+                .starts_with("opus/")
+                .then_some(payload_type)
+        })
+}
+
+fn opus_params(sdp: &str) -> Option<OpusParams> {
+    let lines: Vec<&str> = sdp_lines(sdp).collect();
+    let fmtp_prefix = format!("a=fmtp:{} ", opus_payload_type(&lines)?);
+    let mut params = OpusParams::default();
+    let fmtp = lines
+        .iter()
+        .find_map(|line| line.strip_prefix(&fmtp_prefix));
+    for (key, value) in fmtp.into_iter().flat_map(fmtp_pairs) {
+        match key {
+            "maxaveragebitrate" => params.max_average_bitrate = value.parse().ok(),
+            "stereo" => params.stereo = Some(value == "1"),
+            _ => {}
+        }
+    }
+    Some(params)
+}
+
+fn fmtp_pairs(fmtp: &str) -> impl Iterator<Item = (&str, &str)> {
+    fmtp.split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// `existing` fmtp parameters with those set in `params` replaced or appended.
+fn merge_fmtp(existing: &str, params: OpusParams) -> String {
+    let forced = [
+        (
+            "maxaveragebitrate",
+            params.max_average_bitrate.map(|b| b.to_string()),
+        ),
+        ("stereo", params.stereo.map(|s| u8::from(s).to_string())),
+    ];
+    let mut pairs: Vec<(String, String)> = fmtp_pairs(existing)
+        .filter(|(key, _)| {
+            !forced
+                .iter()
+                .any(|(name, value)| name == key && value.is_some())
+        })
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    for (name, value) in forced {
+        if let Some(value) = value {
+            pairs.push((name.to_string(), value));
+        }
+    }
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFER: &str = "v=0\no=- 1 2 IN IP4 127.0.0.1\ns=-\nt=0 0\n\
+        m=audio 9 UDP/TLS/RTP/SAVPF 111 0\n\
+        a=rtpmap:111 opus/48000/2\n\
+        a=fmtp:111 minptime=10;useinbandfec=1\n\
+        a=rtpmap:0 PCMU/8000\n";
+
+    #[test]
+    fn offers_are_validated_and_normalized() {
+        let offer = SdpOffer::new(OFFER).expect("valid offer");
+        assert!(offer.as_str().starts_with("v=0\r\no=- 1 2"));
+        assert!(offer.as_str().ends_with("PCMU/8000\r\n"));
+
+        for bad in [
+            "",
+            "o=- 1 2\r\n",
+            "v=0\r\ns=-\r\n",
+            "v=0\r\no=-\r\ns=-\r\nnot sdp\r\n",
+        ] {
+            assert!(
+                matches!(SdpOffer::new(bad), Err(Error::InvalidSdp(_))),
+                "{bad:?}"
+            );
+        }
+        assert!(SdpOffer::new("v=0\r\no=-\r\ns=-\r\nm=video 9 RTP/AVP 96\r\n").is_err());
+    }
+
+    #[test]
+    fn opus_params_are_read_and_forced() {
+        let offer = SdpOffer::new(OFFER).unwrap();
+        assert_eq!(offer.opus_params(), Some(OpusParams::default()));
+
+        let forced = offer
+            .force_opus_params(OpusParams {
+                max_average_bitrate: Some(32_000),
+                stereo: Some(false),
+            })
+            .unwrap();
+        assert!(forced.as_str().contains(
+            "a=fmtp:111 minptime=10;useinbandfec=1;maxaveragebitrate=32000;stereo=0\r\n"
+        ));
+        assert_eq!(
+            forced.opus_params(),
+            Some(OpusParams {
+                max_average_bitrate: Some(32_000),
+                stereo: Some(false),
+            })
+        );
+
+        let bare = SdpOffer::new(OFFER.replace("a=fmtp:111 minptime=10;useinbandfec=1\n", ""))
+            .unwrap()
+            .force_opus_params(OpusParams {
+                stereo: Some(true),
+                ..OpusParams::default()
+            })
+            .unwrap();
+        assert!(
+            bare.as_str()
+                .contains("a=rtpmap:111 opus/48000/2\r\na=fmtp:111 stereo=1\r\n")
+        );
+
+        let too_low = OpusParams {
+            max_average_bitrate: Some(100),
+            stereo: None,
+        };
+        assert!(
+            SdpOffer::new(OFFER)
+                .unwrap()
+                .force_opus_params(too_low)
+                .is_err()
+        );
+    }
+}
@@ -1,9 +1,12 @@
-use crate::error::Result;
+use crate::error::{ConnectError, Error, Result};
 use crate::protocol::models::DEFAULT_MODEL;
 use reqwest::header::HeaderValue;
+use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::net::TcpStream;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
-use url::Url;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, client_async_tls};
+use url::{Host, Url};
 
 #[derive(Debug)]
 pub struct WsStream(WebSocketStream<MaybeTlsStream<TcpStream>>);
@@ -60,7 +63,10 @@ impl futures::Sink<tokio_tungstenite::tungstenite::Message> for WsStream {
     }
 }
 
-const WS_BASE_URL: &str = "wss://api.openai.com/v1/realtime";
+pub const WS_BASE_URL: &str = "wss://api.openai.com/v1/realtime";
+
+/// How long DNS, TCP, TLS and the WebSocket upgrade may take together by default.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Establish a WebSocket connection to the Realtime API.
 ///
@@ -71,7 +77,39 @@ pub async fn connect(
     model: Option<&str>,
     call_id: Option<&str>,
 ) -> Result<WsStream> {
-    let mut url = Url::parse(WS_BASE_URL)?;
+    connect_to(WS_BASE_URL, api_key, model, call_id).await
+}
+
+/// Establish a WebSocket connection to a Realtime-compatible endpoint, such as a
+/// proxy or the `mock` feature's `MockServer`, within [`DEFAULT_CONNECT_TIMEOUT`].
+///
+/// # Errors
+/// Returns an error if the URL is invalid or the handshake fails.
+pub async fn connect_to(
+    base_url: &str,
+    api_key: &str,
+    model: Option<&str>,
+    call_id: Option<&str>,
+) -> Result<WsStream> {
+    connect_with_timeout(base_url, api_key, model, call_id, DEFAULT_CONNECT_TIMEOUT).await
+}
+
+/// Like [`connect_to`], giving up after `timeout`.
+///
+/// The returned future can be dropped at any point, e.g. as a losing `tokio::select!`
+/// branch; a partly opened connection is closed with it.
+///
+/// # Errors
+/// Returns `Error::ConnectTimeout` if `timeout` passes first, `Error::Connect` naming
+/// the DNS, TCP, TLS or upgrade step that failed, or an error if the URL is invalid.
+pub async fn connect_with_timeout(
+    base_url: &str,
+    api_key: &str,
+    model: Option<&str>,
+    call_id: Option<&str>,
+    timeout: Duration,
+) -> Result<WsStream> {
+    let mut url = Url::parse(base_url)?;
 
     {
         let mut query = url.query_pairs_mut();
@@ -89,9 +127,73 @@ pub async fn connect(
     )?;
     let h = req.headers_mut();
     h.insert(reqwest::header::AUTHORIZATION, auth_header);
-    let (ws_stream, _) = connect_async(req).await?;
+    let ws_stream = tokio::time::timeout(timeout, handshake(&url, req))
+        .await
+        .map_err(|_| Error::ConnectTimeout(timeout))??;
 
-    tracing::info!("Connected to OpenAI Realtime");
+    tracing::info!("Connected to {base_url}");
 
     Ok(WsStream::new(ws_stream))
 }
+
+/// Resolve, connect and upgrade step by step, so a failure names its step.
+async fn handshake(
+    url: &Url,
+    req: tungstenite::handshake::client::Request,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let host = url.host_str().unwrap_or_default().to_string();
+    let addrs = resolve(url).await.map_err(|source| ConnectError::Dns {
+        host: host.clone(),
+        source,
+    })?;
+    let tcp = TcpStream::connect(addrs.as_slice())
+        .await
+        .map_err(|source| ConnectError::Tcp {
+            host: host.clone(),
+            source,
+        })?;
+    tcp.set_nodelay(true)?;
+    match client_async_tls(req, tcp).await {
+        Ok((stream, _)) => Ok(stream),
+        Err(source @ tungstenite::Error::Tls(_)) => Err(ConnectError::Tls { host, source }.into()),
+        Err(tungstenite::Error::Http(response)) => Err(ConnectError::Upgrade {
+            status: response.status().as_u16(),
+            body: response
+                .body()
+                .as_deref()
+                .map(|body| String::from_utf8_lossy(body).into_owned()),
+        }
+        .into()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The addresses to connect to for `url`. IP literals are used as given; `host_str`
+/// would keep an IPv6 address's brackets, which the resolver rejects.
+async fn resolve(url: &Url) -> std::io::Result<Vec<SocketAddr>> {
+    let port = url.port_or_known_default().unwrap_or(443);
+    match url.host() {
+        Some(Host::Ipv4(ip)) => Ok(vec![SocketAddr::from((ip, port))]),
+        Some(Host::Ipv6(ip)) => Ok(vec![SocketAddr::from((ip, port))]),
+        Some(Host::Domain(domain)) => Ok(tokio::net::lookup_host((domain, port)).await?.collect()),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the URL has no host",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    #[tokio::test]
+    async fn ipv6_literals_resolve_without_brackets() {
+        let url = Url::parse("ws://[::1]:8080/v1/realtime").unwrap();
+        assert_eq!(
+            resolve(&url).await.unwrap(),
+            [SocketAddr::from((Ipv6Addr::LOCALHOST, 8080))]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "axum", feature = "mock"))]
+
+use axum::response::IntoResponse;
+use futures::StreamExt;
+use oai_rt_rs::integrations::axum::sse;
+use oai_rt_rs::mock::MockServer;
+use oai_rt_rs::sdk::Realtime;
+use std::time::Duration;
+
+#[tokio::test]
+async fn sse_streams_text_events() {
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+    session.say("ping").await.unwrap();
+    session.respond().await.unwrap();
+
+    let mut body = sse(session).into_response().into_body().into_data_stream();
+    let mut received = String::new();
+    while !received.contains("event: text_done") {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), body.next())
+            .await
+            .unwrap()
+            .expect("stream open")
+            .unwrap();
+        received.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+
+    assert!(received.contains("event: text_delta"));
+    assert!(received.contains(r#""text":"You said: ping""#));
+    assert!(!received.contains("session_created"));
+}
@@ -0,0 +1,543 @@
+#![cfg(feature = "mock")]
+
+use oai_rt_rs::mock::MockServer;
+use oai_rt_rs::protocol::models::{AudioFormat, ContentPart, Item, Role};
+use oai_rt_rs::sdk::{
+    HandoffOptions, KeyPool, Realtime, SdkEvent, ToolCall, ToolDispatcher, ToolRegistry,
+    ToolResult, handoff,
+};
+use oai_rt_rs::{ConnectError, Error, RealtimeClient};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct LookupArgs {
+    id: u32,
+}
+
+#[tokio::test]
+async fn text_session_round_trips_through_mock_server() {
+    let server = MockServer::start().await.unwrap();
+    let mut session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    assert_eq!(session.model().as_deref(), Some("gpt-realtime-mock"));
+    let reply = session.ask("ping").await.unwrap();
+    assert_eq!(reply.as_deref(), Some("You said: ping"));
+}
+
+#[tokio::test]
+async fn builder_configures_audio_without_voice_session() {
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .input_audio(AudioFormat::Pcmu)
+        .input_transcription("gpt-4o-transcribe")
+        .output_audio_config(AudioFormat::Pcmu, "marin", 1.2)
+        .unwrap()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let audio = session
+        .server_session()
+        .await
+        .and_then(|s| s.config.audio)
+        .expect("audio config");
+    let input = audio.input.expect("input audio config");
+    assert_eq!(input.format, Some(AudioFormat::Pcmu));
+    let transcription = serde_json::to_value(input.transcription).unwrap();
+    assert_eq!(transcription["model"], "gpt-4o-transcribe");
+    let output = audio.output.expect("output audio config");
+    assert_eq!(output.format, Some(AudioFormat::Pcmu));
+    assert_eq!(output.speed, Some(1.2));
+    assert!(output.voice.is_some());
+}
+
+#[tokio::test]
+async fn voice_session_applies_speaking_speed() {
+    assert!(
+        Realtime::builder()
+            .voice_session()
+            .speaking_speed(2.0)
+            .is_err()
+    );
+
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .voice_session()
+        .api_key("mock")
+        .base_url(server.url())
+        .voice("marin")
+        .speaking_speed(1.2)
+        .unwrap()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let output = session
+        .server_session()
+        .await
+        .and_then(|s| s.config.audio)
+        .and_then(|audio| audio.output)
+        .expect("output audio config");
+    assert_eq!(output.speed, Some(1.2));
+    assert!(output.voice.is_some());
+}
+
+#[tokio::test]
+async fn push_to_talk_commits_and_responds() {
+    let server = MockServer::start().await.unwrap();
+    let mut session = Realtime::builder()
+        .voice_session()
+        .api_key("mock")
+        .base_url(server.url())
+        .push_to_talk()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    // Audio from before the button press is discarded.
+    session.audio_in_append_pcm16(&[0; 1200]).await.unwrap();
+    session.ptt_start().await.unwrap();
+    session.audio_in_append_pcm16(&[0; 2400]).await.unwrap();
+    session.ptt_stop().await.unwrap();
+
+    let mut cleared = false;
+    let mut committed = false;
+    let transcript = loop {
+        match session.next_event().await.unwrap().expect("event") {
+            SdkEvent::InputAudioCleared => cleared = true,
+            SdkEvent::InputAudioCommitted { .. } => committed = cleared,
+            SdkEvent::TranscriptDone { transcript, .. } => break transcript,
+            _ => {}
+        }
+    };
+    assert!(committed);
+    assert_eq!(transcript, "You said: 100 milliseconds of audio");
+}
+
+#[tokio::test]
+async fn session_reports_trace_identifiers() {
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .tracing("support-line", Some("call-42".to_string()), None)
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let trace = session.trace_info().await.expect("tracing enabled");
+    let server_session = session.server_session().await.unwrap();
+    assert_eq!(trace.session_id, server_session.id);
+    assert_eq!(trace.workflow_name.as_deref(), Some("support-line"));
+    assert_eq!(trace.group_id.as_deref(), Some("call-42"));
+}
+
+#[tokio::test]
+async fn say_item_returns_the_added_item() {
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let item = session.say_item("hello").await.unwrap();
+    let item_id = item.id().expect("item id").to_string();
+    assert!(matches!(
+        item,
+        Item::Message {
+            role: Role::User,
+            ..
+        }
+    ));
+
+    let retrieved = session.retrieve_item(&item_id).await.unwrap();
+    assert_eq!(retrieved.id(), Some(item_id.as_str()));
+}
+
+#[tokio::test]
+async fn injected_tool_exchange_primes_the_next_response() {
+    let server = MockServer::start().await.unwrap();
+    let mut session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let (call, output) = session
+        .inject_tool_exchange(
+            "lookup",
+            &json!({ "id": 7 }),
+            &json!({ "status": "shipped" }),
+        )
+        .await
+        .unwrap();
+    let Item::FunctionCall {
+        id: Some(call_item_id),
+        name,
+        call_id,
+        arguments,
+        ..
+    } = call
+    else {
+        panic!("expected a function call, got {call:?}");
+    };
+    assert_eq!(name, "lookup");
+    assert!(call_id.starts_with("call_sdk_"));
+    assert_eq!(arguments, r#"{"id":7}"#);
+    let Item::FunctionCallOutput {
+        id: Some(output_item_id),
+        call_id: output_call_id,
+        ..
+    } = output
+    else {
+        panic!("expected a function call output, got {output:?}");
+    };
+    assert_eq!(output_call_id, call_id);
+    assert_ne!(output_item_id, call_item_id);
+
+    session.respond().await.unwrap();
+    let reply = session.next_text().await.unwrap();
+    assert_eq!(
+        reply.as_deref(),
+        Some(r#"The tool returned {"status":"shipped"}."#)
+    );
+}
+
+#[tokio::test]
+async fn key_pool_supplies_the_key_and_tracks_sessions() {
+    let server = MockServer::start().await.unwrap();
+    let pool = KeyPool::new().key("primary", "mock-a", 1);
+    let session = Realtime::builder()
+        .key_pool(pool.clone())
+        .base_url(server.url())
+        .output_text()
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+    let stats = &pool.stats()[0];
+    assert_eq!((stats.sessions, stats.active_sessions), (1, 1));
+    assert_eq!(stats.errors, 0);
+
+    drop(session);
+
+    // Nothing listens on a port once its listener is dropped.
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let failed = Realtime::builder()
+        .key_pool(pool.clone())
+        .base_url(format!("ws://{addr}/v1/realtime"))
+        .connect_timeout(Duration::from_secs(1))
+        .connect_ws()
+        .await;
+    assert!(failed.is_err());
+    assert_eq!(pool.stats()[0].errors, 1);
+}
+
+#[tokio::test]
+async fn scripted_tool_call_reaches_registered_tool() {
+    let server = MockServer::builder()
+        .tool_call("order", "lookup", json!({ "id": 7 }))
+        .start()
+        .await
+        .unwrap();
+    let mut session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .tool("lookup", |args: LookupArgs| async move {
+            Ok(json!({ "status": format!("order {} shipped", args.id) }))
+        })
+        .connect_ws()
+        .await
+        .unwrap();
+
+    session.say("where is my order?").await.unwrap();
+    session.respond().await.unwrap();
+
+    let mut called = false;
+    let text = loop {
+        match session.next_event().await.unwrap().expect("event") {
+            SdkEvent::ToolCall { name, .. } => called = name == "lookup",
+            SdkEvent::TextDone { text, .. } => break text,
+            _ => {}
+        }
+    };
+    assert!(called);
+    assert_eq!(text, r#"The tool returned {"status":"order 7 shipped"}."#);
+}
+
+/// Answers every call itself, as a remote execution service would.
+struct RemoteTools {
+    definitions: ToolRegistry,
+    calls: Mutex<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl ToolDispatcher for RemoteTools {
+    async fn dispatch(&self, call: ToolCall) -> oai_rt_rs::Result<ToolResult> {
+        self.calls.lock().unwrap().push(call.name);
+        Ok(ToolResult {
+            call_id: call.call_id,
+            output: json!({ "status": "handled remotely" }),
+        })
+    }
+
+    fn tool_definitions(&self) -> Vec<oai_rt_rs::protocol::models::Tool> {
+        self.definitions.tool_definitions()
+    }
+}
+
+#[tokio::test]
+async fn custom_dispatcher_runs_tool_calls() {
+    let server = MockServer::builder()
+        .tool_call("order", "lookup", json!({ "id": 7 }))
+        .start()
+        .await
+        .unwrap();
+    let mut definitions = ToolRegistry::new();
+    definitions.tool("lookup", |_: LookupArgs| async { Ok(json!({})) });
+    let remote = Arc::new(RemoteTools {
+        definitions,
+        calls: Mutex::new(Vec::new()),
+    });
+    let mut session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .dispatcher(remote.clone())
+        .connect_ws()
+        .await
+        .unwrap();
+
+    session.say("where is my order?").await.unwrap();
+    session.respond().await.unwrap();
+
+    let text = loop {
+        if let SdkEvent::TextDone { text, .. } = session.next_event().await.unwrap().expect("event")
+        {
+            break text;
+        }
+    };
+    assert_eq!(*remote.calls.lock().unwrap(), ["lookup"]);
+    assert_eq!(text, r#"The tool returned {"status":"handled remotely"}."#);
+}
+
+fn message_texts(items: &[Item]) -> Vec<(Role, String)> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Message { role, content, .. } => match content.first() {
+                Some(ContentPart::InputText { text } | ContentPart::OutputText { text }) => {
+                    Some((*role, text.clone()))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn handoff_replays_conversation_into_new_session() {
+    let server = MockServer::start().await.unwrap();
+    let builder = || {
+        Realtime::builder()
+            .api_key("mock")
+            .base_url(server.url())
+            .output_text()
+    };
+    let mut first = builder().connect_ws().await.unwrap();
+    let reply = first.ask("hello").await.unwrap();
+    assert_eq!(reply.as_deref(), Some("You said: hello"));
+
+    let second = handoff(&first, builder(), HandoffOptions::default())
+        .await
+        .unwrap();
+    let expected = vec![
+        (Role::User, "hello".to_string()),
+        (Role::Assistant, "You said: hello".to_string()),
+    ];
+    let mut replayed = Vec::new();
+    for _ in 0..50 {
+        replayed = message_texts(&second.conversation().await.unwrap());
+        if replayed == expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(replayed, expected);
+}
+
+#[tokio::test]
+async fn stalled_handshake_times_out() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let accept = tokio::spawn(async move { listener.accept().await });
+
+    let err = Realtime::builder()
+        .api_key("mock")
+        .base_url(url)
+        .output_text()
+        .connect_timeout(Duration::from_millis(100))
+        .connect_ws()
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::ConnectTimeout(timeout) if timeout == Duration::from_millis(100)));
+    assert!(err.is_connection());
+    accept.abort();
+}
+
+#[tokio::test]
+async fn rejected_upgrade_reports_status_and_body() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0_u8; 4096];
+        let _ = socket.read(&mut request).await;
+        let body = "invalid api key";
+        let response = format!(
+            "HTTP/1.1 401 Unauthorized\r\ncontent-length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let err = RealtimeClient::connect_with_timeout(&url, "bad", None, None, Duration::from_secs(5))
+        .await
+        .err()
+        .unwrap();
+    match err {
+        Error::Connect(connect) => match *connect {
+            ConnectError::Upgrade { status, body } => {
+                assert_eq!(status, 401);
+                assert_eq!(body.as_deref(), Some("invalid api key"));
+            }
+            other => panic!("expected an upgrade failure, got {other:?}"),
+        },
+        other => panic!("expected a connect error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn refused_connection_names_the_tcp_step() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let err = RealtimeClient::connect_to(&url, "mock", None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&err, Error::Connect(connect) if matches!(**connect, ConnectError::Tcp { .. })),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn oversized_events_are_rejected_before_sending() {
+    let server = MockServer::start().await.unwrap();
+    let session = Realtime::builder()
+        .api_key("mock")
+        .base_url(server.url())
+        .output_text()
+        .max_event_bytes(4096)
+        .wait_for_ready(Duration::from_secs(5))
+        .connect_ws()
+        .await
+        .unwrap();
+
+    let err = session.say(&"a".repeat(8192)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EventTooLarge {
+            event_type: "conversation.item.create",
+            bytes,
+        } if bytes > 8192
+    ));
+    assert!(err.is_validation());
+
+    session.say("hello").await.unwrap();
+    let stats = session.outbound_stats();
+    assert_eq!(stats.by_type["conversation.item.create"].count, 1);
+    assert_eq!(stats.by_type["session.update"].count, 1);
+    assert!(stats.largest.is_some_and(|largest| largest.bytes < 4096));
+}
+
+#[tokio::test]
+async fn server_close_frame_is_reported() {
+    use oai_rt_rs::sdk::CloseReason;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        use futures::{SinkExt, StreamExt};
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+        // Close once the client's initial `session.update` has arrived.
+        let _ = ws.next().await;
+        let frame = CloseFrame {
+            code: CloseCode::Policy,
+            reason: "client secret expired".into(),
+        };
+        ws.send(Message::Close(Some(frame))).await.unwrap();
+        while ws.next().await.is_some() {}
+    });
+
+    let mut session = Realtime::builder()
+        .api_key("mock")
+        .base_url(url)
+        .output_text()
+        .connect_ws()
+        .await
+        .unwrap();
+    let mut last = None;
+    while let Some(event) = session.next_event().await.unwrap() {
+        last = Some(event);
+    }
+    assert!(matches!(
+        last,
+        Some(SdkEvent::ConnectionClosed { code: 1008, ref reason }) if reason == "client secret expired"
+    ));
+    let Some(CloseReason::ServerClosed(Some(close))) = session.closed_reason() else {
+        panic!("expected a server close, got {:?}", session.closed_reason());
+    };
+    let err = close.error().unwrap();
+    assert!(matches!(&err, Error::AuthExpired(reason) if reason == "client secret expired"));
+    assert!(err.is_connection());
+}
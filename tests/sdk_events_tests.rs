@@ -1,5 +1,9 @@
+use oai_rt_rs::protocol::models::McpError;
 use oai_rt_rs::protocol::server_events::ServerEvent;
 use oai_rt_rs::sdk::events::SdkEvent;
+use oai_rt_rs::sdk::{AudioChunk, VoiceEvent};
+use serde_json::json;
+use std::time::{Duration, SystemTime};
 
 #[test]
 fn sdk_event_maps_text_delta() {
@@ -27,3 +31,166 @@ fn sdk_event_maps_text_delta() {
         other => panic!("unexpected mapping: {other:?}"),
     }
 }
+
+#[test]
+fn sdk_event_maps_function_call_output_item_to_tool_call_started() {
+    let evt: ServerEvent = serde_json::from_value(serde_json::json!({
+        "type": "response.output_item.added",
+        "event_id": "evt_2",
+        "response_id": "resp_1",
+        "output_index": 1,
+        "item": {
+            "type": "function_call",
+            "id": "item_fc",
+            "status": "in_progress",
+            "name": "lookup_weather",
+            "call_id": "call_1",
+            "arguments": ""
+        }
+    }))
+    .expect("deserialize output_item.added");
+
+    let mapped = SdkEvent::from_server(evt).expect("event maps");
+    match mapped {
+        SdkEvent::ToolCallStarted {
+            response_id,
+            item_id,
+            output_index,
+            call_id,
+            name,
+        } => {
+            assert_eq!(response_id, "resp_1");
+            assert_eq!(item_id, "item_fc");
+            assert_eq!(output_index, 1);
+            assert_eq!(call_id, "call_1");
+            assert_eq!(name, "lookup_weather");
+        }
+        other => panic!("unexpected mapping: {other:?}"),
+    }
+}
+
+#[test]
+fn sdk_event_maps_failed_mcp_call_item() {
+    let evt: ServerEvent = serde_json::from_value(json!({
+        "type": "response.output_item.done",
+        "event_id": "evt_3",
+        "response_id": "resp_1",
+        "output_index": 0,
+        "item": {
+            "type": "mcp_call",
+            "id": "item_1",
+            "call_id": "call_1",
+            "server_label": "docs",
+            "name": "search",
+            "arguments": "{}",
+            "error": { "type": "http_error", "code": 503, "message": "Unavailable" }
+        }
+    }))
+    .unwrap();
+
+    match SdkEvent::from_server(evt).expect("event maps") {
+        SdkEvent::McpCallFailed {
+            item_id,
+            server_label,
+            error,
+            ..
+        } => {
+            assert_eq!(
+                (item_id.as_str(), server_label.as_str()),
+                ("item_1", "docs")
+            );
+            assert_eq!(
+                error,
+                McpError::Http {
+                    code: 503,
+                    message: "Unavailable".to_string(),
+                }
+            );
+        }
+        other => panic!("unexpected mapping: {other:?}"),
+    }
+}
+
+#[test]
+fn sdk_event_maps_speech_boundaries() {
+    let started = ServerEvent::InputAudioBufferSpeechStarted {
+        event_id: "evt_3".to_string(),
+        audio_start_ms: 120,
+        item_id: "item_user".to_string(),
+    };
+    let stopped = ServerEvent::InputAudioBufferSpeechStopped {
+        event_id: "evt_4".to_string(),
+        audio_end_ms: 900,
+        item_id: "item_user".to_string(),
+    };
+
+    assert!(matches!(
+        SdkEvent::from_server(started),
+        Some(SdkEvent::SpeechStarted { ref item_id, audio_start_ms: 120 }) if item_id == "item_user"
+    ));
+    assert!(matches!(
+        SdkEvent::from_server(stopped),
+        Some(SdkEvent::SpeechStopped { ref item_id, audio_end_ms: 900 }) if item_id == "item_user"
+    ));
+}
+
+#[test]
+fn sdk_events_round_trip_through_json() {
+    let delta = SdkEvent::TextDelta {
+        response_id: "resp_1".to_string(),
+        item_id: "item_1".to_string(),
+        output_index: 0,
+        content_index: 0,
+        delta: "hi".to_string(),
+    };
+    let json = serde_json::to_value(&delta).unwrap();
+    assert_eq!(json["type"], "text_delta");
+    assert_eq!(json["data"]["delta"], "hi");
+
+    let raw = SdkEvent::Raw(Box::new(ServerEvent::InputAudioBufferCleared {
+        event_id: "evt_5".to_string(),
+    }));
+    let json = serde_json::to_value(&raw).unwrap();
+    assert_eq!(json["data"]["type"], "input_audio_buffer.cleared");
+    assert!(matches!(
+        serde_json::from_value(json).unwrap(),
+        SdkEvent::Raw(evt) if matches!(*evt, ServerEvent::InputAudioBufferCleared { .. })
+    ));
+
+    let cleared: SdkEvent =
+        serde_json::from_value(json!({ "type": "input_audio_cleared" })).unwrap();
+    assert!(matches!(cleared, SdkEvent::InputAudioCleared));
+}
+
+#[test]
+fn voice_pcm_serializes_as_base64() {
+    let event = VoiceEvent::AudioDelta {
+        response_id: "resp_1".to_string(),
+        item_id: "item_1".to_string(),
+        output_index: 0,
+        content_index: 0,
+        pcm: vec![1, 2, 3],
+    };
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["data"]["pcm"], "AQID");
+    match serde_json::from_value(json).unwrap() {
+        VoiceEvent::AudioDelta { pcm, .. } => assert_eq!(pcm, vec![1, 2, 3]),
+        other => panic!("unexpected event: {other:?}"),
+    }
+
+    let chunk = AudioChunk {
+        response_id: "resp_1".to_string(),
+        item_id: "item_1".to_string(),
+        output_index: 0,
+        content_index: 0,
+        pcm: vec![0, 255],
+        local: false,
+        duration: Duration::from_millis(20),
+        offset: Duration::ZERO,
+        received_at: SystemTime::UNIX_EPOCH,
+    };
+    let json = serde_json::to_string(&chunk).unwrap();
+    let back: AudioChunk = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.pcm, chunk.pcm);
+    assert_eq!(back.duration, chunk.duration);
+}
@@ -1,5 +1,6 @@
+use oai_rt_rs::protocol::models::McpToolConfig;
 use oai_rt_rs::realtime_tool;
-use oai_rt_rs::sdk::{Realtime, ToolRegistry};
+use oai_rt_rs::sdk::{Realtime, ResponseBuilder, ToolRegistry};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -67,3 +68,83 @@ fn tool_registry_registers_tool_spec() {
     assert_eq!(registry.definitions().len(), 1);
     assert_eq!(registry.definitions()[0].name, "sum");
 }
+
+#[test]
+fn response_builder_restricts_mcp_tools() {
+    let mut registry = ToolRegistry::new();
+    registry
+        .mcp_tool(McpToolConfig {
+            server_label: "docs".to_string(),
+            server_url: Some("https://mcp.example.com".to_string()),
+            allowed_tools: Some(vec!["search".to_string(), "fetch".to_string()]),
+            ..McpToolConfig::default()
+        })
+        .unwrap();
+
+    let config = ResponseBuilder::new()
+        .tools(&registry)
+        .unwrap()
+        .allow_mcp_tools("docs", ["search", "delete"])
+        .unwrap()
+        .tool_choice_mcp("docs", "search")
+        .build();
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        json["tools"][0]["tool_names"],
+        serde_json::json!(["search"])
+    );
+    assert_eq!(
+        json["tool_choice"],
+        serde_json::json!({"type": "mcp", "name": "search", "server_label": "docs"})
+    );
+
+    assert!(
+        ResponseBuilder::new()
+            .allow_mcp_tools("docs", ["search"])
+            .is_err()
+    );
+}
+
+#[test]
+fn prelude_covers_a_basic_session() {
+    use oai_rt_rs::prelude::*;
+
+    let builder: RealtimeBuilder = Realtime::builder().input_audio(AudioFormat::pcm_24khz());
+    let _: VoiceSessionBuilder = builder.voice_session().voice("marin");
+    let item = Item::Message {
+        id: None,
+        status: None,
+        role: Role::User,
+        content: vec![ContentPart::InputText {
+            text: "hi".to_string(),
+        }],
+    };
+    let _ = ClientEvent::ConversationItemCreate {
+        event_id: None,
+        previous_item_id: None,
+        item: Box::new(item),
+    };
+    // `Result` stays the std one, with both parameters.
+    let result: Result<(), Error> = Ok(());
+    assert!(result.is_ok());
+}
+
+#[test]
+#[allow(deprecated)]
+fn root_aliases_still_resolve() {
+    let _: oai_rt_rs::RealtimeBuilder = oai_rt_rs::Realtime::builder();
+    let _ = oai_rt_rs::ResponseBuilder::new().build();
+    let _ = oai_rt_rs::OutputModalities::Audio;
+}
+
+#[test]
+fn root_enum_variants_resolve_in_use_paths() {
+    use oai_rt_rs::Role::User;
+    use oai_rt_rs::VoiceEvent::*;
+
+    assert_eq!(User, oai_rt_rs::protocol::models::Role::User);
+    let event = oai_rt_rs::sdk::VoiceEvent::SpeechStarted {
+        audio_start_ms: None,
+    };
+    assert!(matches!(event, SpeechStarted { .. }));
+}
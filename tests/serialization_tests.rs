@@ -1,7 +1,8 @@
 use oai_rt_rs::protocol::client_events::ClientEvent;
 use oai_rt_rs::protocol::models::{
-    AudioFormat, ConversationMode, Infinite, InputItem, ItemStatus, MaxTokens, OutputModalities,
-    ResponseStatus, Role, Session, SessionConfig, SessionKind,
+    AudioFormat, ConversationMode, Infinite, InputItem, Item, ItemStatus, MaxTokens, McpError,
+    OutputModalities, Response, ResponseStatus, Role, Session, SessionConfig, SessionKind,
+    SessionUpdate, SessionUpdateConfig, Tool, ToolChoiceMode, UsageKind,
 };
 use oai_rt_rs::protocol::server_events::ServerEvent;
 use serde_json::json;
@@ -139,6 +140,48 @@ fn test_server_event_flat_deserialization() {
     }
 }
 
+#[test]
+fn test_transcription_usage_shapes() {
+    let completed = |usage: serde_json::Value| {
+        let event: ServerEvent = serde_json::from_value(json!({
+            "type": "conversation.item.input_audio_transcription.completed",
+            "event_id": "evt_1",
+            "item_id": "item_1",
+            "content_index": 0,
+            "transcript": "hello",
+            "usage": usage,
+        }))
+        .expect("Deserialize transcription completed");
+        match event {
+            ServerEvent::InputAudioTranscriptionCompleted { usage, .. } => usage.expect("usage"),
+            other => panic!("Wrong variant: {other:?}"),
+        }
+    };
+
+    let tokens = completed(json!({
+        "type": "tokens",
+        "total_tokens": 26,
+        "input_tokens": 17,
+        "output_tokens": 9,
+        "input_token_details": { "text_tokens": 0, "audio_tokens": 17 },
+    }));
+    assert_eq!(tokens.kind, Some(UsageKind::Tokens));
+    assert_eq!(tokens.total_tokens, 26);
+    assert_eq!(
+        tokens.input_token_details.and_then(|d| d.audio_tokens),
+        Some(17)
+    );
+    assert_eq!(tokens.seconds, None);
+
+    let duration = completed(json!({ "type": "duration", "seconds": 2.5 }));
+    assert_eq!(duration.kind, Some(UsageKind::Duration));
+    assert_eq!(duration.seconds, Some(2.5));
+    assert_eq!(duration.total_tokens, 0);
+    let value = serde_json::to_value(&duration).unwrap();
+    assert_eq!(value["type"], "duration");
+    assert_eq!(value["seconds"], 2.5);
+}
+
 #[test]
 fn test_serialization_roundtrip() {
     let original = json!({
@@ -199,3 +242,306 @@ fn test_response_status_enum() {
     let status: ResponseStatus = serde_json::from_value(json).unwrap();
     assert_eq!(status, ResponseStatus::Cancelled);
 }
+
+#[test]
+fn unknown_enum_values_are_kept_as_other() {
+    let status: ResponseStatus = serde_json::from_value(json!("paused")).unwrap();
+    assert_eq!(status, ResponseStatus::Other("paused".to_string()));
+    assert_eq!(serde_json::to_value(&status).unwrap(), json!("paused"));
+    assert_eq!(
+        serde_json::from_value::<ResponseStatus>(json!("in_progress")).unwrap(),
+        ResponseStatus::InProgress
+    );
+
+    let item: Item = serde_json::from_value(json!({
+        "type": "message",
+        "status": "archived",
+        "role": "assistant",
+        "content": []
+    }))
+    .unwrap();
+    assert!(matches!(
+        item,
+        Item::Message { status: Some(ItemStatus::Other(ref s)), .. } if s == "archived"
+    ));
+
+    let mode: ToolChoiceMode = serde_json::from_value(json!("parallel")).unwrap();
+    assert_eq!(mode.as_str(), "parallel");
+}
+
+#[test]
+fn response_output_accessors_walk_items() {
+    let response: Response = serde_json::from_value(json!({
+        "id": "resp_1",
+        "object": "realtime.response",
+        "status": "completed",
+        "output": [
+            {
+                "type": "message",
+                "role": "assistant",
+                "content": [
+                    { "type": "output_text", "text": "Hello, " },
+                    { "type": "output_audio", "transcript": "Hi" },
+                    { "type": "output_text", "text": "world." }
+                ]
+            },
+            {
+                "type": "function_call",
+                "id": "item_2",
+                "name": "sum",
+                "call_id": "call_1",
+                "arguments": "{\"a\":1,\"b\":2}"
+            },
+            {
+                "type": "mcp_call",
+                "call_id": "call_2",
+                "server_label": "docs",
+                "name": "search",
+                "arguments": "{}",
+                "output": "found"
+            }
+        ]
+    }))
+    .unwrap();
+
+    assert_eq!(response.text().as_deref(), Some("Hello, world."));
+    assert_eq!(response.audio_transcript().as_deref(), Some("Hi"));
+    let calls: Vec<_> = response.function_calls().collect();
+    assert_eq!(calls.len(), 1);
+    assert_eq!((calls[0].name, calls[0].item_id), ("sum", Some("item_2")));
+    let args: serde_json::Value = calls[0].parse_arguments().unwrap();
+    assert_eq!(args["b"], 2);
+    let mcp: Vec<_> = response.mcp_calls().collect();
+    assert_eq!(
+        (mcp[0].server_label, mcp[0].output),
+        ("docs", Some("found"))
+    );
+
+    let empty: Response = serde_json::from_value(json!({
+        "id": "resp_2",
+        "object": "realtime.response",
+        "status": "cancelled"
+    }))
+    .unwrap();
+    assert_eq!(empty.text(), None);
+    assert_eq!(empty.function_calls().count(), 0);
+}
+
+#[test]
+fn test_session_update_config_passes_through_unknown_fields() {
+    let json = json!({
+        "instructions": "Be brief.",
+        "future_field": { "enabled": true }
+    });
+    let config: SessionUpdateConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(config.instructions.as_deref(), Some("Be brief."));
+    assert_eq!(
+        config.extra.get("future_field"),
+        Some(&json!({ "enabled": true }))
+    );
+
+    let update = serde_json::to_value(SessionUpdate { config }).unwrap();
+    assert_eq!(update["type"], "realtime");
+    assert_eq!(update["future_field"], json!({ "enabled": true }));
+}
+
+#[test]
+fn disabled_turn_detection_serializes_as_null() {
+    use oai_rt_rs::protocol::models::{AudioConfig, InputAudioConfig, Nullable};
+
+    let update = SessionUpdate {
+        config: SessionUpdateConfig {
+            audio: Some(AudioConfig {
+                input: Some(InputAudioConfig {
+                    turn_detection: Some(Nullable::Null),
+                    ..InputAudioConfig::default()
+                }),
+                output: None,
+            }),
+            ..SessionUpdateConfig::default()
+        },
+    };
+
+    let json = serde_json::to_value(&update).unwrap();
+    let input = json["audio"]["input"].as_object().expect("audio.input");
+    assert_eq!(input.get("turn_detection"), Some(&serde_json::Value::Null));
+}
+
+#[test]
+fn tracing_helpers_serialize_to_api_shapes() {
+    use oai_rt_rs::protocol::models::Tracing;
+    use oai_rt_rs::sdk::ResponseBuilder;
+
+    assert_eq!(
+        serde_json::to_value(Tracing::auto()).unwrap(),
+        json!("auto")
+    );
+    assert_eq!(
+        serde_json::to_value(Tracing::workflow("triage", Some("grp_1".to_string()), None)).unwrap(),
+        json!({ "workflow_name": "triage", "group_id": "grp_1", "metadata": null })
+    );
+
+    let plain = serde_json::to_value(ResponseBuilder::new().build()).unwrap();
+    assert!(plain.get("tracing").is_none());
+    let traced = serde_json::to_value(ResponseBuilder::new().tracing_auto().build()).unwrap();
+    assert_eq!(traced["tracing"], "auto");
+}
+
+#[test]
+fn include_is_sent_only_when_set() {
+    use oai_rt_rs::protocol::models::Include;
+    use oai_rt_rs::sdk::ResponseBuilder;
+
+    let retrieve = ClientEvent::ConversationItemRetrieve {
+        event_id: None,
+        item_id: "item_1".to_string(),
+        include: Some(vec![Include::InputAudioTranscriptionLogprobs]),
+    };
+    assert_eq!(
+        serde_json::to_value(&retrieve).unwrap(),
+        json!({
+            "type": "conversation.item.retrieve",
+            "item_id": "item_1",
+            "include": ["item.input_audio_transcription.logprobs"]
+        })
+    );
+
+    let plain = serde_json::to_value(ResponseBuilder::new().build()).unwrap();
+    assert!(plain.get("include").is_none());
+    let config = ResponseBuilder::new()
+        .include(Include::InputAudioTranscriptionLogprobs)
+        .include("item.future_field")
+        .build();
+    assert_eq!(
+        serde_json::to_value(config).unwrap()["include"],
+        json!([
+            "item.input_audio_transcription.logprobs",
+            "item.future_field"
+        ])
+    );
+}
+
+#[test]
+fn response_done_with_failed_mcp_calls_keeps_typed_errors() {
+    let mcp_call = |call_id: &str, error: serde_json::Value| {
+        json!({
+            "type": "mcp_call",
+            "id": format!("item_{call_id}"),
+            "status": "failed",
+            "call_id": call_id,
+            "server_label": "docs",
+            "name": "search",
+            "arguments": "{}",
+            "output": null,
+            "error": error
+        })
+    };
+    let event: ServerEvent = serde_json::from_value(json!({
+        "type": "response.done",
+        "event_id": "evt_1",
+        "response": {
+            "id": "resp_1",
+            "object": "realtime.response",
+            "status": "completed",
+            "output": [
+                mcp_call("call_1", json!({
+                    "type": "protocol_error", "code": -32601, "message": "Method not found"
+                })),
+                mcp_call("call_2", json!({
+                    "type": "tool_execution_error", "message": "Index unavailable"
+                })),
+                mcp_call("call_3", json!({
+                    "type": "http_error", "code": 502, "message": "Bad gateway"
+                })),
+                mcp_call("call_4", json!({ "type": "quota_error", "message": "Later" }))
+            ]
+        }
+    }))
+    .unwrap();
+
+    let ServerEvent::ResponseDone { response, .. } = &event else {
+        panic!("expected response.done");
+    };
+    let errors: Vec<_> = response
+        .mcp_calls()
+        .map(|call| call.error.cloned())
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            Some(McpError::Protocol {
+                code: -32601,
+                message: "Method not found".to_string(),
+            }),
+            Some(McpError::ToolExecution {
+                message: "Index unavailable".to_string(),
+            }),
+            Some(McpError::Http {
+                code: 502,
+                message: "Bad gateway".to_string(),
+            }),
+            Some(McpError::Unknown),
+        ]
+    );
+    assert_eq!(
+        errors[2].as_ref().and_then(McpError::message),
+        Some("Bad gateway")
+    );
+
+    let value = serde_json::to_value(&errors[0]).unwrap();
+    assert_eq!(value["type"], "protocol_error");
+    assert_eq!(value["code"], -32601);
+}
+
+#[test]
+fn server_event_type_matches_the_wire_name() {
+    let events = [
+        (
+            true,
+            json!({
+                "type": "response.output_text.delta",
+                "event_id": "evt_1",
+                "response_id": "resp_1",
+                "item_id": "item_1",
+                "output_index": 0,
+                "content_index": 0,
+                "delta": "Hi"
+            }),
+        ),
+        (
+            false,
+            json!({ "type": "input_audio_buffer.cleared", "event_id": "evt_2" }),
+        ),
+        (
+            true,
+            json!({ "type": "response.future_thing.delta", "event_id": "evt_3" }),
+        ),
+    ];
+    for (is_delta, value) in events {
+        let event: ServerEvent = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(event.event_type(), value["type"]);
+        assert_eq!(event.is_delta(), is_delta);
+    }
+}
+
+#[test]
+fn mcp_allowed_tools_round_trip_as_tool_names() {
+    let wire = json!({
+        "type": "mcp",
+        "server_label": "docs",
+        "server_url": "https://mcp.example.com",
+        "tool_names": ["search", "fetch"]
+    });
+    let tool: Tool = serde_json::from_value(wire.clone()).unwrap();
+    let Tool::Mcp(config) = &tool else {
+        panic!("expected an MCP tool");
+    };
+    assert_eq!(
+        config.allowed_tools.as_deref(),
+        Some(&["search".to_string(), "fetch".to_string()][..])
+    );
+
+    let json = serde_json::to_value(&tool).unwrap();
+    assert_eq!(json["tool_names"], wire["tool_names"]);
+    assert!(json.get("allowed_tools").is_none());
+}
@@ -1,11 +1,10 @@
 use oai_rt_rs::Error;
 use oai_rt_rs::protocol::models::{
-    AudioConfig, AudioFormat, InputAudioConfig, McpToolConfig, ResponseConfig, SessionUpdateConfig,
-    Tool,
+    AudioConfig, AudioFormat, InputAudioConfig, McpToolConfig, Modality, OutputModalities,
+    ResponseConfig, SessionUpdateConfig, Tool,
 };
 
 // Replicate the base64 validation logic for testing
-#[allow(clippy::result_large_err)]
 fn validate_base64_audio(s: &str) -> Result<(), Error> {
     const MAX_BYTES: usize = 15 * 1024 * 1024;
     let bytes = s.as_bytes();
@@ -305,3 +304,64 @@ fn valid_session_config_passes() {
         }
     }
 }
+
+#[test]
+fn session_update_rejects_fields_fixed_at_creation() {
+    let resent: SessionUpdateConfig = serde_json::from_value(serde_json::json!({
+        "id": "sess_1",
+        "object": "realtime.session",
+        "instructions": "Be brief."
+    }))
+    .unwrap();
+    let err = resent.validate_immutable_fields().unwrap_err();
+    assert!(
+        matches!(err, Error::InvalidClientEvent(msg) if msg.contains("assigned by the server"))
+    );
+
+    for key in ["model", "voice"] {
+        let mut config = SessionUpdateConfig::default();
+        config.extra.insert(key.to_string(), serde_json::json!("x"));
+        let err = config.validate_immutable_fields().unwrap_err();
+        assert!(matches!(err, Error::InvalidClientEvent(msg) if msg.contains(&format!("`{key}`"))));
+    }
+
+    let mut config = SessionUpdateConfig::default();
+    config
+        .extra
+        .insert("future_field".to_string(), serde_json::json!(3));
+    assert!(config.validate_immutable_fields().is_ok());
+}
+
+#[test]
+fn output_modalities_parses_both_but_rejects_them_for_ga() {
+    let both: OutputModalities =
+        serde_json::from_value(serde_json::json!(["text", "audio"])).unwrap();
+    assert_eq!(both, OutputModalities::AudioAndText);
+    assert!(both.contains(Modality::Audio) && both.contains(Modality::Text));
+    assert_eq!(
+        serde_json::to_value(both).unwrap(),
+        serde_json::json!(["audio", "text"])
+    );
+    assert!(matches!(both.validate(), Err(Error::InvalidClientEvent(_))));
+    assert!(OutputModalities::Audio.validate().is_ok());
+
+    let empty = serde_json::from_value::<OutputModalities>(serde_json::json!([]));
+    assert!(empty.is_err());
+}
+
+#[test]
+fn error_stays_small_and_exposes_sources() {
+    use std::error::Error as _;
+
+    assert!(std::mem::size_of::<Error>() <= 32);
+
+    let parse = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    let err = Error::from(parse);
+    assert!(err.source().is_some());
+    assert!(err.is_protocol());
+    assert!(!err.is_connection());
+
+    assert!(Error::ConnectionClosed.is_connection());
+    assert!(Error::InvalidClientEvent("bad".to_string()).is_validation());
+    assert!(Error::ReadOnly("response.create").is_validation());
+}